@@ -8,7 +8,7 @@ use crate::heaven_http_client::HeavenHttpClient;
 use crate::icons::CombinedAssets;
 use crate::theme::apply_heaven_theme;
 use crate::zed_theme_import;
-use crate::{auth, scrobble_refresh, status_center, voice};
+use crate::{auth, now_playing, scrobble_refresh, status_center, voice};
 use std::sync::Arc;
 
 pub(crate) fn run() {
@@ -41,7 +41,9 @@ pub(crate) fn run() {
         }
         cx.set_global(initial_auth);
         cx.set_global(scrobble_refresh::ScrobbleRefreshSignal::default());
+        cx.set_global(now_playing::NowPlayingState::default());
         cx.set_global(status_center::StatusCenter::default());
+        auth::session::SessionManager::start(cx);
 
         cx.open_window(
             WindowOptions {
@@ -151,6 +151,17 @@ impl HeavenApp {
         }
     }
 
+    /// Show an activity log record on the Wallet page, e.g. from a status entry's tx link.
+    fn open_activity(&mut self, activity_id: String, cx: &mut Context<Self>) {
+        let _ = self.wallet_view.update(cx, |view, cx| {
+            view.focus_activity(activity_id, cx);
+        });
+        self.nav_channel.update(cx, |ch, cx| {
+            ch.target = Some(Page::Wallet);
+            cx.notify();
+        });
+    }
+
     fn handle_zoom_in(&mut self, _: &ZoomIn, window: &mut Window, cx: &mut Context<Self>) {
         let theme = Theme::global_mut(cx);
         let cur: f32 = theme.font_size.into();
@@ -179,6 +190,7 @@ impl HeavenApp {
 
 impl Render for HeavenApp {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let app = cx.entity().downgrade();
         let theme = cx.theme();
 
         div()
@@ -230,7 +242,14 @@ impl Render for HeavenApp {
                             .child(self.active_page.render_placeholder())
                             .into_any_element(),
                     })
-                    .when_some(render_status_overlay(cx), |el, overlay| el.child(overlay)),
+                    .when_some(
+                        render_status_overlay(cx, move |activity_id, _window, cx| {
+                            let _ = app.update(cx, |this, cx| {
+                                this.open_activity(activity_id, cx);
+                            });
+                        }),
+                        |el, overlay| el.child(overlay),
+                    ),
             )
             .child(
                 div()
@@ -8,11 +8,16 @@
 //!
 //! Ported from the legacy desktop auth module (tokio → smol)
 
+pub mod accounts;
 mod callback_flow;
 mod persistence;
+pub mod session;
 
-pub use callback_flow::run_auth_callback_server;
-pub use persistence::{delete_from_disk, load_from_disk, save_to_disk, to_persisted};
+pub use callback_flow::{run_auth_callback_server, run_auth_callback_server_with, AuthFlowOptions};
+pub use persistence::{
+    delete_from_disk, list_accounts, load_from_disk, remove_account, save_to_disk, switch_account,
+    to_persisted,
+};
 use serde::{Deserialize, Serialize};
 
 // Auth page URL
@@ -61,6 +66,9 @@ pub struct AuthResult {
     pub tempo_session_key_authorization: Option<String>,
     #[serde(default)]
     pub access_token: Option<String>,
+    /// Session key addresses the auth page revoked on-chain during this flow.
+    #[serde(default)]
+    pub revoked_session_keys: Option<Vec<String>>,
     pub is_new_user: Option<bool>,
     pub error: Option<String>,
 }
@@ -97,6 +105,18 @@ pub struct PersistedAuth {
     pub tempo_session_key_authorization: Option<String>,
     #[serde(default)]
     pub access_token: Option<String>,
+    /// Replaced session keys that may still be authorized on-chain until revoked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retired_session_keys: Vec<RetiredSessionKey>,
+}
+
+/// A session key rotated out locally but not yet confirmed revoked on-chain.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetiredSessionKey {
+    pub address: String,
+    #[serde(default)]
+    pub expires_at: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,184 @@
+//! Registry of signed-in identities and the per-account data directories.
+//!
+//! Each account keeps its auth, content keypair and decrypted caches under
+//! `accounts/<address>/`. Exactly one account is active; switching only changes which
+//! directory (and which secret-store keys) the rest of the app resolves.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+const ACCOUNTS_FILE: &str = "accounts.json";
+const ACCOUNTS_DIR: &str = "accounts";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountEntry {
+    pub address: String,
+    pub added_at: u64,
+    #[serde(default)]
+    pub last_used_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountsFile {
+    #[serde(default)]
+    active: Option<String>,
+    #[serde(default)]
+    accounts: Vec<AccountEntry>,
+    /// Account that inherited the single-account files from before accounts existed.
+    #[serde(default)]
+    legacy_owner: Option<String>,
+}
+
+pub struct AccountRegistry {
+    base: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl AccountRegistry {
+    pub fn new(base: PathBuf) -> Self {
+        Self {
+            base,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn shared() -> &'static AccountRegistry {
+        static SHARED: OnceLock<AccountRegistry> = OnceLock::new();
+        SHARED.get_or_init(|| {
+            Self::new(
+                dirs::data_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("heaven-gpui"),
+            )
+        })
+    }
+
+    /// App data dir shared by every account.
+    pub fn base_dir(&self) -> &Path {
+        &self.base
+    }
+
+    /// Accounts, most recently used first.
+    pub fn accounts(&self) -> Vec<AccountEntry> {
+        let mut accounts = self.read().accounts;
+        accounts.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+        accounts
+    }
+
+    pub fn active(&self) -> Option<String> {
+        self.read().active
+    }
+
+    pub fn account_dir(&self, address: &str) -> PathBuf {
+        self.base
+            .join(ACCOUNTS_DIR)
+            .join(normalize_account_address(address))
+    }
+
+    /// Data dir for the active account, or the shared dir when signed out.
+    pub fn data_dir(&self) -> PathBuf {
+        match self.active() {
+            Some(address) => self.account_dir(&address),
+            None => self.base.clone(),
+        }
+    }
+
+    /// `name` inside the active account's dir. The legacy owner takes over a file of the
+    /// same name left in the shared dir by the single-account layout.
+    pub fn scoped_path(&self, name: &str) -> PathBuf {
+        let data = self.read();
+        let Some(active) = data.active else {
+            return self.base.join(name);
+        };
+        let scoped = self.account_dir(&active).join(name);
+        if data.legacy_owner.as_deref() == Some(active.as_str()) && !scoped.exists() {
+            let legacy = self.base.join(name);
+            if legacy.exists() {
+                let moved = fs::create_dir_all(self.account_dir(&active))
+                    .and_then(|_| fs::rename(&legacy, &scoped));
+                match moved {
+                    Ok(()) => log::info!("[Accounts] moved {name} into account {active}"),
+                    Err(e) => log::warn!("[Accounts] failed moving {name} into account: {e}"),
+                }
+            }
+        }
+        scoped
+    }
+
+    /// Secret-store key for `name` scoped to the active account.
+    pub fn scoped_secret(&self, name: &str) -> String {
+        match self.active() {
+            Some(address) => format!("{name}.{address}"),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn is_legacy_owner(&self, address: &str) -> bool {
+        self.read().legacy_owner.as_deref() == Some(normalize_account_address(address).as_str())
+    }
+
+    /// Register `address` (if new) and make it the active account.
+    pub fn activate(&self, address: &str, now: u64) -> Result<(), String> {
+        let address = normalize_account_address(address);
+        self.update(|data| {
+            match data.accounts.iter_mut().find(|a| a.address == address) {
+                Some(entry) => entry.last_used_at = now,
+                None => data.accounts.push(AccountEntry {
+                    address: address.clone(),
+                    added_at: now,
+                    last_used_at: now,
+                }),
+            }
+            data.active = Some(address.clone());
+        })
+    }
+
+    /// Record which account inherits pre-accounts files. Only the first caller wins.
+    pub fn claim_legacy_files(&self, address: &str) -> Result<(), String> {
+        let address = normalize_account_address(address);
+        self.update(|data| {
+            data.legacy_owner.get_or_insert(address);
+        })
+    }
+
+    /// Forget `address`; if it was active, no account is active afterwards.
+    pub fn remove(&self, address: &str) -> Result<(), String> {
+        let address = normalize_account_address(address);
+        self.update(|data| {
+            data.accounts.retain(|a| a.address != address);
+            if data.active.as_deref() == Some(address.as_str()) {
+                data.active = None;
+            }
+        })
+    }
+
+    fn read(&self) -> AccountsFile {
+        fs::read_to_string(self.base.join(ACCOUNTS_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut AccountsFile)) -> Result<(), String> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = self.read();
+        apply(&mut data);
+        fs::create_dir_all(&self.base)
+            .map_err(|e| format!("Failed to create {}: {e}", self.base.display()))?;
+        let json = serde_json::to_string_pretty(&data)
+            .map_err(|e| format!("Failed to encode accounts: {e}"))?;
+        let path = self.base.join(ACCOUNTS_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write accounts: {e}"))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace accounts: {e}"))
+    }
+}
+
+pub fn normalize_account_address(address: &str) -> String {
+    address.trim().to_ascii_lowercase()
+}
@@ -12,10 +12,24 @@ const DEFAULT_TEMPO_KEY_MANAGER_URL: &str = "https://keys.tempo.xyz";
 const DEFAULT_TEMPO_FEE_PAYER_URL: &str = "https://sponsor.moderato.tempo.xyz";
 const DEFAULT_TEMPO_CHAIN_ID: u64 = 42431;
 
+/// Extra requests passed to the auth page alongside the sign-in.
+#[derive(Debug, Clone, Default)]
+pub struct AuthFlowOptions {
+    /// Session key addresses the page should revoke on-chain after authorizing a new one.
+    pub revoke_session_keys: Vec<String>,
+}
+
 /// Run the browser callback auth flow.
 /// Binds a local TCP server, opens browser, waits for POST callback.
 /// Returns the parsed AuthResult on success.
 pub async fn run_auth_callback_server() -> Result<AuthResult, String> {
+    run_auth_callback_server_with(&AuthFlowOptions::default()).await
+}
+
+/// [`run_auth_callback_server`] with extra requests for the auth page.
+pub async fn run_auth_callback_server_with(
+    options: &AuthFlowOptions,
+) -> Result<AuthResult, String> {
     let listener = TcpListener::bind("127.0.0.1:0")
         .await
         .map_err(|e| format!("Failed to bind: {e}"))?;
@@ -50,6 +64,12 @@ pub async fn run_auth_callback_server() -> Result<AuthResult, String> {
     if let Some(tempo_rp_id) = resolve_tempo_rp_id() {
         query.push(format!("tempoRpId={}", urlencoding::encode(&tempo_rp_id)));
     }
+    if !options.revoke_session_keys.is_empty() {
+        query.push(format!(
+            "revokeSessionKeys={}",
+            urlencoding::encode(&options.revoke_session_keys.join(","))
+        ));
+    }
     let auth_url = format!("{AUTH_PAGE_URL}?{}", query.join("&"));
 
     log::info!("Opening browser to: {}", auth_url);
@@ -1,35 +1,204 @@
+use super::accounts::{normalize_account_address, AccountEntry, AccountRegistry};
 use super::{short_hex, AuthResult, PersistedAuth, AUTH_FILE};
+use crate::scrobble::now_epoch_sec;
+use crate::secret_store::SecretStore;
 use std::path::PathBuf;
 
-fn app_data_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("heaven-gpui")
+/// Secret fields of [`PersistedAuth`]; they live in the secret store, never in the JSON file.
+/// Per-account keys carry the wallet address as a suffix.
+const SESSION_KEY_SECRET: &str = "auth.tempo_session_private_key";
+const ACCESS_TOKEN_SECRET: &str = "auth.access_token";
+
+/// Where one account's auth lives: its JSON file and the suffix on its secret-store keys.
+struct AuthLocation {
+    path: PathBuf,
+    secret_scope: Option<String>,
+}
+
+impl AuthLocation {
+    /// The single-account file from before multiple accounts were supported.
+    fn legacy(registry: &AccountRegistry) -> Self {
+        Self {
+            path: registry.base_dir().join(AUTH_FILE),
+            secret_scope: None,
+        }
+    }
+
+    fn account(registry: &AccountRegistry, address: &str) -> Self {
+        Self {
+            path: registry.account_dir(address).join(AUTH_FILE),
+            secret_scope: Some(normalize_account_address(address)),
+        }
+    }
+
+    fn secret(&self, name: &str) -> String {
+        match &self.secret_scope {
+            Some(scope) => format!("{name}.{scope}"),
+            None => name.to_string(),
+        }
+    }
 }
 
+/// Persist `auth` under its wallet's account and make that account active.
 pub fn save_to_disk(auth: &PersistedAuth) -> Result<(), String> {
-    let dir = app_data_dir();
-    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create dir: {e}"))?;
+    save_account(SecretStore::shared(), AccountRegistry::shared(), auth)
+}
 
-    let path = dir.join(AUTH_FILE);
-    let json =
-        serde_json::to_string_pretty(auth).map_err(|e| format!("Failed to serialize: {e}"))?;
+/// Auth for the active account.
+pub fn load_from_disk() -> Option<PersistedAuth> {
+    load_active(SecretStore::shared(), AccountRegistry::shared())
+}
+
+/// Sign out of the active account. Its content keypair and caches stay on disk.
+pub fn delete_from_disk() {
+    let registry = AccountRegistry::shared();
+    if let Some(active) = registry.active() {
+        remove_account(&active);
+    }
+}
+
+pub fn list_accounts() -> Vec<AccountEntry> {
+    AccountRegistry::shared().accounts()
+}
+
+/// Make `address` the active account and return its auth.
+pub fn switch_account(address: &str) -> Result<PersistedAuth, String> {
+    switch_with(SecretStore::shared(), AccountRegistry::shared(), address)
+}
+
+/// Forget the saved sign-in for `address`, keeping its content keypair and caches.
+pub fn remove_account(address: &str) {
+    let store = SecretStore::shared();
+    let registry = AccountRegistry::shared();
+    remove_location(store, &AuthLocation::account(registry, address));
+    if let Err(e) = registry.remove(address) {
+        log::warn!("[Auth] Failed to remove account {address}: {e}");
+    }
+}
+
+fn save_account(
+    store: &SecretStore,
+    registry: &AccountRegistry,
+    auth: &PersistedAuth,
+) -> Result<(), String> {
+    let address = auth
+        .wallet_address()
+        .ok_or("Cannot save auth without a wallet address.")?;
+    save_with(store, &AuthLocation::account(registry, address), auth)?;
+    registry.activate(address, now_epoch_sec())
+}
+
+fn load_active(store: &SecretStore, registry: &AccountRegistry) -> Option<PersistedAuth> {
+    migrate_legacy_auth(store, registry);
+    let active = registry.active()?;
+    load_with(store, &AuthLocation::account(registry, &active))
+}
+
+fn switch_with(
+    store: &SecretStore,
+    registry: &AccountRegistry,
+    address: &str,
+) -> Result<PersistedAuth, String> {
+    let auth = load_with(store, &AuthLocation::account(registry, address))
+        .ok_or_else(|| format!("No saved sign-in for {address}; sign in to add it again."))?;
+    registry.activate(address, now_epoch_sec())?;
+    Ok(auth)
+}
+
+/// Move the pre-accounts auth file into its wallet's account.
+fn migrate_legacy_auth(store: &SecretStore, registry: &AccountRegistry) {
+    let legacy = AuthLocation::legacy(registry);
+    if !legacy.path.exists() {
+        return;
+    }
+    let Some(auth) = load_with(store, &legacy) else {
+        return;
+    };
+    let Some(address) = auth.wallet_address() else {
+        return;
+    };
+    let migrated = registry
+        .claim_legacy_files(address)
+        .and_then(|_| save_account(store, registry, &auth));
+    match migrated {
+        Ok(()) => {
+            log::info!("[Auth] Migrated single-account auth into account {address}");
+            remove_location(store, &legacy);
+        }
+        Err(e) => log::warn!("[Auth] Failed to migrate legacy auth: {e}"),
+    }
+}
+
+fn remove_location(store: &SecretStore, location: &AuthLocation) {
+    match std::fs::remove_file(&location.path) {
+        Ok(_) => log::info!("[Auth] Removed persisted auth file: {:?}", location.path),
+        Err(e) => log::warn!(
+            "[Auth] Failed to remove auth file {:?}: {}",
+            location.path,
+            e
+        ),
+    }
+    for name in [SESSION_KEY_SECRET, ACCESS_TOKEN_SECRET] {
+        if let Err(e) = store.delete(&location.secret(name)) {
+            log::warn!("[Auth] Failed to remove {name} from secret store: {e}");
+        }
+    }
+}
+
+fn save_with(
+    store: &SecretStore,
+    location: &AuthLocation,
+    auth: &PersistedAuth,
+) -> Result<(), String> {
+    let path = &location.path;
+    store.put_optional(
+        &location.secret(SESSION_KEY_SECRET),
+        auth.tempo_session_private_key.as_deref(),
+    )?;
+    store.put_optional(
+        &location.secret(ACCESS_TOKEN_SECRET),
+        auth.access_token.as_deref(),
+    )?;
+
+    let mut public = auth.clone();
+    public.tempo_session_private_key = None;
+    public.access_token = None;
 
-    std::fs::write(&path, json).map_err(|e| format!("Failed to write: {e}"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create dir: {e}"))?;
+    }
+    let json =
+        serde_json::to_string_pretty(&public).map_err(|e| format!("Failed to serialize: {e}"))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write: {e}"))?;
 
-    log::info!("Saved auth to {:?}", path);
+    log::info!(
+        "Saved auth to {:?} (secrets in {})",
+        path,
+        store.backend_name()
+    );
     super::log_persisted_auth("Saved auth", auth);
     Ok(())
 }
 
-pub fn load_from_disk() -> Option<PersistedAuth> {
-    let path = app_data_dir().join(AUTH_FILE);
+fn load_with(store: &SecretStore, location: &AuthLocation) -> Option<PersistedAuth> {
+    let path = &location.path;
     if !path.exists() {
         return None;
     }
 
-    let contents = std::fs::read_to_string(&path).ok()?;
-    let parsed: PersistedAuth = serde_json::from_str(&contents).ok()?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    let mut parsed: PersistedAuth = serde_json::from_str(&contents).ok()?;
+
+    if parsed.tempo_session_private_key.is_some() || parsed.access_token.is_some() {
+        // Written before secrets moved out of the file: move them now.
+        match save_with(store, location, &parsed) {
+            Ok(()) => log::info!("[Auth] Migrated auth secrets to {}", store.backend_name()),
+            Err(e) => log::warn!("[Auth] Keeping plaintext auth secrets; migration failed: {e}"),
+        }
+    } else {
+        parsed.tempo_session_private_key = read_secret(store, &location.secret(SESSION_KEY_SECRET));
+        parsed.access_token = read_secret(store, &location.secret(ACCESS_TOKEN_SECRET));
+    }
 
     log::debug!(
         "[Auth] Loaded auth from disk: version={:?}, provider={:?}, wallet={:?}, tempo_credential_id={:?}, tempo_public_key={}",
@@ -46,12 +215,11 @@ pub fn load_from_disk() -> Option<PersistedAuth> {
     Some(parsed)
 }
 
-pub fn delete_from_disk() {
-    let path = app_data_dir().join(AUTH_FILE);
-    match std::fs::remove_file(&path) {
-        Ok(_) => log::info!("[Auth] Removed persisted auth file: {:?}", path),
-        Err(e) => log::warn!("[Auth] Failed to remove auth file {:?}: {}", path, e),
-    }
+fn read_secret(store: &SecretStore, key: &str) -> Option<String> {
+    store.get(key).unwrap_or_else(|e| {
+        log::warn!("[Auth] Failed to read {key} from secret store: {e}");
+        None
+    })
 }
 
 /// Convert AuthResult → PersistedAuth (strips transient fields)
@@ -71,5 +239,95 @@ pub fn to_persisted(result: &AuthResult) -> PersistedAuth {
         tempo_session_expires_at: result.tempo_session_expires_at,
         tempo_session_key_authorization: result.tempo_session_key_authorization.clone(),
         access_token: result.access_token.clone(),
+        retired_session_keys: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret_store::mock::MockKeyring;
+
+    fn temp_base() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "heaven-accounts-{}-{}",
+            std::process::id(),
+            rand::random::<u32>()
+        ))
+    }
+
+    fn tempo_auth(wallet: &str, session_key: &str) -> PersistedAuth {
+        let mut auth = to_persisted(
+            &serde_json::from_value(serde_json::json!({ "walletAddress": wallet })).unwrap(),
+        );
+        auth.tempo_session_private_key = Some(session_key.to_string());
+        auth
+    }
+
+    #[test]
+    fn legacy_plaintext_auth_migrates_into_its_account() {
+        let base = temp_base();
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(
+            base.join(AUTH_FILE),
+            r#"{"provider":"tempo-passkey","walletAddress":"0xAbc","tempoSessionPrivateKey":"0xsecretkey","accessToken":"tok"}"#,
+        )
+        .unwrap();
+        std::fs::write(base.join("content_keypair_v1.json"), "{}").unwrap();
+        let keyring = MockKeyring::default();
+        let store = SecretStore::with_backend(Box::new(keyring.clone()));
+        let registry = AccountRegistry::new(base.clone());
+
+        let migrated = load_active(&store, &registry).unwrap();
+        assert_eq!(
+            migrated.tempo_session_private_key.as_deref(),
+            Some("0xsecretkey")
+        );
+        assert!(!base.join(AUTH_FILE).exists());
+        let on_disk =
+            std::fs::read_to_string(registry.account_dir("0xabc").join(AUTH_FILE)).unwrap();
+        assert!(!on_disk.contains("0xsecretkey"));
+        assert_eq!(
+            keyring
+                .value("auth.tempo_session_private_key.0xabc")
+                .as_deref(),
+            Some("0xsecretkey")
+        );
+        assert_eq!(keyring.value(SESSION_KEY_SECRET), None);
+
+        let reloaded = load_active(&store, &registry).unwrap();
+        assert_eq!(reloaded.access_token.as_deref(), Some("tok"));
+        assert!(registry.is_legacy_owner("0xABC"));
+        assert_eq!(
+            registry.scoped_path("content_keypair_v1.json"),
+            registry
+                .account_dir("0xabc")
+                .join("content_keypair_v1.json")
+        );
+        assert!(!base.join("content_keypair_v1.json").exists());
+        let _ = std::fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn accounts_keep_separate_auth_and_switch_without_reauth() {
+        let base = temp_base();
+        let store = SecretStore::with_backend(Box::new(MockKeyring::default()));
+        let registry = AccountRegistry::new(base.clone());
+
+        save_account(&store, &registry, &tempo_auth("0xaaa", "key-a")).unwrap();
+        save_account(&store, &registry, &tempo_auth("0xbbb", "key-b")).unwrap();
+        assert_eq!(registry.active().as_deref(), Some("0xbbb"));
+        assert_eq!(registry.scoped_secret("content"), "content.0xbbb");
+
+        let switched = switch_with(&store, &registry, "0xAAA").unwrap();
+        assert_eq!(switched.tempo_session_private_key.as_deref(), Some("key-a"));
+        let active = load_active(&store, &registry).unwrap();
+        assert_eq!(active.wallet_address(), Some("0xaaa"));
+        assert!(!registry.is_legacy_owner("0xaaa"));
+
+        registry.remove("0xaaa").unwrap();
+        assert_eq!(registry.active(), None);
+        assert!(switch_with(&store, &registry, "0xccc").is_err());
+        let _ = std::fs::remove_dir_all(base);
     }
 }
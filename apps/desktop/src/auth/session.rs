@@ -0,0 +1,499 @@
+//! Tempo session key lifecycle: expiry tracking, renewal and revocation of replaced keys.
+//!
+//! Signing paths call [`await_signing_auth`] from a background thread. When the key is
+//! about to lapse that blocks until the [`SessionManager`] has re-run the passkey
+//! callback flow, so queued work resumes with the rotated key instead of failing.
+
+use std::collections::HashSet;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use gpui::{App, AsyncApp};
+use smol::channel::{Receiver, Sender};
+
+use super::{
+    load_from_disk, run_auth_callback_server_with, save_to_disk, to_persisted, AuthFlowOptions,
+    AuthResult, AuthState, PersistedAuth, RetiredSessionKey,
+};
+use crate::scrobble::now_epoch_sec;
+use crate::status_center::StatusCenter;
+
+/// Keys closer than this to expiry are treated as expired so a tx never outlives its key.
+pub const SIGNING_MARGIN_SECS: u64 = 60;
+const DEFAULT_WARNING_SECS: u64 = 60 * 60;
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Covers the 2 minute browser flow plus time to persist the result.
+const RENEWAL_WAIT: Duration = Duration::from_secs(150);
+const STATUS_KEY: &str = "auth.session";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionHealth {
+    /// No Tempo session key in auth; nothing to renew.
+    Missing,
+    Fresh {
+        expires_at: u64,
+    },
+    ExpiringSoon {
+        expires_at: u64,
+    },
+    Expired,
+}
+
+pub fn session_health(auth: &PersistedAuth, now: u64) -> SessionHealth {
+    let (Some(_), Some(expires_at)) = (
+        auth.tempo_session_private_key.as_deref(),
+        auth.tempo_session_expires_at,
+    ) else {
+        return SessionHealth::Missing;
+    };
+    if now.saturating_add(SIGNING_MARGIN_SECS) >= expires_at {
+        SessionHealth::Expired
+    } else if now.saturating_add(warning_secs()) >= expires_at {
+        SessionHealth::ExpiringSoon { expires_at }
+    } else {
+        SessionHealth::Fresh { expires_at }
+    }
+}
+
+fn warning_secs() -> u64 {
+    std::env::var("HEAVEN_TEMPO_SESSION_WARN_SECS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_WARNING_SECS)
+}
+
+/// Return auth whose session key can sign right now, waiting for a renewal if needed.
+///
+/// Blocks the calling thread; never call it from the GPUI foreground executor.
+pub fn await_signing_auth(auth: &PersistedAuth) -> Result<PersistedAuth, String> {
+    if session_health(auth, now_epoch_sec()) != SessionHealth::Expired {
+        return Ok(auth.clone());
+    }
+    // The caller may hold a copy taken before a renewal already landed on disk.
+    if let Some(current) = renewed_on_disk(auth) {
+        return Ok(current);
+    }
+    log::info!("[Auth] Session key expired; waiting for renewal before signing");
+    gate()
+        .request_and_wait(RENEWAL_WAIT)
+        .map_err(|err| format!("Tempo session key expired and was not renewed: {err}"))?;
+    renewed_on_disk(auth).ok_or_else(|| {
+        "Tempo session key has expired. Renew it in Settings to continue.".to_string()
+    })
+}
+
+fn renewed_on_disk(auth: &PersistedAuth) -> Option<PersistedAuth> {
+    let current = load_from_disk()?;
+    let same_wallet = match (current.wallet_address(), auth.wallet_address()) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => false,
+    };
+    let usable = matches!(
+        session_health(&current, now_epoch_sec()),
+        SessionHealth::Fresh { .. } | SessionHealth::ExpiringSoon { .. }
+    );
+    (same_wallet && usable).then_some(current)
+}
+
+/// Session keys the next renewal should ask the auth page to revoke on-chain.
+fn keys_to_revoke(auth: &PersistedAuth, now: u64) -> Vec<RetiredSessionKey> {
+    let mut keys: Vec<RetiredSessionKey> = auth.retired_session_keys.clone();
+    if let Some(address) = auth.tempo_session_address.clone() {
+        keys.push(RetiredSessionKey {
+            address,
+            expires_at: auth.tempo_session_expires_at,
+        });
+    }
+    // Expired keys are already rejected on-chain.
+    keys.retain(|key| key.expires_at.is_none_or(|expires_at| expires_at > now));
+    let mut seen = HashSet::new();
+    keys.retain(|key| seen.insert(key.address.to_ascii_lowercase()));
+    keys
+}
+
+/// Auth to persist after a renewal: the new session plus whatever revocation is still owed.
+fn rotated_auth(
+    previous: Option<&PersistedAuth>,
+    result: &AuthResult,
+    requested: Vec<RetiredSessionKey>,
+) -> PersistedAuth {
+    let mut persisted = to_persisted(result);
+    let same_wallet = match (
+        previous.and_then(PersistedAuth::wallet_address),
+        persisted.wallet_address(),
+    ) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => false,
+    };
+    if !same_wallet {
+        return persisted;
+    }
+    let mut settled: HashSet<String> = result
+        .revoked_session_keys
+        .iter()
+        .flatten()
+        .map(|address| address.to_ascii_lowercase())
+        .collect();
+    if let Some(current) = persisted.tempo_session_address.as_deref() {
+        settled.insert(current.to_ascii_lowercase());
+    }
+    persisted.retired_session_keys = requested
+        .into_iter()
+        .filter(|key| !settled.contains(&key.address.to_ascii_lowercase()))
+        .collect();
+    persisted
+}
+
+/// Rendezvous between blocked signers and the foreground renewal flow.
+struct RenewalGate {
+    state: Mutex<GateState>,
+    changed: Condvar,
+    requests: (Sender<()>, Receiver<()>),
+}
+
+#[derive(Default)]
+struct GateState {
+    /// Bumped each time a renewal finishes, successfully or not.
+    generation: u64,
+    requested: bool,
+    renewing: bool,
+    last_error: Option<String>,
+    /// Signers blocked in `request_and_wait`.
+    waiting: usize,
+}
+
+fn gate() -> &'static RenewalGate {
+    static GATE: OnceLock<RenewalGate> = OnceLock::new();
+    GATE.get_or_init(RenewalGate::new)
+}
+
+impl RenewalGate {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(GateState::default()),
+            changed: Condvar::new(),
+            requests: smol::channel::unbounded(),
+        }
+    }
+
+    /// Ask for a renewal (once, however many signers are waiting) and block until one finishes.
+    fn request_and_wait(&self, timeout: Duration) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let started = state.generation;
+        state.waiting += 1;
+        if !state.requested && !state.renewing {
+            state.requested = true;
+            let _ = self.requests.0.try_send(());
+        }
+        let deadline = Instant::now() + timeout;
+        let outcome = loop {
+            if state.generation != started {
+                break match &state.last_error {
+                    Some(err) => Err(err.clone()),
+                    None => Ok(()),
+                };
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Err("timed out waiting for the passkey sign-in".to_string());
+            }
+            state = self
+                .changed
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        };
+        state.waiting -= 1;
+        outcome
+    }
+
+    /// A signer asked for a renewal that hasn't started yet.
+    fn pending(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.requested && !state.renewing
+    }
+
+    /// Claim the renewal; false if one is already running.
+    fn begin(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.renewing {
+            return false;
+        }
+        state.renewing = true;
+        state.requested = false;
+        true
+    }
+
+    fn finish(&self, outcome: Result<(), String>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.renewing = false;
+        state.generation = state.generation.wrapping_add(1);
+        state.last_error = outcome.err();
+        self.changed.notify_all();
+    }
+}
+
+/// Watches the persisted session key and runs renewals on the foreground executor.
+#[derive(Default)]
+pub struct SessionManager {
+    /// Expiry (and whether it had lapsed) the current warning was shown for.
+    warned_for: Option<(u64, bool)>,
+}
+
+impl gpui::Global for SessionManager {}
+
+impl SessionManager {
+    pub fn start(cx: &mut App) {
+        cx.set_global(Self::default());
+        let requests = gate().requests.1.clone();
+        cx.spawn(async move |cx: &mut AsyncApp| loop {
+            let requested = smol::future::or(async { requests.recv().await.is_ok() }, async {
+                smol::Timer::after(CHECK_INTERVAL).await;
+                false
+            })
+            .await;
+            let alive = cx.update(|cx| {
+                if requested {
+                    Self::renew(cx);
+                } else {
+                    Self::check(cx);
+                }
+            });
+            if alive.is_err() {
+                break;
+            }
+        })
+        .detach();
+    }
+
+    fn check(cx: &mut App) {
+        let auth_state = cx.global::<AuthState>();
+        if auth_state.authing {
+            return;
+        }
+        // A request that arrived during an interactive sign-in is picked up once it ends.
+        if gate().pending() {
+            Self::renew(cx);
+            return;
+        }
+        let Some(auth) = auth_state.persisted.as_ref() else {
+            return;
+        };
+        let now = now_epoch_sec();
+        let (warning, message) = match session_health(auth, now) {
+            SessionHealth::ExpiringSoon { expires_at } => (
+                (expires_at, false),
+                format!(
+                    "Tempo session key expires in {}. Renew it in Settings to keep scrobbles and uploads signing.",
+                    format_remaining(expires_at.saturating_sub(now))
+                ),
+            ),
+            SessionHealth::Expired => (
+                (auth.tempo_session_expires_at.unwrap_or_default(), true),
+                "Tempo session key has expired. Renew it in Settings; scrobbles are queued until then."
+                    .to_string(),
+            ),
+            SessionHealth::Missing | SessionHealth::Fresh { .. } => return,
+        };
+        if cx.global::<Self>().warned_for == Some(warning) {
+            return;
+        }
+        cx.global_mut::<Self>().warned_for = Some(warning);
+        cx.update_global::<StatusCenter, _>(|status, _| {
+            status.publish_warning(STATUS_KEY, message)
+        });
+    }
+
+    /// Re-run the passkey flow to authorize a new session key and revoke the replaced ones.
+    /// Does nothing while another sign-in is in progress.
+    pub fn renew(cx: &mut App) {
+        if cx.global::<AuthState>().authing {
+            log::info!("[Auth] Sign-in in progress; deferring session key renewal");
+            return;
+        }
+        if !gate().begin() {
+            return;
+        }
+        let previous = cx
+            .global::<AuthState>()
+            .persisted
+            .clone()
+            .or_else(load_from_disk);
+        let revoke = previous
+            .as_ref()
+            .map(|auth| keys_to_revoke(auth, now_epoch_sec()))
+            .unwrap_or_default();
+        cx.update_global::<AuthState, _>(|state, _| state.authing = true);
+        cx.update_global::<StatusCenter, _>(|status, _| {
+            status.publish_progress(
+                STATUS_KEY,
+                "Opening browser to renew the Tempo session key...",
+                None,
+            )
+        });
+
+        cx.spawn(async move |cx: &mut AsyncApp| {
+            let options = AuthFlowOptions {
+                revoke_session_keys: revoke.iter().map(|key| key.address.clone()).collect(),
+            };
+            let result = run_auth_callback_server_with(&options)
+                .await
+                .and_then(|result| {
+                    let persisted = rotated_auth(previous.as_ref(), &result, revoke);
+                    save_to_disk(&persisted)?;
+                    Ok(persisted)
+                });
+            let outcome = result.as_ref().map(|_| ()).map_err(Clone::clone);
+            let _ = cx.update(|cx| {
+                cx.update_global::<AuthState, _>(|state, _| {
+                    state.authing = false;
+                    if let Ok(persisted) = &result {
+                        state.persisted = Some(persisted.clone());
+                    }
+                });
+                cx.global_mut::<Self>().warned_for = None;
+                cx.update_global::<StatusCenter, _>(|status, _| match &result {
+                    Ok(persisted) if persisted.retired_session_keys.is_empty() => {
+                        status.publish_success(STATUS_KEY, "Tempo session key renewed.")
+                    }
+                    Ok(persisted) => status.publish_warning(
+                        STATUS_KEY,
+                        format!(
+                            "Tempo session key renewed; {} old key(s) are still awaiting revocation.",
+                            persisted.retired_session_keys.len()
+                        ),
+                    ),
+                    Err(err) => status.publish_error(
+                        STATUS_KEY,
+                        format!("Session key renewal failed: {err}"),
+                    ),
+                });
+            });
+            gate().finish(outcome);
+        })
+        .detach();
+    }
+}
+
+fn format_remaining(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{} min", secs.div_ceil(60).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn auth_expiring_at(expires_at: u64) -> PersistedAuth {
+        let mut auth = to_persisted(&serde_json::from_str(r#"{"walletAddress":"0xabc"}"#).unwrap());
+        auth.tempo_session_private_key = Some("0x01".to_string());
+        auth.tempo_session_address = Some("0xKeyB".to_string());
+        auth.tempo_session_expires_at = Some(expires_at);
+        auth
+    }
+
+    #[test]
+    fn health_tracks_warning_window_and_signing_margin() {
+        let now = 1_000_000;
+        assert_eq!(
+            session_health(&auth_expiring_at(now + 2 * DEFAULT_WARNING_SECS), now),
+            SessionHealth::Fresh {
+                expires_at: now + 2 * DEFAULT_WARNING_SECS
+            }
+        );
+        assert_eq!(
+            session_health(&auth_expiring_at(now + 600), now),
+            SessionHealth::ExpiringSoon {
+                expires_at: now + 600
+            }
+        );
+        assert_eq!(
+            session_health(&auth_expiring_at(now + SIGNING_MARGIN_SECS), now),
+            SessionHealth::Expired
+        );
+        let mut missing = auth_expiring_at(now + 600);
+        missing.tempo_session_private_key = None;
+        assert_eq!(session_health(&missing, now), SessionHealth::Missing);
+    }
+
+    #[test]
+    fn rotation_keeps_only_keys_not_yet_revoked() {
+        let now = 1_000_000;
+        let mut previous = auth_expiring_at(now + 600);
+        previous.retired_session_keys = vec![
+            RetiredSessionKey {
+                address: "0xKeyA".to_string(),
+                expires_at: Some(now + 86_400),
+            },
+            RetiredSessionKey {
+                address: "0xStale".to_string(),
+                expires_at: Some(now - 1),
+            },
+        ];
+        let revoke = keys_to_revoke(&previous, now);
+        let addresses: Vec<&str> = revoke.iter().map(|key| key.address.as_str()).collect();
+        assert_eq!(addresses, ["0xKeyA", "0xKeyB"]);
+
+        let result: AuthResult = serde_json::from_str(
+            r#"{"walletAddress":"0xABC","tempoSessionAddress":"0xKeyC","revokedSessionKeys":["0xkeya"]}"#,
+        )
+        .unwrap();
+        let rotated = rotated_auth(Some(&previous), &result, revoke);
+        assert_eq!(rotated.tempo_session_address.as_deref(), Some("0xKeyC"));
+        assert_eq!(
+            rotated.retired_session_keys,
+            vec![RetiredSessionKey {
+                address: "0xKeyB".to_string(),
+                expires_at: Some(now + 600),
+            }]
+        );
+    }
+
+    #[test]
+    fn gate_releases_all_waiters_after_one_request() {
+        let gate = Arc::new(RenewalGate::new());
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let gate = gate.clone();
+                std::thread::spawn(move || gate.request_and_wait(Duration::from_secs(5)))
+            })
+            .collect();
+
+        let receiver = gate.requests.1.clone();
+        smol::block_on(receiver.recv()).unwrap();
+        // Every waiter must have taken its generation before the renewal completes.
+        while gate.state.lock().unwrap().waiting < 3 {
+            std::thread::yield_now();
+        }
+        assert!(gate.begin());
+        assert!(!gate.begin());
+        gate.finish(Ok(()));
+
+        for waiter in waiters {
+            assert_eq!(waiter.join().unwrap(), Ok(()));
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn gate_reports_failed_renewal_and_times_out() {
+        let gate = Arc::new(RenewalGate::new());
+        let waiter = {
+            let gate = gate.clone();
+            std::thread::spawn(move || gate.request_and_wait(Duration::from_secs(5)))
+        };
+        smol::block_on(gate.requests.1.recv()).unwrap();
+        assert!(gate.begin());
+        gate.finish(Err("passkey prompt dismissed".to_string()));
+        assert_eq!(
+            waiter.join().unwrap(),
+            Err("passkey prompt dismissed".to_string())
+        );
+
+        assert!(gate.request_and_wait(Duration::from_millis(20)).is_err());
+    }
+}
@@ -222,10 +222,21 @@ impl ChatView {
                 Ok(mut voice) => voice.reset_auth(),
                 Err(poisoned) => poisoned.into_inner().reset_auth(),
             }
+            let switched_accounts = self.own_address.is_some() && new_address.is_some();
             self.own_address = new_address;
             self.disappearing_message_seconds.clear();
             self.reload_scarlett_history_for_current_owner();
-            if self.own_address.is_some() && !self.connected && !self.connecting {
+            if switched_accounts && (self.connected || self.connecting) {
+                // Drop the previous wallet's installation before connecting the new one.
+                self.global_stream_generation = self.global_stream_generation.wrapping_add(1);
+                lock_xmtp(&self.xmtp).disconnect();
+                self.connected = false;
+                self.conversations.clear();
+                self.ensure_scarlett_conversation();
+                self.messages = self.scarlett_messages.clone();
+                self.active_conversation_id = None;
+                self.try_connect(cx);
+            } else if self.own_address.is_some() && !self.connected && !self.connecting {
                 self.try_connect(cx);
             } else if self.own_address.is_none() {
                 // Logged out
@@ -29,6 +29,13 @@ impl ChatView {
             .and_then(|r| r);
 
             let _ = this.update(cx, |this, cx| {
+                if this.own_address.as_deref() != Some(address.as_str()) {
+                    // The account changed mid-connect; a newer attempt owns the state.
+                    log::info!(
+                        "[Chat] Ignoring XMTP connect result for previous account {address}"
+                    );
+                    return;
+                }
                 this.connecting = false;
                 match result {
                     Ok(inbox_id) => {
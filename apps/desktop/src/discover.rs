@@ -20,7 +20,9 @@ use gpui_component::StyledExt;
 use serde_json::Value;
 
 use crate::library;
+use crate::music_db::ScrobbleScope;
 use crate::pages::Page;
+use crate::profile::scrobbles_feed::{open_scrobble_store, sync_scrobble_history};
 use crate::shared::{ipfs, rpc::http_post_json};
 use crate::shell::app_sidebar::NavChannel;
 use crate::ui::overflow_menu::track_row_overflow_menu;
@@ -28,6 +30,8 @@ use crate::ui::overflow_menu::track_row_overflow_menu;
 const MAX_TRENDING_ROOMS: usize = 10;
 const MAX_NEW_RELEASES: usize = 10;
 const MAX_TOP_SONGS: usize = 10;
+/// Opening Discover reuses stored play counts when the last sync is this recent.
+const TOP_SONGS_SYNC_MAX_AGE_SECS: u64 = 300;
 
 const DEFAULT_SUBGRAPH_MUSIC_SOCIAL_URL: &str =
     "https://graph.dotheaven.org/subgraphs/name/dotheaven/music-social-tempo";
@@ -232,7 +236,14 @@ impl DiscoverView {
 
         let mode = self.top_songs_mode;
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
-            let result = smol::unblock(move || fetch_top_songs(mode, MAX_TOP_SONGS)).await;
+            let sync_max_age_secs = if force {
+                0
+            } else {
+                TOP_SONGS_SYNC_MAX_AGE_SECS
+            };
+            let result =
+                smol::unblock(move || fetch_top_songs(mode, MAX_TOP_SONGS, sync_max_age_secs))
+                    .await;
             let _ = this.update(cx, |this, cx| {
                 if this.top_songs_fetch_seq != fetch_seq {
                     return;
@@ -976,54 +987,39 @@ fn fetch_latest_tracks(max_entries: usize) -> Result<Vec<ReleaseRow>, String> {
     Ok(out)
 }
 
-fn fetch_top_songs(mode: TopSongsMode, max_entries: usize) -> Result<Vec<TopSongRow>, String> {
-    let subgraph_url = subgraph_music_social_url();
-    let order_by = match mode {
-        TopSongsMode::All => "scrobbleCountTotal",
-        TopSongsMode::Verified => "scrobbleCountVerified",
-    };
-
-    // Default to MBID + Story ipId tracks.
-    let where_clause = match mode {
-        TopSongsMode::All => "where: { kind_in: [1, 2] }",
-        TopSongsMode::Verified => "where: { kind_in: [1, 2], scrobbleCountVerified_gt: \"0\" }",
-    };
-
-    let query = format!(
-        "{{ tracks({where_clause}, first: {max_entries}, orderBy: {order_by}, orderDirection: desc) {{ id title artist album coverCid scrobbleCountTotal scrobbleCountVerified }} }}"
-    );
-
-    let response = http_post_json(&subgraph_url, serde_json::json!({ "query": query }))?;
-    if let Some(errors) = response.get("errors") {
-        return Err(format!("Music-social subgraph error: {errors}"));
+/// Top songs from the local scrobble store, after a sync unless the last one is recent.
+fn fetch_top_songs(
+    mode: TopSongsMode,
+    max_entries: usize,
+    sync_max_age_secs: u64,
+) -> Result<Vec<TopSongRow>, String> {
+    let db = open_scrobble_store()?;
+    if let Err(err) = sync_scrobble_history(&db, &ScrobbleScope::All, sync_max_age_secs) {
+        log::warn!("[Discover] scrobble history sync failed: {}", err);
     }
+    let stats = db
+        .lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?
+        .scrobbled_track_stats()?;
 
-    let rows = response
-        .get("data")
-        .and_then(|v| v.get("tracks"))
-        .and_then(|v| v.as_array())
-        .cloned()
-        .unwrap_or_default();
-
-    let mut out = Vec::new();
-    for row in rows {
-        let title = sanitize_track_field(row.get("title"), "Unknown Track");
-        let artist = sanitize_track_field(row.get("artist"), "Unknown Artist");
-        let album = sanitize_track_field(row.get("album"), "");
-        let cover_cid = sanitize_cover_cid(row.get("coverCid"));
-        let play_count_total = parse_u64_field(row.get("scrobbleCountTotal"));
-        let play_count_verified = parse_u64_field(row.get("scrobbleCountVerified"));
-
-        out.push(TopSongRow {
-            title,
-            artist,
-            album,
-            cover_cid,
-            play_count_total,
-            play_count_verified,
-        });
+    // Default to MBID + Story ipId tracks.
+    let mut out: Vec<TopSongRow> = stats
+        .into_iter()
+        .filter(|stats| matches!(stats.meta.kind, Some(1 | 2)))
+        .map(|stats| TopSongRow {
+            title: stats.meta.title,
+            artist: stats.meta.artist,
+            album: stats.meta.album,
+            cover_cid: stats.meta.cover_cid,
+            play_count_total: stats.plays,
+            play_count_verified: stats.meta.verified_plays.unwrap_or(0),
+        })
+        .filter(|row| mode == TopSongsMode::All || row.play_count_verified > 0)
+        .collect();
+    if mode == TopSongsMode::Verified {
+        out.sort_by(|a, b| b.play_count_verified.cmp(&a.play_count_verified));
     }
-
+    out.truncate(max_entries);
     Ok(out)
 }
 
@@ -1,3 +1,4 @@
+use crate::shared::gateways::{GatewayPool, Integrity};
 use gpui::http_client::{self, HttpClient};
 use std::any::type_name;
 use std::time::Duration;
@@ -71,8 +72,23 @@ impl HttpClient for HeavenHttpClient {
                     .insert(http_client::http::header::USER_AGENT, ua);
             }
 
+            // Plain GETs of gateway content go through the pool, which fails over between
+            // gateways and checks bodies against their CID.
+            let pooled = (parts.method == http_client::http::Method::GET && body_bytes.is_empty())
+                .then(|| GatewayPool::shared().resource_for_url(&parts.uri.to_string()))
+                .flatten();
+
             smol::unblock(
                 move || -> anyhow::Result<http_client::Response<http_client::AsyncBody>> {
+                    if let Some(resource) = pooled {
+                        let fetched = GatewayPool::shared()
+                            .fetch(&resource, Integrity::default(), None)
+                            .map_err(|e| anyhow::anyhow!(e))?;
+                        return Ok(http_client::Response::builder()
+                            .status(200)
+                            .body(http_client::AsyncBody::from(fetched.bytes))?);
+                    }
+
                     let request = http_client::Request::from_parts(parts, body_bytes);
 
                     let mut response = agent
@@ -5,9 +5,8 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::io::Read;
 use std::path::PathBuf;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicU8};
 use std::sync::{Arc, Mutex};
 
 use alloy_primitives::Address;
@@ -19,13 +18,21 @@ use gpui_component::menu::{DropdownMenu, PopupMenuItem};
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::Sizable;
 use gpui_component::StyledExt;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::audio::AudioHandle;
 use crate::auth;
-use crate::load_storage::{LoadStorageService, PlaylistTrackInput, TrackMetaInput};
-use crate::music_db::{MusicDb, ScanProgress, StorageStatus, TrackRow};
+pub use crate::load_storage::PlaylistSummary;
+use crate::load_storage::{
+    ArweaveArchivePlan, ContentUpload, LoadStorageError, LoadStorageService, PlaylistTrackInput,
+    RegisteredContent, TrackMetaInput, UploadControl, UploadPhase, UploadProgress,
+};
+use crate::music_db::{
+    MusicDb, PendingRevocationRow, RecordSyncState, ScanProgress, ScrobbleOutboxCounts,
+    SharedGrantRecord, StorageStatus, TrackArchiveRow, TrackRow, UploadJobRow, UploadedTrackRecord,
+};
+use crate::scrobble::eligibility::{PlaySession, ScrobbleRules};
+use crate::scrobble::sinks::ScrobbleSinkKind;
 use crate::scrobble::{now_epoch_sec, ScrobbleService};
 use crate::ui::overflow_menu::track_row_overflow_menu;
 
@@ -150,42 +157,11 @@ struct AlbumCloudStats {
     track_scrobbles: HashMap<String, usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct UploadedTrackRecord {
-    owner_address: String,
-    file_path: String,
-    title: String,
-    artist: String,
-    album: String,
-    track_id: String,
-    content_id: String,
-    piece_cid: String,
-    gateway_url: String,
-    tx_hash: String,
-    register_version: String,
-    created_at_ms: i64,
-    #[serde(default)]
-    saved_forever: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SharedGrantRecord {
-    owner_address: String,
-    grantee_address: String,
-    #[serde(default)]
-    title: String,
-    #[serde(default)]
-    artist: String,
-    #[serde(default)]
-    album: String,
-    #[serde(default)]
-    track_id: Option<String>,
-    content_id: String,
-    piece_cid: String,
-    gateway_url: String,
-    tx_hash: String,
-    mirror_tx_hash: String,
-    shared_at_ms: i64,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct UploadQueueCounts {
+    running: usize,
+    waiting: usize,
+    failed: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -197,6 +173,28 @@ struct ActiveSharedPlayback {
     local_path: String,
 }
 
+/// Save Forever priced and waiting for the user to accept the Arweave cost.
+struct ArchiveConfirmation {
+    label: String,
+    owner_address: String,
+    tracks: Vec<PricedArchive>,
+}
+
+/// One track on its way to Arweave, before anything is paid for.
+struct PricedArchive {
+    track: TrackRow,
+    /// Load record for the track; kept when a fresh Load upload went through.
+    record: Option<UploadedTrackRecord>,
+    step: Result<ArchiveStep, LoadStorageError>,
+}
+
+enum ArchiveStep {
+    /// Posted by an earlier attempt and still waiting for a gateway to serve it.
+    Posted(TrackArchiveRow),
+    /// Staged and priced; uploaded once confirmed.
+    Planned(ArweaveArchivePlan),
+}
+
 #[derive(Debug, Clone)]
 struct PlaylistDetailTrack {
     track_id: String,
@@ -229,15 +227,6 @@ struct PendingPlaylistMutation {
     created_at_ms: i64,
 }
 
-#[derive(Debug, Clone)]
-pub struct PlaylistSummary {
-    pub id: String,
-    pub name: String,
-    pub cover_cid: Option<String>,
-    pub visibility: u8,
-    pub track_count: usize,
-}
-
 // =============================================================================
 // Library state
 // =============================================================================
@@ -257,8 +246,17 @@ pub struct LibraryView {
     track_started_at_sec: Option<u64>,
     last_scrobbled_key: Option<String>,
     scrobble_service: Option<Arc<Mutex<ScrobbleService>>>,
+    scrobble_outbox_counts: ScrobbleOutboxCounts,
+    scrobble_outbox_flushing: bool,
+    scrobble_outbox_wake_at: Option<u64>,
+    scrobble_sinks_enabled: Vec<ScrobbleSinkKind>,
+    scrobble_sinks_flushing: HashSet<ScrobbleSinkKind>,
+    scrobble_sinks_wake_at: Option<u64>,
+    scrobble_rules: ScrobbleRules,
+    play_session: Option<PlaySession>,
     storage: Arc<Mutex<LoadStorageService>>,
     upload_busy: bool,
+    archive_confirmation: Option<ArchiveConfirmation>,
     status_message: Option<String>,
     storage_balance: Option<String>,
     storage_monthly: Option<String>,
@@ -273,6 +271,14 @@ pub struct LibraryView {
     share_modal_track_index: Option<usize>,
     share_modal_submitting: bool,
     share_modal_error: Option<String>,
+    /// Grants issued for the track open in the share modal.
+    share_modal_grantees: Vec<SharedGrantRecord>,
+    /// Grantee whose access is being revoked from the share modal.
+    share_modal_revoking: Option<String>,
+    /// Unfinished revoke of the track open in the share modal, offered for retry.
+    share_modal_pending_revocation: Option<PendingRevocationRow>,
+    /// Content ids whose revoke is publishing envelopes right now.
+    revocations_publishing: HashSet<String>,
     playlist_share_modal_open: bool,
     playlist_share_modal_playlist_id: Option<String>,
     playlist_share_modal_playlist_name: Option<String>,
@@ -327,6 +333,20 @@ pub struct LibraryView {
     artist_cloud_stats: Option<ArtistCloudStats>,
     album_cloud_stats_key: Option<String>,
     album_cloud_stats: Option<AlbumCloudStats>,
+    lyrics_prefetch_seen: HashSet<String>,
+    lyrics_prefetch_cancel: Option<Arc<AtomicBool>>,
+    lyrics_prefetch_progress: Option<(usize, usize)>,
+    scrobble_import_cancel: Option<Arc<AtomicBool>>,
+    scrobble_import_progress: Option<(usize, usize)>,
+    upload_jobs: Vec<UploadJobRow>,
+    upload_job_progress: HashMap<i64, UploadProgress>,
+    /// Pause/cancel flags of running upload workers, keyed by job id.
+    upload_job_controls: HashMap<i64, Arc<AtomicU8>>,
+    upload_queue_wake_at: Option<u64>,
+    transfers_panel_open: bool,
+    record_sync_running: bool,
+    /// Bumped per scheduled record sync so a superseded timer does nothing.
+    record_sync_seq: u64,
 }
 
 mod impl_constructor_playback;
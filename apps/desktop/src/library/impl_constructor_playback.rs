@@ -1,9 +1,15 @@
 use super::*;
 
 mod init_and_queue;
+mod lyrics_prefetch;
+mod lyrics_publish;
+mod play_tracking;
 mod playback_navigation;
 mod scanning;
 mod scrobble_enqueue;
+mod scrobble_import;
+mod scrobble_outbox;
+mod scrobble_sinks;
 mod scrobble_submit;
 mod search_sort;
 mod storage;
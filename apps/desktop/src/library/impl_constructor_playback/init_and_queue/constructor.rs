@@ -35,8 +35,17 @@ impl LibraryView {
             track_started_at_sec: None,
             last_scrobbled_key: None,
             scrobble_service,
+            scrobble_outbox_counts: ScrobbleOutboxCounts::default(),
+            scrobble_outbox_flushing: false,
+            scrobble_outbox_wake_at: None,
+            scrobble_sinks_enabled: vec![ScrobbleSinkKind::Tempo],
+            scrobble_sinks_flushing: HashSet::new(),
+            scrobble_sinks_wake_at: None,
+            scrobble_rules: ScrobbleRules::default(),
+            play_session: None,
             storage: Arc::new(Mutex::new(LoadStorageService::new())),
             upload_busy: false,
+            archive_confirmation: None,
             status_message: None,
             storage_balance: None,
             storage_monthly: None,
@@ -51,6 +60,10 @@ impl LibraryView {
             share_modal_track_index: None,
             share_modal_submitting: false,
             share_modal_error: None,
+            share_modal_grantees: Vec::new(),
+            share_modal_revoking: None,
+            share_modal_pending_revocation: None,
+            revocations_publishing: HashSet::new(),
             playlist_share_modal_open: false,
             playlist_share_modal_playlist_id: None,
             playlist_share_modal_playlist_name: None,
@@ -105,6 +118,18 @@ impl LibraryView {
             artist_cloud_stats: None,
             album_cloud_stats_key: None,
             album_cloud_stats: None,
+            lyrics_prefetch_seen: HashSet::new(),
+            lyrics_prefetch_cancel: None,
+            lyrics_prefetch_progress: None,
+            scrobble_import_cancel: None,
+            scrobble_import_progress: None,
+            upload_jobs: Vec::new(),
+            upload_job_progress: HashMap::new(),
+            upload_job_controls: HashMap::new(),
+            upload_queue_wake_at: None,
+            transfers_panel_open: false,
+            record_sync_running: false,
+            record_sync_seq: 0,
         };
 
         cx.subscribe_in(
@@ -151,11 +176,22 @@ impl LibraryView {
         .detach();
 
         cx.observe_global::<auth::AuthState>(|this, cx| {
+            // Account switches re-scope both indexes, whichever mode is showing.
+            this.refresh_uploaded_index_from_auth();
             match this.mode {
-                LibraryMode::Library => this.refresh_uploaded_index_from_auth(),
+                LibraryMode::Library => this.clear_shared_records_if_owner_changed(),
                 LibraryMode::SharedWithMe => this.refresh_shared_records_for_auth(cx),
             }
             this.refresh_sidebar_playlists(cx);
+            this.claim_signed_out_scrobbles();
+            this.refresh_scrobble_sinks_enabled();
+            this.refresh_scrobble_outbox_counts(cx);
+            this.flush_scrobble_outbox(cx);
+            this.flush_scrobble_sinks(cx);
+            this.run_scrobble_import(cx);
+            this.pump_upload_queue(cx);
+            this.resume_pending_revocations(cx);
+            this.run_record_sync(cx);
             cx.notify();
         })
         .detach();
@@ -165,6 +201,41 @@ impl LibraryView {
                 let saved_folder = db.get_setting("folder_path");
                 let db = Arc::new(Mutex::new(db));
                 this.db = Some(db.clone());
+                this.scrobble_rules = ScrobbleRules::configured(Some(&db));
+                this.refresh_scrobble_sinks_enabled();
+                if let Err(err) = db
+                    .lock()
+                    .map_err(|e| format!("lock: {e}"))
+                    .and_then(|db| db.requeue_interrupted_upload_jobs(now_epoch_sec() as i64))
+                {
+                    log::warn!("[Upload] requeueing interrupted jobs failed: {}", err);
+                }
+                match with_music_db(Some(&db), import_legacy_record_files) {
+                    Ok(0) => {}
+                    Ok(imported) => {
+                        log::info!(
+                            "[Library] imported {} legacy upload/share records",
+                            imported
+                        )
+                    }
+                    Err(err) => log::warn!("[Library] legacy record import failed: {}", err),
+                }
+
+                let purge_db = db.clone();
+                cx.spawn(async move |_this: WeakEntity<Self>, _cx: &mut AsyncApp| {
+                    let purged = smol::unblock(move || {
+                        crate::lyrics::prefetch::purge_expired_lyrics_cache(&purge_db)
+                    })
+                    .await;
+                    match purged {
+                        Ok(removed) if removed > 0 => {
+                            log::info!("[lyrics] purged {} expired lyrics cache rows", removed)
+                        }
+                        Ok(_) => {}
+                        Err(err) => log::warn!("[lyrics] lyrics cache purge failed: {}", err),
+                    }
+                })
+                .detach();
 
                 if let Some(folder) = saved_folder {
                     this.folder = Some(folder.clone());
@@ -179,7 +250,15 @@ impl LibraryView {
         }
 
         this.fetch_storage_status(cx);
+        this.claim_signed_out_scrobbles();
+        this.refresh_scrobble_outbox_counts(cx);
+        this.flush_scrobble_outbox(cx);
+        this.flush_scrobble_sinks(cx);
+        this.run_scrobble_import(cx);
+        this.pump_upload_queue(cx);
         this.refresh_uploaded_index_from_auth();
+        this.resume_pending_revocations(cx);
+        this.run_record_sync(cx);
         this.refresh_sidebar_playlists(cx);
         this
     }
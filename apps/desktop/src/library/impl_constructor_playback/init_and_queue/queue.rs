@@ -28,7 +28,7 @@ impl LibraryView {
         cx.notify();
     }
 
-    pub(in crate::library) fn play_track(&mut self, index: usize, _cx: &mut Context<Self>) {
+    pub(in crate::library) fn play_track(&mut self, index: usize, cx: &mut Context<Self>) {
         if let Some(track) = self.tracks.get(index) {
             log::info!(
                 "[Playback] play_track: index={}, title='{}', artist='{}', file='{}'",
@@ -50,6 +50,7 @@ impl LibraryView {
                 .iter()
                 .position(|path| path == &track.file_path);
             self.track_started_at_sec = Some(now_epoch_sec());
+            self.prefetch_upcoming_lyrics(cx);
         } else {
             log::warn!(
                 "[Playback] play_track ignored: index={} is out of bounds (tracks={})",
@@ -0,0 +1,199 @@
+use super::*;
+use crate::library::impl_detail_mode::lyrics_signature_for_track;
+use crate::lyrics::prefetch::{
+    lrclib_backoff_remaining, lyrics_coverage, prefetch_lyrics, purge_expired_lyrics_cache,
+    LyricsPrefetchOutcome, PREFETCH_REMOTE_SPACING, QUEUE_PREFETCH_LOOKAHEAD,
+};
+use crate::lyrics::LyricsTrackSignature;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+impl LibraryView {
+    /// Warm lyrics for the next few queue entries so the side player resolves them instantly.
+    pub(in crate::library) fn prefetch_upcoming_lyrics(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(active_pos) = self.active_queue_pos else {
+            return;
+        };
+        let upcoming: Vec<String> = self
+            .playback_queue_paths
+            .iter()
+            .skip(active_pos + 1)
+            .take(QUEUE_PREFETCH_LOOKAHEAD)
+            .filter(|path| !self.lyrics_prefetch_seen.contains(*path))
+            .cloned()
+            .collect();
+        let signatures: Vec<LyricsTrackSignature> = upcoming
+            .iter()
+            .filter_map(|path| self.lyrics_signature_for_path(path, None))
+            .collect();
+        self.lyrics_prefetch_seen.extend(upcoming);
+        if signatures.is_empty() {
+            return;
+        }
+
+        cx.spawn(async move |_this: WeakEntity<Self>, _cx: &mut AsyncApp| {
+            for signature in signatures {
+                let db = db.clone();
+                let track_name = signature.track_name.clone();
+                let result = smol::unblock(move || prefetch_lyrics(&signature, &db)).await;
+                match result {
+                    Ok(outcome) => {
+                        log::debug!("[lyrics] queue prefetch '{}': {:?}", track_name, outcome);
+                        if outcome == LyricsPrefetchOutcome::Remote {
+                            smol::Timer::after(PREFETCH_REMOTE_SPACING).await;
+                        }
+                    }
+                    Err(err) => {
+                        log::warn!("[lyrics] queue prefetch '{}' failed: {}", track_name, err)
+                    }
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub(in crate::library) fn lyrics_prefetch_running(&self) -> bool {
+        self.lyrics_prefetch_cancel.is_some()
+    }
+
+    /// Purge expired cache rows, then resolve lyrics for every library track, paced for LRCLIB.
+    pub(in crate::library) fn start_library_lyrics_prefetch(&mut self, cx: &mut Context<Self>) {
+        if self.lyrics_prefetch_running() {
+            return;
+        }
+        let Some(db) = self.db.clone() else {
+            self.set_status_message(
+                "Lyrics prefetch unavailable: library database not open.",
+                cx,
+            );
+            return;
+        };
+        let signatures: Vec<LyricsTrackSignature> = self
+            .tracks
+            .iter()
+            .map(|track| lyrics_signature_for_track(track, None))
+            .collect();
+        if signatures.is_empty() {
+            self.set_status_message("No tracks to prefetch lyrics for.", cx);
+            return;
+        }
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.lyrics_prefetch_cancel = Some(cancel.clone());
+        self.lyrics_prefetch_progress = Some((0, signatures.len()));
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let purge_db = db.clone();
+            match smol::unblock(move || purge_expired_lyrics_cache(&purge_db)).await {
+                Ok(removed) if removed > 0 => {
+                    log::info!("[lyrics] purged {} expired lyrics cache rows", removed)
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("[lyrics] lyrics cache purge failed: {}", err),
+            }
+
+            let total = signatures.len();
+            let mut failures = 0usize;
+            for (idx, signature) in signatures.iter().cloned().enumerate() {
+                if cancel.load(AtomicOrdering::Relaxed) {
+                    break;
+                }
+                while let Some(wait) = lrclib_backoff_remaining() {
+                    if cancel.load(AtomicOrdering::Relaxed) {
+                        break;
+                    }
+                    smol::Timer::after(wait.min(std::time::Duration::from_secs(1))).await;
+                }
+                if cancel.load(AtomicOrdering::Relaxed) {
+                    break;
+                }
+
+                let job_db = db.clone();
+                let result = smol::unblock(move || prefetch_lyrics(&signature, &job_db)).await;
+                let paced = match result {
+                    Ok(outcome) => outcome == LyricsPrefetchOutcome::Remote,
+                    Err(err) => {
+                        failures += 1;
+                        log::warn!("[lyrics] library prefetch failed: {}", err);
+                        true
+                    }
+                };
+
+                let still_running = this
+                    .update(cx, |this, cx| {
+                        this.lyrics_prefetch_progress = Some((idx + 1, total));
+                        cx.notify();
+                    })
+                    .is_ok();
+                if !still_running {
+                    return;
+                }
+                if paced {
+                    smol::Timer::after(PREFETCH_REMOTE_SPACING).await;
+                }
+            }
+
+            let cancelled = cancel.load(AtomicOrdering::Relaxed);
+            let coverage_db = db.clone();
+            let coverage =
+                smol::unblock(move || lyrics_coverage(&signatures, Some(&coverage_db))).await;
+            let _ = this.update(cx, |this, cx| {
+                this.lyrics_prefetch_cancel = None;
+                this.lyrics_prefetch_progress = None;
+                let prefix = if cancelled {
+                    "Lyrics prefetch stopped."
+                } else if failures > 0 {
+                    "Lyrics prefetch finished with errors."
+                } else {
+                    "Lyrics prefetch finished."
+                };
+                match coverage {
+                    Ok(coverage) => {
+                        this.set_status_message(format!("{prefix} {}", coverage.summary()), cx)
+                    }
+                    Err(err) => this.set_status_message(format!("{prefix} ({err})"), cx),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    pub(in crate::library) fn cancel_library_lyrics_prefetch(&mut self, cx: &mut Context<Self>) {
+        if let Some(cancel) = self.lyrics_prefetch_cancel.as_ref() {
+            cancel.store(true, AtomicOrdering::Relaxed);
+            self.set_status_message("Stopping lyrics prefetch...", cx);
+        }
+    }
+
+    /// Report synced/plain/none coverage for the current library without touching the network.
+    pub(in crate::library) fn report_lyrics_coverage(&mut self, cx: &mut Context<Self>) {
+        let db = self.db.clone();
+        let signatures: Vec<LyricsTrackSignature> = self
+            .tracks
+            .iter()
+            .map(|track| lyrics_signature_for_track(track, None))
+            .collect();
+        self.set_status_message("Checking lyrics coverage...", cx);
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                if let Some(db) = db.as_ref() {
+                    purge_expired_lyrics_cache(db)?;
+                }
+                lyrics_coverage(&signatures, db.as_ref())
+            })
+            .await;
+            let _ = this.update(cx, |this, cx| match result {
+                Ok(coverage) => this.set_status_message(coverage.summary(), cx),
+                Err(err) => {
+                    this.set_status_message(format!("Lyrics coverage check failed: {err}"), cx)
+                }
+            });
+        })
+        .detach();
+    }
+}
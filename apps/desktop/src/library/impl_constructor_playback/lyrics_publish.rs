@@ -0,0 +1,111 @@
+use super::*;
+use crate::lyrics::{LyricsTrackSignature, ResolvedLyrics};
+
+impl LibraryView {
+    /// Upload edited lyrics to Arweave and anchor the ref on-chain via `setTrackLyricsFor`.
+    ///
+    /// ScrobbleV4 only lets operators overwrite an existing ref, so when the track already
+    /// has on-chain lyrics the upload is kept and reported but the anchor is left unchanged.
+    pub fn publish_edited_lyrics(
+        &mut self,
+        signature: LyricsTrackSignature,
+        lyrics: ResolvedLyrics,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(auth) = auth::load_from_disk() else {
+            self.set_status_message("Sign in to publish lyrics.", cx);
+            return;
+        };
+        let Some(service) = self.scrobble_service.clone() else {
+            self.set_status_message("Lyrics publish unavailable: scrobble service disabled.", cx);
+            return;
+        };
+        let db_handle = self.db.clone();
+        let track_id = match signature.track_id.clone() {
+            Some(track_id) => track_id,
+            None => match crate::scrobble::derive_track_id(&crate::scrobble::SubmitScrobbleInput {
+                artist: signature.artist_name.clone(),
+                title: signature.track_name.clone(),
+                album: Some(signature.album_name.clone()).filter(|a| !a.trim().is_empty()),
+                mbid: None,
+                ip_id: None,
+                duration_sec: 0,
+                played_at_sec: 0,
+            }) {
+                Ok(track_id) => track_id,
+                Err(err) => {
+                    self.set_status_message(format!("Lyrics publish failed: {err}"), cx);
+                    return;
+                }
+            },
+        };
+        let track_id = track_id.trim().to_ascii_lowercase();
+
+        cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+            status.publish_progress("lyrics-publish", "Publishing lyrics...", None);
+        });
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                let payload = crate::lyrics::lyrics_publish_payload(&track_id, &signature, &lyrics)?;
+                let uploaded_ref = {
+                    let mut service = service
+                        .lock()
+                        .map_err(|e| format!("scrobble service lock failed: {e}"))?;
+                    service.upload_track_lyrics_ref(&auth, &track_id, &payload)?
+                };
+                if let Some(db_handle) = db_handle.as_ref() {
+                    let db = db_handle
+                        .lock()
+                        .map_err(|e| format!("lyrics publish db lock failed: {e}"))?;
+                    db.set_track_lyrics_state_uploaded(&track_id, &uploaded_ref)?;
+                }
+
+                let anchored_ref = {
+                    let mut service = service
+                        .lock()
+                        .map_err(|e| format!("scrobble service lock failed: {e}"))?;
+                    service.ensure_track_lyrics_synced(&auth, &track_id, &uploaded_ref)?
+                };
+                if let Some(db_handle) = db_handle.as_ref() {
+                    let db = db_handle
+                        .lock()
+                        .map_err(|e| format!("lyrics publish db lock failed: {e}"))?;
+                    db.set_track_lyrics_state_synced(&track_id, &anchored_ref)?;
+                }
+                Ok::<_, String>((uploaded_ref, anchored_ref))
+            })
+            .await;
+
+            let _ = this.update(cx, |this, cx| {
+                cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                    status.dismiss_key("lyrics-publish");
+                });
+                match result {
+                    Ok((uploaded_ref, anchored_ref)) if uploaded_ref == anchored_ref => {
+                        log::info!("[lyrics] published edited lyrics: ref={}", anchored_ref);
+                        this.set_status_message("Lyrics published.", cx);
+                    }
+                    Ok((uploaded_ref, anchored_ref)) => {
+                        log::warn!(
+                            "[lyrics] edited lyrics uploaded but track already anchored: uploaded={} existing={}",
+                            uploaded_ref,
+                            anchored_ref
+                        );
+                        this.set_status_message(
+                            format!(
+                                "Lyrics uploaded ({uploaded_ref}), but this track already has published lyrics; an operator must replace them."
+                            ),
+                            cx,
+                        );
+                    }
+                    Err(err) => {
+                        log::warn!("[lyrics] publish failed: {}", err);
+                        this.set_status_message(format!("Lyrics publish failed: {err}"), cx);
+                    }
+                }
+            });
+        })
+        .detach();
+    }
+}
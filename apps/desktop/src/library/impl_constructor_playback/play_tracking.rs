@@ -0,0 +1,115 @@
+use super::storage::format_duration_mmss;
+use super::*;
+use crate::audio::PlaybackState;
+use crate::now_playing::{NowPlaying, NowPlayingState};
+use crate::scrobble::eligibility::PlaySession;
+
+impl LibraryView {
+    /// Playback poll hook: accumulate listening time for the current play, scrobble it once
+    /// it meets `scrobble_rules`, and keep the shared now-playing state current.
+    pub fn track_playback_progress(&mut self, cx: &mut Context<Self>) {
+        let state = self.audio.read_state();
+        let Some(track_path) = state.track_path.clone() else {
+            self.play_session = None;
+            self.publish_now_playing(&state, true, cx);
+            return;
+        };
+        let Some(started_at_sec) = self.track_started_at_sec else {
+            return;
+        };
+
+        let is_new_play = self.play_session.as_ref().is_none_or(|session| {
+            session.track_path != track_path || session.started_at_sec != started_at_sec
+        });
+        if is_new_play {
+            self.play_session = Some(PlaySession::new(track_path.clone(), started_at_sec));
+        }
+        self.publish_now_playing(&state, is_new_play, cx);
+
+        let Some(session) = self.play_session.as_mut() else {
+            return;
+        };
+        session.observe(state.playing, state.position);
+        if session.submitted
+            || !self
+                .scrobble_rules
+                .is_eligible(session.listened_secs, state.duration)
+        {
+            return;
+        }
+        session.submitted = true;
+        let listened_secs = session.listened_secs;
+
+        let Some(track) = self.scrobble_track_for_path(&track_path, state.duration) else {
+            return;
+        };
+        log::info!(
+            "[Scrobble] play eligible: title='{}' listened={:.0}s duration={:?}",
+            track.title,
+            listened_secs,
+            state.duration
+        );
+        self.submit_scrobble_for_track(track, started_at_sec, cx);
+    }
+
+    fn scrobble_track_for_path(&self, path: &str, duration: Option<f64>) -> Option<TrackRow> {
+        if let Some(track) = self.tracks.iter().find(|track| track.file_path == path) {
+            return Some(track.clone());
+        }
+
+        let shared = self
+            .active_shared_playback
+            .as_ref()
+            .filter(|shared| shared.local_path == path)?;
+        let duration_seconds = duration
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(|secs| secs.round() as u64)
+            .unwrap_or(0);
+        Some(TrackRow {
+            id: format!("shared-{}", shared.content_id),
+            title: if shared.title.trim().is_empty() {
+                "Shared Track".to_string()
+            } else {
+                shared.title.clone()
+            },
+            artist: if shared.artist.trim().is_empty() {
+                "Unknown Artist".to_string()
+            } else {
+                shared.artist.clone()
+            },
+            album: shared.album.clone(),
+            duration: format_duration_mmss(duration_seconds),
+            file_path: shared.local_path.clone(),
+            mbid: None,
+            ip_id: None,
+            cover_path: None,
+            storage_status: StorageStatus::default(),
+        })
+    }
+
+    fn publish_now_playing(
+        &self,
+        state: &PlaybackState,
+        track_changed: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let current = cx.global::<NowPlayingState>().current();
+        if !track_changed && current.map(|now| now.playing) == Some(state.playing) {
+            return;
+        }
+
+        let next = state
+            .track_path
+            .as_deref()
+            .and_then(|path| self.track_metadata_for_path(path))
+            .map(|(title, artist, album)| NowPlaying {
+                title,
+                artist,
+                album,
+                playing: state.playing,
+            });
+        if current != next.as_ref() {
+            cx.update_global::<NowPlayingState, _>(|now_playing, _| now_playing.set(next));
+        }
+    }
+}
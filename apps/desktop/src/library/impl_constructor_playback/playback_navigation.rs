@@ -1,4 +1,3 @@
-use super::storage::format_duration_mmss;
 use super::*;
 
 impl LibraryView {
@@ -8,12 +7,7 @@ impl LibraryView {
         if state.track_path.is_some() && !state.playing {
             if let Some(dur) = state.duration {
                 if state.position >= dur - 0.5 && dur > 0.0 {
-                    let played_at_sec = self.track_started_at_sec.unwrap_or_else(now_epoch_sec);
                     if let Some(idx) = self.active_track_index() {
-                        if let Some(track) = self.tracks.get(idx).cloned() {
-                            self.submit_scrobble_for_track(track, played_at_sec, cx);
-                        }
-
                         if self.advance_queue(1, cx) {
                             cx.notify();
                             return;
@@ -29,33 +23,9 @@ impl LibraryView {
                             self.play_track(next, cx);
                             cx.notify();
                         }
-                    } else if let Some(shared) = self.active_shared_playback.take() {
-                        let duration_seconds = if dur.is_finite() && dur > 0.0 {
-                            dur.round() as u64
-                        } else {
-                            0
-                        };
-                        let synthetic_track = TrackRow {
-                            id: format!("shared-{}", shared.content_id),
-                            title: if shared.title.trim().is_empty() {
-                                "Shared Track".to_string()
-                            } else {
-                                shared.title
-                            },
-                            artist: if shared.artist.trim().is_empty() {
-                                "Unknown Artist".to_string()
-                            } else {
-                                shared.artist
-                            },
-                            album: shared.album,
-                            duration: format_duration_mmss(duration_seconds),
-                            file_path: shared.local_path,
-                            mbid: None,
-                            ip_id: None,
-                            cover_path: None,
-                            storage_status: StorageStatus::default(),
-                        };
-                        self.submit_scrobble_for_track(synthetic_track, played_at_sec, cx);
+                    } else {
+                        // Shared plays were scrobbled by `track_playback_progress`.
+                        self.active_shared_playback = None;
                     }
                 }
             }
@@ -0,0 +1,216 @@
+use super::*;
+use crate::scrobble::import::{import_interval_secs, stage_history_file, HistoryImport};
+use crate::scrobble::outbox::{flush_outbox_batch, OutboxFlushStep};
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+impl LibraryView {
+    pub(in crate::library) fn scrobble_import_running(&self) -> bool {
+        self.scrobble_import_cancel.is_some()
+    }
+
+    /// Pick exported history files, stage their new plays and start submitting them.
+    pub(in crate::library) fn import_scrobble_history(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.clone() else {
+            self.set_status_message("History import unavailable: library database not open.", cx);
+            return;
+        };
+        let Some(user_address) =
+            auth::load_from_disk().and_then(|auth| auth.wallet_address().map(str::to_string))
+        else {
+            self.set_status_message("Sign in to import listening history.", cx);
+            return;
+        };
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let picked = smol::unblock(|| {
+                rfd::FileDialog::new()
+                    .set_title("Import Listening History")
+                    .add_filter("Listening history", &["json", "jsonl", "csv"])
+                    .pick_files()
+            })
+            .await;
+            let Some(paths) = picked.filter(|paths| !paths.is_empty()) else {
+                return;
+            };
+
+            let _ = this.update(cx, |this, cx| {
+                this.set_status_message(format!("Reading {} history file(s)...", paths.len()), cx);
+            });
+            let results = smol::unblock(move || {
+                paths
+                    .iter()
+                    .map(|path| stage_history_file(path, &db, &user_address))
+                    .collect::<Vec<_>>()
+            })
+            .await;
+
+            let messages: Vec<String> = results
+                .iter()
+                .map(|result| match result {
+                    Ok(summary) => summary.describe(),
+                    Err(err) => format!("Import failed: {err}"),
+                })
+                .collect();
+            let _ = this.update(cx, |this, cx| {
+                this.set_status_message(messages.join(" "), cx);
+                this.run_scrobble_import(cx);
+            });
+        })
+        .detach();
+    }
+
+    /// Submit staged history batch by batch, pausing between transactions. Safe to call at
+    /// startup: it exits quietly when nothing is pending.
+    pub(in crate::library) fn run_scrobble_import(&mut self, cx: &mut Context<Self>) {
+        if self.scrobble_import_running() {
+            return;
+        }
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(service) = self.scrobble_service.clone() else {
+            return;
+        };
+        let Some(auth) = auth::load_from_disk() else {
+            return;
+        };
+        let Some(user_address) = auth.wallet_address().map(str::to_string) else {
+            return;
+        };
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.scrobble_import_cancel = Some(cancel.clone());
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let mut submitted = 0usize;
+            let mut last_error: Option<String> = None;
+            let mut held: Option<String> = None;
+            let mut awaiting_receipt = false;
+            loop {
+                let count_db = db.clone();
+                let count_user = user_address.clone();
+                let counts = smol::unblock(move || {
+                    count_db
+                        .lock()
+                        .map_err(|e| format!("music db lock failed: {e}"))?
+                        .imported_scrobble_counts(&count_user)
+                })
+                .await;
+                let still_running = this
+                    .update(cx, |this, cx| {
+                        this.scrobble_import_progress = match &counts {
+                            Ok(counts) if counts.pending > 0 => {
+                                let done = counts.confirmed + counts.failed;
+                                Some((done, done + counts.pending))
+                            }
+                            _ => None,
+                        };
+                        cx.notify();
+                    })
+                    .is_ok();
+                if !still_running
+                    || cancel.load(AtomicOrdering::Relaxed)
+                    || !counts.is_ok_and(|counts| counts.pending > 0)
+                {
+                    break;
+                }
+
+                let step_service = service.clone();
+                let step_auth = auth.clone();
+                let step_db = db.clone();
+                let step_user = user_address.clone();
+                let step = smol::unblock(move || {
+                    flush_outbox_batch::<HistoryImport>(
+                        &step_service,
+                        &step_auth,
+                        &step_db,
+                        &step_user,
+                    )
+                })
+                .await;
+                match step {
+                    Ok(OutboxFlushStep::Confirmed { plays, .. }) => submitted += plays.len(),
+                    Ok(OutboxFlushStep::Unconfirmed { tx_hash, plays }) => {
+                        // Wait out its inclusion window so the next step checks the receipt.
+                        log::warn!(
+                            "[ScrobbleImport] awaiting receipt: txHash={} plays={}",
+                            tx_hash,
+                            plays
+                        );
+                        awaiting_receipt = true;
+                    }
+                    Ok(OutboxFlushStep::Failed { error, .. }) => {
+                        // Rescheduled with backoff; the next run picks them up again.
+                        last_error = Some(error);
+                        break;
+                    }
+                    Ok(OutboxFlushStep::Held(reason)) => {
+                        held = Some(reason);
+                        break;
+                    }
+                    Ok(OutboxFlushStep::Idle) => break,
+                    Err(err) => {
+                        last_error = Some(err);
+                        break;
+                    }
+                }
+
+                let pause_secs = if std::mem::take(&mut awaiting_receipt) {
+                    import_interval_secs().max(crate::tempo::inclusion_window_secs() + 1)
+                } else {
+                    import_interval_secs()
+                };
+                let pause = std::time::Duration::from_secs(pause_secs);
+                let mut waited = std::time::Duration::ZERO;
+                while waited < pause && !cancel.load(AtomicOrdering::Relaxed) {
+                    let tick = (pause - waited).min(std::time::Duration::from_secs(1));
+                    smol::Timer::after(tick).await;
+                    waited += tick;
+                }
+            }
+
+            let cancelled = cancel.load(AtomicOrdering::Relaxed);
+            let _ = this.update(cx, |this, cx| {
+                this.scrobble_import_cancel = None;
+                this.scrobble_import_progress = None;
+                if let Some(err) = last_error {
+                    cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                        status.publish_error(
+                            "scrobble-import",
+                            format!("History import paused after {submitted} plays: {err}"),
+                        );
+                    });
+                } else if let Some(reason) = held {
+                    log::warn!("[ScrobbleImport] holding staged plays: {}", reason);
+                } else if cancelled {
+                    this.set_status_message(
+                        format!("History import stopped after {submitted} plays."),
+                        cx,
+                    );
+                } else if submitted > 0 {
+                    cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                        status.dismiss_key("scrobble-import");
+                    });
+                    this.set_status_message(
+                        format!("History import finished: {submitted} plays scrobbled."),
+                        cx,
+                    );
+                    cx.update_global::<crate::scrobble_refresh::ScrobbleRefreshSignal, _>(
+                        |signal, _| {
+                            signal.bump();
+                        },
+                    );
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    pub(in crate::library) fn stop_scrobble_import(&mut self, cx: &mut Context<Self>) {
+        if let Some(cancel) = self.scrobble_import_cancel.as_ref() {
+            cancel.store(true, AtomicOrdering::Relaxed);
+            self.set_status_message("Stopping history import...", cx);
+        }
+    }
+}
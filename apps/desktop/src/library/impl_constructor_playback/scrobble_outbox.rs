@@ -0,0 +1,283 @@
+use super::scrobble_submit::sync_scrobbled_track_media;
+use super::*;
+use crate::scrobble::outbox::{flush_outbox_batch, OutboxFlushStep, PlayOutbox};
+
+impl LibraryView {
+    /// Outbox counts for the signed-in wallet; while signed out, the plays held for the
+    /// next sign-in.
+    pub(in crate::library) fn refresh_scrobble_outbox_counts(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let user_address =
+            auth::load_from_disk().and_then(|auth| auth.wallet_address().map(str::to_string));
+        let counts = db
+            .lock()
+            .map_err(|e| format!("scrobble outbox lock failed: {e}"))
+            .and_then(|db| db.scrobble_outbox_counts(user_address.as_deref()));
+        match counts {
+            Ok(counts) => {
+                if counts != self.scrobble_outbox_counts {
+                    self.scrobble_outbox_counts = counts;
+                    cx.notify();
+                }
+            }
+            Err(err) => log::warn!("[Scrobble] outbox count failed: {}", err),
+        }
+    }
+
+    /// Bind plays recorded while signed out to the wallet that is now signed in. Runs on
+    /// every auth change and at startup; once claimed, a later account switch leaves them be.
+    pub(in crate::library) fn claim_signed_out_scrobbles(&mut self) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let Some(user_address) =
+            auth::load_from_disk().and_then(|auth| auth.wallet_address().map(str::to_string))
+        else {
+            return;
+        };
+        let claimed = db
+            .lock()
+            .map_err(|e| format!("scrobble outbox lock failed: {e}"))
+            .and_then(|db| db.claim_signed_out_scrobbles(&user_address, now_epoch_sec() as i64));
+        match claimed {
+            Ok(0) => {}
+            Ok(count) => log::info!(
+                "[Scrobble] outbox: {} signed-out plays claimed by {}",
+                count,
+                user_address
+            ),
+            Err(err) => log::warn!("[Scrobble] outbox claim failed: {}", err),
+        }
+    }
+
+    /// Submit due outbox plays batch by batch until nothing is due, then sleep until the
+    /// earliest backoff expires. Holds plays untouched while the user is signed out.
+    pub(in crate::library) fn flush_scrobble_outbox(&mut self, cx: &mut Context<Self>) {
+        if self.scrobble_outbox_flushing
+            || !self.scrobble_sink_enabled(crate::scrobble::sinks::ScrobbleSinkKind::Tempo)
+        {
+            return;
+        }
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(service) = self.scrobble_service.clone() else {
+            log::warn!("[Scrobble] outbox idle: scrobble service unavailable");
+            return;
+        };
+        let Some(auth) = auth::load_from_disk() else {
+            if self.scrobble_outbox_counts.pending > 0 {
+                log::info!(
+                    "[Scrobble] outbox holding {} plays: user not authenticated",
+                    self.scrobble_outbox_counts.pending
+                );
+            }
+            return;
+        };
+        let Some(user_address) = auth.wallet_address().map(str::to_string) else {
+            return;
+        };
+
+        self.scrobble_outbox_flushing = true;
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let mut confirmed_any = false;
+            let mut last_error: Option<String> = None;
+            // Held or broken outboxes wait for an auth change instead of polling.
+            let mut rearm = true;
+            loop {
+                let step_service = service.clone();
+                let step_auth = auth.clone();
+                let step_db = db.clone();
+                let step_user = user_address.clone();
+                let step = smol::unblock(move || {
+                    flush_outbox_batch::<PlayOutbox>(&step_service, &step_auth, &step_db, &step_user)
+                })
+                .await;
+
+                match step {
+                    Ok(OutboxFlushStep::Confirmed { tx_hash, plays }) => {
+                        confirmed_any = true;
+                        log::info!(
+                            "[Scrobble] submitted: txHash={} plays={}",
+                            tx_hash,
+                            plays.len()
+                        );
+                        let _ = this.update(cx, |this, cx| {
+                            for (track_id, play) in &plays {
+                                this.enqueue_scrobble_media_pending(
+                                    track_id,
+                                    play.cover_path.as_deref(),
+                                );
+                            }
+                            this.refresh_scrobble_outbox_counts(cx);
+                            log::info!("[Scrobble] refresh signal bump: immediate");
+                            cx.update_global::<crate::scrobble_refresh::ScrobbleRefreshSignal, _>(
+                                |signal, _| {
+                                    signal.bump();
+                                },
+                            );
+                        });
+
+                        let media_service = service.clone();
+                        let media_auth = auth.clone();
+                        let media_db = db.clone();
+                        smol::unblock(move || {
+                            let mut synced = HashSet::new();
+                            for (track_id, play) in &plays {
+                                if synced.insert(track_id.to_ascii_lowercase()) {
+                                    sync_scrobbled_track_media(
+                                        &media_service,
+                                        &media_auth,
+                                        Some(&media_db),
+                                        track_id,
+                                        play,
+                                    );
+                                }
+                            }
+                        })
+                        .await;
+                    }
+                    Ok(OutboxFlushStep::Failed { error, plays }) => {
+                        log::error!("[Scrobble] submit failed: plays={} err={}", plays, error);
+                        last_error = Some(error);
+                        break;
+                    }
+                    Ok(OutboxFlushStep::Unconfirmed { tx_hash, plays }) => {
+                        // Parked until its receipt check is due; the wake below fires then.
+                        log::warn!(
+                            "[Scrobble] awaiting receipt: txHash={} plays={}",
+                            tx_hash,
+                            plays
+                        );
+                        break;
+                    }
+                    Ok(OutboxFlushStep::Held(reason)) => {
+                        log::warn!("[Scrobble] outbox holding plays: {}", reason);
+                        rearm = false;
+                        break;
+                    }
+                    Ok(OutboxFlushStep::Idle) => break,
+                    Err(err) => {
+                        log::error!("[Scrobble] outbox flush failed: {}", err);
+                        last_error = Some(err);
+                        rearm = false;
+                        break;
+                    }
+                }
+            }
+
+            let wake_db = db.clone();
+            let wake_user = user_address.clone();
+            let next_attempt_at = smol::unblock(move || {
+                wake_db
+                    .lock()
+                    .map_err(|e| format!("scrobble outbox lock failed: {e}"))?
+                    .next_scrobble_attempt_at(&wake_user)
+            })
+            .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.scrobble_outbox_flushing = false;
+                this.refresh_scrobble_outbox_counts(cx);
+                if let Some(err) = last_error {
+                    let counts = this.scrobble_outbox_counts;
+                    cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                        status.publish_error(
+                            "scrobble-outbox",
+                            format!(
+                                "Scrobbles not submitted ({} pending, {} failed): {err}",
+                                counts.pending, counts.failed
+                            ),
+                        );
+                    });
+                } else if confirmed_any {
+                    cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                        status.dismiss_key("scrobble-outbox");
+                    });
+                    // Tempo confirmation can lag behind initial broadcast acceptance.
+                    // Schedule a few delayed bumps to catch post-broadcast confirmation.
+                    for delay_ms in [6_000_u64, 20_000_u64, 60_000_u64] {
+                        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                            smol::Timer::after(std::time::Duration::from_millis(delay_ms)).await;
+                            let _ = this.update(cx, |_this, cx| {
+                                log::info!(
+                                    "[Scrobble] refresh signal bump: delayed={}ms",
+                                    delay_ms
+                                );
+                                cx.update_global::<crate::scrobble_refresh::ScrobbleRefreshSignal, _>(
+                                    |signal, _| {
+                                        signal.bump();
+                                    },
+                                );
+                            });
+                        })
+                        .detach();
+                    }
+                }
+                match next_attempt_at {
+                    Ok(Some(at)) if rearm => {
+                        this.schedule_scrobble_outbox_wake(at.max(0) as u64, cx)
+                    }
+                    Ok(_) => {}
+                    Err(err) => log::warn!("[Scrobble] outbox wake lookup failed: {}", err),
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Arm a single timer for the earliest pending retry; a later request never pushes it back.
+    fn schedule_scrobble_outbox_wake(&mut self, at_sec: u64, cx: &mut Context<Self>) {
+        if self
+            .scrobble_outbox_wake_at
+            .is_some_and(|armed| armed <= at_sec)
+        {
+            return;
+        }
+        self.scrobble_outbox_wake_at = Some(at_sec);
+        let delay_secs = at_sec.saturating_sub(now_epoch_sec()).max(1);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            smol::Timer::after(std::time::Duration::from_secs(delay_secs)).await;
+            let _ = this.update(cx, |this, cx| {
+                if this.scrobble_outbox_wake_at == Some(at_sec) {
+                    this.scrobble_outbox_wake_at = None;
+                    this.flush_scrobble_outbox(cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    pub(in crate::library) fn retry_failed_scrobbles(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let user_address =
+            auth::load_from_disk().and_then(|auth| auth.wallet_address().map(str::to_string));
+        let reset = db
+            .lock()
+            .map_err(|e| format!("scrobble outbox lock failed: {e}"))
+            .and_then(|db| {
+                let now = now_epoch_sec() as i64;
+                match user_address.as_deref() {
+                    Some(user_address) => Ok(db.retry_failed_scrobbles(user_address, now)?
+                        + db.retry_failed_sink_deliveries(user_address, now)?),
+                    None => Ok(0),
+                }
+            });
+        match reset {
+            Ok(0) => self.set_status_message("No failed scrobbles to retry.", cx),
+            Ok(count) => {
+                self.set_status_message(format!("Retrying {count} failed scrobbles..."), cx);
+                self.refresh_scrobble_outbox_counts(cx);
+                self.flush_scrobble_outbox(cx);
+                self.flush_scrobble_sinks(cx);
+            }
+            Err(err) => {
+                self.set_status_message(format!("Could not retry scrobbles: {err}"), cx);
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use super::*;
+use crate::scrobble::sinks::{
+    build_external_sink, flush_sink_batch, set_sink_enabled, sink_enabled_since, ScrobbleSinkKind,
+    SinkFlushStep,
+};
+
+impl LibraryView {
+    /// Reload which sinks the signed-in account has switched on; none while signed out.
+    pub(in crate::library) fn refresh_scrobble_sinks_enabled(&mut self) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let Ok(db) = db.lock() else {
+            return;
+        };
+        let Some(user_address) = signed_in_wallet() else {
+            self.scrobble_sinks_enabled.clear();
+            return;
+        };
+        self.scrobble_sinks_enabled = ScrobbleSinkKind::ALL
+            .into_iter()
+            .filter(|kind| sink_enabled_since(&db, *kind, &user_address).is_some())
+            .collect();
+    }
+
+    pub(in crate::library) fn scrobble_sink_enabled(&self, kind: ScrobbleSinkKind) -> bool {
+        self.scrobble_sinks_enabled.contains(&kind)
+    }
+
+    pub(in crate::library) fn toggle_scrobble_sink(
+        &mut self,
+        kind: ScrobbleSinkKind,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(user_address) = signed_in_wallet() else {
+            self.set_status_message("Sign in to choose where your plays are scrobbled.", cx);
+            return;
+        };
+        let enable = !self.scrobble_sink_enabled(kind);
+        let result = db
+            .lock()
+            .map_err(|e| format!("settings lock failed: {e}"))
+            .and_then(|db| {
+                set_sink_enabled(&db, kind, &user_address, enable, now_epoch_sec() as i64)
+            });
+        if let Err(err) = result {
+            self.set_status_message(format!("Could not update {}: {err}", kind.label()), cx);
+            return;
+        }
+        self.refresh_scrobble_sinks_enabled();
+
+        if enable {
+            self.set_status_message(format!("{} scrobbling on for new plays.", kind.label()), cx);
+        } else {
+            self.set_status_message(format!("{} scrobbling paused.", kind.label()), cx);
+            cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                status.dismiss_key(&sink_status_key(kind));
+            });
+        }
+        if kind == ScrobbleSinkKind::Tempo {
+            self.flush_scrobble_outbox(cx);
+        } else {
+            self.flush_scrobble_sinks(cx);
+        }
+    }
+
+    /// Deliver due plays to every enabled non-Tempo sink. Each sink runs its own loop, so a
+    /// dead endpoint only delays that sink.
+    pub(in crate::library) fn flush_scrobble_sinks(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(user_address) = signed_in_wallet() else {
+            return;
+        };
+        let kinds: Vec<ScrobbleSinkKind> = self
+            .scrobble_sinks_enabled
+            .iter()
+            .copied()
+            .filter(|kind| *kind != ScrobbleSinkKind::Tempo)
+            .filter(|kind| !self.scrobble_sinks_flushing.contains(kind))
+            .collect();
+
+        for kind in kinds {
+            self.scrobble_sinks_flushing.insert(kind);
+            let db = db.clone();
+            let user_address = user_address.clone();
+            cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+                let mut delivered = 0_usize;
+                let mut last_error: Option<String> = None;
+                let mut held: Option<String> = None;
+                loop {
+                    let step_db = db.clone();
+                    let step_user = user_address.clone();
+                    let step = smol::unblock(move || {
+                        let (sink, enabled_since) = {
+                            let guard = step_db
+                                .lock()
+                                .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+                            let Some(enabled_since) = sink_enabled_since(&guard, kind, &step_user)
+                            else {
+                                return Ok(SinkFlushStep::Idle);
+                            };
+                            (build_external_sink(&guard, kind, &step_user), enabled_since)
+                        };
+                        let Some(mut sink) = sink else {
+                            return Ok(SinkFlushStep::Idle);
+                        };
+                        flush_sink_batch(sink.as_mut(), &step_db, &step_user, enabled_since)
+                    })
+                    .await;
+
+                    match step {
+                        Ok(SinkFlushStep::Delivered(count)) => delivered += count,
+                        Ok(SinkFlushStep::Idle) => break,
+                        Ok(SinkFlushStep::Held(reason)) => {
+                            log::warn!(
+                                "[Scrobble] sink={} holding plays: {}",
+                                kind.as_str(),
+                                reason
+                            );
+                            held = Some(reason);
+                            break;
+                        }
+                        Ok(SinkFlushStep::Failed { error, .. }) | Err(error) => {
+                            last_error = Some(error);
+                            break;
+                        }
+                    }
+                }
+
+                let wake_db = db.clone();
+                let next_attempt_at = smol::unblock(move || {
+                    let guard = wake_db
+                        .lock()
+                        .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+                    match sink_enabled_since(&guard, kind, &user_address) {
+                        Some(since) => {
+                            guard.next_sink_attempt_at(kind.as_str(), &user_address, since)
+                        }
+                        None => Ok(None),
+                    }
+                })
+                .await;
+
+                let _ = this.update(cx, |this, cx| {
+                    this.scrobble_sinks_flushing.remove(&kind);
+                    let key = sink_status_key(kind);
+                    if let Some(err) = last_error.or(held.clone()) {
+                        cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                            status.publish_error(
+                                key,
+                                format!("{} scrobbles not delivered: {err}", kind.label()),
+                            );
+                        });
+                    } else if delivered > 0 {
+                        log::info!(
+                            "[Scrobble] sink={} delivered total={}",
+                            kind.as_str(),
+                            delivered
+                        );
+                        cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                            status.dismiss_key(&key);
+                        });
+                    }
+                    // A held sink waits for the user to fix its settings instead of polling.
+                    match next_attempt_at {
+                        Ok(Some(at)) if held.is_none() => {
+                            this.schedule_scrobble_sinks_wake(at.max(0) as u64, cx)
+                        }
+                        Ok(_) => {}
+                        Err(err) => log::warn!("[Scrobble] sink wake lookup failed: {}", err),
+                    }
+                });
+            })
+            .detach();
+        }
+    }
+
+    fn schedule_scrobble_sinks_wake(&mut self, at_sec: u64, cx: &mut Context<Self>) {
+        if self
+            .scrobble_sinks_wake_at
+            .is_some_and(|armed| armed <= at_sec)
+        {
+            return;
+        }
+        self.scrobble_sinks_wake_at = Some(at_sec);
+        let delay_secs = at_sec.saturating_sub(now_epoch_sec()).max(1);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            smol::Timer::after(std::time::Duration::from_secs(delay_secs)).await;
+            let _ = this.update(cx, |this, cx| {
+                if this.scrobble_sinks_wake_at == Some(at_sec) {
+                    this.scrobble_sinks_wake_at = None;
+                    this.flush_scrobble_sinks(cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// One-line delivery summary per enabled sink, shown in the status bar.
+    pub(in crate::library) fn report_scrobble_sinks(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let Ok(db) = db.lock() else {
+            return;
+        };
+        let Some(user_address) = signed_in_wallet() else {
+            drop(db);
+            self.set_status_message("Sign in to see scrobble delivery.", cx);
+            return;
+        };
+        let mut parts = Vec::new();
+        for kind in ScrobbleSinkKind::ALL {
+            let Some(since) = sink_enabled_since(&db, kind, &user_address) else {
+                continue;
+            };
+            let counts = if kind == ScrobbleSinkKind::Tempo {
+                db.scrobble_outbox_counts(Some(&user_address))
+            } else {
+                db.sink_delivery_counts(kind.as_str(), &user_address, since)
+            };
+            let mut part = match counts {
+                Ok(counts) => format!(
+                    "{}: {} pending · {} sent · {} failed",
+                    kind.label(),
+                    counts.pending,
+                    counts.confirmed,
+                    counts.failed
+                ),
+                Err(err) => format!("{}: {err}", kind.label()),
+            };
+            if kind != ScrobbleSinkKind::Tempo {
+                if let Ok(Some(err)) = db.last_sink_error(kind.as_str(), &user_address) {
+                    part.push_str(&format!(" (last error: {err})"));
+                }
+            }
+            parts.push(part);
+        }
+        drop(db);
+
+        if parts.is_empty() {
+            self.set_status_message("All scrobble destinations are off.", cx);
+        } else {
+            self.set_status_message(parts.join(" | "), cx);
+        }
+    }
+}
+
+fn signed_in_wallet() -> Option<String> {
+    auth::load_from_disk().and_then(|auth| auth.wallet_address().map(str::to_string))
+}
+
+fn sink_status_key(kind: ScrobbleSinkKind) -> String {
+    format!("scrobble-sink-{}", kind.as_str())
+}
@@ -24,7 +24,8 @@ impl LibraryView {
                     let rank = |status: StorageStatus| match status {
                         StorageStatus::Permanent => 0_u8,
                         StorageStatus::Uploaded => 1_u8,
-                        StorageStatus::Local => 2_u8,
+                        StorageStatus::Missing => 2_u8,
+                        StorageStatus::Local => 3_u8,
                     };
                     rank(track_a.storage_status).cmp(&rank(track_b.storage_status))
                 }
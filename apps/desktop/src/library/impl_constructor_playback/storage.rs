@@ -20,18 +20,10 @@ impl LibraryView {
             let _ = this.update(cx, |this, cx| {
                 this.storage_loading = false;
                 match result {
-                    Ok(val) => {
-                        this.storage_balance = Some(
-                            val.get("balance")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("0")
-                                .to_string(),
-                        );
-                        this.storage_monthly = val
-                            .get("monthlyCost")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        this.storage_days = val.get("daysRemaining").and_then(|v| v.as_i64());
+                    Ok(status) => {
+                        this.storage_balance = Some(status.balance);
+                        this.storage_monthly = status.monthly_cost;
+                        this.storage_days = status.days_remaining;
                     }
                     Err(e) => {
                         this.storage_balance = Some("0".to_string());
@@ -42,7 +34,10 @@ impl LibraryView {
                             .is_some_and(|msg| msg.contains("Refreshing storage status"))
                         {
                             this.set_status_message(
-                                format!("Funding submitted, but balance refresh failed: {}", e),
+                                format!(
+                                    "Funding submitted, but balance refresh failed: {}",
+                                    e.user_message()
+                                ),
                                 cx,
                             );
                         }
@@ -72,31 +67,20 @@ impl LibraryView {
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 let mut svc = storage.lock().map_err(|e| format!("lock: {e}"))?;
-                svc.storage_deposit_and_approve(&auth, "0.0001")
+                svc.storage_deposit_and_approve(&auth)
             })
             .await;
 
             let _ = this.update(cx, |this, cx| {
                 this.add_funds_busy = false;
                 match result {
-                    Ok(val) => {
-                        let funding_enabled = val
-                            .get("turboFundingEnabled")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(true);
-                        if !funding_enabled {
-                            let message = val
-                                .get("message")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("Add Credits is disabled in offchain mode.");
-                            log::info!("[Library] add_funds skipped: {}", message);
-                            this.set_status_message(message.to_string(), cx);
+                    Ok(outcome) => {
+                        if !outcome.funding_enabled {
+                            log::info!("[Library] add_funds skipped: {}", outcome.message);
+                            this.set_status_message(outcome.message, cx);
                             return;
                         }
-                        let tx_hash = val
-                            .get("txHash")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown");
+                        let tx_hash = outcome.tx_hash.as_deref().unwrap_or("unknown");
                         log::info!(
                             "[Library] storage funding flow complete: txHash={}",
                             tx_hash
@@ -109,7 +93,10 @@ impl LibraryView {
                     }
                     Err(e) => {
                         log::error!("[Library] storage funding flow failed: {}", e);
-                        this.set_status_message(format!("Funding failed: {}", e), cx);
+                        this.set_status_message(
+                            format!("Funding failed: {}", e.user_message()),
+                            cx,
+                        );
                     }
                 }
             });
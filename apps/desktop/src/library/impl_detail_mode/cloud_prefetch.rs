@@ -23,10 +23,11 @@ impl LibraryView {
         let request_seq = self.detail_fetch_seq;
         let artist_for_fetch = artist.clone();
         let tracks_snapshot = self.tracks.clone();
+        let db = self.db.clone();
 
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
-                fetch_artist_cloud_stats(&artist_for_fetch, tracks_snapshot.as_ref())
+                fetch_artist_cloud_stats(db.as_deref(), &artist_for_fetch, tracks_snapshot.as_ref())
             })
             .await;
 
@@ -78,10 +79,12 @@ impl LibraryView {
         let artist_for_fetch = artist.clone();
         let album_for_fetch = album.clone();
         let tracks_snapshot = self.tracks.clone();
+        let db = self.db.clone();
 
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 fetch_album_cloud_stats(
+                    db.as_deref(),
                     &artist_for_fetch,
                     &album_for_fetch,
                     tracks_snapshot.as_ref(),
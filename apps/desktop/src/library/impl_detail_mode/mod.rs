@@ -6,3 +6,5 @@ mod navigation;
 mod playlist_cover;
 mod playlist_delete;
 mod playlist_detail;
+
+pub(in crate::library) use mode_helpers::lyrics_signature_for_track;
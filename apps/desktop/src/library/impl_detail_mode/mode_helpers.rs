@@ -69,14 +69,7 @@ impl LibraryView {
         duration_hint_sec: Option<u64>,
     ) -> Option<LyricsTrackSignature> {
         if let Some(track) = self.tracks.iter().find(|track| track.file_path == path) {
-            return Some(LyricsTrackSignature {
-                track_path: track.file_path.clone(),
-                track_name: track.title.clone(),
-                artist_name: track.artist.clone(),
-                album_name: track.album.clone(),
-                duration_sec: parse_duration_label_to_seconds(&track.duration)
-                    .or(duration_hint_sec),
-            });
+            return Some(lyrics_signature_for_track(track, duration_hint_sec));
         }
 
         self.active_shared_playback
@@ -88,6 +81,30 @@ impl LibraryView {
                 artist_name: shared.artist.clone(),
                 album_name: shared.album.clone(),
                 duration_sec: duration_hint_sec,
+                track_id: None,
             })
     }
 }
+
+pub(in crate::library) fn lyrics_signature_for_track(
+    track: &TrackRow,
+    duration_hint_sec: Option<u64>,
+) -> LyricsTrackSignature {
+    LyricsTrackSignature {
+        track_path: track.file_path.clone(),
+        track_name: track.title.clone(),
+        artist_name: track.artist.clone(),
+        album_name: track.album.clone(),
+        duration_sec: parse_duration_label_to_seconds(&track.duration).or(duration_hint_sec),
+        track_id: crate::scrobble::derive_track_id(&crate::scrobble::SubmitScrobbleInput {
+            artist: track.artist.clone(),
+            title: track.title.clone(),
+            album: Some(track.album.clone()).filter(|album| !album.trim().is_empty()),
+            mbid: track.mbid.clone(),
+            ip_id: track.ip_id.clone(),
+            duration_sec: 0,
+            played_at_sec: 0,
+        })
+        .ok(),
+    }
+}
@@ -95,14 +95,7 @@ impl LibraryView {
                                 visibility,
                                 None,
                             )?;
-                            let returned_cover = payload
-                                .get("coverCid")
-                                .and_then(Value::as_str)
-                                .map(str::trim)
-                                .filter(|v| !v.is_empty())
-                                .map(str::to_string)
-                                .unwrap_or(cover_ref);
-                            Ok(returned_cover)
+                            Ok(payload.cover_cid.unwrap_or(cover_ref))
                         }
                     })
                     .await
@@ -113,6 +113,8 @@ impl LibraryView {
             }
             let status = if record.saved_forever {
                 StorageStatus::Permanent
+            } else if record.sync_state == RecordSyncState::Drifted {
+                StorageStatus::Missing
             } else {
                 StorageStatus::Uploaded
             };
@@ -151,36 +153,24 @@ impl LibraryView {
                     Ok(raw_tracks) => {
                         let mut parsed_tracks = Vec::<PlaylistDetailTrack>::new();
                         for row in raw_tracks {
-                            let Some(track_id) = row.get("trackId").and_then(Value::as_str) else {
-                                continue;
-                            };
-                            let track_id = track_id.trim().to_lowercase();
+                            let track_id = row.track_id.trim().to_lowercase();
                             if track_id.is_empty() {
                                 continue;
                             }
-                            let title = row
-                                .get("title")
-                                .and_then(Value::as_str)
-                                .map(str::trim)
+                            let title = Some(row.title.trim())
                                 .filter(|v| !v.is_empty())
                                 .map(str::to_string)
                                 .unwrap_or_else(|| {
                                     format!("Track {}", abbreviate_for_status(&track_id))
                                 });
-                            let artist = row
-                                .get("artist")
-                                .and_then(Value::as_str)
-                                .map(str::trim)
+                            let artist = Some(row.artist.trim())
                                 .filter(|v| !v.is_empty())
-                                .map(str::to_string)
-                                .unwrap_or_else(|| "Unknown Artist".to_string());
-                            let album = row
-                                .get("album")
-                                .and_then(Value::as_str)
-                                .map(str::trim)
+                                .unwrap_or("Unknown Artist")
+                                .to_string();
+                            let album = Some(row.album.trim())
                                 .filter(|v| !v.is_empty())
-                                .map(str::to_string)
-                                .unwrap_or_else(|| "Unknown Album".to_string());
+                                .unwrap_or("Unknown Album")
+                                .to_string();
                             let lookup_key = format!(
                                 "{}\n{}\n{}",
                                 normalize_lookup_key(&title),
@@ -302,9 +292,9 @@ impl LibraryView {
                             "[Library] playlist detail fetch failed: id={}, hasCached={}, err={}",
                             abbreviate_for_status(&playlist_id),
                             has_cached,
-                            summarize_status_error(&err)
+                            summarize_status_error(err.message())
                         );
-                        this.detail_error = Some(err);
+                        this.detail_error = Some(err.user_message());
                     }
                 }
                 cx.notify();
@@ -0,0 +1,131 @@
+use super::*;
+
+impl LibraryView {
+    pub(in crate::library) fn render_archive_confirm_modal(
+        &self,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let Some(confirmation) = self.archive_confirmation.as_ref() else {
+            return div().into_any_element();
+        };
+        let (mut uploads, mut bytes, mut credit) = (0usize, 0u64, 0.0);
+        let (mut posted, mut failed) = (0usize, 0usize);
+        for priced in &confirmation.tracks {
+            match &priced.step {
+                Ok(ArchiveStep::Planned(plan)) => {
+                    uploads += 1;
+                    bytes += plan.bytes;
+                    credit += plan.estimated_credit;
+                }
+                Ok(ArchiveStep::Posted(_)) => posted += 1,
+                Err(_) => failed += 1,
+            }
+        }
+        let mut notes = Vec::new();
+        if posted > 0 {
+            notes.push(format!(
+                "{posted} already on Arweave will be re-checked at no cost."
+            ));
+        }
+        if failed > 0 {
+            notes.push(format!("{failed} could not be priced and will be skipped."));
+        }
+
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(hsla(0., 0., 0., 0.55))
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .relative()
+                    .w(px(540.))
+                    .max_w(px(660.))
+                    .mx_4()
+                    .rounded(px(14.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .v_flex()
+                    .gap_3()
+                    .p_4()
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(FontWeight::BOLD)
+                            .text_color(TEXT_PRIMARY())
+                            .child("Save Forever"),
+                    )
+                    .child(div().text_base().text_color(TEXT_MUTED()).child(format!(
+                        "Saving {} forever uploads {} {} ({:.1} MB) to Arweave.",
+                        confirmation.label,
+                        uploads,
+                        if uploads == 1 { "track" } else { "tracks" },
+                        bytes as f64 / (1024.0 * 1024.0)
+                    )))
+                    .child(div().text_base().text_color(TEXT_PRIMARY()).child(format!(
+                        "Estimated cost: ~{credit:.6} Turbo credits, paid from your session \
+                         key's balance."
+                    )))
+                    .children(
+                        notes
+                            .into_iter()
+                            .map(|note| div().text_base().text_color(TEXT_MUTED()).child(note)),
+                    )
+                    .child(
+                        div()
+                            .text_base()
+                            .text_color(TEXT_AMBER)
+                            .child("Arweave storage is permanent and cannot be refunded."),
+                    )
+                    .child(
+                        div()
+                            .h_flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .id("archive-confirm-cancel-btn")
+                                    .px_4()
+                                    .h(px(34.))
+                                    .rounded_full()
+                                    .bg(BG_HOVER())
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.cancel_save_forever(cx);
+                                    }))
+                                    .child(div().text_color(TEXT_PRIMARY()).child("Cancel")),
+                            )
+                            .child(
+                                div()
+                                    .id("archive-confirm-submit-btn")
+                                    .px_4()
+                                    .h(px(34.))
+                                    .rounded_full()
+                                    .bg(ACCENT_BLUE())
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.confirm_save_forever(cx);
+                                    }))
+                                    .child(
+                                        div()
+                                            .text_color(hsla(0., 0., 0.09, 1.))
+                                            .child("Save Forever"),
+                                    ),
+                            ),
+                    ),
+            )
+            .into_any_element()
+    }
+}
@@ -1,6 +1,8 @@
 use super::*;
 
+mod archive_confirm_modal;
 mod delete_playlist_modal;
 mod playlist_modal;
 mod playlist_share_modal;
 mod share_modal;
+mod transfers_panel;
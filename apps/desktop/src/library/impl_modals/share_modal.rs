@@ -63,6 +63,15 @@ impl LibraryView {
                                 ),
                             ),
                     )
+                    .when(!self.share_modal_grantees.is_empty(), |el| {
+                        el.child(self.render_share_modal_grantees(cx))
+                    })
+                    .when_some(
+                        self.share_modal_pending_revocation.clone(),
+                        |el: Div, row| {
+                            el.child(self.render_share_modal_pending_revocation(row, cx))
+                        },
+                    )
                     .when_some(self.share_modal_error.clone(), |el: Div, err| {
                         el.child(div().text_color(hsla(0., 0.7, 0.6, 1.)).child(err))
                     })
@@ -112,4 +121,122 @@ impl LibraryView {
                     ),
             )
     }
+
+    /// Notice for a revoke whose new key is not yet shared with everyone, with a Retry button.
+    fn render_share_modal_pending_revocation(
+        &self,
+        row: PendingRevocationRow,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let publishing = self.revocations_publishing.contains(&row.content_id);
+        let detail = if publishing {
+            "Sharing the new key with the remaining grantees...".to_string()
+        } else {
+            match row.last_error.as_deref() {
+                Some(err) => format!("The last revoke did not finish: {err}"),
+                None => "The last revoke did not finish.".to_string(),
+            }
+        };
+        div()
+            .h_flex()
+            .items_center()
+            .gap_2()
+            .child(
+                div()
+                    .flex_1()
+                    .text_sm()
+                    .text_color(TEXT_MUTED())
+                    .child(detail),
+            )
+            .when(!publishing, |el| {
+                el.child(
+                    div()
+                        .id("share-retry-revoke-btn")
+                        .px_3()
+                        .h(px(28.))
+                        .rounded_full()
+                        .bg(BG_HOVER())
+                        .cursor_pointer()
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .on_click(cx.listener(|this, _, _window, cx| {
+                            this.retry_pending_revocation(cx);
+                        }))
+                        .child(div().text_sm().text_color(TEXT_PRIMARY()).child("Retry")),
+                )
+            })
+    }
+
+    /// Current grantees of the shared track, each with a Revoke button.
+    fn render_share_modal_grantees(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .share_modal_grantees
+            .iter()
+            .map(|record| {
+                let grantee = record.grantee_address.clone();
+                let revoking = self
+                    .share_modal_revoking
+                    .as_deref()
+                    .is_some_and(|address| address.eq_ignore_ascii_case(&grantee));
+                let shared_on = chrono::DateTime::from_timestamp_millis(record.shared_at_ms)
+                    .map(|at| format!("shared {}", at.format("%Y-%m-%d")))
+                    .unwrap_or_default();
+                div()
+                    .h_flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .text_color(TEXT_PRIMARY())
+                            .child(abbreviate_for_status(&grantee)),
+                    )
+                    .child(div().text_sm().text_color(TEXT_MUTED()).child(shared_on))
+                    .child(
+                        div()
+                            .id(ElementId::Name(format!("share-revoke-{grantee}").into()))
+                            .px_3()
+                            .h(px(28.))
+                            .rounded_full()
+                            .bg(BG_HOVER())
+                            .cursor_pointer()
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.revoke_track_access(grantee.clone(), cx);
+                            }))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(TEXT_PRIMARY())
+                                    .child(if revoking { "Revoking..." } else { "Revoke" }),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .text_sm()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .text_color(TEXT_SECONDARY())
+                    .child("People with access"),
+            )
+            .children(rows)
+            .when(self.share_modal_revoking.is_some(), |el| {
+                el.child(
+                    div()
+                        .text_sm()
+                        .text_color(TEXT_MUTED())
+                        .child("Re-encrypting and re-uploading the track with a new key..."),
+                )
+            })
+    }
 }
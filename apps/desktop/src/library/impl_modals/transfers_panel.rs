@@ -0,0 +1,249 @@
+use super::*;
+use crate::music_db::{UploadJobRow, UploadJobStatus};
+
+fn transfer_action_button(
+    id: SharedString,
+    label: &'static str,
+    on_click: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(ElementId::Name(id))
+        .px_3()
+        .h(px(28.))
+        .rounded_full()
+        .bg(BG_HOVER())
+        .cursor_pointer()
+        .flex()
+        .items_center()
+        .justify_center()
+        .on_click(on_click)
+        .child(div().text_sm().text_color(TEXT_PRIMARY()).child(label))
+}
+
+impl LibraryView {
+    pub(in crate::library) fn render_transfers_panel(
+        &self,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (estimated_total, unpriced) = self.pending_upload_cost();
+        let pending = self
+            .upload_jobs
+            .iter()
+            .filter(|job| job.status.is_active())
+            .count();
+        let cost_line = if pending == 0 {
+            "No pending uploads.".to_string()
+        } else if unpriced > 0 {
+            format!(
+                "Estimated cost for {pending} pending uploads: {estimated_total:.8} credits ({unpriced} not priced yet)"
+            )
+        } else {
+            format!("Estimated cost for {pending} pending uploads: {estimated_total:.8} credits")
+        };
+        let balance_line = format!(
+            "Turbo balance: {}",
+            self.storage_balance.as_deref().unwrap_or("...")
+        );
+        let short_of_credit = self
+            .storage_balance
+            .as_deref()
+            .and_then(|balance| balance.parse::<f64>().ok())
+            .is_some_and(|balance| balance < estimated_total);
+
+        let rows = self
+            .upload_jobs
+            .iter()
+            .rev()
+            .map(|job| self.render_transfer_row(job, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .bg(hsla(0., 0., 0., 0.55))
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .relative()
+                    .w(px(640.))
+                    .max_w(px(760.))
+                    .mx_4()
+                    .rounded(px(14.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .v_flex()
+                    .gap_3()
+                    .p_4()
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(FontWeight::BOLD)
+                            .text_color(TEXT_PRIMARY())
+                            .child("Transfers"),
+                    )
+                    .child(div().text_base().text_color(TEXT_MUTED()).child(cost_line))
+                    .child(
+                        div()
+                            .text_base()
+                            .text_color(if short_of_credit {
+                                TEXT_AMBER
+                            } else {
+                                TEXT_MUTED()
+                            })
+                            .child(if short_of_credit {
+                                format!("{balance_line} — add credits before these uploads run.")
+                            } else {
+                                balance_line
+                            }),
+                    )
+                    .child(
+                        div()
+                            .id("transfers-list")
+                            .v_flex()
+                            .gap_2()
+                            .max_h(px(420.))
+                            .overflow_y_scroll()
+                            .when(rows.is_empty(), |el| {
+                                el.child(
+                                    div()
+                                        .text_base()
+                                        .text_color(TEXT_MUTED())
+                                        .child("Nothing queued. Use Upload on a track or album."),
+                                )
+                            })
+                            .children(rows),
+                    )
+                    .child(
+                        div()
+                            .h_flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .id("transfers-clear-btn")
+                                    .px_4()
+                                    .h(px(34.))
+                                    .rounded_full()
+                                    .bg(BG_HOVER())
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.clear_finished_upload_jobs(cx);
+                                    }))
+                                    .child(
+                                        div().text_color(TEXT_PRIMARY()).child("Clear finished"),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .id("transfers-close-btn")
+                                    .px_4()
+                                    .h(px(34.))
+                                    .rounded_full()
+                                    .bg(TEXT_PRIMARY())
+                                    .cursor_pointer()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.close_transfers_panel(cx);
+                                    }))
+                                    .child(div().text_color(hsla(0., 0., 0.09, 1.)).child("Close")),
+                            ),
+                    ),
+            )
+    }
+
+    fn render_transfer_row(&self, job: &UploadJobRow, cx: &mut Context<Self>) -> impl IntoElement {
+        let id = job.id;
+        let status_line = self.upload_job_status_line(job);
+        let failed = job.status == UploadJobStatus::Failed;
+        let mut actions = div().h_flex().gap_2().flex_none();
+        match job.status {
+            UploadJobStatus::Queued | UploadJobStatus::Running => {
+                actions = actions.child(transfer_action_button(
+                    format!("transfer-pause-{id}").into(),
+                    "Pause",
+                    cx.listener(move |this, _, _w, cx| this.pause_upload_job(id, cx)),
+                ));
+            }
+            UploadJobStatus::Paused => {
+                actions = actions.child(transfer_action_button(
+                    format!("transfer-resume-{id}").into(),
+                    "Resume",
+                    cx.listener(move |this, _, _w, cx| this.resume_upload_job(id, cx)),
+                ));
+            }
+            UploadJobStatus::Failed | UploadJobStatus::Cancelled => {
+                actions = actions.child(transfer_action_button(
+                    format!("transfer-retry-{id}").into(),
+                    "Retry",
+                    cx.listener(move |this, _, _w, cx| this.resume_upload_job(id, cx)),
+                ));
+            }
+            UploadJobStatus::Done => {}
+        }
+        if job.status.is_active() {
+            actions = actions.child(transfer_action_button(
+                format!("transfer-cancel-{id}").into(),
+                "Cancel",
+                cx.listener(move |this, _, _w, cx| this.cancel_upload_job(id, cx)),
+            ));
+        }
+
+        div()
+            .h_flex()
+            .items_center()
+            .gap_3()
+            .px_3()
+            .py_2()
+            .rounded(px(8.))
+            .bg(BG_HOVER())
+            .child(
+                div()
+                    .v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap(px(2.))
+                    .child(
+                        div()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(TEXT_PRIMARY())
+                            .truncate()
+                            .child(format!("{} — {}", job.track.title, job.track.artist)),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(if failed {
+                                hsla(0., 0.7, 0.6, 1.)
+                            } else {
+                                TEXT_MUTED()
+                            })
+                            .truncate()
+                            .child(status_line),
+                    ),
+            )
+            .when_some(
+                job.estimated_credit.filter(|_| job.status.is_active()),
+                |el, credit| {
+                    el.child(
+                        div()
+                            .flex_none()
+                            .text_sm()
+                            .text_color(TEXT_MUTED())
+                            .child(format!("~{credit:.8}")),
+                    )
+                },
+            )
+            .child(actions)
+    }
+}
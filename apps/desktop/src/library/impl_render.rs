@@ -131,6 +131,12 @@ impl Render for LibraryView {
                 .when(self.delete_playlist_modal_open, |el| {
                     el.child(self.render_delete_playlist_modal(cx))
                 })
+                .when(self.archive_confirmation.is_some(), |el| {
+                    el.child(self.render_archive_confirm_modal(cx))
+                })
+                .when(self.transfers_panel_open, |el| {
+                    el.child(self.render_transfers_panel(cx))
+                })
                 .into_any_element();
         }
 
@@ -154,6 +160,14 @@ impl Render for LibraryView {
         let storage_days = self.storage_days;
         let storage_loading = self.storage_loading;
         let add_funds_busy = self.add_funds_busy;
+        let lyrics_prefetch_progress = self.lyrics_prefetch_progress;
+        let scrobble_import_progress = self.scrobble_import_progress;
+        let scrobble_outbox_counts = self.scrobble_outbox_counts;
+        let upload_queue_counts = self.upload_queue_counts();
+        let scrobble_sinks: Vec<(ScrobbleSinkKind, bool)> = ScrobbleSinkKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.scrobble_sink_enabled(kind)))
+            .collect();
         let sort_state = self.sort_state;
         let search_query = self.search_query.clone();
         let filtered_count = self.filtered_indices.len();
@@ -178,6 +192,11 @@ impl Render for LibraryView {
                 storage_days,
                 storage_loading,
                 add_funds_busy,
+                lyrics_prefetch_progress,
+                scrobble_import_progress,
+                scrobble_outbox_counts,
+                upload_queue_counts,
+                scrobble_sinks,
                 cx,
             ))
             .child(div().px_6().py_2().child(render_library_search_bar(
@@ -258,6 +277,12 @@ impl Render for LibraryView {
             .when(self.delete_playlist_modal_open, |el| {
                 el.child(self.render_delete_playlist_modal(cx))
             })
+            .when(self.archive_confirmation.is_some(), |el| {
+                el.child(self.render_archive_confirm_modal(cx))
+            })
+            .when(self.transfers_panel_open, |el| {
+                el.child(self.render_transfers_panel(cx))
+            })
             .into_any_element()
     }
 }
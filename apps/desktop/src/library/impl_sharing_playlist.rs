@@ -3,6 +3,7 @@ use super::*;
 mod playlist_cover_picker;
 mod playlist_modal;
 mod playlist_share;
+mod record_sync;
 mod share_and_upload;
 mod shared_playback;
 mod state_and_shared_refresh;
@@ -1,11 +1,12 @@
 use super::*;
+use crate::load_storage::PlaylistAction;
 
 const PLAYLIST_PENDING_STALE_AFTER_MS: i64 = 90_000;
 
 enum PlaylistMutationResult {
     Mutated {
         playlist_name: String,
-        payload: Value,
+        payload: PlaylistAction,
         cover_warning: Option<String>,
         cover_cid: Option<String>,
     },
@@ -22,30 +23,11 @@ mod sidebar;
 mod submit;
 mod submit_task;
 
-fn extract_playlist_id_from_payload(payload: &Value) -> Option<String> {
-    let candidates = [
-        payload.get("playlistId").and_then(Value::as_str),
-        payload.get("id").and_then(Value::as_str),
-        payload
-            .get("playlist")
-            .and_then(Value::as_object)
-            .and_then(|playlist| playlist.get("id"))
-            .and_then(Value::as_str),
-        payload
-            .pointer("/result/playlistId")
-            .and_then(Value::as_str),
-        payload.pointer("/result/id").and_then(Value::as_str),
-        payload.pointer("/data/playlistId").and_then(Value::as_str),
-        payload.pointer("/data/id").and_then(Value::as_str),
-    ];
-
-    for id in candidates.into_iter().flatten() {
-        if let Some(normalized) = normalize_playlist_id(id) {
-            return Some(normalized);
-        }
-    }
-
-    None
+fn extract_playlist_id_from_payload(payload: &PlaylistAction) -> Option<String> {
+    payload
+        .playlist_id
+        .as_deref()
+        .and_then(normalize_playlist_id)
 }
 
 fn normalize_playlist_id(raw: &str) -> Option<String> {
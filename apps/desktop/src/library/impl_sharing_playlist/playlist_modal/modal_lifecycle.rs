@@ -64,8 +64,7 @@ impl LibraryView {
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
-                let raw = svc.playlist_fetch_user_playlists(&owner, 100)?;
-                Ok::<Vec<PlaylistSummary>, String>(parse_playlist_summaries(&raw))
+                svc.playlist_fetch_user_playlists(&owner, 100)
             })
             .await;
 
@@ -89,7 +88,7 @@ impl LibraryView {
                         this.playlist_modal_playlists = playlists;
                     }
                     Err(err) => {
-                        this.playlist_modal_error = Some(err);
+                        this.playlist_modal_error = Some(err.user_message());
                         this.refresh_local_playlists_with_pending(now_ms);
                     }
                 }
@@ -24,8 +24,7 @@ impl LibraryView {
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
-                let raw = svc.playlist_fetch_user_playlists(&owner, 100)?;
-                Ok::<Vec<PlaylistSummary>, String>(parse_playlist_summaries(&raw))
+                svc.playlist_fetch_user_playlists(&owner, 100)
             })
             .await;
 
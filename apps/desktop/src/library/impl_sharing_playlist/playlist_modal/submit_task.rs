@@ -111,25 +111,19 @@ impl LibraryView {
                                     None,
                                 ) {
                                     Ok(payload) => {
-                                        cover_cid = payload
-                                            .get("coverCid")
-                                            .and_then(Value::as_str)
-                                            .map(str::trim)
-                                            .filter(|v| !v.is_empty())
-                                            .map(str::to_string)
-                                            .or_else(|| Some(cover_ref));
+                                        cover_cid = payload.cover_cid.or(Some(cover_ref));
                                     }
                                     Err(err) => {
                                         cover_warning = Some(format!(
                                             "Cover update failed: {}",
-                                            summarize_status_error(&err)
+                                            err.user_message()
                                         ));
                                     }
                                 },
                                 Err(err) => {
                                     cover_warning = Some(format!(
                                         "Cover upload failed: {}",
-                                        summarize_status_error(&err)
+                                        err.user_message()
                                     ));
                                 }
                             }
@@ -25,15 +25,6 @@ struct PlaylistShareResolvedTrack {
     gateway_url: String,
 }
 
-fn extract_field_string(payload: &Value, key: &str) -> Option<String> {
-    payload
-        .get(key)
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|v| !v.is_empty() && *v != "n/a")
-        .map(str::to_string)
-}
-
 impl LibraryView {
     pub(in crate::library) fn open_playlist_share_modal(
         &mut self,
@@ -47,28 +47,20 @@ pub(super) async fn grant_access_in_chunks(
 
         match grant_result {
             Ok(resp) => {
-                let tx_hash = resp
-                    .get("txHash")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| {
-                        resp.get("envelopeIds")
-                            .and_then(|v| v.as_array())
-                            .and_then(|arr| arr.first())
-                            .and_then(|v| v.as_str())
-                    })
-                    .unwrap_or("n/a");
-                let mirror_tx_hash = resp
-                    .get("mirrorTxHash")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("n/a");
                 let now_ms = chrono::Utc::now().timestamp_millis();
 
                 let owner_address = owner_address.clone();
                 let grantee_hex = grantee_hex.clone();
-                let tx_hash = tx_hash.to_string();
-                let mirror_tx_hash = mirror_tx_hash.to_string();
                 let _ = this.update(cx, |this, cx| {
                     for track in &chunk_tracks {
+                        // Envelope ids line up with the deduped content ids of the batch.
+                        let envelope_id = resp
+                            .content_ids
+                            .iter()
+                            .position(|id| id.eq_ignore_ascii_case(track.content_id.trim()))
+                            .and_then(|i| resp.envelope_ids.get(i))
+                            .cloned()
+                            .unwrap_or_else(|| "n/a".to_string());
                         let record = SharedGrantRecord {
                             owner_address: owner_address.clone(),
                             grantee_address: grantee_hex.clone(),
@@ -79,11 +71,13 @@ pub(super) async fn grant_access_in_chunks(
                             content_id: track.content_id.clone(),
                             piece_cid: track.piece_cid.clone(),
                             gateway_url: track.gateway_url.clone(),
-                            tx_hash: tx_hash.clone(),
-                            mirror_tx_hash: mirror_tx_hash.clone(),
+                            tx_hash: envelope_id,
+                            mirror_tx_hash: "n/a".to_string(),
                             shared_at_ms: now_ms,
                         };
-                        if let Err(e) = append_shared_grant_record(record) {
+                        if let Err(e) = with_music_db(this.db.as_ref(), |db| {
+                            db.upsert_shared_grant_record(&record)
+                        }) {
                             log::error!("[Library] failed to persist shared grant record: {}", e);
                         }
                     }
@@ -96,7 +90,7 @@ pub(super) async fn grant_access_in_chunks(
                 granted = granted.saturating_add(chunk_tracks.len());
             }
             Err(err) => {
-                grant_errors.push(summarize_status_error(&err));
+                grant_errors.push(summarize_storage_error(&err));
                 break;
             }
         }
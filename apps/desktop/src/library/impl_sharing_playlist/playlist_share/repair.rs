@@ -3,23 +3,19 @@ use super::*;
 pub(super) fn maybe_repair_legacy_content_encryption(
     svc: &mut LoadStorageService,
     auth: &auth::PersistedAuth,
-    payload: Value,
+    payload: RegisteredContent,
     branch: &'static str,
     local_track: Option<&TrackRow>,
     allow_upload: bool,
     title: &str,
     artist: &str,
     album: &str,
-) -> Result<(Value, &'static str), String> {
-    let content_id =
-        super::extract_field_string(&payload, "contentId").unwrap_or_else(|| "n/a".to_string());
-    let piece_cid =
-        super::extract_field_string(&payload, "pieceCid").unwrap_or_else(|| "n/a".to_string());
-    let gateway_url =
-        super::extract_field_string(&payload, "gatewayUrl").unwrap_or_else(|| "n/a".to_string());
-    let track_id = super::extract_field_string(&payload, "trackId");
+) -> Result<(RegisteredContent, &'static str), String> {
+    let content_id = payload.content_id.trim().to_string();
+    let piece_cid = payload.piece_cid.trim().to_string();
+    let track_id = Some(payload.track_id.trim().to_string()).filter(|id| !id.is_empty());
 
-    if content_id == "n/a" || piece_cid == "n/a" {
+    if content_id.is_empty() || piece_cid.is_empty() {
         return Ok((payload, branch));
     }
 
@@ -29,16 +25,12 @@ pub(super) fn maybe_repair_legacy_content_encryption(
         return Ok((payload, branch));
     }
 
-    let hint = if gateway_url == "n/a" {
-        None
-    } else {
-        Some(gateway_url.as_str())
-    };
+    let hint = Some(payload.gateway_url.trim()).filter(|url| !url.is_empty() && *url != "n/a");
 
     match svc.probe_content_decrypt_v1(auth, &content_id, &piece_cid, hint) {
         Ok(()) => Ok((payload, branch)),
         Err(err) => {
-            let lower = err.to_ascii_lowercase();
+            let lower = err.message().to_ascii_lowercase();
             let incompatible = lower.contains("decryption failure")
                 || lower.contains("failed to decrypt and combine");
             if !incompatible {
@@ -85,8 +77,9 @@ pub(super) fn maybe_repair_legacy_content_encryption(
                 title,
                 artist,
                 album,
+                &|_| UploadControl::Continue,
             )?;
-            Ok((new_payload, "replaced"))
+            Ok((new_payload.into(), "replaced"))
         }
     }
 }
@@ -5,8 +5,6 @@ use super::share_and_upload::upload::{
     upload_track_with_diagnostics,
 };
 
-use serde_json::json;
-
 pub(super) async fn run_playlist_share_task(
     this: WeakEntity<LibraryView>,
     cx: &mut AsyncApp,
@@ -53,7 +51,7 @@ pub(super) async fn run_playlist_share_task(
             let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
             let (payload, local_track_out, branch) =
                 match svc.resolve_registered_content_by_track_id(&auth, &track_id) {
-                    Ok(resolved) => Ok::<(Value, Option<TrackRow>, &'static str), String>((
+                    Ok(resolved) => Ok::<(RegisteredContent, Option<TrackRow>, &'static str), String>((
                         resolved,
                         local_track,
                         "resolved",
@@ -75,16 +73,15 @@ pub(super) async fn run_playlist_share_task(
                                     track_id,
                                     record.content_id
                                 );
-                                return Ok::<(Value, Option<TrackRow>, &'static str), String>((
-                                    json!({
-                                        "trackId": record.track_id,
-                                        "contentId": record.content_id,
-                                        "pieceCid": record.piece_cid,
-                                        "gatewayUrl": record.gateway_url,
-                                        "registerVersion": record.register_version,
-                                        "txHash": record.tx_hash,
-                                        "blockNumber": Value::Null,
-                                    }),
+                                return Ok::<(RegisteredContent, Option<TrackRow>, &'static str), String>((
+                                    RegisteredContent {
+                                        track_id: record.track_id,
+                                        content_id: record.content_id,
+                                        piece_cid: record.piece_cid,
+                                        gateway_url: record.gateway_url,
+                                        register_version: record.register_version,
+                                        tx_hash: Some(record.tx_hash),
+                                    },
                                     local_track,
                                     "uploaded-record",
                                 ));
@@ -93,13 +90,13 @@ pub(super) async fn run_playlist_share_task(
                         let Some(local_track) = local_track else {
                             return Err(format!(
                                 "Not registered yet and not available locally: {}",
-                                summarize_status_error(&resolve_err)
+                                summarize_storage_error(&resolve_err)
                             ));
                         };
                         if !allow_upload {
                             return Err(format!(
                                 "Not registered yet (uploads blocked by low Turbo credits): {}",
-                                summarize_status_error(&resolve_err)
+                                summarize_storage_error(&resolve_err)
                             ));
                         }
                         if local_track.file_path.is_empty()
@@ -113,10 +110,13 @@ pub(super) async fn run_playlist_share_task(
                             &auth,
                             &local_track.file_path,
                             meta.clone(),
+                            &|_| UploadControl::Continue,
                         ) {
-                            Ok(upload_resp) => Ok((upload_resp, Some(local_track), "uploaded")),
+                            Ok(upload_resp) => {
+                                Ok((upload_resp.into(), Some(local_track), "uploaded"))
+                            }
                             Err(upload_err) => {
-                                if super::super::is_already_uploaded_error(&upload_err) {
+                                if super::super::is_already_uploaded_error(upload_err.message()) {
                                     match svc.resolve_registered_content_for_track(
                                         &auth,
                                         &local_track.file_path,
@@ -132,7 +132,7 @@ pub(super) async fn run_playlist_share_task(
                                         )),
                                     }
                                 } else {
-                                    Err(upload_err)
+                                    Err(upload_err.to_string())
                                 }
                             }
                         }
@@ -151,22 +151,13 @@ pub(super) async fn run_playlist_share_task(
                 &album,
             )?;
 
-            Ok::<(Value, Option<TrackRow>, &'static str), String>((payload, local_track_out, branch))
+            Ok::<(RegisteredContent, Option<TrackRow>, &'static str), String>((payload, local_track_out, branch))
         })
         .await;
 
         match per_track {
             Ok((payload, local_track, branch)) => {
-                let content_id = super::extract_field_string(&payload, "contentId")
-                    .unwrap_or_else(|| "n/a".to_string());
-                let piece_cid = super::extract_field_string(&payload, "pieceCid")
-                    .unwrap_or_else(|| "n/a".to_string());
-                let gateway_url = super::extract_field_string(&payload, "gatewayUrl")
-                    .unwrap_or_else(|| "n/a".to_string());
-                let track_id_out = super::extract_field_string(&payload, "trackId")
-                    .or_else(|| Some(track.track_id.clone()));
-
-                if content_id == "n/a" || piece_cid == "n/a" {
+                if payload.content_id.trim().is_empty() || payload.piece_cid.trim().is_empty() {
                     failures.push(format!(
                         "{} ({}): incomplete response (branch={})",
                         track_label, pos, branch
@@ -185,11 +176,6 @@ pub(super) async fn run_playlist_share_task(
                             &owner_address,
                             &local_track,
                             &payload_for_record,
-                            if branch == "resolved" {
-                                "onchain-recovered"
-                            } else {
-                                "n/a"
-                            },
                             saved_forever,
                         ) {
                             let status = if record.saved_forever {
@@ -213,10 +199,12 @@ pub(super) async fn run_playlist_share_task(
                     title: track.title.clone(),
                     artist: track.artist.clone(),
                     album: track.album.clone(),
-                    track_id: track_id_out,
-                    content_id,
-                    piece_cid,
-                    gateway_url,
+                    track_id: Some(payload.track_id)
+                        .filter(|id| !id.trim().is_empty())
+                        .or_else(|| Some(track.track_id.clone())),
+                    content_id: payload.content_id,
+                    piece_cid: payload.piece_cid,
+                    gateway_url: payload.gateway_url,
                 });
             }
             Err(err) => {
@@ -325,7 +313,7 @@ pub(super) async fn run_playlist_share_task(
                 abbreviate_for_status(&grantee_hex),
                 err
             );
-            playlist_share_error = Some(summarize_status_error(&err));
+            playlist_share_error = Some(err.user_message());
         }
     }
 
@@ -0,0 +1,697 @@
+//! Offline-first sync of uploaded and shared records. The UI only reads the library database;
+//! this reconciles it on a schedule against the content registry, Load's tag index and
+//! gateway, and the subgraph's access grants.
+
+use super::*;
+use crate::load_storage::{RemoteAccessGrant, RemoteContentState};
+
+/// How long a fresh upload or grant may be missing remotely before that counts as drift.
+const RECORD_SYNC_GRACE_MS: i64 = 30 * 60 * 1000;
+/// Synced records are checked again after this long.
+const RECORD_RECHECK_SECS: i64 = 6 * 60 * 60;
+/// Uploaded records checked per run, so one run stays short on large libraries.
+const RECORD_SYNC_BATCH: usize = 50;
+
+fn record_sync_interval_secs() -> u64 {
+    env::var("HEAVEN_RECORD_SYNC_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(600)
+}
+
+#[derive(Debug, PartialEq)]
+enum UploadedReconcile {
+    /// The content was deactivated, so the upload is gone for good.
+    Delete,
+    /// The track was uploaded again elsewhere after this record; follow the newer blob.
+    Adopt {
+        piece_cid: String,
+    },
+    Mark(RecordSyncState, Option<String>),
+}
+
+fn reconcile_uploaded_record(
+    record: &UploadedTrackRecord,
+    remote: &RemoteContentState,
+    now_ms: i64,
+) -> UploadedReconcile {
+    if remote.deactivated {
+        return UploadedReconcile::Delete;
+    }
+    // Fresh uploads take a while to show up in the tag index.
+    let young = now_ms.saturating_sub(record.created_at_ms) < RECORD_SYNC_GRACE_MS;
+    match remote.piece_cid.as_deref().map(str::trim) {
+        Some(piece_cid) if !piece_cid.eq_ignore_ascii_case(record.piece_cid.trim()) => {
+            // The index may simply not list this upload yet; only a blob posted after it,
+            // once the grace period is over, replaces it. Otherwise judge the record's own.
+            let newer = remote
+                .piece_posted_at_ms
+                .is_some_and(|posted_at_ms| posted_at_ms > record.created_at_ms);
+            if newer && !young {
+                return UploadedReconcile::Adopt {
+                    piece_cid: piece_cid.to_string(),
+                };
+            }
+        }
+        None if young => return UploadedReconcile::Mark(RecordSyncState::Pending, None),
+        None => {
+            return UploadedReconcile::Mark(
+                RecordSyncState::Drifted,
+                Some("Load has no record of this upload.".to_string()),
+            );
+        }
+        Some(_) => {}
+    }
+    // An Arweave copy still serves the track when Load drops the blob.
+    match remote.blob_served {
+        Some(false) if !record.saved_forever && young => {
+            UploadedReconcile::Mark(RecordSyncState::Pending, None)
+        }
+        Some(false) if !record.saved_forever => UploadedReconcile::Mark(
+            RecordSyncState::Drifted,
+            Some("The Load gateway returns 404 for this upload.".to_string()),
+        ),
+        _ => UploadedReconcile::Mark(RecordSyncState::Synced, None),
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct GrantReconcile {
+    upserts: Vec<SharedGrantRecord>,
+    /// (grantee, content id) pairs to forget.
+    deletes: Vec<(String, String)>,
+    /// Local grants past the grace period that the subgraph doesn't list. Tempo grants are
+    /// LS3 key envelopes only, so their absence there proves nothing.
+    unindexed: Vec<SharedGrantRecord>,
+}
+
+/// Merge local grants with the subgraph's view of the same grants. The newest side wins;
+/// local grants the subgraph doesn't list are never dropped here, only set aside for an
+/// envelope check once the grace period is over.
+fn reconcile_grants(
+    local: &[SharedGrantRecord],
+    remote: &[RemoteAccessGrant],
+    now_ms: i64,
+) -> GrantReconcile {
+    let mut latest = HashMap::<(String, String), &RemoteAccessGrant>::new();
+    for grant in remote {
+        latest
+            .entry((grant.grantee_address.clone(), grant.content_id.clone()))
+            .and_modify(|existing| {
+                if grant.updated_at_ms > existing.updated_at_ms {
+                    *existing = grant;
+                }
+            })
+            .or_insert(grant);
+    }
+
+    let mut out = GrantReconcile::default();
+    for record in local {
+        let key = (
+            record.grantee_address.trim().to_lowercase(),
+            record.content_id.trim().to_lowercase(),
+        );
+        match latest.remove(&key) {
+            Some(grant) if !grant.granted => {
+                // A re-grant newer than the revocation may not be indexed yet.
+                if grant.updated_at_ms >= record.shared_at_ms {
+                    out.deletes.push(key);
+                }
+            }
+            Some(grant) => {
+                let mut next = record.clone();
+                if grant.updated_at_ms > record.shared_at_ms {
+                    next.gateway_url =
+                        swap_piece_cid(&next.gateway_url, &next.piece_cid, &grant.piece_cid);
+                    next.piece_cid = grant.piece_cid.clone();
+                    next.shared_at_ms = grant.updated_at_ms;
+                }
+                if next.owner_address.trim().is_empty() {
+                    next.owner_address = grant.owner_address.clone();
+                }
+                if next.track_id.is_none() {
+                    next.track_id = grant.track_id.clone();
+                }
+                if next != *record {
+                    out.upserts.push(next);
+                }
+            }
+            None if now_ms.saturating_sub(record.shared_at_ms) < RECORD_SYNC_GRACE_MS => {}
+            None => out.unindexed.push(record.clone()),
+        }
+    }
+
+    let mut added = latest
+        .into_values()
+        .filter(|grant| grant.granted)
+        .map(|grant| SharedGrantRecord {
+            owner_address: grant.owner_address.clone(),
+            grantee_address: grant.grantee_address.clone(),
+            title: String::new(),
+            artist: String::new(),
+            album: String::new(),
+            track_id: grant.track_id.clone(),
+            content_id: grant.content_id.clone(),
+            piece_cid: grant.piece_cid.clone(),
+            gateway_url: String::new(),
+            tx_hash: "n/a".to_string(),
+            mirror_tx_hash: "n/a".to_string(),
+            shared_at_ms: grant.updated_at_ms,
+        })
+        .collect::<Vec<_>>();
+    added.sort_by(|a, b| b.shared_at_ms.cmp(&a.shared_at_ms));
+    out.upserts.extend(added);
+    out
+}
+
+/// Gateway URLs end in the pieceCid; point one at a different blob.
+fn swap_piece_cid(gateway_url: &str, from: &str, to: &str) -> String {
+    match gateway_url.strip_suffix(from) {
+        Some(base) if !from.is_empty() => format!("{base}{to}"),
+        _ => gateway_url.to_string(),
+    }
+}
+
+#[derive(Debug, Default)]
+struct RecordSyncReport {
+    /// Library tracks whose storage status changed.
+    statuses: Vec<(String, StorageStatus)>,
+    newly_drifted: usize,
+    grants_changed: bool,
+}
+
+impl LibraryView {
+    /// Reconcile the uploaded and shared records with the network, then schedule the next run.
+    pub(in crate::library) fn run_record_sync(&mut self, cx: &mut Context<Self>) {
+        if self.record_sync_running {
+            return;
+        }
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some(owner) = auth::load_from_disk()
+            .and_then(|a| a.wallet_address().map(|value| value.trim().to_lowercase()))
+            .filter(|value| !value.is_empty())
+        else {
+            return;
+        };
+        self.record_sync_running = true;
+        self.record_sync_seq = self.record_sync_seq.wrapping_add(1);
+
+        let storage = self.storage.clone();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let owner_for_sync = owner.clone();
+            let report = smol::unblock(move || sync_records(&db, &storage, &owner_for_sync)).await;
+            let _ = this.update(cx, |this, cx| {
+                this.record_sync_running = false;
+                match report {
+                    Ok(report) => this.apply_record_sync_report(&owner, report, cx),
+                    Err(err) => log::warn!("[Library] record sync failed: {}", err),
+                }
+                this.schedule_record_sync(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn schedule_record_sync(&mut self, cx: &mut Context<Self>) {
+        let seq = self.record_sync_seq;
+        let delay_secs = record_sync_interval_secs();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            smol::Timer::after(std::time::Duration::from_secs(delay_secs)).await;
+            let _ = this.update(cx, |this, cx| {
+                if this.record_sync_seq == seq {
+                    this.run_record_sync(cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn apply_record_sync_report(
+        &mut self,
+        owner: &str,
+        report: RecordSyncReport,
+        cx: &mut Context<Self>,
+    ) {
+        if self.uploaded_index_owner.as_deref() == Some(owner) && !report.statuses.is_empty() {
+            // Force a reload of the index from the database.
+            self.uploaded_index_owner = None;
+            self.refresh_uploaded_index_from_auth();
+            for (file_path, status) in &report.statuses {
+                self.set_track_storage_status(file_path, *status);
+            }
+        }
+        if report.grants_changed || !report.statuses.is_empty() {
+            self.refresh_share_modal_grantees();
+            if self.shared_records_for.is_some() {
+                self.reload_shared_records(cx);
+            }
+        }
+        if report.newly_drifted > 0 {
+            let message = if report.newly_drifted == 1 {
+                "1 uploaded track is missing from Load. Upload it again to restore it.".to_string()
+            } else {
+                format!(
+                    "{} uploaded tracks are missing from Load. Upload them again to restore them.",
+                    report.newly_drifted
+                )
+            };
+            self.set_status_message(message, cx);
+        }
+        cx.notify();
+    }
+}
+
+fn sync_records(
+    db: &Arc<Mutex<MusicDb>>,
+    storage: &Arc<Mutex<LoadStorageService>>,
+    owner: &str,
+) -> Result<RecordSyncReport, String> {
+    let now = now_epoch_sec() as i64;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let mut report = RecordSyncReport::default();
+
+    let due = with_music_db(Some(db), |db| {
+        db.uploaded_track_records_due_for_sync(owner, now - RECORD_RECHECK_SECS, RECORD_SYNC_BATCH)
+    })?;
+    for record in due {
+        // Lock per record so uploads and playback aren't held up for the whole run.
+        let remote = storage
+            .lock()
+            .map_err(|e| format!("storage lock: {e}"))?
+            .remote_content_state(
+                owner,
+                &record.track_id,
+                &record.content_id,
+                &record.piece_cid,
+                &record.register_version,
+            );
+        match remote {
+            Ok(remote) => {
+                let outcome = reconcile_uploaded_record(&record, &remote, now_ms);
+                with_music_db(Some(db), |db| {
+                    apply_uploaded_reconcile(db, owner, &record, outcome, now, &mut report)
+                })?;
+            }
+            Err(err) => {
+                // Unreachable remotes say nothing about the record; check it again later.
+                log::warn!("[Library] record sync skipped '{}': {}", record.title, err);
+                with_music_db(Some(db), |db| {
+                    db.set_uploaded_track_sync_state(
+                        owner,
+                        &record.file_path,
+                        record.sync_state,
+                        record.sync_note.as_deref(),
+                        now,
+                    )
+                })?;
+            }
+        }
+    }
+
+    report.grants_changed = sync_grants(db, storage, owner, now_ms)?;
+    Ok(report)
+}
+
+fn apply_uploaded_reconcile(
+    db: &MusicDb,
+    owner: &str,
+    record: &UploadedTrackRecord,
+    outcome: UploadedReconcile,
+    now: i64,
+    report: &mut RecordSyncReport,
+) -> Result<(), String> {
+    match outcome {
+        UploadedReconcile::Delete => {
+            log::info!(
+                "[Library] content deactivated; forgetting upload of '{}'",
+                record.title
+            );
+            db.delete_uploaded_track_record(owner, &record.file_path)?;
+            report
+                .statuses
+                .push((record.file_path.clone(), StorageStatus::Local));
+        }
+        UploadedReconcile::Adopt { piece_cid } => {
+            log::info!(
+                "[Library] adopting newer pieceCid for '{}': {} -> {}",
+                record.title,
+                record.piece_cid,
+                piece_cid
+            );
+            let gateway_url = swap_piece_cid(&record.gateway_url, &record.piece_cid, &piece_cid);
+            db.adopt_uploaded_track_piece(owner, &record.file_path, &piece_cid, &gateway_url, now)?;
+            // The Arweave copy of the earlier blob still holds this track.
+            let status = if record.saved_forever {
+                StorageStatus::Permanent
+            } else {
+                StorageStatus::Uploaded
+            };
+            db.set_track_storage_status(
+                owner,
+                &record.file_path,
+                &record.content_id,
+                &piece_cid,
+                status,
+                now,
+            )?;
+            report.statuses.push((record.file_path.clone(), status));
+        }
+        UploadedReconcile::Mark(state, note) => {
+            db.set_uploaded_track_sync_state(
+                owner,
+                &record.file_path,
+                state,
+                note.as_deref(),
+                now,
+            )?;
+            if state == record.sync_state || state == RecordSyncState::Pending {
+                return Ok(());
+            }
+            let status = match state {
+                RecordSyncState::Drifted => {
+                    log::warn!(
+                        "[Library] upload of '{}' drifted: {}",
+                        record.title,
+                        note.as_deref().unwrap_or_default()
+                    );
+                    report.newly_drifted += 1;
+                    StorageStatus::Missing
+                }
+                _ if record.saved_forever => StorageStatus::Permanent,
+                _ => StorageStatus::Uploaded,
+            };
+            db.set_track_storage_status(
+                owner,
+                &record.file_path,
+                &record.content_id,
+                &record.piece_cid,
+                status,
+                now,
+            )?;
+            report.statuses.push((record.file_path.clone(), status));
+        }
+    }
+    Ok(())
+}
+
+/// Sync both sides of sharing: grants issued to `owner`, and grants `owner` issued on the
+/// uploads it has on record. A side whose fetch fails is left as is.
+fn sync_grants(
+    db: &Arc<Mutex<MusicDb>>,
+    storage: &Arc<Mutex<LoadStorageService>>,
+    owner: &str,
+    now_ms: i64,
+) -> Result<bool, String> {
+    let mut changed = false;
+
+    let received = storage
+        .lock()
+        .map_err(|e| format!("storage lock: {e}"))?
+        .access_grants_for_grantee(owner);
+    match received {
+        Ok(remote) => {
+            let local = with_music_db(Some(db), |db| db.shared_grant_records_for_grantee(owner))?;
+            let reconcile =
+                confirm_unindexed_grants(storage, reconcile_grants(&local, &remote, now_ms))?;
+            changed |= apply_grant_reconcile(db, reconcile, &[])?;
+        }
+        Err(err) => log::warn!("[Library] received grants sync skipped: {}", err),
+    }
+
+    let uploads = with_music_db(Some(db), |db| db.uploaded_track_records(owner))?;
+    if uploads.is_empty() {
+        return Ok(changed);
+    }
+    let content_ids = uploads
+        .iter()
+        .map(|record| record.content_id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let issued = storage
+        .lock()
+        .map_err(|e| format!("storage lock: {e}"))?
+        .access_grants_for_contents(&content_ids);
+    match issued {
+        Ok(remote) => {
+            // Only grants on the queried contents can be compared.
+            let local = with_music_db(Some(db), |db| db.shared_grant_records_for_owner(owner))?
+                .into_iter()
+                .filter(|record| content_ids.contains(&record.content_id))
+                .collect::<Vec<_>>();
+            let reconcile =
+                confirm_unindexed_grants(storage, reconcile_grants(&local, &remote, now_ms))?;
+            changed |= apply_grant_reconcile(db, reconcile, &uploads)?;
+        }
+        Err(err) => log::warn!("[Library] issued grants sync skipped: {}", err),
+    }
+    Ok(changed)
+}
+
+/// Drop the set-aside grants whose LS3 envelopes a key rotation has superseded. Grants the
+/// lookup can't settle are kept.
+fn confirm_unindexed_grants(
+    storage: &Arc<Mutex<LoadStorageService>>,
+    mut reconcile: GrantReconcile,
+) -> Result<GrantReconcile, String> {
+    for record in std::mem::take(&mut reconcile.unindexed) {
+        let live = storage
+            .lock()
+            .map_err(|e| format!("storage lock: {e}"))?
+            .grant_envelope_live(
+                &record.content_id,
+                &record.owner_address,
+                &record.grantee_address,
+            );
+        match live {
+            Ok(true) => {}
+            Ok(false) => reconcile.deletes.push((
+                record.grantee_address.trim().to_lowercase(),
+                record.content_id.trim().to_lowercase(),
+            )),
+            Err(err) => log::warn!(
+                "[Library] envelope check skipped for contentId={}: {}",
+                record.content_id,
+                err
+            ),
+        }
+    }
+    Ok(reconcile)
+}
+
+/// Write a grant reconcile back, naming grants found only remotely after the upload they cover.
+fn apply_grant_reconcile(
+    db: &Arc<Mutex<MusicDb>>,
+    reconcile: GrantReconcile,
+    uploads: &[UploadedTrackRecord],
+) -> Result<bool, String> {
+    let changed = !reconcile.upserts.is_empty() || !reconcile.deletes.is_empty();
+    with_music_db(Some(db), |db| {
+        for mut record in reconcile.upserts {
+            if record.title.is_empty() {
+                if let Some(upload) = uploads
+                    .iter()
+                    .find(|upload| upload.content_id == record.content_id)
+                {
+                    record.title = upload.title.clone();
+                    record.artist = upload.artist.clone();
+                    record.album = upload.album.clone();
+                    record.gateway_url = upload.gateway_url.clone();
+                }
+            }
+            db.upsert_shared_grant_record(&record)?;
+        }
+        for (grantee, content_id) in &reconcile.deletes {
+            db.delete_shared_grant_record(grantee, content_id)?;
+        }
+        Ok(changed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_MS: i64 = 1_800_000_000_000;
+    const OLD_MS: i64 = NOW_MS - 2 * RECORD_SYNC_GRACE_MS;
+
+    fn uploaded(created_at_ms: i64) -> UploadedTrackRecord {
+        UploadedTrackRecord {
+            owner_address: "0xowner".to_string(),
+            file_path: "/music/a.flac".to_string(),
+            title: "A".to_string(),
+            artist: "B".to_string(),
+            album: String::new(),
+            track_id: "0x01".to_string(),
+            content_id: "0xc1".to_string(),
+            piece_cid: "bafy-old".to_string(),
+            gateway_url: "https://gw/resolve/bafy-old".to_string(),
+            tx_hash: "n/a".to_string(),
+            register_version: "tempo-offchain".to_string(),
+            created_at_ms,
+            saved_forever: false,
+            arweave_id: None,
+            sync_state: RecordSyncState::Pending,
+            sync_note: None,
+        }
+    }
+
+    fn remote(piece_cid: Option<&str>, blob_served: Option<bool>) -> RemoteContentState {
+        RemoteContentState {
+            piece_cid: piece_cid.map(str::to_string),
+            piece_posted_at_ms: Some(OLD_MS + 1_000),
+            deactivated: false,
+            blob_served,
+        }
+    }
+
+    #[test]
+    fn uploaded_records_follow_the_remote_side() {
+        let record = uploaded(OLD_MS);
+        assert_eq!(
+            reconcile_uploaded_record(&record, &remote(Some("bafy-old"), Some(true)), NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+        assert_eq!(
+            reconcile_uploaded_record(&record, &remote(Some("bafy-new"), Some(true)), NOW_MS),
+            UploadedReconcile::Adopt {
+                piece_cid: "bafy-new".to_string()
+            }
+        );
+        // An index entry older than the record is an earlier upload, not a replacement.
+        let stale = RemoteContentState {
+            piece_posted_at_ms: Some(OLD_MS - 1_000),
+            ..remote(Some("bafy-new"), Some(true))
+        };
+        assert_eq!(
+            reconcile_uploaded_record(&record, &stale, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+        let undated = RemoteContentState {
+            piece_posted_at_ms: None,
+            ..remote(Some("bafy-new"), Some(true))
+        };
+        assert_eq!(
+            reconcile_uploaded_record(&record, &undated, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+        // A fresh upload isn't replaced while the index catches up.
+        let fresh = uploaded(NOW_MS - 1_000);
+        let newer = RemoteContentState {
+            piece_posted_at_ms: Some(NOW_MS),
+            ..remote(Some("bafy-new"), Some(true))
+        };
+        assert_eq!(
+            reconcile_uploaded_record(&fresh, &newer, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+        let deactivated = RemoteContentState {
+            deactivated: true,
+            ..remote(Some("bafy-old"), Some(true))
+        };
+        assert_eq!(
+            reconcile_uploaded_record(&record, &deactivated, NOW_MS),
+            UploadedReconcile::Delete
+        );
+        // A probe that couldn't reach the gateway isn't evidence of drift.
+        assert_eq!(
+            reconcile_uploaded_record(&record, &remote(Some("bafy-old"), None), NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+    }
+
+    #[test]
+    fn gateway_404s_drift_only_after_the_grace_period() {
+        let missing = remote(Some("bafy-old"), Some(false));
+        assert_eq!(
+            reconcile_uploaded_record(&uploaded(NOW_MS - 1_000), &missing, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Pending, None)
+        );
+        assert!(matches!(
+            reconcile_uploaded_record(&uploaded(OLD_MS), &missing, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Drifted, Some(_))
+        ));
+        assert!(matches!(
+            reconcile_uploaded_record(&uploaded(OLD_MS), &remote(None, None), NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Drifted, Some(_))
+        ));
+
+        let archived = UploadedTrackRecord {
+            saved_forever: true,
+            ..uploaded(OLD_MS)
+        };
+        assert_eq!(
+            reconcile_uploaded_record(&archived, &missing, NOW_MS),
+            UploadedReconcile::Mark(RecordSyncState::Synced, None)
+        );
+    }
+
+    fn local_grant(content_id: &str, shared_at_ms: i64) -> SharedGrantRecord {
+        SharedGrantRecord {
+            owner_address: "0xowner".to_string(),
+            grantee_address: "0xfriend".to_string(),
+            title: "A".to_string(),
+            artist: "B".to_string(),
+            album: String::new(),
+            track_id: Some("0x01".to_string()),
+            content_id: content_id.to_string(),
+            piece_cid: "bafy-old".to_string(),
+            gateway_url: "https://gw/resolve/bafy-old".to_string(),
+            tx_hash: "env-1".to_string(),
+            mirror_tx_hash: "n/a".to_string(),
+            shared_at_ms,
+        }
+    }
+
+    fn remote_grant(content_id: &str, granted: bool, updated_at_ms: i64) -> RemoteAccessGrant {
+        RemoteAccessGrant {
+            owner_address: "0xowner".to_string(),
+            grantee_address: "0xfriend".to_string(),
+            content_id: content_id.to_string(),
+            piece_cid: "bafy-new".to_string(),
+            track_id: Some("0x01".to_string()),
+            granted,
+            updated_at_ms,
+        }
+    }
+
+    #[test]
+    fn grants_reconcile_revocations_repoints_and_set_aside_unindexed_shares() {
+        let local = vec![
+            local_grant("0xrevoked", OLD_MS),
+            local_grant("0xregranted", NOW_MS - 1_000),
+            local_grant("0xrepointed", OLD_MS),
+            local_grant("0xunindexed", NOW_MS - 1_000),
+            local_grant("0xgone", OLD_MS),
+        ];
+        let remote = vec![
+            remote_grant("0xrevoked", false, OLD_MS + 1_000),
+            remote_grant("0xregranted", false, OLD_MS),
+            remote_grant("0xrepointed", true, OLD_MS - 5_000),
+            remote_grant("0xrepointed", true, OLD_MS + 5_000),
+            remote_grant("0xelsewhere", true, OLD_MS),
+            remote_grant("0xelsewhere-revoked", false, OLD_MS),
+        ];
+        let out = reconcile_grants(&local, &remote, NOW_MS);
+
+        assert_eq!(
+            out.deletes,
+            vec![("0xfriend".to_string(), "0xrevoked".to_string())]
+        );
+        // Tempo grants never reach the subgraph; only an envelope check may drop them.
+        assert_eq!(out.unindexed, vec![local_grant("0xgone", OLD_MS)]);
+        assert_eq!(out.upserts.len(), 2);
+        let repointed = &out.upserts[0];
+        assert_eq!(repointed.content_id, "0xrepointed");
+        assert_eq!(repointed.piece_cid, "bafy-new");
+        assert_eq!(repointed.gateway_url, "https://gw/resolve/bafy-new");
+        assert_eq!(repointed.shared_at_ms, OLD_MS + 5_000);
+        assert_eq!(repointed.title, "A");
+        let added = &out.upserts[1];
+        assert_eq!(added.content_id, "0xelsewhere");
+        assert!(added.title.is_empty());
+    }
+}
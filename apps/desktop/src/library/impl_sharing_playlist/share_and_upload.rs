@@ -1,8 +1,11 @@
 use super::*;
 
+mod archive;
 mod copy_refs;
 mod path_helpers;
+mod revoke;
 mod share_modal;
 pub(in crate::library) mod upload;
+mod upload_queue;
 
 pub(in crate::library) use path_helpers::shared_library_target_path;
@@ -0,0 +1,548 @@
+use super::upload::build_uploaded_track_record;
+use super::*;
+use crate::load_storage::PendingRevocation;
+
+const INCOMPLETE_REVOKE_UPLOAD: &str = "The re-encrypted upload details were incomplete.";
+
+impl LibraryView {
+    /// Reload the grantee list for the track open in the share modal.
+    pub(in crate::library) fn refresh_share_modal_grantees(&mut self) {
+        let record = self
+            .share_modal_track_index
+            .and_then(|i| self.tracks.get(i))
+            .and_then(|track| self.uploaded_index.get(&track.file_path));
+        self.share_modal_grantees = match record {
+            Some(record) => with_music_db(self.db.as_ref(), |db| {
+                db.shared_grant_records_for_content(&record.owner_address, &record.content_id)
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("[Library] failed to load grantees: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        self.share_modal_pending_revocation = record.and_then(|record| {
+            with_music_db(self.db.as_ref(), |db| {
+                db.pending_revocations(&record.owner_address)
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("[Library] failed to load pending revocations: {}", e);
+                Vec::new()
+            })
+            .into_iter()
+            .find(|row| row.content_id.eq_ignore_ascii_case(&record.content_id))
+        });
+    }
+
+    pub(in crate::library) fn revoke_track_access(
+        &mut self,
+        grantee: String,
+        cx: &mut Context<Self>,
+    ) {
+        if self.share_modal_revoking.is_some() || self.share_modal_submitting {
+            return;
+        }
+        let Some(track) = self
+            .share_modal_track_index
+            .and_then(|i| self.tracks.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(uploaded) = self.uploaded_index.get(&track.file_path).cloned() else {
+            self.share_modal_error = Some("Track has not been uploaded yet.".to_string());
+            cx.notify();
+            return;
+        };
+        if track.file_path.is_empty() || !std::path::Path::new(&track.file_path).exists() {
+            self.share_modal_error = Some(
+                "Local file is missing; revoking needs it to re-encrypt the track.".to_string(),
+            );
+            cx.notify();
+            return;
+        }
+        let Some(auth) = auth::load_from_disk() else {
+            self.share_modal_error = Some("Sign in before changing track access.".to_string());
+            cx.notify();
+            return;
+        };
+        // A second revoke would re-encrypt under a key the grantees don't have yet.
+        if self.share_modal_pending_revocation.is_some()
+            || self
+                .revocations_publishing
+                .contains(&uploaded.content_id.to_ascii_lowercase())
+        {
+            self.share_modal_error =
+                Some("Finish the previous revoke for this track first.".to_string());
+            cx.notify();
+            return;
+        }
+
+        let mut remaining = self
+            .share_modal_grantees
+            .iter()
+            .map(|record| record.grantee_address.clone())
+            .filter(|address| !address.eq_ignore_ascii_case(&grantee))
+            .collect::<Vec<_>>();
+        self.share_modal_revoking = Some(grantee.clone());
+        self.share_modal_error = None;
+        self.set_status_message(
+            format!(
+                "Revoking access to \"{}\" for {}...",
+                track.title,
+                abbreviate_for_status(&grantee)
+            ),
+            cx,
+        );
+
+        let storage = self.storage.clone();
+        let (progress_tx, progress_task) = self.spawn_upload_progress(track.title.clone(), cx);
+        let file_path = track.file_path.clone();
+        let track_id = uploaded.track_id.clone();
+        let content_id = uploaded.content_id.clone();
+        let owner_address = uploaded.owner_address.clone();
+        let revoked = vec![grantee.clone()];
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let reuploaded = smol::unblock({
+                let auth = auth.clone();
+                move || {
+                    let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
+                    // Grants made from another device or lost with a reinstall are only on
+                    // chain and in the index; the new key has to reach those grantees too.
+                    remaining.extend(svc.content_grantees(&content_id, &owner_address)?);
+                    svc.content_revoke_reupload(
+                        &auth,
+                        &file_path,
+                        &track_id,
+                        &revoked,
+                        &remaining,
+                        &|update| {
+                            let _ = progress_tx.try_send(update);
+                            UploadControl::Continue
+                        },
+                    )
+                }
+            })
+            .await;
+            progress_task.await;
+
+            // Point the records at the new blob before any envelope for its key exists, so a
+            // failure while publishing leaves them matching what our key opens.
+            let _ = this.update(cx, |this, cx| {
+                this.share_modal_revoking = None;
+                match reuploaded {
+                    Ok(pending) => {
+                        let owner_address = uploaded.owner_address.clone();
+                        if this.apply_access_revocation(&track, uploaded, &grantee, &pending, cx) {
+                            this.publish_revocation(auth, owner_address, track.title, pending, cx);
+                        }
+                    }
+                    Err(err) => this.report_revoke_failure(&track.title, &grantee, &err, cx),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Retry the unfinished revoke of the track open in the share modal.
+    pub(in crate::library) fn retry_pending_revocation(&mut self, cx: &mut Context<Self>) {
+        let Some(row) = self.share_modal_pending_revocation.clone() else {
+            return;
+        };
+        let Some(auth) = auth::load_from_disk() else {
+            self.share_modal_error = Some("Sign in before changing track access.".to_string());
+            cx.notify();
+            return;
+        };
+        self.share_modal_error = None;
+        self.resume_pending_revocation(auth, row, cx);
+    }
+
+    /// Pick up revokes whose envelopes were not all published, e.g. because the app quit or
+    /// the gateway failed halfway. Runs at startup and on every auth change.
+    pub(in crate::library) fn resume_pending_revocations(&mut self, cx: &mut Context<Self>) {
+        let Some(auth) = auth::load_from_disk() else {
+            return;
+        };
+        let Some(owner_address) = auth.wallet_address().map(str::to_string) else {
+            return;
+        };
+        let rows = match with_music_db(self.db.as_ref(), |db| {
+            db.pending_revocations(&owner_address)
+        }) {
+            Ok(rows) => rows,
+            Err(err) => {
+                log::warn!("[Library] failed to load pending revocations: {}", err);
+                return;
+            }
+        };
+        for row in rows {
+            self.resume_pending_revocation(auth.clone(), row, cx);
+        }
+    }
+
+    fn resume_pending_revocation(
+        &mut self,
+        auth: auth::PersistedAuth,
+        row: PendingRevocationRow,
+        cx: &mut Context<Self>,
+    ) {
+        if self.revocations_publishing.contains(&row.content_id) {
+            return;
+        }
+        match serde_json::from_str::<PendingRevocation>(&row.payload) {
+            Ok(pending) => {
+                log::info!(
+                    "[Library] resuming revoke for '{}' ({} of {} envelope(s) published)",
+                    row.title,
+                    pending.envelope_ids.len(),
+                    pending.remaining_grantees.len()
+                );
+                self.publish_revocation(auth, row.owner_address, row.title, pending, cx);
+            }
+            Err(e) => {
+                // Nothing can finish it; keep the row so the failure stays visible.
+                log::error!(
+                    "[Library] unreadable pending revoke for '{}' ({}): {}",
+                    row.title,
+                    row.content_id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Save `pending`, then publish the new key envelopes and the supersede record. The
+    /// saved copy is updated with whatever got published when this fails, and dropped
+    /// once everything is out.
+    fn publish_revocation(
+        &mut self,
+        auth: auth::PersistedAuth,
+        owner_address: String,
+        title: String,
+        mut pending: PendingRevocation,
+        cx: &mut Context<Self>,
+    ) {
+        let content_id = pending.upload.content.content_id.clone();
+        if !self.revocations_publishing.insert(content_id.clone()) {
+            return;
+        }
+        self.save_pending_revocation(&owner_address, &title, &pending, None);
+        self.refresh_share_modal_grantees();
+        cx.notify();
+
+        let storage = self.storage.clone();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let (published, pending) = smol::unblock(move || {
+                let published = match storage.lock() {
+                    Ok(mut svc) => svc.content_publish_revocation(&auth, &mut pending),
+                    Err(e) => Err(format!("storage lock: {e}").into()),
+                };
+                (published, pending)
+            })
+            .await;
+            let _ = this.update(cx, |this, cx| {
+                this.revocations_publishing.remove(&content_id);
+                let revoked = pending
+                    .revoked_grantees
+                    .iter()
+                    .map(|address| abbreviate_for_status(address))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match published {
+                    Ok(resp) => {
+                        if let Err(e) = with_music_db(this.db.as_ref(), |db| {
+                            db.delete_pending_revocation(&owner_address, &content_id)
+                        }) {
+                            log::warn!("[Library] failed to clear pending revoke: {}", e);
+                        }
+                        this.set_status_message(
+                            format!(
+                                "Revoked {} from \"{}\"; {} other grantee(s) keep access.",
+                                revoked,
+                                title,
+                                resp.remaining_grantees.len()
+                            ),
+                            cx,
+                        );
+                    }
+                    Err(err) => {
+                        let message = summarize_storage_error(&err);
+                        this.save_pending_revocation(
+                            &owner_address,
+                            &title,
+                            &pending,
+                            Some(&message),
+                        );
+                        this.report_revoke_failure(&title, &revoked, &err, cx);
+                    }
+                }
+                this.refresh_share_modal_grantees();
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn save_pending_revocation(
+        &self,
+        owner_address: &str,
+        title: &str,
+        pending: &PendingRevocation,
+        last_error: Option<&str>,
+    ) {
+        let saved = with_music_db(self.db.as_ref(), |db| {
+            store_pending_revocation(
+                db,
+                owner_address,
+                title,
+                pending,
+                last_error,
+                now_epoch_sec() as i64,
+            )
+        });
+        if let Err(e) = saved {
+            log::error!(
+                "[Library] failed to save pending revoke for '{}': {}",
+                title,
+                e
+            );
+        }
+    }
+
+    fn report_revoke_failure(
+        &mut self,
+        title: &str,
+        grantee: &str,
+        err: &LoadStorageError,
+        cx: &mut Context<Self>,
+    ) {
+        log::error!(
+            "[Library] revoke failed for '{}' ({}): {}",
+            title,
+            grantee,
+            err
+        );
+        let summary = summarize_storage_error(err);
+        if self.share_modal_open {
+            self.share_modal_error = Some(summary.clone());
+        }
+        self.set_status_message(format!("Revoke failed for \"{}\": {}", title, summary), cx);
+    }
+
+    /// Point the local upload and grant records at the re-encrypted blob, minus the
+    /// revoked grantee. The new blob is only on Load, so any Arweave copy of the old one
+    /// no longer applies. Returns false when the new upload details were unusable.
+    fn apply_access_revocation(
+        &mut self,
+        track: &TrackRow,
+        previous: UploadedTrackRecord,
+        grantee: &str,
+        resp: &PendingRevocation,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(record) =
+            revoked_upload_record(self.db.as_ref(), &previous.owner_address, track, resp)
+        else {
+            self.refresh_share_modal_grantees();
+            self.set_status_message(
+                format!(
+                    "Revoke of \"{}\" is unfinished; the new upload details were incomplete.",
+                    track.title
+                ),
+                cx,
+            );
+            return false;
+        };
+
+        let loaded = with_music_db(self.db.as_ref(), |db| {
+            db.shared_grant_records_for_content(&previous.owner_address, &previous.content_id)
+        });
+        // Leave the stored grants alone when they couldn't be read.
+        let replaced = match loaded {
+            Ok(records) => {
+                let kept = records
+                    .into_iter()
+                    .filter(|r| !r.grantee_address.eq_ignore_ascii_case(grantee))
+                    .map(|mut r| {
+                        r.piece_cid = record.piece_cid.clone();
+                        r.gateway_url = record.gateway_url.clone();
+                        r
+                    })
+                    .collect::<Vec<_>>();
+                with_music_db(self.db.as_ref(), |db| {
+                    db.replace_shared_grant_records_for_content(
+                        &previous.owner_address,
+                        &previous.content_id,
+                        &kept,
+                    )
+                })
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = replaced {
+            log::error!(
+                "[Library] failed to persist grant records after revoke: {}",
+                e
+            );
+        }
+
+        self.persist_uploaded_record(
+            &track.title,
+            track.file_path.clone(),
+            previous.owner_address.clone(),
+            record,
+            StorageStatus::Uploaded,
+        );
+        self.refresh_share_modal_grantees();
+        self.set_status_message(
+            format!(
+                "Re-encrypted \"{}\"; sharing the new key with {} remaining grantee(s)...",
+                track.title,
+                resp.remaining_grantees.len()
+            ),
+            cx,
+        );
+        self.fetch_storage_status(cx);
+        true
+    }
+}
+
+/// The record for the re-encrypted upload of `track`. When the new upload details are
+/// unusable the revoke is saved as pending instead: the old key is already rotated out, so its
+/// envelopes must still be published from the share modal or at the next start.
+fn revoked_upload_record(
+    db: Option<&Arc<Mutex<MusicDb>>>,
+    owner_address: &str,
+    track: &TrackRow,
+    pending: &PendingRevocation,
+) -> Option<UploadedTrackRecord> {
+    let record = build_uploaded_track_record(owner_address, track, &pending.upload.content, false);
+    if record.is_none() {
+        log::error!(
+            "[Library] revoke response incomplete for '{}': {:?}",
+            track.title,
+            pending
+        );
+        let saved = with_music_db(db, |db| {
+            store_pending_revocation(
+                db,
+                owner_address,
+                &track.title,
+                pending,
+                Some(INCOMPLETE_REVOKE_UPLOAD),
+                now_epoch_sec() as i64,
+            )
+        });
+        if let Err(e) = saved {
+            log::error!(
+                "[Library] failed to save pending revoke for '{}': {}",
+                track.title,
+                e
+            );
+        }
+    }
+    record
+}
+
+/// Encode `pending` and save it as `owner_address`'s unfinished revoke of its content.
+fn store_pending_revocation(
+    db: &MusicDb,
+    owner_address: &str,
+    title: &str,
+    pending: &PendingRevocation,
+    last_error: Option<&str>,
+    now: i64,
+) -> Result<(), String> {
+    let payload = serde_json::to_string(pending)
+        .map_err(|e| format!("Failed encoding pending revoke: {e}"))?;
+    db.save_pending_revocation(
+        owner_address,
+        &pending.upload.content.content_id,
+        title,
+        &payload,
+        last_error,
+        now,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_storage::{ContentUpload, RegisteredContent};
+
+    fn track() -> TrackRow {
+        TrackRow {
+            id: "1".to_string(),
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            album: String::new(),
+            duration: "3:20".to_string(),
+            file_path: "/music/song.flac".to_string(),
+            mbid: None,
+            ip_id: None,
+            cover_path: None,
+            storage_status: StorageStatus::Uploaded,
+        }
+    }
+
+    fn pending(piece_cid: &str) -> PendingRevocation {
+        PendingRevocation {
+            upload: ContentUpload {
+                content: RegisteredContent {
+                    track_id: "0x01".to_string(),
+                    content_id: "0xC1".to_string(),
+                    piece_cid: piece_cid.to_string(),
+                    gateway_url: "https://gw/resolve/bafy-b".to_string(),
+                    register_version: "tempo-offchain".to_string(),
+                    tx_hash: None,
+                },
+                ip_id: None,
+                blob_size: 1024,
+                winc: None,
+                replaced: true,
+                metadata_registered: true,
+            },
+            revoked_grantees: vec!["0xbob".to_string()],
+            remaining_grantees: vec!["0xcarol".to_string()],
+            superseded_envelope_ids: vec!["env-old".to_string()],
+            envelope_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn incomplete_revoke_upload_is_kept_as_pending() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-revoke-incomplete-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = Arc::new(Mutex::new(MusicDb::open(&dir).unwrap()));
+
+        let record = revoked_upload_record(Some(&db), "0xOwner", &track(), &pending("bafy-b"));
+        assert_eq!(record.map(|r| r.piece_cid).as_deref(), Some("bafy-b"));
+        assert!(db
+            .lock()
+            .unwrap()
+            .pending_revocations("0xowner")
+            .unwrap()
+            .is_empty());
+
+        let record = revoked_upload_record(Some(&db), "0xOwner", &track(), &pending(" "));
+        assert!(record.is_none());
+        let rows = db.lock().unwrap().pending_revocations("0xowner").unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].content_id, "0xc1");
+        assert_eq!(
+            rows[0].last_error.as_deref(),
+            Some(INCOMPLETE_REVOKE_UPLOAD)
+        );
+        let saved: PendingRevocation = serde_json::from_str(&rows[0].payload).unwrap();
+        assert_eq!(saved.remaining_grantees, vec!["0xcarol".to_string()]);
+        assert_eq!(saved.superseded_envelope_ids, vec!["env-old".to_string()]);
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,6 +2,7 @@ use super::upload::{
     build_uploaded_track_record, track_meta_input_from_row, upload_track_with_diagnostics,
 };
 use super::*;
+use crate::load_storage::ContentGrant;
 
 impl LibraryView {
     pub(in crate::library) fn open_share_modal(
@@ -14,6 +15,7 @@ impl LibraryView {
         self.share_modal_track_index = Some(track_index);
         self.share_modal_submitting = false;
         self.share_modal_error = None;
+        self.refresh_share_modal_grantees();
         cx.notify();
     }
 
@@ -22,11 +24,13 @@ impl LibraryView {
         self.share_modal_track_index = None;
         self.share_modal_submitting = false;
         self.share_modal_error = None;
+        self.share_modal_grantees.clear();
         cx.notify();
     }
 
     pub(in crate::library) fn submit_share_modal(&mut self, cx: &mut Context<Self>) {
-        if self.share_modal_submitting {
+        // A share during a revoke would wrap the key that is about to be replaced.
+        if self.share_modal_submitting || self.share_modal_revoking.is_some() {
             return;
         }
 
@@ -112,13 +116,12 @@ impl LibraryView {
                     existing
                 } else {
                     let track_meta = track_meta_input_from_row(&track_for_lookup);
-                    let (resolved, default_register_version) = match svc
-                        .resolve_registered_content_for_track(
-                            &auth,
-                            &path_for_lookup,
-                            track_meta.clone(),
-                        ) {
-                        Ok(resolved) => (resolved, "onchain-recovered"),
+                    let resolved = match svc.resolve_registered_content_for_track(
+                        &auth,
+                        &path_for_lookup,
+                        track_meta.clone(),
+                    ) {
+                        Ok(resolved) => resolved,
                         Err(resolve_err) => {
                             log::warn!(
                                 "[Library] direct share resolve failed: title=\"{}\" path=\"{}\" err={}",
@@ -131,7 +134,7 @@ impl LibraryView {
                             {
                                 return Err(format!(
                                     "Track is not registered and file is missing on disk: {}",
-                                    summarize_status_error(&resolve_err)
+                                    summarize_storage_error(&resolve_err)
                                 ));
                             }
                             match upload_track_with_diagnostics(
@@ -139,18 +142,19 @@ impl LibraryView {
                                 &auth,
                                 &path_for_lookup,
                                 track_meta.clone(),
+                                &|_| UploadControl::Continue,
                             ) {
-                                Ok(upload_resp) => (upload_resp, "n/a"),
+                                Ok(upload_resp) => upload_resp.into(),
                                 Err(upload_err) => {
-                                    if super::super::is_already_uploaded_error(&upload_err) {
+                                    if super::super::is_already_uploaded_error(
+                                        upload_err.message(),
+                                    ) {
                                         match svc.resolve_registered_content_for_track(
                                             &auth,
                                             &path_for_lookup,
                                             track_meta,
                                         ) {
-                                            Ok(resolved_after_upload) => {
-                                                (resolved_after_upload, "onchain-recovered")
-                                            }
+                                            Ok(resolved_after_upload) => resolved_after_upload,
                                             Err(resolve_after_err) => {
                                                 return Err(format!(
                                                     "{upload_err}\nResolve after already-uploaded error failed: {resolve_after_err}"
@@ -158,7 +162,7 @@ impl LibraryView {
                                             }
                                         }
                                     } else {
-                                        return Err(upload_err);
+                                        return Err(upload_err.to_string());
                                     }
                                 }
                             }
@@ -169,7 +173,6 @@ impl LibraryView {
                         &owner_for_lookup,
                         &track_for_lookup,
                         &resolved,
-                        default_register_version,
                         false,
                     )
                     .ok_or_else(|| {
@@ -180,7 +183,7 @@ impl LibraryView {
 
                 let grant_resp =
                     svc.content_grant_access(&auth, &uploaded.content_id, &grantee_for_request)?;
-                Ok::<(UploadedTrackRecord, serde_json::Value), String>((uploaded, grant_resp))
+                Ok::<(UploadedTrackRecord, ContentGrant), String>((uploaded, grant_resp))
             })
             .await;
 
@@ -189,7 +192,9 @@ impl LibraryView {
                 this.share_modal_submitting = false;
                 match result {
                     Ok((uploaded_resolved, resp)) => {
-                        if let Err(e) = upsert_uploaded_track_record(uploaded_resolved.clone()) {
+                        if let Err(e) = with_music_db(this.db.as_ref(), |db| {
+                            db.upsert_uploaded_track_record(&uploaded_resolved)
+                        }) {
                             log::error!(
                                 "[Library] failed to persist recovered uploaded track record: {}",
                                 e
@@ -202,16 +207,6 @@ impl LibraryView {
                             );
                         }
 
-                        let tx_hash = resp
-                            .get("txHash")
-                            .and_then(|v| v.as_str())
-                            .or_else(|| resp.get("envelopeId").and_then(|v| v.as_str()))
-                            .unwrap_or("n/a");
-                        let mirror_tx_hash = resp
-                            .get("mirrorTxHash")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("n/a");
-
                         let record = SharedGrantRecord {
                             owner_address,
                             grantee_address: grantee_hex.clone(),
@@ -222,11 +217,13 @@ impl LibraryView {
                             content_id: uploaded_resolved.content_id.clone(),
                             piece_cid: uploaded_resolved.piece_cid.clone(),
                             gateway_url: uploaded_resolved.gateway_url.clone(),
-                            tx_hash: tx_hash.to_string(),
-                            mirror_tx_hash: mirror_tx_hash.to_string(),
+                            tx_hash: resp.envelope_id,
+                            mirror_tx_hash: "n/a".to_string(),
                             shared_at_ms: chrono::Utc::now().timestamp_millis(),
                         };
-                        if let Err(e) = append_shared_grant_record(record) {
+                        if let Err(e) =
+                            with_music_db(this.db.as_ref(), |db| db.upsert_shared_grant_record(&record))
+                        {
                             log::error!("[Library] failed to persist shared grant record: {}", e);
                         }
 
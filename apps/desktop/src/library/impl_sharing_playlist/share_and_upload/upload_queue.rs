@@ -0,0 +1,605 @@
+//! Background upload queue: encrypt → upload → register jobs persisted in `MusicDb`, run a
+//! few at a time, retried with backoff on transient failures and resumable across restarts
+//! through the Load upload journal.
+
+use super::upload::{build_uploaded_track_record, format_upload_bytes};
+use super::*;
+use crate::music_db::{UploadJobInput, UploadJobRow, UploadJobStatus};
+use std::sync::atomic::{AtomicU8, Ordering as AtomicOrdering};
+
+const DEFAULT_UPLOAD_CONCURRENCY: usize = 2;
+const MAX_UPLOAD_CONCURRENCY: usize = 6;
+/// Failed attempts before a job is parked as `failed` until the user retries it.
+const MAX_UPLOAD_ATTEMPTS: u32 = 5;
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+const RETRY_MAX_DELAY_SECS: i64 = 30 * 60;
+
+const CONTROL_CONTINUE: u8 = 0;
+const CONTROL_PAUSE: u8 = 1;
+const CONTROL_CANCEL: u8 = 2;
+
+fn upload_concurrency() -> usize {
+    std::env::var("HEAVEN_UPLOAD_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_UPLOAD_CONCURRENCY)
+        .clamp(1, MAX_UPLOAD_CONCURRENCY)
+}
+
+fn next_upload_attempt_after_failure(failures: u32, now: i64) -> Option<i64> {
+    if failures >= MAX_UPLOAD_ATTEMPTS {
+        return None;
+    }
+    let exponent = failures.saturating_sub(1).min(20);
+    let delay = RETRY_BASE_DELAY_SECS
+        .saturating_mul(1_i64 << exponent)
+        .min(RETRY_MAX_DELAY_SECS);
+    Some(now.saturating_add(delay))
+}
+
+fn upload_control(flag: &AtomicU8) -> UploadControl {
+    match flag.load(AtomicOrdering::Relaxed) {
+        CONTROL_PAUSE => UploadControl::Pause,
+        CONTROL_CANCEL => UploadControl::Cancel,
+        _ => UploadControl::Continue,
+    }
+}
+
+fn track_meta_input_from_job(track: &UploadJobInput) -> TrackMetaInput {
+    TrackMetaInput {
+        title: Some(track.title.clone()),
+        artist: Some(track.artist.clone()),
+        album: Some(track.album.clone()),
+        mbid: track.mbid.clone(),
+        ip_id: track.ip_id.clone(),
+    }
+}
+
+/// The library row a finished job belongs to; jobs outlive scans, so fall back to the
+/// metadata captured at enqueue time.
+fn track_row_for_job(tracks: &[TrackRow], track: &UploadJobInput) -> TrackRow {
+    tracks
+        .iter()
+        .find(|row| row.file_path == track.file_path)
+        .cloned()
+        .unwrap_or_else(|| TrackRow {
+            id: String::new(),
+            title: track.title.clone(),
+            artist: track.artist.clone(),
+            album: track.album.clone(),
+            duration: String::new(),
+            file_path: track.file_path.clone(),
+            mbid: track.mbid.clone(),
+            ip_id: track.ip_id.clone(),
+            cover_path: None,
+            storage_status: StorageStatus::Local,
+        })
+}
+
+impl LibraryView {
+    fn upload_queue_owner() -> Option<(auth::PersistedAuth, String)> {
+        let auth = auth::load_from_disk()?;
+        let owner = auth.wallet_address()?.trim().to_lowercase();
+        (!owner.is_empty()).then_some((auth, owner))
+    }
+
+    pub(in crate::library) fn refresh_upload_jobs(&mut self, cx: &mut Context<Self>) {
+        let (Some(db), Some((_, owner))) = (self.db.as_ref(), Self::upload_queue_owner()) else {
+            self.upload_jobs.clear();
+            return;
+        };
+        let jobs = db
+            .lock()
+            .map_err(|e| format!("upload queue lock failed: {e}"))
+            .and_then(|db| db.upload_jobs(&owner));
+        match jobs {
+            Ok(jobs) => {
+                self.upload_jobs = jobs;
+                cx.notify();
+            }
+            Err(err) => log::warn!("[Upload] job list failed: {}", err),
+        }
+    }
+
+    /// Queue tracks for background upload and start workers. Tracks already stored or
+    /// already queued are skipped.
+    pub(in crate::library) fn enqueue_track_uploads(
+        &mut self,
+        tracks: Vec<TrackRow>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(db) = self.db.clone() else {
+            self.set_status_message("Library database unavailable; upload not queued.", cx);
+            return;
+        };
+        let Some((auth, owner)) = Self::upload_queue_owner() else {
+            self.set_status_message("Sign in from Wallet before uploading.", cx);
+            return;
+        };
+
+        let now = now_epoch_sec() as i64;
+        let mut queued = Vec::new();
+        let mut skipped = 0usize;
+        for track in tracks {
+            if !matches!(
+                track.storage_status,
+                StorageStatus::Local | StorageStatus::Missing
+            ) || track.file_path.is_empty()
+                || !std::path::Path::new(&track.file_path).exists()
+            {
+                skipped += 1;
+                continue;
+            }
+            let input = UploadJobInput {
+                file_path: track.file_path.clone(),
+                title: track.title.clone(),
+                artist: track.artist.clone(),
+                album: track.album.clone(),
+                mbid: track.mbid.clone(),
+                ip_id: track.ip_id.clone(),
+            };
+            let inserted = db
+                .lock()
+                .map_err(|e| format!("upload queue lock failed: {e}"))
+                .and_then(|db| db.enqueue_upload_job(&owner, &input, now));
+            match inserted {
+                Ok(Some(id)) => queued.push((id, track.file_path)),
+                Ok(None) => skipped += 1,
+                Err(err) => {
+                    self.set_status_message(format!("Could not queue upload: {err}"), cx);
+                    return;
+                }
+            }
+        }
+
+        let message = match (queued.len(), skipped) {
+            (0, _) => "Nothing to upload: tracks are already stored or queued.".to_string(),
+            (1, 0) => "Queued 1 upload.".to_string(),
+            (count, 0) => format!("Queued {count} uploads."),
+            (count, skipped) => format!("Queued {count} uploads ({skipped} skipped)."),
+        };
+        self.set_status_message(message, cx);
+        if queued.is_empty() {
+            return;
+        }
+        self.refresh_upload_jobs(cx);
+        self.estimate_upload_jobs(db, auth, queued, cx);
+        self.pump_upload_queue(cx);
+    }
+
+    /// Price queued jobs in the background so the transfers panel can total their cost.
+    fn estimate_upload_jobs(
+        &mut self,
+        db: Arc<Mutex<MusicDb>>,
+        auth: auth::PersistedAuth,
+        jobs: Vec<(i64, String)>,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            smol::unblock(move || {
+                let mut svc = LoadStorageService::new();
+                for (id, file_path) in jobs {
+                    let Ok(meta) = std::fs::metadata(&file_path) else {
+                        continue;
+                    };
+                    let bytes = LoadStorageService::estimated_upload_bytes(meta.len());
+                    let readiness = svc.ensure_upload_ready(Some(&auth), Some(bytes as usize));
+                    if let Ok(db) = db.lock() {
+                        if let Err(err) = db.set_upload_job_estimate(id, readiness.estimated_credit)
+                        {
+                            log::warn!("[Upload] saving estimate failed: {}", err);
+                        }
+                    }
+                }
+            })
+            .await;
+            let _ = this.update(cx, |this, cx| this.refresh_upload_jobs(cx));
+        })
+        .detach();
+    }
+
+    /// Start workers for due jobs until the concurrency limit is reached, then arm a wake-up
+    /// for the earliest backoff.
+    pub(in crate::library) fn pump_upload_queue(&mut self, cx: &mut Context<Self>) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let Some((auth, owner)) = Self::upload_queue_owner() else {
+            return;
+        };
+        let limit = upload_concurrency();
+        while self.upload_job_controls.len() < limit {
+            let claimed = db
+                .lock()
+                .map_err(|e| format!("upload queue lock failed: {e}"))
+                .and_then(|db| db.claim_due_upload_job(&owner, now_epoch_sec() as i64));
+            match claimed {
+                Ok(Some(job)) => self.run_upload_job(job, auth.clone(), cx),
+                Ok(None) => break,
+                Err(err) => {
+                    log::warn!("[Upload] claiming job failed: {}", err);
+                    break;
+                }
+            }
+        }
+        self.refresh_upload_jobs(cx);
+
+        let next_attempt_at = db
+            .lock()
+            .map_err(|e| format!("upload queue lock failed: {e}"))
+            .and_then(|db| db.next_upload_job_attempt_at(&owner));
+        match next_attempt_at {
+            Ok(Some(at)) => self.schedule_upload_queue_wake(at.max(0) as u64, cx),
+            Ok(None) => {}
+            Err(err) => log::warn!("[Upload] queue wake lookup failed: {}", err),
+        }
+    }
+
+    fn schedule_upload_queue_wake(&mut self, at_sec: u64, cx: &mut Context<Self>) {
+        if self
+            .upload_queue_wake_at
+            .is_some_and(|armed| armed <= at_sec)
+        {
+            return;
+        }
+        self.upload_queue_wake_at = Some(at_sec);
+        let delay_secs = at_sec.saturating_sub(now_epoch_sec()).max(1);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            smol::Timer::after(std::time::Duration::from_secs(delay_secs)).await;
+            let _ = this.update(cx, |this, cx| {
+                if this.upload_queue_wake_at == Some(at_sec) {
+                    this.upload_queue_wake_at = None;
+                    this.pump_upload_queue(cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn run_upload_job(
+        &mut self,
+        job: UploadJobRow,
+        auth: auth::PersistedAuth,
+        cx: &mut Context<Self>,
+    ) {
+        let control = Arc::new(AtomicU8::new(CONTROL_CONTINUE));
+        self.upload_job_controls.insert(job.id, control.clone());
+        log::info!(
+            "[Upload] job {} started: '{}' attempt={}",
+            job.id,
+            job.track.title,
+            job.attempts + 1
+        );
+
+        let job_id = job.id;
+        let (progress_tx, progress_rx) = smol::channel::unbounded::<UploadProgress>();
+        let progress_task = cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            while let Ok(update) = progress_rx.recv().await {
+                let _ = this.update(cx, |this, cx| {
+                    this.upload_job_progress.insert(job_id, update);
+                    cx.notify();
+                });
+            }
+        });
+
+        let file_path = job.track.file_path.clone();
+        let track_meta = track_meta_input_from_job(&job.track);
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let worker_control = control.clone();
+            let result = smol::unblock(move || {
+                if !std::path::Path::new(&file_path).exists() {
+                    return Err(LoadStorageError::Other(
+                        "Track file is missing on disk.".to_string(),
+                    ));
+                }
+                let mut svc = LoadStorageService::new();
+                svc.content_encrypt_upload_register(
+                    &auth,
+                    &file_path,
+                    true,
+                    track_meta,
+                    &|update| {
+                        let _ = progress_tx.try_send(update);
+                        upload_control(&worker_control)
+                    },
+                )
+            })
+            .await;
+            progress_task.await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.upload_job_controls.remove(&job.id);
+                this.upload_job_progress.remove(&job.id);
+                this.finish_upload_job(job, upload_control(&control), result, cx);
+                this.pump_upload_queue(cx);
+            });
+        })
+        .detach();
+    }
+
+    fn finish_upload_job(
+        &mut self,
+        job: UploadJobRow,
+        control: UploadControl,
+        result: Result<ContentUpload, LoadStorageError>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let now = now_epoch_sec() as i64;
+        let title = job.track.title.clone();
+        let outcome = match result {
+            Ok(resp) => {
+                let track = track_row_for_job(&self.tracks, &job.track);
+                match build_uploaded_track_record(&job.owner_address, &track, &resp.content, false)
+                {
+                    Some(record) => self.persist_uploaded_record(
+                        &title,
+                        job.track.file_path.clone(),
+                        job.owner_address.clone(),
+                        record,
+                        StorageStatus::Uploaded,
+                    ),
+                    None => log::warn!(
+                        "[Upload] job {} finished with an incomplete response for '{}'",
+                        job.id,
+                        title
+                    ),
+                }
+                self.fetch_storage_status(cx);
+                cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                    status.publish_success("library", format!("Upload complete: \"{title}\"."));
+                });
+                db.lock()
+                    .map_err(|e| format!("upload queue lock failed: {e}"))
+                    .and_then(|db| {
+                        let response = serde_json::to_string(&resp).unwrap_or_default();
+                        db.finish_upload_job(job.id, &response, now)
+                    })
+            }
+            Err(err) => {
+                let parked = match control {
+                    UploadControl::Pause => Some(UploadJobStatus::Paused),
+                    UploadControl::Cancel => Some(UploadJobStatus::Cancelled),
+                    UploadControl::Continue => None,
+                };
+                let summary = summarize_storage_error(&err);
+                // Network hiccups and server-side errors are worth another attempt; missing
+                // files, bad sessions and empty credit balances are not.
+                let next_attempt_at = if err.is_transient() {
+                    next_upload_attempt_after_failure(job.attempts + 1, now)
+                } else {
+                    None
+                };
+                if parked.is_none() {
+                    log::warn!(
+                        "[Upload] job {} failed for '{}' (retry at {:?}): {}",
+                        job.id,
+                        title,
+                        next_attempt_at,
+                        err
+                    );
+                    if next_attempt_at.is_none() {
+                        cx.update_global::<crate::status_center::StatusCenter, _>(|status, _| {
+                            status.publish_error(
+                                "library",
+                                format!("Upload failed for \"{title}\": {summary}"),
+                            );
+                        });
+                    }
+                }
+                db.lock()
+                    .map_err(|e| format!("upload queue lock failed: {e}"))
+                    .and_then(|db| match parked {
+                        Some(status) => db.set_upload_job_status(job.id, status, now),
+                        None => {
+                            db.record_upload_job_failure(job.id, &summary, next_attempt_at, now)
+                        }
+                    })
+            }
+        };
+        if let Err(err) = outcome {
+            log::error!("[Upload] updating job {} failed: {}", job.id, err);
+        }
+    }
+
+    pub(in crate::library) fn pause_upload_job(&mut self, id: i64, cx: &mut Context<Self>) {
+        if let Some(control) = self.upload_job_controls.get(&id) {
+            // The worker stops after its current chunk and parks the job itself.
+            control.store(CONTROL_PAUSE, AtomicOrdering::Relaxed);
+            return;
+        }
+        self.update_upload_job(cx, |db, now| {
+            db.set_upload_job_status(id, UploadJobStatus::Paused, now)
+        });
+    }
+
+    pub(in crate::library) fn cancel_upload_job(&mut self, id: i64, cx: &mut Context<Self>) {
+        if let Some(control) = self.upload_job_controls.get(&id) {
+            control.store(CONTROL_CANCEL, AtomicOrdering::Relaxed);
+            return;
+        }
+        if let Some(job) = self.upload_jobs.iter().find(|job| job.id == id) {
+            LoadStorageService::new().discard_staged_upload(&job.track.file_path);
+        }
+        self.update_upload_job(cx, |db, now| {
+            db.set_upload_job_status(id, UploadJobStatus::Cancelled, now)
+        });
+    }
+
+    /// Resume a paused job, or retry a failed or cancelled one with a fresh attempt budget.
+    pub(in crate::library) fn resume_upload_job(&mut self, id: i64, cx: &mut Context<Self>) {
+        let paused = self
+            .upload_jobs
+            .iter()
+            .any(|job| job.id == id && job.status == UploadJobStatus::Paused);
+        self.update_upload_job(cx, |db, now| db.requeue_upload_job(id, !paused, now));
+        self.pump_upload_queue(cx);
+    }
+
+    pub(in crate::library) fn clear_finished_upload_jobs(&mut self, cx: &mut Context<Self>) {
+        let Some((_, owner)) = Self::upload_queue_owner() else {
+            return;
+        };
+        self.update_upload_job(cx, |db, _| {
+            db.clear_finished_upload_jobs(&owner).map(|_| ())
+        });
+    }
+
+    fn update_upload_job(
+        &mut self,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&MusicDb, i64) -> Result<(), String>,
+    ) {
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let result = db
+            .lock()
+            .map_err(|e| format!("upload queue lock failed: {e}"))
+            .and_then(|db| update(&db, now_epoch_sec() as i64));
+        if let Err(err) = result {
+            self.set_status_message(format!("Could not update upload: {err}"), cx);
+        }
+        self.refresh_upload_jobs(cx);
+    }
+
+    /// Total estimated credits for jobs that still have to upload, and how many of them
+    /// could not be priced.
+    pub(in crate::library) fn pending_upload_cost(&self) -> (f64, usize) {
+        self.upload_jobs
+            .iter()
+            .filter(|job| job.status.is_active())
+            .fold((0.0, 0), |(total, unknown), job| {
+                match job.estimated_credit {
+                    Some(credit) => (total + credit, unknown),
+                    None => (total, unknown + 1),
+                }
+            })
+    }
+
+    /// Running, waiting (queued or paused) and failed job counts for the library hero.
+    pub(in crate::library) fn upload_queue_counts(&self) -> UploadQueueCounts {
+        let mut counts = UploadQueueCounts::default();
+        for job in &self.upload_jobs {
+            match job.status {
+                UploadJobStatus::Running => counts.running += 1,
+                UploadJobStatus::Queued | UploadJobStatus::Paused => counts.waiting += 1,
+                UploadJobStatus::Failed => counts.failed += 1,
+                UploadJobStatus::Done | UploadJobStatus::Cancelled => {}
+            }
+        }
+        counts
+    }
+
+    /// One-line state of a job for the transfers panel.
+    pub(in crate::library) fn upload_job_status_line(&self, job: &UploadJobRow) -> String {
+        match job.status {
+            UploadJobStatus::Running => match self.upload_job_progress.get(&job.id) {
+                Some(progress) if progress.phase == UploadPhase::Uploading => format!(
+                    "Uploading {} / {}",
+                    format_upload_bytes(progress.sent_bytes),
+                    format_upload_bytes(progress.total_bytes)
+                ),
+                Some(progress) if progress.phase == UploadPhase::Finalizing => {
+                    "Finalizing...".to_string()
+                }
+                _ => "Encrypting...".to_string(),
+            },
+            UploadJobStatus::Queued if job.attempts > 0 => format!(
+                "Retrying (attempt {}): {}",
+                job.attempts + 1,
+                job.last_error
+                    .as_deref()
+                    .unwrap_or("previous attempt failed")
+            ),
+            UploadJobStatus::Queued => "Queued".to_string(),
+            UploadJobStatus::Paused => "Paused".to_string(),
+            UploadJobStatus::Done => "Uploaded".to_string(),
+            UploadJobStatus::Cancelled => "Cancelled".to_string(),
+            UploadJobStatus::Failed => format!(
+                "Failed: {}",
+                job.last_error.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
+
+    pub(in crate::library) fn open_transfers_panel(&mut self, cx: &mut Context<Self>) {
+        self.transfers_panel_open = true;
+        self.refresh_upload_jobs(cx);
+        self.fetch_storage_status(cx);
+    }
+
+    pub(in crate::library) fn close_transfers_panel(&mut self, cx: &mut Context<Self>) {
+        self.transfers_panel_open = false;
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::rpc::HttpError;
+
+    #[test]
+    fn only_transient_failures_are_retried_with_capped_backoff() {
+        let paused = LoadStorageError::Network("Load POST failed: connection reset".to_string())
+            .context("Upload paused at 4000/10250 bytes");
+        assert!(paused.is_transient());
+        assert!(LoadStorageError::from(HttpError::Status(503, "busy".to_string())).is_transient());
+        assert!(!LoadStorageError::InsufficientCredit("below minimum".to_string()).is_transient());
+        assert!(
+            !LoadStorageError::Other("Track file is missing on disk.".to_string()).is_transient()
+        );
+
+        let now = 1_000;
+        assert_eq!(next_upload_attempt_after_failure(1, now), Some(now + 30));
+        assert_eq!(next_upload_attempt_after_failure(2, now), Some(now + 60));
+        assert_eq!(
+            next_upload_attempt_after_failure(MAX_UPLOAD_ATTEMPTS - 1, now),
+            Some(now + 240)
+        );
+        assert_eq!(
+            next_upload_attempt_after_failure(MAX_UPLOAD_ATTEMPTS, now),
+            None
+        );
+    }
+
+    #[test]
+    fn queue_skips_duplicate_files_and_requeues_interrupted_jobs() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-upload-queue-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).unwrap();
+        let track = UploadJobInput {
+            file_path: "/music/a.flac".to_string(),
+            title: "A".to_string(),
+            artist: "Artist".to_string(),
+            album: String::new(),
+            mbid: None,
+            ip_id: None,
+        };
+        let id = db.enqueue_upload_job("0xABC", &track, 10).unwrap().unwrap();
+        assert_eq!(db.enqueue_upload_job("0xabc", &track, 11).unwrap(), None);
+
+        let claimed = db.claim_due_upload_job("0xabc", 10).unwrap().unwrap();
+        assert_eq!(claimed.id, id);
+        assert!(db.claim_due_upload_job("0xabc", 10).unwrap().is_none());
+
+        assert_eq!(db.requeue_interrupted_upload_jobs(20).unwrap(), 1);
+        let jobs = db.upload_jobs("0xabc").unwrap();
+        assert_eq!(jobs[0].status, UploadJobStatus::Queued);
+
+        db.claim_due_upload_job("0xabc", 20).unwrap().unwrap();
+        db.finish_upload_job(id, "{}", 21).unwrap();
+        assert!(db
+            .enqueue_upload_job("0xabc", &track, 22)
+            .unwrap()
+            .is_some());
+        assert_eq!(db.clear_finished_upload_jobs("0xabc").unwrap(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
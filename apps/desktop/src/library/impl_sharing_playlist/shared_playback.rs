@@ -48,58 +48,38 @@ impl LibraryView {
                 this.shared_play_busy = false;
                 match result {
                     Ok(payload) => {
-                        let local_path = payload
-                            .get("localPath")
-                            .and_then(|v| v.as_str())
-                            .map(str::to_string);
-                        match local_path {
-                            Some(path) => {
-                                audio.play(&path, None, Some(record_for_ui.artist.clone()), None);
-                                this.active_shared_playback = Some(ActiveSharedPlayback {
-                                    content_id: record_for_ui.content_id.clone(),
-                                    title: record_for_ui.title.clone(),
-                                    artist: record_for_ui.artist.clone(),
-                                    album: record_for_ui.album.clone(),
-                                    local_path: path.clone(),
-                                });
-                                this.active_track_path = None;
-                                this.playback_queue_paths.clear();
-                                this.active_queue_pos = None;
-                                this.track_started_at_sec = Some(now_epoch_sec());
-                                let cache_hit = payload
-                                    .get("cacheHit")
-                                    .and_then(|v| v.as_bool())
-                                    .unwrap_or(false);
-                                if cache_hit {
-                                    this.set_status_message(
-                                        format!(
-                                            "Playing shared track \"{}\" (cached decrypt).",
-                                            record_for_ui.title
-                                        ),
-                                        cx,
-                                    );
-                                } else {
-                                    this.set_status_message(
-                                        format!(
-                                            "Playing shared track \"{}\".",
-                                            record_for_ui.title
-                                        ),
-                                        cx,
-                                    );
-                                }
-                            }
-                            None => {
-                                this.set_status_message(
-                                    "Shared decrypt succeeded but no local file was produced.",
-                                    cx,
-                                );
-                            }
+                        let path = payload.local_path.to_string_lossy().to_string();
+                        audio.play(&path, None, Some(record_for_ui.artist.clone()), None);
+                        this.active_shared_playback = Some(ActiveSharedPlayback {
+                            content_id: record_for_ui.content_id.clone(),
+                            title: record_for_ui.title.clone(),
+                            artist: record_for_ui.artist.clone(),
+                            album: record_for_ui.album.clone(),
+                            local_path: path,
+                        });
+                        this.active_track_path = None;
+                        this.playback_queue_paths.clear();
+                        this.active_queue_pos = None;
+                        this.track_started_at_sec = Some(now_epoch_sec());
+                        if payload.cache_hit {
+                            this.set_status_message(
+                                format!(
+                                    "Playing shared track \"{}\" (cached decrypt).",
+                                    record_for_ui.title
+                                ),
+                                cx,
+                            );
+                        } else {
+                            this.set_status_message(
+                                format!("Playing shared track \"{}\".", record_for_ui.title),
+                                cx,
+                            );
                         }
                     }
                     Err(err) => {
                         log::error!("[Library] shared playback failed: {}", err);
                         this.set_status_message(
-                            format!("Shared playback failed: {}", summarize_status_error(&err)),
+                            format!("Shared playback failed: {}", summarize_storage_error(&err)),
                             cx,
                         );
                     }
@@ -169,14 +149,7 @@ impl LibraryView {
                     Some(&record_for_request.owner_address),
                     Some(&record_for_request.grantee_address),
                 )?;
-                let local_path = payload
-                    .get("localPath")
-                    .and_then(|v| v.as_str())
-                    .map(str::to_string)
-                    .ok_or_else(|| {
-                        "Shared decrypt succeeded but no local file was produced.".to_string()
-                    })?;
-                let source_path = PathBuf::from(&local_path);
+                let source_path = payload.local_path;
                 if !source_path.exists() {
                     return Err(format!(
                         "Decrypted shared file is missing on disk: {}",
@@ -219,10 +192,7 @@ impl LibraryView {
                     Err(e) => Some(format!("db lock: {e}")),
                 };
 
-                let cache_hit = payload
-                    .get("cacheHit")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
+                let cache_hit = payload.cache_hit;
                 Ok::<(String, String, bool, bool, String, Option<String>), String>((
                     record_for_request.title.clone(),
                     target_path.to_string_lossy().to_string(),
@@ -1,44 +1,5 @@
 use super::*;
 
-fn decode_subgraph_bytes_to_utf8(raw: &str) -> String {
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return String::new();
-    }
-    if !trimmed.starts_with("0x") {
-        return trimmed.to_string();
-    }
-
-    let body = &trimmed[2..];
-    if body.is_empty() || body.len() % 2 != 0 {
-        return trimmed.to_string();
-    }
-
-    let mut bytes = Vec::<u8>::with_capacity(body.len() / 2);
-    let mut i = 0usize;
-    while i + 2 <= body.len() {
-        match u8::from_str_radix(&body[i..i + 2], 16) {
-            Ok(b) => bytes.push(b),
-            Err(_) => return trimmed.to_string(),
-        }
-        i = i.saturating_add(2);
-    }
-
-    match String::from_utf8(bytes) {
-        Ok(s) => s.trim_matches('\u{0}').trim().to_string(),
-        Err(_) => trimmed.to_string(),
-    }
-}
-
-fn parse_i64_field(value: Option<&serde_json::Value>) -> Option<i64> {
-    match value {
-        Some(v) if v.is_i64() => v.as_i64(),
-        Some(v) if v.is_u64() => v.as_u64().and_then(|n| i64::try_from(n).ok()),
-        Some(v) if v.is_string() => v.as_str()?.trim().parse::<i64>().ok(),
-        _ => None,
-    }
-}
-
 impl LibraryView {
     pub(in crate::library) fn refresh_uploaded_index_from_auth(&mut self) {
         let owner = auth::load_from_disk()
@@ -58,7 +19,11 @@ impl LibraryView {
             return;
         }
 
-        let records = load_uploaded_track_records_for_owner(&owner);
+        let records = with_music_db(self.db.as_ref(), |db| db.uploaded_track_records(&owner))
+            .unwrap_or_else(|e| {
+                log::warn!("[Library] failed to load uploaded track records: {}", e);
+                Vec::new()
+            });
         self.uploaded_index = records
             .into_iter()
             .map(|r| (r.file_path.clone(), r))
@@ -81,6 +46,9 @@ impl LibraryView {
                 StorageStatus::Permanent => StorageStatus::Permanent,
                 _ => match self.uploaded_index.get(&track.file_path) {
                     Some(record) if record.saved_forever => StorageStatus::Permanent,
+                    Some(record) if record.sync_state == RecordSyncState::Drifted => {
+                        StorageStatus::Missing
+                    }
                     Some(_) => StorageStatus::Uploaded,
                     None => StorageStatus::Local,
                 },
@@ -118,9 +86,43 @@ impl LibraryView {
         if changed {
             self.tracks = Arc::new(next_tracks);
         }
+
+        // Playlist detail rows carry their own copy of the status.
+        let Some(index) = self
+            .tracks
+            .iter()
+            .position(|track| track.file_path == file_path)
+        else {
+            return;
+        };
+        for row in self.playlist_detail_tracks.iter_mut().chain(
+            self.playlist_detail_cache
+                .values_mut()
+                .flat_map(|entry| entry.tracks.iter_mut()),
+        ) {
+            if row.local_track_index == Some(index) {
+                row.storage_status = status;
+            }
+        }
     }
 
+    /// Drop "Shared With Me" rows cached for a wallet that is no longer signed in.
+    pub(in crate::library) fn clear_shared_records_if_owner_changed(&mut self) {
+        let grantee = auth::load_from_disk()
+            .and_then(|a| a.wallet_address().map(|value| value.to_lowercase()));
+        if self.shared_records_for.is_some() && self.shared_records_for != grantee {
+            self.shared_records_for = None;
+            self.shared_records.clear();
+        }
+    }
+
+    /// Show the local "Shared With Me" records, then sync them against the subgraph.
     pub(in crate::library) fn refresh_shared_records_for_auth(&mut self, cx: &mut Context<Self>) {
+        self.reload_shared_records(cx);
+        self.run_record_sync(cx);
+    }
+
+    pub(in crate::library) fn reload_shared_records(&mut self, cx: &mut Context<Self>) {
         let grantee = auth::load_from_disk()
             .and_then(|a| a.wallet_address().map(|value| value.to_string()))
             .unwrap_or_default()
@@ -132,206 +134,17 @@ impl LibraryView {
             return;
         }
 
-        // Seed from local cache for offline/optimistic UX.
-        let mut local_records = load_shared_grant_records_for_grantee(&grantee);
-        local_records.sort_by(|a, b| b.shared_at_ms.cmp(&a.shared_at_ms));
-        self.shared_records = local_records.clone();
-        self.shared_records_for = Some(grantee.clone());
-
-        // Background: reconcile with subgraph grants so "Shared With Me" works cross-device.
-        let local_for_merge = local_records.clone();
-        let grantee_for_subgraph = grantee.clone();
-        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
-            let grantee = grantee_for_subgraph;
-            let grantee_for_fetch = grantee.clone();
-            let fetched: Result<Vec<SharedGrantRecord>, String> = smol::unblock(move || {
-                let gql_grantee = escape_gql(&grantee_for_fetch);
-                // Keep responses bounded; if this becomes a problem, paginate with `skip`.
-                let limit = 200_usize;
-                let query = format!(
-                    "{{ accessGrants(where: {{ grantee: \"{gql_grantee}\", granted: true }}, orderBy: updatedAt, orderDirection: desc, first: {limit}) {{ updatedAt grantee granted content {{ id owner pieceCid trackId }} }} }}"
-                );
-                let payload = http_post_json(
-                    &subgraph_music_social_url(),
-                    serde_json::json!({
-                        "query": query,
-                    }),
-                )?;
-
-                if let Some(errors) = payload.get("errors") {
-                    return Err(format!("Subgraph query failed: {errors}"));
-                }
-
-                let entries = payload
-                    .get("data")
-                    .and_then(|v| v.get("accessGrants"))
-                    .and_then(|v| v.as_array())
-                    .cloned()
-                    .unwrap_or_default();
-
-                let mut out = Vec::<SharedGrantRecord>::with_capacity(entries.len());
-                for entry in entries {
-                    let Some(content) = entry.get("content").and_then(|v| v.as_object()) else {
-                        continue;
-                    };
-                    let Some(content_id) = content.get("id").and_then(|v| v.as_str()) else {
-                        continue;
-                    };
-                    let content_id = content_id.trim().to_lowercase();
-                    if content_id.is_empty() {
-                        continue;
-                    }
-
-                    let owner_address = content
-                        .get("owner")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or_default()
-                        .trim()
-                        .to_lowercase();
-                    let grantee_address = entry
-                        .get("grantee")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or(grantee_for_fetch.as_str())
-                        .trim()
-                        .to_lowercase();
-                    let piece_cid = content
-                        .get("pieceCid")
-                        .and_then(|v| v.as_str())
-                        .map(decode_subgraph_bytes_to_utf8)
-                        .unwrap_or_default();
-                    if piece_cid.is_empty() {
-                        // Without a pieceCid, decrypt can't proceed; skip the record.
-                        continue;
-                    }
-                    let track_id = content
-                        .get("trackId")
-                        .and_then(|v| v.as_str())
-                        .map(|v| v.trim().to_lowercase())
-                        .filter(|v| !v.is_empty());
-
-                    let updated_at = parse_i64_field(entry.get("updatedAt"));
-                    let now_ms = chrono::Utc::now().timestamp_millis();
-                    let shared_at_ms = updated_at
-                        .map(|raw| {
-                            // The subgraph stores timestamps as seconds (BigInt).
-                            if raw > 1_000_000_000_000 {
-                                raw
-                            } else {
-                                raw.saturating_mul(1000)
-                            }
-                        })
-                        .unwrap_or(now_ms);
-
-                    out.push(SharedGrantRecord {
-                        owner_address,
-                        grantee_address,
-                        title: String::new(),
-                        artist: String::new(),
-                        album: String::new(),
-                        track_id,
-                        content_id,
-                        piece_cid,
-                        gateway_url: String::new(),
-                        tx_hash: "n/a".to_string(),
-                        mirror_tx_hash: "n/a".to_string(),
-                        shared_at_ms,
-                    });
-                }
-                Ok(out)
-            })
-            .await;
-
-            let _ = this.update(cx, |this, cx| {
-                let remote_records = match fetched {
-                    Ok(records) => records,
-                    Err(err) => {
-                        log::warn!("[Library] shared grants fetch failed: {}", err);
-                        // Still attempt enrichment of whatever we have locally.
-                        this.spawn_shared_metadata_enrichment(grantee.clone(), local_for_merge, cx);
-                        cx.notify();
-                        return;
-                    }
-                };
-
-                if this.shared_records_for.as_deref() != Some(grantee.as_str()) {
-                    return;
-                }
-
-                let mut by_content = HashMap::<String, SharedGrantRecord>::new();
-                for record in remote_records {
-                    by_content.insert(record.content_id.to_lowercase(), record);
-                }
-                for record in local_for_merge {
-                    let key = record.content_id.to_lowercase();
-                    match by_content.get_mut(&key) {
-                        Some(existing) => {
-                            if existing.owner_address.trim().is_empty()
-                                && !record.owner_address.trim().is_empty()
-                            {
-                                existing.owner_address = record.owner_address.clone();
-                            }
-                            if existing.grantee_address.trim().is_empty()
-                                && !record.grantee_address.trim().is_empty()
-                            {
-                                existing.grantee_address = record.grantee_address.clone();
-                            }
-                            if existing.title.trim().is_empty() && !record.title.trim().is_empty() {
-                                existing.title = record.title.clone();
-                            }
-                            if existing.artist.trim().is_empty()
-                                && !record.artist.trim().is_empty()
-                            {
-                                existing.artist = record.artist.clone();
-                            }
-                            if existing.album.trim().is_empty() && !record.album.trim().is_empty() {
-                                existing.album = record.album.clone();
-                            }
-                            if existing.track_id.is_none() && record.track_id.is_some() {
-                                existing.track_id = record.track_id.clone();
-                            }
-                            if existing.piece_cid.trim().is_empty()
-                                && !record.piece_cid.trim().is_empty()
-                            {
-                                existing.piece_cid = record.piece_cid.clone();
-                            }
-                            if existing.gateway_url.trim().is_empty()
-                                && !record.gateway_url.trim().is_empty()
-                            {
-                                existing.gateway_url = record.gateway_url.clone();
-                            }
-                            if existing.tx_hash.trim().is_empty()
-                                && !record.tx_hash.trim().is_empty()
-                            {
-                                existing.tx_hash = record.tx_hash.clone();
-                            }
-                            if existing.mirror_tx_hash.trim().is_empty()
-                                && !record.mirror_tx_hash.trim().is_empty()
-                            {
-                                existing.mirror_tx_hash = record.mirror_tx_hash.clone();
-                            }
-                            if record.shared_at_ms > existing.shared_at_ms {
-                                existing.shared_at_ms = record.shared_at_ms;
-                            }
-                        }
-                        None => {
-                            by_content.insert(key, record);
-                        }
-                    }
-                }
-
-                let mut merged = by_content.into_values().collect::<Vec<_>>();
-                merged.sort_by(|a, b| b.shared_at_ms.cmp(&a.shared_at_ms));
-                this.shared_records = merged.clone();
-
-                if let Err(err) = upsert_shared_grant_records_for_grantee(&grantee, &merged) {
-                    log::warn!("[Library] failed to persist shared records: {}", err);
-                }
-
-                this.spawn_shared_metadata_enrichment(grantee.clone(), merged, cx);
-                cx.notify();
-            });
+        let records = with_music_db(self.db.as_ref(), |db| {
+            db.shared_grant_records_for_grantee(&grantee)
         })
-        .detach();
+        .unwrap_or_else(|e| {
+            log::warn!("[Library] failed to load shared records: {}", e);
+            Vec::new()
+        });
+        self.shared_records = records.clone();
+        self.shared_records_for = Some(grantee.clone());
+        self.spawn_shared_metadata_enrichment(grantee, records, cx);
+        cx.notify();
     }
 
     fn spawn_shared_metadata_enrichment(
@@ -370,40 +183,32 @@ impl LibraryView {
                     };
 
                     let mut record_changed = false;
-                    if let Some(track_id) = metadata.get("trackId").and_then(|v| v.as_str()) {
-                        let norm = track_id.trim().to_lowercase();
-                        if !norm.is_empty()
-                            && record
-                                .track_id
-                                .as_deref()
-                                .unwrap_or_default()
-                                .to_lowercase()
-                                != norm
-                        {
-                            record.track_id = Some(norm);
-                            record_changed = true;
-                        }
+                    let norm = metadata.track_id.trim().to_lowercase();
+                    if !norm.is_empty()
+                        && record
+                            .track_id
+                            .as_deref()
+                            .unwrap_or_default()
+                            .to_lowercase()
+                            != norm
+                    {
+                        record.track_id = Some(norm);
+                        record_changed = true;
                     }
-                    if let Some(title) = metadata.get("title").and_then(|v| v.as_str()) {
-                        let title = title.trim();
-                        if !title.is_empty() && record.title != title {
-                            record.title = title.to_string();
-                            record_changed = true;
-                        }
+                    let title = metadata.title.trim();
+                    if !title.is_empty() && record.title != title {
+                        record.title = title.to_string();
+                        record_changed = true;
                     }
-                    if let Some(artist) = metadata.get("artist").and_then(|v| v.as_str()) {
-                        let artist = artist.trim();
-                        if !artist.is_empty() && record.artist != artist {
-                            record.artist = artist.to_string();
-                            record_changed = true;
-                        }
+                    let artist = metadata.artist.trim();
+                    if !artist.is_empty() && record.artist != artist {
+                        record.artist = artist.to_string();
+                        record_changed = true;
                     }
-                    if let Some(album) = metadata.get("album").and_then(|v| v.as_str()) {
-                        let album = album.trim();
-                        if record.album != album {
-                            record.album = album.to_string();
-                            record_changed = true;
-                        }
+                    let album = metadata.album.trim();
+                    if record.album != album {
+                        record.album = album.to_string();
+                        record_changed = true;
                     }
 
                     if record_changed {
@@ -419,15 +224,27 @@ impl LibraryView {
                 match enriched {
                     Ok((records, changed)) => {
                         if changed && this.shared_records_for.as_deref() == Some(grantee.as_str()) {
-                            if let Err(err) =
-                                upsert_shared_grant_records_for_grantee(&grantee, &records)
-                            {
-                                log::warn!(
-                                    "[Library] failed to persist enriched shared records: {}",
-                                    err
-                                );
+                            // A sync may have dropped or repointed grants meanwhile; only
+                            // carry the metadata over to the rows still listed.
+                            for record in records {
+                                let Some(existing) = this.shared_records.iter_mut().find(|r| {
+                                    r.content_id.eq_ignore_ascii_case(&record.content_id)
+                                }) else {
+                                    continue;
+                                };
+                                existing.track_id = record.track_id;
+                                existing.title = record.title;
+                                existing.artist = record.artist;
+                                existing.album = record.album;
+                                if let Err(err) = with_music_db(this.db.as_ref(), |db| {
+                                    db.upsert_shared_grant_record(existing)
+                                }) {
+                                    log::warn!(
+                                        "[Library] failed to persist enriched shared record: {}",
+                                        err
+                                    );
+                                }
                             }
-                            this.shared_records = records;
                         }
                     }
                     Err(err) => {
@@ -77,6 +77,13 @@ pub(in crate::library) fn render_album_detail_page(
             .and_then(|stats| stats.image_path.clone())
     });
 
+    let local_album_tracks: Vec<TrackRow> = album_indices
+        .iter()
+        .filter_map(|index| tracks.get(*index))
+        .filter(|track| matches!(track.storage_status, StorageStatus::Local))
+        .cloned()
+        .collect();
+
     let row_count = album_indices.len();
     let row_indices = Arc::new(album_indices);
     let tracks_snapshot = tracks.clone();
@@ -100,6 +107,17 @@ pub(in crate::library) fn render_album_detail_page(
             &subtitle,
             &hero_cover_path,
         )))
+        .when(!local_album_tracks.is_empty(), |el| {
+            el.child(div().px_6().pb_4().h_flex().child(hero_button(
+                "album-upload-all",
+                "icons/cloud.svg",
+                "Upload Album",
+                true,
+                cx.listener(move |this, _, _w, cx| {
+                    this.enqueue_track_uploads(local_album_tracks.clone(), cx);
+                }),
+            )))
+        })
         .child(if row_count == 0 {
             div()
                 .flex_1()
@@ -87,6 +87,16 @@ pub(in crate::library) fn render_playlist_detail_page(
     let playlist_id_for_share = playlist_id.clone();
     let playlist_name_for_share = playlist_name.clone();
     let share_disabled = upload_busy || detail_loading || row_count == 0;
+    let archive_entity = entity.clone();
+    let playlist_name_for_archive = playlist_name.clone();
+    let archive_tracks = playlist_tracks_snapshot
+        .iter()
+        .filter_map(|track| track.local_track_index)
+        .filter_map(|index| tracks.get(index))
+        .filter(|track| track.storage_status != StorageStatus::Permanent)
+        .cloned()
+        .collect::<Vec<_>>();
+    let archive_disabled = upload_busy || detail_loading || archive_tracks.is_empty();
     let cover_visibility = playlist_summary
         .as_ref()
         .map(|pl| pl.visibility)
@@ -167,6 +177,24 @@ pub(in crate::library) fn render_playlist_detail_page(
                                 }
                             }),
                     )
+                    .item(
+                        PopupMenuItem::new("Save Playlist Forever")
+                            .disabled(archive_disabled)
+                            .on_click({
+                                let archive_entity = archive_entity.clone();
+                                let playlist_name_for_archive = playlist_name_for_archive.clone();
+                                let archive_tracks = archive_tracks.clone();
+                                move |_, _, cx| {
+                                    let _ = archive_entity.update(cx, |this, cx| {
+                                        this.save_playlist_forever(
+                                            playlist_name_for_archive.clone(),
+                                            archive_tracks.clone(),
+                                            cx,
+                                        );
+                                    });
+                                }
+                            }),
+                    )
                     .separator()
                     .item(PopupMenuItem::new("Delete Playlist").on_click({
                         let delete_entity = delete_entity.clone();
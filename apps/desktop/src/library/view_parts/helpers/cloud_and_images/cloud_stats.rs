@@ -1,52 +1,22 @@
 use super::*;
 
+use crate::music_db::{ScrobbleScope, ScrobbledTrackStats};
+use crate::profile::scrobbles_feed::sync_scrobble_history;
+
+/// Detail pages reuse the stored play counts when the last sync is this recent.
+const CLOUD_STATS_SYNC_MAX_AGE_SECS: u64 = 300;
+
 pub(in crate::library) fn fetch_artist_cloud_stats(
+    db: Option<&Mutex<MusicDb>>,
     artist: &str,
     tracks: &[TrackRow],
 ) -> Result<ArtistCloudStats, String> {
-    let escaped_artist = escape_gql(artist);
-    let query = format!(
-        "{{ tracks(where: {{ artist_contains_nocase: \"{}\" }}, first: 300, orderBy: registeredAt, orderDirection: desc) {{ id artist scrobbles(first: 1000) {{ id user }} }} }}",
-        escaped_artist
-    );
-    let payload = http_post_json(
-        &subgraph_music_social_url(),
-        serde_json::json!({ "query": query }),
-    )?;
-    let rows = payload
-        .get("data")
-        .and_then(|v| v.get("tracks"))
-        .and_then(Value::as_array)
-        .cloned()
-        .unwrap_or_default();
-
     let target_artist = normalize_artist_name(artist);
-    let mut track_scrobbles = HashMap::<String, usize>::new();
-
-    for row in rows {
-        let row_artist = row
-            .get("artist")
-            .and_then(Value::as_str)
-            .unwrap_or_default();
-        if !artist_matches_target(row_artist, &target_artist) {
-            continue;
-        }
-
-        let track_id = row
-            .get("id")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .trim();
-        let scrobbles = row
-            .get("scrobbles")
-            .and_then(Value::as_array)
-            .cloned()
-            .unwrap_or_default();
-        let play_count = scrobbles.len();
-        if !track_id.is_empty() {
-            track_scrobbles.insert(track_id.to_string(), play_count);
-        }
-    }
+    let track_scrobbles = scrobbled_track_stats(db)?
+        .into_iter()
+        .filter(|stats| artist_matches_target(&stats.meta.artist, &target_artist))
+        .map(|stats| (stats.meta.track_id, stats.plays as usize))
+        .collect();
 
     let image_path = resolve_artist_image_path(artist, tracks, None);
 
@@ -58,65 +28,21 @@ pub(in crate::library) fn fetch_artist_cloud_stats(
 }
 
 pub(in crate::library) fn fetch_album_cloud_stats(
+    db: Option<&Mutex<MusicDb>>,
     artist: &str,
     album: &str,
     tracks: &[TrackRow],
 ) -> Result<AlbumCloudStats, String> {
-    let where_clause = if album.trim().is_empty() {
-        format!(
-            "artist_contains_nocase: \"{}\"",
-            escape_gql(&sanitize_detail_value(artist.to_string(), "Unknown Artist"))
-        )
-    } else {
-        format!("album_contains_nocase: \"{}\"", escape_gql(album))
-    };
-    let query = format!(
-        "{{ tracks(where: {{ {} }}, first: 300, orderBy: registeredAt, orderDirection: desc) {{ id artist album scrobbles(first: 1000) {{ id user }} }} }}",
-        where_clause
-    );
-    let payload = http_post_json(
-        &subgraph_music_social_url(),
-        serde_json::json!({ "query": query }),
-    )?;
-    let rows = payload
-        .get("data")
-        .and_then(|v| v.get("tracks"))
-        .and_then(Value::as_array)
-        .cloned()
-        .unwrap_or_default();
-
     let target_artist = normalize_artist_name(artist);
     let target_album_variants = normalize_album_variants(album);
-    let mut track_scrobbles = HashMap::<String, usize>::new();
-
-    for row in rows {
-        let row_artist = row
-            .get("artist")
-            .and_then(Value::as_str)
-            .unwrap_or_default();
-        let row_album = row.get("album").and_then(Value::as_str).unwrap_or_default();
-        if !artist_matches_target(row_artist, &target_artist) {
-            continue;
-        }
-        if !album_matches_target(row_album, &target_album_variants) {
-            continue;
-        }
-
-        let track_id = row
-            .get("id")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .trim();
-        let scrobbles = row
-            .get("scrobbles")
-            .and_then(Value::as_array)
-            .cloned()
-            .unwrap_or_default();
-        let play_count = scrobbles.len();
-        if !track_id.is_empty() {
-            track_scrobbles.insert(track_id.to_string(), play_count);
-        }
-    }
+    let track_scrobbles = scrobbled_track_stats(db)?
+        .into_iter()
+        .filter(|stats| {
+            artist_matches_target(&stats.meta.artist, &target_artist)
+                && album_matches_target(&stats.meta.album, &target_album_variants)
+        })
+        .map(|stats| (stats.meta.track_id, stats.plays as usize))
+        .collect();
 
     let image_path = resolve_album_image_path(artist, album, tracks, None);
 
@@ -127,3 +53,16 @@ pub(in crate::library) fn fetch_album_cloud_stats(
         track_scrobbles,
     })
 }
+
+/// Play counts from the local scrobble store. The sync is best effort so detail pages still
+/// show stored counts offline.
+fn scrobbled_track_stats(db: Option<&Mutex<MusicDb>>) -> Result<Vec<ScrobbledTrackStats>, String> {
+    let db = db.ok_or("Music database is unavailable.")?;
+    if let Err(err) = sync_scrobble_history(db, &ScrobbleScope::All, CLOUD_STATS_SYNC_MAX_AGE_SECS)
+    {
+        log::warn!("[Library] scrobble history sync failed: {}", err);
+    }
+    db.lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?
+        .scrobbled_track_stats()
+}
@@ -1,4 +1,6 @@
 use super::*;
+use crate::load_storage::{CacheKind, ContentCache};
+use crate::shared::gateways::GatewayPool;
 
 pub(in crate::library) fn resolve_artist_image_path(
     artist: &str,
@@ -181,28 +183,33 @@ pub(in crate::library) fn cache_remote_image(
     namespace: &str,
     cache_key: &str,
 ) -> Option<String> {
-    let cache_dir = app_data_dir().join("detail-images").join(namespace);
-    if fs::create_dir_all(&cache_dir).is_err() {
-        return None;
+    let cache = ContentCache::active();
+    let key = format!("{namespace}:{cache_key}");
+    if let Some(path) = cache.lookup(CacheKind::Cover, &key) {
+        return Some(path.to_string_lossy().to_string());
     }
-    let ext = guess_image_extension(url);
-    let file_name = format!(
-        "{}.{}",
-        stable_cache_hash(&format!("{namespace}:{cache_key}")),
-        ext
-    );
-    let cache_path = cache_dir.join(file_name);
-    if cache_path.exists() {
-        return Some(cache_path.to_string_lossy().to_string());
-    }
-    let bytes = http_get_bytes(url).ok()?;
-    if bytes.is_empty() {
-        return None;
-    }
-    if fs::write(&cache_path, bytes).is_err() {
-        return None;
+    let file_name = format!("{}.{}", stable_cache_hash(&key), guess_image_extension(url));
+    // Artwork fetched before the managed cache is adopted instead of fetched again.
+    let legacy_path = app_data_dir()
+        .join("detail-images")
+        .join(namespace)
+        .join(&file_name);
+    let cached = if legacy_path.exists() {
+        cache.insert_file(CacheKind::Cover, &key, &legacy_path, &file_name)
+    } else {
+        let bytes = GatewayPool::shared().get_bytes(url).ok()?;
+        if bytes.is_empty() {
+            return None;
+        }
+        cache.insert_bytes(CacheKind::Cover, &key, &bytes, &file_name)
+    };
+    match cached {
+        Ok(path) => Some(path.to_string_lossy().to_string()),
+        Err(err) => {
+            log::warn!("[Library] failed caching {namespace} image: {err}");
+            None
+        }
     }
-    Some(cache_path.to_string_lossy().to_string())
 }
 
 pub(in crate::library) fn stable_cache_hash(input: &str) -> String {
@@ -97,14 +97,6 @@ pub(in crate::library) fn normalize_alnum_phrase(value: &str) -> String {
     out.trim().to_string()
 }
 
-pub(in crate::library) fn escape_gql(value: &str) -> String {
-    value
-        .replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', " ")
-        .replace('\r', " ")
-}
-
 pub(in crate::library) fn resolver_url() -> String {
     env::var("HEAVEN_RESOLVER_URL")
         .ok()
@@ -117,24 +109,6 @@ pub(in crate::library) fn resolver_url() -> String {
         .to_string()
 }
 
-pub(in crate::library) fn subgraph_music_social_url() -> String {
-    env::var("SUBGRAPH_MUSIC_SOCIAL_URL")
-        .ok()
-        .map(|value| value.trim().trim_end_matches('/').to_string())
-        .filter(|value| !value.is_empty())
-        .or_else(|| {
-            env::var("SUBGRAPH_BASE_URL")
-                .ok()
-                .map(|value| value.trim().trim_end_matches('/').to_string())
-                .filter(|value| !value.is_empty())
-                .map(|base| format!("{base}/subgraphs/name/dotheaven/music-social-tempo"))
-        })
-        .filter(|value| !value.is_empty())
-        .unwrap_or_else(|| {
-            panic!("Missing SUBGRAPH_MUSIC_SOCIAL_URL (or SUBGRAPH_BASE_URL)")
-        })
-}
-
 pub(in crate::library) fn http_get_json(url: &str) -> Result<Value, String> {
     let request = ureq::get(url).config().http_status_as_error(false).build();
     let mut response = request
@@ -148,41 +122,3 @@ pub(in crate::library) fn http_get_json(url: &str) -> Result<Value, String> {
     serde_json::from_str(&body)
         .map_err(|err| format!("HTTP GET {url} returned invalid JSON: {err}; body={body}"))
 }
-
-pub(in crate::library) fn http_post_json(url: &str, payload: Value) -> Result<Value, String> {
-    let request = ureq::post(url)
-        .header("Content-Type", "application/json")
-        .config()
-        .http_status_as_error(false)
-        .build();
-    let mut response = request
-        .send_json(payload)
-        .map_err(|err| format!("HTTP POST failed ({url}): {err}"))?;
-    let status = response.status().as_u16();
-    let body = response.body_mut().read_to_string().unwrap_or_default();
-    if status >= 400 {
-        return Err(format!("HTTP POST {url} failed ({status}): {body}"));
-    }
-    serde_json::from_str(&body)
-        .map_err(|err| format!("HTTP POST {url} returned invalid JSON: {err}; body={body}"))
-}
-
-pub(in crate::library) fn http_get_bytes(url: &str) -> Result<Vec<u8>, String> {
-    let request = ureq::get(url).config().http_status_as_error(false).build();
-    let mut response = request
-        .call()
-        .map_err(|err| format!("HTTP GET failed ({url}): {err}"))?;
-    let status = response.status().as_u16();
-    if status >= 400 {
-        let body = response.body_mut().read_to_string().unwrap_or_default();
-        return Err(format!("HTTP GET {url} failed ({status}): {body}"));
-    }
-
-    let mut bytes = Vec::new();
-    response
-        .body_mut()
-        .as_reader()
-        .read_to_end(&mut bytes)
-        .map_err(|err| format!("Failed reading HTTP bytes ({url}): {err}"))?;
-    Ok(bytes)
-}
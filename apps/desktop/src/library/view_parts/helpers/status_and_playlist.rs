@@ -77,57 +77,15 @@ pub(in crate::library) fn summarize_status_error(raw: &str) -> String {
     }
 }
 
-pub(in crate::library) fn parse_number_field(value: Option<&serde_json::Value>) -> usize {
-    match value {
-        Some(v) if v.is_number() => v.as_u64().unwrap_or_default() as usize,
-        Some(v) if v.is_string() => v
-            .as_str()
-            .unwrap_or_default()
-            .trim()
-            .parse::<u64>()
-            .unwrap_or_default() as usize,
-        _ => 0,
-    }
-}
-
-pub(in crate::library) fn parse_playlist_summaries(
-    raw: &serde_json::Value,
-) -> Vec<PlaylistSummary> {
-    let mut out = Vec::<PlaylistSummary>::new();
-    let entries = raw.as_array().cloned().unwrap_or_default();
-    for entry in entries {
-        let Some(id) = entry.get("id").and_then(|v| v.as_str()) else {
-            continue;
-        };
-        let id = id.trim().to_lowercase();
-        if id.is_empty() {
-            continue;
-        }
-        let name = entry
-            .get("name")
-            .and_then(|v| v.as_str())
-            .map(str::trim)
-            .filter(|v| !v.is_empty())
-            .unwrap_or("Untitled Playlist")
-            .to_string();
-        let cover_cid = entry
-            .get("coverCid")
-            .and_then(|v| v.as_str())
-            .map(str::trim)
-            .filter(|v| !v.is_empty())
-            .map(str::to_string);
-        let visibility = parse_number_field(entry.get("visibility")).min(255) as u8;
-        let track_count = parse_number_field(entry.get("trackCount"));
-
-        out.push(PlaylistSummary {
-            id,
-            name,
-            cover_cid,
-            visibility,
-            track_count,
-        });
+/// Status-line text for a storage failure: the variant's explanation when it has one,
+/// otherwise the summarized message.
+pub(in crate::library) fn summarize_storage_error(err: &LoadStorageError) -> String {
+    match err {
+        LoadStorageError::Network(_)
+        | LoadStorageError::Auth(_)
+        | LoadStorageError::InsufficientCredit(_) => err.user_message(),
+        _ => summarize_status_error(err.message()),
     }
-    out
 }
 
 pub(in crate::library) fn playlist_track_input_from_track(track: &TrackRow) -> PlaylistTrackInput {
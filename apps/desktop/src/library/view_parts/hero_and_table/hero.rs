@@ -12,6 +12,11 @@ pub(in crate::library) fn render_hero(
     _storage_days: Option<i64>,
     storage_loading: bool,
     add_funds_busy: bool,
+    lyrics_prefetch_progress: Option<(usize, usize)>,
+    scrobble_import_progress: Option<(usize, usize)>,
+    scrobble_outbox: ScrobbleOutboxCounts,
+    upload_queue: UploadQueueCounts,
+    scrobble_sinks: Vec<(ScrobbleSinkKind, bool)>,
     cx: &mut Context<LibraryView>,
 ) -> impl IntoElement {
     let subtitle: Option<String> = if scanning {
@@ -22,10 +27,32 @@ pub(in crate::library) fn render_hero(
         })
     } else if loading {
         Some(format!("Loading... {}/{} tracks", loaded, count))
+    } else if let Some((done, total)) = lyrics_prefetch_progress {
+        Some(format!("Prefetching lyrics... {}/{}", done, total))
+    } else if let Some((done, total)) = scrobble_import_progress {
+        Some(format!("Importing history... {}/{} plays", done, total))
     } else {
         None
     };
 
+    let scrobble_summary = (scrobble_outbox != ScrobbleOutboxCounts::default()).then(|| {
+        format!(
+            "Scrobbles: {} pending · {} confirmed · {} failed",
+            scrobble_outbox.pending, scrobble_outbox.confirmed, scrobble_outbox.failed
+        )
+    });
+
+    let upload_summary = (upload_queue != UploadQueueCounts::default()).then(|| {
+        let mut summary = format!(
+            "Uploads: {} running · {} waiting",
+            upload_queue.running, upload_queue.waiting
+        );
+        if upload_queue.failed > 0 {
+            summary.push_str(&format!(" · {} failed", upload_queue.failed));
+        }
+        summary
+    });
+
     let entity = cx.entity().clone();
 
     div()
@@ -57,7 +84,49 @@ pub(in crate::library) fn render_hero(
                             el.child(div().text_color(TEXT_MUTED()).child(sub))
                         }),
                 )
-                .child(render_hero_overflow_menu(entity)),
+                .child(
+                    div()
+                        .h_flex()
+                        .items_center()
+                        .gap_3()
+                        .when_some(upload_summary, |el, summary| {
+                            el.child(
+                                div()
+                                    .id("library-upload-summary")
+                                    .text_sm()
+                                    .text_color(if upload_queue.failed > 0 {
+                                        TEXT_SECONDARY()
+                                    } else {
+                                        TEXT_MUTED()
+                                    })
+                                    .cursor_pointer()
+                                    .hover(|s| s.text_color(TEXT_PRIMARY()))
+                                    .on_click(cx.listener(|this, _, _w, cx| {
+                                        this.open_transfers_panel(cx);
+                                    }))
+                                    .child(summary),
+                            )
+                        })
+                        .when_some(scrobble_summary, |el, summary| {
+                            el.child(
+                                div()
+                                    .text_sm()
+                                    .text_color(if scrobble_outbox.failed > 0 {
+                                        TEXT_SECONDARY()
+                                    } else {
+                                        TEXT_MUTED()
+                                    })
+                                    .child(summary),
+                            )
+                        })
+                        .child(render_hero_overflow_menu(
+                            entity,
+                            lyrics_prefetch_progress.is_some(),
+                            scrobble_import_progress.is_some(),
+                            scrobble_outbox.failed,
+                            scrobble_sinks,
+                        )),
+                ),
         )
         // Turbo Credits card (full-width)
         .child(render_turbo_credits_card(
@@ -161,10 +230,23 @@ fn render_turbo_credits_card(
         )
 }
 
-/// Three-dot overflow menu for library management actions (Pick Folder, Rescan).
-fn render_hero_overflow_menu(entity: Entity<LibraryView>) -> impl IntoElement {
+/// Three-dot overflow menu for library management actions (Pick Folder, Rescan, transfers,
+/// lyrics, scrobble retry, history import and destinations).
+fn render_hero_overflow_menu(
+    entity: Entity<LibraryView>,
+    lyrics_prefetch_running: bool,
+    scrobble_import_running: bool,
+    failed_scrobbles: usize,
+    scrobble_sinks: Vec<(ScrobbleSinkKind, bool)>,
+) -> impl IntoElement {
     let folder_entity = entity.clone();
-    let rescan_entity = entity;
+    let rescan_entity = entity.clone();
+    let prefetch_entity = entity.clone();
+    let coverage_entity = entity.clone();
+    let scrobble_retry_entity = entity.clone();
+    let scrobble_import_entity = entity.clone();
+    let scrobble_sinks_entity = entity.clone();
+    let transfers_entity = entity;
 
     Button::new("library-overflow")
         .ghost()
@@ -182,22 +264,118 @@ fn render_hero_overflow_menu(entity: Entity<LibraryView>) -> impl IntoElement {
                 .text_color(TEXT_SECONDARY()),
         )
         .dropdown_menu_with_anchor(Corner::TopRight, move |menu, _window, _cx| {
-            menu.item(PopupMenuItem::new("Pick Folder").on_click({
-                let ent = folder_entity.clone();
-                move |_, _, cx| {
-                    let _ = ent.update(cx, |this, cx| {
-                        this.browse_folder(cx);
-                    });
-                }
-            }))
-            .item(PopupMenuItem::new("Rescan Library").on_click({
-                let ent = rescan_entity.clone();
-                move |_, _, cx| {
+            let mut menu = menu
+                .item(PopupMenuItem::new("Pick Folder").on_click({
+                    let ent = folder_entity.clone();
+                    move |_, _, cx| {
+                        let _ = ent.update(cx, |this, cx| {
+                            this.browse_folder(cx);
+                        });
+                    }
+                }))
+                .item(PopupMenuItem::new("Rescan Library").on_click({
+                    let ent = rescan_entity.clone();
+                    move |_, _, cx| {
+                        let _ = ent.update(cx, |this, cx| {
+                            this.rescan(cx);
+                        });
+                    }
+                }))
+                .item(PopupMenuItem::new("Transfers...").on_click({
+                    let ent = transfers_entity.clone();
+                    move |_, _, cx| {
+                        let _ = ent.update(cx, |this, cx| {
+                            this.open_transfers_panel(cx);
+                        });
+                    }
+                }))
+                .separator()
+                .item(
+                    PopupMenuItem::new(if lyrics_prefetch_running {
+                        "Stop Lyrics Prefetch"
+                    } else {
+                        "Prefetch Lyrics for Library"
+                    })
+                    .on_click({
+                        let ent = prefetch_entity.clone();
+                        move |_, _, cx| {
+                            let _ = ent.update(cx, |this, cx| {
+                                if this.lyrics_prefetch_running() {
+                                    this.cancel_library_lyrics_prefetch(cx);
+                                } else {
+                                    this.start_library_lyrics_prefetch(cx);
+                                }
+                            });
+                        }
+                    }),
+                )
+                .item(PopupMenuItem::new("Lyrics Coverage").on_click({
+                    let ent = coverage_entity.clone();
+                    move |_, _, cx| {
+                        let _ = ent.update(cx, |this, cx| {
+                            this.report_lyrics_coverage(cx);
+                        });
+                    }
+                }))
+                .separator()
+                .item(
+                    PopupMenuItem::new(if failed_scrobbles > 0 {
+                        format!("Retry {failed_scrobbles} Failed Scrobbles")
+                    } else {
+                        "Retry Failed Scrobbles".to_string()
+                    })
+                    .on_click({
+                        let ent = scrobble_retry_entity.clone();
+                        move |_, _, cx| {
+                            let _ = ent.update(cx, |this, cx| {
+                                this.retry_failed_scrobbles(cx);
+                            });
+                        }
+                    }),
+                )
+                .item(
+                    PopupMenuItem::new(if scrobble_import_running {
+                        "Stop History Import"
+                    } else {
+                        "Import Listening History..."
+                    })
+                    .on_click({
+                        let ent = scrobble_import_entity.clone();
+                        move |_, _, cx| {
+                            let _ = ent.update(cx, |this, cx| {
+                                if this.scrobble_import_running() {
+                                    this.stop_scrobble_import(cx);
+                                } else {
+                                    this.import_scrobble_history(cx);
+                                }
+                            });
+                        }
+                    }),
+                )
+                .separator();
+            for (kind, enabled) in scrobble_sinks.iter().copied() {
+                let label = if enabled {
+                    format!("Stop Scrobbling to {}", kind.label())
+                } else {
+                    format!("Scrobble to {}", kind.label())
+                };
+                let ent = scrobble_sinks_entity.clone();
+                menu = menu.item(PopupMenuItem::new(label).on_click(move |_, _, cx| {
                     let _ = ent.update(cx, |this, cx| {
-                        this.rescan(cx);
+                        this.toggle_scrobble_sink(kind, cx);
                     });
-                }
-            }))
+                }));
+            }
+            menu.item(
+                PopupMenuItem::new("Scrobble Destinations Status").on_click({
+                    let ent = scrobble_sinks_entity.clone();
+                    move |_, _, cx| {
+                        let _ = ent.update(cx, |this, cx| {
+                            this.report_scrobble_sinks(cx);
+                        });
+                    }
+                }),
+            )
         })
 }
 
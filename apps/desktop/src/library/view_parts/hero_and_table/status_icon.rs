@@ -8,6 +8,7 @@ fn storage_status_tooltip_text(status: StorageStatus) -> &'static str {
         StorageStatus::Local => "Local only",
         StorageStatus::Uploaded => "Temporary storage",
         StorageStatus::Permanent => "Stored forever",
+        StorageStatus::Missing => "Missing from Load",
     }
 }
 
@@ -32,6 +33,14 @@ pub(in crate::library) fn render_storage_status_icon(status: StorageStatus) -> i
                     .text_color(TEXT_SECONDARY()),
             );
         }
+        StorageStatus::Missing => {
+            container = container.child(
+                gpui::svg()
+                    .path("icons/cloud.svg")
+                    .size(px(16.))
+                    .text_color(TEXT_AMBER),
+            );
+        }
     }
 
     container
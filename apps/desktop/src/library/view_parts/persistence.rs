@@ -6,132 +6,58 @@ pub(in crate::library) fn app_data_dir() -> PathBuf {
         .join("heaven-gpui")
 }
 
-fn uploaded_records_path() -> PathBuf {
-    app_data_dir().join("uploaded_tracks.json")
+/// Run `f` against the library database, when it opened.
+pub(in crate::library) fn with_music_db<T>(
+    db: Option<&Arc<Mutex<MusicDb>>>,
+    f: impl FnOnce(&MusicDb) -> Result<T, String>,
+) -> Result<T, String> {
+    let db = db.ok_or("Library database is unavailable")?;
+    let db = db
+        .lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?;
+    f(&db)
 }
 
-fn shared_grants_path() -> PathBuf {
-    app_data_dir().join("shared_grants.json")
-}
-
-pub(in crate::library) fn load_uploaded_track_records_for_owner(
-    owner: &str,
-) -> Vec<UploadedTrackRecord> {
-    let path = uploaded_records_path();
-    let Ok(text) = fs::read_to_string(path) else {
-        return Vec::new();
-    };
-    let Ok(records) = serde_json::from_str::<Vec<UploadedTrackRecord>>(&text) else {
-        return Vec::new();
-    };
-    let owner_lc = owner.to_lowercase();
-    records
-        .into_iter()
-        .filter(|r| r.owner_address.to_lowercase() == owner_lc)
-        .collect()
-}
-
-pub(in crate::library) fn upsert_uploaded_track_record(
-    record: UploadedTrackRecord,
-) -> Result<(), String> {
-    let path = uploaded_records_path();
-    let mut all = if let Ok(text) = fs::read_to_string(&path) {
-        serde_json::from_str::<Vec<UploadedTrackRecord>>(&text).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-
-    all.retain(|r| !(r.owner_address == record.owner_address && r.file_path == record.file_path));
-    all.push(record);
-
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed creating app data dir ({}): {e}", parent.display()))?;
+/// Move the upload and grant records earlier builds kept in JSON files into the database.
+/// Each file is renamed once imported so this runs a single time.
+pub(in crate::library) fn import_legacy_record_files(db: &MusicDb) -> Result<usize, String> {
+    let mut imported = 0;
+    let uploaded_path = app_data_dir().join("uploaded_tracks.json");
+    if let Some(records) = read_legacy_records::<UploadedTrackRecord>(&uploaded_path) {
+        for record in &records {
+            db.upsert_uploaded_track_record(record)?;
+        }
+        imported += records.len();
+        retire_legacy_file(&uploaded_path)?;
     }
-    let encoded = serde_json::to_string_pretty(&all)
-        .map_err(|e| format!("Failed encoding uploaded track records: {e}"))?;
-    fs::write(&path, encoded).map_err(|e| {
-        format!(
-            "Failed writing uploaded track records ({}): {e}",
-            path.display()
-        )
-    })
-}
-
-pub(in crate::library) fn load_shared_grant_records_for_grantee(
-    grantee: &str,
-) -> Vec<SharedGrantRecord> {
-    let path = shared_grants_path();
-    let Ok(text) = fs::read_to_string(path) else {
-        return Vec::new();
-    };
-    let Ok(mut records) = serde_json::from_str::<Vec<SharedGrantRecord>>(&text) else {
-        return Vec::new();
-    };
-    let grantee_lc = grantee.to_lowercase();
-    records.retain(|r| r.grantee_address.to_lowercase() == grantee_lc);
-    records.sort_by(|a, b| b.shared_at_ms.cmp(&a.shared_at_ms));
-    records
-}
-
-pub(in crate::library) fn append_shared_grant_record(
-    record: SharedGrantRecord,
-) -> Result<(), String> {
-    let path = shared_grants_path();
-    let mut all = if let Ok(text) = fs::read_to_string(&path) {
-        serde_json::from_str::<Vec<SharedGrantRecord>>(&text).unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-    // De-dupe by (grantee, contentId) so re-sharing doesn't spam the list.
-    all.retain(|existing| {
-        !(existing
-            .grantee_address
-            .eq_ignore_ascii_case(record.grantee_address.as_str())
-            && existing
-                .content_id
-                .eq_ignore_ascii_case(record.content_id.as_str()))
-    });
-    all.push(record);
-
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed creating app data dir ({}): {e}", parent.display()))?;
+    let grants_path = app_data_dir().join("shared_grants.json");
+    if let Some(records) = read_legacy_records::<SharedGrantRecord>(&grants_path) {
+        for record in &records {
+            db.upsert_shared_grant_record(record)?;
+        }
+        imported += records.len();
+        retire_legacy_file(&grants_path)?;
     }
-    let encoded = serde_json::to_string_pretty(&all)
-        .map_err(|e| format!("Failed encoding shared grant records: {e}"))?;
-    fs::write(&path, encoded).map_err(|e| {
-        format!(
-            "Failed writing shared grant records ({}): {e}",
-            path.display()
-        )
-    })
+    Ok(imported)
 }
 
-pub(in crate::library) fn upsert_shared_grant_records_for_grantee(
-    grantee: &str,
-    records: &[SharedGrantRecord],
-) -> Result<(), String> {
-    let path = shared_grants_path();
-    let mut all = if let Ok(text) = fs::read_to_string(&path) {
-        serde_json::from_str::<Vec<SharedGrantRecord>>(&text).unwrap_or_default()
-    } else {
+fn read_legacy_records<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> Option<Vec<T>> {
+    let text = fs::read_to_string(path).ok()?;
+    Some(serde_json::from_str(&text).unwrap_or_else(|e| {
+        log::warn!(
+            "[Library] skipping unreadable legacy records ({}): {}",
+            path.display(),
+            e
+        );
         Vec::new()
-    };
-
-    let grantee_lc = grantee.to_lowercase();
-    all.retain(|r| r.grantee_address.to_lowercase() != grantee_lc);
-    all.extend(records.iter().cloned());
+    }))
+}
 
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed creating app data dir ({}): {e}", parent.display()))?;
-    }
-    let encoded = serde_json::to_string_pretty(&all)
-        .map_err(|e| format!("Failed encoding shared grant records: {e}"))?;
-    fs::write(&path, encoded).map_err(|e| {
+fn retire_legacy_file(path: &std::path::Path) -> Result<(), String> {
+    let retired = path.with_extension("json.imported");
+    fs::rename(path, &retired).map_err(|e| {
         format!(
-            "Failed writing shared grant records ({}): {e}",
+            "Failed retiring legacy records file ({}): {e}",
             path.display()
         )
     })
@@ -272,7 +272,10 @@ pub(in crate::library) fn render_track_row(
                                         let upload_track = upload_track.clone();
                                         move |_, _, cx| {
                                             let _ = upload_entity.update(cx, |this, cx| {
-                                                this.encrypt_upload_track(upload_track.clone(), cx);
+                                                this.enqueue_track_uploads(
+                                                    vec![upload_track.clone()],
+                                                    cx,
+                                                );
                                             });
                                         }
                                     }),
@@ -20,18 +20,35 @@ use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 
 use crate::auth::PersistedAuth;
-use crate::shared::rpc::{http_get_bytes, http_post_json, read_json_or_text};
+use crate::shared::rpc::{http_post_json, read_json_or_text, HttpError};
+mod archive;
+mod cache;
 mod config;
 mod content;
 mod decrypt;
+mod error;
 mod helpers;
 mod model;
 mod playlist;
 mod upload;
+pub use archive::verify_arweave_archive;
+pub use cache::{CacheKind, ContentCache};
 use config::*;
+pub use error::{LoadStorageError, LoadStorageResult};
 use helpers::*;
-use model::{ContentRegistryEntry, LoadHealthResult, ParsedContentBlob, UploadResult};
-pub use model::{PlaylistCoverImageInput, PlaylistTrackInput, TrackMetaInput};
+pub use model::{
+    AccessRevocation, ContentCacheSettings, ContentCacheUsage, ContentGrant, ContentGrantBatch,
+    ContentKeyBackup, ContentKeyRestore, ContentKeyRestoreOutcome, ContentKeyStatus, ContentUpload,
+    DeviceKeySync, FundingOutcome, LinkedDeviceSummary, PendingRevocation, PlaylistAction,
+    PlaylistCoverImageInput, PlaylistShareOutcome, PlaylistSummary, PlaylistTrackInput,
+    RegisteredContent, RemoteAccessGrant, RemoteContentState, SharedContentFile,
+    StorageAccountStatus, StorageHealth, StoragePreflight, TrackMetaInput, TrackMetadata,
+    UploadControl, UploadPhase, UploadProgress, UploadReadiness,
+};
+use model::{
+    ArweaveArchive, ArweaveArchivePlan, ContentRegistryEntry, LoadHealthResult, ParsedContentBlob,
+    UploadResult,
+};
 
 pub struct LoadStorageService {
     _private: (),
@@ -48,53 +65,53 @@ impl LoadStorageService {
         Self { _private: () }
     }
 
-    pub fn health(&mut self) -> Result<Value, String> {
-        Ok(json!({
-            "ok": true,
-            "component": "load-native",
-            "loadUploadMode": load_upload_mode_label(),
-            "loadUploadUrl": load_turbo_upload_url(),
-            "loadUploadToken": load_turbo_upload_token(),
-            "loadGatewayUrl": load_gateway_url(),
-            "turboFundingEnabled": load_user_pays_enabled(),
-            "turboFundingProxyUrl": turbo_funding_proxy_url(),
-            "turboFundingToken": turbo_funding_token(),
-            "baseSepoliaRpcUrl": base_sepolia_rpc_url(),
-            "usesBun": false,
-        }))
+    /// Encrypted size of a `source_len`-byte track, for estimates made before encrypting it.
+    pub fn estimated_upload_bytes(source_len: u64) -> u64 {
+        stream_payload_len(source_len, DEFAULT_STREAM_CHUNK_SIZE)
     }
 
-    pub fn storage_status(&mut self, auth: &PersistedAuth) -> Result<Value, String> {
+    pub fn health(&mut self) -> LoadStorageResult<StorageHealth> {
+        Ok(StorageHealth {
+            ok: true,
+            upload_mode: load_upload_mode_label(),
+            upload_url: load_turbo_upload_url(),
+            upload_token: load_turbo_upload_token(),
+            gateway_url: load_gateway_url(),
+            turbo_funding_enabled: load_user_pays_enabled(),
+            turbo_funding_proxy_url: turbo_funding_proxy_url(),
+            turbo_funding_token: turbo_funding_token(),
+            base_sepolia_rpc_url: base_sepolia_rpc_url(),
+        })
+    }
+
+    pub fn storage_status(
+        &mut self,
+        auth: &PersistedAuth,
+    ) -> LoadStorageResult<StorageAccountStatus> {
         let user_pays = load_user_pays_enabled();
         let health = self.load_health_check();
-        let free_limit = health
-            .info
-            .as_ref()
-            .and_then(|v| v.get("freeUploadLimitBytes"))
-            .and_then(Value::as_u64);
-        let upload_mode = load_upload_mode_label();
-
-        let mut balance_display = "0".to_string();
-        let mut balance_raw = Value::Null;
-        let mut storage_info_error = Value::Null;
+        let free_limit = health.info.as_ref().and_then(free_upload_limit_bytes);
+
+        let mut balance_credits = None;
+        let mut balance_raw = None;
+        let mut storage_info_error = None;
         let mut credit_ready = true;
 
         match self.fetch_turbo_balance(auth) {
             Ok(balance_payload) => {
-                let parsed = extract_balance_hint(&balance_payload);
-                if let Some(amount) = parsed {
-                    balance_display = format!("{amount:.8}");
-                }
+                balance_credits = extract_balance_hint(&balance_payload);
                 if user_pays {
-                    credit_ready = parsed.map(|v| v >= min_upload_credit()).unwrap_or(false);
+                    credit_ready = balance_credits
+                        .map(|v| v >= min_upload_credit())
+                        .unwrap_or(false);
                 }
-                balance_raw = balance_payload;
+                balance_raw = Some(balance_payload);
             }
             Err(err) => {
                 if user_pays {
                     credit_ready = false;
                 }
-                storage_info_error = json!(err);
+                storage_info_error = Some(err.to_string());
             }
         }
 
@@ -109,76 +126,86 @@ impl LoadStorageService {
             )
         };
 
-        Ok(json!({
-            "balance": balance_display,
-            "balanceRaw": balance_raw,
-            "operatorApproved": health.ok,
-            "monthlyCost": "n/a",
-            "daysRemaining": Value::Null,
-            "ready": health.ok && credit_ready,
-            "accountInfoError": account_info_error,
-            "storageInfoError": storage_info_error,
-            "uploadMode": upload_mode,
-            "endpoint": health.endpoint,
-            "status": health.status,
-            "gatewayUrl": load_gateway_url(),
-            "loadUploadToken": load_turbo_upload_token(),
-            "turboFundingEnabled": user_pays,
-            "turboFundingProxyUrl": turbo_funding_proxy_url(),
-            "turboFundingToken": turbo_funding_token(),
-            "freeUploadLimitBytes": free_limit,
-            "fallbackUsed": false,
-        }))
+        Ok(StorageAccountStatus {
+            balance: balance_credits
+                .map(|amount| format!("{amount:.8}"))
+                .unwrap_or_else(|| "0".to_string()),
+            balance_credits,
+            balance_raw,
+            monthly_cost: None,
+            days_remaining: None,
+            ready: health.ok && credit_ready,
+            endpoint_healthy: health.ok,
+            account_info_error,
+            storage_info_error,
+            upload_mode: load_upload_mode_label(),
+            endpoint: health.endpoint,
+            status: health.status,
+            gateway_url: load_gateway_url(),
+            turbo_funding_enabled: user_pays,
+            free_upload_limit_bytes: free_limit,
+        })
     }
 
     pub fn storage_preflight(
         &mut self,
         auth: &PersistedAuth,
         size_bytes: u64,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<StoragePreflight> {
         let ready = self.ensure_upload_ready(Some(auth), Some(size_bytes as usize));
-        Ok(json!({
-            "ready": ready.0,
-            "reason": ready.1,
-            "suggestedDeposit": Value::Null,
-            "uploadMode": load_upload_mode_label(),
-            "uploadToken": load_turbo_upload_token(),
-            "turboFundingEnabled": load_user_pays_enabled(),
-        }))
+        Ok(StoragePreflight {
+            ready: ready.ready,
+            reason: ready.reason.map(|err| err.to_string()),
+            estimated_credit: ready.estimated_credit,
+            upload_mode: load_upload_mode_label(),
+            turbo_funding_enabled: load_user_pays_enabled(),
+        })
     }
 
     pub fn storage_deposit_and_approve(
         &mut self,
         _auth: &PersistedAuth,
-        amount_hint: &str,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<FundingOutcome> {
         if !load_user_pays_enabled() {
-            return Ok(json!({
-                "ok": true,
-                "txHash": Value::Null,
-                "message": "Offchain Load upload mode has no in-app deposit step.",
-                "uploadMode": load_upload_mode_label(),
-                "uploadToken": load_turbo_upload_token(),
-                "amountHint": amount_hint,
-                "turboFundingEnabled": false,
-            }));
+            return Ok(FundingOutcome {
+                funding_enabled: false,
+                tx_hash: None,
+                message: "Offchain Load upload mode has no in-app deposit step.".to_string(),
+            });
         }
-        Err("Turbo user-pays funding is not yet available for Tempo sessions.".to_string())
+        Err(LoadStorageError::Auth(
+            "Turbo user-pays funding is not yet available for Tempo sessions.".to_string(),
+        ))
     }
 
-    fn fetch_turbo_balance(&self, auth: &PersistedAuth) -> Result<Value, String> {
-        let user_address = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+    fn fetch_turbo_balance(&self, auth: &PersistedAuth) -> LoadStorageResult<Value> {
+        let user_address = signed_in_wallet(auth)?;
         let proxy_url = turbo_funding_proxy_url();
         let balance_url = format!("{proxy_url}/turbo/balance");
-        http_post_json(
+        Ok(http_post_json(
             &balance_url,
             json!({
                 "token": turbo_funding_token(),
                 "userAddress": user_address,
             }),
-        )
+        )?)
+    }
+
+    /// Credits Turbo charges for an upload of `byte_count` bytes.
+    fn fetch_turbo_upload_price(&self, byte_count: usize) -> LoadStorageResult<f64> {
+        let price_url = format!("{}/turbo/price", turbo_funding_proxy_url());
+        let payload = http_post_json(
+            &price_url,
+            json!({
+                "token": turbo_funding_token(),
+                "byteCount": byte_count,
+            }),
+        )?;
+        extract_price_hint(&payload).ok_or_else(|| {
+            LoadStorageError::Other(format!(
+                "Turbo price response had no credit amount: {payload}"
+            ))
+        })
     }
 
     fn load_health_check(&self) -> LoadHealthResult {
@@ -0,0 +1,316 @@
+//! "Save forever": copy an encrypted blob from Load's temporary storage onto Arweave.
+//!
+//! The blob is streamed to a staging file and priced, then, once the user has confirmed the
+//! cost, re-signed as a new DataItem under the session key and posted to Arweave Turbo.
+//! The Arweave gateways are polled until one serves the same bytes back.
+
+use super::*;
+use std::io::{Read, Write};
+
+use crate::shared::gateways::{GatewayKind, GatewayPool};
+use crate::shared::rpc::{http_get_bytes, http_get_json};
+
+/// Winston credits in one Turbo credit (1 AR).
+const WINC_PER_CREDIT: f64 = 1e12;
+const VERIFY_ATTEMPTS: u32 = 8;
+/// Blobs being archived wait here between pricing and upload, instead of in memory.
+const ARCHIVE_STAGING_DIR: &str = "arweave-archive-staging";
+/// Leading bytes checked to tell an encrypted payload from a gateway's error page.
+const ARCHIVE_HEAD_CHECK_BYTES: usize = 512;
+const VERIFY_FIRST_DELAY: Duration = Duration::from_secs(3);
+const VERIFY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+impl LoadStorageService {
+    /// Stream the encrypted blob `piece_cid` from Load into a staging file and price
+    /// anchoring it on Arweave. The staged copy is removed when the plan is dropped.
+    pub fn plan_arweave_archive(
+        &mut self,
+        piece_cid: &str,
+    ) -> LoadStorageResult<ArweaveArchivePlan> {
+        let piece_cid = piece_cid.trim();
+        if piece_cid.is_empty() {
+            return Err(LoadStorageError::Other("pieceCid is empty".to_string()));
+        }
+        let stream = open_shared_blob(piece_cid, None).map_err(|err| {
+            LoadStorageError::from(err).context(format!(
+                "Failed to fetch content blob for archival (pieceCid={piece_cid})"
+            ))
+        })?;
+        let staged_path = archive_staging_path(piece_cid)?;
+        let (bytes, sha256) = match stage_archive_blob(stream.reader, &staged_path) {
+            Ok(staged) => staged,
+            Err(err) => {
+                let _ = fs::remove_file(&staged_path);
+                return Err(err.context(format!(
+                    "Failed to fetch content blob for archival (pieceCid={piece_cid})"
+                )));
+            }
+        };
+        let mut plan = ArweaveArchivePlan {
+            piece_cid: piece_cid.to_string(),
+            bytes,
+            sha256,
+            estimated_credit: 0.0,
+            staged_path,
+        };
+        if let Some(pinned) = pinned_blob_digest(piece_cid) {
+            if !plan.sha256.eq_ignore_ascii_case(pinned.trim()) {
+                return Err(LoadStorageError::Crypto(format!(
+                    "Content blob for pieceCid={piece_cid} does not match its pinned digest"
+                )));
+            }
+        }
+        let byte_count = usize::try_from(plan.bytes).unwrap_or(usize::MAX);
+        plan.estimated_credit = ArweavePayments::from_env().upload_price(byte_count)?;
+        Ok(plan)
+    }
+
+    /// Sign the staged blob as a DataItem for `content_id` and post it to Arweave Turbo,
+    /// once the session key's Turbo balance covers the estimate. The DataItem is written
+    /// next to the blob and streamed from disk.
+    pub fn upload_arweave_archive(
+        &mut self,
+        auth: &PersistedAuth,
+        content_id: &str,
+        plan: &ArweaveArchivePlan,
+    ) -> LoadStorageResult<ArweaveArchive> {
+        ArweavePayments::from_env()
+            .ensure_credit(auth.tempo_session_address.as_deref(), plan.estimated_credit)?;
+
+        let tags = vec![
+            json!({"name": "Content-Type", "value": "application/octet-stream"}),
+            json!({"name": "App-Name", "value": "Heaven"}),
+            json!({"name": "Heaven-Type", "value": "content-archive"}),
+            json!({"name": "Content-Id", "value": content_id.trim().to_lowercase()}),
+            json!({"name": "Load-Piece-Cid", "value": plan.piece_cid}),
+            json!({"name": "Upload-Source", "value": "heaven-desktop"}),
+        ];
+        let item_path = plan.staged_path.with_extension("item");
+        let uploaded = self
+            .stage_signed_dataitem(auth, &plan.staged_path, &tags, &item_path)
+            .and_then(|()| upload_staged_dataitem_to_arweave(&item_path));
+        let _ = fs::remove_file(&item_path);
+        let arweave_id = uploaded?;
+        if let Err(err) = remember_archived_copy(&plan.piece_cid, &arweave_id) {
+            log::warn!("[LoadStorage] failed recording Arweave copy {arweave_id}: {err}");
+        }
+        log::info!(
+            "[LoadStorage] archived pieceCid={} to Arweave id={} ({} bytes)",
+            plan.piece_cid,
+            arweave_id,
+            plan.bytes
+        );
+        Ok(ArweaveArchive {
+            piece_cid: plan.piece_cid.clone(),
+            arweave_id,
+            sha256: plan.sha256.clone(),
+            bytes: plan.bytes,
+        })
+    }
+}
+
+/// Staging file for the blob `piece_cid` while its archive is priced and confirmed.
+fn archive_staging_path(piece_cid: &str) -> LoadStorageResult<PathBuf> {
+    let dir = crate::auth::accounts::AccountRegistry::shared().scoped_path(ARCHIVE_STAGING_DIR);
+    fs::create_dir_all(&dir).map_err(|e| {
+        format!(
+            "Failed creating archive staging dir ({}): {e}",
+            dir.display()
+        )
+    })?;
+    Ok(dir.join(format!("{}.blob", sanitize_shared_file_stem(piece_cid))))
+}
+
+/// Copy `reader` to `path`, returning its length and sha256 (hex). The body must look like
+/// an encrypted payload, not an error page a gateway served with a 200.
+fn stage_archive_blob(mut reader: impl Read, path: &Path) -> LoadStorageResult<(u64, String)> {
+    let write_err = |e: std::io::Error| {
+        LoadStorageError::Other(format!(
+            "Failed writing archive staging file ({}): {e}",
+            path.display()
+        ))
+    };
+    let mut out = std::io::BufWriter::new(fs::File::create(path).map_err(write_err)?);
+    let mut digest = Sha256::new();
+    let mut head = Vec::new();
+    let mut len = 0u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .map_err(|e| LoadStorageError::Network(format!("Blob download failed: {e}")))?;
+        if n == 0 {
+            break;
+        }
+        if head.len() < ARCHIVE_HEAD_CHECK_BYTES {
+            let take = n.min(ARCHIVE_HEAD_CHECK_BYTES - head.len());
+            head.extend_from_slice(&buf[..take]);
+        }
+        digest.update(&buf[..n]);
+        out.write_all(&buf[..n]).map_err(write_err)?;
+        len += n as u64;
+    }
+    out.into_inner()
+        .map_err(|e| write_err(e.into_error()))?
+        .sync_all()
+        .map_err(write_err)?;
+    check_encrypted_blob(&head).map_err(LoadStorageError::Crypto)?;
+    Ok((len, hex::encode(digest.finalize())))
+}
+
+/// Poll the preferred Arweave gateway until it serves `arweave_id` with the archived
+/// digest. `Ok(false)` when it has not within the polling window; bundles can take a
+/// while to settle, so the caller should keep the archive pending and check again.
+pub fn verify_arweave_archive(arweave_id: &str, sha256: &str) -> LoadStorageResult<bool> {
+    let base = GatewayPool::shared()
+        .preferred_base(GatewayKind::Arweave)
+        .ok_or("No Arweave gateway is configured.")?;
+    let url = format!("{base}/{}", arweave_id.trim());
+    let mut delay = VERIFY_FIRST_DELAY;
+    for attempt in 1..=VERIFY_ATTEMPTS {
+        match http_get_bytes(&url) {
+            Ok(bytes) if hex::encode(Sha256::digest(&bytes)).eq_ignore_ascii_case(sha256) => {
+                return Ok(true);
+            }
+            Ok(bytes) => log::warn!(
+                "[LoadStorage] {url} served {} bytes that do not match the archive (attempt {attempt})",
+                bytes.len()
+            ),
+            Err(err) => log::debug!("[LoadStorage] archive not served yet ({attempt}): {err}"),
+        }
+        if attempt < VERIFY_ATTEMPTS {
+            std::thread::sleep(delay);
+            delay = (delay * 2).min(VERIFY_MAX_DELAY);
+        }
+    }
+    Ok(false)
+}
+
+/// Arweave Turbo's payment service, which prices uploads and holds the signer's credit.
+struct ArweavePayments {
+    base_url: String,
+    token: String,
+}
+
+impl ArweavePayments {
+    fn from_env() -> Self {
+        Self {
+            base_url: arweave_turbo_payment_url(),
+            token: arweave_turbo_token(),
+        }
+    }
+
+    /// Turbo credits for `byte_count` bytes; uploads under the free limit price at zero.
+    fn upload_price(&self, byte_count: usize) -> LoadStorageResult<f64> {
+        let url = format!("{}/v1/price/bytes/{byte_count}", self.base_url);
+        let payload = http_get_json(&url)?;
+        let winc = extract_price_hint(&payload)
+            .ok_or_else(|| format!("Arweave Turbo price response had no winc amount: {payload}"))?;
+        Ok(winc / WINC_PER_CREDIT)
+    }
+
+    /// Turbo credits held by `signer`, the session key that signs archive uploads.
+    fn balance(&self, signer: &str) -> LoadStorageResult<f64> {
+        let url = format!(
+            "{}/v1/account/balance/{}?address={}",
+            self.base_url,
+            self.token,
+            signer.to_lowercase()
+        );
+        match http_get_json(&url) {
+            Ok(payload) => Ok(extract_price_hint(&payload).unwrap_or(0.0) / WINC_PER_CREDIT),
+            // Turbo has no account for a signer that never topped up.
+            Err(HttpError::Status(404, _)) => Ok(0.0),
+            Err(err) => {
+                Err(LoadStorageError::from(err).context("Arweave Turbo balance lookup failed"))
+            }
+        }
+    }
+
+    /// Fail with `InsufficientCredit` unless `signer` can pay `estimated_credit`.
+    fn ensure_credit(&self, signer: Option<&str>, estimated_credit: f64) -> LoadStorageResult<()> {
+        if estimated_credit <= 0.0 {
+            return Ok(());
+        }
+        let signer = signer
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| {
+                LoadStorageError::Auth(
+                    "Missing Tempo session address in auth; sign in again.".to_string(),
+                )
+            })?;
+        let balance = self.balance(signer)?;
+        if balance < estimated_credit {
+            return Err(LoadStorageError::InsufficientCredit(format!(
+                "Arweave Turbo credit ({balance:.8}) is below the {estimated_credit:.8} this archive costs. Use Add Funds first."
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_stub::{HttpStub, StubResponse};
+    use std::collections::HashMap;
+
+    /// Answers requests from `routes` (path and query to status and body), 404 otherwise.
+    fn spawn_mock_payments(routes: HashMap<&'static str, (u16, &'static str)>) -> ArweavePayments {
+        let stub = HttpStub::spawn(move |request| match routes.get(request.path.as_str()) {
+            Some((status, body)) => StubResponse::new(*status, *body),
+            None => StubResponse::not_found(),
+        });
+        ArweavePayments {
+            base_url: stub.url,
+            token: "ethereum".to_string(),
+        }
+    }
+
+    #[test]
+    fn prices_and_balances_parse_from_winc() {
+        let payments = spawn_mock_payments(HashMap::from([
+            ("/v1/price/bytes/1000", (200, r#"{"winc":"2500000000"}"#)),
+            ("/v1/price/bytes/7", (200, r#"{"adjustments":[]}"#)),
+            (
+                "/v1/account/balance/ethereum?address=0xfunded",
+                (200, r#"{"winc":"1000000000000"}"#),
+            ),
+            (
+                "/v1/account/balance/ethereum?address=0xbroken",
+                (500, r#"{"error":"down"}"#),
+            ),
+        ]));
+
+        assert_eq!(payments.upload_price(1000).unwrap(), 0.0025);
+        assert!(payments.upload_price(7).is_err());
+        assert_eq!(payments.balance("0xFUNDED").unwrap(), 1.0);
+        // Turbo has no account for a signer that never topped up.
+        assert_eq!(payments.balance("0xnew").unwrap(), 0.0);
+        assert!(payments.balance("0xbroken").is_err());
+    }
+
+    #[test]
+    fn credit_gate_blocks_archives_the_signer_cannot_pay_for() {
+        let payments = spawn_mock_payments(HashMap::from([(
+            "/v1/account/balance/ethereum?address=0xfunded",
+            (200, r#"{"winc":"1000000000000"}"#),
+        )]));
+
+        assert_eq!(payments.ensure_credit(None, 0.0), Ok(()));
+        assert_eq!(payments.ensure_credit(Some("0xfunded"), 0.5), Ok(()));
+        assert!(matches!(
+            payments.ensure_credit(Some("0xfunded"), 2.0),
+            Err(LoadStorageError::InsufficientCredit(_))
+        ));
+        assert!(matches!(
+            payments.ensure_credit(Some("0xnew"), 0.1),
+            Err(LoadStorageError::InsufficientCredit(_))
+        ));
+        assert!(matches!(
+            payments.ensure_credit(Some(" "), 0.1),
+            Err(LoadStorageError::Auth(_))
+        ));
+    }
+}
@@ -0,0 +1,481 @@
+//! Managed on-disk cache for decrypted shared audio and downloaded artwork.
+//!
+//! Every file is listed in an index with its size, the sha256 of its plaintext and when it
+//! was last used. Inserts evict the least recently used entries past the size cap, lookups
+//! drop files that no longer match their digest, and shared audio can be kept sealed at
+//! rest, in which case only the files being played are written out in the clear.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[path = "cache/index.rs"]
+mod index;
+#[path = "cache/sealed.rs"]
+mod sealed;
+use index::{CacheEntry, CacheIndex, StoredSettings};
+use sealed::{cache_key, hash_file, open_file, partial_path, seal_file};
+
+const CACHE_DIR: &str = "content-cache";
+const PLAYBACK_DIR: &str = "playback";
+const STAGING_DIR: &str = "staging";
+const DEFAULT_CACHE_MAX_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+/// Opened copies of sealed audio kept around, so a track and the one queued next stay
+/// playable while older copies are wiped.
+const PLAYBACK_KEEP: usize = 2;
+
+/// Serializes index updates from the playback and artwork threads.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheKind {
+    SharedAudio,
+    Cover,
+}
+
+impl CacheKind {
+    fn dir(self) -> &'static str {
+        match self {
+            Self::SharedAudio => "audio",
+            Self::Cover => "covers",
+        }
+    }
+
+    /// Only decrypted shares are private; artwork is public and never sealed.
+    fn sealable(self) -> bool {
+        matches!(self, Self::SharedAudio)
+    }
+}
+
+pub struct ContentCache {
+    root: PathBuf,
+}
+
+impl ContentCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The active account's cache.
+    pub fn active() -> Self {
+        Self::new(crate::auth::accounts::AccountRegistry::shared().scoped_path(CACHE_DIR))
+    }
+
+    /// A playable path for `key`, or `None` when it is not cached or failed verification.
+    pub fn lookup(&self, kind: CacheKind, key: &str) -> Option<PathBuf> {
+        let id = entry_id(kind, key);
+        let entry = {
+            let _guard = INDEX_LOCK.lock();
+            CacheIndex::read(&self.root).entries.get(&id).cloned()?
+        };
+        let stored = self.root.join(&entry.file);
+        let verified = if entry.sealed {
+            self.open_sealed(&entry, &stored)
+        } else {
+            verify_plain(&entry, &stored).map(|()| stored)
+        };
+        match verified {
+            Ok(path) => {
+                let _ = self.with_index(|index| {
+                    if let Some(entry) = index.entries.get_mut(&id) {
+                        entry.last_access_ms = now_ms();
+                    }
+                    Ok(())
+                });
+                Some(path)
+            }
+            Err(err) => {
+                log::warn!("[ContentCache] dropping {id}: {err}");
+                let _ = self.remove(&id);
+                None
+            }
+        }
+    }
+
+    /// Where a writer should stage `file_name` before handing it to [`Self::insert_file`].
+    pub fn staging_path(&self, file_name: &str) -> Result<PathBuf, String> {
+        let dir = self.root.join(STAGING_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed creating cache dir ({}): {e}", dir.display()))?;
+        Ok(dir.join(file_name))
+    }
+
+    /// Move the finished plaintext file `staged` into the cache under `key`, then evict past
+    /// the cap. Returns a playable path.
+    pub fn insert_file(
+        &self,
+        kind: CacheKind,
+        key: &str,
+        staged: &Path,
+        file_name: &str,
+    ) -> Result<PathBuf, String> {
+        let dir = self.root.join(kind.dir());
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed creating cache dir ({}): {e}", dir.display()))?;
+        let seal = kind.sealable() && self.settings().encrypt_at_rest;
+        let plain_path = dir.join(file_name);
+        let (stored, sha256) = if seal {
+            let sealed_path = dir.join(format!("{file_name}.sealed"));
+            let sha256 = seal_file(&cache_key()?, staged, &sealed_path)?;
+            let _ = fs::remove_file(staged);
+            // An opened copy of what this replaces would otherwise be served again.
+            let _ = fs::remove_file(self.root.join(PLAYBACK_DIR).join(file_name));
+            (sealed_path, sha256)
+        } else {
+            let (_, sha256) = hash_file(staged)?;
+            move_file(staged, &plain_path)?;
+            (plain_path.clone(), sha256)
+        };
+        let bytes = fs::metadata(&stored).map(|meta| meta.len()).unwrap_or(0);
+
+        let id = entry_id(kind, key);
+        let entry = CacheEntry {
+            kind,
+            file: relative_name(kind, &stored),
+            bytes,
+            sha256,
+            sealed: seal,
+            last_access_ms: now_ms(),
+        };
+        let (replaced, evicted) = self.with_index(|index| {
+            let replaced = index.entries.insert(id.clone(), entry.clone());
+            let evicted = index.evict_to_cap(&id);
+            Ok((replaced, evicted))
+        })?;
+        for old in replaced.into_iter().chain(evicted) {
+            if old.file != entry.file {
+                let _ = fs::remove_file(self.root.join(&old.file));
+            }
+        }
+
+        if seal {
+            self.open_sealed(&entry, &stored)
+        } else {
+            Ok(plain_path)
+        }
+    }
+
+    pub fn insert_bytes(
+        &self,
+        kind: CacheKind,
+        key: &str,
+        bytes: &[u8],
+        file_name: &str,
+    ) -> Result<PathBuf, String> {
+        let staged = self.staging_path(file_name)?;
+        fs::write(&staged, bytes)
+            .map_err(|e| format!("Failed staging cache file ({}): {e}", staged.display()))?;
+        self.insert_file(kind, key, &staged, file_name)
+    }
+
+    pub fn settings(&self) -> ContentCacheSettings {
+        let _guard = INDEX_LOCK.lock();
+        CacheIndex::read(&self.root).settings.into()
+    }
+
+    pub fn usage(&self) -> ContentCacheUsage {
+        let _guard = INDEX_LOCK.lock();
+        usage_of(&CacheIndex::read(&self.root))
+    }
+
+    /// Apply a new cap (evicting down to it) and seal or open existing audio to match the
+    /// at-rest setting.
+    pub fn configure(&self, settings: ContentCacheSettings) -> Result<ContentCacheUsage, String> {
+        let key = if settings.encrypt_at_rest {
+            Some(cache_key()?)
+        } else {
+            None
+        };
+        let _guard = INDEX_LOCK.lock();
+        let mut index = CacheIndex::read(&self.root);
+        index.settings = StoredSettings::from(settings);
+
+        let mut failed = Vec::new();
+        for (id, entry) in index.entries.iter_mut() {
+            if !entry.kind.sealable() || entry.sealed == settings.encrypt_at_rest {
+                continue;
+            }
+            if let Err(err) = self.reseal(entry, key.as_ref()) {
+                log::warn!("[ContentCache] dropping {id} while changing at-rest mode: {err}");
+                failed.push(id.clone());
+            }
+        }
+        for id in failed {
+            if let Some(entry) = index.entries.remove(&id) {
+                let _ = fs::remove_file(self.root.join(entry.file));
+            }
+        }
+        for entry in index.evict_to_cap("") {
+            let _ = fs::remove_file(self.root.join(entry.file));
+        }
+        if !settings.encrypt_at_rest {
+            let _ = fs::remove_dir_all(self.root.join(PLAYBACK_DIR));
+        }
+        index.write(&self.root)?;
+        Ok(usage_of(&index))
+    }
+
+    /// Remove every cached file; settings are kept.
+    pub fn clear(&self) -> Result<ContentCacheUsage, String> {
+        let _guard = INDEX_LOCK.lock();
+        let mut index = CacheIndex::read(&self.root);
+        index.entries.clear();
+        for dir in [
+            CacheKind::SharedAudio.dir(),
+            CacheKind::Cover.dir(),
+            PLAYBACK_DIR,
+            STAGING_DIR,
+        ] {
+            let path = self.root.join(dir);
+            if path.exists() {
+                fs::remove_dir_all(&path)
+                    .map_err(|e| format!("Failed clearing cache ({}): {e}", path.display()))?;
+            }
+        }
+        index.write(&self.root)?;
+        Ok(usage_of(&index))
+    }
+
+    fn with_index<T>(
+        &self,
+        update: impl FnOnce(&mut CacheIndex) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let _guard = INDEX_LOCK.lock();
+        let mut index = CacheIndex::read(&self.root);
+        let out = update(&mut index)?;
+        index.write(&self.root)?;
+        Ok(out)
+    }
+
+    fn remove(&self, id: &str) -> Result<(), String> {
+        let removed = self.with_index(|index| Ok(index.entries.remove(id)))?;
+        if let Some(entry) = removed {
+            let _ = fs::remove_file(self.root.join(entry.file));
+        }
+        Ok(())
+    }
+
+    /// Open a sealed entry into the playback dir, checking the plaintext digest.
+    fn open_sealed(&self, entry: &CacheEntry, stored: &Path) -> Result<PathBuf, String> {
+        let dir = self.root.join(PLAYBACK_DIR);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed creating playback dir ({}): {e}", dir.display()))?;
+        let name = stored.file_stem().unwrap_or_default();
+        let out = dir.join(name);
+        if !out.exists() {
+            let sha256 = open_file(&cache_key()?, stored, &out)?;
+            if sha256 != entry.sha256 {
+                let _ = fs::remove_file(&out);
+                return Err("sealed file does not match its sha256".to_string());
+            }
+        }
+        // Bump the mtime so the copy in use is the last one pruned.
+        let _ = fs::File::options()
+            .append(true)
+            .open(&out)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+        prune_playback(&dir);
+        Ok(out)
+    }
+
+    /// Switch one audio entry between sealed and plain storage.
+    fn reseal(&self, entry: &mut CacheEntry, key: Option<&[u8; 32]>) -> Result<(), String> {
+        let current = self.root.join(&entry.file);
+        let next = match key {
+            Some(key) => {
+                let sealed_path = current.with_file_name(format!(
+                    "{}.sealed",
+                    current.file_name().unwrap_or_default().to_string_lossy()
+                ));
+                if seal_file(key, &current, &sealed_path)? != entry.sha256 {
+                    let _ = fs::remove_file(&sealed_path);
+                    return Err("cached file does not match its sha256".to_string());
+                }
+                sealed_path
+            }
+            None => {
+                let plain_path = current.with_extension("");
+                if open_file(&cache_key()?, &current, &plain_path)? != entry.sha256 {
+                    let _ = fs::remove_file(&plain_path);
+                    return Err("sealed file does not match its sha256".to_string());
+                }
+                plain_path
+            }
+        };
+        let _ = fs::remove_file(&current);
+        entry.sealed = key.is_some();
+        entry.bytes = fs::metadata(&next).map(|meta| meta.len()).unwrap_or(0);
+        entry.file = relative_name(entry.kind, &next);
+        Ok(())
+    }
+}
+
+impl From<StoredSettings> for ContentCacheSettings {
+    fn from(stored: StoredSettings) -> Self {
+        Self {
+            max_bytes: stored.max_bytes,
+            encrypt_at_rest: stored.encrypt_at_rest,
+        }
+    }
+}
+
+impl From<ContentCacheSettings> for StoredSettings {
+    fn from(settings: ContentCacheSettings) -> Self {
+        Self {
+            max_bytes: settings.max_bytes,
+            encrypt_at_rest: settings.encrypt_at_rest,
+        }
+    }
+}
+
+fn entry_id(kind: CacheKind, key: &str) -> String {
+    format!("{}:{}", kind.dir(), key.trim())
+}
+
+fn relative_name(kind: CacheKind, path: &Path) -> String {
+    format!(
+        "{}/{}",
+        kind.dir(),
+        path.file_name().unwrap_or_default().to_string_lossy()
+    )
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn usage_of(index: &CacheIndex) -> ContentCacheUsage {
+    let mut usage = ContentCacheUsage {
+        settings: index.settings.into(),
+        audio_files: 0,
+        audio_bytes: 0,
+        cover_files: 0,
+        cover_bytes: 0,
+    };
+    for entry in index.entries.values() {
+        match entry.kind {
+            CacheKind::SharedAudio => {
+                usage.audio_files += 1;
+                usage.audio_bytes += entry.bytes;
+            }
+            CacheKind::Cover => {
+                usage.cover_files += 1;
+                usage.cover_bytes += entry.bytes;
+            }
+        }
+    }
+    usage
+}
+
+fn verify_plain(entry: &CacheEntry, path: &Path) -> Result<(), String> {
+    let on_disk =
+        fs::metadata(path).map_err(|e| format!("cached file missing ({}): {e}", path.display()))?;
+    if on_disk.len() != entry.bytes {
+        return Err(format!(
+            "cached file size changed ({} != {})",
+            on_disk.len(),
+            entry.bytes
+        ));
+    }
+    let (_, sha256) = hash_file(path)?;
+    if sha256 != entry.sha256 {
+        return Err("cached file does not match its sha256".to_string());
+    }
+    Ok(())
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // Staged files can sit on another filesystem (legacy cache dirs); copy instead.
+    let partial = partial_path(to);
+    fs::copy(from, &partial)
+        .and_then(|_| fs::rename(&partial, to))
+        .map_err(|e| format!("Failed moving {} into the cache: {e}", from.display()))?;
+    let _ = fs::remove_file(from);
+    Ok(())
+}
+
+/// Keep only the most recently used opened copies of sealed audio.
+fn prune_playback(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files = entries
+        .flatten()
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, path) in files.into_iter().skip(PLAYBACK_KEEP) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> ContentCache {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-content-cache-{name}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        ContentCache::new(dir)
+    }
+
+    #[test]
+    fn evicts_least_recently_used_past_the_cap() {
+        let cache = temp_cache("lru");
+        cache
+            .configure(ContentCacheSettings {
+                max_bytes: 25,
+                encrypt_at_rest: false,
+            })
+            .unwrap();
+        let a = cache
+            .insert_bytes(CacheKind::Cover, "a", &[1u8; 10], "a.jpg")
+            .unwrap();
+        cache
+            .insert_bytes(CacheKind::Cover, "b", &[2u8; 10], "b.jpg")
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(cache.lookup(CacheKind::Cover, "a"), Some(a));
+
+        cache
+            .insert_bytes(CacheKind::SharedAudio, "c", &[3u8; 10], "c.mp3")
+            .unwrap();
+        assert!(cache.lookup(CacheKind::Cover, "b").is_none());
+        assert!(cache.lookup(CacheKind::Cover, "a").is_some());
+        assert!(cache.lookup(CacheKind::SharedAudio, "c").is_some());
+        let usage = cache.usage();
+        assert_eq!((usage.cover_files, usage.audio_files), (1, 1));
+        assert_eq!(usage.total_bytes(), 20);
+
+        assert_eq!(cache.clear().unwrap().total_bytes(), 0);
+        assert!(cache.lookup(CacheKind::Cover, "a").is_none());
+        assert_eq!(cache.settings().max_bytes, 25);
+        let _ = fs::remove_dir_all(&cache.root);
+    }
+
+    #[test]
+    fn drops_entries_that_fail_their_digest() {
+        let cache = temp_cache("digest");
+        let path = cache
+            .insert_bytes(CacheKind::SharedAudio, "0xabc", b"decrypted audio", "t.mp3")
+            .unwrap();
+        fs::write(&path, b"tampered audio!").unwrap();
+        assert!(cache.lookup(CacheKind::SharedAudio, "0xabc").is_none());
+        assert!(!path.exists());
+        assert_eq!(cache.usage().audio_files, 0);
+        let _ = fs::remove_dir_all(&cache.root);
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+use std::collections::BTreeMap;
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct CacheIndex {
+    #[serde(default)]
+    pub(super) settings: StoredSettings,
+    /// `<kind>:<key>` -> entry.
+    #[serde(default)]
+    pub(super) entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct StoredSettings {
+    pub(super) max_bytes: u64,
+    #[serde(default)]
+    pub(super) encrypt_at_rest: bool,
+}
+
+impl Default for StoredSettings {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_CACHE_MAX_BYTES,
+            encrypt_at_rest: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct CacheEntry {
+    pub(super) kind: CacheKind,
+    /// Path relative to the cache root.
+    pub(super) file: String,
+    /// Size on disk, sealed or not.
+    pub(super) bytes: u64,
+    /// Digest of the plaintext.
+    pub(super) sha256: String,
+    #[serde(default)]
+    pub(super) sealed: bool,
+    pub(super) last_access_ms: i64,
+}
+
+impl CacheIndex {
+    pub(super) fn read(root: &Path) -> Self {
+        let Ok(text) = fs::read_to_string(root.join(INDEX_FILE)) else {
+            return Self::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|err| {
+            log::warn!("[ContentCache] index unreadable, starting empty: {err}");
+            Self::default()
+        })
+    }
+
+    pub(super) fn write(&self, root: &Path) -> Result<(), String> {
+        fs::create_dir_all(root)
+            .map_err(|e| format!("Failed creating cache dir ({}): {e}", root.display()))?;
+        let path = root.join(INDEX_FILE);
+        let partial = path.with_extension("json.part");
+        let encoded = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed encoding cache index: {e}"))?;
+        fs::write(&partial, encoded)
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed writing cache index ({}): {e}", path.display()))
+    }
+
+    pub(super) fn total_bytes(&self) -> u64 {
+        self.entries.values().map(|entry| entry.bytes).sum()
+    }
+
+    /// Drop least recently used entries until the total fits the cap, sparing `keep`.
+    /// Returns the evicted entries so their files can be removed.
+    pub(super) fn evict_to_cap(&mut self, keep: &str) -> Vec<CacheEntry> {
+        let mut evicted = Vec::new();
+        while self.total_bytes() > self.settings.max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(id, _)| id.as_str() != keep)
+                .min_by_key(|(_, entry)| entry.last_access_ms)
+                .map(|(id, _)| id.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                evicted.push(entry);
+            }
+        }
+        evicted
+    }
+}
@@ -0,0 +1,137 @@
+//! At-rest sealing for cached files: the v2 content stream format under a per-account key
+//! kept in the secret store, so sealed files can be opened chunk by chunk.
+
+use super::*;
+use crate::auth::accounts::AccountRegistry;
+use crate::secret_store::SecretStore;
+use std::io::{self, Read};
+
+const CACHE_KEY_SECRET: &str = "content_cache.key";
+
+/// The active account's at-rest key, created on first use.
+pub(super) fn cache_key() -> Result<[u8; 32], String> {
+    let store = SecretStore::shared();
+    let name = AccountRegistry::shared().scoped_secret(CACHE_KEY_SECRET);
+    if let Some(existing) = store.get(&name)? {
+        let bytes =
+            hex::decode(existing.trim()).map_err(|e| format!("Invalid cache key hex: {e}"))?;
+        return bytes
+            .try_into()
+            .map_err(|_| "Invalid cache key length (expected 32 bytes).".to_string());
+    }
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    store.set(&name, &hex::encode(key))?;
+    Ok(key)
+}
+
+/// Passes bytes through while hashing them.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    fn finish(self) -> String {
+        hex::encode(self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(out)?;
+        self.hasher.update(&out[..n]);
+        Ok(n)
+    }
+}
+
+/// Size and sha256 of a plaintext file.
+pub(super) fn hash_file(path: &Path) -> Result<(u64, String), String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed opening cached file ({}): {e}", path.display()))?;
+    let mut reader = HashingReader::new(file);
+    let bytes = io::copy(&mut reader, &mut io::sink())
+        .map_err(|e| format!("Failed reading cached file ({}): {e}", path.display()))?;
+    Ok((bytes, reader.finish()))
+}
+
+/// Seal the plaintext `source` into `dest`; returns the plaintext digest.
+pub(super) fn seal_file(key: &[u8; 32], source: &Path, dest: &Path) -> Result<String, String> {
+    let file = fs::File::open(source)
+        .map_err(|e| format!("Failed opening file to seal ({}): {e}", source.display()))?;
+    let mut plaintext = HashingReader::new(file);
+    let mut sealed = StreamEncryptor::new(&mut plaintext, key, DEFAULT_STREAM_CHUNK_SIZE)?;
+    write_through(&mut sealed, dest)?;
+    Ok(plaintext.finish())
+}
+
+/// Open the sealed `source` into `dest`; returns the plaintext digest.
+pub(super) fn open_file(key: &[u8; 32], source: &Path, dest: &Path) -> Result<String, String> {
+    let file = fs::File::open(source)
+        .map_err(|e| format!("Failed opening sealed file ({}): {e}", source.display()))?;
+    let mut plaintext = HashingReader::new(StreamDecryptor::new(file, key)?);
+    write_through(&mut plaintext, dest)?;
+    Ok(plaintext.finish())
+}
+
+/// Copy `reader` into `dest` via a `.part` file, so `dest` only ever holds whole output.
+fn write_through(reader: &mut impl Read, dest: &Path) -> Result<(), String> {
+    let partial = partial_path(dest);
+    let copied = fs::File::create(&partial).and_then(|mut file| io::copy(reader, &mut file));
+    if let Err(err) = copied {
+        let _ = fs::remove_file(&partial);
+        return Err(format!("Failed writing {}: {err}", dest.display()));
+    }
+    fs::rename(&partial, dest).map_err(|e| format!("Failed finalizing {}: {e}", dest.display()))
+}
+
+pub(super) fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_files_open_to_the_same_digest() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-cache-sealed-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("track.mp3");
+        let audio = (0..200_000u32).map(|i| (i % 253) as u8).collect::<Vec<_>>();
+        fs::write(&plain, &audio).unwrap();
+
+        let key = [7u8; 32];
+        let sealed = dir.join("track.mp3.sealed");
+        let digest = seal_file(&key, &plain, &sealed).unwrap();
+        assert_eq!(
+            hash_file(&plain).unwrap(),
+            (audio.len() as u64, digest.clone())
+        );
+        assert_ne!(fs::read(&sealed).unwrap()[..audio.len()], audio[..]);
+
+        let opened = dir.join("opened.mp3");
+        assert_eq!(open_file(&key, &sealed, &opened).unwrap(), digest);
+        assert_eq!(fs::read(&opened).unwrap(), audio);
+        assert!(open_file(&[8u8; 32], &sealed, &dir.join("wrong.mp3")).is_err());
+        assert!(!dir.join("wrong.mp3").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,11 +3,17 @@ pub(super) const DEFAULT_LOAD_TURBO_UPLOAD_URL: &str = "https://loaded-turbo-api
 pub(super) const DEFAULT_LOAD_TURBO_TOKEN: &str = "ethereum";
 pub(super) const DEFAULT_LOAD_GATEWAY_URL: &str = "https://gateway.s3-node-1.load.network";
 pub(super) const DEFAULT_LOAD_AGENT_URL: &str = "https://load-s3-agent.load.network";
+pub(super) const DEFAULT_ARWEAVE_TURBO_UPLOAD_URL: &str = "https://upload.ardrive.io";
+pub(super) const DEFAULT_ARWEAVE_TURBO_PAYMENT_URL: &str = "https://payment.ardrive.io";
+pub(super) const DEFAULT_ARWEAVE_TURBO_TOKEN: &str = "ethereum";
 pub(super) const DEFAULT_TURBO_FUNDING_PROXY_URL: &str = "http://127.0.0.1:8788";
 pub(super) const DEFAULT_TURBO_FUNDING_TOKEN: &str = "base-eth";
 pub(super) const DEFAULT_BASE_SEPOLIA_RPC_URL: &str = "https://base-sepolia-rpc.publicnode.com/";
 pub(super) const DEFAULT_MEGAETH_RPC_URL: &str = "https://carrot.megaeth.com/rpc";
 pub(super) const DEFAULT_TEMPO_RPC_URL: &str = "https://rpc.moderato.tempo.xyz";
+/// Tempo's account keychain precompile, which tracks the session keys each account authorized.
+pub(super) const DEFAULT_TEMPO_ACCOUNT_KEYCHAIN: &str =
+    "0xAAAAAAAA00000000000000000000000000000000";
 pub(super) const DEFAULT_PLAYLIST_V1: &str = "0xeF6a21324548155630670397DA68318E126510EF";
 pub(super) const DEFAULT_PLAYLIST_SHARE_V1: &str = "0x1912cEa18eAFC17cd0f21F58fCF87E699Be512Aa";
 pub(super) const DEFAULT_SCROBBLE_V4: &str = "0xe00e82086480E61AaC8d5ad8B05B56A582dD0000";
@@ -18,4 +24,8 @@ pub(super) const DEFAULT_SUBGRAPH_PLAYLISTS: &str =
 pub(super) const BASE_SEPOLIA_CHAIN_ID: u64 = 84532;
 pub(super) const DEFAULT_MIN_UPLOAD_CREDIT: f64 = 0.00000001;
 pub(super) const MAX_UPLOAD_BYTES: usize = 500 * 1024 * 1024;
+/// Upper bound on ids one Load tag query collects while widening its window.
+pub(super) const MAX_TAG_QUERY_RESULTS: usize = 1024;
 pub(super) const ALGO_AES_GCM_256: u8 = 1;
+/// Chunked AES-256-GCM (v2 payloads); see `content_crypto/stream.rs`.
+pub(super) const ALGO_AES_GCM_256_STREAM: u8 = 2;
@@ -1,6 +1,8 @@
 use super::*;
 
 mod grant_access;
+mod key_backup;
 mod register_encrypt;
+mod remote_state;
 mod resolve;
 mod upload_register;
@@ -25,7 +25,7 @@ fn parse_uncompressed_p256_pubkey(raw: &str) -> Result<Vec<u8>, String> {
     Ok(decoded)
 }
 
-fn resolve_primary_name_node(user_address: &str) -> Result<Option<[u8; 32]>, String> {
+fn resolve_primary_name_node(user_address: &str) -> LoadStorageResult<Option<[u8; 32]>> {
     let user = user_address
         .parse::<Address>()
         .map_err(|e| format!("Invalid user address ({user_address}): {e}"))?;
@@ -85,9 +85,11 @@ fn resolve_primary_name_node(user_address: &str) -> Result<Option<[u8; 32]>, Str
     Ok(Some(node))
 }
 
-fn fetch_content_pubkey_for_address(user_address: &str) -> Result<Vec<u8>, String> {
+fn fetch_content_pubkey_for_address(user_address: &str) -> LoadStorageResult<Vec<u8>> {
     let Some(node) = resolve_primary_name_node(user_address)? else {
-        return Err("Recipient has no primary name set for contentPubKey lookup.".to_string());
+        return Err(LoadStorageError::NotFound(
+            "Recipient has no primary name set for contentPubKey lookup.".to_string(),
+        ));
     };
 
     let key_bytes = CONTENT_PUBKEY_RECORD_KEY.as_bytes();
@@ -108,7 +110,9 @@ fn fetch_content_pubkey_for_address(user_address: &str) -> Result<Vec<u8>, Strin
         &to_hex_prefixed(call_data.as_slice()),
     )?;
     if output.is_empty() {
-        return Err("Recipient contentPubKey record is empty.".to_string());
+        return Err(LoadStorageError::Other(
+            "Recipient contentPubKey record is empty.".to_string(),
+        ));
     }
     let decoded = abi_decode(&[ParamType::String], output.as_slice())
         .map_err(|e| format!("Failed decoding RecordsV1 text(bytes32,string) response: {e}"))?;
@@ -120,9 +124,11 @@ fn fetch_content_pubkey_for_address(user_address: &str) -> Result<Vec<u8>, Strin
         })
         .unwrap_or_default();
     if value.is_empty() {
-        return Err("Recipient contentPubKey record is not set.".to_string());
+        return Err(LoadStorageError::NotFound(
+            "Recipient contentPubKey record is not set.".to_string(),
+        ));
     }
-    parse_uncompressed_p256_pubkey(&value)
+    Ok(parse_uncompressed_p256_pubkey(&value)?)
 }
 
 impl LoadStorageService {
@@ -131,7 +137,7 @@ impl LoadStorageService {
         auth: &PersistedAuth,
         content_id_hex: &str,
         grantee_address: &str,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<ContentGrant> {
         self.content_share_envelope(auth, content_id_hex, grantee_address)
     }
 
@@ -140,14 +146,26 @@ impl LoadStorageService {
         auth: &PersistedAuth,
         content_id_hex: &str,
         grantee_address: &str,
-    ) -> Result<Value, String> {
-        let owner_address = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+    ) -> LoadStorageResult<ContentGrant> {
+        self.publish_key_envelope(auth, content_id_hex, grantee_address, None)
+    }
+
+    /// Wrap the current content key for `grantee_address` and publish it to LS3. `piece_cid`
+    /// names the blob the key opens, so grantees can follow a re-encrypted upload.
+    fn publish_key_envelope(
+        &mut self,
+        auth: &PersistedAuth,
+        content_id_hex: &str,
+        grantee_address: &str,
+        piece_cid: Option<&str>,
+    ) -> LoadStorageResult<ContentGrant> {
+        let owner_address = signed_in_wallet(auth)?;
         let owner = normalize_address(owner_address)?;
         let grantee = normalize_address(grantee_address)?;
         if owner == grantee {
-            return Err("Cannot share content with your own wallet address.".to_string());
+            return Err(LoadStorageError::Other(
+                "Cannot share content with your own wallet address.".to_string(),
+            ));
         }
 
         let normalized_content_id = normalize_content_id_hex(content_id_hex)?;
@@ -156,16 +174,19 @@ impl LoadStorageService {
             Some(envelope) => envelope,
             None => ensure_wrapped_key_from_ls3(&normalized_content_id, &owner, &owner)?
                 .ok_or_else(|| {
-                    "Missing wrapped content key for this track on this device.".to_string()
+                    LoadStorageError::NotFound(
+                        "Missing wrapped content key for this track on this device.".to_string(),
+                    )
                 })?,
         };
 
         let mut raw_key = ecies_decrypt(&content_keypair.private_key, &wrapped_key)?;
         let recipient_pubkey = fetch_content_pubkey_for_address(&grantee)?;
-        let recipient_envelope = ecies_encrypt(&recipient_pubkey, raw_key.as_slice())?;
+        let recipient_envelope = ecies_encrypt(&recipient_pubkey, raw_key.as_slice());
         raw_key.fill(0);
+        let recipient_envelope = recipient_envelope?;
 
-        let payload = json!({
+        let mut payload = json!({
             "version": 1,
             "contentId": normalized_content_id,
             "owner": owner,
@@ -175,6 +196,9 @@ impl LoadStorageService {
             "iv": hex::encode(&recipient_envelope.iv),
             "ciphertext": hex::encode(&recipient_envelope.ciphertext),
         });
+        if let Some(piece_cid) = piece_cid {
+            payload["pieceCid"] = json!(piece_cid);
+        }
         let payload_bytes = serde_json::to_vec(&payload)
             .map_err(|e| format!("Failed encoding envelope payload JSON: {e}"))?;
         let upload = self.upload_to_load(
@@ -192,14 +216,12 @@ impl LoadStorageService {
             ],
         )?;
 
-        Ok(json!({
-            "success": true,
-            "version": "tempo-envelope-v1",
-            "txHash": Value::Null,
-            "mirrorTxHash": Value::Null,
-            "envelopeId": upload.id,
-            "gatewayUrl": upload.gateway_url,
-        }))
+        Ok(ContentGrant {
+            content_id: normalized_content_id,
+            grantee,
+            envelope_id: upload.id,
+            gateway_url: upload.gateway_url,
+        })
     }
 
     pub fn content_grant_access_batch(
@@ -207,9 +229,11 @@ impl LoadStorageService {
         auth: &PersistedAuth,
         content_ids_hex: &[String],
         grantee_address: &str,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<ContentGrantBatch> {
         if content_ids_hex.is_empty() {
-            return Err("contentIds must be a non-empty array".to_string());
+            return Err(LoadStorageError::Other(
+                "contentIds must be a non-empty array".to_string(),
+            ));
         }
 
         let grantee = grantee_address
@@ -226,32 +250,241 @@ impl LoadStorageService {
             }
         }
         if normalized_content_ids.is_empty() {
-            return Err("contentIds must contain at least one valid entry".to_string());
+            return Err(LoadStorageError::Other(
+                "contentIds must contain at least one valid entry".to_string(),
+            ));
         }
 
         let mut envelope_ids = Vec::<String>::with_capacity(normalized_content_ids.len());
         for content_id in &normalized_content_ids {
-            let payload = self.content_share_envelope(auth, content_id, &grantee_hex)?;
-            let envelope_id = payload
-                .get("envelopeId")
-                .and_then(Value::as_str)
-                .map(str::trim)
-                .filter(|value| !value.is_empty())
-                .ok_or_else(|| {
-                    format!(
-                        "Tempo envelope share response missing envelopeId for contentId={content_id}"
-                    )
-                })?;
-            envelope_ids.push(envelope_id.to_string());
+            let grant = self.content_share_envelope(auth, content_id, &grantee_hex)?;
+            envelope_ids.push(grant.envelope_id);
+        }
+
+        Ok(ContentGrantBatch {
+            grantee: grantee_hex,
+            content_ids: normalized_content_ids,
+            envelope_ids,
+        })
+    }
+
+    /// Start removing `revoked` from a track's grantees: re-encrypt the track under a new key
+    /// and upload it again. Our own wrapped key only switches to the new one once the upload
+    /// succeeds. The caller persists the new pieceCid, then finishes with
+    /// [`Self::content_publish_revocation`]. The old blob stays on Load, so anyone who
+    /// already fetched the old key can still read that copy.
+    pub fn content_revoke_reupload(
+        &mut self,
+        auth: &PersistedAuth,
+        file_path: &str,
+        track_id_hex: &str,
+        revoked: &[String],
+        remaining: &[String],
+        progress: &dyn Fn(UploadProgress) -> UploadControl,
+    ) -> LoadStorageResult<PendingRevocation> {
+        let owner_address = signed_in_wallet(auth)?;
+        let owner = normalize_address(owner_address)?;
+        if revoked.is_empty() {
+            return Err(LoadStorageError::Other(
+                "No grantees selected to revoke.".to_string(),
+            ));
+        }
+        let revoked = revoked
+            .iter()
+            .map(|address| normalize_address(address))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut kept = Vec::<String>::with_capacity(remaining.len());
+        for address in remaining {
+            let address = normalize_address(address)?;
+            if !revoked.contains(&address) && !kept.contains(&address) {
+                kept.push(address);
+            }
         }
 
-        Ok(json!({
-            "success": true,
-            "version": "tempo-envelope-batch-v1",
-            "txHash": Value::Null,
-            "mirrorTxHash": Value::Null,
-            "envelopeIds": envelope_ids,
-            "contentIds": normalized_content_ids,
-        }))
+        let track_id_norm = normalize_bytes32_hex(track_id_hex, "trackId")?;
+        let track_id = B256::from(decode_bytes32_hex(&track_id_norm, "trackId")?);
+        let content_id_hex =
+            to_hex_prefixed(compute_content_id(track_id, &owner)?.as_slice()).to_lowercase();
+
+        // Collect the old envelopes before any new ones exist for the same grantees.
+        let mut superseded = Vec::<String>::new();
+        for grantee in revoked.iter().chain(kept.iter()) {
+            superseded.extend(live_envelope_ids(&content_id_hex, &owner, grantee)?);
+        }
+
+        // A staged upload from before would reuse the key being revoked.
+        UploadJournal::open_default().discard(&content_id_hex);
+        let replaced = self.content_encrypt_upload_replace_by_track_id(
+            auth,
+            file_path,
+            &track_id_norm,
+            "",
+            "",
+            "",
+            progress,
+        )?;
+
+        Ok(PendingRevocation {
+            upload: replaced,
+            revoked_grantees: revoked,
+            remaining_grantees: kept,
+            superseded_envelope_ids: superseded,
+            envelope_ids: Vec::new(),
+        })
+    }
+
+    /// Finish a revoke: re-wrap the new key for the grantees who keep access and mark every
+    /// envelope issued for the old key superseded. Progress is recorded in `pending`, so
+    /// after an error the caller can save it and retry without re-wrapping for grantees
+    /// that already have an envelope.
+    pub fn content_publish_revocation(
+        &mut self,
+        auth: &PersistedAuth,
+        pending: &mut PendingRevocation,
+    ) -> LoadStorageResult<AccessRevocation> {
+        let owner = normalize_address(signed_in_wallet(auth)?)?;
+        let content_id_hex = pending.upload.content.content_id.clone();
+        let piece_cid = pending.upload.content.piece_cid.clone();
+
+        publish_missing_envelopes(pending, |grantee| {
+            self.publish_key_envelope(auth, &content_id_hex, grantee, Some(&piece_cid))
+                .map(|grant| grant.envelope_id)
+        })?;
+
+        let superseded = &pending.superseded_envelope_ids;
+        let supersede_id = if superseded.is_empty() {
+            None
+        } else {
+            let superseded_at_ms = chrono::Utc::now().timestamp_millis();
+            let signature = self.sign_record(
+                auth,
+                &supersede_signing_message(
+                    &content_id_hex,
+                    &owner,
+                    &piece_cid,
+                    superseded_at_ms,
+                    superseded,
+                ),
+            )?;
+            let record = json!({
+                "version": 1,
+                "contentId": content_id_hex,
+                "owner": owner,
+                "pieceCid": piece_cid,
+                "supersededEnvelopeIds": superseded,
+                "revokedGrantees": pending.revoked_grantees,
+                "supersededAtMs": superseded_at_ms,
+                "signature": signature,
+            });
+            let record_bytes = serde_json::to_vec(&record)
+                .map_err(|e| format!("Failed encoding supersede record JSON: {e}"))?;
+            let upload = self.upload_to_load(
+                auth,
+                &record_bytes,
+                None,
+                vec![
+                    json!({"name": "Content-Type", "value": "application/json"}),
+                    json!({"name": "App-Name", "value": "Heaven"}),
+                    json!({"name": "Heaven-Type", "value": SUPERSEDE_TAG_TYPE}),
+                    json!({"name": "Content-Id", "value": content_id_hex}),
+                    json!({"name": "Owner", "value": owner}),
+                    json!({"name": "Upload-Source", "value": "heaven-desktop"}),
+                ],
+            )?;
+            Some(upload.id)
+        };
+
+        let pending = pending.clone();
+        Ok(AccessRevocation {
+            upload: pending.upload,
+            revoked_grantees: pending.revoked_grantees,
+            remaining_grantees: pending.remaining_grantees,
+            envelope_ids: pending.envelope_ids,
+            superseded_envelope_ids: pending.superseded_envelope_ids,
+            supersede_record_id: supersede_id,
+        })
+    }
+}
+
+/// Publish an envelope for each remaining grantee that doesn't have one yet, recording
+/// each id in `pending` as soon as it exists.
+fn publish_missing_envelopes(
+    pending: &mut PendingRevocation,
+    mut publish: impl FnMut(&str) -> LoadStorageResult<String>,
+) -> LoadStorageResult<()> {
+    let done = pending.envelope_ids.len();
+    for grantee in pending.remaining_grantees.iter().skip(done) {
+        let envelope_id = publish(grantee)?;
+        pending.envelope_ids.push(envelope_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending_for(remaining: &[&str]) -> PendingRevocation {
+        PendingRevocation {
+            upload: ContentUpload {
+                content: RegisteredContent {
+                    track_id: format!("0x{}", "11".repeat(32)),
+                    content_id: format!("0x{}", "22".repeat(32)),
+                    piece_cid: "bafknew".to_string(),
+                    gateway_url: "https://gateway.example/bafknew".to_string(),
+                    register_version: "v1".to_string(),
+                    tx_hash: None,
+                },
+                ip_id: None,
+                blob_size: 4096,
+                winc: None,
+                replaced: true,
+                metadata_registered: false,
+            },
+            revoked_grantees: vec!["0xrevoked".to_string()],
+            remaining_grantees: remaining.iter().map(|a| a.to_string()).collect(),
+            superseded_envelope_ids: vec!["env-old-1".to_string(), "env-old-2".to_string()],
+            envelope_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn failed_envelope_publish_resumes_after_the_last_success() {
+        let mut pending = pending_for(&["0xa", "0xb", "0xc"]);
+        let mut published = Vec::<String>::new();
+        let err = publish_missing_envelopes(&mut pending, |grantee| {
+            if grantee == "0xb" {
+                return Err(LoadStorageError::Network("gateway timed out".to_string()));
+            }
+            published.push(grantee.to_string());
+            Ok(format!("env-{grantee}"))
+        })
+        .unwrap_err();
+        assert!(err.is_transient());
+        assert_eq!(published, vec!["0xa"]);
+        assert_eq!(pending.envelope_ids, vec!["env-0xa"]);
+
+        // The saved copy is what a later retry starts from.
+        let saved = serde_json::to_string(&pending).unwrap();
+        let mut restored: PendingRevocation = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored.upload.content, pending.upload.content);
+        assert_eq!(
+            restored.superseded_envelope_ids,
+            pending.superseded_envelope_ids
+        );
+
+        let mut retried = Vec::<String>::new();
+        publish_missing_envelopes(&mut restored, |grantee| {
+            retried.push(grantee.to_string());
+            Ok(format!("env-{grantee}"))
+        })
+        .unwrap();
+        assert_eq!(retried, vec!["0xb", "0xc"]);
+        assert_eq!(restored.envelope_ids, vec!["env-0xa", "env-0xb", "env-0xc"]);
+
+        publish_missing_envelopes(&mut restored, |grantee| {
+            panic!("{grantee} already has an envelope")
+        })
+        .unwrap();
     }
 }
@@ -0,0 +1,295 @@
+use super::register_encrypt::{ensure_tempo_content_pubkey_published, published_content_pubkey};
+use super::*;
+
+impl LoadStorageService {
+    pub fn content_key_status(&mut self) -> LoadStorageResult<ContentKeyStatus> {
+        let keypair = load_or_create_content_keypair()?;
+        let registry = read_device_registry();
+        Ok(ContentKeyStatus {
+            public_key: to_hex_prefixed(&keypair.public_key),
+            fingerprint: content_key_fingerprint(&keypair.public_key),
+            secondary: registry.secondary,
+            cached_keys: cached_wrapped_key_digests().len(),
+            linked_devices: registry
+                .devices
+                .iter()
+                .map(|device| LinkedDeviceSummary {
+                    fingerprint: device.fingerprint.clone(),
+                    public_key: format!("0x{}", device.public_key),
+                    linked_at_ms: device.linked_at_ms,
+                    synced_keys: device.synced.len(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Write the content keypair to `path`, sealed under `passphrase`.
+    pub fn content_key_export_backup(
+        &mut self,
+        path: &Path,
+        passphrase: &str,
+    ) -> LoadStorageResult<ContentKeyBackup> {
+        let keypair = load_or_create_content_keypair()?;
+        let sealed = seal_content_key_backup(&keypair, passphrase)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed creating backup dir ({}): {e}", parent.display()))?;
+        }
+        fs::write(path, sealed).map_err(|e| {
+            format!(
+                "Failed writing content key backup ({}): {e}",
+                path.display()
+            )
+        })?;
+        Ok(ContentKeyBackup {
+            path: path.to_path_buf(),
+            fingerprint: content_key_fingerprint(&keypair.public_key),
+        })
+    }
+
+    /// Restore the keypair sealed in `path`. A key other than the published one is only
+    /// installed with `replace_published`.
+    pub fn content_key_import_backup(
+        &mut self,
+        auth: &PersistedAuth,
+        path: &Path,
+        passphrase: &str,
+        replace_published: bool,
+    ) -> LoadStorageResult<ContentKeyRestoreOutcome> {
+        let text = fs::read_to_string(path).map_err(|e| {
+            format!(
+                "Failed reading content key backup ({}): {e}",
+                path.display()
+            )
+        })?;
+        let keypair =
+            open_content_key_backup(&text, passphrase).map_err(LoadStorageError::Crypto)?;
+        adopt_content_keypair(auth, &keypair, replace_published)
+    }
+
+    /// The private key as 24 BIP-39 words. Anyone holding them can read every track
+    /// shared with this account.
+    pub fn content_key_recovery_phrase(&mut self) -> LoadStorageResult<String> {
+        let keypair = load_or_create_content_keypair()?;
+        Ok(content_key_recovery_phrase(&keypair)?)
+    }
+
+    /// Like [`Self::content_key_import_backup`], from the recovery phrase.
+    pub fn content_key_restore_from_phrase(
+        &mut self,
+        auth: &PersistedAuth,
+        phrase: &str,
+        replace_published: bool,
+    ) -> LoadStorageResult<ContentKeyRestoreOutcome> {
+        let keypair = content_keypair_from_recovery_phrase(phrase)?;
+        adopt_content_keypair(auth, &keypair, replace_published)
+    }
+
+    /// Mark this device as receiving keys from another device of the account (or undo it).
+    /// A secondary device keeps its own key off chain, so shares keep going to the primary.
+    pub fn content_key_set_secondary(
+        &mut self,
+        secondary: bool,
+    ) -> LoadStorageResult<ContentKeyStatus> {
+        let mut registry = read_device_registry();
+        if registry.secondary != secondary {
+            registry.secondary = secondary;
+            write_device_registry(&registry)?;
+        }
+        self.content_key_status()
+    }
+
+    /// Start re-wrapping this device's content keys for another device's content public key.
+    pub fn content_key_link_device(
+        &mut self,
+        auth: &PersistedAuth,
+        public_key_hex: &str,
+    ) -> LoadStorageResult<DeviceKeySync> {
+        let public_key = parse_content_public_key(public_key_hex)?;
+        let own = load_or_create_content_keypair()?;
+        if public_key == own.public_key {
+            return Err(LoadStorageError::Other(
+                "That is this device's own content key.".to_string(),
+            ));
+        }
+        let mut registry = read_device_registry();
+        let device = LinkedDevice::new(&public_key);
+        if !registry
+            .devices
+            .iter()
+            .any(|linked| linked.fingerprint == device.fingerprint)
+        {
+            registry.devices.push(device);
+            write_device_registry(&registry)?;
+        }
+        self.content_key_sync_devices(auth)
+    }
+
+    /// Stop re-wrapping for a device. Envelopes it already received stay readable to it.
+    pub fn content_key_unlink_device(&mut self, fingerprint: &str) -> LoadStorageResult<()> {
+        let mut registry = read_device_registry();
+        let before = registry.devices.len();
+        registry
+            .devices
+            .retain(|device| !device.fingerprint.eq_ignore_ascii_case(fingerprint.trim()));
+        if registry.devices.len() == before {
+            return Err(LoadStorageError::NotFound(format!(
+                "No linked device with fingerprint {fingerprint}."
+            )));
+        }
+        Ok(write_device_registry(&registry)?)
+    }
+
+    /// Publish a device envelope for every cached key a linked device has not received yet,
+    /// or that changed since it last did. Stops early when uploads run out of credit.
+    pub fn content_key_sync_devices(
+        &mut self,
+        auth: &PersistedAuth,
+    ) -> LoadStorageResult<DeviceKeySync> {
+        let wallet = signed_in_wallet(auth)?;
+        let wallet = normalize_address(wallet)?;
+        let own = load_or_create_content_keypair()?;
+        let held = cached_wrapped_key_digests();
+        let mut registry = read_device_registry();
+
+        let mut envelope_ids = Vec::<String>::new();
+        let mut failed_content_ids = Vec::<String>::new();
+        let mut halted = None;
+        'devices: for device in registry.devices.iter_mut() {
+            let device_key = device.public_key_bytes()?;
+            for (content_id, digest) in &held {
+                if device.synced.get(content_id) == Some(digest) {
+                    continue;
+                }
+                match self.publish_device_envelope(
+                    auth,
+                    &wallet,
+                    &own,
+                    content_id,
+                    &device_key,
+                    &device.fingerprint,
+                ) {
+                    Ok(envelope_id) => {
+                        envelope_ids.push(envelope_id);
+                        device.synced.insert(content_id.clone(), digest.clone());
+                    }
+                    Err(err @ LoadStorageError::InsufficientCredit(_)) => {
+                        halted = Some(err);
+                        break 'devices;
+                    }
+                    Err(err) => {
+                        log::warn!(
+                            "[LoadStorage] device key sync failed: device={} contentId={} err={}",
+                            device.fingerprint,
+                            content_id,
+                            err
+                        );
+                        failed_content_ids.push(content_id.clone());
+                    }
+                }
+            }
+        }
+        write_device_registry(&registry)?;
+        if let Some(err) = halted {
+            return Err(err);
+        }
+
+        Ok(DeviceKeySync {
+            devices: registry.devices.len(),
+            envelope_ids,
+            failed_content_ids,
+        })
+    }
+
+    fn publish_device_envelope(
+        &mut self,
+        auth: &PersistedAuth,
+        wallet: &str,
+        own: &ContentKeyPair,
+        content_id: &str,
+        device_key: &[u8],
+        fingerprint: &str,
+    ) -> LoadStorageResult<String> {
+        let wrapped_key = load_wrapped_key_for_content(content_id).ok_or_else(|| {
+            LoadStorageError::NotFound(format!("No cached wrapped key for contentId={content_id}"))
+        })?;
+        let mut raw_key = ecies_decrypt(&own.private_key, &wrapped_key)?;
+        let envelope = ecies_encrypt(device_key, raw_key.as_slice());
+        raw_key.fill(0);
+        let envelope = envelope?;
+
+        // Same shape as a grant envelope, from the wallet to itself, so the receiving device
+        // parses it with the usual envelope checks.
+        let payload = json!({
+            "version": 1,
+            "contentId": content_id,
+            "owner": wallet,
+            "grantee": wallet,
+            "device": fingerprint,
+            "algo": ALGO_AES_GCM_256,
+            "ephemeralPub": hex::encode(&envelope.ephemeral_pub),
+            "iv": hex::encode(&envelope.iv),
+            "ciphertext": hex::encode(&envelope.ciphertext),
+        });
+        let payload_bytes = serde_json::to_vec(&payload)
+            .map_err(|e| format!("Failed encoding device envelope JSON: {e}"))?;
+        let upload = self.upload_to_load(
+            auth,
+            &payload_bytes,
+            None,
+            vec![
+                json!({"name": "Content-Type", "value": "application/json"}),
+                json!({"name": "App-Name", "value": "Heaven"}),
+                json!({"name": "Heaven-Type", "value": DEVICE_ENVELOPE_TAG_TYPE}),
+                json!({"name": "Content-Id", "value": content_id}),
+                json!({"name": "Owner", "value": wallet}),
+                json!({"name": "Device", "value": fingerprint}),
+                json!({"name": "Upload-Source", "value": "heaven-desktop"}),
+            ],
+        )?;
+        Ok(upload.id)
+    }
+}
+
+/// Install `keypair` and publish its public key. Grants made to a published key that differs
+/// from it would stop opening here, so that needs `replace_published`. Linked devices never
+/// publish, so they have nothing to check.
+fn adopt_content_keypair(
+    auth: &PersistedAuth,
+    keypair: &ContentKeyPair,
+    replace_published: bool,
+) -> LoadStorageResult<ContentKeyRestoreOutcome> {
+    let public_key = to_hex_prefixed(&keypair.public_key);
+    if !replace_published && !read_device_registry().secondary {
+        let owner = normalize_address(signed_in_wallet(auth)?)?;
+        let published = published_content_pubkey(&owner)
+            .map_err(|e| format!("Cannot check the published content key: {e}"))?;
+        if let Some(published) = published.filter(|key| !key.eq_ignore_ascii_case(&public_key)) {
+            let published_fingerprint = parse_content_public_key(&published)
+                .map(|key| content_key_fingerprint(&key))
+                .unwrap_or(published);
+            return Ok(ContentKeyRestoreOutcome::NeedsConfirmation {
+                published_fingerprint,
+            });
+        }
+    }
+
+    let (replaced_previous, rewrapped_keys) = install_content_keypair(keypair)?;
+    let published = match ensure_tempo_content_pubkey_published(auth, &keypair.public_key) {
+        Ok(()) => true,
+        Err(err) => {
+            log::warn!(
+                "[LoadStorage] restored contentPubKey publish failed: {}",
+                err
+            );
+            false
+        }
+    };
+    Ok(ContentKeyRestoreOutcome::Restored(ContentKeyRestore {
+        fingerprint: content_key_fingerprint(&keypair.public_key),
+        public_key,
+        replaced_previous,
+        rewrapped_keys,
+        published,
+    }))
+}
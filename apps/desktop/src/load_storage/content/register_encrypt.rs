@@ -1,4 +1,5 @@
 use super::*;
+use std::io::{Read, Write};
 
 const REGISTRY_V1: &str = "0xA111c5cA16752B09fF16B3B8B24BA55a8486aB23";
 const RECORDS_V1: &str = "0x57e36738f02Bb90664d00E4EC0C8507feeF3995c";
@@ -121,14 +122,27 @@ fn set_text_call_data(node: &[u8; 32], key: &str, value: &str) -> Vec<u8> {
     out
 }
 
-fn ensure_tempo_content_pubkey_published(
+/// The content public key published for `owner`'s primary name, if it has both.
+pub(super) fn published_content_pubkey(owner: &str) -> Result<Option<String>, String> {
+    let Some(node) = resolve_primary_name_node_for_owner(owner)? else {
+        return Ok(None);
+    };
+    read_text_record(&node, CONTENT_PUBKEY_RECORD_KEY)
+}
+
+pub(super) fn ensure_tempo_content_pubkey_published(
     auth: &PersistedAuth,
     content_public_key: &[u8],
 ) -> Result<(), String> {
-    let owner = auth
-        .wallet_address()
-        .ok_or("Missing wallet address in auth")?;
+    let owner = signed_in_wallet(auth)?;
     let owner = normalize_address(owner)?;
+    if read_device_registry().secondary {
+        log::info!(
+            "[LoadStorage] contentPubKey publish skipped: this device is linked as secondary for owner={}",
+            owner
+        );
+        return Ok(());
+    }
     let Some(node) = resolve_primary_name_node_for_owner(&owner)? else {
         log::info!(
             "[LoadStorage] contentPubKey publish skipped: no primary name set for owner={}",
@@ -147,6 +161,7 @@ fn ensure_tempo_content_pubkey_published(
     let call_data = set_text_call_data(&node, CONTENT_PUBKEY_RECORD_KEY, desired.as_str());
     let tx_hash = crate::scrobble::submit_tempo_contract_call(
         auth,
+        crate::tempo::TxKind::ContentKey,
         RECORDS_V1,
         call_data,
         GAS_LIMIT_SET_TEXT,
@@ -164,32 +179,44 @@ impl LoadStorageService {
     pub(super) fn encrypt_for_upload(
         &mut self,
         auth: &PersistedAuth,
-        source_bytes: &[u8],
-        content_id: &B256,
-    ) -> Result<Vec<u8>, String> {
-        self.encrypt_for_upload_tempo(auth, source_bytes, content_id)
+        source: impl Read,
+        out: &mut impl Write,
+    ) -> LoadStorageResult<(u64, EciesEnvelope)> {
+        self.encrypt_for_upload_tempo(auth, source, out)
     }
 
+    /// Seal `source` into `out` as a v2 chunked payload under a fresh content key. Returns
+    /// the payload size and the key wrapped to our own content public key; the caller stores
+    /// it once the payload is uploaded.
     fn encrypt_for_upload_tempo(
         &mut self,
         auth: &PersistedAuth,
-        source_bytes: &[u8],
-        content_id: &B256,
-    ) -> Result<Vec<u8>, String> {
-        let mut encrypted = encrypt_audio_blob(source_bytes)?;
+        source: impl Read,
+        out: &mut impl Write,
+    ) -> LoadStorageResult<(u64, EciesEnvelope)> {
+        let mut raw_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut raw_key);
+        let sealed = StreamEncryptor::new(source, &raw_key, DEFAULT_STREAM_CHUNK_SIZE).and_then(
+            |mut encryptor| {
+                std::io::copy(&mut encryptor, out).map_err(|e| {
+                    LoadStorageError::Other(format!("Failed encrypting audio payload: {e}"))
+                })
+            },
+        );
+        let blob_len = match sealed {
+            Ok(blob_len) => blob_len,
+            Err(err) => {
+                raw_key.fill(0);
+                return Err(err);
+            }
+        };
+
         let content_keypair = load_or_create_content_keypair()?;
         if let Err(err) = ensure_tempo_content_pubkey_published(auth, &content_keypair.public_key) {
             log::warn!("[LoadStorage] contentPubKey publish failed: {}", err);
         }
-        let wrapped_key = ecies_encrypt(&content_keypair.public_key, &encrypted.raw_key)?;
-        encrypted.raw_key.fill(0);
-
-        let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
-        save_wrapped_key_for_content(&content_id_hex, &wrapped_key)?;
-
-        let mut blob = Vec::with_capacity(12 + encrypted.ciphertext.len());
-        blob.extend_from_slice(&encrypted.iv);
-        blob.extend_from_slice(&encrypted.ciphertext);
-        Ok(blob)
+        let wrapped_key = ecies_encrypt(&content_keypair.public_key, &raw_key);
+        raw_key.fill(0);
+        Ok((blob_len, wrapped_key?))
     }
 }
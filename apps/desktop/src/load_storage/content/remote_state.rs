@@ -0,0 +1,310 @@
+//! Remote views of uploads and grants, for reconciling the local records against them.
+
+use super::resolve::{json_value_to_i64, resolve_tempo_offchain_piece_cid, timestamp_to_ms};
+use super::*;
+use crate::shared::rpc::http_get_bytes_range;
+
+const GRANTS_PAGE_SIZE: usize = 200;
+const GRANTS_MAX_PAGES: usize = 5;
+/// Content ids per `content_in` filter, to keep queries under gateway size limits.
+const GRANTS_CONTENT_CHUNK: usize = 100;
+
+impl LoadStorageService {
+    /// Ask the tag index, the registry (for onchain registrations) and the gateway about one
+    /// upload. `piece_cid` is the blob the caller has on record; the probe checks that one.
+    pub fn remote_content_state(
+        &mut self,
+        owner_address: &str,
+        track_id: &str,
+        content_id: &str,
+        piece_cid: &str,
+        register_version: &str,
+    ) -> LoadStorageResult<RemoteContentState> {
+        let owner = owner_address.trim().to_lowercase();
+        let track_id = normalize_bytes32_hex(track_id, "trackId")?;
+        let content_id = normalize_content_id_hex(content_id)?;
+
+        let indexed = resolve_tempo_offchain_piece_cid(&owner, &track_id, &content_id)?;
+        let mut state = RemoteContentState {
+            piece_cid: indexed.as_ref().map(|piece| piece.piece_cid.clone()),
+            piece_posted_at_ms: indexed.and_then(|piece| piece.posted_at_ms),
+            ..RemoteContentState::default()
+        };
+        // Tempo uploads never touch the registry; older builds registered onchain.
+        if !register_version.starts_with("tempo-") {
+            let entry = fetch_content_registry_entry(&content_id)?;
+            let registered = entry
+                .owner
+                .trim_start_matches("0x")
+                .chars()
+                .any(|c| c != '0');
+            if registered {
+                state.deactivated = !entry.active;
+                if !entry.piece_cid.is_empty() && state.piece_cid.as_ref() != Some(&entry.piece_cid)
+                {
+                    state.piece_cid = Some(entry.piece_cid);
+                    // The registry doesn't say when its pieceCid was set.
+                    state.piece_posted_at_ms = None;
+                }
+            }
+        }
+
+        let piece_cid = piece_cid.trim();
+        if !piece_cid.is_empty() {
+            let url = format!("{}/resolve/{}", load_gateway_url(), piece_cid);
+            state.blob_served = match http_get_bytes_range(&url, 0, 0) {
+                Ok(_) => Some(true),
+                Err(HttpError::Status(404 | 410, _)) => Some(false),
+                Err(err) => {
+                    log::debug!("[LoadStorage] gateway probe inconclusive for {url}: {err}");
+                    None
+                }
+            };
+        }
+        Ok(state)
+    }
+
+    /// Every grant issued to `grantee`, revoked ones included, newest first.
+    pub fn access_grants_for_grantee(
+        &mut self,
+        grantee: &str,
+    ) -> LoadStorageResult<Vec<RemoteAccessGrant>> {
+        let grantee = grantee.trim().to_lowercase();
+        if !is_hex_address(&grantee) {
+            return Err(LoadStorageError::Other(format!(
+                "Invalid grantee address: {grantee}"
+            )));
+        }
+        query_access_grants(&format!("grantee: \"{grantee}\""))
+    }
+
+    /// Whether `owner` still has an LS3 key envelope out for `grantee` that no key rotation
+    /// superseded. Tempo grants exist only as these envelopes; the subgraph never sees them.
+    pub fn grant_envelope_live(
+        &mut self,
+        content_id: &str,
+        owner_address: &str,
+        grantee: &str,
+    ) -> LoadStorageResult<bool> {
+        let content_id = normalize_content_id_hex(content_id)?;
+        let owner = normalize_address(owner_address)?;
+        let grantee = normalize_address(grantee)?;
+        Ok(!live_envelope_ids(&content_id, &owner, &grantee)?.is_empty())
+    }
+
+    /// Everyone besides the owner who still holds the key to `content_id`: grantees whose
+    /// newest indexed grant is not revoked, plus those with a live LS3 key envelope (the only
+    /// record a Tempo grant leaves).
+    pub fn content_grantees(
+        &mut self,
+        content_id: &str,
+        owner_address: &str,
+    ) -> LoadStorageResult<Vec<String>> {
+        let content_id = normalize_content_id_hex(content_id)?;
+        let owner = normalize_address(owner_address)?;
+        let indexed = self.access_grants_for_contents(std::slice::from_ref(&content_id))?;
+        let mut grantees = Vec::<String>::new();
+        for grantee in granted_grantees(&indexed)
+            .into_iter()
+            .chain(live_envelope_grantees(&content_id, &owner)?)
+        {
+            // Index tags are free for any uploader to set; skip what isn't an address.
+            match normalize_address(&grantee) {
+                Ok(grantee) if grantee != owner && !grantees.contains(&grantee) => {
+                    grantees.push(grantee)
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("[LoadStorage] skipping grantee for {content_id}: {err}"),
+            }
+        }
+        Ok(grantees)
+    }
+
+    /// Every grant on the given contents, revoked ones included.
+    pub fn access_grants_for_contents(
+        &mut self,
+        content_ids: &[String],
+    ) -> LoadStorageResult<Vec<RemoteAccessGrant>> {
+        let ids = content_ids
+            .iter()
+            .map(|id| normalize_content_id_hex(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut grants = Vec::new();
+        for chunk in ids.chunks(GRANTS_CONTENT_CHUNK) {
+            let list = chunk
+                .iter()
+                .map(|id| format!("\"{id}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            grants.extend(query_access_grants(&format!("content_in: [{list}]"))?);
+        }
+        Ok(grants)
+    }
+}
+
+/// Grantees whose newest grant in `grants` is still granted, in first-seen order.
+fn granted_grantees(grants: &[RemoteAccessGrant]) -> Vec<String> {
+    let mut latest = Vec::<&RemoteAccessGrant>::new();
+    for grant in grants {
+        match latest
+            .iter_mut()
+            .find(|seen| seen.grantee_address == grant.grantee_address)
+        {
+            Some(seen) if grant.updated_at_ms > seen.updated_at_ms => *seen = grant,
+            Some(_) => {}
+            None => latest.push(grant),
+        }
+    }
+    latest
+        .into_iter()
+        .filter(|grant| grant.granted && !grant.grantee_address.is_empty())
+        .map(|grant| grant.grantee_address.clone())
+        .collect()
+}
+
+fn is_hex_address(value: &str) -> bool {
+    value.len() == 42
+        && value.starts_with("0x")
+        && value[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn query_access_grants(filter: &str) -> LoadStorageResult<Vec<RemoteAccessGrant>> {
+    let mut grants = Vec::new();
+    for page in 0..GRANTS_MAX_PAGES {
+        let query = format!(
+            "{{ accessGrants(where: {{ {filter} }}, orderBy: updatedAt, orderDirection: desc, first: {GRANTS_PAGE_SIZE}, skip: {}) {{ updatedAt grantee granted content {{ id owner pieceCid trackId }} }} }}",
+            page * GRANTS_PAGE_SIZE
+        );
+        let payload = http_post_json(&subgraph_music_social_url(), json!({ "query": query }))?;
+        if let Some(errors) = payload.get("errors") {
+            return Err(LoadStorageError::Other(format!(
+                "Subgraph accessGrants query failed: {errors}"
+            )));
+        }
+        let entries = payload
+            .get("data")
+            .and_then(|v| v.get("accessGrants"))
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let full_page = entries.len() == GRANTS_PAGE_SIZE;
+        grants.extend(entries.iter().filter_map(parse_access_grant));
+        if !full_page {
+            break;
+        }
+    }
+    Ok(grants)
+}
+
+fn parse_access_grant(entry: &Value) -> Option<RemoteAccessGrant> {
+    let content = entry.get("content")?;
+    let field = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_default()
+    };
+    let content_id = field(content.get("id"));
+    // Without a pieceCid there is nothing to decrypt.
+    let piece_cid = content
+        .get("pieceCid")
+        .and_then(Value::as_str)
+        .map(decode_subgraph_bytes_to_utf8)
+        .unwrap_or_default();
+    if content_id.is_empty() || piece_cid.is_empty() {
+        return None;
+    }
+    // The subgraph stores timestamps as seconds.
+    let updated_at_ms = entry
+        .get("updatedAt")
+        .and_then(json_value_to_i64)
+        .map(timestamp_to_ms)
+        .unwrap_or_default();
+    Some(RemoteAccessGrant {
+        owner_address: field(content.get("owner")),
+        grantee_address: field(entry.get("grantee")),
+        content_id,
+        piece_cid,
+        track_id: Some(field(content.get("trackId"))).filter(|v| !v.is_empty()),
+        granted: entry
+            .get("granted")
+            .and_then(Value::as_bool)
+            .unwrap_or(true),
+        updated_at_ms,
+    })
+}
+
+/// `Bytes` fields come back hex-encoded; pieceCids are UTF-8 text underneath.
+fn decode_subgraph_bytes_to_utf8(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let Some(body) = trimmed.strip_prefix("0x") else {
+        return trimmed.to_string();
+    };
+    match hex::decode(body).ok().map(String::from_utf8) {
+        Some(Ok(text)) => text.trim_matches('\u{0}').trim().to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_revoked_grants_and_hex_piece_cids() {
+        let entry = json!({
+            "updatedAt": "1700000000",
+            "grantee": "0xBEEF000000000000000000000000000000000001",
+            "granted": false,
+            "content": {
+                "id": "0xABC",
+                "owner": "0xF00D000000000000000000000000000000000002",
+                "pieceCid": format!("0x{}", hex::encode("bafy-piece")),
+                "trackId": null
+            }
+        });
+        let grant = parse_access_grant(&entry).unwrap();
+        assert_eq!(grant.content_id, "0xabc");
+        assert_eq!(grant.piece_cid, "bafy-piece");
+        assert_eq!(
+            grant.grantee_address,
+            "0xbeef000000000000000000000000000000000001"
+        );
+        assert!(!grant.granted);
+        assert_eq!(grant.track_id, None);
+        assert_eq!(grant.updated_at_ms, 1_700_000_000_000);
+
+        let no_piece = json!({"grantee": "0x1", "content": {"id": "0xabc", "pieceCid": ""}});
+        assert!(parse_access_grant(&no_piece).is_none());
+    }
+
+    #[test]
+    fn only_grantees_whose_newest_grant_stands_are_kept() {
+        let grant = |grantee: &str, granted: bool, updated_at_ms: i64| RemoteAccessGrant {
+            owner_address: "0xowner".to_string(),
+            grantee_address: grantee.to_string(),
+            content_id: "0xc1".to_string(),
+            piece_cid: "bafy-a".to_string(),
+            track_id: None,
+            granted,
+            updated_at_ms,
+        };
+        let grants = [
+            grant("0xbob", false, 30),
+            grant("0xcarol", true, 20),
+            grant("0xbob", true, 10),
+            grant("0xdave", true, 5),
+            grant("0xdave", false, 1),
+            grant("0xerin", false, 5),
+            grant("0xerin", true, 8),
+        ];
+        assert_eq!(
+            granted_grantees(&grants),
+            vec![
+                "0xcarol".to_string(),
+                "0xdave".to_string(),
+                "0xerin".to_string()
+            ]
+        );
+    }
+}
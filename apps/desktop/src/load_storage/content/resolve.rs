@@ -1,6 +1,25 @@
 use super::*;
 
-fn query_piece_cid_by_tags(filters: Vec<Value>) -> Result<Option<String>, String> {
+/// The newest data item the tag index holds for a track.
+pub(super) struct IndexedPiece {
+    pub(super) piece_cid: String,
+    pub(super) register_version: &'static str,
+    /// When the index says the item was posted; `None` when it carries no timestamp.
+    pub(super) posted_at_ms: Option<i64>,
+}
+
+/// Index and subgraph timestamps come as seconds or milliseconds.
+pub(super) fn timestamp_to_ms(raw: i64) -> i64 {
+    if raw > 1_000_000_000_000 {
+        raw
+    } else {
+        raw.saturating_mul(1000)
+    }
+}
+
+fn query_piece_cid_by_tags(
+    filters: Vec<Value>,
+) -> LoadStorageResult<Option<(String, Option<i64>)>> {
     let payload = http_post_json(
         &format!("{}/tags/query", load_agent_url()),
         json!({
@@ -44,8 +63,8 @@ fn query_piece_cid_by_tags(filters: Vec<Value>) -> Result<Option<String>, String
     }
 
     Ok(best_timestamp_candidate
-        .map(|(_, id)| id)
-        .or(first_candidate))
+        .map(|(timestamp, id)| (id, Some(timestamp_to_ms(timestamp))))
+        .or(first_candidate.map(|id| (id, None))))
 }
 
 fn item_has_heaven_type(item: &Value, expected: &str) -> bool {
@@ -93,7 +112,7 @@ fn item_timestamp_hint(item: &Value) -> Option<i64> {
     None
 }
 
-fn json_value_to_i64(value: &Value) -> Option<i64> {
+pub(super) fn json_value_to_i64(value: &Value) -> Option<i64> {
     match value {
         Value::Number(num) => num
             .as_i64()
@@ -107,19 +126,23 @@ fn json_value_to_i64(value: &Value) -> Option<i64> {
     }
 }
 
-fn resolve_tempo_offchain_piece_cid(
+pub(super) fn resolve_tempo_offchain_piece_cid(
     owner_address: &str,
     track_id_hex: &str,
     content_id_hex: &str,
-) -> Result<Option<(String, &'static str)>, String> {
+) -> LoadStorageResult<Option<IndexedPiece>> {
     let owner = owner_address.to_lowercase();
     let current = query_piece_cid_by_tags(vec![
         json!({"key": "Track-Id", "value": track_id_hex}),
         json!({"key": "Content-Id", "value": content_id_hex}),
         json!({"key": "Owner", "value": owner}),
     ])?;
-    if let Some(piece_cid) = current {
-        return Ok(Some((piece_cid, "tempo-load-index-v1")));
+    if let Some((piece_cid, posted_at_ms)) = current {
+        return Ok(Some(IndexedPiece {
+            piece_cid,
+            register_version: "tempo-load-index-v1",
+            posted_at_ms,
+        }));
     }
 
     // Legacy fallback: older desktop builds wrote Content-Id with trackId value.
@@ -127,8 +150,12 @@ fn resolve_tempo_offchain_piece_cid(
         json!({"key": "Content-Id", "value": track_id_hex}),
         json!({"key": "Owner", "value": owner}),
     ])?;
-    if let Some(piece_cid) = legacy {
-        return Ok(Some((piece_cid, "tempo-load-index-legacy-v1")));
+    if let Some((piece_cid, posted_at_ms)) = legacy {
+        return Ok(Some(IndexedPiece {
+            piece_cid,
+            register_version: "tempo-load-index-legacy-v1",
+            posted_at_ms,
+        }));
     }
 
     Ok(None)
@@ -139,10 +166,8 @@ impl LoadStorageService {
         &mut self,
         auth: &PersistedAuth,
         track_id_hex: &str,
-    ) -> Result<Value, String> {
-        let owner = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+    ) -> LoadStorageResult<RegisteredContent> {
+        let owner = signed_in_wallet(auth)?;
         let owner_norm = owner.to_lowercase();
 
         let track_id_norm = normalize_bytes32_hex(track_id_hex, "trackId")?;
@@ -151,24 +176,26 @@ impl LoadStorageService {
         let content_id = compute_content_id(track_id, owner)?;
         let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
 
-        if let Some((piece_cid, register_version)) =
-            resolve_tempo_offchain_piece_cid(&owner_norm, &track_id_norm, &content_id_hex)?
+        if let Some(IndexedPiece {
+            piece_cid,
+            register_version,
+            ..
+        }) = resolve_tempo_offchain_piece_cid(&owner_norm, &track_id_norm, &content_id_hex)?
         {
-            return Ok(json!({
-                "trackId": track_id_norm,
-                "contentId": content_id_hex,
-                "pieceCid": piece_cid,
-                "gatewayUrl": format!("{}/resolve/{}", load_gateway_url(), piece_cid),
-                "registerVersion": register_version,
-                "txHash": Value::Null,
-                "blockNumber": Value::Null,
-            }));
+            return Ok(RegisteredContent {
+                track_id: track_id_norm,
+                content_id: content_id_hex,
+                gateway_url: format!("{}/resolve/{}", load_gateway_url(), piece_cid),
+                piece_cid,
+                register_version: register_version.to_string(),
+                tx_hash: None,
+            });
         }
 
-        Err(format!(
+        Err(LoadStorageError::NotFound(format!(
             "No offchain Tempo upload found for trackId={} contentId={} owner={}",
             track_id_norm, content_id_hex, owner_norm
-        ))
+        )))
     }
 
     pub fn resolve_registered_content_for_track(
@@ -176,7 +203,7 @@ impl LoadStorageService {
         auth: &PersistedAuth,
         file_path: &str,
         track: TrackMetaInput,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<RegisteredContent> {
         let fallback = infer_title_artist_album(file_path);
         let title = track
             .title
@@ -212,9 +239,7 @@ impl LoadStorageService {
             .filter(|v| !v.is_empty())
             .map(str::to_string);
 
-        let owner = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+        let owner = signed_in_wallet(auth)?;
         let owner_norm = owner.to_lowercase();
 
         let track_id = build_track_id(&title, &artist, &album, mbid.as_deref(), ip_id.as_deref())?;
@@ -222,63 +247,64 @@ impl LoadStorageService {
         let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
         let track_id_hex = to_hex_prefixed(track_id.as_slice()).to_lowercase();
 
-        if let Some((piece_cid, register_version)) =
-            resolve_tempo_offchain_piece_cid(&owner_norm, &track_id_hex, &content_id_hex)?
+        if let Some(IndexedPiece {
+            piece_cid,
+            register_version,
+            ..
+        }) = resolve_tempo_offchain_piece_cid(&owner_norm, &track_id_hex, &content_id_hex)?
         {
-            return Ok(json!({
-                "trackId": track_id_hex,
-                "contentId": content_id_hex,
-                "pieceCid": piece_cid,
-                "gatewayUrl": format!("{}/resolve/{}", load_gateway_url(), piece_cid),
-                "registerVersion": register_version,
-                "txHash": Value::Null,
-                "blockNumber": Value::Null,
-            }));
+            return Ok(RegisteredContent {
+                track_id: track_id_hex,
+                content_id: content_id_hex,
+                gateway_url: format!("{}/resolve/{}", load_gateway_url(), piece_cid),
+                piece_cid,
+                register_version: register_version.to_string(),
+                tx_hash: None,
+            });
         }
 
-        Err(format!(
+        Err(LoadStorageError::NotFound(format!(
             "No offchain Tempo upload found for trackId={} contentId={} owner={}",
             track_id_hex, content_id_hex, owner_norm
-        ))
+        )))
     }
 
     pub fn resolve_shared_track_metadata(
         &mut self,
         content_id_hex: &str,
         track_id_hint: Option<&str>,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<TrackMetadata> {
         let content_id = normalize_content_id_hex(content_id_hex)?;
         let track_id = if let Some(hint) = track_id_hint.filter(|v| !v.trim().is_empty()) {
             normalize_bytes32_hex(hint, "trackId")?
         } else {
-            fetch_track_id_for_content_subgraph(&content_id)?
-                .ok_or_else(|| format!("No trackId found for contentId={content_id}"))?
+            fetch_track_id_for_content_subgraph(&content_id)?.ok_or_else(|| {
+                LoadStorageError::NotFound(format!("No trackId found for contentId={content_id}"))
+            })?
         };
 
         if let Some((title, artist, album)) = fetch_track_metadata_subgraph(&track_id)? {
-            return Ok(json!({
-                "trackId": track_id,
-                "contentId": content_id,
-                "title": title,
-                "artist": artist,
-                "album": album,
-                "source": "subgraph",
-            }));
+            return Ok(TrackMetadata {
+                track_id,
+                title,
+                artist,
+                album,
+                source: "subgraph",
+            });
         }
 
         if let Some((title, artist, album)) = fetch_track_metadata_onchain(&track_id)? {
-            return Ok(json!({
-                "trackId": track_id,
-                "contentId": content_id,
-                "title": title,
-                "artist": artist,
-                "album": album,
-                "source": "onchain",
-            }));
+            return Ok(TrackMetadata {
+                track_id,
+                title,
+                artist,
+                album,
+                source: "onchain",
+            });
         }
 
-        Err(format!(
+        Err(LoadStorageError::NotFound(format!(
             "Track metadata unavailable for contentId={content_id} (trackId={track_id})"
-        ))
+        )))
     }
 }
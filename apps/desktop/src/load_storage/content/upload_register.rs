@@ -7,10 +7,8 @@ impl LoadStorageService {
         file_path: &str,
         _with_cdn: bool,
         track: TrackMetaInput,
-    ) -> Result<Value, String> {
-        let source_bytes = fs::read(file_path)
-            .map_err(|e| format!("Failed to read file for upload ({}): {e}", file_path))?;
-
+        progress: &dyn Fn(UploadProgress) -> UploadControl,
+    ) -> LoadStorageResult<ContentUpload> {
         let fallback = infer_title_artist_album(file_path);
         let title = track
             .title
@@ -46,26 +44,15 @@ impl LoadStorageService {
             .filter(|v| !v.is_empty())
             .map(str::to_string);
 
-        let owner = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+        let owner = signed_in_wallet(auth)?;
 
         let track_id = build_track_id(&title, &artist, &album, mbid.as_deref(), ip_id.as_deref())?;
         let content_id = compute_content_id(track_id, owner)?;
 
-        let encrypted_blob = self.encrypt_for_upload(auth, &source_bytes, &content_id)?;
-
-        let ready = self.ensure_upload_ready(Some(auth), Some(encrypted_blob.len()));
-        if !ready.0 {
-            return Err(ready
-                .1
-                .unwrap_or_else(|| "Load upload endpoint unavailable".to_string()));
-        }
-
-        let upload_result = self.upload_to_load(
+        let (upload_result, blob_size) = self.encrypt_and_upload_to_load(
             auth,
-            &encrypted_blob,
-            Some(&format!("{file_path}.enc")),
+            file_path,
+            &content_id,
             vec![
                 json!({"name": "App-Name", "value": "Heaven Desktop"}),
                 json!({"name": "Content-Id", "value": to_hex_prefixed(content_id.as_slice()).to_lowercase()}),
@@ -73,30 +60,17 @@ impl LoadStorageService {
                 json!({"name": "Owner", "value": owner.to_lowercase()}),
                 json!({"name": "Upload-Source", "value": "heaven-desktop"}),
             ],
+            progress,
         )?;
 
-        let track_id_hex = to_hex_prefixed(track_id.as_slice()).to_lowercase();
-        let register_response = json!({
-            "version": "tempo-direct-upload-v1",
-            "txHash": Value::Null,
-            "blockNumber": Value::Null,
-        });
-        let metadata_registered = false;
-
-        Ok(json!({
-            "trackId": track_id_hex,
-            "ipId": ip_id,
-            "contentId": to_hex_prefixed(content_id.as_slice()),
-            "pieceCid": upload_result.id,
-            "blobSize": encrypted_blob.len(),
-            "uploadSize": encrypted_blob.len(),
-            "gatewayUrl": upload_result.gateway_url,
-            "winc": upload_result.winc,
-            "trackMetadataRegistered": metadata_registered,
-            "registerVersion": register_response.get("version").cloned().unwrap_or(Value::Null),
-            "txHash": register_response.get("txHash").cloned().unwrap_or(Value::Null),
-            "blockNumber": register_response.get("blockNumber").cloned().unwrap_or(Value::Null),
-        }))
+        Ok(content_upload(
+            to_hex_prefixed(track_id.as_slice()).to_lowercase(),
+            &content_id,
+            ip_id,
+            upload_result,
+            blob_size,
+            false,
+        ))
     }
 
     pub fn content_encrypt_upload_replace_by_track_id(
@@ -107,32 +81,19 @@ impl LoadStorageService {
         _title: &str,
         _artist: &str,
         _album: &str,
-    ) -> Result<Value, String> {
-        let source_bytes = fs::read(file_path)
-            .map_err(|e| format!("Failed to read file for upload ({}): {e}", file_path))?;
-
-        let owner = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+        progress: &dyn Fn(UploadProgress) -> UploadControl,
+    ) -> LoadStorageResult<ContentUpload> {
+        let owner = signed_in_wallet(auth)?;
 
         let track_id_norm = normalize_bytes32_hex(track_id_hex, "trackId")?;
         let track_id_bytes = decode_bytes32_hex(&track_id_norm, "trackId")?;
         let track_id = B256::from(track_id_bytes);
         let content_id = compute_content_id(track_id, owner)?;
 
-        let encrypted_blob = self.encrypt_for_upload(auth, &source_bytes, &content_id)?;
-
-        let ready = self.ensure_upload_ready(Some(auth), Some(encrypted_blob.len()));
-        if !ready.0 {
-            return Err(ready
-                .1
-                .unwrap_or_else(|| "Load upload endpoint unavailable".to_string()));
-        }
-
-        let upload_result = self.upload_to_load(
+        let (upload_result, blob_size) = self.encrypt_and_upload_to_load(
             auth,
-            &encrypted_blob,
-            Some(&format!("{file_path}.enc")),
+            file_path,
+            &content_id,
             vec![
                 json!({"name": "App-Name", "value": "Heaven Desktop"}),
                 json!({"name": "Content-Id", "value": to_hex_prefixed(content_id.as_slice()).to_lowercase()}),
@@ -140,31 +101,42 @@ impl LoadStorageService {
                 json!({"name": "Owner", "value": owner.to_lowercase()}),
                 json!({"name": "Upload-Source", "value": "heaven-desktop"}),
             ],
+            progress,
         )?;
 
-        let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
-        let deactivate_payload = Value::Null;
-        let register_response = json!({
-            "version": "tempo-direct-upload-v1",
-            "txHash": Value::Null,
-            "blockNumber": Value::Null,
-        });
-        let metadata_registered = false;
+        Ok(content_upload(
+            track_id_norm,
+            &content_id,
+            None,
+            upload_result,
+            blob_size,
+            true,
+        ))
+    }
+}
 
-        Ok(json!({
-            "replaced": true,
-            "trackId": track_id_norm,
-            "contentId": content_id_hex,
-            "pieceCid": upload_result.id,
-            "blobSize": encrypted_blob.len(),
-            "uploadSize": encrypted_blob.len(),
-            "gatewayUrl": upload_result.gateway_url,
-            "winc": upload_result.winc,
-            "deactivate": deactivate_payload,
-            "trackMetadataRegistered": metadata_registered,
-            "registerVersion": register_response.get("version").cloned().unwrap_or(Value::Null),
-            "txHash": register_response.get("txHash").cloned().unwrap_or(Value::Null),
-            "blockNumber": register_response.get("blockNumber").cloned().unwrap_or(Value::Null),
-        }))
+/// Tempo uploads are not registered onchain, so there is no tx and no metadata record.
+fn content_upload(
+    track_id: String,
+    content_id: &B256,
+    ip_id: Option<String>,
+    upload: UploadResult,
+    blob_size: u64,
+    replaced: bool,
+) -> ContentUpload {
+    ContentUpload {
+        content: RegisteredContent {
+            track_id,
+            content_id: to_hex_prefixed(content_id.as_slice()).to_lowercase(),
+            piece_cid: upload.id,
+            gateway_url: upload.gateway_url,
+            register_version: "tempo-direct-upload-v1".to_string(),
+            tx_hash: None,
+        },
+        ip_id,
+        blob_size,
+        winc: upload.winc,
+        replaced,
+        metadata_registered: false,
     }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::io::{Read, Write};
 
 impl LoadStorageService {
     pub fn probe_content_decrypt_v1(
@@ -7,27 +8,25 @@ impl LoadStorageService {
         content_id_hex: &str,
         piece_cid: &str,
         _gateway_url_hint: Option<&str>,
-    ) -> Result<(), String> {
+    ) -> LoadStorageResult<()> {
         let normalized_content_id = normalize_content_id_hex(content_id_hex)?;
         let piece_cid = piece_cid.trim();
         if piece_cid.is_empty() {
-            return Err("pieceCid is empty".to_string());
+            return Err(LoadStorageError::Other("pieceCid is empty".to_string()));
         }
 
         if load_wrapped_key_for_content(&normalized_content_id).is_some() {
             return Ok(());
         }
 
-        let owner = auth
-            .wallet_address()
-            .ok_or("Missing wallet address in auth")?;
+        let owner = signed_in_wallet(auth)?;
         if ensure_wrapped_key_from_ls3(&normalized_content_id, owner, owner)?.is_some() {
             return Ok(());
         }
 
-        Err(format!(
+        Err(LoadStorageError::NotFound(format!(
             "No wrapped key envelope found for contentId={normalized_content_id} (pieceCid={piece_cid})."
-        ))
+        )))
     }
 
     pub fn decrypt_shared_content_to_local_file(
@@ -39,76 +38,79 @@ impl LoadStorageService {
         file_stem_hint: Option<&str>,
         owner_address_hint: Option<&str>,
         grantee_address_hint: Option<&str>,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<SharedContentFile> {
         let normalized_content_id = normalize_content_id_hex(content_id_hex)?;
 
-        if let Some(existing) = find_cached_shared_audio_path(&normalized_content_id) {
-            return Ok(json!({
-                "contentId": normalized_content_id,
-                "pieceCid": piece_cid,
-                "localPath": existing.to_string_lossy().to_string(),
-                "cacheHit": true,
-            }));
+        if let Some(existing) = cached_shared_audio(&ContentCache::active(), &normalized_content_id)
+        {
+            return Ok(SharedContentFile {
+                content_id: normalized_content_id,
+                piece_cid: piece_cid.to_string(),
+                local_path: existing,
+                bytes: None,
+                cache_hit: true,
+                fetched_from: None,
+                decrypt_chain: None,
+            });
         }
 
         let piece_cid = piece_cid.trim();
         if piece_cid.is_empty() {
-            return Err("pieceCid is empty".to_string());
+            return Err(LoadStorageError::Other("pieceCid is empty".to_string()));
         }
 
-        let mut blob = None;
-        let mut fetched_from = None;
-        let mut fetch_errors = Vec::new();
-        for url in build_shared_gateway_urls(piece_cid, gateway_url_hint) {
-            match http_get_bytes(&url) {
-                Ok(bytes) => {
-                    blob = Some(bytes);
-                    fetched_from = Some(url);
-                    break;
-                }
-                Err(err) => fetch_errors.push(format!("{url}: {err}")),
-            }
+        let first_attempt = self.decrypt_shared_content_tempo(
+            &normalized_content_id,
+            piece_cid,
+            gateway_url_hint,
+            file_stem_hint,
+            owner_address_hint,
+            grantee_address_hint,
+        );
+        let (Err(first_err), Some(owner), Some(grantee)) =
+            (&first_attempt, owner_address_hint, grantee_address_hint)
+        else {
+            return first_attempt;
+        };
+        // Trying again will not help while the gateways are unreachable.
+        if first_err.is_transient() {
+            return first_attempt;
         }
-        let blob = blob.ok_or_else(|| {
-            format!(
-                "Failed to fetch encrypted content blob for pieceCid={piece_cid}: {}",
-                fetch_errors.join(" | ")
-            )
-        })?;
 
+        // The owner may have rotated the key since it was cached here; follow the newest
+        // envelope, and the re-encrypted blob it names.
+        let Ok(Some((_, current_piece_cid))) =
+            refresh_wrapped_key_from_ls3(&normalized_content_id, owner, grantee)
+        else {
+            return first_attempt;
+        };
+        let current_piece_cid = current_piece_cid.unwrap_or_else(|| piece_cid.to_string());
+        let hint = gateway_url_hint.filter(|_| current_piece_cid == piece_cid);
+        log::info!(
+            "[LoadStorage] retrying shared decrypt with refreshed key: contentId={} pieceCid={}",
+            normalized_content_id,
+            current_piece_cid
+        );
         self.decrypt_shared_content_tempo(
             &normalized_content_id,
-            piece_cid,
-            &blob,
-            fetched_from,
+            &current_piece_cid,
+            hint,
             file_stem_hint,
             owner_address_hint,
             grantee_address_hint,
         )
     }
 
+    /// Unwrap the content key, then decrypt the blob into the cache as it downloads.
     fn decrypt_shared_content_tempo(
         &mut self,
         content_id_hex: &str,
         piece_cid: &str,
-        blob: &[u8],
-        fetched_from: Option<String>,
+        gateway_url_hint: Option<&str>,
         file_stem_hint: Option<&str>,
         owner_address_hint: Option<&str>,
         grantee_address_hint: Option<&str>,
-    ) -> Result<Value, String> {
-        if blob.len() < 13 {
-            return Err(format!(
-                "Encrypted payload too small for Tempo decrypt ({} bytes).",
-                blob.len()
-            ));
-        }
-        let iv = &blob[..12];
-        let ciphertext = &blob[12..];
-        if ciphertext.is_empty() {
-            return Err("Encrypted payload missing ciphertext bytes.".to_string());
-        }
-
+    ) -> LoadStorageResult<SharedContentFile> {
         let content_keypair = load_or_create_content_keypair()?;
         let wrapped_key = match load_wrapped_key_for_content(content_id_hex) {
             Some(envelope) => envelope,
@@ -118,44 +120,244 @@ impl LoadStorageService {
                 let grantee = grantee_address_hint
                     .ok_or("Missing grantee address for wrapped-key lookup.")?;
                 ensure_wrapped_key_from_ls3(content_id_hex, owner, grantee)?.ok_or_else(|| {
-                    "No wrapped key envelope found for this shared track.".to_string()
+                    LoadStorageError::NotFound(
+                        "No wrapped key envelope found for this shared track.".to_string(),
+                    )
                 })?
             }
         };
 
+        let stream = open_shared_blob(piece_cid, gateway_url_hint).map_err(|err| {
+            LoadStorageError::from(err).context(format!(
+                "Failed to fetch encrypted content blob for pieceCid={piece_cid}"
+            ))
+        })?;
         let mut raw_key = ecies_decrypt(&content_keypair.private_key, &wrapped_key)?;
-        let decrypted_audio = decrypt_audio_blob(raw_key.as_slice(), iv, ciphertext)?;
-        raw_key.fill(0);
-
-        let ext = infer_audio_extension(&decrypted_audio);
-        let local_path = shared_audio_cache_path(
+        let written = write_shared_payload(
+            &ContentCache::active(),
+            raw_key.as_slice(),
+            stream.reader,
+            piece_cid,
             content_id_hex,
             file_stem_hint.unwrap_or("shared-track"),
-            ext,
         );
-        if let Some(parent) = local_path.parent() {
-            fs::create_dir_all(parent).map_err(|e| {
-                format!(
-                    "Failed creating shared audio cache dir ({}): {e}",
-                    parent.display()
-                )
-            })?;
+        raw_key.fill(0);
+        let written = written?;
+        // The payload authenticated under its key, so these are the bytes the owner sealed.
+        if let Err(err) = pin_shared_blob_digest(piece_cid, &written.blob_sha256) {
+            log::warn!("[LoadStorage] failed pinning blob digest for {piece_cid}: {err}");
         }
-        fs::write(&local_path, &decrypted_audio).map_err(|e| {
-            format!(
-                "Failed writing decrypted shared audio ({}): {e}",
-                local_path.display()
-            )
-        })?;
 
-        Ok(json!({
-            "contentId": content_id_hex,
-            "pieceCid": piece_cid,
-            "localPath": local_path.to_string_lossy().to_string(),
-            "bytes": decrypted_audio.len(),
-            "cacheHit": false,
-            "fetchedFrom": fetched_from,
-            "decryptChain": "tempo-ecies-envelope-v1",
-        }))
+        Ok(SharedContentFile {
+            content_id: content_id_hex.to_string(),
+            piece_cid: piece_cid.to_string(),
+            local_path: written.local_path,
+            bytes: Some(written.bytes),
+            cache_hit: false,
+            fetched_from: Some(stream.url),
+            decrypt_chain: Some(if written.streamed {
+                "tempo-ecies-envelope-v2-stream"
+            } else {
+                "tempo-ecies-envelope-v1"
+            }),
+        })
     }
 }
+
+/// The cached plaintext for `content_id`, adopting a file left by the pre-cache layout.
+fn cached_shared_audio(cache: &ContentCache, content_id: &str) -> Option<PathBuf> {
+    if let Some(path) = cache.lookup(CacheKind::SharedAudio, content_id) {
+        return Some(path);
+    }
+    let legacy = find_legacy_shared_audio_path(content_id)?;
+    let file_name = legacy.file_name()?.to_string_lossy().to_string();
+    match cache.insert_file(CacheKind::SharedAudio, content_id, &legacy, &file_name) {
+        Ok(path) => Some(path),
+        Err(err) => {
+            log::warn!(
+                "[LoadStorage] failed adopting cached shared audio ({}): {}",
+                legacy.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+/// A decrypted blob now in the cache.
+struct WrittenPayload {
+    local_path: PathBuf,
+    bytes: u64,
+    /// sha256 (hex) of the encrypted blob as read.
+    blob_sha256: String,
+    streamed: bool,
+}
+
+/// sha256 of everything read through it, so a blob decrypted as it downloads can still be
+/// held to its pinned digest.
+struct DigestReader<R> {
+    inner: R,
+    digest: Sha256,
+}
+
+impl<R: Read> DigestReader<R> {
+    /// The digest so far, failing when it differs from `pinned`.
+    fn verify(&self, pinned: Option<&str>) -> LoadStorageResult<String> {
+        let actual = hex::encode(self.digest.clone().finalize());
+        match pinned {
+            Some(expected) if !actual.eq_ignore_ascii_case(expected.trim()) => {
+                Err(LoadStorageError::Crypto(format!(
+                    "Encrypted blob does not match its pinned digest (got {actual}, want {expected})"
+                )))
+            }
+            _ => Ok(actual),
+        }
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(out)?;
+        self.digest.update(&out[..n]);
+        Ok(n)
+    }
+}
+
+/// A chunk that fails authentication or a blob cut short is a `Crypto` error; any other
+/// read failure came from the download.
+fn blob_read_error(context: &str, err: std::io::Error) -> LoadStorageError {
+    let message = format!("{context}: {err}");
+    match err.kind() {
+        std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof => {
+            LoadStorageError::Crypto(message)
+        }
+        _ => LoadStorageError::Network(message),
+    }
+}
+
+/// Decrypt the blob read from `body` into the cache. v2 payloads are decrypted chunk by
+/// chunk as they arrive; anything else is read whole and opened as a pre-v2 payload.
+fn write_shared_payload(
+    cache: &ContentCache,
+    raw_key: &[u8],
+    body: impl Read,
+    piece_cid: &str,
+    content_id_hex: &str,
+    file_stem: &str,
+) -> LoadStorageResult<WrittenPayload> {
+    let pinned = pinned_blob_digest(piece_cid);
+    let mut body = DigestReader {
+        inner: body,
+        digest: Sha256::new(),
+    };
+    let mut head = Vec::new();
+    (&mut body)
+        .take(STREAM_HEADER_LEN as u64)
+        .read_to_end(&mut head)
+        .map_err(|e| blob_read_error("Failed reading encrypted content blob", e))?;
+
+    if is_stream_payload(&head) {
+        let (local_path, bytes, blob_sha256) = write_stream_payload(
+            cache,
+            raw_key,
+            &head,
+            &mut body,
+            pinned.as_deref(),
+            content_id_hex,
+            file_stem,
+        )?;
+        return Ok(WrittenPayload {
+            local_path,
+            bytes,
+            blob_sha256,
+            streamed: true,
+        });
+    }
+
+    let mut blob = head;
+    body.read_to_end(&mut blob)
+        .map_err(|e| blob_read_error("Failed reading encrypted content blob", e))?;
+    let blob_sha256 = body.verify(pinned.as_deref())?;
+    check_encrypted_blob(&blob).map_err(LoadStorageError::Crypto)?;
+    let (local_path, bytes) =
+        write_legacy_payload(cache, raw_key, &blob, content_id_hex, file_stem)?;
+    Ok(WrittenPayload {
+        local_path,
+        bytes,
+        blob_sha256,
+        streamed: false,
+    })
+}
+
+/// `iv || ciphertext`, sealed as one AES-256-GCM message by uploads before v2.
+fn write_legacy_payload(
+    cache: &ContentCache,
+    raw_key: &[u8],
+    blob: &[u8],
+    content_id_hex: &str,
+    file_stem: &str,
+) -> LoadStorageResult<(PathBuf, u64)> {
+    let iv = &blob[..12];
+    let ciphertext = &blob[12..];
+    if ciphertext.is_empty() {
+        return Err(LoadStorageError::Crypto(
+            "Encrypted payload missing ciphertext bytes.".to_string(),
+        ));
+    }
+    let decrypted_audio = decrypt_audio_blob(raw_key, iv, ciphertext)?;
+
+    let ext = infer_audio_extension(&decrypted_audio);
+    let file_name = shared_audio_file_name(content_id_hex, file_stem, ext);
+    let local_path = cache
+        .insert_bytes(
+            CacheKind::SharedAudio,
+            content_id_hex,
+            &decrypted_audio,
+            &file_name,
+        )
+        .map_err(|e| format!("Failed caching decrypted shared audio: {e}"))?;
+    Ok((local_path, decrypted_audio.len() as u64))
+}
+
+/// Decrypt a v2 payload into the cache chunk by chunk as `body` delivers it; neither the
+/// payload nor the plaintext is held whole. The staged plaintext only enters the cache
+/// once every chunk authenticated and the blob matched `pinned`.
+fn write_stream_payload<R: Read>(
+    cache: &ContentCache,
+    raw_key: &[u8],
+    head: &[u8],
+    body: &mut DigestReader<R>,
+    pinned: Option<&str>,
+    content_id_hex: &str,
+    file_stem: &str,
+) -> LoadStorageResult<(PathBuf, u64, String)> {
+    let mut reader = StreamDecryptor::new(head.chain(&mut *body), raw_key)?;
+    // Enough leading bytes to recognize the container.
+    let mut audio_head = Vec::new();
+    (&mut reader)
+        .take(16)
+        .read_to_end(&mut audio_head)
+        .map_err(|e| blob_read_error("Failed decrypting audio payload", e))?;
+
+    let ext = infer_audio_extension(&audio_head);
+    let file_name = shared_audio_file_name(content_id_hex, file_stem, ext);
+    let staged = cache.staging_path(&file_name)?;
+    let copied = fs::File::create(&staged)
+        .and_then(|mut file| {
+            file.write_all(&audio_head)?;
+            std::io::copy(&mut reader, &mut file)
+        })
+        .map_err(|e| blob_read_error("Failed writing decrypted shared audio", e));
+    let checked = copied.and_then(|copied| body.verify(pinned).map(|digest| (copied, digest)));
+    let (copied, blob_sha256) = match checked {
+        Ok(checked) => checked,
+        Err(err) => {
+            let _ = fs::remove_file(&staged);
+            return Err(err);
+        }
+    };
+    let local_path = cache
+        .insert_file(CacheKind::SharedAudio, content_id_hex, &staged, &file_name)
+        .map_err(|e| format!("Failed caching decrypted shared audio: {e}"))?;
+    Ok((local_path, audio_head.len() as u64 + copied, blob_sha256))
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::shared::rpc::HttpError;
+
+/// Why a `LoadStorageService` call failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadStorageError {
+    /// Gateway, RPC or subgraph unreachable, timed out, throttled or failing server-side.
+    Network(String),
+    /// No usable sign-in: missing wallet, rejected session or passkey required.
+    Auth(String),
+    /// Turbo credit is below what the operation needs.
+    InsufficientCredit(String),
+    /// The content, envelope, record or playlist does not exist.
+    NotFound(String),
+    /// Encrypting, decrypting or wrapping a content key failed.
+    Crypto(String),
+    /// Invalid input and anything else.
+    Other(String),
+}
+
+pub type LoadStorageResult<T> = Result<T, LoadStorageError>;
+
+impl LoadStorageError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Network(message)
+            | Self::Auth(message)
+            | Self::InsufficientCredit(message)
+            | Self::NotFound(message)
+            | Self::Crypto(message)
+            | Self::Other(message) => message,
+        }
+    }
+
+    /// The same failure with `context` in front of its message.
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let wrap = |message: String| format!("{context}: {message}");
+        match self {
+            Self::Network(message) => Self::Network(wrap(message)),
+            Self::Auth(message) => Self::Auth(wrap(message)),
+            Self::InsufficientCredit(message) => Self::InsufficientCredit(wrap(message)),
+            Self::NotFound(message) => Self::NotFound(wrap(message)),
+            Self::Crypto(message) => Self::Crypto(wrap(message)),
+            Self::Other(message) => Self::Other(wrap(message)),
+        }
+    }
+
+    /// Worth retrying unchanged later.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_))
+    }
+
+    /// One-line explanation for status lines; the full message belongs in logs.
+    pub fn user_message(&self) -> String {
+        match self {
+            Self::Network(_) => {
+                "Load storage is unreachable right now. Check your connection and try again."
+                    .to_string()
+            }
+            Self::Auth(_) => "Sign in again from Wallet to use Load storage.".to_string(),
+            Self::InsufficientCredit(_) => {
+                "Not enough Turbo credit for this upload. Use Add Credits first.".to_string()
+            }
+            Self::NotFound(message) => format!("Not found: {}", first_line(message)),
+            Self::Crypto(message) => format!("Encryption error: {}", first_line(message)),
+            Self::Other(message) => first_line(message).to_string(),
+        }
+    }
+}
+
+fn first_line(message: &str) -> &str {
+    message.lines().next().unwrap_or_default().trim()
+}
+
+impl fmt::Display for LoadStorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for LoadStorageError {}
+
+/// Failing sites pick their variant; a bare message is only ever `Other`.
+impl From<String> for LoadStorageError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for LoadStorageError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+impl From<HttpError> for LoadStorageError {
+    fn from(err: HttpError) -> Self {
+        match err {
+            HttpError::Transport(message) => Self::Network(message),
+            HttpError::Status(401 | 403, message) => Self::Auth(message),
+            HttpError::Status(402, message) => Self::InsufficientCredit(message),
+            HttpError::Status(404 | 410, message) => Self::NotFound(message),
+            HttpError::Status(408 | 429 | 500..=599, message) => Self::Network(message),
+            HttpError::Status(_, message) | HttpError::Response(message) => Self::Other(message),
+        }
+    }
+}
+
+impl From<LoadStorageError> for String {
+    fn from(err: LoadStorageError) -> Self {
+        match err {
+            LoadStorageError::Network(message)
+            | LoadStorageError::Auth(message)
+            | LoadStorageError::InsufficientCredit(message)
+            | LoadStorageError::NotFound(message)
+            | LoadStorageError::Crypto(message)
+            | LoadStorageError::Other(message) => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_failures_map_by_status() {
+        let err = LoadStorageError::from(HttpError::Status(402, "payment required".to_string()));
+        assert!(matches!(err, LoadStorageError::InsufficientCredit(_)));
+        let err = LoadStorageError::from(HttpError::Status(503, "busy".to_string()));
+        assert!(err.is_transient());
+        let err = LoadStorageError::from(HttpError::Status(404, "none".to_string()));
+        assert!(matches!(err, LoadStorageError::NotFound(_)));
+        let err = LoadStorageError::from(HttpError::Transport("connection refused".to_string()));
+        assert!(err.is_transient());
+        let err = LoadStorageError::from(HttpError::Response("execution reverted".to_string()));
+        assert!(matches!(err, LoadStorageError::Other(_)));
+    }
+
+    #[test]
+    fn bare_messages_are_not_sniffed() {
+        let err = LoadStorageError::from("Sign in with your passkey: connection insufficient");
+        assert!(matches!(err, LoadStorageError::Other(_)));
+        let err = LoadStorageError::Network("timed out".to_string()).context("Balance check");
+        assert_eq!(
+            err,
+            LoadStorageError::Network("Balance check: timed out".to_string())
+        );
+    }
+
+    #[test]
+    fn converting_back_to_string_keeps_the_message() {
+        let err = LoadStorageError::Auth("Missing wallet address in auth".to_string());
+        assert_eq!(err.to_string(), "Missing wallet address in auth");
+        assert_eq!(String::from(err), "Missing wallet address in auth");
+    }
+}
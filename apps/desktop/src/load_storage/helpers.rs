@@ -2,16 +2,20 @@ use super::*;
 
 mod balance;
 mod chain;
+mod chunked_upload;
 mod content_crypto;
 mod env;
 mod ids;
 mod shared_audio;
 mod upload;
+mod upload_session;
 
 pub(super) use balance::*;
 pub(super) use chain::*;
+pub(super) use chunked_upload::*;
 pub(super) use content_crypto::*;
 pub(super) use env::*;
 pub(super) use ids::*;
 pub(super) use shared_audio::*;
 pub(super) use upload::*;
+pub(super) use upload_session::*;
@@ -96,6 +96,24 @@ pub(crate) fn extract_balance_hint(value: &Value) -> Option<f64> {
         .fold(None, |acc, v| Some(acc.map(|x| x.max(v)).unwrap_or(v)))
 }
 
+pub(crate) fn extract_price_hint(value: &Value) -> Option<f64> {
+    let map = value.as_object()?;
+    ["credits", "price", "amount", "winc"]
+        .iter()
+        .filter_map(|key| map.get(*key))
+        .find_map(|v| match v {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        })
+        .filter(|v| v.is_finite() && *v >= 0.0)
+}
+
+/// Largest payload the upload service accepts without charging, from its `/info` payload.
+pub(crate) fn free_upload_limit_bytes(info: &Value) -> Option<u64> {
+    info.get("freeUploadLimitBytes").and_then(Value::as_u64)
+}
+
 pub(crate) fn collect_balance_candidates(value: &Value, out: &mut Vec<f64>) {
     match value {
         Value::Number(n) => {
@@ -1,2 +1,138 @@
 // Content access mirror (Base Sepolia) was removed as part of the Lit→Tempo migration.
 // All content access control is now handled via ECIES key sharing on MegaETH.
+use super::*;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Expiry (0 for none) of (account, key) pairs already confirmed unrevoked, so repeated
+/// lookups skip the RPC round trip. A later revocation shows up after a restart.
+fn confirmed_account_keys() -> &'static Mutex<HashMap<(String, String), u64>> {
+    static CONFIRMED: OnceLock<Mutex<HashMap<(String, String), u64>>> = OnceLock::new();
+    CONFIRMED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Past-authorization answers for keys no longer authorized; history doesn't change.
+fn checked_key_history() -> &'static Mutex<HashMap<(String, String, u64), bool>> {
+    static HISTORY: OnceLock<Mutex<HashMap<(String, String, u64), bool>>> = OnceLock::new();
+    HISTORY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// What the account keychain holds for one key at some block.
+struct KeychainEntry {
+    /// False for a key the account never authorized.
+    known: bool,
+    expiry: u64,
+    revoked: bool,
+}
+
+impl KeychainEntry {
+    fn signs_at(&self, at_secs: u64) -> bool {
+        self.known && !self.revoked && (self.expiry == 0 || at_secs < self.expiry)
+    }
+}
+
+fn fetch_keychain_entry(
+    account: Address,
+    key: Address,
+    block: &str,
+) -> LoadStorageResult<KeychainEntry> {
+    let mut call_data = Vec::with_capacity(4 + 64);
+    call_data.extend_from_slice(&keccak256(b"getKey(address,address)")[..4]);
+    call_data.extend_from_slice(&[0u8; 12]);
+    call_data.extend_from_slice(account.as_slice());
+    call_data.extend_from_slice(&[0u8; 12]);
+    call_data.extend_from_slice(key.as_slice());
+    let output = eth_call_raw_at(
+        &tempo_rpc_url(),
+        &tempo_account_keychain(),
+        &to_hex_prefixed(&call_data),
+        block,
+    )?;
+    let decoded = abi_decode(
+        &[
+            ParamType::Uint(8),
+            ParamType::Address,
+            ParamType::Uint(64),
+            ParamType::Bool,
+            ParamType::Bool,
+        ],
+        &output,
+    )
+    .map_err(|e| format!("Failed decoding AccountKeychain getKey response: {e}"))?;
+    // An unknown key comes back zeroed, so its keyId won't match.
+    match (decoded.get(1), decoded.get(2), decoded.get(4)) {
+        (Some(Token::Address(key_id)), Some(Token::Uint(expiry)), Some(Token::Bool(revoked))) => {
+            Ok(KeychainEntry {
+                known: key_id.as_bytes() == key.as_slice(),
+                expiry: expiry.low_u64(),
+                revoked: *revoked,
+            })
+        }
+        _ => Err(LoadStorageError::Other(format!(
+            "Unexpected AccountKeychain getKey response size: {}",
+            decoded.len()
+        ))),
+    }
+}
+
+/// Whether `key` signed for `account` at `at_secs`: the account itself, or a session key the
+/// account's Tempo keychain had authorized, unexpired and unrevoked, at that time. Session
+/// keys are revoked on every renewal, so a key revoked since is checked at the block mined
+/// at `at_secs`.
+pub(crate) fn account_key_authorized_at(
+    account: &str,
+    key: &str,
+    at_secs: u64,
+) -> LoadStorageResult<bool> {
+    let account = account
+        .trim()
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid account address ({account}): {e}"))?;
+    let key = key
+        .trim()
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid signing key address ({key}): {e}"))?;
+    if account == key {
+        return Ok(true);
+    }
+    let cache_key = (
+        to_hex_prefixed(account.as_slice()).to_lowercase(),
+        to_hex_prefixed(key.as_slice()).to_lowercase(),
+    );
+    let confirmed_expiry = confirmed_account_keys()
+        .lock()
+        .ok()
+        .and_then(|confirmed| confirmed.get(&cache_key).copied());
+    if let Some(expiry) = confirmed_expiry {
+        return Ok(expiry == 0 || at_secs < expiry);
+    }
+
+    let current = fetch_keychain_entry(account, key, "latest")?;
+    if current.known && !current.revoked {
+        if let Ok(mut confirmed) = confirmed_account_keys().lock() {
+            confirmed.insert(cache_key, current.expiry);
+        }
+        return Ok(current.signs_at(at_secs));
+    }
+    if current.known && current.expiry != 0 && at_secs >= current.expiry {
+        return Ok(false);
+    }
+    let history_key = (cache_key.0, cache_key.1, at_secs);
+    if let Some(authorized) = checked_key_history()
+        .lock()
+        .ok()
+        .and_then(|history| history.get(&history_key).copied())
+    {
+        return Ok(authorized);
+    }
+    let authorized = match block_at_timestamp(&tempo_rpc_url(), at_secs)? {
+        Some(block) => {
+            fetch_keychain_entry(account, key, &format!("{block:#x}"))?.signs_at(at_secs)
+        }
+        None => false,
+    };
+    if let Ok(mut history) = checked_key_history().lock() {
+        history.insert(history_key, authorized);
+    }
+    Ok(authorized)
+}
@@ -2,7 +2,7 @@ use super::*;
 
 pub(crate) fn fetch_track_id_for_content_subgraph(
     content_id_hex: &str,
-) -> Result<Option<String>, String> {
+) -> LoadStorageResult<Option<String>> {
     let content_id = normalize_content_id_hex(content_id_hex)?;
     let query = format!(
         "{{ contentEntries(where: {{ id: \"{content_id}\" }}, first: 1) {{ id trackId }} }}"
@@ -32,7 +32,7 @@ pub(crate) fn fetch_track_id_for_content_subgraph(
 
 pub(crate) fn fetch_track_metadata_subgraph(
     track_id_hex: &str,
-) -> Result<Option<(String, String, String)>, String> {
+) -> LoadStorageResult<Option<(String, String, String)>> {
     let track_id = normalize_bytes32_hex(track_id_hex, "trackId")?;
     let query = format!(
         "{{ tracks(where: {{ id_in: [\"{track_id}\"] }}, first: 1) {{ id title artist album }} }}"
@@ -81,7 +81,7 @@ pub(crate) fn fetch_track_metadata_subgraph(
 
 pub(crate) fn fetch_track_metadata_onchain(
     track_id_hex: &str,
-) -> Result<Option<(String, String, String)>, String> {
+) -> LoadStorageResult<Option<(String, String, String)>> {
     let track_id = decode_bytes32_hex(track_id_hex, "trackId")?;
     let mut call_data = Vec::with_capacity(4 + 32);
     call_data.extend_from_slice(&keccak256(b"getTrack(bytes32)")[..4]);
@@ -111,10 +111,10 @@ pub(crate) fn fetch_track_metadata_onchain(
     )
     .map_err(|e| format!("Failed decoding ScrobbleV4 getTrack response: {e}"))?;
     if decoded.len() != 8 {
-        return Err(format!(
+        return Err(LoadStorageError::Other(format!(
             "Unexpected ScrobbleV4 getTrack response size: {}",
             decoded.len()
-        ));
+        )));
     }
 
     let title = match &decoded[0] {
@@ -1,6 +1,16 @@
 use super::*;
 
-pub(crate) fn eth_call_raw(rpc_url: &str, to: &str, data_hex: &str) -> Result<Vec<u8>, String> {
+pub(crate) fn eth_call_raw(rpc_url: &str, to: &str, data_hex: &str) -> LoadStorageResult<Vec<u8>> {
+    eth_call_raw_at(rpc_url, to, data_hex, "latest")
+}
+
+/// [`eth_call_raw`] against the state at `block` (a block tag or hex quantity).
+pub(crate) fn eth_call_raw_at(
+    rpc_url: &str,
+    to: &str,
+    data_hex: &str,
+    block: &str,
+) -> LoadStorageResult<Vec<u8>> {
     let to_addr = to
         .parse::<Address>()
         .map_err(|e| format!("Invalid contract address ({to}): {e}"))?;
@@ -13,24 +23,82 @@ pub(crate) fn eth_call_raw(rpc_url: &str, to: &str, data_hex: &str) -> Result<Ve
                 "to": to_hex_prefixed(to_addr.as_slice()),
                 "data": data_hex,
             },
-            "latest"
+            block
         ]
     });
 
     let response = http_post_json(rpc_url, payload)?;
     if let Some(err) = response.get("error") {
-        return Err(format!("RPC eth_call error ({rpc_url}): {err}"));
+        return Err(LoadStorageError::Other(format!(
+            "RPC eth_call error ({rpc_url}): {err}"
+        )));
     }
     let result_hex = response
         .get("result")
         .and_then(Value::as_str)
         .ok_or_else(|| format!("RPC eth_call missing result ({rpc_url})"))?;
-    decode_eth_hex_bytes(result_hex)
+    Ok(decode_eth_hex_bytes(result_hex)?)
+}
+
+/// Number and timestamp (seconds) of `block` (a block tag or hex quantity).
+fn fetch_block_header(rpc_url: &str, block: &str) -> LoadStorageResult<(u64, u64)> {
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": [block, false],
+    });
+    let response = http_post_json(rpc_url, payload)?;
+    if let Some(err) = response.get("error") {
+        return Err(LoadStorageError::Other(format!(
+            "RPC eth_getBlockByNumber error ({rpc_url}): {err}"
+        )));
+    }
+    let quantity = |field: &str| {
+        response
+            .get("result")
+            .and_then(|block| block.get(field))
+            .and_then(Value::as_str)
+            .and_then(|raw| u64::from_str_radix(raw.trim_start_matches("0x"), 16).ok())
+    };
+    match (quantity("number"), quantity("timestamp")) {
+        (Some(number), Some(timestamp)) => Ok((number, timestamp)),
+        _ => Err(LoadStorageError::NotFound(format!(
+            "RPC eth_getBlockByNumber returned no block {block} ({rpc_url})"
+        ))),
+    }
+}
+
+/// The last block mined at or before `timestamp_secs`, or `None` if it predates the chain.
+pub(crate) fn block_at_timestamp(
+    rpc_url: &str,
+    timestamp_secs: u64,
+) -> LoadStorageResult<Option<u64>> {
+    let (latest, latest_at) = fetch_block_header(rpc_url, "latest")?;
+    if latest_at <= timestamp_secs {
+        return Ok(Some(latest));
+    }
+    let (mut low, mut high) = (0u64, latest);
+    let (_, genesis_at) = fetch_block_header(rpc_url, "0x0")?;
+    if genesis_at > timestamp_secs {
+        return Ok(None);
+    }
+    // Invariant: block `low` is at or before the timestamp, block `high` is after it.
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let (_, mid_at) = fetch_block_header(rpc_url, &format!("{mid:#x}"))?;
+        if mid_at <= timestamp_secs {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(Some(low))
 }
 
 pub(crate) fn fetch_content_registry_entry(
     content_id_hex: &str,
-) -> Result<ContentRegistryEntry, String> {
+) -> LoadStorageResult<ContentRegistryEntry> {
     let content_id = decode_hex_32(content_id_hex)?;
     let mut call_data = Vec::with_capacity(4 + 32);
     call_data.extend_from_slice(&keccak256(b"getContent(bytes32)")[..4]);
@@ -42,10 +110,10 @@ pub(crate) fn fetch_content_registry_entry(
         &to_hex_prefixed(&call_data),
     )?;
     if output.is_empty() {
-        return Err(format!(
+        return Err(LoadStorageError::Other(format!(
             "ContentRegistry returned empty response for contentId={}",
             normalize_content_id_hex(content_id_hex)?
-        ));
+        )));
     }
 
     let decoded = abi_decode(
@@ -61,18 +129,18 @@ pub(crate) fn fetch_content_registry_entry(
     )
     .map_err(|e| format!("Failed decoding ContentRegistry getContent response: {e}"))?;
     if decoded.len() != 6 {
-        return Err(format!(
+        return Err(LoadStorageError::Other(format!(
             "Unexpected ContentRegistry getContent response size: {}",
             decoded.len()
-        ));
+        )));
     }
 
     let owner = match &decoded[0] {
         Token::Address(addr) => format!("{:#x}", addr),
         other => {
-            return Err(format!(
+            return Err(LoadStorageError::Other(format!(
                 "Unexpected owner type in ContentRegistry response: {other:?}"
-            ));
+            )));
         }
     };
 
@@ -81,18 +149,18 @@ pub(crate) fn fetch_content_registry_entry(
             String::from_utf8(bytes.clone()).unwrap_or_else(|_| to_hex_prefixed(bytes.as_slice()))
         }
         other => {
-            return Err(format!(
+            return Err(LoadStorageError::Other(format!(
                 "Unexpected pieceCid type in ContentRegistry response: {other:?}"
-            ));
+            )));
         }
     };
 
     let active = match &decoded[5] {
         Token::Bool(v) => *v,
         other => {
-            return Err(format!(
+            return Err(LoadStorageError::Other(format!(
                 "Unexpected active flag type in ContentRegistry response: {other:?}"
-            ));
+            )));
         }
     };
 
@@ -175,7 +243,7 @@ pub(crate) fn normalize_lit_action_response(raw: Value, label: &str) -> Result<V
     }
 }
 
-pub(crate) fn fetch_playlist_user_nonce(user_address: &str) -> Result<String, String> {
+pub(crate) fn fetch_playlist_user_nonce(user_address: &str) -> LoadStorageResult<String> {
     let user = user_address
         .parse::<Address>()
         .map_err(|e| format!("Invalid user address ({user_address}): {e}"))?;
@@ -192,15 +260,17 @@ pub(crate) fn fetch_playlist_user_nonce(user_address: &str) -> Result<String, St
         &to_hex_prefixed(&call_data),
     )?;
     if output.is_empty() {
-        return Err("PlaylistV1 ownerNonces returned empty response".to_string());
+        return Err(LoadStorageError::Other(
+            "PlaylistV1 ownerNonces returned empty response".to_string(),
+        ));
     }
 
     let decoded = abi_decode(&[ParamType::Uint(256)], &output)
         .map_err(|e| format!("Failed decoding PlaylistV1 ownerNonces response: {e}"))?;
     match decoded.first() {
         Some(Token::Uint(v)) => Ok(v.to_string()),
-        other => Err(format!(
+        other => Err(LoadStorageError::Other(format!(
             "Unexpected PlaylistV1 ownerNonces response payload: {other:?}"
-        )),
+        ))),
     }
 }
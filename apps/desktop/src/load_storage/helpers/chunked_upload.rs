@@ -0,0 +1,587 @@
+use super::*;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Signed DataItems up to this size go up in a single request.
+pub(crate) const SINGLE_REQUEST_UPLOAD_MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Used when the server does not announce its own chunk size.
+const DEFAULT_UPLOAD_CHUNK_BYTES: u64 = 5 * 1024 * 1024;
+const CHUNK_RETRY_ATTEMPTS: u32 = 4;
+const CHUNK_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const FINALIZE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const FINALIZE_POLL_ATTEMPTS: u32 = 90;
+const CHUNK_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Turbo-compatible multipart upload API (`/v1/chunks/{token}/...`).
+pub(crate) struct ChunkedUploadEndpoint {
+    base_url: String,
+    token: String,
+    retry_delay: Duration,
+    poll_interval: Duration,
+}
+
+enum RequestError {
+    /// Transport failures, 5xx and 429; worth another attempt.
+    Transient(String),
+    Fatal(String),
+}
+
+impl RequestError {
+    fn into_error(self) -> LoadStorageError {
+        match self {
+            Self::Transient(message) => LoadStorageError::Network(message),
+            Self::Fatal(message) => LoadStorageError::Other(message),
+        }
+    }
+}
+
+impl ChunkedUploadEndpoint {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            base_url: load_turbo_upload_url(),
+            token: load_turbo_upload_token(),
+            retry_delay: CHUNK_RETRY_BASE_DELAY,
+            poll_interval: FINALIZE_POLL_INTERVAL,
+        }
+    }
+
+    fn url(&self, tail: &str) -> String {
+        format!("{}/v1/chunks/{}/{tail}", self.base_url, self.token)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        tail: &str,
+        body: Option<&[u8]>,
+    ) -> Result<(u16, Value), RequestError> {
+        let url = self.url(tail);
+        let sent = match body {
+            Some(bytes) => ureq::post(&url)
+                .header("Content-Type", "application/octet-stream")
+                .config()
+                .timeout_global(Some(CHUNK_REQUEST_TIMEOUT))
+                .http_status_as_error(false)
+                .build()
+                .send(bytes),
+            None if method == "POST" => ureq::post(&url)
+                .config()
+                .timeout_global(Some(CHUNK_REQUEST_TIMEOUT))
+                .http_status_as_error(false)
+                .build()
+                .send_empty(),
+            None => ureq::get(&url)
+                .config()
+                .timeout_global(Some(CHUNK_REQUEST_TIMEOUT))
+                .http_status_as_error(false)
+                .build()
+                .call(),
+        };
+        let mut resp = sent
+            .map_err(|e| RequestError::Transient(format!("Load {method} failed ({url}): {e}")))?;
+        let status = resp.status().as_u16();
+        let body = read_json_or_text(&mut resp);
+        if status >= 400 {
+            let message = body
+                .get("error")
+                .or_else(|| body.get("raw"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("status {status}"));
+            let message = format!("Load {method} {url} failed: {message}");
+            if status >= 500 || status == 429 {
+                return Err(RequestError::Transient(message));
+            }
+            return Ok((status, json!({ "error": message })));
+        }
+        Ok((status, body))
+    }
+
+    fn request_with_retry(
+        &self,
+        method: &str,
+        tail: &str,
+        body: Option<&[u8]>,
+    ) -> Result<(u16, Value), RequestError> {
+        let mut attempt = 0;
+        loop {
+            match self.request(method, tail, body) {
+                Ok(ok) => return Ok(ok),
+                Err(RequestError::Transient(message)) if attempt + 1 < CHUNK_RETRY_ATTEMPTS => {
+                    attempt += 1;
+                    log::warn!(
+                        "[LoadStorage] chunked upload request failed (attempt {attempt}/{CHUNK_RETRY_ATTEMPTS}): {message}"
+                    );
+                    std::thread::sleep(self.retry_delay * 2u32.pow(attempt - 1));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Start a server-side upload; returns its id and the chunk size to use.
+    fn create(&self) -> LoadStorageResult<(String, u64)> {
+        let (status, body) = self
+            .request_with_retry("GET", "-1/-1", None)
+            .map_err(RequestError::into_error)?;
+        if status >= 400 {
+            return Err(HttpError::Status(status, error_message(&body)).into());
+        }
+        let id = body
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or("Chunked upload create response did not include an upload id")?;
+        let chunk_size = body
+            .get("chunkSize")
+            .and_then(Value::as_u64)
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_UPLOAD_CHUNK_BYTES);
+        Ok((id.to_string(), chunk_size))
+    }
+
+    /// Bytes the server holds contiguously from the start, or `None` once the upload id is
+    /// gone (expired or already cleaned up).
+    fn acknowledged_prefix(&self, upload_id: &str) -> LoadStorageResult<Option<u64>> {
+        let (status, body) = self
+            .request_with_retry("GET", &format!("{upload_id}/-1"), None)
+            .map_err(RequestError::into_error)?;
+        if status == 404 {
+            return Ok(None);
+        }
+        if status >= 400 {
+            return Err(HttpError::Status(status, error_message(&body)).into());
+        }
+        Ok(Some(contiguous_chunk_prefix(&body)))
+    }
+
+    fn post_chunk(&self, upload_id: &str, offset: u64, bytes: &[u8]) -> LoadStorageResult<()> {
+        let (status, body) = self
+            .request_with_retry("POST", &format!("{upload_id}/{offset}"), Some(bytes))
+            .map_err(RequestError::into_error)?;
+        if status >= 400 {
+            return Err(HttpError::Status(status, error_message(&body)).into());
+        }
+        Ok(())
+    }
+
+    /// Ask the server to assemble the upload and wait for its receipt. `Fatal` means the
+    /// server rejected the item and the staged copy is not worth resuming.
+    fn finalize(&self, upload_id: &str) -> Result<Value, RequestError> {
+        let (status, body) =
+            self.request_with_retry("POST", &format!("{upload_id}/finalize"), None)?;
+        if status >= 400 {
+            // A finalize that already went through before a restart is answered with an
+            // error; the status endpoint still knows the outcome.
+            log::warn!(
+                "[LoadStorage] chunked upload finalize rejected, checking status: {}",
+                error_message(&body)
+            );
+        }
+
+        for _ in 0..FINALIZE_POLL_ATTEMPTS {
+            let (status, body) =
+                self.request_with_retry("GET", &format!("{upload_id}/status"), None)?;
+            if status >= 400 {
+                return Err(RequestError::Transient(error_message(&body)));
+            }
+            let state = body
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            match state.as_str() {
+                "FINALIZED" => return Ok(body.get("receipt").cloned().unwrap_or(body)),
+                state @ ("INVALID" | "UNDERFUNDED" | "APPROVAL_FAILED" | "REVOKED") => {
+                    return Err(RequestError::Fatal(format!(
+                        "Load rejected the upload while finalizing: {state}"
+                    )));
+                }
+                _ => std::thread::sleep(self.poll_interval),
+            }
+        }
+        Err(RequestError::Transient(
+            "Timed out waiting for Load to finalize the upload; retry to resume.".to_string(),
+        ))
+    }
+}
+
+fn error_message(body: &Value) -> String {
+    body.get("error")
+        .and_then(Value::as_str)
+        .unwrap_or("Load chunked upload request failed")
+        .to_string()
+}
+
+/// Turbo reports received chunks as `[[offset, size], ...]` in no particular order.
+fn contiguous_chunk_prefix(info: &Value) -> u64 {
+    let mut chunks = info
+        .get("chunks")
+        .and_then(Value::as_array)
+        .map(|chunks| {
+            chunks
+                .iter()
+                .filter_map(|chunk| {
+                    let pair = chunk.as_array()?;
+                    Some((pair.first()?.as_u64()?, pair.get(1)?.as_u64()?))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    chunks.sort_unstable();
+    let mut prefix = 0;
+    for (offset, size) in chunks {
+        if offset > prefix {
+            break;
+        }
+        prefix = prefix.max(offset + size);
+    }
+    prefix
+}
+
+/// Act on the caller's answer to a progress report: a pause keeps the journal so the next
+/// attempt resumes, a cancel drops the staged item.
+pub(crate) fn apply_upload_control(
+    control: UploadControl,
+    journal: &UploadJournal,
+    session: &UploadSession,
+) -> Result<(), String> {
+    match control {
+        UploadControl::Continue => Ok(()),
+        UploadControl::Pause => Err(format!(
+            "Upload paused at {}/{} bytes",
+            session.confirmed_bytes, session.item_len
+        )),
+        UploadControl::Cancel => {
+            journal.discard(&session.content_id);
+            Err("Upload cancelled".to_string())
+        }
+    }
+}
+
+/// Upload a journaled DataItem in chunks, resuming after the last acknowledged byte. The
+/// journal entry is updated after every chunk and removed once the server has the item.
+pub(crate) fn upload_staged_dataitem(
+    endpoint: &ChunkedUploadEndpoint,
+    journal: &UploadJournal,
+    session: &mut UploadSession,
+    progress: &dyn Fn(UploadProgress) -> UploadControl,
+) -> LoadStorageResult<UploadResult> {
+    let total = session.item_len;
+    let report = |phase, sent_bytes| {
+        progress(UploadProgress {
+            phase,
+            sent_bytes,
+            total_bytes: total,
+        })
+    };
+
+    let acknowledged = match session.upload_id.as_deref() {
+        Some(upload_id) => endpoint.acknowledged_prefix(upload_id)?,
+        None => None,
+    };
+    let (upload_id, mut offset) = match (session.upload_id.clone(), acknowledged) {
+        (Some(upload_id), Some(acknowledged)) => {
+            log::info!(
+                "[LoadStorage] resuming upload: contentId={} uploadId={} at {}/{} bytes",
+                session.content_id,
+                upload_id,
+                acknowledged,
+                total
+            );
+            (upload_id, acknowledged.min(total))
+        }
+        _ => {
+            let (upload_id, chunk_size) = endpoint.create()?;
+            session.upload_id = Some(upload_id.clone());
+            session.chunk_size = chunk_size;
+            (upload_id, 0)
+        }
+    };
+    session.confirmed_bytes = offset;
+    journal.save(session)?;
+    apply_upload_control(report(UploadPhase::Uploading, offset), journal, session)?;
+
+    let item_path = journal.item_path(&session.content_id);
+    let mut item = fs::File::open(&item_path).map_err(|e| {
+        format!(
+            "Failed opening staged upload ({}): {e}",
+            item_path.display()
+        )
+    })?;
+    let chunk_size = if session.chunk_size > 0 {
+        session.chunk_size
+    } else {
+        DEFAULT_UPLOAD_CHUNK_BYTES
+    };
+    let mut buf = vec![0u8; chunk_size.min(total) as usize];
+    while offset < total {
+        let len = chunk_size.min(total - offset) as usize;
+        item.seek(SeekFrom::Start(offset))
+            .and_then(|_| item.read_exact(&mut buf[..len]))
+            .map_err(|e| {
+                format!(
+                    "Failed reading staged upload ({}): {e}",
+                    item_path.display()
+                )
+            })?;
+        endpoint
+            .post_chunk(&upload_id, offset, &buf[..len])
+            .map_err(|err| err.context(format!("Upload paused at {offset}/{total} bytes")))?;
+        offset += len as u64;
+        session.confirmed_bytes = offset;
+        journal.save(session)?;
+        apply_upload_control(report(UploadPhase::Uploading, offset), journal, session)?;
+    }
+
+    // Every byte is on the server; finalizing is no longer interruptible.
+    report(UploadPhase::Finalizing, total);
+    let receipt = match endpoint.finalize(&upload_id) {
+        Ok(receipt) => receipt,
+        Err(err @ RequestError::Fatal(_)) => {
+            journal.discard(&session.content_id);
+            return Err(err.into_error());
+        }
+        Err(err) => return Err(err.into_error()),
+    };
+    journal.discard(&session.content_id);
+
+    let id =
+        extract_upload_id(&receipt).ok_or("Upload finalized but no dataitem id was returned")?;
+    let gateway_base = extract_gateway_base(&receipt).unwrap_or_else(load_gateway_url);
+    Ok(UploadResult {
+        id: id.clone(),
+        gateway_url: format!("{}/resolve/{}", gateway_base.trim_end_matches('/'), id),
+        winc: receipt
+            .get("winc")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_stub::{HttpStub, StubResponse};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const MOCK_CHUNK_SIZE: u64 = 1000;
+
+    #[derive(Default)]
+    struct MockTurbo {
+        uploads: HashMap<String, Vec<(u64, Vec<u8>)>>,
+        next_id: u32,
+        chunk_offsets: Vec<u64>,
+        /// Chunk posts at or past this offset answer 503 while `failures_left > 0`.
+        fail_from: u64,
+        failures_left: u32,
+        finalized: Option<Vec<u8>>,
+    }
+
+    impl MockTurbo {
+        fn handle(&mut self, method: &str, path: &str, body: Vec<u8>) -> (u16, String) {
+            let parts = path
+                .trim_start_matches("/v1/chunks/ethereum/")
+                .split('/')
+                .collect::<Vec<_>>();
+            match (method, parts.as_slice()) {
+                ("GET", ["-1", "-1"]) => {
+                    self.next_id += 1;
+                    let id = format!("upload-{}", self.next_id);
+                    self.uploads.insert(id.clone(), Vec::new());
+                    (
+                        200,
+                        json!({"id": id, "min": 1, "max": MOCK_CHUNK_SIZE, "chunkSize": MOCK_CHUNK_SIZE})
+                            .to_string(),
+                    )
+                }
+                ("GET", [id, "-1"]) => match self.uploads.get(*id) {
+                    Some(chunks) => {
+                        let chunks = chunks
+                            .iter()
+                            .map(|(offset, bytes)| json!([offset, bytes.len()]))
+                            .collect::<Vec<_>>();
+                        (200, json!({ "id": id, "chunks": chunks }).to_string())
+                    }
+                    None => (404, json!({"error": "upload not found"}).to_string()),
+                },
+                ("GET", [_, "status"]) => (
+                    200,
+                    json!({"status": "FINALIZED", "receipt": {"id": "mock-item-id", "winc": "42"}})
+                        .to_string(),
+                ),
+                ("POST", [id, "finalize"]) => {
+                    let mut chunks = self.uploads.remove(*id).unwrap_or_default();
+                    chunks.sort_by_key(|(offset, _)| *offset);
+                    self.finalized =
+                        Some(chunks.into_iter().flat_map(|(_, bytes)| bytes).collect());
+                    (202, String::new())
+                }
+                ("POST", [id, offset]) => {
+                    let offset = offset.parse::<u64>().expect("chunk offset");
+                    if offset >= self.fail_from && self.failures_left > 0 {
+                        self.failures_left -= 1;
+                        return (503, json!({"error": "flaky"}).to_string());
+                    }
+                    self.chunk_offsets.push(offset);
+                    let chunks = self.uploads.get_mut(*id).expect("known upload");
+                    chunks.retain(|(existing, _)| *existing != offset);
+                    chunks.push((offset, body));
+                    (200, "{}".to_string())
+                }
+                _ => (400, json!({"error": "unexpected request"}).to_string()),
+            }
+        }
+    }
+
+    fn spawn_mock_turbo(state: Arc<Mutex<MockTurbo>>) -> String {
+        HttpStub::spawn(move |request| {
+            let (status, body) =
+                state
+                    .lock()
+                    .unwrap()
+                    .handle(&request.method, &request.path, request.body.clone());
+            StubResponse::new(status, body)
+        })
+        .url
+    }
+
+    fn fixture(
+        name: &str,
+    ) -> (
+        ChunkedUploadEndpoint,
+        UploadJournal,
+        UploadSession,
+        Vec<u8>,
+        Arc<Mutex<MockTurbo>>,
+    ) {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-chunked-upload-{name}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("track.mp3");
+        fs::write(&source, b"source audio").unwrap();
+        let item = (0..10_250u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let journal = UploadJournal::new(dir.join("sessions"));
+        fs::write(journal.staging_path("0x01").unwrap(), &item).unwrap();
+        let session = journal
+            .create("0x01", source.to_str().unwrap(), 10_000, None)
+            .unwrap();
+
+        let state = Arc::new(Mutex::new(MockTurbo::default()));
+        let endpoint = ChunkedUploadEndpoint {
+            base_url: spawn_mock_turbo(state.clone()),
+            token: "ethereum".to_string(),
+            retry_delay: Duration::from_millis(1),
+            poll_interval: Duration::from_millis(1),
+        };
+        (endpoint, journal, session, item, state)
+    }
+
+    #[test]
+    fn resumes_after_an_interrupted_upload_without_resending_chunks() {
+        let (endpoint, journal, mut session, item, state) = fixture("resume");
+        {
+            let mut mock = state.lock().unwrap();
+            mock.fail_from = 4 * MOCK_CHUNK_SIZE;
+            mock.failures_left = CHUNK_RETRY_ATTEMPTS;
+        }
+        let reported = Mutex::new(Vec::new());
+        let progress = |update: UploadProgress| {
+            reported.lock().unwrap().push(update);
+            UploadControl::Continue
+        };
+
+        let err = upload_staged_dataitem(&endpoint, &journal, &mut session, &progress).unwrap_err();
+        assert!(err.is_transient(), "{err}");
+        assert!(
+            err.message().starts_with("Upload paused at 4000/10250"),
+            "{err}"
+        );
+        let source_path = session.source_path.clone();
+        let mut resumed = journal
+            .resumable("0x01", &source_path)
+            .expect("journaled session");
+        assert_eq!(resumed.confirmed_bytes, 4 * MOCK_CHUNK_SIZE);
+        assert_eq!(
+            reported
+                .lock()
+                .unwrap()
+                .last()
+                .map(|update| update.sent_bytes),
+            Some(4 * MOCK_CHUNK_SIZE)
+        );
+
+        state.lock().unwrap().chunk_offsets.clear();
+        reported.lock().unwrap().clear();
+        let result = upload_staged_dataitem(&endpoint, &journal, &mut resumed, &progress).unwrap();
+        assert_eq!(result.id, "mock-item-id");
+
+        let mock = state.lock().unwrap();
+        assert_eq!(mock.chunk_offsets.first(), Some(&(4 * MOCK_CHUNK_SIZE)));
+        assert_eq!(mock.finalized.as_deref(), Some(item.as_slice()));
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.first().map(|update| update.sent_bytes), Some(4000));
+        assert_eq!(
+            reported.last().copied(),
+            Some(UploadProgress {
+                phase: UploadPhase::Finalizing,
+                sent_bytes: 10_250,
+                total_bytes: 10_250,
+            })
+        );
+        assert!(journal.resumable("0x01", &source_path).is_none());
+    }
+
+    #[test]
+    fn expired_server_upload_starts_over_from_the_staged_item() {
+        let (endpoint, journal, mut session, item, state) = fixture("expired");
+        session.upload_id = Some("upload-gone".to_string());
+        session.chunk_size = MOCK_CHUNK_SIZE;
+        session.confirmed_bytes = 6 * MOCK_CHUNK_SIZE;
+        journal.save(&session).unwrap();
+
+        let result = upload_staged_dataitem(&endpoint, &journal, &mut session, &|_| {
+            UploadControl::Continue
+        })
+        .unwrap();
+        assert_eq!(result.id, "mock-item-id");
+        let mock = state.lock().unwrap();
+        assert_eq!(mock.chunk_offsets.first(), Some(&0));
+        assert_eq!(mock.finalized.as_deref(), Some(item.as_slice()));
+    }
+
+    #[test]
+    fn pause_keeps_the_session_and_cancel_drops_it() {
+        let (endpoint, journal, mut session, _item, _state) = fixture("control");
+        let source_path = session.source_path.clone();
+        let pause_after = |update: UploadProgress| {
+            if update.sent_bytes >= 2 * MOCK_CHUNK_SIZE {
+                UploadControl::Pause
+            } else {
+                UploadControl::Continue
+            }
+        };
+
+        let err =
+            upload_staged_dataitem(&endpoint, &journal, &mut session, &pause_after).unwrap_err();
+        assert!(err.message().contains("paused at 2000/10250"), "{err}");
+        let mut resumed = journal
+            .resumable("0x01", &source_path)
+            .expect("paused session stays journaled");
+        assert_eq!(resumed.confirmed_bytes, 2 * MOCK_CHUNK_SIZE);
+
+        let err = upload_staged_dataitem(&endpoint, &journal, &mut resumed, &|_| {
+            UploadControl::Cancel
+        })
+        .unwrap_err();
+        assert_eq!(err, LoadStorageError::Other("Upload cancelled".to_string()));
+        assert!(journal.resumable("0x01", &source_path).is_none());
+    }
+}
@@ -1,20 +1,52 @@
 use super::*;
+use crate::auth::accounts::AccountRegistry;
+use crate::secret_store::SecretStore;
 use p256::ecdh::diffie_hellman;
 use p256::elliptic_curve::rand_core::OsRng;
 use p256::elliptic_curve::sec1::ToEncodedPoint;
 use p256::{PublicKey, SecretKey};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
 
+#[path = "content_crypto/backup.rs"]
+mod backup;
+#[path = "content_crypto/devices.rs"]
+mod devices;
 #[path = "content_crypto/envelope_lookup.rs"]
 mod envelope_lookup;
-use envelope_lookup::{fetch_resolve_payload, parse_envelope_payload, query_envelope_ids};
+#[path = "content_crypto/stream.rs"]
+mod stream;
+pub(crate) use backup::{
+    content_key_recovery_phrase, content_keypair_from_recovery_phrase, open_content_key_backup,
+    seal_content_key_backup,
+};
+pub(crate) use devices::{
+    cached_wrapped_key_digests, content_key_fingerprint, parse_content_public_key,
+    read_device_registry, write_device_registry, LinkedDevice,
+};
+pub(crate) use envelope_lookup::supersede_signing_message;
+use envelope_lookup::{
+    fetch_resolve_payload, parse_envelope_payload, parse_envelope_piece_cid,
+    query_content_envelopes, query_device_envelope_ids, query_envelope_ids,
+    query_superseded_envelope_ids,
+};
+pub(crate) use stream::{
+    decrypt_stream_payload, is_stream_payload, stream_payload_len, StreamDecryptor,
+    StreamEncryptor, StreamHeader, DEFAULT_STREAM_CHUNK_SIZE, STREAM_HEADER_LEN,
+};
 
 const CONTENT_KEYPAIR_FILE: &str = "content_keypair_v1.json";
 const WRAPPED_KEYS_FILE: &str = "content_wrapped_keys_v1.json";
 const ENVELOPE_TAG_TYPE: &str = "content-key-envelope";
+pub(crate) const SUPERSEDE_TAG_TYPE: &str = "content-key-supersede";
+
+/// Serializes read-modify-write of the wrapped key store across upload workers.
+static WRAPPED_KEYS_LOCK: Mutex<()> = Mutex::new(());
+pub(crate) const DEVICE_ENVELOPE_TAG_TYPE: &str = "content-key-device-envelope";
 const CONTENT_KEYPAIR_ENC_PREFIX: &str = "enc:v1";
 const CONTENT_KEYPAIR_ENC_SALT: &[u8] = b"heaven-content-keypair-v1";
+const CONTENT_PRIVATE_KEY_SECRET: &str = "content.keypair.private_key";
 
 #[derive(Debug, Clone)]
 pub(crate) struct ContentKeyPair {
@@ -29,41 +61,84 @@ pub(crate) struct EciesEnvelope {
     pub(crate) ciphertext: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct EncryptedAudioBlob {
-    pub(crate) iv: [u8; 12],
-    pub(crate) ciphertext: Vec<u8>,
-    pub(crate) raw_key: [u8; 32],
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct StoredContentKeyPair {
+    /// Only set in files written before the private key moved to the secret store.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     private_key: String,
     public_key: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct StoredEnvelope {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StoredEnvelope {
     ephemeral_pub: String,
     iv: String,
     ciphertext: String,
 }
 
-fn load_storage_data_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("heaven-gpui")
+impl From<&EciesEnvelope> for StoredEnvelope {
+    fn from(envelope: &EciesEnvelope) -> Self {
+        Self {
+            ephemeral_pub: hex::encode(&envelope.ephemeral_pub),
+            iv: hex::encode(&envelope.iv),
+            ciphertext: hex::encode(&envelope.ciphertext),
+        }
+    }
+}
+
+impl StoredEnvelope {
+    pub(crate) fn to_envelope(&self) -> Option<EciesEnvelope> {
+        let ephemeral_pub =
+            decode_hex_bytes(&self.ephemeral_pub, "wrapped key ephemeral pub").ok()?;
+        let iv = decode_hex_bytes(&self.iv, "wrapped key iv").ok()?;
+        let ciphertext = decode_hex_bytes(&self.ciphertext, "wrapped key ciphertext").ok()?;
+        if ephemeral_pub.len() != 65 || iv.len() != 12 || ciphertext.is_empty() {
+            return None;
+        }
+        Some(EciesEnvelope {
+            ephemeral_pub,
+            iv,
+            ciphertext,
+        })
+    }
 }
 
 fn content_keypair_path() -> PathBuf {
-    load_storage_data_dir().join(CONTENT_KEYPAIR_FILE)
+    AccountRegistry::shared().scoped_path(CONTENT_KEYPAIR_FILE)
 }
 
 fn wrapped_keys_path() -> PathBuf {
-    load_storage_data_dir().join(WRAPPED_KEYS_FILE)
+    AccountRegistry::shared().scoped_path(WRAPPED_KEYS_FILE)
+}
+
+/// Secret-store key for the active account's content private key.
+fn content_private_key_secret() -> String {
+    AccountRegistry::shared().scoped_secret(CONTENT_PRIVATE_KEY_SECRET)
+}
+
+/// Read the active account's content private key, adopting the pre-accounts entry if
+/// this account inherited the single-account files.
+fn read_content_private_key_secret(store: &SecretStore) -> Result<Option<String>, String> {
+    let scoped = content_private_key_secret();
+    if let Some(value) = store.get(&scoped)? {
+        return Ok(Some(value));
+    }
+    let registry = AccountRegistry::shared();
+    let is_legacy_owner = registry
+        .active()
+        .is_some_and(|active| registry.is_legacy_owner(&active));
+    if !is_legacy_owner {
+        return Ok(None);
+    }
+    let Some(value) = store.get(CONTENT_PRIVATE_KEY_SECRET)? else {
+        return Ok(None);
+    };
+    store.set(&scoped, &value)?;
+    store.delete(CONTENT_PRIVATE_KEY_SECRET)?;
+    Ok(Some(value))
 }
 
-fn normalize_content_key(content_id_hex: &str) -> String {
+pub(crate) fn normalize_content_key(content_id_hex: &str) -> String {
     normalize_content_id_hex(content_id_hex)
         .unwrap_or_else(|_| content_id_hex.trim().to_lowercase())
         .trim()
@@ -78,7 +153,7 @@ pub(crate) fn normalize_address(address: &str) -> Result<String, String> {
     Ok(format!("{parsed:#x}").to_lowercase())
 }
 
-fn ensure_parent_dir(path: &Path) -> Result<(), String> {
+pub(crate) fn ensure_parent_dir(path: &Path) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| format!("Failed creating data dir ({}): {e}", parent.display()))?;
@@ -103,54 +178,17 @@ fn write_content_keypair_file(path: &Path, stored: &StoredContentKeyPair) -> Res
         .map_err(|e| format!("Failed writing content keypair ({}): {e}", path.display()))
 }
 
-fn machine_secret_material() -> String {
-    if let Ok(raw) = fs::read_to_string("/etc/machine-id") {
-        let trimmed = raw.trim();
-        if !trimmed.is_empty() {
-            return trimmed.to_string();
-        }
-    }
-    let host = std::env::var("HOSTNAME")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .unwrap_or_else(|| "unknown-host".to_string());
-    let user = std::env::var("USER")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .unwrap_or_else(|| "unknown-user".to_string());
-    let home = dirs::home_dir()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| ".".to_string());
-    format!("{host}:{user}:{home}")
-}
-
+/// Opens `enc:v1` entries in keypair files written before the secret store existed.
 fn derive_content_keypair_wrap_key() -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(CONTENT_KEYPAIR_ENC_SALT);
-    hasher.update(machine_secret_material().as_bytes());
+    hasher.update(crate::secret_store::machine_secret_material().as_bytes());
     let digest = hasher.finalize();
     let mut key = [0u8; 32];
     key.copy_from_slice(&digest);
     key
 }
 
-fn encrypt_private_key_hex(private_key_hex: &str) -> Result<String, String> {
-    let mut iv = [0u8; 12];
-    rand::thread_rng().fill_bytes(&mut iv);
-    let key = derive_content_keypair_wrap_key();
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed creating content-key encryption cipher: {e}"))?;
-    let ciphertext = cipher
-        .encrypt(Nonce::from_slice(&iv), private_key_hex.as_bytes())
-        .map_err(|e| format!("Failed encrypting content private key: {e}"))?;
-    Ok(format!(
-        "{}:{}:{}",
-        CONTENT_KEYPAIR_ENC_PREFIX,
-        hex::encode(iv),
-        hex::encode(ciphertext)
-    ))
-}
-
 fn decrypt_private_key_hex(encoded: &str) -> Result<String, String> {
     let trimmed = encoded.trim();
     let mut parts = trimmed.split(':');
@@ -178,12 +216,22 @@ fn decrypt_private_key_hex(encoded: &str) -> Result<String, String> {
     String::from_utf8(plaintext).map_err(|e| format!("Invalid UTF-8 in decrypted content key: {e}"))
 }
 
-fn read_wrapped_keys() -> HashMap<String, StoredEnvelope> {
+/// A store that exists but doesn't parse is an error: treating it as empty would let the
+/// next write drop every key in it.
+fn read_wrapped_keys() -> Result<HashMap<String, StoredEnvelope>, String> {
     let path = wrapped_keys_path();
-    let Ok(text) = fs::read_to_string(path) else {
-        return HashMap::new();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => {
+            return Err(format!(
+                "Failed reading wrapped key store ({}): {err}",
+                path.display()
+            ))
+        }
     };
-    serde_json::from_str::<HashMap<String, StoredEnvelope>>(&text).unwrap_or_default()
+    serde_json::from_str::<HashMap<String, StoredEnvelope>>(&text)
+        .map_err(|e| format!("Failed parsing wrapped key store ({}): {e}", path.display()))
 }
 
 fn write_wrapped_keys(entries: &HashMap<String, StoredEnvelope>) -> Result<(), String> {
@@ -191,19 +239,43 @@ fn write_wrapped_keys(entries: &HashMap<String, StoredEnvelope>) -> Result<(), S
     ensure_parent_dir(&path)?;
     let encoded = serde_json::to_string_pretty(entries)
         .map_err(|e| format!("Failed encoding wrapped key store: {e}"))?;
-    fs::write(&path, encoded)
-        .map_err(|e| format!("Failed writing wrapped key store ({}): {e}", path.display()))
+    write_atomic(&path, encoded.as_bytes())
 }
 
-pub(crate) fn load_or_create_content_keypair() -> Result<ContentKeyPair, String> {
+/// Apply `change` to the wrapped key store while holding the process-wide lock.
+fn update_wrapped_keys<T>(
+    change: impl FnOnce(&mut HashMap<String, StoredEnvelope>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = WRAPPED_KEYS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_wrapped_keys()?;
+    let out = change(&mut entries)?;
+    write_wrapped_keys(&entries)?;
+    Ok(out)
+}
+
+fn lookup_wrapped_keys() -> HashMap<String, StoredEnvelope> {
+    let _guard = WRAPPED_KEYS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_wrapped_keys().unwrap_or_else(|err| {
+        log::warn!("[LoadStorage] {err}");
+        HashMap::new()
+    })
+}
+
+/// The active account's content keypair, or `None` before one has been created.
+fn load_content_keypair() -> Result<Option<ContentKeyPair>, String> {
+    let store = SecretStore::shared();
     let path = content_keypair_path();
     if path.exists() {
         let text = fs::read_to_string(&path)
             .map_err(|e| format!("Failed reading content keypair ({}): {e}", path.display()))?;
         let mut stored = serde_json::from_str::<StoredContentKeyPair>(&text)
             .map_err(|e| format!("Failed parsing content keypair JSON: {e}"))?;
-        let mut migrated_to_encrypted = false;
-        let private_key = if stored
+        let legacy_entry = !stored.private_key.trim().is_empty();
+        let private_key = if !legacy_entry {
+            let private_key_hex = read_content_private_key_secret(store)?
+                .ok_or("Content private key is missing from the secret store.")?;
+            decode_hex_bytes(&private_key_hex, "content private key")?
+        } else if stored
             .private_key
             .trim()
             .starts_with(CONTENT_KEYPAIR_ENC_PREFIX)
@@ -211,22 +283,7 @@ pub(crate) fn load_or_create_content_keypair() -> Result<ContentKeyPair, String>
             let private_key_hex = decrypt_private_key_hex(&stored.private_key)?;
             decode_hex_bytes(&private_key_hex, "content private key (encrypted)")?
         } else {
-            let key = decode_hex_bytes(&stored.private_key, "content private key")?;
-            let key_hex = hex::encode(&key);
-            match encrypt_private_key_hex(&key_hex) {
-                Ok(encrypted_value) => {
-                    stored.private_key = encrypted_value;
-                    migrated_to_encrypted = true;
-                    key
-                }
-                Err(err) => {
-                    log::warn!(
-                        "[LoadStorage] content key migration to encrypted file format failed; continuing with plaintext legacy entry: {}",
-                        err
-                    );
-                    key
-                }
-            }
+            decode_hex_bytes(&stored.private_key, "content private key")?
         };
         let public_key = decode_hex_bytes(&stored.public_key, "content public key")?;
         if private_key.len() != 32 {
@@ -241,35 +298,127 @@ pub(crate) fn load_or_create_content_keypair() -> Result<ContentKeyPair, String>
                     .to_string(),
             );
         }
-        if migrated_to_encrypted {
-            if let Err(err) = write_content_keypair_file(&path, &stored) {
-                log::warn!(
-                    "[LoadStorage] failed rewriting encrypted content keypair file after migration: {}",
+        if legacy_entry {
+            match store.set(&content_private_key_secret(), &hex::encode(&private_key)) {
+                Ok(()) => {
+                    stored.private_key.clear();
+                    if let Err(err) = write_content_keypair_file(&path, &stored) {
+                        log::warn!(
+                            "[LoadStorage] content key moved to secret store but keypair file rewrite failed: {}",
+                            err
+                        );
+                    }
+                }
+                Err(err) => log::warn!(
+                    "[LoadStorage] content key migration to secret store failed; keeping legacy file entry: {}",
                     err
-                );
+                ),
             }
         }
-        return Ok(ContentKeyPair {
+        return Ok(Some(ContentKeyPair {
             private_key,
             public_key,
-        });
+        }));
     }
+    Ok(None)
+}
 
-    let secret = SecretKey::random(&mut OsRng);
-    let public = secret.public_key().to_encoded_point(false);
-    let private_key = secret.to_bytes().to_vec();
-    let public_key = public.as_bytes().to_vec();
+pub(crate) fn load_or_create_content_keypair() -> Result<ContentKeyPair, String> {
+    if let Some(keypair) = load_content_keypair()? {
+        return Ok(keypair);
+    }
+    let keypair = content_keypair_from_private_key(&SecretKey::random(&mut OsRng).to_bytes())?;
+    store_content_keypair(&keypair)?;
+    Ok(keypair)
+}
+
+fn content_keypair_from_private_key(private_key: &[u8]) -> Result<ContentKeyPair, String> {
+    let secret = SecretKey::from_slice(private_key)
+        .map_err(|e| format!("Invalid content private key bytes: {e}"))?;
+    Ok(ContentKeyPair {
+        private_key: secret.to_bytes().to_vec(),
+        public_key: secret
+            .public_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec(),
+    })
+}
 
+fn store_content_keypair(keypair: &ContentKeyPair) -> Result<(), String> {
+    SecretStore::shared().set(
+        &content_private_key_secret(),
+        &hex::encode(&keypair.private_key),
+    )?;
     let stored = StoredContentKeyPair {
-        private_key: encrypt_private_key_hex(&hex::encode(&private_key))?,
-        public_key: hex::encode(&public_key),
+        private_key: String::new(),
+        public_key: hex::encode(&keypair.public_key),
     };
-    write_content_keypair_file(&path, &stored)?;
+    write_content_keypair_file(&content_keypair_path(), &stored)
+}
 
-    Ok(ContentKeyPair {
-        private_key,
-        public_key,
-    })
+/// Make `keypair` the active account's content key. Wrapped keys cached for a different
+/// previous key are re-wrapped so nothing this device could open becomes unreadable.
+/// Returns whether a different key was replaced, and how many cached keys were re-wrapped.
+pub(crate) fn install_content_keypair(keypair: &ContentKeyPair) -> Result<(bool, usize), String> {
+    let previous =
+        load_content_keypair()?.filter(|previous| previous.public_key != keypair.public_key);
+    let Some(previous) = previous else {
+        store_content_keypair(keypair)?;
+        return Ok((false, 0));
+    };
+
+    let _guard = WRAPPED_KEYS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let rewrapped = install_rewrapped_keys(
+        read_wrapped_keys()?,
+        &previous,
+        keypair,
+        write_wrapped_keys,
+        store_content_keypair,
+    )?;
+    Ok((true, rewrapped))
+}
+
+/// Re-wrap `entries` from `previous` to `keypair`, persist them, then store `keypair`.
+/// The key only changes once the re-wrapped store is on disk; if storing the key fails,
+/// the original entries are written back so they keep opening with the key still active.
+fn install_rewrapped_keys(
+    entries: HashMap<String, StoredEnvelope>,
+    previous: &ContentKeyPair,
+    keypair: &ContentKeyPair,
+    mut write_entries: impl FnMut(&HashMap<String, StoredEnvelope>) -> Result<(), String>,
+    store_keypair: impl FnOnce(&ContentKeyPair) -> Result<(), String>,
+) -> Result<usize, String> {
+    let mut rewrapped_entries = entries.clone();
+    let mut rewrapped = 0;
+    for (content_id, entry) in rewrapped_entries.iter_mut() {
+        let Some(envelope) = entry.to_envelope() else {
+            continue;
+        };
+        let Ok(mut raw_key) = ecies_decrypt(&previous.private_key, &envelope) else {
+            log::warn!(
+                "[LoadStorage] cached wrapped key for contentId={} does not open with the previous content key; leaving it",
+                content_id
+            );
+            continue;
+        };
+        let envelope = ecies_encrypt(&keypair.public_key, &raw_key);
+        raw_key.fill(0);
+        *entry = StoredEnvelope::from(&envelope?);
+        rewrapped += 1;
+    }
+
+    write_entries(&rewrapped_entries)?;
+    if let Err(err) = store_keypair(keypair) {
+        if let Err(restore_err) = write_entries(&entries) {
+            log::error!(
+                "[LoadStorage] restoring wrapped keys after a failed key install failed: {}",
+                restore_err
+            );
+        }
+        return Err(err);
+    }
+    Ok(rewrapped)
 }
 
 fn derive_ecies_key(private_key: &SecretKey, public_key: &PublicKey) -> [u8; 32] {
@@ -280,66 +429,50 @@ fn derive_ecies_key(private_key: &SecretKey, public_key: &PublicKey) -> [u8; 32]
     out
 }
 
-pub(crate) fn encrypt_audio_blob(source_bytes: &[u8]) -> Result<EncryptedAudioBlob, String> {
-    let mut raw_key = [0u8; 32];
-    rand::thread_rng().fill_bytes(&mut raw_key);
-    let mut iv = [0u8; 12];
-    rand::thread_rng().fill_bytes(&mut iv);
-
-    let cipher = Aes256Gcm::new_from_slice(&raw_key)
-        .map_err(|e| format!("Failed to initialize AES-256-GCM key: {e}"))?;
-    let ciphertext = cipher
-        .encrypt(Nonce::from_slice(&iv), source_bytes)
-        .map_err(|e| format!("Failed encrypting audio payload: {e}"))?;
-
-    Ok(EncryptedAudioBlob {
-        iv,
-        ciphertext,
-        raw_key,
-    })
-}
-
 pub(crate) fn decrypt_audio_blob(
     raw_key: &[u8],
     iv: &[u8],
     ciphertext: &[u8],
-) -> Result<Vec<u8>, String> {
+) -> LoadStorageResult<Vec<u8>> {
     if raw_key.len() != 32 {
-        return Err(format!(
+        return Err(LoadStorageError::Crypto(format!(
             "Invalid AES key length for decrypt: expected 32, got {}",
             raw_key.len()
-        ));
+        )));
     }
     if iv.len() != 12 {
-        return Err(format!(
+        return Err(LoadStorageError::Crypto(format!(
             "Invalid IV length for decrypt: expected 12, got {}",
             iv.len()
-        ));
+        )));
     }
-    let cipher = Aes256Gcm::new_from_slice(raw_key)
-        .map_err(|e| format!("Failed to initialize AES key for decrypt: {e}"))?;
+    let cipher = Aes256Gcm::new_from_slice(raw_key).map_err(|e| {
+        LoadStorageError::Crypto(format!("Failed to initialize AES key for decrypt: {e}"))
+    })?;
     cipher
         .decrypt(Nonce::from_slice(iv), ciphertext)
-        .map_err(|e| format!("Failed decrypting audio payload: {e}"))
+        .map_err(|e| LoadStorageError::Crypto(format!("Failed decrypting audio payload: {e}")))
 }
 
 pub(crate) fn ecies_encrypt(
     recipient_public_key: &[u8],
     plaintext: &[u8],
-) -> Result<EciesEnvelope, String> {
-    let recipient = PublicKey::from_sec1_bytes(recipient_public_key)
-        .map_err(|e| format!("Invalid recipient content public key: {e}"))?;
+) -> LoadStorageResult<EciesEnvelope> {
+    let recipient = PublicKey::from_sec1_bytes(recipient_public_key).map_err(|e| {
+        LoadStorageError::Crypto(format!("Invalid recipient content public key: {e}"))
+    })?;
     let ephemeral_secret = SecretKey::random(&mut OsRng);
     let ephemeral_pub = ephemeral_secret.public_key().to_encoded_point(false);
     let key = derive_ecies_key(&ephemeral_secret, &recipient);
 
     let mut iv = [0u8; 12];
     rand::thread_rng().fill_bytes(&mut iv);
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to initialize ECIES AES key: {e}"))?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| {
+        LoadStorageError::Crypto(format!("Failed to initialize ECIES AES key: {e}"))
+    })?;
     let ciphertext = cipher
         .encrypt(Nonce::from_slice(&iv), plaintext)
-        .map_err(|e| format!("Failed ECIES encrypt: {e}"))?;
+        .map_err(|e| LoadStorageError::Crypto(format!("Failed ECIES encrypt: {e}")))?;
 
     Ok(EciesEnvelope {
         ephemeral_pub: ephemeral_pub.as_bytes().to_vec(),
@@ -351,20 +484,21 @@ pub(crate) fn ecies_encrypt(
 pub(crate) fn ecies_decrypt(
     recipient_private_key: &[u8],
     envelope: &EciesEnvelope,
-) -> Result<Vec<u8>, String> {
+) -> LoadStorageResult<Vec<u8>> {
     let secret = SecretKey::from_slice(recipient_private_key)
-        .map_err(|e| format!("Invalid content private key bytes: {e}"))?;
+        .map_err(|e| LoadStorageError::Crypto(format!("Invalid content private key bytes: {e}")))?;
     let ephemeral = PublicKey::from_sec1_bytes(&envelope.ephemeral_pub)
-        .map_err(|e| format!("Invalid ECIES ephemeral key bytes: {e}"))?;
+        .map_err(|e| LoadStorageError::Crypto(format!("Invalid ECIES ephemeral key bytes: {e}")))?;
     let key = derive_ecies_key(&secret, &ephemeral);
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to initialize ECIES decrypt AES key: {e}"))?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| {
+        LoadStorageError::Crypto(format!("Failed to initialize ECIES decrypt AES key: {e}"))
+    })?;
     cipher
         .decrypt(
             Nonce::from_slice(&envelope.iv),
             envelope.ciphertext.as_slice(),
         )
-        .map_err(|e| format!("Failed ECIES decrypt: {e}"))
+        .map_err(|e| LoadStorageError::Crypto(format!("Failed ECIES decrypt: {e}")))
 }
 
 pub(crate) fn save_wrapped_key_for_content(
@@ -372,61 +506,216 @@ pub(crate) fn save_wrapped_key_for_content(
     envelope: &EciesEnvelope,
 ) -> Result<(), String> {
     let key = normalize_content_key(content_id_hex);
-    let mut store = read_wrapped_keys();
-    store.insert(
-        key,
-        StoredEnvelope {
-            ephemeral_pub: hex::encode(&envelope.ephemeral_pub),
-            iv: hex::encode(&envelope.iv),
-            ciphertext: hex::encode(&envelope.ciphertext),
-        },
-    );
-    write_wrapped_keys(&store)
+    update_wrapped_keys(|entries| {
+        entries.insert(key, StoredEnvelope::from(envelope));
+        Ok(())
+    })
 }
 
 pub(crate) fn load_wrapped_key_for_content(content_id_hex: &str) -> Option<EciesEnvelope> {
     let key = normalize_content_key(content_id_hex);
-    let store = read_wrapped_keys();
-    let entry = store.get(&key)?;
-    let ephemeral_pub = decode_hex_bytes(&entry.ephemeral_pub, "wrapped key ephemeral pub").ok()?;
-    let iv = decode_hex_bytes(&entry.iv, "wrapped key iv").ok()?;
-    let ciphertext = decode_hex_bytes(&entry.ciphertext, "wrapped key ciphertext").ok()?;
-    if ephemeral_pub.len() != 65 || iv.len() != 12 || ciphertext.is_empty() {
-        return None;
-    }
-    Some(EciesEnvelope {
-        ephemeral_pub,
-        iv,
-        ciphertext,
-    })
+    lookup_wrapped_keys().get(&key)?.to_envelope()
 }
 
 pub(crate) fn ensure_wrapped_key_from_ls3(
     content_id_hex: &str,
     owner_address: &str,
     grantee_address: &str,
-) -> Result<Option<EciesEnvelope>, String> {
+) -> LoadStorageResult<Option<EciesEnvelope>> {
     if let Some(existing) = load_wrapped_key_for_content(content_id_hex) {
         return Ok(Some(existing));
     }
+    Ok(
+        refresh_wrapped_key_from_ls3(content_id_hex, owner_address, grantee_address)?
+            .map(|(envelope, _)| envelope),
+    )
+}
 
+/// Replace the cached wrapped key with the newest live envelope on LS3, along with the
+/// blob it was issued for. Used when the owner may have rotated the key. A linked device
+/// prefers the envelopes its primary device re-wrapped for it.
+pub(crate) fn refresh_wrapped_key_from_ls3(
+    content_id_hex: &str,
+    owner_address: &str,
+    grantee_address: &str,
+) -> LoadStorageResult<Option<(EciesEnvelope, Option<String>)>> {
     let normalized_content_id = normalize_content_id_hex(content_id_hex)?;
     let owner = normalize_address(owner_address)?;
     let grantee = normalize_address(grantee_address)?;
-    let envelope_ids = query_envelope_ids(&normalized_content_id, &owner, &grantee)?;
-    if envelope_ids.is_empty() {
-        return Ok(None);
+    for envelope_id in device_envelope_ids(&normalized_content_id, &grantee)? {
+        if let Some(found) =
+            adopt_envelope(&envelope_id, &normalized_content_id, &grantee, &grantee)?
+        {
+            return Ok(Some(found));
+        }
     }
-
-    for envelope_id in envelope_ids {
-        let payload = fetch_resolve_payload(&envelope_id)?;
-        if let Some(envelope) =
-            parse_envelope_payload(&payload, &normalized_content_id, &owner, &grantee)
+    for envelope_id in live_envelope_ids(&normalized_content_id, &owner, &grantee)? {
+        if let Some(found) = adopt_envelope(&envelope_id, &normalized_content_id, &owner, &grantee)?
         {
-            save_wrapped_key_for_content(&normalized_content_id, &envelope)?;
-            return Ok(Some(envelope));
+            return Ok(Some(found));
         }
     }
 
     Ok(None)
 }
+
+fn adopt_envelope(
+    envelope_id: &str,
+    content_id_hex: &str,
+    owner: &str,
+    grantee: &str,
+) -> LoadStorageResult<Option<(EciesEnvelope, Option<String>)>> {
+    let payload = fetch_resolve_payload(envelope_id)?;
+    let Some(envelope) = parse_envelope_payload(&payload, content_id_hex, owner, grantee) else {
+        return Ok(None);
+    };
+    save_wrapped_key_for_content(content_id_hex, &envelope)?;
+    Ok(Some((envelope, parse_envelope_piece_cid(&payload))))
+}
+
+/// Device envelopes addressed to this device's key. Only a device linked as secondary
+/// looks for them.
+fn device_envelope_ids(
+    content_id_hex: &str,
+    wallet_address: &str,
+) -> LoadStorageResult<Vec<String>> {
+    if !read_device_registry().secondary {
+        return Ok(Vec::new());
+    }
+    let Some(keypair) = load_content_keypair()? else {
+        return Ok(Vec::new());
+    };
+    query_device_envelope_ids(
+        content_id_hex,
+        wallet_address,
+        &content_key_fingerprint(&keypair.public_key),
+    )
+}
+
+/// Envelope ids for one grantee that no key rotation has superseded yet.
+pub(crate) fn live_envelope_ids(
+    content_id_hex: &str,
+    owner_address: &str,
+    grantee_address: &str,
+) -> LoadStorageResult<Vec<String>> {
+    let mut envelope_ids = query_envelope_ids(content_id_hex, owner_address, grantee_address)?;
+    if envelope_ids.is_empty() {
+        return Ok(envelope_ids);
+    }
+    let superseded = query_superseded_envelope_ids(content_id_hex, owner_address)?;
+    envelope_ids.retain(|id| !superseded.contains(id));
+    Ok(envelope_ids)
+}
+
+/// Grantees holding an envelope for `content_id_hex` that no key rotation has superseded yet.
+pub(crate) fn live_envelope_grantees(
+    content_id_hex: &str,
+    owner_address: &str,
+) -> LoadStorageResult<Vec<String>> {
+    let envelopes = query_content_envelopes(content_id_hex, owner_address)?;
+    if envelopes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let superseded = query_superseded_envelope_ids(content_id_hex, owner_address)?;
+    let mut grantees = Vec::<String>::new();
+    for (envelope_id, grantee) in envelopes {
+        if !superseded.contains(&envelope_id) && !grantees.contains(&grantee) {
+            grantees.push(grantee);
+        }
+    }
+    Ok(grantees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn wrapped_for(keypair: &ContentKeyPair, raw_key: &[u8]) -> StoredEnvelope {
+        StoredEnvelope::from(&ecies_encrypt(&keypair.public_key, raw_key).unwrap())
+    }
+
+    fn opens_with(keypair: &ContentKeyPair, entry: &StoredEnvelope) -> Option<Vec<u8>> {
+        ecies_decrypt(&keypair.private_key, &entry.to_envelope()?).ok()
+    }
+
+    fn fixture() -> (
+        ContentKeyPair,
+        ContentKeyPair,
+        HashMap<String, StoredEnvelope>,
+    ) {
+        let previous = content_keypair_from_private_key(&[3u8; 32]).unwrap();
+        let next = content_keypair_from_private_key(&[9u8; 32]).unwrap();
+        let entries = HashMap::from([
+            ("0xaa".to_string(), wrapped_for(&previous, &[1u8; 32])),
+            ("0xbb".to_string(), wrapped_for(&previous, &[2u8; 32])),
+        ]);
+        (previous, next, entries)
+    }
+
+    #[test]
+    fn failed_store_write_keeps_the_previous_key() {
+        let (previous, next, entries) = fixture();
+        let stored = entries.clone();
+        let mut installed = false;
+
+        let result = install_rewrapped_keys(
+            entries,
+            &previous,
+            &next,
+            |_| Err("disk full".to_string()),
+            |_| {
+                installed = true;
+                Ok(())
+            },
+        );
+        assert_eq!(result.unwrap_err(), "disk full");
+        assert!(!installed);
+        for (content_id, raw_key) in [("0xaa", [1u8; 32]), ("0xbb", [2u8; 32])] {
+            let entry = &stored[content_id];
+            assert_eq!(opens_with(&previous, entry).as_deref(), Some(&raw_key[..]));
+        }
+    }
+
+    #[test]
+    fn failed_key_store_puts_the_original_entries_back() {
+        let (previous, next, entries) = fixture();
+        let stored = RefCell::new(HashMap::new());
+
+        let result = install_rewrapped_keys(
+            entries,
+            &previous,
+            &next,
+            |written| {
+                *stored.borrow_mut() = written.clone();
+                Ok(())
+            },
+            |_| Err("secret store locked".to_string()),
+        );
+        assert_eq!(result.unwrap_err(), "secret store locked");
+        let stored = stored.into_inner();
+        assert_eq!(opens_with(&previous, &stored["0xaa"]), Some(vec![1u8; 32]));
+        assert_eq!(opens_with(&previous, &stored["0xbb"]), Some(vec![2u8; 32]));
+    }
+
+    #[test]
+    fn installed_key_opens_every_rewrapped_entry() {
+        let (previous, next, entries) = fixture();
+        let stored = RefCell::new(HashMap::new());
+        let rewrapped = install_rewrapped_keys(
+            entries,
+            &previous,
+            &next,
+            |written| {
+                *stored.borrow_mut() = written.clone();
+                Ok(())
+            },
+            |_| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(rewrapped, 2);
+        let stored = stored.into_inner();
+        assert_eq!(opens_with(&next, &stored["0xaa"]), Some(vec![1u8; 32]));
+        assert!(opens_with(&previous, &stored["0xbb"]).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+//! Offline copies of the content keypair: a passphrase-sealed backup file, and a 24-word
+//! BIP-39 recovery phrase that encodes the private key directly.
+//!
+//! Backup file: the 32-byte private key sealed with AES-256-GCM under an Argon2id key
+//! derived from the passphrase, with the public key bound as associated data.
+
+use super::*;
+use aes_gcm::aead::Payload;
+
+const BACKUP_KIND: &str = "heaven-content-key-backup";
+const BACKUP_VERSION: u32 = 1;
+const BACKUP_KDF: &str = "argon2id";
+const MIN_BACKUP_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContentKeyBackupFile {
+    kind: String,
+    version: u32,
+    kdf: String,
+    salt: String,
+    public_key: String,
+    iv: String,
+    ciphertext: String,
+}
+
+fn backup_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed deriving backup key: {e}"))?;
+    let cipher =
+        Aes256Gcm::new_from_slice(&key).map_err(|e| format!("Failed creating backup cipher: {e}"));
+    key.fill(0);
+    cipher
+}
+
+pub(crate) fn seal_content_key_backup(
+    keypair: &ContentKeyPair,
+    passphrase: &str,
+) -> Result<String, String> {
+    if passphrase.chars().count() < MIN_BACKUP_PASSPHRASE_LEN {
+        return Err(format!(
+            "Backup passphrase must be at least {MIN_BACKUP_PASSPHRASE_LEN} characters."
+        ));
+    }
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut iv = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut iv);
+    let ciphertext = backup_cipher(passphrase, &salt)?
+        .encrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: &keypair.private_key,
+                aad: &keypair.public_key,
+            },
+        )
+        .map_err(|e| format!("Failed encrypting content key backup: {e}"))?;
+
+    serde_json::to_string_pretty(&ContentKeyBackupFile {
+        kind: BACKUP_KIND.to_string(),
+        version: BACKUP_VERSION,
+        kdf: BACKUP_KDF.to_string(),
+        salt: hex::encode(salt),
+        public_key: hex::encode(&keypair.public_key),
+        iv: hex::encode(iv),
+        ciphertext: hex::encode(ciphertext),
+    })
+    .map_err(|e| format!("Failed encoding content key backup: {e}"))
+}
+
+pub(crate) fn open_content_key_backup(
+    text: &str,
+    passphrase: &str,
+) -> Result<ContentKeyPair, String> {
+    let file: ContentKeyBackupFile = serde_json::from_str(text)
+        .map_err(|e| format!("Failed parsing content key backup: {e}"))?;
+    if file.kind != BACKUP_KIND || file.version != BACKUP_VERSION || file.kdf != BACKUP_KDF {
+        return Err(format!(
+            "Unsupported content key backup (kind {}, version {}, kdf {}).",
+            file.kind, file.version, file.kdf
+        ));
+    }
+    let salt = decode_hex_bytes(&file.salt, "backup salt")?;
+    let public_key = decode_hex_bytes(&file.public_key, "backup public key")?;
+    let iv = decode_hex_bytes(&file.iv, "backup iv")?;
+    if iv.len() != 12 {
+        return Err(format!(
+            "Invalid backup IV length: expected 12, got {}",
+            iv.len()
+        ));
+    }
+    let ciphertext = decode_hex_bytes(&file.ciphertext, "backup ciphertext")?;
+
+    let mut private_key = backup_cipher(passphrase, &salt)?
+        .decrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: &ciphertext,
+                aad: &public_key,
+            },
+        )
+        .map_err(|_| "Backup passphrase is incorrect or the file is damaged.".to_string())?;
+    let keypair = content_keypair_from_private_key(&private_key);
+    private_key.fill(0);
+    let keypair = keypair?;
+    if keypair.public_key != public_key {
+        return Err("Backup public key does not match its private key.".to_string());
+    }
+    Ok(keypair)
+}
+
+pub(crate) fn content_key_recovery_phrase(keypair: &ContentKeyPair) -> Result<String, String> {
+    bip39::Mnemonic::from_entropy(&keypair.private_key)
+        .map(|mnemonic| mnemonic.to_string())
+        .map_err(|e| format!("Failed encoding recovery phrase: {e}"))
+}
+
+pub(crate) fn content_keypair_from_recovery_phrase(phrase: &str) -> Result<ContentKeyPair, String> {
+    let normalized = phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mnemonic = bip39::Mnemonic::parse_normalized(&normalized)
+        .map_err(|e| format!("Invalid recovery phrase: {e}"))?;
+    if mnemonic.word_count() != 24 {
+        return Err(format!(
+            "Recovery phrase must have 24 words, got {}.",
+            mnemonic.word_count()
+        ));
+    }
+    let mut entropy = mnemonic.to_entropy();
+    let keypair = content_keypair_from_private_key(&entropy);
+    entropy.fill(0);
+    keypair
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_keypair() -> ContentKeyPair {
+        content_keypair_from_private_key(&[7u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn backup_opens_only_with_its_passphrase() {
+        let keypair = test_keypair();
+        let sealed = seal_content_key_backup(&keypair, "correct horse").unwrap();
+
+        let opened = open_content_key_backup(&sealed, "correct horse").unwrap();
+        assert_eq!(opened.private_key, keypair.private_key);
+        assert_eq!(opened.public_key, keypair.public_key);
+        assert!(open_content_key_backup(&sealed, "wrong horse").is_err());
+        assert!(seal_content_key_backup(&keypair, "short").is_err());
+    }
+
+    #[test]
+    fn recovery_phrase_round_trips_the_private_key() {
+        let keypair = test_keypair();
+        let phrase = content_key_recovery_phrase(&keypair).unwrap();
+        assert_eq!(phrase.split_whitespace().count(), 24);
+
+        let shouted = format!("  {}  ", phrase.to_uppercase().replace(' ', "\n"));
+        let restored = content_keypair_from_recovery_phrase(&shouted).unwrap();
+        assert_eq!(restored.private_key, keypair.private_key);
+        assert_eq!(restored.public_key, keypair.public_key);
+
+        let twelve_words = format!("{} about", ["abandon"; 11].join(" "));
+        assert!(content_keypair_from_recovery_phrase(&twelve_words).is_err());
+        assert!(content_keypair_from_recovery_phrase("not a recovery phrase").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use std::collections::BTreeMap;
+
+const LINKED_DEVICES_FILE: &str = "content_linked_devices_v1.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeviceRegistry {
+    /// Set on a device that receives keys from another one. It must not replace the
+    /// account's published contentPubKey with its own.
+    #[serde(default)]
+    pub(crate) secondary: bool,
+    /// Devices this one re-wraps its content keys for.
+    #[serde(default)]
+    pub(crate) devices: Vec<LinkedDevice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LinkedDevice {
+    pub(crate) public_key: String,
+    pub(crate) fingerprint: String,
+    pub(crate) linked_at_ms: i64,
+    /// contentId -> digest of the wrapped key last published to this device.
+    #[serde(default)]
+    pub(crate) synced: BTreeMap<String, String>,
+}
+
+impl LinkedDevice {
+    pub(crate) fn new(public_key: &[u8]) -> Self {
+        Self {
+            public_key: hex::encode(public_key),
+            fingerprint: content_key_fingerprint(public_key),
+            linked_at_ms: chrono::Utc::now().timestamp_millis(),
+            synced: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn public_key_bytes(&self) -> Result<Vec<u8>, String> {
+        decode_hex_bytes(&self.public_key, "linked device public key")
+    }
+}
+
+fn linked_devices_path() -> PathBuf {
+    AccountRegistry::shared().scoped_path(LINKED_DEVICES_FILE)
+}
+
+pub(crate) fn read_device_registry() -> DeviceRegistry {
+    let Ok(text) = fs::read_to_string(linked_devices_path()) else {
+        return DeviceRegistry::default();
+    };
+    serde_json::from_str(&text).unwrap_or_default()
+}
+
+pub(crate) fn write_device_registry(registry: &DeviceRegistry) -> Result<(), String> {
+    let path = linked_devices_path();
+    ensure_parent_dir(&path)?;
+    let encoded = serde_json::to_string_pretty(registry)
+        .map_err(|e| format!("Failed encoding linked devices: {e}"))?;
+    fs::write(&path, encoded)
+        .map_err(|e| format!("Failed writing linked devices ({}): {e}", path.display()))
+}
+
+/// Short id for a content public key; tags the envelopes addressed to one device.
+pub(crate) fn content_key_fingerprint(public_key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(public_key)[..8])
+}
+
+pub(crate) fn parse_content_public_key(raw: &str) -> Result<Vec<u8>, String> {
+    let decoded = decode_hex_bytes(raw, "device content public key")?;
+    if decoded.len() != 65 || decoded[0] != 0x04 {
+        return Err(
+            "Invalid device content public key (expected 65-byte uncompressed P256 key)."
+                .to_string(),
+        );
+    }
+    PublicKey::from_sec1_bytes(&decoded)
+        .map_err(|e| format!("Invalid device content public key: {e}"))?;
+    Ok(decoded)
+}
+
+/// Content ids whose wrapped key this device holds, each with a digest that changes when
+/// the cached key is replaced (for example after the owner rotates it).
+pub(crate) fn cached_wrapped_key_digests() -> Vec<(String, String)> {
+    lookup_wrapped_keys()
+        .into_iter()
+        .map(|(content_id, entry)| {
+            let digest = hex::encode(&Sha256::digest(entry.ciphertext.as_bytes())[..16]);
+            (content_id, digest)
+        })
+        .collect()
+}
@@ -1,52 +1,213 @@
 use super::*;
+use crate::shared::gateways::{GatewayPool, GatewayResource, Integrity};
+
+/// Every dataitem id matching `filters`.
+fn query_dataitem_ids(filters: Vec<Value>, page_size: usize) -> LoadStorageResult<Vec<String>> {
+    Ok(dataitem_ids(&query_dataitems(filters, page_size, false)?))
+}
+
+/// Every index item matching `filters`. The agent only takes a `first` limit, so the window
+/// doubles from `page_size` until a response comes back short of it.
+fn query_dataitems(
+    filters: Vec<Value>,
+    page_size: usize,
+    include_tags: bool,
+) -> LoadStorageResult<Vec<Value>> {
+    let mut first = page_size.max(1);
+    loop {
+        let payload = http_post_json(
+            &format!("{}/tags/query", load_agent_url()),
+            json!({
+                "filters": filters,
+                "first": first,
+                "include_tags": include_tags,
+            }),
+        )?;
+        let items = payload
+            .get("items")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let complete = items.len() < first;
+        if complete || first >= MAX_TAG_QUERY_RESULTS {
+            if !complete {
+                log::warn!(
+                    "[LoadStorage] tag query stopped at {} results; older matches were skipped",
+                    items.len()
+                );
+            }
+            return Ok(items);
+        }
+        first = (first * 2).min(MAX_TAG_QUERY_RESULTS);
+    }
+}
+
+fn dataitem_id(item: &Value) -> &str {
+    item.get("dataitem_id")
+        .and_then(Value::as_str)
+        .or_else(|| item.get("dataitemId").and_then(Value::as_str))
+        .or_else(|| item.get("id").and_then(Value::as_str))
+        .map(str::trim)
+        .unwrap_or_default()
+}
+
+fn dataitem_ids(items: &[Value]) -> Vec<String> {
+    let mut out = Vec::<String>::new();
+    for item in items {
+        let id = dataitem_id(item);
+        if !id.is_empty() && !out.iter().any(|seen| seen == id) {
+            out.push(id.to_string());
+        }
+    }
+    out
+}
 
 pub(super) fn query_envelope_ids(
     content_id_hex: &str,
     owner_address: &str,
     grantee_address: &str,
-) -> Result<Vec<String>, String> {
-    let payload = http_post_json(
-        &format!("{}/tags/query", load_agent_url()),
-        json!({
-            "filters": [
-                {"key": "App-Name", "value": "Heaven"},
-                {"key": "Heaven-Type", "value": ENVELOPE_TAG_TYPE},
-                {"key": "Content-Id", "value": content_id_hex},
-                {"key": "Owner", "value": owner_address},
-                {"key": "Grantee", "value": grantee_address},
-            ],
-            "first": 8,
-            "include_tags": false,
-        }),
+) -> LoadStorageResult<Vec<String>> {
+    query_dataitem_ids(
+        vec![
+            json!({"key": "App-Name", "value": "Heaven"}),
+            json!({"key": "Heaven-Type", "value": ENVELOPE_TAG_TYPE}),
+            json!({"key": "Content-Id", "value": content_id_hex}),
+            json!({"key": "Owner", "value": owner_address}),
+            json!({"key": "Grantee", "value": grantee_address}),
+        ],
+        8,
+    )
+}
+
+/// Every envelope `owner_address` published for `content_id_hex`, as (envelope id, grantee)
+/// pairs read from the index tags.
+pub(super) fn query_content_envelopes(
+    content_id_hex: &str,
+    owner_address: &str,
+) -> LoadStorageResult<Vec<(String, String)>> {
+    let items = query_dataitems(
+        vec![
+            json!({"key": "App-Name", "value": "Heaven"}),
+            json!({"key": "Heaven-Type", "value": ENVELOPE_TAG_TYPE}),
+            json!({"key": "Content-Id", "value": content_id_hex}),
+            json!({"key": "Owner", "value": owner_address}),
+        ],
+        32,
+        true,
     )?;
-    let items = payload
-        .get("items")
-        .and_then(Value::as_array)
-        .cloned()
-        .unwrap_or_default();
+    Ok(items.iter().filter_map(envelope_grantee).collect())
+}
 
-    let mut out = Vec::<String>::new();
-    for item in items {
-        let id = item
-            .get("dataitem_id")
-            .and_then(Value::as_str)
-            .or_else(|| item.get("dataitemId").and_then(Value::as_str))
-            .or_else(|| item.get("id").and_then(Value::as_str))
-            .map(str::trim)
-            .unwrap_or_default();
-        if !id.is_empty() {
-            out.push(id.to_string());
-        }
+fn envelope_grantee(item: &Value) -> Option<(String, String)> {
+    let id = dataitem_id(item);
+    let grantee = item
+        .get("tags")
+        .and_then(Value::as_array)?
+        .iter()
+        .find(|tag| {
+            tag.get("name")
+                .and_then(Value::as_str)
+                .or_else(|| tag.get("key").and_then(Value::as_str))
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case("Grantee"))
+        })?
+        .get("value")
+        .and_then(Value::as_str)?
+        .trim()
+        .to_lowercase();
+    if id.is_empty() || grantee.is_empty() {
+        return None;
+    }
+    Some((id.to_string(), grantee))
+}
+
+/// Envelopes another device of `wallet_address` re-wrapped for the device whose content
+/// key has `fingerprint`.
+pub(super) fn query_device_envelope_ids(
+    content_id_hex: &str,
+    wallet_address: &str,
+    fingerprint: &str,
+) -> LoadStorageResult<Vec<String>> {
+    query_dataitem_ids(
+        vec![
+            json!({"key": "App-Name", "value": "Heaven"}),
+            json!({"key": "Heaven-Type", "value": DEVICE_ENVELOPE_TAG_TYPE}),
+            json!({"key": "Content-Id", "value": content_id_hex}),
+            json!({"key": "Owner", "value": wallet_address}),
+            json!({"key": "Device", "value": fingerprint}),
+        ],
+        8,
+    )
+}
+
+/// Envelope ids the owner has marked superseded for `content_id_hex` after a key rotation.
+pub(super) fn query_superseded_envelope_ids(
+    content_id_hex: &str,
+    owner_address: &str,
+) -> LoadStorageResult<HashSet<String>> {
+    let record_ids = query_dataitem_ids(
+        vec![
+            json!({"key": "App-Name", "value": "Heaven"}),
+            json!({"key": "Heaven-Type", "value": SUPERSEDE_TAG_TYPE}),
+            json!({"key": "Content-Id", "value": content_id_hex}),
+            json!({"key": "Owner", "value": owner_address}),
+        ],
+        32,
+    )?;
+
+    let mut out = HashSet::new();
+    for record_id in record_ids {
+        let payload = fetch_resolve_payload(&record_id)?;
+        out.extend(parse_supersede_payload(
+            &payload,
+            content_id_hex,
+            owner_address,
+            &|signer, at_secs| Ok(account_key_authorized_at(owner_address, signer, at_secs)?),
+        )?);
     }
     Ok(out)
 }
 
-pub(super) fn fetch_resolve_payload(dataitem_id: &str) -> Result<Vec<u8>, String> {
+/// What the owner signs in a supersede record. Its tags and JSON fields are free for any
+/// uploader to set, so only this signature ties the record to the owner.
+pub(crate) fn supersede_signing_message(
+    content_id_hex: &str,
+    owner_address: &str,
+    piece_cid: &str,
+    superseded_at_ms: i64,
+    superseded_envelope_ids: &[String],
+) -> Vec<u8> {
+    format!(
+        "Heaven content-key-supersede v1\ncontentId: {}\nowner: {}\npieceCid: {}\nsupersededAtMs: {}\nsupersededEnvelopeIds: {}",
+        content_id_hex.trim().to_lowercase(),
+        owner_address.trim().to_lowercase(),
+        piece_cid.trim(),
+        superseded_at_ms,
+        superseded_envelope_ids.join(","),
+    )
+    .into_bytes()
+}
+
+pub(super) fn fetch_resolve_payload(dataitem_id: &str) -> LoadStorageResult<Vec<u8>> {
     let id = dataitem_id.trim();
     if id.is_empty() {
-        return Err("Envelope dataitem id is empty".to_string());
+        return Err(LoadStorageError::Other(
+            "Envelope dataitem id is empty".to_string(),
+        ));
     }
-    http_get_bytes(&format!("{}/resolve/{id}", load_gateway_url()))
+    // Envelope records are JSON; anything else is a gateway error page.
+    let integrity = Integrity {
+        sha256: None,
+        check: Some(&check_json_record),
+    };
+    Ok(GatewayPool::shared()
+        .fetch(&GatewayResource::load(id), integrity, None)?
+        .bytes)
+}
+
+fn check_json_record(bytes: &[u8]) -> Result<(), String> {
+    serde_json::from_slice::<Value>(bytes)
+        .map(|_| ())
+        .map_err(|e| format!("not a JSON record: {e}"))
 }
 
 pub(super) fn parse_envelope_payload(
@@ -117,3 +278,209 @@ pub(super) fn parse_envelope_payload(
         ciphertext,
     })
 }
+
+/// Blob the envelope's key was issued for, when the owner recorded one.
+pub(super) fn parse_envelope_piece_cid(payload: &[u8]) -> Option<String> {
+    let json: Value = serde_json::from_slice(payload).ok()?;
+    json.get("pieceCid")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Envelope ids a supersede record retires, if it is for this content and owner and carries
+/// a signature from a key `key_authorized` accepts for the owner at the record's
+/// `supersededAtMs` (in seconds).
+fn parse_supersede_payload(
+    payload: &[u8],
+    expected_content_id: &str,
+    expected_owner: &str,
+    key_authorized: &dyn Fn(&str, u64) -> Result<bool, String>,
+) -> Result<Vec<String>, String> {
+    let Ok(json) = serde_json::from_slice::<Value>(payload) else {
+        return Ok(Vec::new());
+    };
+    let matches = |key: &str, expected: &str| {
+        json.get(key)
+            .and_then(Value::as_str)
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case(expected))
+    };
+    if json.get("version").and_then(Value::as_u64) != Some(1)
+        || !matches("contentId", expected_content_id)
+        || !matches("owner", expected_owner)
+    {
+        return Ok(Vec::new());
+    }
+    let ids = json
+        .get("supersededEnvelopeIds")
+        .and_then(Value::as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let superseded_at_ms = json
+        .get("supersededAtMs")
+        .and_then(Value::as_i64)
+        .unwrap_or_default();
+    let message = supersede_signing_message(
+        expected_content_id,
+        expected_owner,
+        json.get("pieceCid")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+        superseded_at_ms,
+        &ids,
+    );
+    let signer = json
+        .get("signature")
+        .and_then(Value::as_str)
+        .and_then(|raw| decode_hex_bytes(raw, "supersede signature").ok())
+        .and_then(|raw| ethers::types::Signature::try_from(raw.as_slice()).ok())
+        .and_then(|signature| signature.recover(message).ok());
+    let Some(signer) = signer else {
+        log::warn!(
+            "[LoadStorage] ignoring unsigned supersede record for contentId={}",
+            expected_content_id
+        );
+        return Ok(Vec::new());
+    };
+    let signer = format!("{signer:#x}");
+    if !key_authorized(&signer, superseded_at_ms.max(0) as u64 / 1000)? {
+        log::warn!(
+            "[LoadStorage] ignoring supersede record for contentId={} signed by {}, not the owner",
+            expected_content_id,
+            signer
+        );
+        return Ok(Vec::new());
+    }
+    Ok(ids
+        .into_iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    const CONTENT_ID: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    const OWNER: &str = "0x00000000000000000000000000000000000000aa";
+
+    fn wallet(key: &str) -> LocalWallet {
+        key.parse().unwrap()
+    }
+
+    fn signed_record(signer: &LocalWallet, ids: &[&str]) -> Value {
+        let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let message = supersede_signing_message(CONTENT_ID, OWNER, "new-piece", 1_000, &ids);
+        let signature = signer
+            .sign_hash(ethers::utils::hash_message(message))
+            .unwrap();
+        json!({
+            "version": 1,
+            "contentId": CONTENT_ID,
+            "owner": OWNER.to_uppercase().replace("0X", "0x"),
+            "pieceCid": "new-piece",
+            "supersededEnvelopeIds": ids,
+            "supersededAtMs": 1_000,
+            "signature": to_hex_prefixed(&signature.to_vec()),
+        })
+    }
+
+    #[test]
+    fn envelope_grantees_come_from_the_grantee_tag() {
+        let item = json!({
+            "dataitem_id": " env-1 ",
+            "tags": [
+                {"name": "Heaven-Type", "value": "content-key-envelope"},
+                {"name": "Grantee", "value": "0xBEEF000000000000000000000000000000000001"}
+            ]
+        });
+        assert_eq!(
+            envelope_grantee(&item),
+            Some((
+                "env-1".to_string(),
+                "0xbeef000000000000000000000000000000000001".to_string()
+            ))
+        );
+        let keyed = json!({"id": "env-2", "tags": [{"key": "grantee", "value": "0xabc"}]});
+        assert_eq!(
+            envelope_grantee(&keyed),
+            Some(("env-2".to_string(), "0xabc".to_string()))
+        );
+        assert_eq!(
+            envelope_grantee(&json!({"dataitem_id": "env-3", "tags": []})),
+            None
+        );
+        assert_eq!(envelope_grantee(&json!({"dataitem_id": "env-4"})), None);
+    }
+
+    #[test]
+    fn supersede_records_only_count_for_their_own_content_and_owner() {
+        let session = wallet("0x0101010101010101010101010101010101010101010101010101010101010101");
+        let session_address = format!("{:#x}", session.address());
+        // Checked as of the record's own supersededAtMs (1_000 ms).
+        let authorized =
+            |signer: &str, at_secs: u64| Ok::<_, String>(signer == session_address && at_secs == 1);
+        let payload =
+            serde_json::to_vec(&signed_record(&session, &["env-1", " ", "env-2"])).unwrap();
+
+        assert_eq!(
+            parse_supersede_payload(&payload, CONTENT_ID, OWNER, &authorized).unwrap(),
+            vec!["env-1".to_string(), "env-2".to_string()]
+        );
+        assert!(
+            parse_supersede_payload(&payload, CONTENT_ID, "0xbb", &authorized)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            parse_supersede_payload(&payload, "0x02", OWNER, &authorized)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            parse_envelope_piece_cid(&payload).as_deref(),
+            Some("new-piece")
+        );
+    }
+
+    #[test]
+    fn supersede_records_not_signed_by_an_owner_key_are_ignored() {
+        let session = wallet("0x0101010101010101010101010101010101010101010101010101010101010101");
+        let stranger = wallet("0x0202020202020202020202020202020202020202020202020202020202020202");
+        let session_address = format!("{:#x}", session.address());
+        let authorized = |signer: &str, _at_secs: u64| Ok::<_, String>(signer == session_address);
+
+        let forged = serde_json::to_vec(&signed_record(&stranger, &["env-1"])).unwrap();
+        assert!(
+            parse_supersede_payload(&forged, CONTENT_ID, OWNER, &authorized)
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut tampered = signed_record(&session, &["env-1"]);
+        tampered["supersededEnvelopeIds"] = json!(["env-1", "env-live"]);
+        let tampered = serde_json::to_vec(&tampered).unwrap();
+        assert!(
+            parse_supersede_payload(&tampered, CONTENT_ID, OWNER, &authorized)
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut unsigned = signed_record(&session, &["env-1"]);
+        unsigned.as_object_mut().unwrap().remove("signature");
+        let unsigned = serde_json::to_vec(&unsigned).unwrap();
+        assert!(
+            parse_supersede_payload(&unsigned, CONTENT_ID, OWNER, &authorized)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! v2 content payloads: AES-256-GCM over fixed-size segments (the STREAM construction).
+//!
+//! Layout: `HVC2 | algo | nonce prefix (7) | chunk size (u32 BE)`, then sealed chunks of
+//! `chunk size` plaintext bytes plus a 16-byte tag; only the last chunk may be shorter.
+//! Chunk `i` is sealed under nonce `prefix || i (u32 BE) || last flag` with the header as
+//! associated data, so chunks cannot be reordered, dropped from the end or spliced between
+//! payloads, and each one can be checked as soon as it arrives.
+
+use super::*;
+use aes_gcm::aead::Payload;
+use std::io::{self, Read};
+
+pub(crate) const STREAM_MAGIC: &[u8; 4] = b"HVC2";
+pub(crate) const STREAM_NONCE_PREFIX_LEN: usize = 7;
+pub(crate) const STREAM_HEADER_LEN: usize = 4 + 1 + STREAM_NONCE_PREFIX_LEN + 4;
+pub(crate) const DEFAULT_STREAM_CHUNK_SIZE: u32 = 64 * 1024;
+const MAX_STREAM_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+const TAG_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StreamHeader {
+    pub(crate) nonce_prefix: [u8; STREAM_NONCE_PREFIX_LEN],
+    pub(crate) chunk_size: u32,
+}
+
+impl StreamHeader {
+    pub(crate) fn random(chunk_size: u32) -> Result<Self, String> {
+        validate_chunk_size(chunk_size)?;
+        let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_prefix);
+        Ok(Self {
+            nonce_prefix,
+            chunk_size,
+        })
+    }
+
+    pub(crate) fn encode(&self) -> [u8; STREAM_HEADER_LEN] {
+        let mut out = [0u8; STREAM_HEADER_LEN];
+        out[..4].copy_from_slice(STREAM_MAGIC);
+        out[4] = ALGO_AES_GCM_256_STREAM;
+        out[5..5 + STREAM_NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        out[5 + STREAM_NONCE_PREFIX_LEN..].copy_from_slice(&self.chunk_size.to_be_bytes());
+        out
+    }
+
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < STREAM_HEADER_LEN || !bytes.starts_with(STREAM_MAGIC) {
+            return Err("Not a v2 content payload.".to_string());
+        }
+        if bytes[4] != ALGO_AES_GCM_256_STREAM {
+            return Err(format!("Unsupported v2 content algorithm: {}", bytes[4]));
+        }
+        let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_LEN];
+        nonce_prefix.copy_from_slice(&bytes[5..5 + STREAM_NONCE_PREFIX_LEN]);
+        let mut size = [0u8; 4];
+        size.copy_from_slice(&bytes[5 + STREAM_NONCE_PREFIX_LEN..STREAM_HEADER_LEN]);
+        let chunk_size = u32::from_be_bytes(size);
+        validate_chunk_size(chunk_size)?;
+        Ok(Self {
+            nonce_prefix,
+            chunk_size,
+        })
+    }
+
+    fn nonce(&self, index: u32, last: bool) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..STREAM_NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[STREAM_NONCE_PREFIX_LEN..11].copy_from_slice(&index.to_be_bytes());
+        nonce[11] = u8::from(last);
+        nonce
+    }
+}
+
+/// Size of the v2 payload sealing `plaintext_len` bytes.
+pub(crate) fn stream_payload_len(plaintext_len: u64, chunk_size: u32) -> u64 {
+    let chunks = plaintext_len.div_ceil(u64::from(chunk_size)).max(1);
+    STREAM_HEADER_LEN as u64 + plaintext_len + chunks * TAG_LEN as u64
+}
+
+fn validate_chunk_size(chunk_size: u32) -> Result<(), String> {
+    if chunk_size == 0 || chunk_size > MAX_STREAM_CHUNK_SIZE {
+        return Err(format!("Invalid v2 content chunk size: {chunk_size}"));
+    }
+    Ok(())
+}
+
+/// Whether `bytes` open with a well-formed v2 header. The magic alone is not enough, since
+/// a pre-v2 payload starts with a random IV that can begin with the same four bytes.
+pub(crate) fn is_stream_payload(bytes: &[u8]) -> bool {
+    StreamHeader::parse(bytes).is_ok()
+}
+
+/// Reads plaintext from `inner` and yields the v2 payload, header first.
+pub(crate) struct StreamEncryptor<R> {
+    inner: R,
+    cipher: Aes256Gcm,
+    header: StreamHeader,
+    aad: [u8; STREAM_HEADER_LEN],
+    index: u32,
+    lookahead: Vec<u8>,
+    pending: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl<R: Read> StreamEncryptor<R> {
+    pub(crate) fn new(inner: R, key: &[u8; 32], chunk_size: u32) -> LoadStorageResult<Self> {
+        let header = StreamHeader::random(chunk_size).map_err(LoadStorageError::Crypto)?;
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| {
+            LoadStorageError::Crypto(format!("Failed to initialize AES-256-GCM key: {e}"))
+        })?;
+        let aad = header.encode();
+        Ok(Self {
+            inner,
+            cipher,
+            header,
+            aad,
+            index: 0,
+            lookahead: Vec::new(),
+            pending: aad.to_vec(),
+            pos: 0,
+            finished: false,
+        })
+    }
+
+    fn seal_next(&mut self) -> io::Result<()> {
+        let chunk_size = self.header.chunk_size as usize;
+        let mut chunk = std::mem::take(&mut self.lookahead);
+        // One byte past the chunk tells whether this is the last one.
+        fill_to(&mut self.inner, &mut chunk, chunk_size + 1)?;
+        let last = chunk.len() <= chunk_size;
+        if !last {
+            self.lookahead = chunk.split_off(chunk_size);
+        }
+        let nonce = self.header.nonce(self.index, last);
+        self.pending = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &chunk,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|e| invalid_data(format!("Failed encrypting content chunk: {e}")))?;
+        self.pos = 0;
+        self.finished = last;
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| invalid_data("Content exceeds the v2 chunk limit.".to_string()))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for StreamEncryptor<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.seal_next()?;
+        }
+        let n = out.len().min(self.pending.len() - self.pos);
+        out[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Reads a v2 payload from `inner` and yields plaintext, failing on the first chunk that
+/// does not authenticate or when the payload ends before its last chunk.
+pub(crate) struct StreamDecryptor<R> {
+    inner: R,
+    cipher: Aes256Gcm,
+    header: StreamHeader,
+    aad: [u8; STREAM_HEADER_LEN],
+    index: u32,
+    lookahead: Vec<u8>,
+    pending: Vec<u8>,
+    pos: usize,
+    finished: bool,
+}
+
+impl<R: Read> StreamDecryptor<R> {
+    pub(crate) fn new(mut inner: R, key: &[u8]) -> LoadStorageResult<Self> {
+        let mut header_bytes = [0u8; STREAM_HEADER_LEN];
+        inner
+            .read_exact(&mut header_bytes)
+            .map_err(|e| stream_read_error("Failed reading v2 content header", e))?;
+        let header = StreamHeader::parse(&header_bytes).map_err(LoadStorageError::Crypto)?;
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| {
+            LoadStorageError::Crypto(format!("Failed to initialize AES key for decrypt: {e}"))
+        })?;
+        Ok(Self {
+            inner,
+            cipher,
+            header,
+            aad: header_bytes,
+            index: 0,
+            lookahead: Vec::new(),
+            pending: Vec::new(),
+            pos: 0,
+            finished: false,
+        })
+    }
+
+    fn open_next(&mut self) -> io::Result<()> {
+        let sealed_size = self.header.chunk_size as usize + TAG_LEN;
+        let mut sealed = std::mem::take(&mut self.lookahead);
+        fill_to(&mut self.inner, &mut sealed, sealed_size + 1)?;
+        if sealed.len() < TAG_LEN {
+            return Err(invalid_data("Content payload is truncated.".to_string()));
+        }
+        let last = sealed.len() <= sealed_size;
+        if !last {
+            self.lookahead = sealed.split_off(sealed_size);
+        }
+        let nonce = self.header.nonce(self.index, last);
+        self.pending = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &sealed,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| {
+                invalid_data(format!(
+                    "Content chunk {} failed authentication (corrupt or truncated payload).",
+                    self.index
+                ))
+            })?;
+        self.pos = 0;
+        self.finished = last;
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| invalid_data("Content exceeds the v2 chunk limit.".to_string()))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for StreamDecryptor<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.open_next()?;
+        }
+        let n = out.len().min(self.pending.len() - self.pos);
+        out[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Decrypt a whole v2 payload held in memory.
+pub(crate) fn decrypt_stream_payload(key: &[u8], payload: &[u8]) -> LoadStorageResult<Vec<u8>> {
+    let mut plaintext = Vec::new();
+    StreamDecryptor::new(payload, key)?
+        .read_to_end(&mut plaintext)
+        .map_err(|e| stream_read_error("Failed decrypting audio payload", e))?;
+    Ok(plaintext)
+}
+
+/// A payload that is cut short or fails authentication is a `Crypto` error; anything else
+/// went wrong in the reader underneath.
+fn stream_read_error(context: &str, err: io::Error) -> LoadStorageError {
+    let message = format!("{context}: {err}");
+    match err.kind() {
+        io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+            LoadStorageError::Crypto(message)
+        }
+        _ => LoadStorageError::Other(message),
+    }
+}
+
+/// Read from `inner` until `buf` holds `target` bytes or the input ends.
+fn fill_to(inner: &mut impl Read, buf: &mut Vec<u8>, target: usize) -> io::Result<()> {
+    let wanted = target.saturating_sub(buf.len()) as u64;
+    inner.take(wanted).read_to_end(buf)?;
+    Ok(())
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(plaintext: &[u8], key: &[u8; 32], chunk_size: u32) -> Vec<u8> {
+        let mut payload = Vec::new();
+        StreamEncryptor::new(plaintext, key, chunk_size)
+            .unwrap()
+            .read_to_end(&mut payload)
+            .unwrap();
+        payload
+    }
+
+    #[test]
+    fn round_trips_and_rejects_tampering() {
+        let key = [7u8; 32];
+        for len in [0usize, 1, 15, 16, 17, 64, 1000] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let payload = encrypt(&plaintext, &key, 16);
+            assert!(is_stream_payload(&payload));
+            let chunks = len.div_ceil(16).max(1);
+            assert_eq!(payload.len(), STREAM_HEADER_LEN + len + chunks * TAG_LEN);
+            assert_eq!(decrypt_stream_payload(&key, &payload).unwrap(), plaintext);
+        }
+
+        let plaintext = vec![42u8; 40];
+        let payload = encrypt(&plaintext, &key, 16);
+        let sealed = 16 + TAG_LEN;
+
+        // Dropping the final chunk leaves a non-final chunk at the end.
+        let truncated = &payload[..STREAM_HEADER_LEN + 2 * sealed];
+        assert!(decrypt_stream_payload(&key, truncated).is_err());
+
+        let mut swapped = payload.clone();
+        let (first, second) = (STREAM_HEADER_LEN, STREAM_HEADER_LEN + sealed);
+        let chunk = swapped[first..second].to_vec();
+        swapped.copy_within(second..second + sealed, first);
+        swapped[second..second + sealed].copy_from_slice(&chunk);
+        assert!(decrypt_stream_payload(&key, &swapped).is_err());
+
+        let mut resized = payload.clone();
+        resized[STREAM_HEADER_LEN - 1] = 32;
+        assert!(decrypt_stream_payload(&key, &resized).is_err());
+
+        assert!(decrypt_stream_payload(&[8u8; 32], &payload).is_err());
+    }
+
+    #[test]
+    fn legacy_payloads_starting_with_the_magic_are_not_v2() {
+        let mut legacy = STREAM_MAGIC.to_vec();
+        legacy.extend_from_slice(&[0x9c; 40]);
+        assert!(!is_stream_payload(&legacy));
+
+        let payload = encrypt(b"audio", &[7u8; 32], 16);
+        assert!(is_stream_payload(&payload[..STREAM_HEADER_LEN]));
+    }
+}
@@ -50,6 +50,14 @@ pub(crate) fn tempo_rpc_url() -> String {
         .unwrap_or_else(|| DEFAULT_TEMPO_RPC_URL.to_string())
 }
 
+pub(crate) fn tempo_account_keychain() -> String {
+    std::env::var("HEAVEN_TEMPO_ACCOUNT_KEYCHAIN")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_TEMPO_ACCOUNT_KEYCHAIN.to_string())
+}
+
 pub(crate) fn subgraph_music_social_url() -> String {
     std::env::var("SUBGRAPH_MUSIC_SOCIAL_URL")
         .ok()
@@ -139,6 +147,35 @@ pub(crate) fn load_turbo_upload_token() -> String {
         .to_lowercase()
 }
 
+pub(crate) fn arweave_turbo_upload_url() -> String {
+    std::env::var("HEAVEN_ARWEAVE_TURBO_UPLOAD_URL")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_ARWEAVE_TURBO_UPLOAD_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+pub(crate) fn arweave_turbo_payment_url() -> String {
+    std::env::var("HEAVEN_ARWEAVE_TURBO_PAYMENT_URL")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_ARWEAVE_TURBO_PAYMENT_URL.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+pub(crate) fn arweave_turbo_token() -> String {
+    std::env::var("HEAVEN_ARWEAVE_TURBO_TOKEN")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_ARWEAVE_TURBO_TOKEN.to_string())
+        .to_lowercase()
+}
+
 pub(crate) fn load_gateway_url() -> String {
     std::env::var("HEAVEN_LOAD_GATEWAY_URL")
         .ok()
@@ -1,5 +1,11 @@
 use super::*;
 
+/// The signed-in wallet, or an `Auth` error when the session has none.
+pub(crate) fn signed_in_wallet(auth: &PersistedAuth) -> LoadStorageResult<&str> {
+    auth.wallet_address()
+        .ok_or_else(|| LoadStorageError::Auth("Missing wallet address in auth".to_string()))
+}
+
 pub(crate) fn normalize_content_id_hex(content_id_hex: &str) -> Result<String, String> {
     let raw = content_id_hex.trim();
     if raw.is_empty() {
@@ -1,54 +1,171 @@
 use super::*;
+use crate::shared::gateways::{GatewayKind, GatewayPool, GatewayResource, GatewayStream};
+use std::collections::HashMap;
 
-pub(crate) fn build_shared_gateway_urls(
+/// sha256 of each encrypted blob that has decrypted here, keyed by pieceCid.
+const BLOB_DIGESTS_FILE: &str = "shared_blob_digests_v1.json";
+/// Arweave ids of the blobs archived from here, keyed by pieceCid.
+const ARCHIVED_COPIES_FILE: &str = "archived_copies_v1.json";
+/// Archive copies tried per pieceCid; anyone can tag an item, so more than one may match.
+const MAX_ARCHIVE_CANDIDATES: usize = 5;
+
+/// Open an encrypted content blob to decrypt as it downloads. When Load no longer serves
+/// it, the copies "save forever" put on Arweave are tried. The caller checks what it reads
+/// against [`pinned_blob_digest`] once the body ends, and decryption rejects a substituted
+/// copy.
+pub(crate) fn open_shared_blob(
     piece_cid: &str,
     gateway_url_hint: Option<&str>,
-) -> Vec<String> {
-    let mut out = Vec::<String>::new();
-    let mut seen = HashSet::<String>::new();
-    let piece_cid = piece_cid.trim();
-
-    let push = |seen: &mut HashSet<String>, out: &mut Vec<String>, candidate: String| {
-        if candidate.is_empty() {
-            return;
-        }
-        if seen.insert(candidate.clone()) {
-            out.push(candidate);
-        }
+) -> Result<GatewayStream, HttpError> {
+    let pool = GatewayPool::shared();
+    let err = match pool.open(&GatewayResource::load(piece_cid), gateway_url_hint) {
+        Ok(stream) => return Ok(stream),
+        Err(err) => err,
     };
+    for arweave_id in archived_copy_ids(piece_cid) {
+        match pool.open(&GatewayResource::arweave(&arweave_id), None) {
+            Ok(stream) => {
+                log::info!("[LoadStorage] pieceCid={piece_cid} streaming from Arweave copy {arweave_id}");
+                return Ok(stream);
+            }
+            Err(archive_err) => log::warn!(
+                "[LoadStorage] Arweave copy {arweave_id} of pieceCid={piece_cid} failed: {archive_err}"
+            ),
+        }
+    }
+    Err(err)
+}
+
+/// Record that `piece_cid` was archived as `arweave_id`, so reads here try it first.
+pub(crate) fn remember_archived_copy(piece_cid: &str, arweave_id: &str) -> Result<(), String> {
+    let mut copies = read_archived_copies();
+    let ids = copies.entry(piece_cid.trim().to_string()).or_default();
+    if ids.iter().any(|id| id == arweave_id) {
+        return Ok(());
+    }
+    ids.insert(0, arweave_id.to_string());
+    let path = archived_copies_path();
+    ensure_parent_dir(&path)?;
+    let encoded = serde_json::to_string_pretty(&copies)
+        .map_err(|e| format!("Failed encoding archived copies: {e}"))?;
+    fs::write(&path, encoded)
+        .map_err(|e| format!("Failed writing archived copies ({}): {e}", path.display()))
+}
 
-    if let Some(hint) = gateway_url_hint {
-        let hint = hint.trim();
-        if !hint.is_empty() {
-            if hint.contains("/resolve/") {
-                push(&mut seen, &mut out, hint.to_string());
-            } else if hint.starts_with("http://") || hint.starts_with("https://") {
-                push(
-                    &mut seen,
-                    &mut out,
-                    format!("{}/resolve/{piece_cid}", hint.trim_end_matches('/')),
-                );
+/// Arweave ids of the "save forever" copies of `piece_cid`: those archived from here, then
+/// the DataItems an Arweave gateway indexes under the archive's `Load-Piece-Cid` tag.
+fn archived_copy_ids(piece_cid: &str) -> Vec<String> {
+    let mut ids = read_archived_copies().remove(piece_cid).unwrap_or_default();
+    match query_archived_copies(piece_cid) {
+        Ok(found) => {
+            for id in found {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
             }
         }
+        Err(err) => {
+            log::warn!(
+                "[LoadStorage] Arweave archive lookup for pieceCid={piece_cid} failed: {err}"
+            )
+        }
     }
+    ids.truncate(MAX_ARCHIVE_CANDIDATES);
+    ids
+}
+
+fn read_archived_copies() -> HashMap<String, Vec<String>> {
+    fs::read_to_string(archived_copies_path())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn archived_copies_path() -> PathBuf {
+    crate::auth::accounts::AccountRegistry::shared().scoped_path(ARCHIVED_COPIES_FILE)
+}
 
-    push(
-        &mut seen,
-        &mut out,
-        format!("{}/resolve/{piece_cid}", load_gateway_url()),
-    );
-    push(
-        &mut seen,
-        &mut out,
-        format!("https://gateway.s3-node-1.load.network/resolve/{piece_cid}"),
-    );
-    push(
-        &mut seen,
-        &mut out,
-        format!("https://arweave.net/{piece_cid}"),
-    );
-
-    out
+fn query_archived_copies(piece_cid: &str) -> Result<Vec<String>, HttpError> {
+    let Some(base) = GatewayPool::shared().preferred_base(GatewayKind::Arweave) else {
+        return Ok(Vec::new());
+    };
+    let query = "query($pieceCid: String!, $first: Int!) { transactions(tags: [\
+        {name: \"App-Name\", values: [\"Heaven\"]}, \
+        {name: \"Heaven-Type\", values: [\"content-archive\"]}, \
+        {name: \"Load-Piece-Cid\", values: [$pieceCid]}], \
+        first: $first, sort: HEIGHT_DESC) { edges { node { id } } } }";
+    let payload = http_post_json(
+        &format!("{base}/graphql"),
+        json!({
+            "query": query,
+            "variables": {"pieceCid": piece_cid, "first": MAX_ARCHIVE_CANDIDATES},
+        }),
+    )?;
+    Ok(parse_archived_copy_ids(&payload))
+}
+
+fn parse_archived_copy_ids(payload: &Value) -> Vec<String> {
+    payload
+        .pointer("/data/transactions/edges")
+        .and_then(Value::as_array)
+        .map(|edges| {
+            edges
+                .iter()
+                .filter_map(|edge| edge.pointer("/node/id").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// sha256 (hex) pinned for `piece_cid` by an earlier decrypt.
+pub(crate) fn pinned_blob_digest(piece_cid: &str) -> Option<String> {
+    read_blob_digests().remove(piece_cid)
+}
+
+pub(crate) fn pin_shared_blob_digest(piece_cid: &str, digest: &str) -> Result<(), String> {
+    let mut digests = read_blob_digests();
+    if digests.get(piece_cid).map(String::as_str) == Some(digest) {
+        return Ok(());
+    }
+    digests.insert(piece_cid.to_string(), digest.to_string());
+    let path = blob_digests_path();
+    ensure_parent_dir(&path)?;
+    let encoded = serde_json::to_string_pretty(&digests)
+        .map_err(|e| format!("Failed encoding blob digests: {e}"))?;
+    fs::write(&path, encoded)
+        .map_err(|e| format!("Failed writing blob digests ({}): {e}", path.display()))
+}
+
+fn read_blob_digests() -> HashMap<String, String> {
+    fs::read_to_string(blob_digests_path())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn blob_digests_path() -> PathBuf {
+    crate::auth::accounts::AccountRegistry::shared().scoped_path(BLOB_DIGESTS_FILE)
+}
+
+/// Reject bodies that cannot be an encrypted payload, such as the HTML or JSON error pages
+/// some gateways serve with a 200.
+pub(crate) fn check_encrypted_blob(bytes: &[u8]) -> Result<(), String> {
+    if is_stream_payload(bytes) {
+        return Ok(());
+    }
+    if bytes.len() < 13 {
+        return Err(format!(
+            "Encrypted payload too small ({} bytes).",
+            bytes.len()
+        ));
+    }
+    let head = &bytes[..bytes.len().min(512)];
+    let first = head.iter().find(|b| !b.is_ascii_whitespace());
+    if matches!(first, Some(b'<' | b'{')) && std::str::from_utf8(head).is_ok() {
+        return Err("Gateway returned a text page instead of an encrypted payload.".to_string());
+    }
+    Ok(())
 }
 
 pub(crate) fn parse_content_blob(blob: &[u8]) -> Result<ParsedContentBlob, String> {
@@ -169,13 +286,29 @@ pub(crate) fn parse_content_blob_raw(blob: &[u8]) -> Result<ParsedContentBlob, S
         .first()
         .ok_or("Missing algorithm byte")?;
 
-    let iv_len = *take(blob, &mut offset, 1, "iv length byte")?
-        .first()
-        .ok_or("Missing iv length byte")? as usize;
-    let iv = take(blob, &mut offset, iv_len, "iv")?.to_vec();
+    let (iv, audio) = match algo {
+        ALGO_AES_GCM_256 => {
+            let iv_len = *take(blob, &mut offset, 1, "iv length byte")?
+                .first()
+                .ok_or("Missing iv length byte")? as usize;
+            let iv = take(blob, &mut offset, iv_len, "iv")?.to_vec();
 
-    let audio_len = take_u32(blob, &mut offset, "audio length")?;
-    let audio = take(blob, &mut offset, audio_len, "encrypted audio")?.to_vec();
+            let audio_len = take_u32(blob, &mut offset, "audio length")?;
+            let audio = take(blob, &mut offset, audio_len, "encrypted audio")?.to_vec();
+            (iv, audio)
+        }
+        // v2: a u64 length, then a self-describing chunked payload (header + sealed chunks).
+        ALGO_AES_GCM_256_STREAM => {
+            let mut len = [0u8; 8];
+            len.copy_from_slice(take(blob, &mut offset, 8, "payload length")?);
+            let payload_len = usize::try_from(u64::from_be_bytes(len))
+                .map_err(|_| "Malformed content blob: payload length overflows".to_string())?;
+            let payload = take(blob, &mut offset, payload_len, "encrypted payload")?;
+            StreamHeader::parse(&payload[..payload.len().min(STREAM_HEADER_LEN)])?;
+            (Vec::new(), payload.to_vec())
+        }
+        other => return Err(format!("Unsupported content blob algorithm: {other}")),
+    };
     if offset != blob.len() {
         return Err(format!(
             "Malformed content blob: trailing bytes detected ({})",
@@ -197,11 +330,10 @@ pub(crate) fn parse_content_blob_raw(blob: &[u8]) -> Result<ParsedContentBlob, S
     })
 }
 
-pub(crate) fn shared_audio_cache_dir() -> PathBuf {
-    dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("heaven-gpui")
-        .join("shared-audio-cache")
+/// Where shared audio was decrypted before the managed content cache; files found here are
+/// adopted into it.
+pub(crate) fn legacy_shared_audio_dir() -> PathBuf {
+    crate::auth::accounts::AccountRegistry::shared().scoped_path("shared-audio-cache")
 }
 
 pub(crate) fn sanitize_shared_file_stem(input: &str) -> String {
@@ -224,24 +356,24 @@ pub(crate) fn sanitize_shared_file_stem(input: &str) -> String {
     }
 }
 
-pub(crate) fn shared_audio_cache_path(
+pub(crate) fn shared_audio_file_name(
     content_id_hex: &str,
     file_stem_hint: &str,
     ext: &str,
-) -> PathBuf {
+) -> String {
     let normalized = normalize_content_id_hex(content_id_hex)
         .unwrap_or_else(|_| content_id_hex.trim().to_string());
     let id = normalized.trim_start_matches("0x");
     let short = &id[..id.len().min(8)];
     let stem = sanitize_shared_file_stem(file_stem_hint);
-    shared_audio_cache_dir().join(format!("{stem}-{short}.{ext}"))
+    format!("{stem}-{short}.{ext}")
 }
 
-pub(crate) fn find_cached_shared_audio_path(content_id_hex: &str) -> Option<PathBuf> {
+pub(crate) fn find_legacy_shared_audio_path(content_id_hex: &str) -> Option<PathBuf> {
     let normalized = normalize_content_id_hex(content_id_hex).ok()?;
     let id = normalized.trim_start_matches("0x");
     let short = &id[..id.len().min(8)];
-    let cache_dir = shared_audio_cache_dir();
+    let cache_dir = legacy_shared_audio_dir();
 
     // Backward compatibility with older cache naming (`<contentId>.<ext>`).
     for ext in ["mp3", "m4a", "aac", "flac", "wav", "ogg", "opus", "bin"] {
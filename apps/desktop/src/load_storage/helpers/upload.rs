@@ -1,4 +1,13 @@
 use super::*;
+use sha2::Sha384;
+use std::io::Write;
+
+/// Where the signature and the tags sit in a DataItem without target or anchor: signature
+/// type, signature, owner, the two presence flags, then tag count and tag byte length.
+pub(crate) const DATAITEM_SIGNATURE_OFFSET: usize = 2;
+const DATAITEM_OWNER_OFFSET: usize = DATAITEM_SIGNATURE_OFFSET + 65;
+const DATAITEM_FLAGS_OFFSET: usize = DATAITEM_OWNER_OFFSET + 65;
+const DATAITEM_TAGS_OFFSET: usize = DATAITEM_FLAGS_OFFSET + 2 + 8 + 8;
 
 pub(crate) fn convert_tags(tags: &[Value]) -> Vec<Tag> {
     let mut out = Vec::new();
@@ -21,11 +30,49 @@ pub(crate) fn convert_tags(tags: &[Value]) -> Vec<Tag> {
     out
 }
 
-pub(crate) fn upload_signed_dataitem(signed_dataitem: &[u8]) -> Result<UploadResult, String> {
+pub(crate) fn upload_signed_dataitem(signed_dataitem: &[u8]) -> LoadStorageResult<UploadResult> {
     let token = load_turbo_upload_token();
     let endpoint = format!("{}/v1/tx/{}", load_turbo_upload_url(), token);
+    let body = post_signed_dataitem(&endpoint, signed_dataitem, "Load")?;
+
+    let id = extract_upload_id(&body).ok_or("Upload succeeded but no dataitem id was returned")?;
+    let gateway_base = extract_gateway_base(&body).unwrap_or_else(load_gateway_url);
+
+    Ok(UploadResult {
+        id: id.clone(),
+        gateway_url: format!("{}/resolve/{}", gateway_base.trim_end_matches('/'), id),
+        winc: body.get("winc").and_then(Value::as_str).map(str::to_string),
+    })
+}
+
+/// Post the signed DataItem staged at `item_path` to Arweave Turbo, which bundles it onto
+/// Arweave, streaming it from disk. Returns its id.
+pub(crate) fn upload_staged_dataitem_to_arweave(item_path: &Path) -> LoadStorageResult<String> {
+    let endpoint = format!(
+        "{}/v1/tx/{}",
+        arweave_turbo_upload_url(),
+        arweave_turbo_token()
+    );
+    let item = fs::File::open(item_path).map_err(|e| {
+        format!(
+            "Failed reading staged archive ({}): {e}",
+            item_path.display()
+        )
+    })?;
+    let body = post_signed_dataitem(&endpoint, item, "Arweave")?;
+    extract_upload_id(&body).ok_or_else(|| {
+        LoadStorageError::Other(format!(
+            "Arweave upload succeeded but no dataitem id was returned: {body}"
+        ))
+    })
+}
 
-    let request = ureq::post(&endpoint)
+fn post_signed_dataitem(
+    endpoint: &str,
+    signed_dataitem: impl ureq::AsSendBody,
+    label: &str,
+) -> LoadStorageResult<Value> {
+    let request = ureq::post(endpoint)
         .header("Content-Type", "application/octet-stream")
         .config()
         .http_status_as_error(false)
@@ -33,7 +80,7 @@ pub(crate) fn upload_signed_dataitem(signed_dataitem: &[u8]) -> Result<UploadRes
 
     let mut resp = request
         .send(signed_dataitem)
-        .map_err(|e| format!("Load upload request failed: {e}"))?;
+        .map_err(|e| LoadStorageError::Network(format!("{label} upload request failed: {e}")))?;
 
     let status = resp.status().as_u16();
     let body = read_json_or_text(&mut resp);
@@ -43,18 +90,102 @@ pub(crate) fn upload_signed_dataitem(signed_dataitem: &[u8]) -> Result<UploadRes
             .get("error")
             .and_then(Value::as_str)
             .map(str::to_string)
-            .unwrap_or_else(|| format!("Load upload failed with status {status}"));
-        return Err(format!("{message}; endpoint={endpoint}"));
+            .unwrap_or_else(|| format!("{label} upload failed with status {status}"));
+        return Err(HttpError::Status(status, format!("{message}; endpoint={endpoint}")).into());
     }
+    Ok(body)
+}
 
-    let id = extract_upload_id(&body).ok_or("Upload succeeded but no dataitem id was returned")?;
-    let gateway_base = extract_gateway_base(&body).unwrap_or_else(load_gateway_url);
+/// Counts and sha384-hashes a DataItem's data on its way to `inner`.
+pub(crate) struct DataDigestWriter<W> {
+    inner: W,
+    digest: Sha384,
+    len: u64,
+}
 
-    Ok(UploadResult {
-        id: id.clone(),
-        gateway_url: format!("{}/resolve/{}", gateway_base.trim_end_matches('/'), id),
-        winc: body.get("winc").and_then(Value::as_str).map(str::to_string),
-    })
+impl<W: Write> DataDigestWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            digest: Sha384::new(),
+            len: 0,
+        }
+    }
+
+    /// The writer back, with the data length and its sha384.
+    pub(crate) fn finish(self) -> (W, u64, Vec<u8>) {
+        (self.inner, self.len, self.digest.finalize().to_vec())
+    }
+}
+
+impl<W: Write> Write for DataDigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.digest.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// ANS-104 signing message for a DataItem given its header (everything before the data)
+/// and the data's length and sha384. Data only enters the deep hash through its digest, so
+/// it can be signed without being held in memory.
+pub(crate) fn dataitem_signing_message(
+    header: &[u8],
+    data_len: u64,
+    data_sha384: &[u8],
+) -> Result<Vec<u8>, String> {
+    let malformed = || "Malformed dataitem header".to_string();
+    if header.len() < DATAITEM_TAGS_OFFSET {
+        return Err(malformed());
+    }
+    if header[DATAITEM_FLAGS_OFFSET..DATAITEM_FLAGS_OFFSET + 2] != [0, 0] {
+        return Err("Dataitem headers with a target or anchor are not supported".to_string());
+    }
+    let mut tags_len = [0u8; 8];
+    tags_len.copy_from_slice(&header[DATAITEM_TAGS_OFFSET - 8..DATAITEM_TAGS_OFFSET]);
+    let tags_end = usize::try_from(u64::from_le_bytes(tags_len))
+        .ok()
+        .and_then(|len| DATAITEM_TAGS_OFFSET.checked_add(len))
+        .filter(|end| *end == header.len())
+        .ok_or_else(malformed)?;
+    let signature_type = u16::from_le_bytes([header[0], header[1]]).to_string();
+
+    let fields = [
+        deep_hash_blob(b"dataitem"),
+        deep_hash_blob(b"1"),
+        deep_hash_blob(signature_type.as_bytes()),
+        deep_hash_blob(&header[DATAITEM_OWNER_OFFSET..DATAITEM_FLAGS_OFFSET]),
+        deep_hash_blob(b""),
+        deep_hash_blob(b""),
+        deep_hash_blob(&header[DATAITEM_TAGS_OFFSET..tags_end]),
+        deep_hash_blob_digest(data_len, data_sha384),
+    ];
+    let mut acc = Sha384::digest(format!("list{}", fields.len()).as_bytes()).to_vec();
+    for field in fields {
+        acc = Sha384::new()
+            .chain_update(&acc)
+            .chain_update(field)
+            .finalize()
+            .to_vec();
+    }
+    Ok(acc)
+}
+
+fn deep_hash_blob(bytes: &[u8]) -> Vec<u8> {
+    deep_hash_blob_digest(bytes.len() as u64, &Sha384::digest(bytes))
+}
+
+fn deep_hash_blob_digest(len: u64, sha384: &[u8]) -> Vec<u8> {
+    Sha384::new()
+        .chain_update(Sha384::digest(format!("blob{len}").as_bytes()))
+        .chain_update(sha384)
+        .finalize()
+        .to_vec()
 }
 
 pub(crate) fn extract_gateway_base(payload: &Value) -> Option<String> {
@@ -157,3 +288,36 @@ pub(crate) fn extract_upload_id(payload: &Value) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_signing_message_matches_the_whole_dataitem() {
+        let data = (0..5000u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let build = |data: Vec<u8>| {
+            let tags = vec![
+                Tag::new("Content-Type", "application/octet-stream"),
+                Tag::new("App-Name", "Heaven"),
+            ];
+            let mut item = DataItem::new(None, None, tags, data).unwrap();
+            item.signature_type = SignatureType::Ethereum;
+            item.owner = vec![4u8; 65];
+            item.signature = vec![0u8; 65];
+            item
+        };
+        let whole = build(data.clone());
+        let header = build(Vec::new()).to_bytes().unwrap();
+
+        let mut writer = DataDigestWriter::new(Vec::new());
+        writer.write_all(&data).unwrap();
+        let (written, data_len, data_sha384) = writer.finish();
+        assert_eq!(written, data);
+        assert_eq!(
+            dataitem_signing_message(&header, data_len, &data_sha384).unwrap(),
+            whole.signing_message().to_vec()
+        );
+        assert_eq!(whole.to_bytes().unwrap(), [header, data].concat());
+    }
+}
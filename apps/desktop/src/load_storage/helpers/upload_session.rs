@@ -0,0 +1,231 @@
+use super::*;
+use crate::auth::accounts::AccountRegistry;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+const UPLOAD_SESSIONS_DIR: &str = "upload_sessions";
+/// Sessions nobody resumed within a week are dropped; Turbo expires the upload id well before.
+const UPLOAD_SESSION_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Journal entry for one staged upload. The signed DataItem sits next to it on disk, so a
+/// resume sends the exact bytes that were signed instead of re-encrypting the track.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UploadSession {
+    pub(crate) content_id: String,
+    pub(crate) source_path: String,
+    pub(crate) source_len: u64,
+    pub(crate) source_modified_sec: u64,
+    /// Encrypted payload size, before the DataItem envelope.
+    pub(crate) blob_len: u64,
+    pub(crate) item_len: u64,
+    /// Turbo chunked-upload id, once the server has handed one out.
+    pub(crate) upload_id: Option<String>,
+    pub(crate) chunk_size: u64,
+    /// Bytes of the DataItem the server has acknowledged, from the start.
+    pub(crate) confirmed_bytes: u64,
+    /// Content key wrapped to our content key. It goes into the wrapped key store only once
+    /// the upload succeeds, so a failed upload never replaces the key of a blob that exists.
+    #[serde(default)]
+    pub(crate) wrapped_key: Option<StoredEnvelope>,
+    pub(crate) created_at: u64,
+    pub(crate) updated_at: u64,
+}
+
+/// On-disk upload session journal; one `<content id>.json` + `<content id>.item` per upload.
+pub(crate) struct UploadJournal {
+    dir: PathBuf,
+}
+
+impl UploadJournal {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Journal for the active account.
+    pub(crate) fn open_default() -> Self {
+        Self::new(AccountRegistry::shared().scoped_path(UPLOAD_SESSIONS_DIR))
+    }
+
+    fn session_path(&self, content_id: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.json", normalize_content_key(content_id)))
+    }
+
+    pub(crate) fn item_path(&self, content_id: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.item", normalize_content_key(content_id)))
+    }
+
+    /// The session for `content_id` if it can still be resumed for `source_path`. Sessions
+    /// whose source file changed or whose staged item is damaged are discarded.
+    pub(crate) fn resumable(&self, content_id: &str, source_path: &str) -> Option<UploadSession> {
+        let text = fs::read_to_string(self.session_path(content_id)).ok()?;
+        let Ok(session) = serde_json::from_str::<UploadSession>(&text) else {
+            self.discard(content_id);
+            return None;
+        };
+        let staged_len = fs::metadata(self.item_path(content_id))
+            .map(|meta| meta.len())
+            .ok();
+        let fresh = now_sec().saturating_sub(session.updated_at) <= UPLOAD_SESSION_MAX_AGE_SECS;
+        if !fresh
+            || staged_len != Some(session.item_len)
+            || session.source_path != source_path
+            || source_fingerprint(source_path).ok()
+                != Some((session.source_len, session.source_modified_sec))
+        {
+            log::info!(
+                "[LoadStorage] discarding stale upload session: contentId={} path={}",
+                session.content_id,
+                session.source_path
+            );
+            self.discard(content_id);
+            return None;
+        }
+        Some(session)
+    }
+
+    /// Scratch file a new DataItem is written to, until [`Self::create`] adopts it.
+    pub(crate) fn staging_path(&self, content_id: &str) -> Result<PathBuf, String> {
+        self.prune_stale();
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            format!(
+                "Failed creating upload session dir ({}): {e}",
+                self.dir.display()
+            )
+        })?;
+        Ok(self.staged_item_path(content_id))
+    }
+
+    fn staged_item_path(&self, content_id: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.item.tmp", normalize_content_key(content_id)))
+    }
+
+    /// Adopt the signed DataItem written (and synced) to [`Self::staging_path`] and start
+    /// its journal entry.
+    pub(crate) fn create(
+        &self,
+        content_id: &str,
+        source_path: &str,
+        blob_len: u64,
+        wrapped_key: Option<&EciesEnvelope>,
+    ) -> Result<UploadSession, String> {
+        let (source_len, source_modified_sec) = source_fingerprint(source_path)?;
+        let staged = self.staged_item_path(content_id);
+        let item_path = self.item_path(content_id);
+        let item_len = fs::metadata(&staged)
+            .and_then(|meta| fs::rename(&staged, &item_path).map(|_| meta.len()))
+            .map_err(|e| {
+                let _ = fs::remove_file(&staged);
+                format!("Failed staging upload ({}): {e}", item_path.display())
+            })?;
+
+        let now = now_sec();
+        let session = UploadSession {
+            content_id: normalize_content_key(content_id),
+            source_path: source_path.to_string(),
+            source_len,
+            source_modified_sec,
+            blob_len,
+            item_len,
+            upload_id: None,
+            chunk_size: 0,
+            confirmed_bytes: 0,
+            wrapped_key: wrapped_key.map(StoredEnvelope::from),
+            created_at: now,
+            updated_at: now,
+        };
+        self.save(&session)?;
+        Ok(session)
+    }
+
+    pub(crate) fn save(&self, session: &UploadSession) -> Result<(), String> {
+        let mut session = session.clone();
+        session.updated_at = now_sec();
+        let encoded = serde_json::to_vec_pretty(&session)
+            .map_err(|e| format!("Failed encoding upload session: {e}"))?;
+        write_atomic(&self.session_path(&session.content_id), &encoded)
+    }
+
+    pub(crate) fn discard(&self, content_id: &str) {
+        let _ = fs::remove_file(self.session_path(content_id));
+        let _ = fs::remove_file(self.item_path(content_id));
+        let _ = fs::remove_file(self.staged_item_path(content_id));
+    }
+
+    /// Drop every staged upload of `source_path`, e.g. after the user cancels a paused upload.
+    pub(crate) fn discard_for_source(&self, source_path: &str) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let matches = fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<UploadSession>(&text).ok())
+                .is_some_and(|session| session.source_path == source_path);
+            if matches {
+                let _ = fs::remove_file(path.with_extension("item"));
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn prune_stale(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let now = now_sec();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let stale = fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<UploadSession>(&text).ok())
+                .map(|session| now.saturating_sub(session.updated_at) > UPLOAD_SESSION_MAX_AGE_SECS)
+                .unwrap_or(true);
+            if stale {
+                let _ = fs::remove_file(path.with_extension("item"));
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+fn source_fingerprint(source_path: &str) -> Result<(u64, u64), String> {
+    let meta = fs::metadata(source_path)
+        .map_err(|e| format!("Failed reading upload source ({source_path}): {e}"))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|age| age.as_secs())
+        .unwrap_or(0);
+    Ok((meta.len(), modified))
+}
+
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(err) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed writing {}: {err}", path.display()));
+    }
+    Ok(())
+}
+
+fn now_sec() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
@@ -1,4 +1,5 @@
 use super::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default)]
 pub struct TrackMetaInput {
@@ -26,6 +27,369 @@ pub struct PlaylistTrackInput {
     pub cover_image: Option<PlaylistCoverImageInput>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadPhase {
+    /// Encrypting and signing, or reattaching to a journaled upload.
+    Preparing,
+    Uploading,
+    /// All bytes sent; waiting for the server to assemble and verify the DataItem.
+    Finalizing,
+}
+
+/// Byte-level progress of a Load upload. `sent_bytes` already counts bytes acknowledged
+/// before a resume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub phase: UploadPhase,
+    pub sent_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// What an upload should do after reporting progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadControl {
+    Continue,
+    /// Stop after the current chunk and keep the session journal for a later resume.
+    Pause,
+    /// Stop after the current chunk and drop the staged upload.
+    Cancel,
+}
+
+/// Pre-upload checks for a payload of a given size.
+#[derive(Debug, Clone, Default)]
+pub struct UploadReadiness {
+    pub ready: bool,
+    pub reason: Option<LoadStorageError>,
+    /// Credits the upload should cost: zero on the free tier or when uploads are not
+    /// user-paid, `None` when the price lookup failed.
+    pub estimated_credit: Option<f64>,
+}
+
+/// Load endpoints and funding settings the service is configured with.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageHealth {
+    pub ok: bool,
+    pub upload_mode: &'static str,
+    pub upload_url: String,
+    pub upload_token: String,
+    pub gateway_url: String,
+    pub turbo_funding_enabled: bool,
+    pub turbo_funding_proxy_url: String,
+    pub turbo_funding_token: String,
+    pub base_sepolia_rpc_url: String,
+}
+
+/// Turbo balance and upload readiness for the signed-in wallet.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageAccountStatus {
+    /// Balance formatted for display; `"0"` when it could not be read.
+    pub balance: String,
+    pub balance_credits: Option<f64>,
+    /// Proxy balance payload as returned, for the Settings diagnostics view.
+    pub balance_raw: Option<Value>,
+    /// Not reported by Load yet.
+    pub monthly_cost: Option<String>,
+    /// Not reported by Load yet.
+    pub days_remaining: Option<i64>,
+    pub ready: bool,
+    pub endpoint_healthy: bool,
+    pub account_info_error: Option<String>,
+    pub storage_info_error: Option<String>,
+    pub upload_mode: &'static str,
+    pub endpoint: String,
+    pub status: Option<u16>,
+    pub gateway_url: String,
+    pub turbo_funding_enabled: bool,
+    pub free_upload_limit_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoragePreflight {
+    pub ready: bool,
+    pub reason: Option<String>,
+    pub estimated_credit: Option<f64>,
+    pub upload_mode: &'static str,
+    pub turbo_funding_enabled: bool,
+}
+
+/// Outcome of Add Credits. Offchain mode has no deposit step, so `funding_enabled` is
+/// false and `message` explains why.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingOutcome {
+    pub funding_enabled: bool,
+    pub tx_hash: Option<String>,
+    pub message: String,
+}
+
+/// Where an uploaded track's encrypted blob lives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredContent {
+    pub track_id: String,
+    pub content_id: String,
+    pub piece_cid: String,
+    pub gateway_url: String,
+    pub register_version: String,
+    pub tx_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentUpload {
+    #[serde(flatten)]
+    pub content: RegisteredContent,
+    pub ip_id: Option<String>,
+    pub blob_size: u64,
+    pub winc: Option<String>,
+    /// Re-encrypted over an earlier upload of the same track.
+    pub replaced: bool,
+    pub metadata_registered: bool,
+}
+
+impl From<ContentUpload> for RegisteredContent {
+    fn from(upload: ContentUpload) -> Self {
+        upload.content
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentGrant {
+    pub content_id: String,
+    pub grantee: String,
+    pub envelope_id: String,
+    pub gateway_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentGrantBatch {
+    pub grantee: String,
+    pub content_ids: Vec<String>,
+    pub envelope_ids: Vec<String>,
+}
+
+/// First half of a revoke: the re-encrypted blob is on Load, but the grantees who keep
+/// access have no envelope for its key yet. Saved between attempts so a half-published
+/// revoke can be resumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRevocation {
+    #[serde(flatten)]
+    pub upload: ContentUpload,
+    pub revoked_grantees: Vec<String>,
+    pub remaining_grantees: Vec<String>,
+    pub superseded_envelope_ids: Vec<String>,
+    /// Envelopes already published for the new key, one per leading entry of
+    /// `remaining_grantees`.
+    pub envelope_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRevocation {
+    /// The re-encrypted upload that replaced the revoked blob.
+    #[serde(flatten)]
+    pub upload: ContentUpload,
+    pub revoked_grantees: Vec<String>,
+    pub remaining_grantees: Vec<String>,
+    pub envelope_ids: Vec<String>,
+    pub superseded_envelope_ids: Vec<String>,
+    pub supersede_record_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedContentFile {
+    pub content_id: String,
+    pub piece_cid: String,
+    pub local_path: PathBuf,
+    /// Bytes written; `None` when an earlier decrypt was reused.
+    pub bytes: Option<u64>,
+    pub cache_hit: bool,
+    pub fetched_from: Option<String>,
+    pub decrypt_chain: Option<&'static str>,
+}
+
+/// A Load blob staged on disk for anchoring on Arweave, priced but not yet uploaded.
+#[derive(Debug)]
+pub struct ArweaveArchivePlan {
+    pub piece_cid: String,
+    pub bytes: u64,
+    pub sha256: String,
+    /// Arweave Turbo credits the upload should cost; zero under the free limit.
+    pub estimated_credit: f64,
+    pub(super) staged_path: PathBuf,
+}
+
+impl Drop for ArweaveArchivePlan {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.staged_path);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArweaveArchive {
+    pub piece_cid: String,
+    pub arweave_id: String,
+    /// Digest of the archived bytes, which the gateways must serve back.
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// What the registry, Load's tag index and the gateway say about one uploaded content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteContentState {
+    /// Newest pieceCid the tag index or the registry holds; `None` when neither knows it.
+    pub piece_cid: Option<String>,
+    /// When the index says `piece_cid` was posted; `None` when that is unknown.
+    pub piece_posted_at_ms: Option<i64>,
+    /// The registry marks the content inactive: its owner deleted it.
+    pub deactivated: bool,
+    /// Whether the Load gateway serves the checked pieceCid; `None` when the probe could
+    /// not tell.
+    pub blob_served: Option<bool>,
+}
+
+/// An access grant as indexed from registry events; revoked grants have `granted: false`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteAccessGrant {
+    pub owner_address: String,
+    pub grantee_address: String,
+    pub content_id: String,
+    pub piece_cid: String,
+    pub track_id: Option<String>,
+    pub granted: bool,
+    pub updated_at_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackMetadata {
+    pub track_id: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// `subgraph`, `onchain`, or `track-id` when only the id is known.
+    pub source: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaylistSummary {
+    pub id: String,
+    pub name: String,
+    pub cover_cid: Option<String>,
+    pub visibility: u8,
+    pub track_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistAction {
+    pub operation: &'static str,
+    pub tx_hash: String,
+    /// `None` when a create receipt did not carry the new id yet.
+    pub playlist_id: Option<String>,
+    pub cover_cid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistShareOutcome {
+    pub operation: &'static str,
+    pub tx_hash: String,
+    pub playlist_id: String,
+    pub grantee: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentKeyStatus {
+    pub public_key: String,
+    pub fingerprint: String,
+    /// This device receives keys from another device of the account.
+    pub secondary: bool,
+    pub cached_keys: usize,
+    pub linked_devices: Vec<LinkedDeviceSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedDeviceSummary {
+    pub fingerprint: String,
+    pub public_key: String,
+    pub linked_at_ms: i64,
+    pub synced_keys: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentKeyBackup {
+    pub path: PathBuf,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentKeyRestore {
+    pub public_key: String,
+    pub fingerprint: String,
+    /// A different key was active before the restore.
+    pub replaced_previous: bool,
+    /// Cached wrapped keys moved over from the replaced key.
+    pub rewrapped_keys: usize,
+    /// False when publishing the restored public key failed; the next upload retries it.
+    /// Linked devices never publish and report true.
+    pub published: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ContentKeyRestoreOutcome {
+    Restored(ContentKeyRestore),
+    /// The restored key differs from the one published for the account, so restoring it
+    /// would re-point new shares. Nothing was changed; restore again with confirmation.
+    NeedsConfirmation {
+        published_fingerprint: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceKeySync {
+    pub devices: usize,
+    pub envelope_ids: Vec<String>,
+    pub failed_content_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentCacheSettings {
+    pub max_bytes: u64,
+    /// Keep decrypted shared audio sealed on disk, opening only what is being played.
+    pub encrypt_at_rest: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentCacheUsage {
+    pub settings: ContentCacheSettings,
+    pub audio_files: usize,
+    pub audio_bytes: u64,
+    pub cover_files: usize,
+    pub cover_bytes: u64,
+}
+
+impl ContentCacheUsage {
+    pub fn total_bytes(&self) -> u64 {
+        self.audio_bytes + self.cover_bytes
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(super) struct LoadHealthResult {
     pub(super) ok: bool,
@@ -54,6 +418,7 @@ pub(super) struct ParsedContentBlob {
     pub(super) lit_ciphertext_base64: String,
     pub(super) data_to_encrypt_hash_hex: String,
     pub(super) algo: u8,
+    /// Empty for `ALGO_AES_GCM_256_STREAM`; v2 payloads carry their nonce prefix in-band.
     pub(super) iv: Vec<u8>,
     pub(super) encrypted_audio: Vec<u8>,
 }
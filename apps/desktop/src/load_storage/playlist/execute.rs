@@ -33,7 +33,7 @@ impl LoadStorageService {
         operation: &str,
         params: serde_json::Map<String, Value>,
         _has_inline_cover_upload: bool,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistAction> {
         let playlist_contract = playlist_v1();
 
         match operation {
@@ -57,6 +57,7 @@ impl LoadStorageService {
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::Playlist,
                     &playlist_contract,
                     call_data,
                     GAS_LIMIT_CREATE_MIN,
@@ -65,13 +66,12 @@ impl LoadStorageService {
 
                 let playlist_id = fetch_created_playlist_id_from_receipt(&tx_hash)?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "create",
-                    "txHash": tx_hash,
-                    "playlistId": playlist_id,
-                    "coverCid": cover_cid,
-                }))
+                Ok(PlaylistAction {
+                    operation: "create",
+                    tx_hash,
+                    playlist_id,
+                    cover_cid: non_empty(cover_cid),
+                })
             }
             "setTracks" => {
                 let playlist_id = required_param_bytes32(&params, "playlistId")?;
@@ -79,25 +79,25 @@ impl LoadStorageService {
 
                 let call_data = setTracksCall {
                     playlistId: playlist_id,
-                    trackIds: track_ids.clone(),
+                    trackIds: track_ids,
                 }
                 .abi_encode();
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::Playlist,
                     &playlist_contract,
                     call_data,
                     GAS_LIMIT_SET_TRACKS_MIN,
                     "playlist setTracks",
                 )?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "setTracks",
-                    "txHash": tx_hash,
-                    "playlistId": to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
-                    "trackCount": track_ids.len(),
-                }))
+                Ok(PlaylistAction {
+                    operation: "setTracks",
+                    tx_hash,
+                    playlist_id: Some(to_hex_prefixed(playlist_id.as_slice()).to_lowercase()),
+                    cover_cid: None,
+                })
             }
             "updateMeta" => {
                 let playlist_id = required_param_bytes32(&params, "playlistId")?;
@@ -119,20 +119,19 @@ impl LoadStorageService {
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::Playlist,
                     &playlist_contract,
                     call_data,
                     GAS_LIMIT_UPDATE_META_MIN,
                     "playlist updateMeta",
                 )?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "updateMeta",
-                    "txHash": tx_hash,
-                    "playlistId": to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
-                    "coverCid": cover_cid,
-                    "visibility": visibility,
-                }))
+                Ok(PlaylistAction {
+                    operation: "updateMeta",
+                    tx_hash,
+                    playlist_id: Some(to_hex_prefixed(playlist_id.as_slice()).to_lowercase()),
+                    cover_cid: non_empty(cover_cid),
+                })
             }
             "delete" => {
                 let playlist_id = required_param_bytes32(&params, "playlistId")?;
@@ -143,24 +142,32 @@ impl LoadStorageService {
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::Playlist,
                     &playlist_contract,
                     call_data,
                     GAS_LIMIT_DELETE_MIN,
                     "playlist delete",
                 )?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "delete",
-                    "txHash": tx_hash,
-                    "playlistId": to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
-                }))
+                Ok(PlaylistAction {
+                    operation: "delete",
+                    tx_hash,
+                    playlist_id: Some(to_hex_prefixed(playlist_id.as_slice()).to_lowercase()),
+                    cover_cid: None,
+                })
             }
-            other => Err(format!("Unsupported playlist operation: {other}")),
+            other => Err(LoadStorageError::Other(format!(
+                "Unsupported playlist operation: {other}"
+            ))),
         }
     }
 }
 
+fn non_empty(value: String) -> Option<String> {
+    let trimmed = value.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
 fn required_param_string(
     params: &serde_json::Map<String, Value>,
     key: &str,
@@ -340,7 +347,7 @@ fn maybe_upload_cover_from_params(
     Ok(Some(uploaded))
 }
 
-fn fetch_created_playlist_id_from_receipt(tx_hash: &str) -> Result<Option<String>, String> {
+fn fetch_created_playlist_id_from_receipt(tx_hash: &str) -> LoadStorageResult<Option<String>> {
     let tx_hash = normalize_bytes32_hex(tx_hash, "txHash")?;
     let payload = http_post_json(
         &tempo_rpc_url(),
@@ -353,7 +360,9 @@ fn fetch_created_playlist_id_from_receipt(tx_hash: &str) -> Result<Option<String
     )?;
 
     if let Some(err) = payload.get("error") {
-        return Err(format!("RPC eth_getTransactionReceipt error: {err}"));
+        return Err(LoadStorageError::Other(format!(
+            "RPC eth_getTransactionReceipt error: {err}"
+        )));
     }
 
     let Some(receipt) = payload.get("result") else {
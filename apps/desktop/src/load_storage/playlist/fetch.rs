@@ -5,7 +5,7 @@ impl LoadStorageService {
         &mut self,
         owner_address: &str,
         max_entries: usize,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<Vec<PlaylistSummary>> {
         let owner = owner_address
             .parse::<Address>()
             .map_err(|e| format!("Invalid owner address ({owner_address}): {e}"))?;
@@ -23,17 +23,17 @@ impl LoadStorageService {
         )?;
 
         Ok(payload
-            .get("data")
-            .and_then(|v| v.get("playlists"))
-            .cloned()
-            .unwrap_or_else(|| Value::Array(Vec::new())))
+            .pointer("/data/playlists")
+            .and_then(Value::as_array)
+            .map(|entries| entries.iter().filter_map(parse_playlist_summary).collect())
+            .unwrap_or_default())
     }
 
     pub fn playlist_fetch_track_ids(
         &mut self,
         playlist_id: &str,
         max_entries: usize,
-    ) -> Result<Vec<String>, String> {
+    ) -> LoadStorageResult<Vec<String>> {
         let playlist_id_norm = normalize_bytes32_hex(playlist_id, "playlistId")?;
         let limit = max_entries.clamp(1, 1000);
 
@@ -68,17 +68,17 @@ impl LoadStorageService {
         &mut self,
         playlist_id: &str,
         max_entries: usize,
-    ) -> Result<Vec<Value>, String> {
+    ) -> LoadStorageResult<Vec<TrackMetadata>> {
         let track_ids = self.playlist_fetch_track_ids(playlist_id, max_entries)?;
-        let mut out = Vec::<Value>::with_capacity(track_ids.len());
+        let mut out = Vec::<TrackMetadata>::with_capacity(track_ids.len());
 
-        for (position, track_id) in track_ids.into_iter().enumerate() {
+        for track_id in track_ids {
             let (title, artist, album, source) = if let Some((title, artist, album)) =
                 fetch_track_metadata_subgraph(&track_id)?
             {
-                (title, artist, album, "subgraph".to_string())
+                (title, artist, album, "subgraph")
             } else if let Some((title, artist, album)) = fetch_track_metadata_onchain(&track_id)? {
-                (title, artist, album, "onchain".to_string())
+                (title, artist, album, "onchain")
             } else {
                 let short = track_id.strip_prefix("0x").unwrap_or(track_id.as_str());
                 let short = &short[..short.len().min(10)];
@@ -86,20 +86,59 @@ impl LoadStorageService {
                     format!("Track {short}"),
                     "Unknown Artist".to_string(),
                     "Unknown Album".to_string(),
-                    "track-id".to_string(),
+                    "track-id",
                 )
             };
 
-            out.push(json!({
-                "trackId": track_id,
-                "position": position,
-                "title": title,
-                "artist": artist,
-                "album": album,
-                "source": source,
-            }));
+            out.push(TrackMetadata {
+                track_id,
+                title,
+                artist,
+                album,
+                source,
+            });
         }
 
         Ok(out)
     }
 }
+
+fn parse_playlist_summary(entry: &Value) -> Option<PlaylistSummary> {
+    let id = entry
+        .get("id")
+        .and_then(Value::as_str)?
+        .trim()
+        .to_lowercase();
+    if id.is_empty() {
+        return None;
+    }
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or("Untitled Playlist")
+        .to_string();
+    let cover_cid = entry
+        .get("coverCid")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+    Some(PlaylistSummary {
+        id,
+        name,
+        cover_cid,
+        visibility: parse_count_field(entry.get("visibility")).min(255) as u8,
+        track_count: parse_count_field(entry.get("trackCount")),
+    })
+}
+
+/// The subgraph returns BigInt fields as strings.
+fn parse_count_field(value: Option<&Value>) -> usize {
+    match value {
+        Some(Value::Number(n)) => n.as_u64().unwrap_or_default() as usize,
+        Some(Value::String(raw)) => raw.trim().parse::<u64>().unwrap_or_default() as usize,
+        _ => 0,
+    }
+}
@@ -4,7 +4,7 @@ impl LoadStorageService {
     pub fn playlist_track_id_from_input(
         &self,
         track: &PlaylistTrackInput,
-    ) -> Result<String, String> {
+    ) -> LoadStorageResult<String> {
         let title = track.title.trim();
         let artist = track.artist.trim();
         let album = track.album.as_deref().unwrap_or("").trim();
@@ -29,11 +29,13 @@ impl LoadStorageService {
         cover_cid: Option<&str>,
         visibility: u8,
         tracks: &[PlaylistTrackInput],
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistAction> {
         let mut params = serde_json::Map::new();
         let trimmed_name = name.trim();
         if trimmed_name.is_empty() {
-            return Err("Playlist name is required".to_string());
+            return Err(LoadStorageError::Other(
+                "Playlist name is required".to_string(),
+            ));
         }
         params.insert("name".to_string(), Value::String(trimmed_name.to_string()));
         params.insert(
@@ -61,7 +63,7 @@ impl LoadStorageService {
         playlist_id: &str,
         tracks: &[PlaylistTrackInput],
         existing_track_ids: Option<&[String]>,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistAction> {
         let mut params = serde_json::Map::new();
         params.insert(
             "playlistId".to_string(),
@@ -97,11 +99,13 @@ impl LoadStorageService {
         cover_cid: Option<&str>,
         visibility: u8,
         cover_image: Option<&PlaylistCoverImageInput>,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistAction> {
         let mut params = serde_json::Map::new();
         let trimmed_name = name.trim();
         if trimmed_name.is_empty() {
-            return Err("Playlist name is required".to_string());
+            return Err(LoadStorageError::Other(
+                "Playlist name is required".to_string(),
+            ));
         }
 
         params.insert(
@@ -134,7 +138,7 @@ impl LoadStorageService {
         &mut self,
         auth: &PersistedAuth,
         playlist_id: &str,
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistAction> {
         let mut params = serde_json::Map::new();
         params.insert(
             "playlistId".to_string(),
@@ -150,7 +154,7 @@ impl LoadStorageService {
         auth: &PersistedAuth,
         cover_image: &PlaylistCoverImageInput,
         file_path: Option<&str>,
-    ) -> Result<String, String> {
+    ) -> LoadStorageResult<String> {
         let mut temp_file: Option<PathBuf> = None;
         let upload_path = match file_path
             .map(str::trim)
@@ -161,7 +165,9 @@ impl LoadStorageService {
             _ => {
                 let bytes = decode_cover_base64(cover_image.base64.as_str())?;
                 if bytes.is_empty() {
-                    return Err("Playlist cover image payload is empty".to_string());
+                    return Err(LoadStorageError::Other(
+                        "Playlist cover image payload is empty".to_string(),
+                    ));
                 }
 
                 let ext = extension_for_content_type(cover_image.content_type.as_str());
@@ -186,7 +192,7 @@ impl LoadStorageService {
             let _ = fs::remove_file(path);
         }
 
-        upload_result
+        upload_result.map_err(LoadStorageError::from)
     }
 }
 
@@ -16,7 +16,7 @@ impl LoadStorageService {
         playlist_id_hex: &str,
         grantee_address: &str,
         operation: &str, // "share" | "unshare"
-    ) -> Result<Value, String> {
+    ) -> LoadStorageResult<PlaylistShareOutcome> {
         let playlist_id = B256::from(decode_bytes32_hex(playlist_id_hex, "playlistId")?);
         let grantee = grantee_address
             .trim()
@@ -34,19 +34,19 @@ impl LoadStorageService {
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::PlaylistShare,
                     &share_contract,
                     call_data,
                     GAS_LIMIT_SHARE_MIN,
                     "playlist share",
                 )?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "share",
-                    "txHash": tx_hash,
-                    "playlistId": to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
-                    "grantee": to_hex_prefixed(grantee.as_slice()).to_lowercase(),
-                }))
+                Ok(PlaylistShareOutcome {
+                    operation: "share",
+                    tx_hash,
+                    playlist_id: to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
+                    grantee: to_hex_prefixed(grantee.as_slice()).to_lowercase(),
+                })
             }
             "unshare" => {
                 let call_data = unsharePlaylistCall {
@@ -57,21 +57,23 @@ impl LoadStorageService {
 
                 let tx_hash = crate::scrobble::submit_tempo_contract_call(
                     auth,
+                    crate::tempo::TxKind::PlaylistShare,
                     &share_contract,
                     call_data,
                     GAS_LIMIT_UNSHARE_MIN,
                     "playlist unshare",
                 )?;
 
-                Ok(json!({
-                    "success": true,
-                    "operation": "unshare",
-                    "txHash": tx_hash,
-                    "playlistId": to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
-                    "grantee": to_hex_prefixed(grantee.as_slice()).to_lowercase(),
-                }))
+                Ok(PlaylistShareOutcome {
+                    operation: "unshare",
+                    tx_hash,
+                    playlist_id: to_hex_prefixed(playlist_id.as_slice()).to_lowercase(),
+                    grantee: to_hex_prefixed(grantee.as_slice()).to_lowercase(),
+                })
             }
-            other => Err(format!("Unsupported playlist share operation: {other}")),
+            other => Err(LoadStorageError::Other(format!(
+                "Unsupported playlist share operation: {other}"
+            ))),
         }
     }
 }
@@ -1,39 +1,49 @@
 use super::*;
 use ethers::signers::{LocalWallet, Signer};
+use std::io::{Seek, SeekFrom, Write};
 use std::str::FromStr;
 
-fn load_tempo_session_wallet(auth: &PersistedAuth) -> Result<LocalWallet, String> {
-    let session_private_key = auth
-        .tempo_session_private_key
-        .as_deref()
-        .ok_or("Missing Tempo session private key in auth")?;
+fn load_tempo_session_wallet(auth: &PersistedAuth) -> LoadStorageResult<LocalWallet> {
+    let session_private_key = auth.tempo_session_private_key.as_deref().ok_or_else(|| {
+        LoadStorageError::Auth("Missing Tempo session private key in auth".to_string())
+    })?;
     let session_wallet = LocalWallet::from_str(session_private_key).map_err(|e| {
-        format!("Invalid Tempo session private key in auth (cannot parse wallet): {e}")
+        LoadStorageError::Auth(format!(
+            "Invalid Tempo session private key in auth (cannot parse wallet): {e}"
+        ))
     })?;
     if let Some(expires_at) = auth.tempo_session_expires_at {
         let now = chrono::Utc::now().timestamp() as u64;
         if now >= expires_at {
-            return Err(
+            return Err(LoadStorageError::Auth(
                 "Tempo session key has expired. Sign in again to refresh the web auth session."
                     .to_string(),
-            );
+            ));
         }
     }
     if let Some(session_address) = auth.tempo_session_address.as_deref() {
         let expected = session_address
             .trim()
             .parse::<ethers::types::Address>()
-            .map_err(|e| format!("Invalid Tempo session address in auth: {e}"))?;
+            .map_err(|e| {
+                LoadStorageError::Auth(format!("Invalid Tempo session address in auth: {e}"))
+            })?;
         if session_wallet.address() != expected {
-            return Err(
+            return Err(LoadStorageError::Auth(
                 "Tempo session private key does not match the callback session address."
                     .to_string(),
-            );
+            ));
         }
     }
     Ok(session_wallet)
 }
 
+/// The session key to sign with, once any pending passkey re-auth has finished.
+fn signing_session_wallet(auth: &PersistedAuth) -> LoadStorageResult<LocalWallet> {
+    let auth = crate::auth::session::await_signing_auth(auth).map_err(LoadStorageError::Auth)?;
+    load_tempo_session_wallet(&auth)
+}
+
 fn tempo_session_owner_pubkey_uncompressed(
     session_wallet: &LocalWallet,
 ) -> Result<Vec<u8>, String> {
@@ -73,74 +83,98 @@ fn sign_dataitem_with_tempo_session(
 }
 
 impl LoadStorageService {
-    pub(super) fn ensure_upload_ready(
+    /// Size, health and balance checks for an upload of `size_bytes`, with the expected cost.
+    pub fn ensure_upload_ready(
         &mut self,
         auth: Option<&PersistedAuth>,
         size_bytes: Option<usize>,
-    ) -> (bool, Option<String>) {
+    ) -> UploadReadiness {
+        let blocked = |reason: LoadStorageError| UploadReadiness {
+            ready: false,
+            reason: Some(reason),
+            estimated_credit: None,
+        };
         if let Some(size) = size_bytes {
             if size > MAX_UPLOAD_BYTES {
-                return (
-                    false,
-                    Some(format!(
-                        "File exceeds current desktop upload limit ({} bytes)",
-                        MAX_UPLOAD_BYTES
-                    )),
-                );
+                return blocked(LoadStorageError::Other(format!(
+                    "File exceeds current desktop upload limit ({} bytes)",
+                    MAX_UPLOAD_BYTES
+                )));
             }
         }
 
         let health = self.load_health_check();
         if !health.ok {
-            return (false, health.reason);
+            let reason = health
+                .reason
+                .unwrap_or_else(|| format!("Load health check failed ({})", health.endpoint));
+            return blocked(match health.status {
+                Some(status) => HttpError::Status(status, reason).into(),
+                None => LoadStorageError::Network(reason),
+            });
         }
 
-        if load_user_pays_enabled() {
-            let auth = match auth {
-                Some(v) => v,
-                None => {
-                    return (
-                        false,
-                        Some(
-                            "Missing auth context required for Turbo user-pays balance checks"
-                                .to_string(),
-                        ),
-                    );
-                }
+        if !load_user_pays_enabled() {
+            return UploadReadiness {
+                ready: true,
+                reason: None,
+                estimated_credit: Some(0.0),
             };
-            if auth.provider_kind() == crate::auth::AuthProviderKind::TempoPasskey {
-                return (
-                    false,
-                    Some(
-                        "Turbo user-pays mode is not yet available for Tempo passkey sessions in GPUI. Disable HEAVEN_LOAD_USER_PAYS_ENABLED."
-                            .to_string(),
-                    ),
-                );
+        }
+
+        let auth = match auth {
+            Some(v) => v,
+            None => {
+                return blocked(LoadStorageError::Auth(
+                    "Missing auth context required for Turbo user-pays balance checks".to_string(),
+                ));
             }
-            match self.fetch_turbo_balance(auth) {
-                Ok(balance_payload) => {
-                    let parsed = extract_balance_hint(&balance_payload);
-                    let min_credit = min_upload_credit();
-                    let has_credit = parsed.map(|v| v >= min_credit).unwrap_or(false);
-                    if !has_credit {
-                        return (
-                            false,
-                            Some(format!(
-                                "Turbo credit is below minimum ({min_credit:.8}). Use Add Funds first."
-                            )),
-                        );
-                    }
-                }
+        };
+        if auth.provider_kind() == crate::auth::AuthProviderKind::TempoPasskey {
+            return blocked(LoadStorageError::Auth(
+                "Turbo user-pays mode is not yet available for Tempo passkey sessions in GPUI. Disable HEAVEN_LOAD_USER_PAYS_ENABLED."
+                    .to_string(),
+            ));
+        }
+
+        let free_limit = health.info.as_ref().and_then(free_upload_limit_bytes);
+        let estimated_credit = match size_bytes {
+            Some(size) if free_limit.is_some_and(|limit| size as u64 <= limit) => Some(0.0),
+            Some(size) => match self.fetch_turbo_upload_price(size) {
+                Ok(price) => Some(price),
                 Err(err) => {
-                    return (
-                        false,
-                        Some(format!("Turbo balance check failed before upload: {err}")),
-                    );
+                    log::warn!("[LoadStorage] Turbo price lookup failed: {err}");
+                    None
                 }
+            },
+            None => None,
+        };
+
+        match self.fetch_turbo_balance(auth) {
+            Ok(balance_payload) => {
+                let parsed = extract_balance_hint(&balance_payload);
+                let required = min_upload_credit().max(estimated_credit.unwrap_or(0.0));
+                let has_credit = parsed.map(|v| v >= required).unwrap_or(false);
+                if !has_credit {
+                    return UploadReadiness {
+                        ready: false,
+                        reason: Some(LoadStorageError::InsufficientCredit(format!(
+                            "Turbo credit is below minimum ({required:.8}) for this upload. Use Add Funds first."
+                        ))),
+                        estimated_credit,
+                    };
+                }
+            }
+            Err(err) => {
+                return blocked(err.context("Turbo balance check failed before upload"));
             }
         }
 
-        (true, None)
+        UploadReadiness {
+            ready: true,
+            reason: None,
+            estimated_credit,
+        }
     }
 
     pub(super) fn run_turbo_user_pays_funding(
@@ -157,45 +191,296 @@ impl LoadStorageService {
         payload: &[u8],
         file_path: Option<&str>,
         tags: Vec<Value>,
-    ) -> Result<UploadResult, String> {
+    ) -> LoadStorageResult<UploadResult> {
         let signed_dataitem = self.build_signed_dataitem(auth, payload, file_path, &tags)?;
         upload_signed_dataitem(&signed_dataitem)
     }
 
-    fn build_signed_dataitem(
+    /// Encrypt `file_path` under `content_id` and upload it to Load. The signed DataItem is
+    /// journaled first, so a retry after a failure or restart picks up where the previous
+    /// attempt stopped instead of re-encrypting. Returns the upload and the encrypted size.
+    pub(super) fn encrypt_and_upload_to_load(
+        &mut self,
+        auth: &PersistedAuth,
+        file_path: &str,
+        content_id: &B256,
+        tags: Vec<Value>,
+        progress: &dyn Fn(UploadProgress) -> UploadControl,
+    ) -> LoadStorageResult<(UploadResult, u64)> {
+        let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
+        let journal = UploadJournal::open_default();
+        let preparing = progress(UploadProgress {
+            phase: UploadPhase::Preparing,
+            sent_bytes: 0,
+            total_bytes: 0,
+        });
+        if preparing == UploadControl::Cancel {
+            journal.discard(&content_id_hex);
+        }
+        if preparing != UploadControl::Continue {
+            return Err(LoadStorageError::Other(
+                "Upload stopped before it started".to_string(),
+            ));
+        }
+        let mut session = match journal.resumable(&content_id_hex, file_path) {
+            Some(session) => {
+                self.require_upload_ready(auth, session.blob_len as usize)?;
+                session
+            }
+            None => self.stage_encrypted_dataitem(auth, file_path, content_id, &tags, &journal)?,
+        };
+
+        let result =
+            if session.upload_id.is_none() && session.item_len <= SINGLE_REQUEST_UPLOAD_MAX_BYTES {
+                let item_path = journal.item_path(&content_id_hex);
+                let signed_dataitem = fs::read(&item_path).map_err(|e| {
+                    format!(
+                        "Failed reading staged upload ({}): {e}",
+                        item_path.display()
+                    )
+                })?;
+                let result = upload_signed_dataitem(&signed_dataitem)?;
+                journal.discard(&content_id_hex);
+                // Already uploaded in one request, so there is nothing left to pause or cancel.
+                let _ = progress(UploadProgress {
+                    phase: UploadPhase::Uploading,
+                    sent_bytes: session.item_len,
+                    total_bytes: session.item_len,
+                });
+                result
+            } else {
+                upload_staged_dataitem(
+                    &ChunkedUploadEndpoint::from_env(),
+                    &journal,
+                    &mut session,
+                    progress,
+                )?
+            };
+        if let Some(wrapped_key) = session
+            .wrapped_key
+            .as_ref()
+            .and_then(StoredEnvelope::to_envelope)
+        {
+            save_wrapped_key_for_content(&content_id_hex, &wrapped_key)?;
+        }
+        Ok((result, session.blob_len))
+    }
+
+    /// Encrypt `file_path` into the journal's staging file behind a placeholder header, then
+    /// sign over the payload's digest and write the real header in its place. Neither the
+    /// plaintext nor the payload is ever held whole in memory.
+    fn stage_encrypted_dataitem(
+        &mut self,
+        auth: &PersistedAuth,
+        file_path: &str,
+        content_id: &B256,
+        tags: &[Value],
+        journal: &UploadJournal,
+    ) -> LoadStorageResult<UploadSession> {
+        let content_id_hex = to_hex_prefixed(content_id.as_slice()).to_lowercase();
+        let source_len = fs::metadata(file_path)
+            .map(|meta| meta.len())
+            .map_err(|e| format!("Failed to read file for upload ({}): {e}", file_path))?;
+        let expected_len = stream_payload_len(source_len, DEFAULT_STREAM_CHUNK_SIZE);
+        self.require_upload_ready(auth, usize::try_from(expected_len).unwrap_or(usize::MAX))?;
+
+        let staged_path = journal.staging_path(&content_id_hex)?;
+        match self.write_encrypted_dataitem(auth, file_path, tags, &staged_path) {
+            Ok((blob_len, wrapped_key)) => {
+                Ok(journal.create(&content_id_hex, file_path, blob_len, Some(&wrapped_key))?)
+            }
+            Err(err) => {
+                let _ = fs::remove_file(&staged_path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Write the signed DataItem for `file_path` to `staged_path` and return the encrypted
+    /// payload size with the wrapped content key.
+    fn write_encrypted_dataitem(
+        &mut self,
+        auth: &PersistedAuth,
+        file_path: &str,
+        tags: &[Value],
+        staged_path: &Path,
+    ) -> LoadStorageResult<(u64, EciesEnvelope)> {
+        let source = fs::File::open(file_path)
+            .map(std::io::BufReader::new)
+            .map_err(|e| format!("Failed to read file for upload ({}): {e}", file_path))?;
+        self.write_staged_dataitem(
+            auth,
+            Some(&format!("{file_path}.enc")),
+            tags,
+            staged_path,
+            |svc, writer| svc.encrypt_for_upload(auth, source, writer),
+        )
+    }
+
+    /// Write `source` to `staged_path` as a signed DataItem, copying it through rather than
+    /// holding it in memory.
+    pub(super) fn stage_signed_dataitem(
+        &mut self,
+        auth: &PersistedAuth,
+        source: &Path,
+        tags: &[Value],
+        staged_path: &Path,
+    ) -> LoadStorageResult<()> {
+        let mut source = fs::File::open(source)
+            .map(std::io::BufReader::new)
+            .map_err(|e| format!("Failed to read {} for upload: {e}", source.display()))?;
+        self.write_staged_dataitem(auth, None, tags, staged_path, |_, writer| {
+            std::io::copy(&mut source, writer).map(|_| ()).map_err(|e| {
+                LoadStorageError::Other(format!(
+                    "Failed writing staged upload ({}): {e}",
+                    staged_path.display()
+                ))
+            })
+        })
+    }
+
+    /// Write a DataItem to `staged_path` behind a placeholder header, with `write_data`
+    /// producing its data, then sign over the data's digest and write the real header in its
+    /// place.
+    fn write_staged_dataitem<T>(
         &mut self,
         auth: &PersistedAuth,
-        payload: &[u8],
         file_path: Option<&str>,
         tags: &[Value],
-    ) -> Result<Vec<u8>, String> {
-        let mut ans_tags = convert_tags(tags);
-        if !ans_tags
-            .iter()
-            .any(|tag| tag.name.eq_ignore_ascii_case("Content-Type"))
-        {
-            ans_tags.insert(0, Tag::new("Content-Type", infer_content_type(file_path)));
+        staged_path: &Path,
+        write_data: impl FnOnce(
+            &mut Self,
+            &mut DataDigestWriter<std::io::BufWriter<fs::File>>,
+        ) -> LoadStorageResult<T>,
+    ) -> LoadStorageResult<T> {
+        let write_err = |e: std::io::Error| {
+            format!(
+                "Failed writing staged upload ({}): {e}",
+                staged_path.display()
+            )
+        };
+        // One session key both owns and signs the item, even if the session renews meanwhile.
+        let session_wallet = signing_session_wallet(auth)?;
+        let mut header = self.unsigned_dataitem_header(&session_wallet, file_path, tags)?;
+        let mut file = fs::File::create(staged_path).map_err(write_err)?;
+        file.write_all(&header).map_err(write_err)?;
+
+        let mut writer = DataDigestWriter::new(std::io::BufWriter::new(file));
+        let written = write_data(self, &mut writer)?;
+        let (buffered, data_len, data_sha384) = writer.finish();
+        let mut file = buffered
+            .into_inner()
+            .map_err(|e| write_err(e.into_error()))?;
+
+        self.sign_dataitem_header(&session_wallet, &mut header, data_len, &data_sha384)?;
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.write_all(&header))
+            .and_then(|_| file.sync_all())
+            .map_err(write_err)?;
+        Ok(written)
+    }
+
+    /// Forget any partially sent upload of `file_path`, so the next attempt starts over.
+    pub fn discard_staged_upload(&self, file_path: &str) {
+        UploadJournal::open_default().discard_for_source(file_path);
+    }
+
+    fn require_upload_ready(
+        &mut self,
+        auth: &PersistedAuth,
+        size_bytes: usize,
+    ) -> LoadStorageResult<()> {
+        let readiness = self.ensure_upload_ready(Some(auth), Some(size_bytes));
+        if readiness.ready {
+            return Ok(());
         }
+        Err(readiness.reason.unwrap_or_else(|| {
+            LoadStorageError::Network("Load upload endpoint unavailable".to_string())
+        }))
+    }
 
-        let mut item = DataItem::new(None, None, ans_tags, payload.to_vec())
+    pub(super) fn build_signed_dataitem(
+        &mut self,
+        auth: &PersistedAuth,
+        payload: &[u8],
+        file_path: Option<&str>,
+        tags: &[Value],
+    ) -> LoadStorageResult<Vec<u8>> {
+        let mut item = DataItem::new(None, None, dataitem_tags(file_path, tags), payload.to_vec())
             .map_err(|e| format!("Failed to build dataitem payload: {e}"))?;
         item.signature_type = SignatureType::Ethereum;
 
         let signing_message = item.signing_message();
-        let session_wallet = load_tempo_session_wallet(auth)?;
+        let session_wallet = signing_session_wallet(auth)?;
         let owner = tempo_session_owner_pubkey_uncompressed(&session_wallet)?;
         let signature = sign_dataitem_with_tempo_session(&session_wallet, &signing_message)?;
 
         if signature.len() != 65 {
-            return Err(format!(
+            return Err(LoadStorageError::Other(format!(
                 "Invalid dataitem signature length: {}",
                 signature.len()
-            ));
+            )));
         }
 
         item.owner = owner;
         item.signature = signature;
+        item.to_bytes().map_err(|e| {
+            LoadStorageError::Other(format!("Failed to encode signed dataitem bytes: {e}"))
+        })
+    }
+
+    /// Signs `message` with the Tempo session key, for records readers check against the
+    /// owner's keychain. Returns the 0x-prefixed 65-byte signature.
+    pub(super) fn sign_record(
+        &mut self,
+        auth: &PersistedAuth,
+        message: &[u8],
+    ) -> LoadStorageResult<String> {
+        let session_wallet = signing_session_wallet(auth)?;
+        let signature = sign_dataitem_with_tempo_session(&session_wallet, message)?;
+        Ok(to_hex_prefixed(&signature))
+    }
+
+    /// Header of a DataItem owned by the Tempo session key, with the data left off and the
+    /// signature zeroed until [`Self::sign_dataitem_header`] fills it in.
+    fn unsigned_dataitem_header(
+        &mut self,
+        session_wallet: &LocalWallet,
+        file_path: Option<&str>,
+        tags: &[Value],
+    ) -> LoadStorageResult<Vec<u8>> {
+        let mut item = DataItem::new(None, None, dataitem_tags(file_path, tags), Vec::new())
+            .map_err(|e| format!("Failed to build dataitem header: {e}"))?;
+        item.signature_type = SignatureType::Ethereum;
+        item.owner = tempo_session_owner_pubkey_uncompressed(session_wallet)?;
+        item.signature = vec![0u8; 65];
         item.to_bytes()
-            .map_err(|e| format!("Failed to encode signed dataitem bytes: {e}"))
+            .map_err(|e| LoadStorageError::Other(format!("Failed to encode dataitem header: {e}")))
+    }
+
+    /// Sign `header` for data of `data_len` bytes hashing to `data_sha384`.
+    fn sign_dataitem_header(
+        &mut self,
+        session_wallet: &LocalWallet,
+        header: &mut [u8],
+        data_len: u64,
+        data_sha384: &[u8],
+    ) -> LoadStorageResult<()> {
+        let signing_message = dataitem_signing_message(header, data_len, data_sha384)?;
+        let signature = sign_dataitem_with_tempo_session(session_wallet, &signing_message)?;
+        header[DATAITEM_SIGNATURE_OFFSET..DATAITEM_SIGNATURE_OFFSET + signature.len()]
+            .copy_from_slice(&signature);
+        Ok(())
+    }
+}
+
+fn dataitem_tags(file_path: Option<&str>, tags: &[Value]) -> Vec<Tag> {
+    let mut ans_tags = convert_tags(tags);
+    if !ans_tags
+        .iter()
+        .any(|tag| tag.name.eq_ignore_ascii_case("Content-Type"))
+    {
+        ans_tags.insert(0, Tag::new("Content-Type", infer_content_type(file_path)));
     }
+    ans_tags
 }
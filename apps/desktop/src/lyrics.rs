@@ -1,18 +1,26 @@
 use std::cmp::Ordering;
-use std::path::Path;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::music_db::{LyricsCacheRow, MusicDb};
 
-const LRCLIB_BASE_URL: &str = "https://lrclib.net";
-const LRCLIB_TIMEOUT_SECS: u64 = 12;
+pub mod editor;
+mod embedded;
+pub mod layers;
+mod lrc;
+mod lrclib;
+mod onchain;
+pub mod prefetch;
+pub mod providers;
+mod romanize;
+mod sidecar;
+
+pub use layers::{LyricsLayer, LyricsLayerKind};
+pub use lrc::{format_lrc_timestamp, parse_lrc};
+pub use providers::{LyricsProviderChain, LyricsProviderKind};
+
 const REMOTE_CACHE_TTL_SECS: i64 = 14 * 24 * 60 * 60;
 const NEGATIVE_CACHE_TTL_SECS: i64 = 6 * 60 * 60;
-const DEFAULT_LRCLIB_USER_AGENT: &str =
-    "heaven-desktop/0.1 (https://github.com/dotheaven/dotheaven)";
 
 #[derive(Debug, Clone)]
 pub struct LyricsTrackSignature {
@@ -21,6 +29,8 @@ pub struct LyricsTrackSignature {
     pub artist_name: String,
     pub album_name: String,
     pub duration_sec: Option<u64>,
+    /// On-chain track id when the caller already knows it; otherwise derived from metadata.
+    pub track_id: Option<String>,
 }
 
 impl LyricsTrackSignature {
@@ -38,16 +48,48 @@ impl LyricsTrackSignature {
 #[derive(Debug, Clone)]
 pub struct LyricsLine {
     pub start_sec: f64,
+    /// Explicit LRC end time, else the next line's start; `None` for the final line.
+    pub end_sec: Option<f64>,
+    pub text: String,
+    /// Enhanced LRC (`<mm:ss.xx>`) word timing; empty for line-timed lyrics.
+    pub words: Vec<LyricsWord>,
+    pub voice: Option<LyricsVoice>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LyricsWord {
+    pub start_sec: f64,
+    pub end_sec: Option<f64>,
     pub text: String,
 }
 
+/// Walaoke duet markers (`M:`, `F:`, `D:`) carried forward until the next marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsVoice {
+    Male,
+    Female,
+    Duet,
+}
+
+/// An additional timed track from the same LRC (a `[la:]` section or interleaved translation).
+#[derive(Debug, Clone)]
+pub struct LyricsLanguageTrack {
+    pub language: Option<String>,
+    pub lines: Vec<LyricsLine>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LyricsSource {
     SidecarSynced,
     SidecarPlain,
+    EmbeddedSynced,
+    EmbeddedPlain,
+    OnchainRef,
     LrclibCached,
     LrclibLive,
     LrclibSearch,
+    /// Timed or corrected by the user in the lyrics editor.
+    UserEdited,
     NoMatch,
 }
 
@@ -56,9 +98,13 @@ impl LyricsSource {
         match self {
             Self::SidecarSynced => "Sidecar (.lrc)",
             Self::SidecarPlain => "Sidecar (.txt)",
+            Self::EmbeddedSynced => "Embedded (synced)",
+            Self::EmbeddedPlain => "Embedded",
+            Self::OnchainRef => "Heaven",
             Self::LrclibCached => "LRCLIB",
             Self::LrclibLive => "LRCLIB",
             Self::LrclibSearch => "LRCLIB",
+            Self::UserEdited => "Edited",
             Self::NoMatch => "No match",
         }
     }
@@ -67,9 +113,13 @@ impl LyricsSource {
         match self {
             Self::SidecarSynced => "sidecar_synced",
             Self::SidecarPlain => "sidecar_plain",
+            Self::EmbeddedSynced => "embedded_synced",
+            Self::EmbeddedPlain => "embedded_plain",
+            Self::OnchainRef => "onchain_ref",
             Self::LrclibCached => "lrclib_cached",
             Self::LrclibLive => "lrclib_live",
             Self::LrclibSearch => "lrclib_search",
+            Self::UserEdited => "user_edited",
             Self::NoMatch => "no_match",
         }
     }
@@ -78,13 +128,69 @@ impl LyricsSource {
         match value {
             "sidecar_synced" => Self::SidecarSynced,
             "sidecar_plain" => Self::SidecarPlain,
+            "embedded_synced" => Self::EmbeddedSynced,
+            "embedded_plain" => Self::EmbeddedPlain,
+            "onchain_ref" => Self::OnchainRef,
             "lrclib_cached" => Self::LrclibCached,
             "lrclib_live" => Self::LrclibLive,
             "lrclib_search" => Self::LrclibSearch,
+            "user_edited" => Self::UserEdited,
             "no_match" => Self::NoMatch,
             _ => Self::NoMatch,
         }
     }
+
+    /// How long a `lyrics_cache` row from this source stays fresh.
+    fn cache_ttl_secs(self) -> i64 {
+        match self {
+            Self::NoMatch => NEGATIVE_CACHE_TTL_SECS,
+            // User edits are authoritative until replaced; never expire them.
+            Self::UserEdited => i64::MAX,
+            _ => REMOTE_CACHE_TTL_SECS,
+        }
+    }
+
+    pub fn provider(self) -> Option<LyricsProviderKind> {
+        match self {
+            Self::SidecarSynced | Self::SidecarPlain | Self::UserEdited => {
+                Some(LyricsProviderKind::Sidecar)
+            }
+            Self::EmbeddedSynced | Self::EmbeddedPlain => Some(LyricsProviderKind::Embedded),
+            Self::OnchainRef => Some(LyricsProviderKind::Onchain),
+            Self::LrclibCached | Self::LrclibLive | Self::LrclibSearch => {
+                Some(LyricsProviderKind::Lrclib)
+            }
+            Self::NoMatch => None,
+        }
+    }
+}
+
+/// Where a lyrics result came from: the provider plus a locator (file path, URL, `ar://` ref).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LyricsProvenance {
+    pub provider: Option<LyricsProviderKind>,
+    pub locator: Option<String>,
+    /// Set when the result was served from `lyrics_cache` rather than a live lookup.
+    pub from_cache: bool,
+}
+
+impl LyricsProvenance {
+    pub fn label(&self) -> Option<String> {
+        let provider = self.provider?.label();
+        if self.from_cache {
+            Some(format!("{provider} · cached"))
+        } else {
+            Some(provider.to_string())
+        }
+    }
+
+    pub fn new(provider: LyricsProviderKind, locator: Option<String>) -> Self {
+        Self {
+            provider: Some(provider),
+            locator,
+            from_cache: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,7 +198,13 @@ pub struct ResolvedLyrics {
     pub plain_lyrics: Option<String>,
     pub synced_lyrics: Option<String>,
     pub synced_lines: Vec<LyricsLine>,
+    /// `[la:]` language of `synced_lines`, when the LRC declares one.
+    pub synced_language: Option<String>,
+    pub alternate_tracks: Vec<LyricsLanguageTrack>,
+    /// Translation/romanization lines aligned to the display lines; derived, never cached.
+    pub layers: Vec<LyricsLayer>,
     pub source: LyricsSource,
+    pub provenance: LyricsProvenance,
     pub lrclib_id: Option<i64>,
     pub fetched_at_epoch_sec: i64,
 }
@@ -106,17 +218,76 @@ impl ResolvedLyrics {
                 .map(str::trim)
                 .is_some_and(|text| !text.is_empty())
     }
-}
 
-#[derive(Debug, Clone)]
-struct LrclibRecord {
-    id: Option<i64>,
-    track_name: String,
-    artist_name: String,
-    album_name: String,
-    duration_sec: Option<u64>,
-    plain_lyrics: Option<String>,
-    synced_lyrics: Option<String>,
+    pub fn no_match(now: i64) -> Self {
+        Self {
+            plain_lyrics: None,
+            synced_lyrics: None,
+            synced_lines: Vec::new(),
+            synced_language: None,
+            alternate_tracks: Vec::new(),
+            layers: Vec::new(),
+            source: LyricsSource::NoMatch,
+            provenance: LyricsProvenance::default(),
+            lrclib_id: None,
+            fetched_at_epoch_sec: now,
+        }
+    }
+
+    /// Build from raw text that may be LRC; falls back to plain text when no line is timed.
+    pub fn from_raw_text(
+        raw: &str,
+        synced_source: LyricsSource,
+        plain_source: LyricsSource,
+        provenance: LyricsProvenance,
+        now: i64,
+    ) -> Option<Self> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let parsed = parse_lrc(trimmed);
+        if parsed.lines.is_empty() {
+            return Self::from_plain_text(trimmed, plain_source, provenance, now);
+        }
+        let plain = plain_from_synced_lines(&parsed.lines).or_else(|| Some(trimmed.into()));
+        Some(Self {
+            plain_lyrics: plain,
+            synced_lyrics: Some(trimmed.to_string()),
+            synced_lines: parsed.lines,
+            synced_language: parsed.language,
+            alternate_tracks: parsed.alternate_tracks,
+            layers: Vec::new(),
+            source: synced_source,
+            provenance,
+            lrclib_id: None,
+            fetched_at_epoch_sec: now,
+        })
+    }
+
+    pub fn from_plain_text(
+        raw: &str,
+        source: LyricsSource,
+        provenance: LyricsProvenance,
+        now: i64,
+    ) -> Option<Self> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        Some(Self {
+            plain_lyrics: Some(trimmed.to_string()),
+            synced_lyrics: None,
+            synced_lines: Vec::new(),
+            synced_language: None,
+            alternate_tracks: Vec::new(),
+            layers: Vec::new(),
+            source,
+            provenance,
+            lrclib_id: None,
+            fetched_at_epoch_sec: now,
+        })
+    }
 }
 
 pub fn parse_duration_label_to_seconds(label: &str) -> Option<u64> {
@@ -143,341 +314,47 @@ pub fn parse_duration_label_to_seconds(label: &str) -> Option<u64> {
     }
 }
 
+/// JSON document uploaded to Arweave and anchored via `setTrackLyricsFor`.
+pub fn lyrics_publish_payload(
+    track_id: &str,
+    signature: &LyricsTrackSignature,
+    lyrics: &ResolvedLyrics,
+) -> Result<String, String> {
+    let payload = serde_json::json!({
+        "trackId": track_id,
+        "trackName": signature.track_name,
+        "artistName": signature.artist_name,
+        "albumName": signature.album_name,
+        "durationSec": signature.duration_sec,
+        "source": lyrics.source.label(),
+        "lrclibId": lyrics.lrclib_id,
+        "fetchedAt": lyrics.fetched_at_epoch_sec,
+        "plainLyrics": lyrics.plain_lyrics,
+        "syncedLyrics": lyrics.synced_lyrics,
+    });
+    serde_json::to_string(&payload).map_err(|e| format!("lyrics payload encode failed: {e}"))
+}
+
+/// Resolve lyrics through the configured provider chain (see [`LyricsProviderChain::configured`]).
 pub fn resolve_lyrics_for_track(
     signature: &LyricsTrackSignature,
     db: Option<Arc<Mutex<MusicDb>>>,
 ) -> Result<ResolvedLyrics, String> {
-    let now = now_epoch_sec();
-
-    if let Some(sidecar) = read_sidecar_lyrics(signature, now) {
-        return Ok(sidecar);
-    }
-
-    if let Some(db_handle) = db.as_ref() {
-        if let Some(cached) = load_fresh_cached_lyrics(db_handle, signature, now)? {
-            return Ok(cached);
-        }
-    }
-
-    let mut should_persist_result = true;
-    let fetched = match fetch_from_lrclib(signature, now) {
-        Ok(found) => found,
-        Err(err) => {
-            // Degrade transient LRCLIB/network failures to a normal no-match UI state.
-            // We intentionally avoid persisting this as negative cache so future retries can succeed.
-            log::warn!(
-                "[lyrics] lrclib lookup failed; using no-match fallback: track='{}' artist='{}' album='{}' err={}",
-                signature.track_name,
-                signature.artist_name,
-                signature.album_name,
-                err
-            );
-            should_persist_result = false;
-            None
-        }
-    };
-    let resolved = fetched.unwrap_or_else(|| ResolvedLyrics {
-        plain_lyrics: None,
-        synced_lyrics: None,
-        synced_lines: Vec::new(),
-        source: LyricsSource::NoMatch,
-        lrclib_id: None,
-        fetched_at_epoch_sec: now,
-    });
-
-    if let Some(db_handle) = db.as_ref() {
-        if should_persist_result
-            && matches!(
-                resolved.source,
-                LyricsSource::LrclibCached
-                    | LyricsSource::LrclibLive
-                    | LyricsSource::LrclibSearch
-                    | LyricsSource::NoMatch
-            )
-        {
-            persist_cached_lyrics(db_handle, signature, &resolved)?;
-        }
-    }
-
+    let mut resolved =
+        LyricsProviderChain::configured(db.as_ref()).resolve(signature, db.as_ref())?;
+    let translation = layers::LyricsTranslationConfig::configured(db.as_ref());
+    layers::attach_lyrics_layers(&mut resolved, translation.as_ref());
     Ok(resolved)
 }
 
-fn now_epoch_sec() -> i64 {
+pub(crate) fn now_epoch_sec() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs() as i64
 }
 
-fn read_sidecar_lyrics(signature: &LyricsTrackSignature, now: i64) -> Option<ResolvedLyrics> {
-    let path = Path::new(&signature.track_path);
-    if !path.exists() {
-        return None;
-    }
-
-    for candidate in [path.with_extension("lrc"), path.with_extension("LRC")] {
-        if !candidate.exists() {
-            continue;
-        }
-        if let Ok(raw) = std::fs::read_to_string(&candidate) {
-            let trimmed = raw.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            let synced_lines = parse_synced_lyrics(trimmed);
-            if !synced_lines.is_empty() {
-                let plain = plain_from_synced_lines(&synced_lines).or_else(|| Some(trimmed.into()));
-                return Some(ResolvedLyrics {
-                    plain_lyrics: plain,
-                    synced_lyrics: Some(trimmed.to_string()),
-                    synced_lines,
-                    source: LyricsSource::SidecarSynced,
-                    lrclib_id: None,
-                    fetched_at_epoch_sec: now,
-                });
-            }
-            return Some(ResolvedLyrics {
-                plain_lyrics: Some(trimmed.to_string()),
-                synced_lyrics: None,
-                synced_lines: Vec::new(),
-                source: LyricsSource::SidecarPlain,
-                lrclib_id: None,
-                fetched_at_epoch_sec: now,
-            });
-        }
-    }
-
-    for candidate in [path.with_extension("txt"), path.with_extension("TXT")] {
-        if !candidate.exists() {
-            continue;
-        }
-        if let Ok(raw) = std::fs::read_to_string(&candidate) {
-            let trimmed = raw.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-            return Some(ResolvedLyrics {
-                plain_lyrics: Some(trimmed.to_string()),
-                synced_lyrics: None,
-                synced_lines: Vec::new(),
-                source: LyricsSource::SidecarPlain,
-                lrclib_id: None,
-                fetched_at_epoch_sec: now,
-            });
-        }
-    }
-
-    None
-}
-
-fn fetch_from_lrclib(
-    signature: &LyricsTrackSignature,
-    now: i64,
-) -> Result<Option<ResolvedLyrics>, String> {
-    if signature.duration_sec.is_some() {
-        if let Some(record) = fetch_lrclib_signature(signature, true)? {
-            return Ok(Some(record_to_lyrics(
-                record,
-                LyricsSource::LrclibCached,
-                now,
-            )));
-        }
-        if let Some(record) = fetch_lrclib_signature(signature, false)? {
-            return Ok(Some(record_to_lyrics(
-                record,
-                LyricsSource::LrclibLive,
-                now,
-            )));
-        }
-    }
-
-    if let Some(record) = fetch_lrclib_search(signature)? {
-        return Ok(Some(record_to_lyrics(
-            record,
-            LyricsSource::LrclibSearch,
-            now,
-        )));
-    }
-
-    Ok(None)
-}
-
-fn fetch_lrclib_signature(
-    signature: &LyricsTrackSignature,
-    cached_only: bool,
-) -> Result<Option<LrclibRecord>, String> {
-    let duration = signature.duration_sec.ok_or_else(|| {
-        "duration is required for /api/get and /api/get-cached signature lookup".to_string()
-    })?;
-
-    let endpoint = if cached_only {
-        "api/get-cached"
-    } else {
-        "api/get"
-    };
-    let url = format!(
-        "{LRCLIB_BASE_URL}/{endpoint}?track_name={}&artist_name={}&album_name={}&duration={duration}",
-        urlencoding::encode(signature.track_name.as_str()),
-        urlencoding::encode(signature.artist_name.as_str()),
-        urlencoding::encode(signature.album_name.as_str()),
-    );
-
-    let Some(json) = lrclib_get_json(&url)? else {
-        return Ok(None);
-    };
-    Ok(parse_lrclib_record(&json))
-}
-
-fn fetch_lrclib_search(signature: &LyricsTrackSignature) -> Result<Option<LrclibRecord>, String> {
-    let mut url = format!(
-        "{LRCLIB_BASE_URL}/api/search?track_name={}&artist_name={}",
-        urlencoding::encode(signature.track_name.as_str()),
-        urlencoding::encode(signature.artist_name.as_str())
-    );
-    if !signature.album_name.trim().is_empty() {
-        url.push_str("&album_name=");
-        url.push_str(&urlencoding::encode(signature.album_name.as_str()));
-    }
-
-    let Some(json) = lrclib_get_json(&url)? else {
-        return Ok(None);
-    };
-    let Some(candidates) = json.as_array() else {
-        return Ok(None);
-    };
-
-    let mut best: Option<(i32, LrclibRecord)> = None;
-    for candidate in candidates {
-        let Some(record) = parse_lrclib_record(candidate) else {
-            continue;
-        };
-        let score = score_search_candidate(&record, signature);
-        match &best {
-            Some((best_score, _)) if score <= *best_score => {}
-            _ => best = Some((score, record)),
-        }
-    }
-
-    let Some((score, record)) = best else {
-        return Ok(None);
-    };
-    if score < 120 {
-        return Ok(None);
-    }
-    Ok(Some(record))
-}
-
-fn lrclib_get_json(url: &str) -> Result<Option<Value>, String> {
-    let request = ureq::get(url)
-        .header("User-Agent", lrclib_user_agent().as_str())
-        .config()
-        .timeout_global(Some(Duration::from_secs(LRCLIB_TIMEOUT_SECS)))
-        .http_status_as_error(false)
-        .build();
-
-    let mut response = request
-        .call()
-        .map_err(|e| format!("LRCLIB request failed ({url}): {e}"))?;
-    let status = response.status().as_u16();
-    let body = response
-        .body_mut()
-        .read_to_string()
-        .unwrap_or_else(|_| String::new());
-
-    if status == 404 {
-        return Ok(None);
-    }
-    if status >= 400 {
-        return Err(format!(
-            "LRCLIB request failed ({status}) for {url}: {}",
-            body.trim()
-        ));
-    }
-
-    serde_json::from_str::<Value>(&body)
-        .map(Some)
-        .map_err(|e| format!("Failed parsing LRCLIB JSON ({url}): {e}"))
-}
-
-fn lrclib_user_agent() -> String {
-    std::env::var("HEAVEN_LRCLIB_USER_AGENT")
-        .ok()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .unwrap_or_else(|| DEFAULT_LRCLIB_USER_AGENT.to_string())
-}
-
-fn parse_lrclib_record(value: &Value) -> Option<LrclibRecord> {
-    let track_name = value.get("trackName")?.as_str()?.trim().to_string();
-    if track_name.is_empty() {
-        return None;
-    }
-    let artist_name = value
-        .get("artistName")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .unwrap_or("Unknown Artist")
-        .to_string();
-    let album_name = value
-        .get("albumName")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .unwrap_or("")
-        .to_string();
-    let id = value.get("id").and_then(Value::as_i64);
-    let duration_sec = value.get("duration").and_then(Value::as_u64);
-    let plain_lyrics = value
-        .get("plainLyrics")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|text| !text.is_empty())
-        .map(str::to_string);
-    let synced_lyrics = value
-        .get("syncedLyrics")
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|text| !text.is_empty())
-        .map(str::to_string);
-
-    Some(LrclibRecord {
-        id,
-        track_name,
-        artist_name,
-        album_name,
-        duration_sec,
-        plain_lyrics,
-        synced_lyrics,
-    })
-}
-
-fn record_to_lyrics(record: LrclibRecord, source: LyricsSource, now: i64) -> ResolvedLyrics {
-    let synced_lines = record
-        .synced_lyrics
-        .as_deref()
-        .map(parse_synced_lyrics)
-        .unwrap_or_default();
-    maybe_log_suspicious_synced_timing(
-        "lrclib",
-        &record.track_name,
-        &record.artist_name,
-        record.duration_sec,
-        &synced_lines,
-    );
-    let plain_lyrics = record
-        .plain_lyrics
-        .or_else(|| plain_from_synced_lines(&synced_lines));
-
-    ResolvedLyrics {
-        plain_lyrics,
-        synced_lyrics: record.synced_lyrics,
-        synced_lines,
-        source,
-        lrclib_id: record.id,
-        fetched_at_epoch_sec: now,
-    }
-}
-
-fn maybe_log_suspicious_synced_timing(
+pub(crate) fn maybe_log_suspicious_synced_timing(
     source: &str,
     track_name: &str,
     artist_name: &str,
@@ -541,70 +418,7 @@ fn synced_timing_stats(lines: &[LyricsLine]) -> Option<(f64, f64, f64)> {
     Some((median_gap, min_gap, last_start))
 }
 
-fn score_search_candidate(record: &LrclibRecord, signature: &LyricsTrackSignature) -> i32 {
-    let track_a = normalize_lookup_text(&record.track_name);
-    let track_b = normalize_lookup_text(&signature.track_name);
-    let artist_a = normalize_lookup_text(&record.artist_name);
-    let artist_b = normalize_lookup_text(&signature.artist_name);
-    let album_a = normalize_lookup_text(&record.album_name);
-    let album_b = normalize_lookup_text(&signature.album_name);
-
-    let mut score = 0;
-
-    if track_a == track_b {
-        score += 100;
-    } else if track_a.contains(&track_b) || track_b.contains(&track_a) {
-        score += 45;
-    }
-
-    if artist_a == artist_b {
-        score += 80;
-    } else if artist_a.contains(&artist_b) || artist_b.contains(&artist_a) {
-        score += 35;
-    }
-
-    if !album_b.is_empty() {
-        if album_a == album_b {
-            score += 30;
-        } else if album_a.contains(&album_b) || album_b.contains(&album_a) {
-            score += 15;
-        }
-    }
-
-    if let (Some(expected), Some(found)) = (signature.duration_sec, record.duration_sec) {
-        let diff = expected.abs_diff(found);
-        if diff <= 2 {
-            score += 40;
-        } else if diff <= 5 {
-            score += 22;
-        } else if diff <= 10 {
-            score += 8;
-        } else {
-            score -= 10;
-        }
-    }
-
-    // Prefer results that have synced lyrics, but only when the duration is
-    // a reasonable match (within 15s) so we don't pick a different mix/edit
-    // just because it happens to have synced lyrics.
-    if record
-        .synced_lyrics
-        .as_ref()
-        .is_some_and(|s| !s.trim().is_empty())
-    {
-        let duration_close = match (signature.duration_sec, record.duration_sec) {
-            (Some(expected), Some(found)) => expected.abs_diff(found) <= 15,
-            _ => true, // no duration info — give benefit of the doubt
-        };
-        if duration_close {
-            score += 50;
-        }
-    }
-
-    score
-}
-
-fn normalize_lookup_text(input: &str) -> String {
+pub(crate) fn normalize_lookup_text(input: &str) -> String {
     let mut out = String::with_capacity(input.len());
     let mut prev_space = false;
     for ch in input.chars() {
@@ -621,63 +435,7 @@ fn normalize_lookup_text(input: &str) -> String {
     out.trim().to_string()
 }
 
-fn parse_synced_lyrics(raw: &str) -> Vec<LyricsLine> {
-    let mut lines = Vec::new();
-
-    for raw_line in raw.lines() {
-        let mut remaining = raw_line.trim();
-        if remaining.is_empty() {
-            continue;
-        }
-
-        let mut timestamps = Vec::new();
-        while let Some(stripped) = remaining.strip_prefix('[') {
-            let Some(close) = stripped.find(']') else {
-                break;
-            };
-            let stamp = stripped[..close].trim();
-            let Some(start_sec) = parse_lrc_timestamp(stamp) else {
-                break;
-            };
-            timestamps.push(start_sec);
-            remaining = stripped[close + 1..].trim_start();
-        }
-
-        if timestamps.is_empty() {
-            continue;
-        }
-
-        let text = remaining.trim().to_string();
-        if text.is_empty() {
-            continue;
-        }
-        for start_sec in timestamps {
-            lines.push(LyricsLine {
-                start_sec,
-                text: text.clone(),
-            });
-        }
-    }
-
-    lines.sort_by(|a, b| {
-        a.start_sec
-            .partial_cmp(&b.start_sec)
-            .unwrap_or(Ordering::Equal)
-    });
-    lines
-}
-
-fn parse_lrc_timestamp(stamp: &str) -> Option<f64> {
-    let (minutes, rest) = stamp.split_once(':')?;
-    let minutes = minutes.parse::<u64>().ok()?;
-    let seconds = rest.parse::<f64>().ok()?;
-    if seconds >= 60.0 {
-        return None;
-    }
-    Some(minutes as f64 * 60.0 + seconds)
-}
-
-fn plain_from_synced_lines(lines: &[LyricsLine]) -> Option<String> {
+pub(crate) fn plain_from_synced_lines(lines: &[LyricsLine]) -> Option<String> {
     if lines.is_empty() {
         return None;
     }
@@ -701,7 +459,7 @@ fn plain_from_synced_lines(lines: &[LyricsLine]) -> Option<String> {
     }
 }
 
-fn load_fresh_cached_lyrics(
+pub(crate) fn load_fresh_cached_lyrics(
     db_handle: &Arc<Mutex<MusicDb>>,
     signature: &LyricsTrackSignature,
     now: i64,
@@ -719,20 +477,16 @@ fn load_fresh_cached_lyrics(
 
     let source = LyricsSource::from_db_key(&row.source);
     let age = now.saturating_sub(row.fetched_at_epoch_sec);
-    let ttl = if source == LyricsSource::NoMatch {
-        NEGATIVE_CACHE_TTL_SECS
-    } else {
-        REMOTE_CACHE_TTL_SECS
-    };
-    if age > ttl {
+    if age > source.cache_ttl_secs() {
         return Ok(None);
     }
 
-    let synced_lines = row
+    let parsed = row
         .synced_lyrics
         .as_deref()
-        .map(parse_synced_lyrics)
+        .map(parse_lrc)
         .unwrap_or_default();
+    let synced_lines = parsed.lines;
     maybe_log_suspicious_synced_timing(
         "cache",
         &signature.track_name,
@@ -749,13 +503,21 @@ fn load_fresh_cached_lyrics(
         plain_lyrics,
         synced_lyrics: row.synced_lyrics,
         synced_lines,
+        synced_language: parsed.language,
+        alternate_tracks: parsed.alternate_tracks,
+        layers: Vec::new(),
         source,
+        provenance: LyricsProvenance {
+            provider: source.provider(),
+            locator: row.provenance,
+            from_cache: true,
+        },
         lrclib_id: row.lrclib_id,
         fetched_at_epoch_sec: row.fetched_at_epoch_sec,
     }))
 }
 
-fn persist_cached_lyrics(
+pub(crate) fn persist_cached_lyrics(
     db_handle: &Arc<Mutex<MusicDb>>,
     signature: &LyricsTrackSignature,
     lyrics: &ResolvedLyrics,
@@ -771,6 +533,7 @@ fn persist_cached_lyrics(
         lrclib_id: lyrics.lrclib_id,
         source: lyrics.source.as_db_key().to_string(),
         fetched_at_epoch_sec: lyrics.fetched_at_epoch_sec,
+        provenance: lyrics.provenance.locator.clone(),
     };
 
     let db = db_handle
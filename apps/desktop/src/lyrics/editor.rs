@@ -0,0 +1,519 @@
+//! Tap-to-sync lyrics editor: paste plain lyrics, stamp each line against the
+//! playback position, nudge a global offset, then save as a sidecar `.lrc`.
+
+use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use super::layers::{attach_lyrics_layers, LyricsTranslationConfig};
+use super::lrc::SAME_CUE_EPSILON_SEC;
+use super::{
+    format_lrc_timestamp, now_epoch_sec, parse_lrc, persist_cached_lyrics, LyricsLanguageTrack,
+    LyricsLine, LyricsProvenance, LyricsProviderKind, LyricsSource, LyricsTrackSignature,
+    LyricsVoice, LyricsWord, ResolvedLyrics,
+};
+use crate::music_db::MusicDb;
+
+pub const OFFSET_STEP_MS: i64 = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct LyricsEditorLine {
+    pub text: String,
+    pub start_sec: Option<f64>,
+    /// Explicit end (a bare LRC end marker), relative to `start_sec`.
+    pub end_after_sec: Option<f64>,
+    /// Enhanced LRC word timing, relative to `start_sec` so a re-tapped line keeps it.
+    pub words: Vec<LyricsWord>,
+    pub voice: Option<LyricsVoice>,
+    /// Interleaved translation lines that share this line's cue.
+    pub interleaved: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LyricsEditorState {
+    pub lines: Vec<LyricsEditorLine>,
+    /// Index of the next line to stamp.
+    pub cursor: usize,
+    /// Positive values show lyrics earlier (LRC `[offset:]` semantics).
+    pub offset_ms: i64,
+    /// `[la:]` language of `lines`.
+    pub language: Option<String>,
+    /// Other `[la:]` sections, saved as they were loaded apart from the offset.
+    pub alternate_tracks: Vec<LyricsLanguageTrack>,
+}
+
+impl LyricsEditorState {
+    /// Accepts plain text or LRC; existing timestamps and `[offset:]` are kept.
+    pub fn from_text(raw: &str) -> Self {
+        let parsed = parse_lrc(raw);
+        if !parsed.lines.is_empty() {
+            return Self::from_synced(
+                &parsed.lines,
+                parsed.language,
+                &parsed.alternate_tracks,
+                parsed.offset_ms,
+            );
+        }
+
+        let lines = raw
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| LyricsEditorLine {
+                text: line.to_string(),
+                ..LyricsEditorLine::default()
+            })
+            .collect();
+        Self {
+            lines,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_resolved(lyrics: &ResolvedLyrics) -> Self {
+        if !lyrics.synced_lines.is_empty() {
+            return Self::from_synced(
+                &lyrics.synced_lines,
+                lyrics.synced_language.clone(),
+                &lyrics.alternate_tracks,
+                0,
+            );
+        }
+        Self::from_text(lyrics.plain_lyrics.as_deref().unwrap_or_default())
+    }
+
+    /// `synced` and `alternate_tracks` have `offset_ms` applied already (as `parse_lrc`
+    /// returns them); undo it so the editor owns the offset.
+    fn from_synced(
+        synced: &[LyricsLine],
+        language: Option<String>,
+        alternate_tracks: &[LyricsLanguageTrack],
+        offset_ms: i64,
+    ) -> Self {
+        let unshift = offset_ms as f64 / 1000.0;
+        let mut lines: Vec<LyricsEditorLine> = synced
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                let start = line.start_sec;
+                let next_start = synced[idx + 1..]
+                    .iter()
+                    .map(|next| next.start_sec)
+                    .find(|next| *next > start + SAME_CUE_EPSILON_SEC);
+                // An end at the next line's start is implied; only a gap needs a marker.
+                let end_after_sec = line
+                    .end_sec
+                    .filter(|end| {
+                        line.words.is_empty()
+                            && next_start
+                                .is_none_or(|next| (next - end).abs() > SAME_CUE_EPSILON_SEC)
+                    })
+                    .map(|end| end - start);
+                LyricsEditorLine {
+                    text: line.text.clone(),
+                    start_sec: Some(start + unshift),
+                    end_after_sec,
+                    words: line
+                        .words
+                        .iter()
+                        .map(|word| LyricsWord {
+                            start_sec: word.start_sec - start,
+                            end_sec: word.end_sec.map(|end| end - start),
+                            text: word.text.clone(),
+                        })
+                        .collect(),
+                    voice: line.voice,
+                    interleaved: Vec::new(),
+                }
+            })
+            .collect();
+
+        let mut tracks = Vec::new();
+        for track in alternate_tracks {
+            if track.language.is_some() {
+                tracks.push(LyricsLanguageTrack {
+                    language: track.language.clone(),
+                    lines: track
+                        .lines
+                        .iter()
+                        .map(|line| shift_line(line, unshift))
+                        .collect(),
+                });
+                continue;
+            }
+            // Duplicate cues: an interleaved translation under the primary lines.
+            for duplicate in &track.lines {
+                let host = synced.iter().position(|line| {
+                    (line.start_sec - duplicate.start_sec).abs() < SAME_CUE_EPSILON_SEC
+                });
+                if let Some(host) = host {
+                    lines[host].interleaved.push(duplicate.text.clone());
+                }
+            }
+        }
+
+        Self {
+            cursor: lines.len(),
+            lines,
+            offset_ms,
+            language,
+            alternate_tracks: tracks,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn timed_count(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.start_sec.is_some())
+            .count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.lines.is_empty() && self.timed_count() == self.lines.len()
+    }
+
+    /// Stamp the line under the cursor with `position_sec` and advance.
+    pub fn tap(&mut self, position_sec: f64) -> bool {
+        let Some(line) = self.lines.get_mut(self.cursor) else {
+            return false;
+        };
+        line.start_sec = Some(position_sec.max(0.0));
+        self.cursor += 1;
+        true
+    }
+
+    pub fn undo_tap(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        if let Some(line) = self.lines.get_mut(self.cursor) {
+            line.start_sec = None;
+        }
+    }
+
+    /// Clear timings from the cursor onward so the remaining lines can be re-tapped.
+    pub fn restart_from(&mut self, index: usize) {
+        self.cursor = index.min(self.lines.len());
+        for line in self.lines.iter_mut().skip(self.cursor) {
+            line.start_sec = None;
+        }
+    }
+
+    pub fn shift_offset(&mut self, delta_ms: i64) {
+        self.offset_ms = self.offset_ms.saturating_add(delta_ms);
+    }
+
+    /// Render as LRC with the global offset baked into every timestamp. Word timing, end
+    /// markers, duet voices, interleaved translations and `[la:]` sections are kept.
+    pub fn to_lrc(&self, signature: &LyricsTrackSignature) -> Result<String, String> {
+        if !self.is_complete() {
+            return Err(format!(
+                "{} of {} lyric lines still need a timestamp",
+                self.lines.len() - self.timed_count(),
+                self.lines.len()
+            ));
+        }
+
+        let mut out = Vec::with_capacity(self.lines.len() + 4);
+        for (tag, value) in [
+            ("ti", signature.track_name.as_str()),
+            ("ar", signature.artist_name.as_str()),
+            ("al", signature.album_name.as_str()),
+        ] {
+            if !value.trim().is_empty() {
+                out.push(format!("[{tag}:{}]", value.trim()));
+            }
+        }
+        if let Some(duration) = signature.duration_sec {
+            out.push(format!("[length:{}:{:02}]", duration / 60, duration % 60));
+        }
+        if let Some(language) = &self.language {
+            out.push(format!("[la:{language}]"));
+        }
+
+        let offset_sec = self.offset_ms as f64 / 1000.0;
+        let mut voice = None;
+        let mut timed: Vec<(f64, &LyricsEditorLine)> = self
+            .lines
+            .iter()
+            .filter_map(|line| Some((line.start_sec? - offset_sec, line)))
+            .collect();
+        timed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        for (start_sec, line) in timed {
+            out.push(format_lrc_line(
+                start_sec,
+                &line.text,
+                &line.words,
+                line.voice,
+                &mut voice,
+            ));
+            for translation in &line.interleaved {
+                out.push(format!(
+                    "[{}]{translation}",
+                    format_lrc_timestamp(start_sec)
+                ));
+            }
+            if let Some(end_after) = line.end_after_sec {
+                out.push(format!("[{}]", format_lrc_timestamp(start_sec + end_after)));
+            }
+        }
+
+        for track in &self.alternate_tracks {
+            let Some(language) = &track.language else {
+                continue;
+            };
+            out.push(format!("[la:{language}]"));
+            for line in &track.lines {
+                let start_sec = line.start_sec - offset_sec;
+                let words: Vec<LyricsWord> = line
+                    .words
+                    .iter()
+                    .map(|word| LyricsWord {
+                        start_sec: word.start_sec - line.start_sec,
+                        end_sec: word.end_sec.map(|end| end - line.start_sec),
+                        text: word.text.clone(),
+                    })
+                    .collect();
+                out.push(format_lrc_line(
+                    start_sec, &line.text, &words, line.voice, &mut voice,
+                ));
+            }
+        }
+        Ok(out.join("\n"))
+    }
+}
+
+fn shift_line(line: &LyricsLine, shift: f64) -> LyricsLine {
+    LyricsLine {
+        start_sec: line.start_sec + shift,
+        end_sec: line.end_sec.map(|end| end + shift),
+        text: line.text.clone(),
+        words: line
+            .words
+            .iter()
+            .map(|word| LyricsWord {
+                start_sec: word.start_sec + shift,
+                end_sec: word.end_sec.map(|end| end + shift),
+                text: word.text.clone(),
+            })
+            .collect(),
+        voice: line.voice,
+    }
+}
+
+/// One `[mm:ss.xx]` line; `words` are relative to `start_sec` and written as enhanced LRC.
+/// A duet prefix is written only where the voice changes, as `parse_lrc` carries it forward.
+fn format_lrc_line(
+    start_sec: f64,
+    text: &str,
+    words: &[LyricsWord],
+    voice: Option<LyricsVoice>,
+    current_voice: &mut Option<LyricsVoice>,
+) -> String {
+    let mut out = format!("[{}]", format_lrc_timestamp(start_sec));
+    if let Some(changed) = voice.filter(|voice| Some(*voice) != *current_voice) {
+        out.push_str(match changed {
+            LyricsVoice::Male => "M: ",
+            LyricsVoice::Female => "F: ",
+            LyricsVoice::Duet => "D: ",
+        });
+        *current_voice = voice;
+    }
+    if words.is_empty() {
+        out.push_str(text);
+        return out;
+    }
+
+    // Keep the line's own spacing so syllable-timed words aren't split apart.
+    let mut rest = text;
+    for (idx, word) in words.iter().enumerate() {
+        out.push_str(&format!(
+            "<{}>",
+            format_lrc_timestamp(start_sec + word.start_sec)
+        ));
+        out.push_str(&word.text);
+        if let Some(found) = rest.find(word.text.as_str()) {
+            rest = &rest[found + word.text.len()..];
+        }
+        if let Some(next) = words.get(idx + 1) {
+            match rest.find(next.text.as_str()) {
+                Some(gap) => out.push_str(&rest[..gap]),
+                None => out.push(' '),
+            }
+        }
+    }
+    if let Some(end) = words.last().and_then(|word| word.end_sec) {
+        out.push_str(&format!("<{}>", format_lrc_timestamp(start_sec + end)));
+    }
+    out
+}
+
+/// Write `lrc` next to the track (keeping a `.lrc.bak` of any previous file) and
+/// into `lyrics_cache`, returning the lyrics as the side player should show them.
+pub fn save_edited_lyrics(
+    signature: &LyricsTrackSignature,
+    lrc: &str,
+    db: Option<&Arc<Mutex<MusicDb>>>,
+) -> Result<ResolvedLyrics, String> {
+    let now = now_epoch_sec();
+    let track_path = Path::new(&signature.track_path);
+    let mut sidecar_path = None;
+    let mut sidecar_error = None;
+    if track_path.exists() {
+        let candidate = track_path.with_extension("lrc");
+        match write_sidecar(&candidate, lrc) {
+            Ok(()) => sidecar_path = Some(candidate.to_string_lossy().to_string()),
+            Err(err) => {
+                log::warn!("[lyrics] editor sidecar write failed: {}", err);
+                sidecar_error = Some(err);
+            }
+        }
+    }
+
+    let provenance = LyricsProvenance::new(LyricsProviderKind::Sidecar, sidecar_path);
+    let mut resolved = ResolvedLyrics::from_raw_text(
+        lrc,
+        LyricsSource::UserEdited,
+        LyricsSource::UserEdited,
+        provenance,
+        now,
+    )
+    .ok_or("edited lyrics are empty")?;
+
+    match db {
+        Some(db_handle) => persist_cached_lyrics(db_handle, signature, &resolved)?,
+        None => {
+            if let Some(err) = sidecar_error {
+                return Err(err);
+            }
+        }
+    }
+    let translation = LyricsTranslationConfig::configured(db);
+    attach_lyrics_layers(&mut resolved, translation.as_ref());
+    Ok(resolved)
+}
+
+fn write_sidecar(path: &Path, lrc: &str) -> Result<(), String> {
+    if let Ok(previous) = std::fs::read_to_string(path) {
+        if previous.trim() != lrc.trim() {
+            let backup = path.with_extension("lrc.bak");
+            std::fs::write(&backup, previous)
+                .map_err(|e| format!("Failed backing up {}: {e}", path.display()))?;
+        }
+    }
+    std::fs::write(path, format!("{}\n", lrc.trim_end()))
+        .map_err(|e| format!("Failed writing {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature() -> LyricsTrackSignature {
+        LyricsTrackSignature {
+            track_path: String::new(),
+            track_name: "Song".to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: String::new(),
+            duration_sec: Some(125),
+            track_id: None,
+        }
+    }
+
+    #[test]
+    fn tap_stamps_lines_in_order_and_bakes_offset() {
+        let mut editor = LyricsEditorState::from_text("first\n\nsecond\n");
+        assert_eq!(editor.lines.len(), 2);
+        assert!(editor.tap(1.0));
+        assert!(editor.to_lrc(&signature()).is_err());
+        assert!(editor.tap(2.5));
+        assert!(!editor.tap(3.0));
+        editor.shift_offset(OFFSET_STEP_MS * 2);
+
+        let lrc = editor.to_lrc(&signature()).expect("complete");
+        assert_eq!(
+            lrc,
+            "[ti:Song]\n[ar:Artist]\n[length:2:05]\n[00:00.80]first\n[00:02.30]second"
+        );
+    }
+
+    #[test]
+    fn undo_and_reimport_round_trip() {
+        let mut editor = LyricsEditorState::from_text("[offset:250]\n[00:01.00]a\n[00:02.00]b");
+        assert_eq!(editor.offset_ms, 250);
+        assert_eq!(editor.lines[0].start_sec, Some(1.0));
+        editor.undo_tap();
+        assert_eq!(editor.cursor, 1);
+        assert_eq!(editor.lines[1].start_sec, None);
+        editor.tap(2.2);
+        let lrc = editor.to_lrc(&signature()).expect("complete");
+        assert!(lrc.ends_with("[00:00.75]a\n[00:01.95]b"));
+    }
+
+    fn assert_same_lines(actual: &[LyricsLine], expected: &[LyricsLine]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert_eq!(actual.text, expected.text);
+            assert!((actual.start_sec - expected.start_sec).abs() < 1e-6);
+            assert_eq!(actual.end_sec, expected.end_sec);
+            assert_eq!(actual.voice, expected.voice);
+            assert_eq!(actual.words.len(), expected.words.len());
+            for (actual, expected) in actual.words.iter().zip(&expected.words) {
+                assert_eq!(actual.text, expected.text);
+                assert_eq!(actual.start_sec, expected.start_sec);
+                assert_eq!(actual.end_sec, expected.end_sec);
+            }
+        }
+    }
+
+    #[test]
+    fn enhanced_multi_language_sidecar_round_trips() {
+        let raw = "[la:ja]\n\
+                   [00:01.00]M: <00:01.00>こん<00:01.50>にちは<00:02.00>\n\
+                   [00:01.00]hello\n\
+                   [00:03.00]F: さようなら\n\
+                   [00:05.00]\n\
+                   [la:en]\n\
+                   [00:01.00]<00:01.00>hi <00:01.40>there<00:02.00>\n";
+        let original = ResolvedLyrics::from_raw_text(
+            raw,
+            LyricsSource::SidecarSynced,
+            LyricsSource::SidecarPlain,
+            LyricsProvenance::default(),
+            0,
+        )
+        .expect("lyrics");
+
+        let editor = LyricsEditorState::from_resolved(&original);
+        let saved = parse_lrc(&editor.to_lrc(&signature()).expect("complete"));
+        assert_eq!(saved.language.as_deref(), Some("ja"));
+        assert_same_lines(&saved.lines, &original.synced_lines);
+        assert_eq!(saved.lines[1].end_sec, Some(5.0));
+        assert_eq!(
+            saved.alternate_tracks.len(),
+            original.alternate_tracks.len()
+        );
+        for (saved, original) in saved
+            .alternate_tracks
+            .iter()
+            .zip(&original.alternate_tracks)
+        {
+            assert_eq!(saved.language, original.language);
+            assert_same_lines(&saved.lines, &original.lines);
+        }
+
+        // Re-tapping a line moves its word timing with it.
+        let mut editor = editor;
+        editor.restart_from(0);
+        editor.tap(10.0);
+        editor.tap(12.0);
+        let retimed = parse_lrc(&editor.to_lrc(&signature()).expect("complete"));
+        assert_eq!(retimed.lines[0].words[1].start_sec, 10.5);
+        assert_eq!(retimed.lines[1].end_sec, Some(14.0));
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use lofty::config::ParseOptions;
+use lofty::file::AudioFile;
+use lofty::id3::v2::{Frame, SynchronizedTextFrame, TimestampFormat};
+use lofty::mpeg::MpegFile;
+use lofty::prelude::*;
+
+use super::providers::{LyricsProvider, LyricsProviderKind};
+use super::{
+    format_lrc_timestamp, LyricsProvenance, LyricsSource, LyricsTrackSignature, ResolvedLyrics,
+};
+
+/// Lyrics embedded in the audio file's tags.
+///
+/// Synced lyrics come from ID3v2 `SYLT` frames; unsynced lyrics from ID3v2 `USLT`,
+/// Vorbis `LYRICS`/`UNSYNCEDLYRICS` and MP4 `©lyr` (all mapped to `ItemKey::Lyrics`
+/// by lofty). Unsynced fields that actually hold LRC text are parsed as synced.
+pub struct EmbeddedTagsProvider;
+
+impl LyricsProvider for EmbeddedTagsProvider {
+    fn kind(&self) -> LyricsProviderKind {
+        LyricsProviderKind::Embedded
+    }
+
+    fn lookup(
+        &self,
+        signature: &LyricsTrackSignature,
+        now: i64,
+    ) -> Result<Option<ResolvedLyrics>, String> {
+        let path = Path::new(&signature.track_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        if let Some(lrc) = read_id3v2_sylt_as_lrc(path) {
+            let provenance = LyricsProvenance::new(
+                LyricsProviderKind::Embedded,
+                Some(format!("{}#SYLT", signature.track_path)),
+            );
+            if let Some(resolved) = ResolvedLyrics::from_raw_text(
+                &lrc,
+                LyricsSource::EmbeddedSynced,
+                LyricsSource::EmbeddedPlain,
+                provenance,
+                now,
+            ) {
+                return Ok(Some(resolved));
+            }
+        }
+
+        let tagged = match lofty::read_from_path(path) {
+            Ok(tagged) => tagged,
+            Err(err) => {
+                log::debug!(
+                    "[lyrics] embedded tag read failed: path='{}' err={}",
+                    signature.track_path,
+                    err
+                );
+                return Ok(None);
+            }
+        };
+
+        let unsynced_key = ItemKey::Unknown("UNSYNCEDLYRICS".to_string());
+        for tag in tagged.tags() {
+            let raw = tag
+                .get_string(&ItemKey::Lyrics)
+                .or_else(|| tag.get_string(&unsynced_key));
+            let Some(raw) = raw else {
+                continue;
+            };
+            let provenance = LyricsProvenance::new(
+                LyricsProviderKind::Embedded,
+                Some(format!("{}#{:?}", signature.track_path, tag.tag_type())),
+            );
+            if let Some(resolved) = ResolvedLyrics::from_raw_text(
+                raw,
+                LyricsSource::EmbeddedSynced,
+                LyricsSource::EmbeddedPlain,
+                provenance,
+                now,
+            ) {
+                return Ok(Some(resolved));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Convert the first millisecond-timed `SYLT` frame into LRC text.
+fn read_id3v2_sylt_as_lrc(path: &Path) -> Option<String> {
+    let is_mpeg = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"));
+    if !is_mpeg {
+        return None;
+    }
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mpeg = MpegFile::read_from(&mut file, ParseOptions::new()).ok()?;
+    let id3v2 = mpeg.id3v2()?;
+
+    for frame in id3v2 {
+        if frame.id_str() != "SYLT" {
+            continue;
+        }
+        let Frame::Binary(binary) = frame else {
+            continue;
+        };
+        let Ok(sylt) = SynchronizedTextFrame::parse(&binary.data, frame.flags()) else {
+            continue;
+        };
+        // MPEG-frame timestamps would need the stream's frame rate; skip them.
+        if sylt.timestamp_format != TimestampFormat::MS {
+            continue;
+        }
+        let lines: Vec<String> = sylt
+            .content
+            .iter()
+            .map(|(ms, text)| (*ms, text.trim()))
+            .filter(|(_, text)| !text.is_empty())
+            .map(|(ms, text)| format!("[{}]{}", format_lrc_timestamp(ms as f64 / 1000.0), text))
+            .collect();
+        if !lines.is_empty() {
+            return Some(lines.join("\n"));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,386 @@
+//! Translation and romanization layers shown alongside the original lyrics.
+//!
+//! Every layer holds one optional string per display line: `synced_lines` when the
+//! lyrics are timed, otherwise the non-empty lines of `plain_lyrics`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::{LyricsLanguageTrack, LyricsLine, ResolvedLyrics};
+use crate::music_db::MusicDb;
+
+/// Settings key for the target language passed to the configured translator (e.g. `en`).
+pub const LYRICS_TRANSLATION_LANGUAGE_SETTING: &str = "lyrics_translation_language";
+
+/// Cue-matching tolerance when aligning another LRC track to the primary one.
+const ALIGN_TOLERANCE_SEC: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsLayerKind {
+    Translation,
+    Romanization,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsLayerOrigin {
+    /// A parallel language track from the same LRC file.
+    Lrc,
+    /// Produced by a [`LyricsTranslator`].
+    Translator,
+    /// Produced by the built-in kana/hangul/pinyin romanizer.
+    BuiltinRomanizer,
+}
+
+#[derive(Debug, Clone)]
+pub struct LyricsLayer {
+    pub kind: LyricsLayerKind,
+    pub language: Option<String>,
+    pub origin: LyricsLayerOrigin,
+    pub lines: Vec<Option<String>>,
+}
+
+impl LyricsLayer {
+    pub fn label(&self) -> String {
+        let base = match self.kind {
+            LyricsLayerKind::Translation => "Translation",
+            LyricsLayerKind::Romanization => "Romanized",
+        };
+        match (self.language.as_deref(), self.origin) {
+            (Some(language), _) if self.kind == LyricsLayerKind::Translation => {
+                format!("{base} ({language})")
+            }
+            (_, LyricsLayerOrigin::BuiltinRomanizer) => format!("{base} · auto"),
+            _ => base.to_string(),
+        }
+    }
+
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.lines.get(index)?.as_deref()
+    }
+}
+
+pub trait LyricsTranslator: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// One entry per input line; `None` leaves that line untranslated.
+    fn translate(
+        &self,
+        lines: &[&str],
+        source_language: Option<&str>,
+        target_language: &str,
+    ) -> Result<Vec<Option<String>>, String>;
+}
+
+/// Offline translator backed by an exact-match phrase table, optionally loaded from a
+/// `source<TAB>translation` glossary at `HEAVEN_LYRICS_GLOSSARY`. Without one it is a no-op.
+#[derive(Debug, Default)]
+pub struct LocalStubTranslator {
+    phrases: HashMap<String, String>,
+}
+
+impl LocalStubTranslator {
+    pub fn with_phrases<I, K, V>(phrases: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self {
+            phrases: phrases
+                .into_iter()
+                .map(|(k, v)| (k.into().trim().to_string(), v.into()))
+                .collect(),
+        }
+    }
+
+    pub fn from_glossary_file(path: &std::path::Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed reading lyrics glossary {}: {e}", path.display()))?;
+        Ok(Self::with_phrases(raw.lines().filter_map(|line| {
+            let (source, target) = line.split_once('\t')?;
+            (!source.trim().is_empty() && !target.trim().is_empty())
+                .then(|| (source.to_string(), target.trim().to_string()))
+        })))
+    }
+}
+
+impl LyricsTranslator for LocalStubTranslator {
+    fn name(&self) -> &'static str {
+        "local-stub"
+    }
+
+    fn translate(
+        &self,
+        lines: &[&str],
+        _source_language: Option<&str>,
+        _target_language: &str,
+    ) -> Result<Vec<Option<String>>, String> {
+        Ok(lines
+            .iter()
+            .map(|line| self.phrases.get(line.trim()).cloned())
+            .collect())
+    }
+}
+
+/// Translator plus target language, resolved from `HEAVEN_LYRICS_TRANSLATOR` and settings.
+pub struct LyricsTranslationConfig {
+    pub translator: Box<dyn LyricsTranslator>,
+    pub target_language: String,
+}
+
+impl LyricsTranslationConfig {
+    pub fn configured(db: Option<&Arc<Mutex<MusicDb>>>) -> Option<Self> {
+        let target_language = std::env::var("HEAVEN_LYRICS_TRANSLATION_LANGUAGE")
+            .ok()
+            .or_else(|| {
+                db.and_then(|handle| handle.lock().ok())
+                    .and_then(|db| db.get_setting(LYRICS_TRANSLATION_LANGUAGE_SETTING))
+            })
+            .map(|lang| lang.trim().to_string())
+            .filter(|lang| !lang.is_empty())?;
+
+        let translator: Box<dyn LyricsTranslator> = match std::env::var("HEAVEN_LYRICS_TRANSLATOR")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "stub" | "local" | "local-stub" => {
+                let glossary = std::env::var("HEAVEN_LYRICS_GLOSSARY")
+                    .ok()
+                    .filter(|path| !path.trim().is_empty());
+                match glossary {
+                    Some(path) => Box::new(
+                        LocalStubTranslator::from_glossary_file(std::path::Path::new(path.trim()))
+                            .unwrap_or_else(|err| {
+                                log::warn!("[lyrics] {}", err);
+                                LocalStubTranslator::default()
+                            }),
+                    ),
+                    None => Box::new(LocalStubTranslator::default()),
+                }
+            }
+            other => {
+                log::warn!(
+                    "[lyrics] unknown translator '{}'; translations disabled",
+                    other
+                );
+                return None;
+            }
+        };
+        Some(Self {
+            translator,
+            target_language,
+        })
+    }
+}
+
+/// Rebuild `lyrics.layers`: LRC language tracks first, then the translator (when no LRC
+/// track already covers the target language), then built-in romanization.
+pub fn attach_lyrics_layers(
+    lyrics: &mut ResolvedLyrics,
+    translation: Option<&LyricsTranslationConfig>,
+) {
+    let display_lines = display_lines(lyrics);
+    let mut layers = Vec::new();
+
+    if !lyrics.synced_lines.is_empty() {
+        for track in &lyrics.alternate_tracks {
+            if let Some(layer) = layer_from_language_track(&lyrics.synced_lines, track) {
+                layers.push(layer);
+            }
+        }
+    }
+
+    if let Some(config) = translation {
+        let already_present = layers.iter().any(|layer: &LyricsLayer| {
+            layer.kind == LyricsLayerKind::Translation
+                && layer
+                    .language
+                    .as_deref()
+                    .is_some_and(|lang| same_language(lang, &config.target_language))
+        });
+        let source_is_target = lyrics
+            .synced_language
+            .as_deref()
+            .is_some_and(|lang| same_language(lang, &config.target_language));
+        if !already_present && !source_is_target && !display_lines.is_empty() {
+            match config.translator.translate(
+                &display_lines,
+                lyrics.synced_language.as_deref(),
+                &config.target_language,
+            ) {
+                Ok(lines)
+                    if lines.len() == display_lines.len() && lines.iter().any(Option::is_some) =>
+                {
+                    layers.push(LyricsLayer {
+                        kind: LyricsLayerKind::Translation,
+                        language: Some(config.target_language.clone()),
+                        origin: LyricsLayerOrigin::Translator,
+                        lines,
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!(
+                    "[lyrics] {} translation failed: {}",
+                    config.translator.name(),
+                    err
+                ),
+            }
+        }
+    }
+
+    if !layers
+        .iter()
+        .any(|layer| layer.kind == LyricsLayerKind::Romanization)
+    {
+        if let Some(lines) =
+            super::romanize::romanize_lines(&display_lines, lyrics.synced_language.as_deref())
+        {
+            layers.push(LyricsLayer {
+                kind: LyricsLayerKind::Romanization,
+                language: None,
+                origin: LyricsLayerOrigin::BuiltinRomanizer,
+                lines,
+            });
+        }
+    }
+
+    lyrics.layers = layers;
+}
+
+/// The lines layers are aligned to, in display order.
+pub fn display_lines(lyrics: &ResolvedLyrics) -> Vec<&str> {
+    if !lyrics.synced_lines.is_empty() {
+        return lyrics
+            .synced_lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+    }
+    lyrics
+        .plain_lyrics
+        .as_deref()
+        .map(|plain| {
+            plain
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn layer_from_language_track(
+    primary: &[LyricsLine],
+    track: &LyricsLanguageTrack,
+) -> Option<LyricsLayer> {
+    let mut lines = Vec::with_capacity(primary.len());
+    let mut matched = 0usize;
+    for line in primary {
+        let candidate = track
+            .lines
+            .iter()
+            .filter(|alt| (alt.start_sec - line.start_sec).abs() <= ALIGN_TOLERANCE_SEC)
+            .min_by(|a, b| {
+                let da = (a.start_sec - line.start_sec).abs();
+                let db = (b.start_sec - line.start_sec).abs();
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|alt| alt.text.trim().to_string())
+            .filter(|text| !text.is_empty());
+        matched += usize::from(candidate.is_some());
+        lines.push(candidate);
+    }
+    if matched == 0 {
+        return None;
+    }
+
+    let kind = if track.language.as_deref().is_some_and(is_romanization_tag) {
+        LyricsLayerKind::Romanization
+    } else {
+        LyricsLayerKind::Translation
+    };
+    Some(LyricsLayer {
+        kind,
+        language: track.language.clone(),
+        origin: LyricsLayerOrigin::Lrc,
+        lines,
+    })
+}
+
+/// `ja-Latn`, `romaji`, `zh-pinyin`, `ko-rom` and friends mark a romanization track.
+fn is_romanization_tag(language: &str) -> bool {
+    let lower = language.trim().to_ascii_lowercase();
+    lower.split(['-', '_']).any(|part| {
+        matches!(
+            part,
+            "latn" | "romaji" | "rom" | "roman" | "pinyin" | "romaja"
+        )
+    }) || matches!(lower.as_str(), "romaji" | "pinyin" | "romanized")
+}
+
+fn same_language(a: &str, b: &str) -> bool {
+    let primary = |tag: &str| {
+        tag.trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    primary(a) == primary(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::{LyricsProvenance, LyricsSource};
+
+    fn resolved(raw: &str) -> ResolvedLyrics {
+        ResolvedLyrics::from_raw_text(
+            raw,
+            LyricsSource::SidecarSynced,
+            LyricsSource::SidecarPlain,
+            LyricsProvenance::default(),
+            0,
+        )
+        .expect("lyrics")
+    }
+
+    #[test]
+    fn aligns_lrc_language_tracks_and_adds_builtin_romanization() {
+        let mut lyrics = resolved(
+            "[la:ja]\n[00:01.00]ゆめ\n[00:03.00]そら\n\
+             [la:en]\n[00:01.10]dream\n[00:03.00]sky\n\
+             [la:ja-Latn]\n[00:01.00]yume",
+        );
+        attach_lyrics_layers(&mut lyrics, None);
+
+        assert_eq!(lyrics.layers.len(), 2);
+        assert_eq!(lyrics.layers[0].kind, LyricsLayerKind::Translation);
+        assert_eq!(lyrics.layers[0].line(0), Some("dream"));
+        assert_eq!(lyrics.layers[0].line(1), Some("sky"));
+        assert_eq!(lyrics.layers[1].kind, LyricsLayerKind::Romanization);
+        assert_eq!(lyrics.layers[1].origin, LyricsLayerOrigin::Lrc);
+        assert_eq!(lyrics.layers[1].line(1), None);
+    }
+
+    #[test]
+    fn translator_fills_missing_target_language() {
+        let mut lyrics = resolved("[00:01.00]사랑해\n[00:02.00]OK");
+        let config = LyricsTranslationConfig {
+            translator: Box::new(LocalStubTranslator::with_phrases([(
+                "사랑해",
+                "I love you",
+            )])),
+            target_language: "en".to_string(),
+        };
+        attach_lyrics_layers(&mut lyrics, Some(&config));
+
+        assert_eq!(lyrics.layers.len(), 2);
+        assert_eq!(lyrics.layers[0].label(), "Translation (en)");
+        assert_eq!(lyrics.layers[0].line(0), Some("I love you"));
+        assert_eq!(lyrics.layers[0].line(1), None);
+        assert_eq!(lyrics.layers[1].origin, LyricsLayerOrigin::BuiltinRomanizer);
+        assert_eq!(lyrics.layers[1].line(0), Some("saranghae"));
+    }
+}
@@ -0,0 +1,371 @@
+//! LRC parsing: line timestamps, enhanced LRC (A2) `<mm:ss.xx>` word timing,
+//! `[offset:]`, `[la:]`/`[lang:]` language sections, end-time markers and
+//! Walaoke `M:`/`F:`/`D:` duet voice prefixes.
+
+use std::cmp::Ordering;
+
+use super::{LyricsLanguageTrack, LyricsLine, LyricsVoice, LyricsWord};
+
+/// Two timestamps closer than this are treated as the same cue.
+pub(super) const SAME_CUE_EPSILON_SEC: f64 = 0.005;
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedLrc {
+    pub offset_ms: i64,
+    pub language: Option<String>,
+    pub lines: Vec<LyricsLine>,
+    pub alternate_tracks: Vec<LyricsLanguageTrack>,
+}
+
+#[derive(Debug, Default)]
+struct LrcSection {
+    language: Option<String>,
+    lines: Vec<LyricsLine>,
+    end_markers: Vec<f64>,
+}
+
+pub fn parse_lrc(raw: &str) -> ParsedLrc {
+    let mut offset_ms: i64 = 0;
+    let mut sections = vec![LrcSection::default()];
+    let mut voice: Option<LyricsVoice> = None;
+
+    for raw_line in raw.lines() {
+        let mut remaining = raw_line.trim();
+        if remaining.is_empty() {
+            continue;
+        }
+
+        let mut timestamps = Vec::new();
+        while let Some(stripped) = remaining.strip_prefix('[') {
+            let Some(close) = stripped.find(']') else {
+                break;
+            };
+            let tag = stripped[..close].trim();
+            if let Some(start_sec) = parse_lrc_timestamp(tag) {
+                timestamps.push(start_sec);
+            } else if timestamps.is_empty() {
+                apply_metadata_tag(tag, &mut offset_ms, &mut sections);
+            } else {
+                break;
+            }
+            remaining = stripped[close + 1..].trim_start();
+        }
+
+        if timestamps.is_empty() {
+            continue;
+        }
+
+        let mut body = remaining.trim();
+        if let Some((next_voice, rest)) = split_voice_prefix(body) {
+            voice = Some(next_voice);
+            body = rest;
+        }
+
+        let section = sections.last_mut().expect("at least one LRC section");
+        let (text, words, trailing_end) = parse_enhanced_words(body);
+        if text.is_empty() {
+            // A bare timestamp closes the previous line (LRC end-time marker).
+            section.end_markers.extend(timestamps);
+            continue;
+        }
+
+        let first_stamp = timestamps[0];
+        for start_sec in timestamps {
+            // Repeated choruses reuse the same word timing relative to the line start.
+            let shift = start_sec - first_stamp;
+            let words = words
+                .iter()
+                .map(|word| LyricsWord {
+                    start_sec: word.start_sec + shift,
+                    end_sec: word.end_sec.map(|end| end + shift),
+                    text: word.text.clone(),
+                })
+                .collect();
+            section.lines.push(LyricsLine {
+                start_sec,
+                end_sec: trailing_end.map(|end| end + shift),
+                text: text.clone(),
+                words,
+                voice,
+            });
+        }
+    }
+
+    let offset_sec = offset_ms as f64 / 1000.0;
+    let mut tracks: Vec<LyricsLanguageTrack> = Vec::new();
+    let mut primary: Option<(Option<String>, Vec<LyricsLine>)> = None;
+    for section in sections {
+        let language = section.language.clone();
+        let lines = finalize_section(section, offset_sec);
+        if lines.is_empty() {
+            continue;
+        }
+        if primary.is_none() {
+            let (lines, duplicates) = split_duplicate_cues(lines);
+            if !duplicates.is_empty() {
+                tracks.push(LyricsLanguageTrack {
+                    language: None,
+                    lines: duplicates,
+                });
+            }
+            primary = Some((language, lines));
+        } else {
+            tracks.push(LyricsLanguageTrack { language, lines });
+        }
+    }
+
+    let (language, lines) = primary.unwrap_or_default();
+    ParsedLrc {
+        offset_ms,
+        language,
+        lines,
+        alternate_tracks: tracks,
+    }
+}
+
+fn apply_metadata_tag(tag: &str, offset_ms: &mut i64, sections: &mut Vec<LrcSection>) {
+    let Some((key, value)) = tag.split_once(':') else {
+        return;
+    };
+    let value = value.trim();
+    match key.trim().to_ascii_lowercase().as_str() {
+        "offset" => {
+            if let Ok(parsed) = value.trim_start_matches('+').parse::<i64>() {
+                *offset_ms = parsed;
+            }
+        }
+        "la" | "lang" | "language" if !value.is_empty() => {
+            let current = sections.last_mut().expect("at least one LRC section");
+            if current.lines.is_empty() && current.end_markers.is_empty() {
+                current.language = Some(value.to_string());
+            } else {
+                sections.push(LrcSection {
+                    language: Some(value.to_string()),
+                    ..LrcSection::default()
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn split_voice_prefix(body: &str) -> Option<(LyricsVoice, &str)> {
+    let (prefix, rest) = body.split_once(':')?;
+    let voice = match prefix.trim() {
+        "M" => LyricsVoice::Male,
+        "F" => LyricsVoice::Female,
+        "D" => LyricsVoice::Duet,
+        _ => return None,
+    };
+    Some((voice, rest.trim_start()))
+}
+
+/// Split `<mm:ss.xx>word <mm:ss.xx>word<mm:ss.xx>` into words. Returns the plain line
+/// text, the words, and the trailing timestamp (end of the last word) when present.
+fn parse_enhanced_words(body: &str) -> (String, Vec<LyricsWord>, Option<f64>) {
+    if !body.contains('<') {
+        return (body.trim().to_string(), Vec::new(), None);
+    }
+
+    let mut words: Vec<LyricsWord> = Vec::new();
+    let mut text = String::new();
+    let mut pending_start: Option<f64> = None;
+    let mut trailing_end = None;
+    let mut rest = body;
+
+    loop {
+        let next_tag = rest.find('<').and_then(|open| {
+            let close = rest[open..].find('>')? + open;
+            let stamp = parse_lrc_timestamp(rest[open + 1..close].trim())?;
+            Some((open, close, stamp))
+        });
+        let (chunk, stamp) = match next_tag {
+            Some((open, close, stamp)) => {
+                let chunk = &rest[..open];
+                rest = &rest[close + 1..];
+                (chunk, Some(stamp))
+            }
+            None => (rest, None),
+        };
+
+        text.push_str(chunk);
+        let word_text = chunk.trim();
+        if let Some(start_sec) = pending_start {
+            if !word_text.is_empty() {
+                if let Some(previous) = words.last_mut() {
+                    previous.end_sec.get_or_insert(start_sec);
+                }
+                words.push(LyricsWord {
+                    start_sec,
+                    end_sec: None,
+                    text: word_text.to_string(),
+                });
+            }
+        }
+
+        match stamp {
+            Some(stamp) => {
+                pending_start = Some(stamp);
+                trailing_end = Some(stamp);
+            }
+            None => {
+                if !word_text.is_empty() {
+                    trailing_end = None;
+                }
+                break;
+            }
+        }
+        if rest.is_empty() {
+            break;
+        }
+    }
+
+    if let (Some(end), Some(last)) = (trailing_end, words.last_mut()) {
+        if end > last.start_sec {
+            last.end_sec = Some(end);
+        }
+    }
+    let trailing_end = trailing_end.filter(|end| words.last().is_some_and(|w| *end > w.start_sec));
+    (
+        text.split_whitespace().collect::<Vec<_>>().join(" "),
+        words,
+        trailing_end,
+    )
+}
+
+fn finalize_section(section: LrcSection, offset_sec: f64) -> Vec<LyricsLine> {
+    let mut lines = section.lines;
+    let mut markers = section.end_markers;
+    lines.sort_by(|a, b| {
+        a.start_sec
+            .partial_cmp(&b.start_sec)
+            .unwrap_or(Ordering::Equal)
+    });
+    markers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let starts: Vec<f64> = lines.iter().map(|line| line.start_sec).collect();
+    for (idx, line) in lines.iter_mut().enumerate() {
+        let next_start = starts[idx + 1..]
+            .iter()
+            .copied()
+            .find(|start| *start > line.start_sec + SAME_CUE_EPSILON_SEC);
+        let marker = markers.iter().copied().find(|marker| {
+            *marker > line.start_sec && next_start.is_none_or(|next| *marker <= next)
+        });
+        if line.end_sec.is_none() {
+            line.end_sec = marker.or(next_start);
+        }
+        if let (Some(end), Some(last)) = (line.end_sec, line.words.last_mut()) {
+            last.end_sec.get_or_insert(end);
+        }
+    }
+
+    if offset_sec != 0.0 {
+        let shift = |value: f64| (value - offset_sec).max(0.0);
+        for line in &mut lines {
+            line.start_sec = shift(line.start_sec);
+            line.end_sec = line.end_sec.map(shift);
+            for word in &mut line.words {
+                word.start_sec = shift(word.start_sec);
+                word.end_sec = word.end_sec.map(shift);
+            }
+        }
+    }
+    lines
+}
+
+/// Files that interleave a translation under each line reuse the same timestamp;
+/// keep the first occurrence and move the rest into a parallel track.
+fn split_duplicate_cues(lines: Vec<LyricsLine>) -> (Vec<LyricsLine>, Vec<LyricsLine>) {
+    let mut primary: Vec<LyricsLine> = Vec::with_capacity(lines.len());
+    let mut duplicates = Vec::new();
+    for line in lines {
+        let is_duplicate = primary.last().is_some_and(|previous| {
+            (previous.start_sec - line.start_sec).abs() < SAME_CUE_EPSILON_SEC
+                && previous.text != line.text
+        });
+        if is_duplicate {
+            duplicates.push(line);
+        } else {
+            primary.push(line);
+        }
+    }
+    (primary, duplicates)
+}
+
+pub(crate) fn parse_lrc_timestamp(stamp: &str) -> Option<f64> {
+    let (minutes, rest) = stamp.split_once(':')?;
+    let minutes = minutes.parse::<u64>().ok()?;
+    let seconds = rest.parse::<f64>().ok()?;
+    if seconds >= 60.0 {
+        return None;
+    }
+    Some(minutes as f64 * 60.0 + seconds)
+}
+
+/// Format seconds as an LRC `mm:ss.xx` timestamp (without brackets).
+pub fn format_lrc_timestamp(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        (centis / 100) % 60,
+        centis % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_line_timestamps_and_end_markers() {
+        let parsed = parse_lrc("[ar:Someone]\n[00:01.00]one\n[00:03.00]two\n[00:05.50]\n");
+        assert_eq!(parsed.lines.len(), 2);
+        assert_eq!(parsed.lines[0].end_sec, Some(3.0));
+        assert_eq!(parsed.lines[1].end_sec, Some(5.5));
+        assert!(parsed.lines[0].words.is_empty());
+    }
+
+    #[test]
+    fn parses_enhanced_word_timing() {
+        let parsed = parse_lrc("[00:10.00]<00:10.00>Hello <00:10.50>big <00:11.00>world<00:12.00>");
+        let line = &parsed.lines[0];
+        assert_eq!(line.text, "Hello big world");
+        assert_eq!(line.words.len(), 3);
+        assert_eq!(line.words[0].end_sec, Some(10.5));
+        assert_eq!(line.words[2].start_sec, 11.0);
+        assert_eq!(line.words[2].end_sec, Some(12.0));
+        assert_eq!(line.end_sec, Some(12.0));
+    }
+
+    #[test]
+    fn applies_offset_tag() {
+        let parsed = parse_lrc("[offset:+500]\n[00:02.00]<00:02.00>a <00:03.00>b");
+        assert_eq!(parsed.offset_ms, 500);
+        assert!((parsed.lines[0].start_sec - 1.5).abs() < 1e-9);
+        assert!((parsed.lines[0].words[1].start_sec - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn splits_language_sections_and_duplicate_cues() {
+        let parsed = parse_lrc(
+            "[la:ja]\n[00:01.00]こんにちは\n[00:01.00]hello\n[00:02.00]さようなら\n[la:en]\n[00:01.00]hi\n",
+        );
+        assert_eq!(parsed.language.as_deref(), Some("ja"));
+        assert_eq!(parsed.lines.len(), 2);
+        assert_eq!(parsed.alternate_tracks.len(), 2);
+        assert_eq!(parsed.alternate_tracks[0].language, None);
+        assert_eq!(parsed.alternate_tracks[0].lines[0].text, "hello");
+        assert_eq!(parsed.alternate_tracks[1].language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn tracks_walaoke_duet_voices() {
+        let parsed = parse_lrc("[00:01.00]M: first\n[00:02.00]second\n[00:03.00]F: third");
+        assert_eq!(parsed.lines[0].voice, Some(LyricsVoice::Male));
+        assert_eq!(parsed.lines[1].voice, Some(LyricsVoice::Male));
+        assert_eq!(parsed.lines[2].voice, Some(LyricsVoice::Female));
+        assert_eq!(parsed.lines[2].text, "third");
+    }
+}
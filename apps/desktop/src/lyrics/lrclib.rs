@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use super::providers::{LyricsProvider, LyricsProviderKind};
+use super::{
+    maybe_log_suspicious_synced_timing, normalize_lookup_text, parse_lrc, plain_from_synced_lines,
+    LyricsProvenance, LyricsSource, LyricsTrackSignature, ResolvedLyrics,
+};
+
+const LRCLIB_BASE_URL: &str = "https://lrclib.net";
+const LRCLIB_TIMEOUT_SECS: u64 = 12;
+const DEFAULT_LRCLIB_USER_AGENT: &str =
+    "heaven-desktop/0.1 (https://github.com/dotheaven/dotheaven)";
+/// Minimum spacing between requests to one LRCLIB host, shared by playback and prefetch.
+const DEFAULT_LRCLIB_MIN_INTERVAL_MS: u64 = 250;
+/// Fallback pause after HTTP 429 when the response carries no `Retry-After`.
+const DEFAULT_LRCLIB_RATE_LIMIT_BACKOFF_SECS: u64 = 60;
+
+#[derive(Debug, Default)]
+struct LrclibThrottle {
+    next_slot: Option<Instant>,
+    blocked_until: Option<Instant>,
+}
+
+fn lrclib_throttles() -> &'static Mutex<HashMap<String, LrclibThrottle>> {
+    static THROTTLES: OnceLock<Mutex<HashMap<String, LrclibThrottle>>> = OnceLock::new();
+    THROTTLES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+#[derive(Debug, Clone)]
+struct LrclibRecord {
+    id: Option<i64>,
+    track_name: String,
+    artist_name: String,
+    album_name: String,
+    duration_sec: Option<u64>,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+/// LRCLIB signature lookup (`/api/get-cached`, `/api/get`) with `/api/search` fallback.
+pub struct LrclibProvider {
+    base_url: String,
+}
+
+impl LrclibProvider {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Self { base_url }
+    }
+
+    pub fn from_env() -> Self {
+        let base_url = std::env::var("HEAVEN_LRCLIB_BASE_URL")
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| LRCLIB_BASE_URL.to_string());
+        Self::new(base_url)
+    }
+
+    fn fetch_signature(
+        &self,
+        signature: &LyricsTrackSignature,
+        cached_only: bool,
+    ) -> Result<Option<(LrclibRecord, String)>, String> {
+        let duration = signature.duration_sec.ok_or_else(|| {
+            "duration is required for /api/get and /api/get-cached signature lookup".to_string()
+        })?;
+
+        let endpoint = if cached_only {
+            "api/get-cached"
+        } else {
+            "api/get"
+        };
+        let url = format!(
+            "{}/{endpoint}?track_name={}&artist_name={}&album_name={}&duration={duration}",
+            self.base_url,
+            urlencoding::encode(signature.track_name.as_str()),
+            urlencoding::encode(signature.artist_name.as_str()),
+            urlencoding::encode(signature.album_name.as_str()),
+        );
+
+        let Some(json) = self.get_json(&url)? else {
+            return Ok(None);
+        };
+        Ok(parse_lrclib_record(&json).map(|record| (record, url)))
+    }
+
+    fn fetch_search(
+        &self,
+        signature: &LyricsTrackSignature,
+    ) -> Result<Option<(LrclibRecord, String)>, String> {
+        let mut url = format!(
+            "{}/api/search?track_name={}&artist_name={}",
+            self.base_url,
+            urlencoding::encode(signature.track_name.as_str()),
+            urlencoding::encode(signature.artist_name.as_str())
+        );
+        if !signature.album_name.trim().is_empty() {
+            url.push_str("&album_name=");
+            url.push_str(&urlencoding::encode(signature.album_name.as_str()));
+        }
+
+        let Some(json) = self.get_json(&url)? else {
+            return Ok(None);
+        };
+        let Some(candidates) = json.as_array() else {
+            return Ok(None);
+        };
+
+        let mut best: Option<(i32, LrclibRecord)> = None;
+        for candidate in candidates {
+            let Some(record) = parse_lrclib_record(candidate) else {
+                continue;
+            };
+            let score = score_search_candidate(&record, signature);
+            match &best {
+                Some((best_score, _)) if score <= *best_score => {}
+                _ => best = Some((score, record)),
+            }
+        }
+
+        let Some((score, record)) = best else {
+            return Ok(None);
+        };
+        if score < 120 {
+            return Ok(None);
+        }
+        Ok(Some((record, url)))
+    }
+
+    /// Time left before this host may be queried again after a 429, if any.
+    pub fn backoff_remaining(&self) -> Option<Duration> {
+        let throttles = lrclib_throttles().lock().ok()?;
+        let blocked_until = throttles.get(&self.base_url)?.blocked_until?;
+        let remaining = blocked_until.saturating_duration_since(Instant::now());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    fn get_json(&self, url: &str) -> Result<Option<Value>, String> {
+        self.acquire_slot()?;
+        match lrclib_get_json(url) {
+            Err(LrclibHttpError::RateLimited { retry_after_secs }) => {
+                let backoff = retry_after_secs.unwrap_or(DEFAULT_LRCLIB_RATE_LIMIT_BACKOFF_SECS);
+                if let Ok(mut throttles) = lrclib_throttles().lock() {
+                    throttles
+                        .entry(self.base_url.clone())
+                        .or_default()
+                        .blocked_until = Some(Instant::now() + Duration::from_secs(backoff));
+                }
+                log::warn!("[lyrics] LRCLIB rate limited; pausing requests for {backoff}s");
+                Err(format!("LRCLIB rate limited (429); retry in {backoff}s"))
+            }
+            Err(LrclibHttpError::Other(err)) => Err(err),
+            Ok(json) => Ok(json),
+        }
+    }
+
+    /// Block until the per-host request slot opens; fail fast while a 429 backoff is active.
+    fn acquire_slot(&self) -> Result<(), String> {
+        let wait = {
+            let mut throttles = lrclib_throttles()
+                .lock()
+                .map_err(|e| format!("LRCLIB throttle lock failed: {e}"))?;
+            let throttle = throttles.entry(self.base_url.clone()).or_default();
+            let now = Instant::now();
+            if let Some(blocked_until) = throttle.blocked_until.filter(|until| *until > now) {
+                return Err(format!(
+                    "LRCLIB rate limited; retry in {}s",
+                    blocked_until.duration_since(now).as_secs().max(1)
+                ));
+            }
+            let slot = throttle.next_slot.filter(|slot| *slot > now).unwrap_or(now);
+            throttle.next_slot = Some(slot + lrclib_min_interval());
+            slot.duration_since(now)
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+        Ok(())
+    }
+}
+
+impl LyricsProvider for LrclibProvider {
+    fn kind(&self) -> LyricsProviderKind {
+        LyricsProviderKind::Lrclib
+    }
+
+    fn lookup(
+        &self,
+        signature: &LyricsTrackSignature,
+        now: i64,
+    ) -> Result<Option<ResolvedLyrics>, String> {
+        if signature.duration_sec.is_some() {
+            if let Some((record, url)) = self.fetch_signature(signature, true)? {
+                return Ok(Some(record_to_lyrics(
+                    record,
+                    LyricsSource::LrclibCached,
+                    url,
+                    now,
+                )));
+            }
+            if let Some((record, url)) = self.fetch_signature(signature, false)? {
+                return Ok(Some(record_to_lyrics(
+                    record,
+                    LyricsSource::LrclibLive,
+                    url,
+                    now,
+                )));
+            }
+        }
+
+        if let Some((record, url)) = self.fetch_search(signature)? {
+            return Ok(Some(record_to_lyrics(
+                record,
+                LyricsSource::LrclibSearch,
+                url,
+                now,
+            )));
+        }
+
+        Ok(None)
+    }
+}
+
+enum LrclibHttpError {
+    RateLimited { retry_after_secs: Option<u64> },
+    Other(String),
+}
+
+fn lrclib_get_json(url: &str) -> Result<Option<Value>, LrclibHttpError> {
+    let request = ureq::get(url)
+        .header("User-Agent", lrclib_user_agent().as_str())
+        .config()
+        .timeout_global(Some(Duration::from_secs(LRCLIB_TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build();
+
+    let mut response = request
+        .call()
+        .map_err(|e| LrclibHttpError::Other(format!("LRCLIB request failed ({url}): {e}")))?;
+    let status = response.status().as_u16();
+    if status == 429 {
+        let retry_after_secs = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        return Err(LrclibHttpError::RateLimited { retry_after_secs });
+    }
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .unwrap_or_else(|_| String::new());
+
+    if status == 404 {
+        return Ok(None);
+    }
+    if status >= 400 {
+        return Err(LrclibHttpError::Other(format!(
+            "LRCLIB request failed ({status}) for {url}: {}",
+            body.trim()
+        )));
+    }
+
+    serde_json::from_str::<Value>(&body)
+        .map(Some)
+        .map_err(|e| LrclibHttpError::Other(format!("Failed parsing LRCLIB JSON ({url}): {e}")))
+}
+
+fn lrclib_min_interval() -> Duration {
+    let millis = std::env::var("HEAVEN_LRCLIB_MIN_INTERVAL_MS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_LRCLIB_MIN_INTERVAL_MS);
+    Duration::from_millis(millis)
+}
+
+fn lrclib_user_agent() -> String {
+    std::env::var("HEAVEN_LRCLIB_USER_AGENT")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_LRCLIB_USER_AGENT.to_string())
+}
+
+fn parse_lrclib_record(value: &Value) -> Option<LrclibRecord> {
+    let track_name = value.get("trackName")?.as_str()?.trim().to_string();
+    if track_name.is_empty() {
+        return None;
+    }
+    let artist_name = value
+        .get("artistName")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or("Unknown Artist")
+        .to_string();
+    let album_name = value
+        .get("albumName")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or("")
+        .to_string();
+    let id = value.get("id").and_then(Value::as_i64);
+    let duration_sec = value.get("duration").and_then(Value::as_u64);
+    let plain_lyrics = value
+        .get("plainLyrics")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+    let synced_lyrics = value
+        .get("syncedLyrics")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+
+    Some(LrclibRecord {
+        id,
+        track_name,
+        artist_name,
+        album_name,
+        duration_sec,
+        plain_lyrics,
+        synced_lyrics,
+    })
+}
+
+fn record_to_lyrics(
+    record: LrclibRecord,
+    source: LyricsSource,
+    url: String,
+    now: i64,
+) -> ResolvedLyrics {
+    let parsed = record
+        .synced_lyrics
+        .as_deref()
+        .map(parse_lrc)
+        .unwrap_or_default();
+    let synced_lines = parsed.lines;
+    maybe_log_suspicious_synced_timing(
+        "lrclib",
+        &record.track_name,
+        &record.artist_name,
+        record.duration_sec,
+        &synced_lines,
+    );
+    let plain_lyrics = record
+        .plain_lyrics
+        .or_else(|| plain_from_synced_lines(&synced_lines));
+
+    ResolvedLyrics {
+        plain_lyrics,
+        synced_lyrics: record.synced_lyrics,
+        synced_lines,
+        synced_language: parsed.language,
+        alternate_tracks: parsed.alternate_tracks,
+        layers: Vec::new(),
+        source,
+        provenance: LyricsProvenance::new(LyricsProviderKind::Lrclib, Some(url)),
+        lrclib_id: record.id,
+        fetched_at_epoch_sec: now,
+    }
+}
+
+fn score_search_candidate(record: &LrclibRecord, signature: &LyricsTrackSignature) -> i32 {
+    let track_a = normalize_lookup_text(&record.track_name);
+    let track_b = normalize_lookup_text(&signature.track_name);
+    let artist_a = normalize_lookup_text(&record.artist_name);
+    let artist_b = normalize_lookup_text(&signature.artist_name);
+    let album_a = normalize_lookup_text(&record.album_name);
+    let album_b = normalize_lookup_text(&signature.album_name);
+
+    let mut score = 0;
+
+    if track_a == track_b {
+        score += 100;
+    } else if track_a.contains(&track_b) || track_b.contains(&track_a) {
+        score += 45;
+    }
+
+    if artist_a == artist_b {
+        score += 80;
+    } else if artist_a.contains(&artist_b) || artist_b.contains(&artist_a) {
+        score += 35;
+    }
+
+    if !album_b.is_empty() {
+        if album_a == album_b {
+            score += 30;
+        } else if album_a.contains(&album_b) || album_b.contains(&album_a) {
+            score += 15;
+        }
+    }
+
+    if let (Some(expected), Some(found)) = (signature.duration_sec, record.duration_sec) {
+        let diff = expected.abs_diff(found);
+        if diff <= 2 {
+            score += 40;
+        } else if diff <= 5 {
+            score += 22;
+        } else if diff <= 10 {
+            score += 8;
+        } else {
+            score -= 10;
+        }
+    }
+
+    // Prefer results that have synced lyrics, but only when the duration is
+    // a reasonable match (within 15s) so we don't pick a different mix/edit
+    // just because it happens to have synced lyrics.
+    if record
+        .synced_lyrics
+        .as_ref()
+        .is_some_and(|s| !s.trim().is_empty())
+    {
+        let duration_close = match (signature.duration_sec, record.duration_sec) {
+            (Some(expected), Some(found)) => expected.abs_diff(found) <= 15,
+            _ => true, // no duration info — give benefit of the doubt
+        };
+        if duration_close {
+            score += 50;
+        }
+    }
+
+    score
+}
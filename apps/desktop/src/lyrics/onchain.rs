@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::providers::{LyricsProvider, LyricsProviderKind};
+use super::{LyricsProvenance, LyricsSource, LyricsTrackSignature, ResolvedLyrics};
+use crate::scrobble::SubmitScrobbleInput;
+use crate::shared::gateways::{GatewayPool, GatewayResource, Integrity};
+
+const LYRICS_REF_FETCH_TIMEOUT_SECS: u64 = 12;
+
+type LyricsRefReader = dyn Fn(&str) -> Result<Option<String>, String> + Send + Sync;
+
+/// Lyrics previously published via `upload_lyrics_to_arweave` and anchored on the
+/// ScrobbleV4 contract (`getTrackLyrics(trackId)`).
+pub struct OnchainRefProvider {
+    read_ref: Box<LyricsRefReader>,
+}
+
+impl OnchainRefProvider {
+    pub fn new() -> Self {
+        Self::with_ref_reader(crate::scrobble::read_track_lyrics_ref_readonly)
+    }
+
+    /// Override how a track id resolves to a lyrics ref (used by tests and stubs).
+    pub fn with_ref_reader(
+        read_ref: impl Fn(&str) -> Result<Option<String>, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            read_ref: Box::new(read_ref),
+        }
+    }
+}
+
+impl Default for OnchainRefProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LyricsProvider for OnchainRefProvider {
+    fn kind(&self) -> LyricsProviderKind {
+        LyricsProviderKind::Onchain
+    }
+
+    fn lookup(
+        &self,
+        signature: &LyricsTrackSignature,
+        now: i64,
+    ) -> Result<Option<ResolvedLyrics>, String> {
+        let track_id = match signature.track_id.as_deref().map(str::trim) {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => crate::scrobble::derive_track_id(&SubmitScrobbleInput {
+                artist: signature.artist_name.clone(),
+                title: signature.track_name.clone(),
+                album: Some(signature.album_name.clone()).filter(|a| !a.trim().is_empty()),
+                mbid: None,
+                ip_id: None,
+                duration_sec: signature.duration_sec.unwrap_or(0) as u32,
+                played_at_sec: 0,
+            })?,
+        };
+
+        let Some(lyrics_ref) = (self.read_ref)(&track_id)? else {
+            return Ok(None);
+        };
+        let payload = match GatewayResource::from_storage_ref(&lyrics_ref) {
+            Some(resource) => fetch_pooled_lyrics_payload(&resource)?,
+            None => {
+                let url = crate::shared::ipfs::resolve_storage_ref_url(&lyrics_ref)
+                    .ok_or_else(|| format!("unsupported lyrics ref '{lyrics_ref}'"))?;
+                fetch_lyrics_payload(&url)?
+            }
+        };
+
+        let provenance = LyricsProvenance::new(LyricsProviderKind::Onchain, Some(lyrics_ref));
+        let synced = payload
+            .get("syncedLyrics")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if let Some(mut resolved) = ResolvedLyrics::from_raw_text(
+            synced,
+            LyricsSource::OnchainRef,
+            LyricsSource::OnchainRef,
+            provenance.clone(),
+            now,
+        ) {
+            if let Some(plain) = payload.get("plainLyrics").and_then(Value::as_str) {
+                let plain = plain.trim();
+                if !plain.is_empty() {
+                    resolved.plain_lyrics = Some(plain.to_string());
+                }
+            }
+            resolved.lrclib_id = payload.get("lrclibId").and_then(Value::as_i64);
+            return Ok(Some(resolved));
+        }
+
+        let plain = payload
+            .get("plainLyrics")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        Ok(ResolvedLyrics::from_plain_text(
+            plain,
+            LyricsSource::OnchainRef,
+            provenance,
+            now,
+        ))
+    }
+}
+
+/// Fetch a stored lyrics document through the gateway pool, so a gateway answering with
+/// something other than JSON loses to the next one.
+fn fetch_pooled_lyrics_payload(resource: &GatewayResource) -> Result<Value, String> {
+    let integrity = Integrity {
+        sha256: None,
+        check: Some(&check_lyrics_document),
+    };
+    let fetched = GatewayPool::shared().fetch(resource, integrity, None)?;
+    serde_json::from_slice::<Value>(&fetched.bytes)
+        .map_err(|e| format!("Failed parsing lyrics ref JSON ({}): {e}", fetched.url))
+}
+
+fn check_lyrics_document(bytes: &[u8]) -> Result<(), String> {
+    serde_json::from_slice::<Value>(bytes)
+        .map(|_| ())
+        .map_err(|e| format!("not a lyrics document: {e}"))
+}
+
+fn fetch_lyrics_payload(url: &str) -> Result<Value, String> {
+    let request = ureq::get(url)
+        .config()
+        .timeout_global(Some(Duration::from_secs(LYRICS_REF_FETCH_TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build();
+    let mut response = request
+        .call()
+        .map_err(|e| format!("lyrics ref fetch failed ({url}): {e}"))?;
+    let status = response.status().as_u16();
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .unwrap_or_else(|_| String::new());
+    if status >= 400 {
+        return Err(format!(
+            "lyrics ref fetch failed ({status}) for {url}: {}",
+            body.trim()
+        ));
+    }
+    serde_json::from_str::<Value>(&body)
+        .map_err(|e| format!("Failed parsing lyrics ref JSON ({url}): {e}"))
+}
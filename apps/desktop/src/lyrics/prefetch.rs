@@ -0,0 +1,271 @@
+//! Background lyrics warm-up for upcoming tracks, cache expiry, and coverage stats.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::embedded::EmbeddedTagsProvider;
+use super::lrclib::LrclibProvider;
+use super::providers::{LyricsProvider, LyricsProviderChain};
+use super::sidecar::SidecarProvider;
+use super::{
+    now_epoch_sec, LyricsSource, LyricsTrackSignature, NEGATIVE_CACHE_TTL_SECS,
+    REMOTE_CACHE_TTL_SECS,
+};
+use crate::music_db::MusicDb;
+
+/// How many queue entries after the current track get their lyrics resolved ahead of time.
+pub const QUEUE_PREFETCH_LOOKAHEAD: usize = 3;
+
+/// Extra pause after a prefetch that went to the network, on top of the LRCLIB throttle,
+/// so a whole-library run stays well under LRCLIB's fair-use limits.
+pub const PREFETCH_REMOTE_SPACING: Duration = Duration::from_millis(1200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsPrefetchOutcome {
+    /// Served by a sidecar file or embedded tags; nothing to warm.
+    Local,
+    /// Already fresh in `lyrics_cache`.
+    Cached,
+    /// Looked up remotely (hit or miss); caller should pace the next lookup.
+    Remote,
+}
+
+/// Resolve through the configured chain so remote results land in `lyrics_cache`.
+pub fn prefetch_lyrics(
+    signature: &LyricsTrackSignature,
+    db: &Arc<Mutex<MusicDb>>,
+) -> Result<LyricsPrefetchOutcome, String> {
+    let resolved = LyricsProviderChain::configured(Some(db)).resolve(signature, Some(db))?;
+    let outcome = if resolved.provenance.from_cache {
+        LyricsPrefetchOutcome::Cached
+    } else if resolved
+        .provenance
+        .provider
+        .is_some_and(|provider| !provider.is_remote())
+    {
+        LyricsPrefetchOutcome::Local
+    } else {
+        LyricsPrefetchOutcome::Remote
+    };
+    Ok(outcome)
+}
+
+/// Remaining LRCLIB 429 backoff for the configured host, so long runs can wait it out.
+pub fn lrclib_backoff_remaining() -> Option<Duration> {
+    LrclibProvider::from_env().backoff_remaining()
+}
+
+/// Drop expired rows using the same TTLs the resolver applies on read.
+pub fn purge_expired_lyrics_cache(db: &Arc<Mutex<MusicDb>>) -> Result<usize, String> {
+    let db = db
+        .lock()
+        .map_err(|e| format!("lyrics cache lock failed: {e}"))?;
+    db.delete_expired_lyrics_cache(
+        now_epoch_sec(),
+        REMOTE_CACHE_TTL_SECS,
+        NEGATIVE_CACHE_TTL_SECS,
+    )
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LyricsCoverage {
+    pub synced: usize,
+    pub plain: usize,
+    /// Confirmed misses (cached `no_match`) and instrumental entries.
+    pub none: usize,
+    /// Not found locally and never looked up remotely (or the cache row expired).
+    pub unchecked: usize,
+}
+
+impl LyricsCoverage {
+    pub fn total(&self) -> usize {
+        self.synced + self.plain + self.none + self.unchecked
+    }
+
+    pub fn summary(&self) -> String {
+        let total = self.total().max(1);
+        let pct = |count: usize| count * 100 / total;
+        format!(
+            "Lyrics coverage: {} synced ({}%), {} plain ({}%), {} none, {} unchecked",
+            self.synced,
+            pct(self.synced),
+            self.plain,
+            pct(self.plain),
+            self.none,
+            self.unchecked
+        )
+    }
+}
+
+/// Classify each track from local sources first, then `lyrics_cache` — no network.
+pub fn lyrics_coverage(
+    signatures: &[LyricsTrackSignature],
+    db: Option<&Arc<Mutex<MusicDb>>>,
+) -> Result<LyricsCoverage, String> {
+    let now = now_epoch_sec();
+    let local: [&dyn LyricsProvider; 2] = [&SidecarProvider, &EmbeddedTagsProvider];
+    let mut coverage = LyricsCoverage::default();
+
+    'tracks: for signature in signatures {
+        for provider in local {
+            if let Ok(Some(found)) = provider.lookup(signature, now) {
+                if found.synced_lines.is_empty() {
+                    coverage.plain += 1;
+                } else {
+                    coverage.synced += 1;
+                }
+                continue 'tracks;
+            }
+        }
+
+        let row = match db {
+            Some(handle) => handle
+                .lock()
+                .map_err(|e| format!("lyrics cache lock failed: {e}"))?
+                .get_lyrics_cache(&signature.cache_key())?,
+            None => None,
+        };
+        let Some(row) = row else {
+            coverage.unchecked += 1;
+            continue;
+        };
+        let has_text = |text: &Option<String>| {
+            text.as_deref()
+                .map(str::trim)
+                .is_some_and(|text| !text.is_empty())
+        };
+        let source = LyricsSource::from_db_key(&row.source);
+        if now.saturating_sub(row.fetched_at_epoch_sec) > source.cache_ttl_secs() {
+            coverage.unchecked += 1;
+        } else if source == LyricsSource::NoMatch {
+            coverage.none += 1;
+        } else if has_text(&row.synced_lyrics) {
+            coverage.synced += 1;
+        } else if has_text(&row.plain_lyrics) {
+            coverage.plain += 1;
+        } else {
+            coverage.none += 1;
+        }
+    }
+
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music_db::LyricsCacheRow;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "heaven-lyrics-{name}-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ))
+    }
+
+    fn signature(dir: &std::path::Path, name: &str) -> LyricsTrackSignature {
+        LyricsTrackSignature {
+            track_path: dir
+                .join(format!("{name}.flac"))
+                .to_string_lossy()
+                .to_string(),
+            track_name: name.to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: String::new(),
+            duration_sec: Some(180),
+            track_id: None,
+        }
+    }
+
+    fn cache_row(signature: &LyricsTrackSignature, source: &str, age_secs: i64) -> LyricsCacheRow {
+        LyricsCacheRow {
+            cache_key: signature.cache_key(),
+            track_name: signature.track_name.clone(),
+            artist_name: signature.artist_name.clone(),
+            album_name: signature.album_name.clone(),
+            duration_sec: Some(180),
+            plain_lyrics: (source != "no_match").then(|| "words".to_string()),
+            synced_lyrics: (source != "no_match").then(|| "[00:01.00]words".to_string()),
+            lrclib_id: None,
+            source: source.to_string(),
+            fetched_at_epoch_sec: now_epoch_sec() - age_secs,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn purge_drops_only_rows_past_their_ttl() {
+        let dir = temp_dir("purge");
+        let db = Arc::new(Mutex::new(MusicDb::open(&dir).unwrap()));
+        let rows = [
+            ("fresh-remote", "lrclib_live", REMOTE_CACHE_TTL_SECS - 60),
+            ("stale-remote", "lrclib_live", REMOTE_CACHE_TTL_SECS + 60),
+            ("fresh-miss", "no_match", NEGATIVE_CACHE_TTL_SECS - 60),
+            ("stale-miss", "no_match", NEGATIVE_CACHE_TTL_SECS + 60),
+            ("old-edit", "user_edited", REMOTE_CACHE_TTL_SECS * 10),
+        ];
+        for (name, source, age) in rows {
+            let row = cache_row(&signature(&dir, name), source, age);
+            db.lock().unwrap().upsert_lyrics_cache(&row).unwrap();
+        }
+
+        assert_eq!(purge_expired_lyrics_cache(&db).unwrap(), 2);
+        let guard = db.lock().unwrap();
+        for (name, _, _) in rows {
+            let kept = guard
+                .get_lyrics_cache(&signature(&dir, name).cache_key())
+                .unwrap()
+                .is_some();
+            assert_eq!(kept, !name.starts_with("stale"), "{name}");
+        }
+        drop(guard);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn coverage_counts_local_files_then_fresh_cache_rows() {
+        let dir = temp_dir("coverage");
+        let db = Arc::new(Mutex::new(MusicDb::open(&dir).unwrap()));
+        for name in ["synced-file", "plain-file"] {
+            std::fs::write(dir.join(format!("{name}.flac")), b"").unwrap();
+        }
+        std::fs::write(dir.join("synced-file.lrc"), "[00:01.00]hello\n").unwrap();
+        std::fs::write(dir.join("plain-file.txt"), "hello\n").unwrap();
+        for (name, source, age) in [
+            ("cached-synced", "lrclib_live", 60),
+            ("cached-miss", "no_match", 60),
+            ("expired-synced", "lrclib_live", REMOTE_CACHE_TTL_SECS + 60),
+            ("expired-miss", "no_match", NEGATIVE_CACHE_TTL_SECS + 60),
+        ] {
+            let row = cache_row(&signature(&dir, name), source, age);
+            db.lock().unwrap().upsert_lyrics_cache(&row).unwrap();
+        }
+        let signatures: Vec<_> = [
+            "synced-file",
+            "plain-file",
+            "cached-synced",
+            "cached-miss",
+            "expired-synced",
+            "expired-miss",
+            "never-looked-up",
+        ]
+        .into_iter()
+        .map(|name| signature(&dir, name))
+        .collect();
+
+        let coverage = lyrics_coverage(&signatures, Some(&db)).unwrap();
+        assert_eq!(
+            coverage,
+            LyricsCoverage {
+                synced: 2,
+                plain: 1,
+                none: 1,
+                unchecked: 3,
+            }
+        );
+        assert_eq!(coverage.total(), signatures.len());
+        assert_eq!(lyrics_coverage(&signatures, None).unwrap().unchecked, 5);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,329 @@
+use std::sync::{Arc, Mutex};
+
+use super::embedded::EmbeddedTagsProvider;
+use super::lrclib::LrclibProvider;
+use super::onchain::OnchainRefProvider;
+use super::sidecar::SidecarProvider;
+use super::{
+    load_fresh_cached_lyrics, now_epoch_sec, persist_cached_lyrics, LyricsTrackSignature,
+    ResolvedLyrics,
+};
+use crate::music_db::MusicDb;
+
+/// Settings key holding a comma-separated provider order (e.g. `sidecar,embedded,lrclib`).
+pub const LYRICS_PROVIDER_ORDER_SETTING: &str = "lyrics_provider_order";
+
+const DEFAULT_PROVIDER_ORDER: [LyricsProviderKind; 4] = [
+    LyricsProviderKind::Sidecar,
+    LyricsProviderKind::Embedded,
+    LyricsProviderKind::Onchain,
+    LyricsProviderKind::Lrclib,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LyricsProviderKind {
+    Sidecar,
+    Embedded,
+    Onchain,
+    Lrclib,
+}
+
+impl LyricsProviderKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sidecar => "Sidecar file",
+            Self::Embedded => "Embedded tags",
+            Self::Onchain => "Heaven (on-chain)",
+            Self::Lrclib => "LRCLIB",
+        }
+    }
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Self::Sidecar => "sidecar",
+            Self::Embedded => "embedded",
+            Self::Onchain => "onchain",
+            Self::Lrclib => "lrclib",
+        }
+    }
+
+    pub fn from_key(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "sidecar" => Some(Self::Sidecar),
+            "embedded" | "tags" => Some(Self::Embedded),
+            "onchain" | "arweave" => Some(Self::Onchain),
+            "lrclib" => Some(Self::Lrclib),
+            _ => None,
+        }
+    }
+
+    /// Remote providers are fronted by `lyrics_cache`; local ones are read every time.
+    pub fn is_remote(self) -> bool {
+        matches!(self, Self::Onchain | Self::Lrclib)
+    }
+}
+
+pub trait LyricsProvider: Send + Sync {
+    fn kind(&self) -> LyricsProviderKind;
+
+    /// `Ok(None)` means "no lyrics here, try the next provider"; `Err` is a lookup failure.
+    fn lookup(
+        &self,
+        signature: &LyricsTrackSignature,
+        now: i64,
+    ) -> Result<Option<ResolvedLyrics>, String>;
+}
+
+/// Ordered list of providers; the first hit wins.
+pub struct LyricsProviderChain {
+    providers: Vec<Box<dyn LyricsProvider>>,
+}
+
+impl LyricsProviderChain {
+    pub fn new(providers: Vec<Box<dyn LyricsProvider>>) -> Self {
+        Self { providers }
+    }
+
+    pub fn from_kinds(kinds: &[LyricsProviderKind]) -> Self {
+        let providers = kinds
+            .iter()
+            .map(|kind| -> Box<dyn LyricsProvider> {
+                match kind {
+                    LyricsProviderKind::Sidecar => Box::new(SidecarProvider),
+                    LyricsProviderKind::Embedded => Box::new(EmbeddedTagsProvider),
+                    LyricsProviderKind::Onchain => Box::new(OnchainRefProvider::new()),
+                    LyricsProviderKind::Lrclib => Box::new(LrclibProvider::from_env()),
+                }
+            })
+            .collect();
+        Self::new(providers)
+    }
+
+    /// Provider order from `HEAVEN_LYRICS_PROVIDERS`, then the settings table, then defaults.
+    pub fn configured(db: Option<&Arc<Mutex<MusicDb>>>) -> Self {
+        Self::from_kinds(&configured_provider_order(db))
+    }
+
+    pub fn resolve(
+        &self,
+        signature: &LyricsTrackSignature,
+        db: Option<&Arc<Mutex<MusicDb>>>,
+    ) -> Result<ResolvedLyrics, String> {
+        let now = now_epoch_sec();
+        let mut checked_cache = false;
+        let mut remote_failed = false;
+        let mut queried_remote = false;
+
+        for provider in &self.providers {
+            let kind = provider.kind();
+            if kind.is_remote() && !checked_cache {
+                checked_cache = true;
+                if let Some(db_handle) = db {
+                    if let Some(cached) = load_fresh_cached_lyrics(db_handle, signature, now)? {
+                        return Ok(cached);
+                    }
+                }
+            }
+            queried_remote |= kind.is_remote();
+
+            match provider.lookup(signature, now) {
+                Ok(Some(found)) => {
+                    if kind.is_remote() {
+                        if let Some(db_handle) = db {
+                            persist_cached_lyrics(db_handle, signature, &found)?;
+                        }
+                    }
+                    return Ok(found);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    // Degrade transient provider/network failures to a normal no-match UI state.
+                    log::warn!(
+                        "[lyrics] {} lookup failed; trying next provider: track='{}' artist='{}' album='{}' err={}",
+                        kind.as_key(),
+                        signature.track_name,
+                        signature.artist_name,
+                        signature.album_name,
+                        err
+                    );
+                    remote_failed |= kind.is_remote();
+                }
+            }
+        }
+
+        let resolved = ResolvedLyrics::no_match(now);
+        // Only cache a negative result when every remote provider actually answered,
+        // so transient failures can succeed on a later retry.
+        if queried_remote && !remote_failed {
+            if let Some(db_handle) = db {
+                persist_cached_lyrics(db_handle, signature, &resolved)?;
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+pub fn parse_provider_order(raw: &str) -> Vec<LyricsProviderKind> {
+    let mut kinds = Vec::new();
+    for piece in raw.split(',') {
+        if piece.trim().is_empty() {
+            continue;
+        }
+        match LyricsProviderKind::from_key(piece) {
+            Some(kind) if !kinds.contains(&kind) => kinds.push(kind),
+            Some(_) => {}
+            None => log::warn!(
+                "[lyrics] ignoring unknown lyrics provider '{}'",
+                piece.trim()
+            ),
+        }
+    }
+    kinds
+}
+
+pub fn configured_provider_order(db: Option<&Arc<Mutex<MusicDb>>>) -> Vec<LyricsProviderKind> {
+    let from_env = std::env::var("HEAVEN_LYRICS_PROVIDERS")
+        .ok()
+        .map(|raw| parse_provider_order(&raw))
+        .filter(|kinds| !kinds.is_empty());
+    if let Some(kinds) = from_env {
+        return kinds;
+    }
+
+    let from_settings = db
+        .and_then(|handle| handle.lock().ok())
+        .and_then(|db| db.get_setting(LYRICS_PROVIDER_ORDER_SETTING))
+        .map(|raw| parse_provider_order(&raw))
+        .filter(|kinds| !kinds.is_empty());
+    from_settings.unwrap_or_else(|| DEFAULT_PROVIDER_ORDER.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::LyricsSource;
+    use crate::test_support::http_stub::{HttpStub, StubResponse};
+
+    /// Answer with the first `(path prefix, status, body)` route that matches, 404 otherwise.
+    fn spawn_stub_server(routes: Vec<(&'static str, u16, String)>) -> HttpStub {
+        HttpStub::spawn(move |request| {
+            routes
+                .iter()
+                .find(|(prefix, _, _)| request.path.starts_with(prefix))
+                .map(|(_, status, body)| StubResponse::new(*status, body.clone()))
+                .unwrap_or_else(StubResponse::not_found)
+        })
+    }
+
+    fn signature() -> LyricsTrackSignature {
+        LyricsTrackSignature {
+            track_path: "/nonexistent/heaven-test/track.mp3".to_string(),
+            track_name: "Song".to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: "Album".to_string(),
+            duration_sec: Some(200),
+            track_id: Some(format!("0x{}", "11".repeat(32))),
+        }
+    }
+
+    #[test]
+    fn parse_provider_order_dedupes_and_skips_unknown() {
+        assert_eq!(
+            parse_provider_order("lrclib, sidecar,bogus,lrclib"),
+            vec![LyricsProviderKind::Lrclib, LyricsProviderKind::Sidecar]
+        );
+    }
+
+    #[test]
+    fn lrclib_provider_reads_signature_hit_from_stub() {
+        let body = serde_json::json!({
+            "id": 7,
+            "trackName": "Song",
+            "artistName": "Artist",
+            "albumName": "Album",
+            "duration": 200,
+            "plainLyrics": "one\ntwo",
+            "syncedLyrics": "[00:01.00]one\n[00:02.50]two",
+        })
+        .to_string();
+        let base = spawn_stub_server(vec![("/api/get-cached", 200, body)]).url;
+
+        let chain = LyricsProviderChain::new(vec![Box::new(LrclibProvider::new(base.clone()))]);
+        let resolved = chain.resolve(&signature(), None).expect("resolve");
+
+        assert_eq!(resolved.source, LyricsSource::LrclibCached);
+        assert_eq!(resolved.lrclib_id, Some(7));
+        assert_eq!(resolved.synced_lines.len(), 2);
+        assert_eq!(
+            resolved.provenance.provider,
+            Some(LyricsProviderKind::Lrclib)
+        );
+        assert!(resolved
+            .provenance
+            .locator
+            .as_deref()
+            .is_some_and(|url| url.starts_with(&base)));
+    }
+
+    #[test]
+    fn chain_falls_through_to_next_provider_on_error() {
+        let lyrics_body = serde_json::json!({
+            "plainLyrics": "from chain",
+            "syncedLyrics": "",
+        })
+        .to_string();
+        let base = spawn_stub_server(vec![
+            ("/api/", 500, "{\"error\":\"boom\"}".to_string()),
+            ("/lyrics.json", 200, lyrics_body),
+        ])
+        .url;
+        let lyrics_url = format!("{base}/lyrics.json");
+
+        let chain = LyricsProviderChain::new(vec![
+            Box::new(LrclibProvider::new(base.clone())),
+            Box::new(OnchainRefProvider::with_ref_reader(move |_| {
+                Ok(Some(lyrics_url.clone()))
+            })),
+        ]);
+        let resolved = chain.resolve(&signature(), None).expect("resolve");
+
+        assert_eq!(resolved.source, LyricsSource::OnchainRef);
+        assert_eq!(resolved.plain_lyrics.as_deref(), Some("from chain"));
+        assert_eq!(
+            resolved.provenance.provider,
+            Some(LyricsProviderKind::Onchain)
+        );
+    }
+
+    #[test]
+    fn lrclib_rate_limit_backs_off_without_further_requests() {
+        let stub = spawn_stub_server(vec![("/api/", 429, String::new())]);
+        let provider = LrclibProvider::new(stub.url.clone());
+
+        let err = provider
+            .lookup(&signature(), 0)
+            .expect_err("429 is an error");
+        assert!(err.contains("rate limited"), "{err}");
+        assert!(provider.backoff_remaining().is_some());
+
+        let err = provider
+            .lookup(&signature(), 0)
+            .expect_err("still backing off");
+        assert!(err.contains("retry in"), "{err}");
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+    }
+
+    #[test]
+    fn chain_reports_no_match_when_every_provider_misses() {
+        let base = spawn_stub_server(vec![("/api/search", 200, "[]".to_string())]).url;
+        let chain = LyricsProviderChain::new(vec![
+            Box::new(SidecarProvider),
+            Box::new(LrclibProvider::new(base)),
+        ]);
+        let resolved = chain.resolve(&signature(), None).expect("resolve");
+        assert_eq!(resolved.source, LyricsSource::NoMatch);
+        assert!(!resolved.has_any_lyrics());
+    }
+}
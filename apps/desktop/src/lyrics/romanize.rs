@@ -0,0 +1,354 @@
+//! Built-in romanizer for kana (Hepburn), hangul (Revised Romanization) and
+//! common hanzi (toneless pinyin, most frequent reading).
+//!
+//! This is a best-effort display aid, not a linguistic tool: kanji in Japanese
+//! lyrics are left untouched, Korean sound-change rules beyond simple liaison
+//! are ignored, and hanzi outside the built-in table pass through unchanged.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Romanize each line; `None` when nothing in the lyrics is in a supported script.
+pub fn romanize_lines(lines: &[&str], language_hint: Option<&str>) -> Option<Vec<Option<String>>> {
+    let japanese = language_hint
+        .map(|lang| lang.trim().to_ascii_lowercase())
+        .map(|lang| lang == "ja" || lang.starts_with("ja-") || lang.starts_with("jp"))
+        .unwrap_or(false)
+        || lines.iter().any(|line| line.chars().any(is_kana));
+
+    let romanized: Vec<Option<String>> = lines
+        .iter()
+        .map(|line| romanize_line(line, japanese))
+        .collect();
+    romanized.iter().any(Option::is_some).then_some(romanized)
+}
+
+/// `japanese` keeps Han characters as-is instead of reading them as pinyin.
+pub fn romanize_line(text: &str, japanese: bool) -> Option<String> {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut changed = false;
+    let mut last_kana_start: Option<usize> = None;
+    let mut pending_sokuon = false;
+    let mut prev_han = false;
+    let mut hangul_carried = false;
+
+    let chars: Vec<char> = text.chars().collect();
+    for (idx, &ch) in chars.iter().enumerate() {
+        if is_hangul_syllable(ch) {
+            changed = true;
+            last_kana_start = None;
+            let next = chars
+                .get(idx + 1)
+                .copied()
+                .filter(|c| is_hangul_syllable(*c));
+            hangul_carried = push_hangul_syllable(&mut out, ch, next, hangul_carried);
+            prev_han = false;
+            continue;
+        }
+        hangul_carried = false;
+
+        if is_kana(ch) {
+            changed = true;
+            prev_han = false;
+            let hira = to_hiragana(ch);
+            match hira {
+                'っ' => pending_sokuon = true,
+                'ー' => {
+                    if let Some(vowel) = out.chars().last().filter(|c| "aiueo".contains(*c)) {
+                        out.push(vowel);
+                    }
+                }
+                'ゃ' | 'ゅ' | 'ょ' => {
+                    let vowel = match hira {
+                        'ゃ' => 'a',
+                        'ゅ' => 'u',
+                        _ => 'o',
+                    };
+                    match last_kana_start.filter(|start| out[*start..].ends_with('i')) {
+                        Some(start) if out.len() - start > 1 => {
+                            out.pop();
+                            if !(out[start..].ends_with("sh")
+                                || out[start..].ends_with("ch")
+                                || out[start..].ends_with('j'))
+                            {
+                                out.push('y');
+                            }
+                            out.push(vowel);
+                        }
+                        _ => {
+                            out.push('y');
+                            out.push(vowel);
+                        }
+                    }
+                }
+                'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                    let vowel = kana_romaji(hira).unwrap_or("");
+                    if let Some(start) = last_kana_start.filter(|start| out.len() - start > 1) {
+                        if out[start..].ends_with(|c: char| "aiueo".contains(c)) {
+                            out.pop();
+                        }
+                    }
+                    out.push_str(vowel);
+                }
+                _ => {
+                    let Some(romaji) = kana_romaji(hira) else {
+                        out.push(ch);
+                        continue;
+                    };
+                    if pending_sokuon {
+                        let doubled = if romaji.starts_with("ch") {
+                            Some('t')
+                        } else {
+                            romaji.chars().next().filter(|c| !"aiueon".contains(*c))
+                        };
+                        if let Some(doubled) = doubled {
+                            out.push(doubled);
+                        }
+                        pending_sokuon = false;
+                    }
+                    last_kana_start = Some(out.len());
+                    out.push_str(romaji);
+                }
+            }
+            continue;
+        }
+
+        last_kana_start = None;
+        pending_sokuon = false;
+        if !japanese && is_han(ch) {
+            if let Some(pinyin) = pinyin_table().get(&ch) {
+                changed = true;
+                if prev_han || out.chars().last().is_some_and(char::is_alphanumeric) {
+                    out.push(' ');
+                }
+                out.push_str(pinyin);
+                prev_han = true;
+                continue;
+            }
+        }
+        if prev_han && ch.is_alphanumeric() {
+            out.push(' ');
+        }
+        prev_han = false;
+        out.push(match ch {
+            '\u{3000}' => ' ',
+            '、' | '，' => ',',
+            '。' => '.',
+            '！' => '!',
+            '？' => '?',
+            other => other,
+        });
+    }
+
+    changed.then(|| out.trim().to_string())
+}
+
+fn is_kana(ch: char) -> bool {
+    matches!(ch, '\u{3041}'..='\u{3096}' | '\u{30A1}'..='\u{30FA}' | 'ー')
+}
+
+fn is_hangul_syllable(ch: char) -> bool {
+    ('\u{AC00}'..='\u{D7A3}').contains(&ch)
+}
+
+fn is_han(ch: char) -> bool {
+    matches!(ch, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}')
+}
+
+fn to_hiragana(ch: char) -> char {
+    match ch {
+        '\u{30A1}'..='\u{30F6}' => char::from_u32(ch as u32 - 0x60).unwrap_or(ch),
+        other => other,
+    }
+}
+
+#[rustfmt::skip]
+fn kana_romaji(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'あ' => "a", 'い' => "i", 'う' => "u", 'え' => "e", 'お' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' => "ya", 'ゆ' => "yu", 'よ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' => "wa", 'ゐ' => "i", 'ゑ' => "e", 'を' => "o", 'ん' => "n", 'ゔ' => "vu",
+        'ぁ' => "a", 'ぃ' => "i", 'ぅ' => "u", 'ぇ' => "e", 'ぉ' => "o", 'ゎ' => "wa",
+        'ゕ' => "ka", 'ゖ' => "ke", 'ヷ' => "va", 'ヸ' => "vi", 'ヹ' => "ve", 'ヺ' => "vo",
+        _ => return None,
+    })
+}
+
+const HANGUL_INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p",
+    "h",
+];
+const HANGUL_MEDIALS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we",
+    "wi", "yu", "eu", "ui", "i",
+];
+const HANGUL_FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p",
+    "t", "t", "ng", "t", "t", "k", "t", "p", "t",
+];
+/// Final consonant re-read as the next syllable's initial when that syllable starts with ㅇ.
+const HANGUL_LIAISON: [Option<&str>; 28] = [
+    None,
+    Some("g"),
+    Some("kk"),
+    None,
+    Some("n"),
+    None,
+    None,
+    Some("d"),
+    Some("r"),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some("m"),
+    Some("b"),
+    None,
+    Some("s"),
+    Some("ss"),
+    None,
+    Some("j"),
+    Some("ch"),
+    Some("k"),
+    Some("t"),
+    Some("p"),
+    None,
+];
+const HANGUL_SILENT_INITIAL: usize = 11;
+
+fn decompose_hangul(ch: char) -> (usize, usize, usize) {
+    let index = ch as usize - 0xAC00;
+    (index / 588, (index % 588) / 28, index % 28)
+}
+
+/// Returns `true` when the final was liaised, i.e. already emitted as the next syllable's initial.
+fn push_hangul_syllable(out: &mut String, ch: char, next: Option<char>, carried: bool) -> bool {
+    let (initial, medial, final_idx) = decompose_hangul(ch);
+    if !carried {
+        out.push_str(HANGUL_INITIALS[initial]);
+    }
+    out.push_str(HANGUL_MEDIALS[medial]);
+
+    let next_is_silent = next
+        .map(decompose_hangul)
+        .is_some_and(|(next_initial, _, _)| next_initial == HANGUL_SILENT_INITIAL);
+    match HANGUL_LIAISON[final_idx].filter(|_| next_is_silent) {
+        Some(liaison) => {
+            out.push_str(liaison);
+            true
+        }
+        None => {
+            out.push_str(HANGUL_FINALS[final_idx]);
+            false
+        }
+    }
+}
+
+fn pinyin_table() -> &'static HashMap<char, &'static str> {
+    static TABLE: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for entry in PINYIN_TABLE.split_whitespace() {
+            let mut chars = entry.chars();
+            if let Some(han) = chars.next() {
+                let reading = &entry[han.len_utf8()..];
+                if !reading.is_empty() {
+                    table.insert(han, reading);
+                }
+            }
+        }
+        table
+    })
+}
+
+/// Common simplified and traditional hanzi with their most frequent reading.
+const PINYIN_TABLE: &str = "\
+的de 一yi 是shi 不bu 了le 人ren 我wo 在zai 有you 他ta 这zhe 這zhe 中zhong 大da 来lai 來lai \
+上shang 国guo 國guo 个ge 個ge 到dao 说shuo 說shuo 们men 們men 为wei 為wei 子zi 和he 你ni 妳ni \
+您nin 地di 出chu 道dao 也ye 时shi 時shi 年nian 得de 就jiu 那na 要yao 下xia 以yi 生sheng 会hui \
+會hui 自zi 着zhe 著zhe 去qu 之zhi 过guo 過guo 家jia 学xue 學xue 对dui 對dui 可ke 她ta 里li \
+裡li 后hou 後hou 小xiao 么me 麼me 心xin 多duo 天tian 而er 能neng 好hao 都dou 然ran 没mei 沒mei \
+日ri 于yu 起qi 还hai 還hai 发fa 發fa 成cheng 事shi 只zhi 作zuo 当dang 當dang 想xiang 看kan \
+文wen 无wu 無wu 开kai 開kai 手shou 十shi 用yong 主zhu 行xing 方fang 又you 如ru 前qian 所suo \
+本ben 见jian 見jian 经jing 經jing 头tou 頭tou 面mian 公gong 同tong 三san 已yi 老lao 从cong \
+從cong 动dong 動dong 两liang 兩liang 长chang 長chang 知zhi 样yang 樣yang 现xian 現xian 分fen \
+将jiang 將jiang 外wai 但dan 身shen 些xie 与yu 與yu 高gao 意yi 进jin 進jin 把ba 此ci 实shi \
+實shi 回hui 二er 理li 美mei 点dian 點dian 月yue 明ming 其qi 种zhong 種zhong 声sheng 聲sheng \
+全quan 己ji 话hua 話hua 儿er 兒er 者zhe 向xiang 情qing 正zheng 名ming 定ding 女nv 问wen 問wen \
+力li 给gei 給gei 等deng 几ji 幾ji 很hen 最zui 间jian 間jian 新xin 什shen 打da 因yin 重zhong \
+被bei 走zou 电dian 電dian 四si 门men 門men 相xiang 次ci 东dong 東dong 海hai 口kou 再zai 平ping \
+真zhen 听ting 聽ting 世shi 气qi 氣qi 信xin 少shao 并bing 内nei 加jia 化hua 由you 却que 卻que \
+先xian 山shan 五wu 太tai 水shui 万wan 萬wan 眼yan 睛jing 别bie 別bie 处chu 處chu 才cai 比bi \
+住zhu 九jiu 笑xiao 通tong 光guang 亲qin 親qin 界jie 今jin 风feng 風feng 直zhi 望wang 色se \
+请qing 請qing 爱ai 愛ai 让rang 讓rang 认ren 認ren 算suan 百bai 吃chi 怎zen 远yuan 遠yuan \
+跟gen 带dai 帶dai 花hua 快kuai 变bian 變bian 言yan 往wang 留liu 红hong 紅hong 完wan 深shen \
+空kong 轻qing 輕qing 告gao 诉su 訴su 语yu 語yu 满man 滿man 写xie 寫xie 呢ne 黑hei 白bai \
+夜ye 梦meng 夢meng 泪lei 淚lei 雨yu 星xing 云yun 雲yun 春chun 秋qiu 冬dong 夏xia 歌ge 唱chang \
+恋lian 戀lian 思si 念nian 忘wang 谁shui 誰shui 啊a 吗ma 嗎ma 吧ba 哦o 呀ya 啦la 哪na 怕pa \
+痛tong 伤shang 傷shang 哭ku 温wen 溫wen 暖nuan 冷leng 孤gu 独du 獨du 寂ji 寞mo 永yong 久jiu \
+愿yuan 願yuan 陪pei 伴ban 抱bao 吻wen 怀huai 懷huai 乐le 樂le 醉zui 飞fei 飛fei 落luo 火huo \
+夕xi 阳yang 陽yang 影ying 灯deng 燈deng 街jie 城cheng 故gu 忆yi 憶yi 曾ceng 始shi 终zhong \
+終zhong 离li 離li 约yue 約yue 承cheng 诺nuo 諾nuo 未wei 昨zuo 晚wan 早zao 浅qian 淺qian 洋yang \
+岸an 船chuan 桥qiao 橋qiao 窗chuang 亮liang 球qiu 宇yu 宙zhou 脸lian 臉lian 嘴zui 唇chun 肩jian \
+背bei 边bian 邊bian 跳tiao 呼hu 吸xi 音yin 脚jiao 腳jiao 步bu 词ci 詞ci 咱zan 虽sui 雖sui \
+切qie 部bu 幸xing 福fu 悲bei 丽li 麗li 漂piao 柔rou 勇yong 敢gan 坚jian 堅jian 强qiang 強qiang \
+脆cui 弱ruo 疯feng 瘋feng 狂kuang 希xi 失shi 绝jue 絕jue 待dai 寻xun 尋xun 找zhao 遇yu 错cuo \
+錯cuo 拥yong 擁yong 放fang 弃qi 棄qi 持chi 继ji 繼ji 续xu 續xu 停ting 止zhi 改gai 记ji 記ji \
+懂dong 沉chen 默mo 安an 静jing 靜jing 岁sui 歲sui 青qing 谢xie 謝xie 欢huan 歡huan 孩hai \
+朋peng 友you 男nan 妈ma 媽ma 爸ba 哥ge 姐jie 弟di 妹mei 睡shui 觉jue 覺jue 醒xing 站zhan 坐zuo \
+跑pao 舞wu 玩wan 喝he 酒jiu 茶cha 路lu 车che 車che 死si 活huo 命ming 神shen 难nan 難nan 安an \
+感gan 金jin 何he 更geng 放fang 做zuo 受shou 王wang 果guo 物wu 记ji 共gong 许xu 許xu 特te \
+字zi 交jiao 论lun 論lun 非fei 流liu 每mei 连lian 連lian 传chuan 傳chuan 近jin 决jue 決jue \
+周zhou 保bao 半ban 候hou 七qi 八ba 六liu 必bi 求qiu 转zhuan 轉zhuan 量liang 英ying 息xi 识shi \
+識shi 极ji 極ji 秘mi 密mi 雪xue 冰bing 霜shuang 烟yan 煙yan 雾wu 霧wu 花hua 草cao 树shu 樹shu \
+叶ye 葉ye 鸟niao 鳥niao 鱼yu 魚yu 狗gou 猫mao 貓mao 天tian 堂tang 地di 狱yu 獄yu 魂hun 灵ling \
+靈ling 命ming 运yun 運yun 缘yuan 緣yuan 分fen 份fen 寂ji 静jing 梦meng 境jing 心xin 碎sui";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romanizes_kana_hangul_and_hanzi() {
+        assert_eq!(
+            romanize_line("きょうはカッコいいー", true).as_deref(),
+            Some("kyouhakakkoiii")
+        );
+        assert_eq!(
+            romanize_line("ちゃんとファン", true).as_deref(),
+            Some("chantofan")
+        );
+        assert_eq!(
+            romanize_line("사랑해요", false).as_deref(),
+            Some("saranghaeyo")
+        );
+        assert_eq!(romanize_line("한국어", false).as_deref(), Some("hangugeo"));
+        assert_eq!(
+            romanize_line("我爱你 baby", false).as_deref(),
+            Some("wo ai ni baby")
+        );
+        assert_eq!(romanize_line("just english", false), None);
+    }
+
+    #[test]
+    fn japanese_context_keeps_kanji() {
+        let lines = romanize_lines(&["夢", "ゆめ"], None).expect("kana present");
+        assert_eq!(lines[0], None);
+        assert_eq!(lines[1].as_deref(), Some("yume"));
+    }
+}
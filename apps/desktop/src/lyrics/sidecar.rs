@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use super::providers::{LyricsProvider, LyricsProviderKind};
+use super::{LyricsProvenance, LyricsSource, LyricsTrackSignature, ResolvedLyrics};
+
+/// `.lrc` / `.txt` files sitting next to the audio file.
+pub struct SidecarProvider;
+
+impl LyricsProvider for SidecarProvider {
+    fn kind(&self) -> LyricsProviderKind {
+        LyricsProviderKind::Sidecar
+    }
+
+    fn lookup(
+        &self,
+        signature: &LyricsTrackSignature,
+        now: i64,
+    ) -> Result<Option<ResolvedLyrics>, String> {
+        let path = Path::new(&signature.track_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        for candidate in [path.with_extension("lrc"), path.with_extension("LRC")] {
+            if !candidate.exists() {
+                continue;
+            }
+            if let Ok(raw) = std::fs::read_to_string(&candidate) {
+                let provenance = LyricsProvenance::new(
+                    LyricsProviderKind::Sidecar,
+                    Some(candidate.to_string_lossy().to_string()),
+                );
+                if let Some(resolved) = ResolvedLyrics::from_raw_text(
+                    &raw,
+                    LyricsSource::SidecarSynced,
+                    LyricsSource::SidecarPlain,
+                    provenance,
+                    now,
+                ) {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+
+        for candidate in [path.with_extension("txt"), path.with_extension("TXT")] {
+            if !candidate.exists() {
+                continue;
+            }
+            if let Ok(raw) = std::fs::read_to_string(&candidate) {
+                let provenance = LyricsProvenance::new(
+                    LyricsProviderKind::Sidecar,
+                    Some(candidate.to_string_lossy().to_string()),
+                );
+                if let Some(resolved) = ResolvedLyrics::from_plain_text(
+                    &raw,
+                    LyricsSource::SidecarPlain,
+                    provenance,
+                    now,
+                ) {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
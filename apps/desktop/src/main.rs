@@ -11,17 +11,22 @@ mod library;
 mod load_storage;
 mod lyrics;
 mod music_db;
+mod now_playing;
 mod pages;
 mod profile;
 mod rooms;
 mod schedule;
 mod scrobble;
 mod scrobble_refresh;
+mod secret_store;
 mod settings;
 mod shared;
 mod shell;
 mod side_player;
 mod status_center;
+mod tempo;
+#[cfg(test)]
+mod test_support;
 mod theme;
 mod ui;
 mod voice;
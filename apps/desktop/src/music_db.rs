@@ -4,17 +4,25 @@
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, UNIX_EPOCH};
 
 use rusqlite::{params, Connection};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 mod metadata;
 use metadata::{extract_metadata, format_duration_ms, is_audio_file};
+mod pending_revocations;
 mod query_ops;
 mod query_settings;
 mod scan_ops;
+mod scrobble_events;
+mod scrobble_import;
+mod scrobble_outbox;
+mod scrobble_sinks;
+mod synced_records;
+mod track_storage;
+mod upload_jobs;
 
 // =============================================================================
 // Types
@@ -31,6 +39,97 @@ pub enum StorageStatus {
     Uploaded,
     /// Anchored to Arweave (permanent).
     Permanent,
+    /// Recorded as uploaded, but Load no longer serves the blob.
+    Missing,
+}
+
+impl StorageStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Uploaded => "uploaded",
+            Self::Permanent => "permanent",
+            Self::Missing => "missing",
+        }
+    }
+}
+
+/// How an uploaded record compares with the registry, Load's tag index and the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordSyncState {
+    /// Not confirmed remotely yet; fresh uploads stay here until they are indexed.
+    #[default]
+    Pending,
+    Synced,
+    /// The remote side disagrees with the local record; `sync_note` says how.
+    Drifted,
+}
+
+impl RecordSyncState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Synced => "synced",
+            Self::Drifted => "drifted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "synced" => Self::Synced,
+            "drifted" => Self::Drifted,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// A local track this account uploaded to Load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedTrackRecord {
+    pub owner_address: String,
+    pub file_path: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub track_id: String,
+    pub content_id: String,
+    pub piece_cid: String,
+    pub gateway_url: String,
+    pub tx_hash: String,
+    pub register_version: String,
+    pub created_at_ms: i64,
+    #[serde(default)]
+    pub saved_forever: bool,
+    /// Arweave transaction holding the archived blob; set once "Save Forever" posts it.
+    #[serde(default)]
+    pub arweave_id: Option<String>,
+    #[serde(default)]
+    pub sync_state: RecordSyncState,
+    /// Why the record drifted, as shown to the user.
+    #[serde(default)]
+    pub sync_note: Option<String>,
+}
+
+/// Access to one content granted by `owner_address` to `grantee_address`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedGrantRecord {
+    pub owner_address: String,
+    pub grantee_address: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub track_id: Option<String>,
+    pub content_id: String,
+    pub piece_cid: String,
+    pub gateway_url: String,
+    pub tx_hash: String,
+    pub mirror_tx_hash: String,
+    pub shared_at_ms: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +158,8 @@ pub struct LyricsCacheRow {
     pub lrclib_id: Option<i64>,
     pub source: String,
     pub fetched_at_epoch_sec: i64,
+    /// Locator for where the lyrics came from (LRCLIB URL, `ar://` ref, ...).
+    pub provenance: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +183,231 @@ pub struct TrackLyricsStateRow {
     pub updated_at: i64,
 }
 
+/// A finished play as stored in the scrobble outbox.
+#[derive(Debug, Clone)]
+pub struct ScrobblePlay {
+    pub track_path: String,
+    pub cover_path: Option<String>,
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub mbid: Option<String>,
+    pub ip_id: Option<String>,
+    pub duration_sec: u32,
+    pub played_at_sec: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScrobbleOutboxRow {
+    pub id: i64,
+    pub attempts: u32,
+    pub play: ScrobblePlay,
+}
+
+/// Rows parked under one broadcast tx whose receipt never came back.
+#[derive(Debug, Clone)]
+pub struct UnconfirmedScrobbles<R> {
+    pub tx_hash: String,
+    /// Each row with the track id it was submitted under.
+    pub rows: Vec<(R, String)>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrobbleOutboxCounts {
+    pub pending: usize,
+    pub confirmed: usize,
+    pub failed: usize,
+}
+
+/// A historical play from an external export, staged for on-chain import.
+#[derive(Debug, Clone)]
+pub struct ImportedScrobble {
+    pub source: String,
+    pub track_id: String,
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub mbid: Option<String>,
+    pub duration_sec: u32,
+    pub played_at_sec: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedScrobbleRow {
+    pub id: i64,
+    pub attempts: u32,
+    pub scrobble: ImportedScrobble,
+}
+
+/// Which `Scrobbled` events a sync cursor covers: one wallet's, or every wallet's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrobbleScope {
+    User(String),
+    All,
+}
+
+impl ScrobbleScope {
+    pub fn user(address: &str) -> Self {
+        Self::User(address.trim().to_ascii_lowercase())
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            Self::User(address) => format!("user:{address}"),
+            Self::All => "all".to_string(),
+        }
+    }
+}
+
+/// One on-chain `Scrobbled` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrobbleEvent {
+    pub user_address: String,
+    pub track_id: String,
+    pub played_at_sec: u64,
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+/// Registered metadata for a scrobbled track id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrobbleTrackMeta {
+    pub track_id: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub cover_cid: Option<String>,
+    pub kind: Option<u8>,
+    /// Verified play count as reported by the subgraph; events carry no verification flag.
+    pub verified_plays: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrobbleSyncCursor {
+    pub block_number: u64,
+    pub block_hash: Option<String>,
+    pub source: String,
+    pub synced_at: i64,
+}
+
+/// Events for blocks `from_block..=to_block` of one scope. Applying it replaces whatever the
+/// store held for that range, which is how reorged-out events disappear.
+#[derive(Debug, Clone)]
+pub struct ScrobbleSyncBatch {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub to_block_hash: Option<String>,
+    pub source: String,
+    pub events: Vec<ScrobbleEvent>,
+    pub tracks: Vec<ScrobbleTrackMeta>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StoredScrobble {
+    pub track_id: String,
+    pub played_at_sec: u64,
+    pub meta: Option<ScrobbleTrackMeta>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScrobbledTrackStats {
+    pub meta: ScrobbleTrackMeta,
+    pub plays: u64,
+}
+
+/// Lifecycle of a background upload job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadJobStatus {
+    Queued,
+    Running,
+    Paused,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl UploadJobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "queued" => Self::Queued,
+            "running" => Self::Running,
+            "paused" => Self::Paused,
+            "done" => Self::Done,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Failed,
+        }
+    }
+
+    /// Still owns the track: a second job for the same file would upload it twice.
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Queued | Self::Running | Self::Paused)
+    }
+}
+
+/// Track a job encrypts, uploads and registers.
+#[derive(Debug, Clone)]
+pub struct UploadJobInput {
+    pub file_path: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub mbid: Option<String>,
+    pub ip_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UploadJobRow {
+    pub id: i64,
+    pub owner_address: String,
+    pub track: UploadJobInput,
+    pub status: UploadJobStatus,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    /// Credits the upload is expected to cost; `None` until estimated or when pricing failed.
+    pub estimated_credit: Option<f64>,
+    /// Register response JSON once the job is done.
+    pub response: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Revoke whose new blob is uploaded but whose envelopes or supersede record are not all
+/// published yet.
+#[derive(Debug, Clone)]
+pub struct PendingRevocationRow {
+    pub owner_address: String,
+    pub content_id: String,
+    pub title: String,
+    /// `PendingRevocation` JSON, including the envelopes published so far.
+    pub payload: String,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Arweave copy of a track's Load blob, posted but possibly not yet served by a gateway.
+#[derive(Debug, Clone)]
+pub struct TrackArchiveRow {
+    pub content_id: String,
+    pub piece_cid: String,
+    pub arweave_id: String,
+    pub sha256: String,
+    pub bytes: u64,
+    pub estimated_credit: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct ScanProgress {
     pub done: usize,
@@ -104,6 +430,9 @@ impl MusicDb {
         let db_path = app_data_dir.join("music.db");
         let conn =
             Connection::open(&db_path).map_err(|e| format!("Failed to open music.db: {e}"))?;
+        // Profile and Discover sync scrobble history through their own connections.
+        conn.busy_timeout(Duration::from_secs(5))
+            .map_err(|e| format!("Failed to set music.db busy timeout: {e}"))?;
 
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS tracks (
@@ -159,6 +488,171 @@ impl MusicDb {
                 lyrics_checked INTEGER,
                 created_at    INTEGER NOT NULL,
                 updated_at    INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS scrobble_outbox (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_address    TEXT,
+                track_path      TEXT NOT NULL,
+                cover_path      TEXT,
+                artist          TEXT NOT NULL,
+                title           TEXT NOT NULL,
+                album           TEXT,
+                mbid            TEXT,
+                ip_id           TEXT,
+                duration_sec    INTEGER NOT NULL DEFAULT 0,
+                played_at_sec   INTEGER NOT NULL,
+                status          TEXT NOT NULL DEFAULT 'pending',
+                attempts        INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL DEFAULT 0,
+                last_error      TEXT,
+                tx_hash         TEXT,
+                track_id        TEXT,
+                created_at      INTEGER NOT NULL,
+                updated_at      INTEGER NOT NULL,
+                UNIQUE(track_path, played_at_sec)
+            );
+            CREATE INDEX IF NOT EXISTS idx_scrobble_outbox_due
+                ON scrobble_outbox(status, next_attempt_at);
+            CREATE TABLE IF NOT EXISTS scrobble_sink_deliveries (
+                outbox_id       INTEGER NOT NULL,
+                sink            TEXT NOT NULL,
+                status          TEXT NOT NULL DEFAULT 'pending',
+                attempts        INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL DEFAULT 0,
+                last_error      TEXT,
+                reference       TEXT,
+                updated_at      INTEGER NOT NULL,
+                PRIMARY KEY(outbox_id, sink)
+            );
+            CREATE TABLE IF NOT EXISTS scrobble_import (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_address    TEXT NOT NULL,
+                source          TEXT NOT NULL,
+                track_id        TEXT NOT NULL,
+                artist          TEXT NOT NULL,
+                title           TEXT NOT NULL,
+                album           TEXT,
+                mbid            TEXT,
+                duration_sec    INTEGER NOT NULL DEFAULT 0,
+                played_at_sec   INTEGER NOT NULL,
+                status          TEXT NOT NULL DEFAULT 'pending',
+                attempts        INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL DEFAULT 0,
+                last_error      TEXT,
+                tx_hash         TEXT,
+                created_at      INTEGER NOT NULL,
+                updated_at      INTEGER NOT NULL,
+                UNIQUE(user_address, track_id, played_at_sec)
+            );
+            CREATE TABLE IF NOT EXISTS scrobble_events (
+                user_address  TEXT NOT NULL,
+                track_id      TEXT NOT NULL,
+                played_at_sec INTEGER NOT NULL,
+                block_number  INTEGER NOT NULL,
+                log_index     INTEGER NOT NULL DEFAULT 0,
+                source        TEXT NOT NULL,
+                PRIMARY KEY(user_address, track_id, played_at_sec)
+            );
+            CREATE INDEX IF NOT EXISTS idx_scrobble_events_block
+                ON scrobble_events(block_number);
+            CREATE INDEX IF NOT EXISTS idx_scrobble_events_track
+                ON scrobble_events(track_id);
+            CREATE TABLE IF NOT EXISTS scrobble_tracks (
+                track_id       TEXT PRIMARY KEY,
+                title          TEXT NOT NULL,
+                artist         TEXT NOT NULL,
+                album          TEXT NOT NULL DEFAULT '',
+                cover_cid      TEXT,
+                kind           INTEGER,
+                verified_plays INTEGER,
+                updated_at     INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS scrobble_sync_cursor (
+                scope        TEXT PRIMARY KEY,
+                block_number INTEGER NOT NULL,
+                block_hash   TEXT,
+                source       TEXT NOT NULL,
+                synced_at    INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS upload_jobs (
+                id               INTEGER PRIMARY KEY AUTOINCREMENT,
+                owner_address    TEXT NOT NULL,
+                file_path        TEXT NOT NULL,
+                title            TEXT NOT NULL,
+                artist           TEXT NOT NULL,
+                album            TEXT NOT NULL DEFAULT '',
+                mbid             TEXT,
+                ip_id            TEXT,
+                status           TEXT NOT NULL DEFAULT 'queued',
+                attempts         INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at  INTEGER NOT NULL DEFAULT 0,
+                last_error       TEXT,
+                estimated_credit REAL,
+                response         TEXT,
+                created_at       INTEGER NOT NULL,
+                updated_at       INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_upload_jobs_due
+                ON upload_jobs(owner_address, status, next_attempt_at);
+            CREATE TABLE IF NOT EXISTS track_storage (
+                owner_address    TEXT NOT NULL,
+                file_path        TEXT NOT NULL,
+                content_id       TEXT NOT NULL,
+                piece_cid        TEXT NOT NULL,
+                status           TEXT NOT NULL DEFAULT 'local',
+                arweave_id       TEXT,
+                sha256           TEXT,
+                bytes            INTEGER,
+                estimated_credit REAL,
+                updated_at       INTEGER NOT NULL,
+                PRIMARY KEY (owner_address, file_path)
+            );
+            CREATE TABLE IF NOT EXISTS uploaded_tracks (
+                owner_address    TEXT NOT NULL,
+                file_path        TEXT NOT NULL,
+                title            TEXT NOT NULL,
+                artist           TEXT NOT NULL,
+                album            TEXT NOT NULL DEFAULT '',
+                track_id         TEXT NOT NULL,
+                content_id       TEXT NOT NULL,
+                piece_cid        TEXT NOT NULL,
+                gateway_url      TEXT NOT NULL,
+                tx_hash          TEXT NOT NULL,
+                register_version TEXT NOT NULL,
+                created_at_ms    INTEGER NOT NULL,
+                saved_forever    INTEGER NOT NULL DEFAULT 0,
+                arweave_id       TEXT,
+                sync_state       TEXT NOT NULL DEFAULT 'pending',
+                sync_note        TEXT,
+                checked_at       INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (owner_address, file_path)
+            );
+            CREATE TABLE IF NOT EXISTS shared_grants (
+                owner_address   TEXT NOT NULL,
+                grantee_address TEXT NOT NULL,
+                content_id      TEXT NOT NULL,
+                title           TEXT NOT NULL DEFAULT '',
+                artist          TEXT NOT NULL DEFAULT '',
+                album           TEXT NOT NULL DEFAULT '',
+                track_id        TEXT,
+                piece_cid       TEXT NOT NULL,
+                gateway_url     TEXT NOT NULL DEFAULT '',
+                tx_hash         TEXT NOT NULL DEFAULT 'n/a',
+                mirror_tx_hash  TEXT NOT NULL DEFAULT 'n/a',
+                shared_at_ms    INTEGER NOT NULL,
+                PRIMARY KEY (grantee_address, content_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_shared_grants_owner
+                ON shared_grants(owner_address, content_id);
+            CREATE TABLE IF NOT EXISTS pending_revocations (
+                owner_address TEXT NOT NULL,
+                content_id    TEXT NOT NULL,
+                title         TEXT NOT NULL DEFAULT '',
+                payload       TEXT NOT NULL,
+                last_error    TEXT,
+                created_at    INTEGER NOT NULL,
+                updated_at    INTEGER NOT NULL,
+                PRIMARY KEY (owner_address, content_id)
             );",
         )
         .map_err(|e| format!("Failed to create tables: {e}"))?;
@@ -180,6 +674,12 @@ impl MusicDb {
                 return Err(format!("Failed to migrate tracks.ip_id: {e}"));
             }
         }
+        if let Err(e) = conn.execute("ALTER TABLE lyrics_cache ADD COLUMN provenance TEXT", []) {
+            let msg = e.to_string();
+            if !msg.contains("duplicate column name") {
+                return Err(format!("Failed to migrate lyrics_cache.provenance: {e}"));
+            }
+        }
 
         let covers_dir = app_data_dir.join("covers");
         std::fs::create_dir_all(&covers_dir).ok();
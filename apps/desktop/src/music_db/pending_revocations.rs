@@ -0,0 +1,126 @@
+use super::*;
+
+const PENDING_REVOCATION_COLUMNS: &str =
+    "owner_address, content_id, title, payload, last_error, created_at, updated_at";
+
+fn pending_revocation_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<PendingRevocationRow> {
+    Ok(PendingRevocationRow {
+        owner_address: row.get(0)?,
+        content_id: row.get(1)?,
+        title: row.get(2)?,
+        payload: row.get(3)?,
+        last_error: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+impl MusicDb {
+    /// Save the progress of a revoke, replacing any earlier save for the same content.
+    pub fn save_pending_revocation(
+        &self,
+        owner_address: &str,
+        content_id: &str,
+        title: &str,
+        payload: &str,
+        last_error: Option<&str>,
+        now: i64,
+    ) -> Result<(), String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let content_id = content_id.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "INSERT INTO pending_revocations (
+                    owner_address, content_id, title, payload, last_error, created_at, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+                 ON CONFLICT(owner_address, content_id) DO UPDATE SET
+                    title = excluded.title,
+                    payload = excluded.payload,
+                    last_error = excluded.last_error,
+                    updated_at = excluded.updated_at",
+                params![owner_address, content_id, title, payload, last_error, now],
+            )
+            .map_err(|e| format!("Failed saving pending_revocations row: {e}"))?;
+        Ok(())
+    }
+
+    /// Every unfinished revoke of `owner_address`, oldest first.
+    pub fn pending_revocations(
+        &self,
+        owner_address: &str,
+    ) -> Result<Vec<PendingRevocationRow>, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {PENDING_REVOCATION_COLUMNS} FROM pending_revocations
+                 WHERE owner_address = ?1
+                 ORDER BY created_at ASC, content_id ASC"
+            ))
+            .map_err(|e| format!("Failed preparing pending_revocations query: {e}"))?;
+        let rows = stmt
+            .query_map(params![owner_address], pending_revocation_from_row)
+            .map_err(|e| format!("Failed querying pending_revocations: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading pending_revocations row: {e}"))
+    }
+
+    /// Forget a revoke once everything for it is published.
+    pub fn delete_pending_revocation(
+        &self,
+        owner_address: &str,
+        content_id: &str,
+    ) -> Result<(), String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let content_id = content_id.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "DELETE FROM pending_revocations WHERE owner_address = ?1 AND content_id = ?2",
+                params![owner_address, content_id],
+            )
+            .map_err(|e| format!("Failed deleting pending_revocations row: {e}"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrobble::now_epoch_sec;
+
+    #[test]
+    fn failed_attempt_keeps_progress_until_deleted() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-pending-revocations-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).unwrap();
+        db.save_pending_revocation("0xABC", "0xC1", "Song", r#"{"envelopeIds":[]}"#, None, 10)
+            .unwrap();
+        db.save_pending_revocation(
+            "0xabc",
+            "0xc1",
+            "Song",
+            r#"{"envelopeIds":["env-a"]}"#,
+            Some("gateway timed out"),
+            20,
+        )
+        .unwrap();
+        db.save_pending_revocation("0xdef", "0xc2", "Other", "{}", None, 15)
+            .unwrap();
+
+        let rows = db.pending_revocations("0xabc").unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].content_id, "0xc1");
+        assert_eq!(rows[0].payload, r#"{"envelopeIds":["env-a"]}"#);
+        assert_eq!(rows[0].last_error.as_deref(), Some("gateway timed out"));
+        assert_eq!((rows[0].created_at, rows[0].updated_at), (10, 20));
+
+        db.delete_pending_revocation("0xABC", "0xC1").unwrap();
+        assert!(db.pending_revocations("0xabc").unwrap().is_empty());
+        assert_eq!(db.pending_revocations("0xdef").unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -56,7 +56,7 @@ impl MusicDb {
             .conn
             .prepare(
                 "SELECT cache_key, track_name, artist_name, album_name, duration_sec, plain_lyrics,
-                        synced_lyrics, lrclib_id, source, fetched_at_epoch_sec
+                        synced_lyrics, lrclib_id, source, fetched_at_epoch_sec, provenance
                  FROM lyrics_cache
                  WHERE cache_key = ?1",
             )
@@ -103,6 +103,9 @@ impl MusicDb {
             fetched_at_epoch_sec: row
                 .get(9)
                 .map_err(|e| format!("Failed reading fetched_at_epoch_sec: {e}"))?,
+            provenance: row
+                .get(10)
+                .map_err(|e| format!("Failed reading provenance: {e}"))?,
         }))
     }
 
@@ -111,8 +114,9 @@ impl MusicDb {
             .execute(
                 "INSERT OR REPLACE INTO lyrics_cache (
                     cache_key, track_name, artist_name, album_name, duration_sec,
-                    plain_lyrics, synced_lyrics, lrclib_id, source, fetched_at_epoch_sec
-                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    plain_lyrics, synced_lyrics, lrclib_id, source, fetched_at_epoch_sec,
+                    provenance
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
                 params![
                     &row.cache_key,
                     &row.track_name,
@@ -124,12 +128,57 @@ impl MusicDb {
                     row.lrclib_id,
                     &row.source,
                     row.fetched_at_epoch_sec,
+                    &row.provenance,
                 ],
             )
             .map_err(|e| format!("Failed upserting lyrics cache row: {e}"))?;
         Ok(())
     }
 
+    /// Delete `no_match` rows older than `negative_ttl_secs` and other remote rows older
+    /// than `remote_ttl_secs`. User-edited rows never expire. Returns the number removed.
+    pub fn delete_expired_lyrics_cache(
+        &self,
+        now_epoch_sec: i64,
+        remote_ttl_secs: i64,
+        negative_ttl_secs: i64,
+    ) -> Result<usize, String> {
+        self.conn
+            .execute(
+                "DELETE FROM lyrics_cache
+                 WHERE (source = 'no_match' AND fetched_at_epoch_sec < ?1)
+                    OR (source NOT IN ('no_match', 'user_edited') AND fetched_at_epoch_sec < ?2)",
+                params![
+                    now_epoch_sec.saturating_sub(negative_ttl_secs),
+                    now_epoch_sec.saturating_sub(remote_ttl_secs),
+                ],
+            )
+            .map_err(|e| format!("Failed deleting expired lyrics cache rows: {e}"))
+    }
+
+    /// Row count and text size of lyrics cached from remote providers.
+    pub fn lyrics_cache_usage(&self) -> Result<(usize, u64), String> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*),
+                        COALESCE(SUM(LENGTH(COALESCE(plain_lyrics, ''))
+                            + LENGTH(COALESCE(synced_lyrics, ''))), 0)
+                 FROM lyrics_cache
+                 WHERE source != 'user_edited'",
+                [],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            )
+            .map(|(rows, bytes)| (rows.max(0) as usize, bytes.max(0) as u64))
+            .map_err(|e| format!("Failed measuring lyrics cache: {e}"))
+    }
+
+    /// Delete every remotely fetched lyrics row. User-edited rows stay.
+    pub fn clear_lyrics_cache(&self) -> Result<usize, String> {
+        self.conn
+            .execute("DELETE FROM lyrics_cache WHERE source != 'user_edited'", [])
+            .map_err(|e| format!("Failed clearing lyrics cache: {e}"))
+    }
+
     pub fn get_track_media_state(
         &self,
         track_id: &str,
@@ -444,3 +493,61 @@ fn now_epoch_sec_i64() -> i64 {
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrobble::now_epoch_sec;
+
+    fn cache_row(cache_key: &str, source: &str, fetched_at_epoch_sec: i64) -> LyricsCacheRow {
+        LyricsCacheRow {
+            cache_key: cache_key.to_string(),
+            track_name: cache_key.to_string(),
+            artist_name: "Artist".to_string(),
+            album_name: String::new(),
+            duration_sec: None,
+            plain_lyrics: None,
+            synced_lyrics: None,
+            lrclib_id: None,
+            source: source.to_string(),
+            fetched_at_epoch_sec,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn expired_lyrics_cache_rows_use_their_source_ttl() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-lyrics-cache-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).unwrap();
+        // now = 1_000, remote TTL 100, negative TTL 10.
+        for row in [
+            cache_row("remote-at-cutoff", "lrclib_live", 900),
+            cache_row("remote-expired", "lrclib_cached", 899),
+            cache_row("miss-at-cutoff", "no_match", 990),
+            cache_row("miss-expired", "no_match", 989),
+            cache_row("remote-past-miss-ttl", "sidecar_synced", 980),
+            cache_row("edited", "user_edited", 0),
+        ] {
+            db.upsert_lyrics_cache(&row).unwrap();
+        }
+
+        assert_eq!(db.delete_expired_lyrics_cache(1_000, 100, 10).unwrap(), 2);
+        for key in [
+            "remote-at-cutoff",
+            "miss-at-cutoff",
+            "remote-past-miss-ttl",
+            "edited",
+        ] {
+            assert!(db.get_lyrics_cache(key).unwrap().is_some(), "{key}");
+        }
+        for key in ["remote-expired", "miss-expired"] {
+            assert!(db.get_lyrics_cache(key).unwrap().is_none(), "{key}");
+        }
+        assert_eq!(db.lyrics_cache_usage().unwrap().0, 3);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
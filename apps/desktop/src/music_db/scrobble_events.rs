@@ -0,0 +1,275 @@
+use super::*;
+
+impl MusicDb {
+    pub fn scrobble_sync_cursor(
+        &self,
+        scope: &ScrobbleScope,
+    ) -> Result<Option<ScrobbleSyncCursor>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT block_number, block_hash, source, synced_at
+                 FROM scrobble_sync_cursor WHERE scope = ?1",
+            )
+            .map_err(|e| format!("Failed preparing scrobble cursor query: {e}"))?;
+        let mut rows = stmt
+            .query_map(params![scope.key()], |row| {
+                Ok(ScrobbleSyncCursor {
+                    block_number: row.get::<_, i64>(0)?.max(0) as u64,
+                    block_hash: row.get(1)?,
+                    source: row.get(2)?,
+                    synced_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed querying scrobble cursor: {e}"))?;
+        rows.next()
+            .transpose()
+            .map_err(|e| format!("Failed reading scrobble cursor: {e}"))
+    }
+
+    /// Replace the scope's events in the batch range, record track metadata and move the
+    /// cursor to the end of the batch. Returns the number of events now stored for the range.
+    pub fn apply_scrobble_sync(
+        &self,
+        scope: &ScrobbleScope,
+        batch: &ScrobbleSyncBatch,
+        now: i64,
+    ) -> Result<usize, String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble sync transaction: {e}"))?;
+        let (from, to) = (batch.from_block as i64, batch.to_block as i64);
+        match scope {
+            ScrobbleScope::User(address) => tx.execute(
+                "DELETE FROM scrobble_events
+                 WHERE user_address = ?1 AND block_number BETWEEN ?2 AND ?3",
+                params![address, from, to],
+            ),
+            ScrobbleScope::All => tx.execute(
+                "DELETE FROM scrobble_events WHERE block_number BETWEEN ?1 AND ?2",
+                params![from, to],
+            ),
+        }
+        .map_err(|e| format!("Failed clearing scrobble_events range: {e}"))?;
+
+        let mut stored = 0;
+        for event in &batch.events {
+            if event.block_number < batch.from_block || event.block_number > batch.to_block {
+                continue;
+            }
+            if let ScrobbleScope::User(address) = scope {
+                if &event.user_address != address {
+                    continue;
+                }
+            }
+            stored += tx
+                .execute(
+                    "INSERT OR REPLACE INTO scrobble_events (
+                        user_address, track_id, played_at_sec, block_number, log_index, source
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        event.user_address.to_ascii_lowercase(),
+                        event.track_id.to_ascii_lowercase(),
+                        event.played_at_sec as i64,
+                        event.block_number as i64,
+                        event.log_index as i64,
+                        batch.source,
+                    ],
+                )
+                .map_err(|e| format!("Failed inserting scrobble_events row: {e}"))?;
+        }
+
+        for track in &batch.tracks {
+            upsert_scrobble_track(&tx, track, now)?;
+        }
+
+        tx.execute(
+            "INSERT INTO scrobble_sync_cursor (scope, block_number, block_hash, source, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(scope) DO UPDATE SET
+                block_number = excluded.block_number,
+                block_hash = excluded.block_hash,
+                source = excluded.source,
+                synced_at = excluded.synced_at",
+            params![scope.key(), to, batch.to_block_hash, batch.source, now],
+        )
+        .map_err(|e| format!("Failed updating scrobble cursor: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble sync: {e}"))?;
+        Ok(stored)
+    }
+
+    pub fn upsert_scrobble_tracks(
+        &self,
+        tracks: &[ScrobbleTrackMeta],
+        now: i64,
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_tracks transaction: {e}"))?;
+        for track in tracks {
+            upsert_scrobble_track(&tx, track, now)?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble_tracks: {e}"))
+    }
+
+    /// Track ids with stored events but no metadata yet.
+    pub fn scrobble_tracks_missing_meta(&self, limit: usize) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT e.track_id FROM scrobble_events e
+                 LEFT JOIN scrobble_tracks t ON t.track_id = e.track_id
+                 WHERE t.track_id IS NULL
+                 LIMIT ?1",
+            )
+            .map_err(|e| format!("Failed preparing scrobble metadata query: {e}"))?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed querying scrobble metadata gaps: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble metadata gap: {e}"))
+    }
+
+    /// The wallet's latest stored plays, newest first.
+    pub fn recent_scrobbles(
+        &self,
+        user_address: &str,
+        limit: usize,
+    ) -> Result<Vec<StoredScrobble>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT e.track_id, e.played_at_sec, {TRACK_META_COLUMNS}
+                 FROM scrobble_events e
+                 LEFT JOIN scrobble_tracks t ON t.track_id = e.track_id
+                 WHERE e.user_address = ?1
+                 ORDER BY e.block_number DESC, e.log_index DESC
+                 LIMIT ?2"
+            ))
+            .map_err(|e| format!("Failed preparing scrobble history query: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address, limit as i64], |row| {
+                Ok(StoredScrobble {
+                    track_id: row.get(0)?,
+                    played_at_sec: row.get::<_, i64>(1)?.max(0) as u64,
+                    meta: track_meta_from_row(row, 2)?,
+                })
+            })
+            .map_err(|e| format!("Failed querying scrobble history: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble history row: {e}"))
+    }
+
+    /// `(track_id, played_at_sec)` of every stored on-chain play for the wallet.
+    pub fn stored_scrobble_keys(
+        &self,
+        user_address: &str,
+    ) -> Result<HashSet<(String, u64)>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT track_id, played_at_sec FROM scrobble_events WHERE user_address = ?1")
+            .map_err(|e| format!("Failed preparing stored scrobble key query: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?.max(0) as u64,
+                ))
+            })
+            .map_err(|e| format!("Failed querying stored scrobble keys: {e}"))?;
+        rows.collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| format!("Failed reading stored scrobble key: {e}"))
+    }
+
+    /// Play counts across every wallet for tracks with known metadata, most played first.
+    pub fn scrobbled_track_stats(&self) -> Result<Vec<ScrobbledTrackStats>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {TRACK_META_COLUMNS}, COUNT(e.track_id) AS plays
+                 FROM scrobble_tracks t
+                 JOIN scrobble_events e ON e.track_id = t.track_id
+                 GROUP BY t.track_id
+                 ORDER BY plays DESC, t.track_id ASC"
+            ))
+            .map_err(|e| format!("Failed preparing scrobble stats query: {e}"))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((track_meta_from_row(row, 0)?, row.get::<_, i64>(7)?))
+            })
+            .map_err(|e| format!("Failed querying scrobble stats: {e}"))?;
+        let mut stats = Vec::new();
+        for row in rows {
+            let (meta, plays) = row.map_err(|e| format!("Failed reading scrobble stats: {e}"))?;
+            if let Some(meta) = meta {
+                stats.push(ScrobbledTrackStats {
+                    meta,
+                    plays: plays.max(0) as u64,
+                });
+            }
+        }
+        Ok(stats)
+    }
+}
+
+const TRACK_META_COLUMNS: &str =
+    "t.track_id, t.title, t.artist, t.album, t.cover_cid, t.kind, t.verified_plays";
+
+fn track_meta_from_row(
+    row: &rusqlite::Row<'_>,
+    offset: usize,
+) -> rusqlite::Result<Option<ScrobbleTrackMeta>> {
+    let Some(track_id) = row.get::<_, Option<String>>(offset)? else {
+        return Ok(None);
+    };
+    Ok(Some(ScrobbleTrackMeta {
+        track_id,
+        title: row.get(offset + 1)?,
+        artist: row.get(offset + 2)?,
+        album: row.get(offset + 3)?,
+        cover_cid: row.get(offset + 4)?,
+        kind: row.get(offset + 5)?,
+        verified_plays: row
+            .get::<_, Option<i64>>(offset + 6)?
+            .map(|v| v.max(0) as u64),
+    }))
+}
+
+/// Metadata from the RPC path has no verified count; keep the last one the subgraph gave.
+fn upsert_scrobble_track(
+    conn: &Connection,
+    track: &ScrobbleTrackMeta,
+    now: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO scrobble_tracks (
+            track_id, title, artist, album, cover_cid, kind, verified_plays, updated_at
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT(track_id) DO UPDATE SET
+            title = excluded.title,
+            artist = excluded.artist,
+            album = excluded.album,
+            cover_cid = excluded.cover_cid,
+            kind = COALESCE(excluded.kind, scrobble_tracks.kind),
+            verified_plays = COALESCE(excluded.verified_plays, scrobble_tracks.verified_plays),
+            updated_at = excluded.updated_at",
+        params![
+            track.track_id.to_ascii_lowercase(),
+            track.title,
+            track.artist,
+            track.album,
+            track.cover_cid,
+            track.kind,
+            track.verified_plays.map(|v| v as i64),
+            now,
+        ],
+    )
+    .map_err(|e| format!("Failed upserting scrobble_tracks row: {e}"))?;
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+impl MusicDb {
+    /// Stage parsed history for `user_address`. Plays already staged (same track id and
+    /// timestamp) are ignored, so re-importing a file is a no-op. Returns rows inserted.
+    pub fn stage_imported_scrobbles(
+        &self,
+        user_address: &str,
+        scrobbles: &[ImportedScrobble],
+        now: i64,
+    ) -> Result<usize, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_import transaction: {e}"))?;
+        let mut inserted = 0;
+        for scrobble in scrobbles {
+            inserted += tx
+                .execute(
+                    "INSERT OR IGNORE INTO scrobble_import (
+                        user_address, source, track_id, artist, title, album, mbid,
+                        duration_sec, played_at_sec, status, attempts, next_attempt_at,
+                        created_at, updated_at
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'pending', 0, ?10, ?10, ?10)",
+                    params![
+                        user_address,
+                        scrobble.source,
+                        scrobble.track_id.to_ascii_lowercase(),
+                        scrobble.artist,
+                        scrobble.title,
+                        scrobble.album,
+                        scrobble.mbid,
+                        scrobble.duration_sec,
+                        scrobble.played_at_sec as i64,
+                        now,
+                    ],
+                )
+                .map_err(|e| format!("Failed inserting scrobble_import row: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble_import rows: {e}"))?;
+        Ok(inserted)
+    }
+
+    /// `(track_id, played_at_sec)` for every play this wallet already has on chain via the
+    /// outbox or a previous import.
+    pub fn known_scrobble_keys(
+        &self,
+        user_address: &str,
+    ) -> Result<HashSet<(String, u64)>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT track_id, played_at_sec FROM scrobble_outbox
+                 WHERE status IN ('confirmed', 'unconfirmed') AND user_address = ?1
+                   AND track_id IS NOT NULL
+                 UNION
+                 SELECT track_id, played_at_sec FROM scrobble_import WHERE user_address = ?1",
+            )
+            .map_err(|e| format!("Failed preparing scrobble key query: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address], |row| {
+                Ok((
+                    row.get::<_, String>(0)?.to_ascii_lowercase(),
+                    row.get::<_, i64>(1)?.max(0) as u64,
+                ))
+            })
+            .map_err(|e| format!("Failed querying scrobble keys: {e}"))?;
+        rows.collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble key: {e}"))
+    }
+
+    /// Pending imported plays whose backoff has elapsed, oldest first.
+    pub fn due_imported_scrobbles(
+        &self,
+        user_address: &str,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<ImportedScrobbleRow>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, attempts, source, track_id, artist, title, album, mbid,
+                        duration_sec, played_at_sec
+                 FROM scrobble_import
+                 WHERE user_address = ?1 AND status = 'pending' AND next_attempt_at <= ?2
+                 ORDER BY played_at_sec ASC, id ASC
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed preparing scrobble_import query: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address, now, limit as i64], imported_row)
+            .map_err(|e| format!("Failed querying scrobble_import: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble_import row: {e}"))
+    }
+
+    pub fn mark_imported_scrobbles_submitted(
+        &self,
+        ids: &[i64],
+        tx_hash: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_import transaction: {e}"))?;
+        for id in ids {
+            tx.execute(
+                "UPDATE scrobble_import
+                 SET status = 'submitted', tx_hash = ?2, last_error = NULL, updated_at = ?3
+                 WHERE id = ?1",
+                params![id, tx_hash, now],
+            )
+            .map_err(|e| format!("Failed marking scrobble_import row: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble_import submission: {e}"))
+    }
+
+    /// Park imported plays whose batch `tx_hash` was broadcast but never got a receipt, until
+    /// the receipt is checked at `check_at`.
+    pub fn mark_imported_scrobbles_unconfirmed(
+        &self,
+        ids: &[i64],
+        tx_hash: &str,
+        error: &str,
+        check_at: i64,
+        now: i64,
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_import transaction: {e}"))?;
+        for id in ids {
+            tx.execute(
+                "UPDATE scrobble_import
+                 SET status = 'unconfirmed', tx_hash = ?2, last_error = ?3, next_attempt_at = ?4,
+                     updated_at = ?5
+                 WHERE id = ?1",
+                params![id, tx_hash, error, check_at, now],
+            )
+            .map_err(|e| format!("Failed parking scrobble_import row: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing unconfirmed imports: {e}"))
+    }
+
+    /// The oldest imported batch of `user_address` parked by
+    /// [`Self::mark_imported_scrobbles_unconfirmed`] whose receipt check is due.
+    pub fn due_unconfirmed_imported_scrobbles(
+        &self,
+        user_address: &str,
+        now: i64,
+    ) -> Result<Option<UnconfirmedScrobbles<ImportedScrobbleRow>>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let tx_hash: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT tx_hash FROM scrobble_import
+                 WHERE status = 'unconfirmed' AND user_address = ?1 AND next_attempt_at <= ?2
+                 ORDER BY next_attempt_at ASC, id ASC
+                 LIMIT 1",
+                params![user_address, now],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed querying unconfirmed imports: {e}"))?;
+        let Some(tx_hash) = tx_hash else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, attempts, source, track_id, artist, title, album, mbid,
+                        duration_sec, played_at_sec
+                 FROM scrobble_import
+                 WHERE status = 'unconfirmed' AND tx_hash = ?1
+                 ORDER BY played_at_sec ASC, id ASC",
+            )
+            .map_err(|e| format!("Failed preparing unconfirmed imports query: {e}"))?;
+        let rows = stmt
+            .query_map(params![tx_hash], imported_row)
+            .map_err(|e| format!("Failed querying unconfirmed imports: {e}"))?
+            .map(|row| {
+                row.map(|row| {
+                    let track_id = row.scrobble.track_id.clone();
+                    (row, track_id)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading unconfirmed import: {e}"))?;
+        Ok(Some(UnconfirmedScrobbles { tx_hash, rows }))
+    }
+
+    /// Count a failed attempt. `next_attempt_at: None` parks the play as failed.
+    pub fn record_imported_scrobble_failure(
+        &self,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        let status = if next_attempt_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        self.conn
+            .execute(
+                "UPDATE scrobble_import
+                 SET status = ?2, attempts = attempts + 1, next_attempt_at = ?3,
+                     last_error = ?4, updated_at = ?5
+                 WHERE id = ?1",
+                params![id, status, next_attempt_at.unwrap_or(now), error, now],
+            )
+            .map_err(|e| format!("Failed updating scrobble_import failure: {e}"))?;
+        Ok(())
+    }
+
+    /// Import progress for `user_address`; `confirmed` counts submitted plays.
+    pub fn imported_scrobble_counts(
+        &self,
+        user_address: &str,
+    ) -> Result<ScrobbleOutboxCounts, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT status, COUNT(*) FROM scrobble_import
+                 WHERE user_address = ?1 GROUP BY status",
+            )
+            .map_err(|e| format!("Failed preparing scrobble_import counts: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(|e| format!("Failed querying scrobble_import counts: {e}"))?;
+
+        let mut counts = ScrobbleOutboxCounts::default();
+        for row in rows {
+            let (status, count) =
+                row.map_err(|e| format!("Failed reading scrobble_import count: {e}"))?;
+            let count = count.max(0) as usize;
+            match status.as_str() {
+                "pending" | "unconfirmed" => counts.pending += count,
+                "submitted" => counts.confirmed += count,
+                "failed" => counts.failed += count,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+
+    pub fn next_imported_scrobble_attempt_at(
+        &self,
+        user_address: &str,
+    ) -> Result<Option<i64>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT MIN(next_attempt_at) FROM scrobble_import
+                 WHERE user_address = ?1 AND status IN ('pending', 'unconfirmed')",
+                params![user_address],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed querying next import attempt: {e}"))
+    }
+}
+
+/// `id, attempts, source, track_id, artist, title, album, mbid, duration_sec, played_at_sec`
+/// as selected by the import queries.
+fn imported_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ImportedScrobbleRow> {
+    Ok(ImportedScrobbleRow {
+        id: row.get(0)?,
+        attempts: row.get(1)?,
+        scrobble: ImportedScrobble {
+            source: row.get(2)?,
+            track_id: row.get(3)?,
+            artist: row.get(4)?,
+            title: row.get(5)?,
+            album: row.get(6)?,
+            mbid: row.get(7)?,
+            duration_sec: row.get(8)?,
+            played_at_sec: row.get::<_, i64>(9)?.max(0) as u64,
+        },
+    })
+}
@@ -0,0 +1,311 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+impl MusicDb {
+    /// Record a finished play. Returns `false` when the same play was already queued.
+    pub fn enqueue_scrobble(
+        &self,
+        user_address: Option<&str>,
+        play: &ScrobblePlay,
+        now: i64,
+    ) -> Result<bool, String> {
+        let user_address = user_address
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_ascii_lowercase);
+        let inserted = self
+            .conn
+            .execute(
+                "INSERT OR IGNORE INTO scrobble_outbox (
+                    user_address, track_path, cover_path, artist, title, album, mbid, ip_id,
+                    duration_sec, played_at_sec, status, attempts, next_attempt_at,
+                    created_at, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'pending', 0, ?11, ?11, ?11)",
+                params![
+                    user_address,
+                    play.track_path,
+                    play.cover_path,
+                    play.artist,
+                    play.title,
+                    play.album,
+                    play.mbid,
+                    play.ip_id,
+                    play.duration_sec,
+                    play.played_at_sec as i64,
+                    now,
+                ],
+            )
+            .map_err(|e| format!("Failed inserting scrobble_outbox row: {e}"))?;
+        Ok(inserted > 0)
+    }
+
+    /// Hand plays recorded while signed out to `user_address`, the next account to sign in.
+    /// Until then no account flushes them.
+    pub fn claim_signed_out_scrobbles(
+        &self,
+        user_address: &str,
+        now: i64,
+    ) -> Result<usize, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "UPDATE scrobble_outbox SET user_address = ?1, updated_at = ?2
+                 WHERE user_address IS NULL",
+                params![user_address, now],
+            )
+            .map_err(|e| format!("Failed claiming signed-out scrobbles: {e}"))
+    }
+
+    /// Pending plays of `user_address` whose backoff has elapsed, oldest first.
+    pub fn due_scrobbles(
+        &self,
+        user_address: &str,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<ScrobbleOutboxRow>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, attempts, track_path, cover_path, artist, title, album, mbid, ip_id,
+                        duration_sec, played_at_sec
+                 FROM scrobble_outbox
+                 WHERE status = 'pending'
+                   AND next_attempt_at <= ?2
+                   AND user_address = ?1
+                 ORDER BY played_at_sec ASC, id ASC
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed preparing scrobble_outbox query: {e}"))?;
+
+        let rows = stmt
+            .query_map(params![user_address, now, limit as i64], outbox_row)
+            .map_err(|e| format!("Failed querying scrobble_outbox: {e}"))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble_outbox row: {e}"))
+    }
+
+    /// Mark plays as confirmed by `tx_hash`; `confirmed` pairs each row id with its track id.
+    pub fn mark_scrobbles_confirmed(
+        &self,
+        confirmed: &[(i64, String)],
+        user_address: &str,
+        tx_hash: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_outbox transaction: {e}"))?;
+        for (id, track_id) in confirmed {
+            tx.execute(
+                "UPDATE scrobble_outbox
+                 SET status = 'confirmed', user_address = ?2, tx_hash = ?3, track_id = ?4,
+                     last_error = NULL, updated_at = ?5
+                 WHERE id = ?1",
+                params![
+                    id,
+                    user_address,
+                    tx_hash,
+                    track_id.to_ascii_lowercase(),
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed confirming scrobble_outbox row: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble_outbox confirmation: {e}"))
+    }
+
+    /// Park plays whose batch `tx_hash` was broadcast but never got a receipt. They are not
+    /// sent again until the receipt is checked at `check_at`; `rows` pairs each row id with
+    /// its track id.
+    pub fn mark_scrobbles_unconfirmed(
+        &self,
+        rows: &[(i64, String)],
+        tx_hash: &str,
+        error: &str,
+        check_at: i64,
+        now: i64,
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble_outbox transaction: {e}"))?;
+        for (id, track_id) in rows {
+            tx.execute(
+                "UPDATE scrobble_outbox
+                 SET status = 'unconfirmed', tx_hash = ?2, track_id = ?3, last_error = ?4,
+                     next_attempt_at = ?5, updated_at = ?6
+                 WHERE id = ?1",
+                params![
+                    id,
+                    tx_hash,
+                    track_id.to_ascii_lowercase(),
+                    error,
+                    check_at,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed parking scrobble_outbox row: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing unconfirmed scrobbles: {e}"))
+    }
+
+    /// The oldest batch of `user_address` parked by [`Self::mark_scrobbles_unconfirmed`] whose
+    /// receipt check is due.
+    pub fn due_unconfirmed_scrobbles(
+        &self,
+        user_address: &str,
+        now: i64,
+    ) -> Result<Option<UnconfirmedScrobbles<ScrobbleOutboxRow>>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let tx_hash: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT tx_hash FROM scrobble_outbox
+                 WHERE status = 'unconfirmed' AND user_address = ?1 AND next_attempt_at <= ?2
+                 ORDER BY next_attempt_at ASC, id ASC
+                 LIMIT 1",
+                params![user_address, now],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed querying unconfirmed scrobbles: {e}"))?;
+        let Some(tx_hash) = tx_hash else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, attempts, track_path, cover_path, artist, title, album, mbid, ip_id,
+                        duration_sec, played_at_sec, track_id
+                 FROM scrobble_outbox
+                 WHERE status = 'unconfirmed' AND tx_hash = ?1
+                 ORDER BY played_at_sec ASC, id ASC",
+            )
+            .map_err(|e| format!("Failed preparing unconfirmed scrobbles query: {e}"))?;
+        let rows = stmt
+            .query_map(params![tx_hash], |row| {
+                Ok((
+                    outbox_row(row)?,
+                    row.get::<_, Option<String>>(11)?.unwrap_or_default(),
+                ))
+            })
+            .map_err(|e| format!("Failed querying unconfirmed scrobbles: {e}"))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading unconfirmed scrobble: {e}"))?;
+        Ok(Some(UnconfirmedScrobbles { tx_hash, rows }))
+    }
+
+    /// Count a failed attempt. `next_attempt_at: None` gives up and marks the play failed.
+    pub fn record_scrobble_failure(
+        &self,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        let status = if next_attempt_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        self.conn
+            .execute(
+                "UPDATE scrobble_outbox
+                 SET status = ?2, attempts = attempts + 1, next_attempt_at = ?3,
+                     last_error = ?4, updated_at = ?5
+                 WHERE id = ?1",
+                params![id, status, next_attempt_at.unwrap_or(now), error, now],
+            )
+            .map_err(|e| format!("Failed updating scrobble_outbox failure: {e}"))?;
+        Ok(())
+    }
+
+    /// Move `user_address`'s failed plays back to pending with a fresh attempt budget.
+    pub fn retry_failed_scrobbles(&self, user_address: &str, now: i64) -> Result<usize, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "UPDATE scrobble_outbox
+                 SET status = 'pending', attempts = 0, next_attempt_at = ?1, updated_at = ?1
+                 WHERE status = 'failed' AND user_address = ?2",
+                params![now, user_address],
+            )
+            .map_err(|e| format!("Failed resetting failed scrobbles: {e}"))
+    }
+
+    /// Outbox counts for `user_address`, or for the unclaimed signed-out plays with `None`.
+    pub fn scrobble_outbox_counts(
+        &self,
+        user_address: Option<&str>,
+    ) -> Result<ScrobbleOutboxCounts, String> {
+        let user_address = user_address.map(|value| value.trim().to_ascii_lowercase());
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT status, COUNT(*) FROM scrobble_outbox
+                 WHERE user_address IS ?1
+                 GROUP BY status",
+            )
+            .map_err(|e| format!("Failed preparing scrobble_outbox counts: {e}"))?;
+        let rows = stmt
+            .query_map(params![user_address], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(|e| format!("Failed querying scrobble_outbox counts: {e}"))?;
+
+        let mut counts = ScrobbleOutboxCounts::default();
+        for row in rows {
+            let (status, count) =
+                row.map_err(|e| format!("Failed reading scrobble_outbox count: {e}"))?;
+            let count = count.max(0) as usize;
+            match status.as_str() {
+                // Unconfirmed plays still wait on their receipt.
+                "pending" | "unconfirmed" => counts.pending += count,
+                "confirmed" => counts.confirmed += count,
+                "failed" => counts.failed += count,
+                _ => {}
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Earliest retry or receipt check among `user_address`'s waiting plays.
+    pub fn next_scrobble_attempt_at(&self, user_address: &str) -> Result<Option<i64>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT MIN(next_attempt_at) FROM scrobble_outbox
+                 WHERE status IN ('pending', 'unconfirmed') AND user_address = ?1",
+                params![user_address],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed querying next scrobble attempt: {e}"))
+    }
+}
+
+/// `id, attempts, track_path, cover_path, artist, title, album, mbid, ip_id, duration_sec,
+/// played_at_sec` as selected by the outbox queries.
+fn outbox_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ScrobbleOutboxRow> {
+    Ok(ScrobbleOutboxRow {
+        id: row.get(0)?,
+        attempts: row.get(1)?,
+        play: ScrobblePlay {
+            track_path: row.get(2)?,
+            cover_path: row.get(3)?,
+            artist: row.get(4)?,
+            title: row.get(5)?,
+            album: row.get(6)?,
+            mbid: row.get(7)?,
+            ip_id: row.get(8)?,
+            duration_sec: row.get(9)?,
+            played_at_sec: row.get::<_, i64>(10)?.max(0) as u64,
+        },
+    })
+}
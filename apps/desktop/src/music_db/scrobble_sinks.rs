@@ -0,0 +1,214 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+impl MusicDb {
+    /// Plays of `user_address` recorded since `enabled_since` that `sink` has not delivered
+    /// yet and whose per-sink backoff has elapsed, oldest first. `attempts` is the sink's own
+    /// attempt count.
+    pub fn due_sink_scrobbles(
+        &self,
+        sink: &str,
+        user_address: &str,
+        enabled_since: i64,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<ScrobbleOutboxRow>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT o.id, COALESCE(d.attempts, 0), o.track_path, o.cover_path, o.artist,
+                        o.title, o.album, o.mbid, o.ip_id, o.duration_sec, o.played_at_sec
+                 FROM scrobble_outbox o
+                 LEFT JOIN scrobble_sink_deliveries d ON d.outbox_id = o.id AND d.sink = ?1
+                 WHERE o.user_address = ?2 AND o.created_at >= ?3
+                   AND (d.status IS NULL OR (d.status = 'pending' AND d.next_attempt_at <= ?4))
+                 ORDER BY o.played_at_sec ASC, o.id ASC
+                 LIMIT ?5",
+            )
+            .map_err(|e| format!("Failed preparing scrobble sink query: {e}"))?;
+
+        let rows = stmt
+            .query_map(
+                params![sink, user_address, enabled_since, now, limit as i64],
+                |row| {
+                    Ok(ScrobbleOutboxRow {
+                        id: row.get(0)?,
+                        attempts: row.get(1)?,
+                        play: ScrobblePlay {
+                            track_path: row.get(2)?,
+                            cover_path: row.get(3)?,
+                            artist: row.get(4)?,
+                            title: row.get(5)?,
+                            album: row.get(6)?,
+                            mbid: row.get(7)?,
+                            ip_id: row.get(8)?,
+                            duration_sec: row.get(9)?,
+                            played_at_sec: row.get::<_, i64>(10)?.max(0) as u64,
+                        },
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed querying scrobble sink deliveries: {e}"))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading scrobble sink row: {e}"))
+    }
+
+    pub fn mark_sink_delivered(
+        &self,
+        sink: &str,
+        outbox_ids: &[i64],
+        reference: Option<&str>,
+        now: i64,
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting scrobble sink transaction: {e}"))?;
+        for id in outbox_ids {
+            tx.execute(
+                "INSERT INTO scrobble_sink_deliveries (
+                    outbox_id, sink, status, attempts, next_attempt_at, reference, updated_at
+                 ) VALUES (?1, ?2, 'delivered', 0, ?4, ?3, ?4)
+                 ON CONFLICT(outbox_id, sink) DO UPDATE SET
+                    status = 'delivered', reference = ?3, last_error = NULL, updated_at = ?4",
+                params![id, sink, reference, now],
+            )
+            .map_err(|e| format!("Failed marking scrobble sink delivery: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing scrobble sink delivery: {e}"))
+    }
+
+    /// Count a failed delivery. `next_attempt_at: None` gives up on this play for this sink only.
+    pub fn record_sink_failure(
+        &self,
+        sink: &str,
+        outbox_id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        let status = if next_attempt_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+        self.conn
+            .execute(
+                "INSERT INTO scrobble_sink_deliveries (
+                    outbox_id, sink, status, attempts, next_attempt_at, last_error, updated_at
+                 ) VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6)
+                 ON CONFLICT(outbox_id, sink) DO UPDATE SET
+                    status = ?3, attempts = attempts + 1, next_attempt_at = ?4,
+                    last_error = ?5, updated_at = ?6",
+                params![
+                    outbox_id,
+                    sink,
+                    status,
+                    next_attempt_at.unwrap_or(now),
+                    error,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed updating scrobble sink failure: {e}"))?;
+        Ok(())
+    }
+
+    /// Move every failed delivery of `user_address`'s plays, for every sink, back to pending
+    /// with a fresh budget.
+    pub fn retry_failed_sink_deliveries(
+        &self,
+        user_address: &str,
+        now: i64,
+    ) -> Result<usize, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "UPDATE scrobble_sink_deliveries
+                 SET status = 'pending', attempts = 0, next_attempt_at = ?1, updated_at = ?1
+                 WHERE status = 'failed'
+                   AND outbox_id IN (SELECT id FROM scrobble_outbox WHERE user_address = ?2)",
+                params![now, user_address],
+            )
+            .map_err(|e| format!("Failed resetting failed sink deliveries: {e}"))
+    }
+
+    /// Delivery counts for `sink` over `user_address`'s plays it is responsible for;
+    /// `confirmed` counts delivered plays.
+    pub fn sink_delivery_counts(
+        &self,
+        sink: &str,
+        user_address: &str,
+        enabled_since: i64,
+    ) -> Result<ScrobbleOutboxCounts, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT
+                    SUM(CASE WHEN d.status IS NULL OR d.status = 'pending' THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN d.status = 'delivered' THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN d.status = 'failed' THEN 1 ELSE 0 END)
+                 FROM scrobble_outbox o
+                 LEFT JOIN scrobble_sink_deliveries d ON d.outbox_id = o.id AND d.sink = ?1
+                 WHERE o.user_address = ?2 AND o.created_at >= ?3",
+                params![sink, user_address, enabled_since],
+                |row| {
+                    let count = |idx: usize| -> rusqlite::Result<usize> {
+                        Ok(row.get::<_, Option<i64>>(idx)?.unwrap_or(0).max(0) as usize)
+                    };
+                    Ok(ScrobbleOutboxCounts {
+                        pending: count(0)?,
+                        confirmed: count(1)?,
+                        failed: count(2)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed querying scrobble sink counts: {e}"))
+    }
+
+    /// Most recent failure message for `sink`, if any delivery of `user_address`'s plays is
+    /// still failing.
+    pub fn last_sink_error(
+        &self,
+        sink: &str,
+        user_address: &str,
+    ) -> Result<Option<String>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT d.last_error FROM scrobble_sink_deliveries d
+                 JOIN scrobble_outbox o ON o.id = d.outbox_id
+                 WHERE d.sink = ?1 AND o.user_address = ?2
+                   AND d.status != 'delivered' AND d.last_error IS NOT NULL
+                 ORDER BY d.updated_at DESC LIMIT 1",
+                params![sink, user_address],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed querying scrobble sink error: {e}"))
+    }
+
+    /// Earliest time `sink` has something of `user_address`'s to send: a fresh play is due
+    /// immediately.
+    pub fn next_sink_attempt_at(
+        &self,
+        sink: &str,
+        user_address: &str,
+        enabled_since: i64,
+    ) -> Result<Option<i64>, String> {
+        let user_address = user_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT MIN(COALESCE(d.next_attempt_at, o.created_at))
+                 FROM scrobble_outbox o
+                 LEFT JOIN scrobble_sink_deliveries d ON d.outbox_id = o.id AND d.sink = ?1
+                 WHERE o.user_address = ?2 AND o.created_at >= ?3
+                   AND (d.status IS NULL OR d.status = 'pending')",
+                params![sink, user_address, enabled_since],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed querying next scrobble sink attempt: {e}"))
+    }
+}
@@ -0,0 +1,336 @@
+use super::*;
+
+const UPLOADED_TRACK_COLUMNS: &str = "owner_address, file_path, title, artist, album, track_id,
+     content_id, piece_cid, gateway_url, tx_hash, register_version, created_at_ms, saved_forever,
+     arweave_id, sync_state, sync_note";
+
+const SHARED_GRANT_COLUMNS: &str = "owner_address, grantee_address, title, artist, album, track_id,
+     content_id, piece_cid, gateway_url, tx_hash, mirror_tx_hash, shared_at_ms";
+
+fn uploaded_track_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<UploadedTrackRecord> {
+    Ok(UploadedTrackRecord {
+        owner_address: row.get(0)?,
+        file_path: row.get(1)?,
+        title: row.get(2)?,
+        artist: row.get(3)?,
+        album: row.get(4)?,
+        track_id: row.get(5)?,
+        content_id: row.get(6)?,
+        piece_cid: row.get(7)?,
+        gateway_url: row.get(8)?,
+        tx_hash: row.get(9)?,
+        register_version: row.get(10)?,
+        created_at_ms: row.get(11)?,
+        saved_forever: row.get(12)?,
+        arweave_id: row.get(13)?,
+        sync_state: RecordSyncState::parse(&row.get::<_, String>(14)?),
+        sync_note: row.get(15)?,
+    })
+}
+
+fn shared_grant_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SharedGrantRecord> {
+    Ok(SharedGrantRecord {
+        owner_address: row.get(0)?,
+        grantee_address: row.get(1)?,
+        title: row.get(2)?,
+        artist: row.get(3)?,
+        album: row.get(4)?,
+        track_id: row.get(5)?,
+        content_id: row.get(6)?,
+        piece_cid: row.get(7)?,
+        gateway_url: row.get(8)?,
+        tx_hash: row.get(9)?,
+        mirror_tx_hash: row.get(10)?,
+        shared_at_ms: row.get(11)?,
+    })
+}
+
+impl MusicDb {
+    pub fn uploaded_track_records(
+        &self,
+        owner_address: &str,
+    ) -> Result<Vec<UploadedTrackRecord>, String> {
+        self.query_uploaded_tracks(
+            &format!(
+                "SELECT {UPLOADED_TRACK_COLUMNS} FROM uploaded_tracks WHERE owner_address = ?1"
+            ),
+            params![owner_address.trim().to_ascii_lowercase()],
+        )
+    }
+
+    /// Records the sync engine should check: everything still pending plus whatever was last
+    /// checked before `checked_before`, least recently checked first.
+    pub fn uploaded_track_records_due_for_sync(
+        &self,
+        owner_address: &str,
+        checked_before: i64,
+        limit: usize,
+    ) -> Result<Vec<UploadedTrackRecord>, String> {
+        self.query_uploaded_tracks(
+            &format!(
+                "SELECT {UPLOADED_TRACK_COLUMNS} FROM uploaded_tracks
+                 WHERE owner_address = ?1 AND (sync_state = 'pending' OR checked_at < ?2)
+                 ORDER BY checked_at LIMIT ?3"
+            ),
+            params![
+                owner_address.trim().to_ascii_lowercase(),
+                checked_before,
+                limit as i64
+            ],
+        )
+    }
+
+    fn query_uploaded_tracks(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<UploadedTrackRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed preparing uploaded_tracks query: {e}"))?;
+        let rows = stmt
+            .query_map(params, uploaded_track_from_row)
+            .map_err(|e| format!("Failed querying uploaded_tracks: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading uploaded_tracks row: {e}"))
+    }
+
+    /// Insert or replace the record for its (owner, file). The next sync re-checks it.
+    pub fn upsert_uploaded_track_record(&self, record: &UploadedTrackRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO uploaded_tracks ({UPLOADED_TRACK_COLUMNS}, checked_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                             ?16, 0)"
+                ),
+                params![
+                    record.owner_address.trim().to_ascii_lowercase(),
+                    record.file_path,
+                    record.title,
+                    record.artist,
+                    record.album,
+                    record.track_id,
+                    record.content_id.trim().to_ascii_lowercase(),
+                    record.piece_cid,
+                    record.gateway_url,
+                    record.tx_hash,
+                    record.register_version,
+                    record.created_at_ms,
+                    record.saved_forever,
+                    record.arweave_id,
+                    record.sync_state.as_str(),
+                    record.sync_note,
+                ],
+            )
+            .map_err(|e| format!("Failed saving uploaded track record: {e}"))?;
+        Ok(())
+    }
+
+    pub fn set_uploaded_track_sync_state(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+        state: RecordSyncState,
+        note: Option<&str>,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE uploaded_tracks SET sync_state = ?3, sync_note = ?4, checked_at = ?5
+                 WHERE owner_address = ?1 AND file_path = ?2",
+                params![
+                    owner_address.trim().to_ascii_lowercase(),
+                    file_path,
+                    state.as_str(),
+                    note,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed updating uploaded track sync state: {e}"))?;
+        Ok(())
+    }
+
+    /// Point the record at a blob uploaded elsewhere. Any Arweave copy of the earlier blob is
+    /// kept on record; it still serves the track.
+    pub fn adopt_uploaded_track_piece(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+        piece_cid: &str,
+        gateway_url: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE uploaded_tracks
+                 SET piece_cid = ?3, gateway_url = ?4, sync_state = 'synced', sync_note = NULL,
+                     checked_at = ?5
+                 WHERE owner_address = ?1 AND file_path = ?2",
+                params![
+                    owner_address.trim().to_ascii_lowercase(),
+                    file_path,
+                    piece_cid,
+                    gateway_url,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed adopting remote pieceCid: {e}"))?;
+        Ok(())
+    }
+
+    /// Forget an upload, along with its storage status.
+    pub fn delete_uploaded_track_record(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+    ) -> Result<(), String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting uploaded_tracks transaction: {e}"))?;
+        for table in ["uploaded_tracks", "track_storage"] {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE owner_address = ?1 AND file_path = ?2"),
+                params![owner_address, file_path],
+            )
+            .map_err(|e| format!("Failed deleting from {table}: {e}"))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing uploaded_tracks delete: {e}"))
+    }
+
+    /// Grants issued to `grantee`, newest first.
+    pub fn shared_grant_records_for_grantee(
+        &self,
+        grantee_address: &str,
+    ) -> Result<Vec<SharedGrantRecord>, String> {
+        self.query_shared_grants(
+            &format!(
+                "SELECT {SHARED_GRANT_COLUMNS} FROM shared_grants
+                 WHERE grantee_address = ?1 ORDER BY shared_at_ms DESC"
+            ),
+            params![grantee_address.trim().to_ascii_lowercase()],
+        )
+    }
+
+    /// Grants `owner` has issued, newest first.
+    pub fn shared_grant_records_for_owner(
+        &self,
+        owner_address: &str,
+    ) -> Result<Vec<SharedGrantRecord>, String> {
+        self.query_shared_grants(
+            &format!(
+                "SELECT {SHARED_GRANT_COLUMNS} FROM shared_grants
+                 WHERE owner_address = ?1 ORDER BY shared_at_ms DESC"
+            ),
+            params![owner_address.trim().to_ascii_lowercase()],
+        )
+    }
+
+    /// Grants `owner` has issued for one content id, newest first.
+    pub fn shared_grant_records_for_content(
+        &self,
+        owner_address: &str,
+        content_id: &str,
+    ) -> Result<Vec<SharedGrantRecord>, String> {
+        self.query_shared_grants(
+            &format!(
+                "SELECT {SHARED_GRANT_COLUMNS} FROM shared_grants
+                 WHERE owner_address = ?1 AND content_id = ?2 ORDER BY shared_at_ms DESC"
+            ),
+            params![
+                owner_address.trim().to_ascii_lowercase(),
+                content_id.trim().to_ascii_lowercase()
+            ],
+        )
+    }
+
+    fn query_shared_grants(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<SharedGrantRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed preparing shared_grants query: {e}"))?;
+        let rows = stmt
+            .query_map(params, shared_grant_from_row)
+            .map_err(|e| format!("Failed querying shared_grants: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading shared_grants row: {e}"))
+    }
+
+    /// Insert or replace the grant for its (grantee, content), so re-sharing keeps one row.
+    pub fn upsert_shared_grant_record(&self, record: &SharedGrantRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO shared_grants ({SHARED_GRANT_COLUMNS})
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+                ),
+                params![
+                    record.owner_address.trim().to_ascii_lowercase(),
+                    record.grantee_address.trim().to_ascii_lowercase(),
+                    record.title,
+                    record.artist,
+                    record.album,
+                    record.track_id,
+                    record.content_id.trim().to_ascii_lowercase(),
+                    record.piece_cid,
+                    record.gateway_url,
+                    record.tx_hash,
+                    record.mirror_tx_hash,
+                    record.shared_at_ms,
+                ],
+            )
+            .map_err(|e| format!("Failed saving shared grant record: {e}"))?;
+        Ok(())
+    }
+
+    pub fn delete_shared_grant_record(
+        &self,
+        grantee_address: &str,
+        content_id: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM shared_grants WHERE grantee_address = ?1 AND content_id = ?2",
+                params![
+                    grantee_address.trim().to_ascii_lowercase(),
+                    content_id.trim().to_ascii_lowercase()
+                ],
+            )
+            .map_err(|e| format!("Failed deleting shared grant record: {e}"))?;
+        Ok(())
+    }
+
+    /// Swap the grants `owner` holds for `content_id` for `records` in one step.
+    pub fn replace_shared_grant_records_for_content(
+        &self,
+        owner_address: &str,
+        content_id: &str,
+        records: &[SharedGrantRecord],
+    ) -> Result<(), String> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting shared_grants transaction: {e}"))?;
+        tx.execute(
+            "DELETE FROM shared_grants WHERE owner_address = ?1 AND content_id = ?2",
+            params![
+                owner_address.trim().to_ascii_lowercase(),
+                content_id.trim().to_ascii_lowercase()
+            ],
+        )
+        .map_err(|e| format!("Failed clearing shared grants: {e}"))?;
+        for record in records {
+            self.upsert_shared_grant_record(record)?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed committing shared grants: {e}"))
+    }
+}
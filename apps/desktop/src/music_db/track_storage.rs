@@ -0,0 +1,161 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+impl MusicDb {
+    /// Record where `file_path` lives on the network for `owner_address`.
+    pub fn set_track_storage_status(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+        content_id: &str,
+        piece_cid: &str,
+        status: StorageStatus,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO track_storage (
+                    owner_address, file_path, content_id, piece_cid, status, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(owner_address, file_path) DO UPDATE SET
+                    content_id = excluded.content_id,
+                    piece_cid = excluded.piece_cid,
+                    status = excluded.status,
+                    updated_at = excluded.updated_at",
+                params![
+                    owner_address.trim().to_ascii_lowercase(),
+                    file_path,
+                    content_id,
+                    piece_cid,
+                    status.as_str(),
+                    now,
+                ],
+            )
+            .map_err(|e| format!("Failed updating track_storage: {e}"))?;
+        Ok(())
+    }
+
+    /// Remember an Arweave upload before it is confirmed, so a later attempt re-checks it
+    /// instead of paying for a second copy.
+    pub fn record_track_archive(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+        archive: &TrackArchiveRow,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO track_storage (
+                    owner_address, file_path, content_id, piece_cid, status, arweave_id, sha256,
+                    bytes, estimated_credit, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, 'uploaded', ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(owner_address, file_path) DO UPDATE SET
+                    content_id = excluded.content_id,
+                    piece_cid = excluded.piece_cid,
+                    arweave_id = excluded.arweave_id,
+                    sha256 = excluded.sha256,
+                    bytes = excluded.bytes,
+                    estimated_credit = excluded.estimated_credit,
+                    updated_at = excluded.updated_at",
+                params![
+                    owner_address.trim().to_ascii_lowercase(),
+                    file_path,
+                    archive.content_id,
+                    archive.piece_cid,
+                    archive.arweave_id,
+                    archive.sha256,
+                    archive.bytes as i64,
+                    archive.estimated_credit,
+                    now,
+                ],
+            )
+            .map_err(|e| format!("Failed recording track archive: {e}"))?;
+        Ok(())
+    }
+
+    /// Unconfirmed Arweave upload of `piece_cid` for `file_path`, if one was made.
+    pub fn pending_track_archive(
+        &self,
+        owner_address: &str,
+        file_path: &str,
+        piece_cid: &str,
+    ) -> Result<Option<TrackArchiveRow>, String> {
+        self.conn
+            .query_row(
+                "SELECT content_id, piece_cid, arweave_id, sha256, bytes, estimated_credit
+                 FROM track_storage
+                 WHERE owner_address = ?1 AND file_path = ?2 AND piece_cid = ?3
+                   AND status != 'permanent' AND arweave_id IS NOT NULL",
+                params![
+                    owner_address.trim().to_ascii_lowercase(),
+                    file_path,
+                    piece_cid
+                ],
+                |row| {
+                    Ok(TrackArchiveRow {
+                        content_id: row.get(0)?,
+                        piece_cid: row.get(1)?,
+                        arweave_id: row.get(2)?,
+                        sha256: row.get(3)?,
+                        bytes: row.get::<_, i64>(4)?.max(0) as u64,
+                        estimated_credit: row.get(5)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| format!("Failed reading track_storage: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrobble::now_epoch_sec;
+
+    #[test]
+    fn pending_archive_is_reused_until_confirmed_permanent() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-track-archive-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).unwrap();
+        let archive = TrackArchiveRow {
+            content_id: "0xcontent".to_string(),
+            piece_cid: "bafkpiece".to_string(),
+            arweave_id: "ar-tx".to_string(),
+            sha256: "ab".repeat(32),
+            bytes: 1024,
+            estimated_credit: 0.25,
+        };
+        db.record_track_archive("0xABC", "/music/a.flac", &archive, 10)
+            .unwrap();
+
+        let pending = db
+            .pending_track_archive("0xabc", "/music/a.flac", "bafkpiece")
+            .unwrap()
+            .expect("pending archive");
+        assert_eq!(pending.arweave_id, "ar-tx");
+        assert_eq!(pending.bytes, 1024);
+        assert!(db
+            .pending_track_archive("0xabc", "/music/a.flac", "bafkother")
+            .unwrap()
+            .is_none());
+
+        db.set_track_storage_status(
+            "0xabc",
+            "/music/a.flac",
+            "0xcontent",
+            "bafkpiece",
+            StorageStatus::Permanent,
+            11,
+        )
+        .unwrap();
+        assert!(db
+            .pending_track_archive("0xabc", "/music/a.flac", "bafkpiece")
+            .unwrap()
+            .is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
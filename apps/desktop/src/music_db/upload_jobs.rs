@@ -0,0 +1,259 @@
+use super::*;
+use rusqlite::OptionalExtension;
+
+const UPLOAD_JOB_COLUMNS: &str = "id, owner_address, file_path, title, artist, album, mbid, ip_id,
+     status, attempts, next_attempt_at, last_error, estimated_credit, response, created_at,
+     updated_at";
+
+fn upload_job_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<UploadJobRow> {
+    Ok(UploadJobRow {
+        id: row.get(0)?,
+        owner_address: row.get(1)?,
+        track: UploadJobInput {
+            file_path: row.get(2)?,
+            title: row.get(3)?,
+            artist: row.get(4)?,
+            album: row.get(5)?,
+            mbid: row.get(6)?,
+            ip_id: row.get(7)?,
+        },
+        status: UploadJobStatus::parse(&row.get::<_, String>(8)?),
+        attempts: row.get(9)?,
+        next_attempt_at: row.get(10)?,
+        last_error: row.get(11)?,
+        estimated_credit: row.get(12)?,
+        response: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+impl MusicDb {
+    /// Queue `track` for upload. Returns `None` when the file already has an active job.
+    pub fn enqueue_upload_job(
+        &self,
+        owner_address: &str,
+        track: &UploadJobInput,
+        now: i64,
+    ) -> Result<Option<i64>, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let active: i64 = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM upload_jobs
+                 WHERE owner_address = ?1 AND file_path = ?2
+                   AND status IN ('queued', 'running', 'paused')",
+                params![owner_address, track.file_path],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed checking upload_jobs: {e}"))?;
+        if active > 0 {
+            return Ok(None);
+        }
+        self.conn
+            .execute(
+                "INSERT INTO upload_jobs (
+                    owner_address, file_path, title, artist, album, mbid, ip_id, status,
+                    attempts, next_attempt_at, created_at, updated_at
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'queued', 0, ?8, ?8, ?8)",
+                params![
+                    owner_address,
+                    track.file_path,
+                    track.title,
+                    track.artist,
+                    track.album,
+                    track.mbid,
+                    track.ip_id,
+                    now,
+                ],
+            )
+            .map_err(|e| format!("Failed inserting upload_jobs row: {e}"))?;
+        Ok(Some(self.conn.last_insert_rowid()))
+    }
+
+    /// Every job of `owner_address`, oldest first.
+    pub fn upload_jobs(&self, owner_address: &str) -> Result<Vec<UploadJobRow>, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {UPLOAD_JOB_COLUMNS} FROM upload_jobs
+                 WHERE owner_address = ?1
+                 ORDER BY id ASC"
+            ))
+            .map_err(|e| format!("Failed preparing upload_jobs query: {e}"))?;
+        let rows = stmt
+            .query_map(params![owner_address], upload_job_from_row)
+            .map_err(|e| format!("Failed querying upload_jobs: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed reading upload_jobs row: {e}"))
+    }
+
+    /// Take the oldest due job and mark it running.
+    pub fn claim_due_upload_job(
+        &self,
+        owner_address: &str,
+        now: i64,
+    ) -> Result<Option<UploadJobRow>, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed starting upload_jobs transaction: {e}"))?;
+        let job = tx
+            .query_row(
+                &format!(
+                    "SELECT {UPLOAD_JOB_COLUMNS} FROM upload_jobs
+                     WHERE owner_address = ?1 AND status = 'queued' AND next_attempt_at <= ?2
+                     ORDER BY next_attempt_at ASC, id ASC
+                     LIMIT 1"
+                ),
+                params![owner_address, now],
+                upload_job_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("Failed querying due upload job: {e}"))?;
+        let Some(mut job) = job else {
+            return Ok(None);
+        };
+        tx.execute(
+            "UPDATE upload_jobs SET status = 'running', updated_at = ?2 WHERE id = ?1",
+            params![job.id, now],
+        )
+        .map_err(|e| format!("Failed claiming upload job: {e}"))?;
+        tx.commit()
+            .map_err(|e| format!("Failed committing upload job claim: {e}"))?;
+        job.status = UploadJobStatus::Running;
+        job.updated_at = now;
+        Ok(Some(job))
+    }
+
+    pub fn set_upload_job_estimate(
+        &self,
+        id: i64,
+        estimated_credit: Option<f64>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE upload_jobs SET estimated_credit = ?2 WHERE id = ?1",
+                params![id, estimated_credit],
+            )
+            .map_err(|e| format!("Failed updating upload job estimate: {e}"))?;
+        Ok(())
+    }
+
+    pub fn finish_upload_job(&self, id: i64, response: &str, now: i64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE upload_jobs
+                 SET status = 'done', response = ?2, last_error = NULL, updated_at = ?3
+                 WHERE id = ?1",
+                params![id, response, now],
+            )
+            .map_err(|e| format!("Failed finishing upload job: {e}"))?;
+        Ok(())
+    }
+
+    /// Count a failed attempt. `next_attempt_at: None` gives up and marks the job failed.
+    pub fn record_upload_job_failure(
+        &self,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        let status = if next_attempt_at.is_some() {
+            UploadJobStatus::Queued
+        } else {
+            UploadJobStatus::Failed
+        };
+        self.conn
+            .execute(
+                "UPDATE upload_jobs
+                 SET status = ?2, attempts = attempts + 1, next_attempt_at = ?3,
+                     last_error = ?4, updated_at = ?5
+                 WHERE id = ?1",
+                params![
+                    id,
+                    status.as_str(),
+                    next_attempt_at.unwrap_or(now),
+                    error,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed updating upload job failure: {e}"))?;
+        Ok(())
+    }
+
+    /// Park a job as paused or cancelled; only jobs that have not finished are touched.
+    pub fn set_upload_job_status(
+        &self,
+        id: i64,
+        status: UploadJobStatus,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE upload_jobs SET status = ?2, updated_at = ?3
+                 WHERE id = ?1 AND status IN ('queued', 'running', 'paused')",
+                params![id, status.as_str(), now],
+            )
+            .map_err(|e| format!("Failed updating upload job status: {e}"))?;
+        Ok(())
+    }
+
+    /// Queue a paused, failed or cancelled job again; `reset_attempts` restores the retry budget.
+    pub fn requeue_upload_job(
+        &self,
+        id: i64,
+        reset_attempts: bool,
+        now: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE upload_jobs
+                 SET status = 'queued', next_attempt_at = ?2, updated_at = ?2,
+                     attempts = CASE WHEN ?3 THEN 0 ELSE attempts END
+                 WHERE id = ?1 AND status IN ('paused', 'failed', 'cancelled')",
+                params![id, now, reset_attempts],
+            )
+            .map_err(|e| format!("Failed requeueing upload job: {e}"))?;
+        Ok(())
+    }
+
+    /// Jobs left `running` by a previous session go back to the queue; their upload journal
+    /// lets them resume where they stopped.
+    pub fn requeue_interrupted_upload_jobs(&self, now: i64) -> Result<usize, String> {
+        self.conn
+            .execute(
+                "UPDATE upload_jobs SET status = 'queued', next_attempt_at = ?1, updated_at = ?1
+                 WHERE status = 'running'",
+                params![now],
+            )
+            .map_err(|e| format!("Failed requeueing interrupted upload jobs: {e}"))
+    }
+
+    pub fn clear_finished_upload_jobs(&self, owner_address: &str) -> Result<usize, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        self.conn
+            .execute(
+                "DELETE FROM upload_jobs
+                 WHERE owner_address = ?1 AND status IN ('done', 'cancelled')",
+                params![owner_address],
+            )
+            .map_err(|e| format!("Failed clearing finished upload jobs: {e}"))
+    }
+
+    /// Earliest retry time among queued jobs of `owner_address`.
+    pub fn next_upload_job_attempt_at(&self, owner_address: &str) -> Result<Option<i64>, String> {
+        let owner_address = owner_address.trim().to_ascii_lowercase();
+        self.conn
+            .query_row(
+                "SELECT MIN(next_attempt_at) FROM upload_jobs
+                 WHERE owner_address = ?1 AND status = 'queued'",
+                params![owner_address],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed querying next upload job attempt: {e}"))
+    }
+}
@@ -0,0 +1,26 @@
+//! Local "now playing" state, published by the library player for rooms and profile presence.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub playing: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct NowPlayingState {
+    current: Option<NowPlaying>,
+}
+
+impl gpui::Global for NowPlayingState {}
+
+impl NowPlayingState {
+    pub fn current(&self) -> Option<&NowPlaying> {
+        self.current.as_ref()
+    }
+
+    pub fn set(&mut self, now_playing: Option<NowPlaying>) {
+        self.current = now_playing;
+    }
+}
@@ -8,7 +8,7 @@ use crate::{auth, chat, library};
 
 mod model;
 mod render;
-mod scrobbles_feed;
+pub(crate) mod scrobbles_feed;
 
 use model::{ProfileScrobbleRow, ProfileTab};
 
@@ -139,6 +139,11 @@ impl ProfileView {
         })
         .detach();
 
+        cx.observe_global::<crate::now_playing::NowPlayingState>(|_this, cx| {
+            cx.notify();
+        })
+        .detach();
+
         // Keep the Playlists tab reactive to LibraryView sidebar playlist refreshes.
         let lib = this.library_view.clone();
         cx.observe(&lib, |this, _lib, cx| {
@@ -276,9 +281,27 @@ impl ProfileView {
         }
 
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            // Stored history renders immediately; the sync below only adds what is new.
+            let cached_user = user.clone();
+            let cached =
+                smol::unblock(move || scrobbles_feed::cached_scrobbles_for_user(&cached_user, 100))
+                    .await;
+            match cached {
+                Ok(rows) if !rows.is_empty() => {
+                    let _ = this.update(cx, |this, cx| {
+                        if this.scrobbles_fetch_seq == fetch_seq {
+                            this.scrobbles = rows;
+                            cx.notify();
+                        }
+                    });
+                }
+                Ok(_) => {}
+                Err(err) => log::warn!("[Profile] scrobble store read failed: {}", err),
+            }
+
             let query_user = user.clone();
             let result =
-                smol::unblock(move || scrobbles_feed::fetch_scrobbles_for_user(&query_user, 100))
+                smol::unblock(move || scrobbles_feed::sync_scrobbles_for_user(&query_user, 100))
                     .await;
 
             let _ = this.update(cx, |this, cx| {
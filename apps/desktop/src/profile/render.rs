@@ -1,4 +1,5 @@
 use super::*;
+use gpui::prelude::FluentBuilder;
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::StyledExt;
 
@@ -123,6 +124,22 @@ fn render_banner() -> impl IntoElement {
 }
 
 fn render_identity_row(view: &ProfileView, cx: &mut Context<ProfileView>) -> impl IntoElement {
+    let now_playing = cx
+        .global::<crate::now_playing::NowPlayingState>()
+        .current()
+        .map(|now| {
+            let verb = if now.playing {
+                "Listening to"
+            } else {
+                "Paused on"
+            };
+            if now.album.trim().is_empty() {
+                format!("{verb} {} by {}", now.title, now.artist)
+            } else {
+                format!("{verb} {} by {} ({})", now.title, now.artist, now.album)
+            }
+        });
+
     div()
         .h_flex()
         .w_full()
@@ -145,7 +162,10 @@ fn render_identity_row(view: &ProfileView, cx: &mut Context<ProfileView>) -> imp
                         .text_sm()
                         .text_color(TEXT_MUTED())
                         .child(PROFILE_HANDLE),
-                ),
+                )
+                .when_some(now_playing, |el, line| {
+                    el.child(div().text_sm().text_color(TEXT_SECONDARY()).child(line))
+                }),
         )
         .child(
             div()
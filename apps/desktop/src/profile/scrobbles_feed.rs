@@ -1,26 +1,33 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::env;
 use std::str::FromStr;
+use std::sync::Mutex;
 
 #[path = "scrobbles_feed/chain.rs"]
 mod chain;
 mod format;
+mod history;
 
 use alloy_primitives::{keccak256, B256};
 use alloy_sol_types::{sol, SolCall};
 use serde_json::{json, Value};
 
+use crate::auth::accounts::AccountRegistry;
+use crate::music_db::{MusicDb, ScrobbleEvent, ScrobbleScope, ScrobbleTrackMeta, StoredScrobble};
 use crate::shared::rpc::rpc_json;
 use format::{format_time_ago, short_track_label};
 
+pub(crate) use history::sync_scrobble_history;
+
 use super::model::ProfileScrobbleRow;
 
 const DEFAULT_TEMPO_RPC_URL: &str = "https://rpc.moderato.tempo.xyz";
 const DEFAULT_TEMPO_SCROBBLE_V4: &str = "0xe00e82086480E61AaC8d5ad8B05B56A582dD0000";
+const DEFAULT_SUBGRAPH_MUSIC_SOCIAL_URL: &str =
+    "https://graph.dotheaven.org/subgraphs/name/dotheaven/music-social-tempo";
 const SCROBBLED_EVENT_SIGNATURE: &str = "Scrobbled(address,bytes32,uint64)";
 const DEFAULT_LOG_CHUNK_BLOCKS: u64 = 50_000;
 const DEFAULT_MAX_LOG_SCAN_BLOCKS: u64 = 1_000_000;
-const DEFAULT_EMPTY_CHUNKS_AFTER_HIT: u32 = 3;
 
 sol! {
     function getTrack(bytes32 trackId) view returns (
@@ -35,23 +42,22 @@ sol! {
     );
 }
 
-#[derive(Debug, Clone)]
-struct ScrobbleMeta {
-    title: String,
-    artist: String,
-    album: String,
-    cover_cid: Option<String>,
+/// The scrobble store lives in the shared `music.db`, next to the library's tables.
+pub(crate) fn open_scrobble_store() -> Result<Mutex<MusicDb>, String> {
+    MusicDb::open(AccountRegistry::shared().base_dir()).map(Mutex::new)
 }
 
-#[derive(Debug, Clone)]
-struct OnchainScrobbleEvent {
-    track_id: String,
-    timestamp: u64,
-    block_number: u64,
-    log_index: u64,
+/// The user's stored scrobbles, without touching the network.
+pub(super) fn cached_scrobbles_for_user(
+    user_address: &str,
+    max_entries: usize,
+) -> Result<Vec<ProfileScrobbleRow>, String> {
+    let db = open_scrobble_store()?;
+    load_profile_rows(&db, user_address, max_entries)
 }
 
-pub(super) fn fetch_scrobbles_for_user(
+/// Sync the user's scrobble history, then read it back from the store.
+pub(super) fn sync_scrobbles_for_user(
     user_address: &str,
     max_entries: usize,
 ) -> Result<Vec<ProfileScrobbleRow>, String> {
@@ -59,58 +65,72 @@ pub(super) fn fetch_scrobbles_for_user(
     if user_address.is_empty() {
         return Ok(Vec::new());
     }
+    let db = open_scrobble_store()?;
+    sync_scrobble_history(&db, &ScrobbleScope::user(&user_address), 0)?;
+    load_profile_rows(&db, &user_address, max_entries)
+}
 
-    let rpc_url = tempo_rpc_url();
-    let scrobble_contract = tempo_scrobble_contract();
-    let events =
-        chain::fetch_scrobbled_events(&rpc_url, &scrobble_contract, &user_address, max_entries)?;
-    if events.is_empty() {
-        log::info!(
-            "[ProfileFeed] scrobbles source=onchain rpc={} contract={} user={} rows=0",
-            rpc_url,
-            scrobble_contract,
-            user_address
-        );
-        return Ok(Vec::new());
+fn load_profile_rows(
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+    max_entries: usize,
+) -> Result<Vec<ProfileScrobbleRow>, String> {
+    let stored = db
+        .lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?
+        .recent_scrobbles(user_address, max_entries)?;
+    Ok(stored.into_iter().map(profile_row).collect())
+}
+
+fn profile_row(stored: StoredScrobble) -> ProfileScrobbleRow {
+    let (title, artist, album, cover_cid) = match stored.meta {
+        Some(meta) => (meta.title, meta.artist, meta.album, meta.cover_cid),
+        None => (
+            short_track_label(&stored.track_id),
+            "Unknown Artist".to_string(),
+            String::new(),
+            None,
+        ),
+    };
+    ProfileScrobbleRow {
+        track_id: Some(stored.track_id),
+        played_at_sec: stored.played_at_sec,
+        title,
+        artist,
+        album,
+        cover_cid,
+        played_ago: format_time_ago(stored.played_at_sec),
     }
+}
 
-    let mut track_ids: Vec<String> = events.iter().map(|event| event.track_id.clone()).collect();
-    track_ids.sort();
-    track_ids.dedup();
-
-    let track_map =
-        chain::fetch_track_metadata_map_onchain(&rpc_url, &scrobble_contract, &track_ids);
-    let mut rows = Vec::with_capacity(events.len());
-    for event in events {
-        let meta = track_map
-            .get(&event.track_id)
-            .cloned()
-            .unwrap_or_else(|| ScrobbleMeta {
-                title: short_track_label(&event.track_id),
-                artist: "Unknown Artist".to_string(),
-                album: String::new(),
-                cover_cid: None,
-            });
-
-        rows.push(ProfileScrobbleRow {
-            track_id: Some(event.track_id),
-            played_at_sec: event.timestamp,
-            title: meta.title,
-            artist: meta.artist,
-            album: meta.album,
-            cover_cid: meta.cover_cid,
-            played_ago: format_time_ago(event.timestamp),
-        });
+/// `(track_id, timestamp)` of every on-chain scrobble by the user, after syncing the store.
+pub(crate) fn fetch_scrobble_keys_for_user(
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+) -> Result<HashSet<(String, u64)>, String> {
+    let user_address = user_address.trim().to_ascii_lowercase();
+    if user_address.is_empty() {
+        return Ok(HashSet::new());
     }
+    sync_scrobble_history(db, &ScrobbleScope::user(&user_address), 0)?;
+    db.lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?
+        .stored_scrobble_keys(&user_address)
+}
 
-    log::info!(
-        "[ProfileFeed] scrobbles source=onchain rpc={} contract={} user={} rows={}",
-        rpc_url,
-        scrobble_contract,
-        user_address,
-        rows.len()
-    );
-    Ok(rows)
+fn subgraph_music_social_url() -> String {
+    env::var("SUBGRAPH_MUSIC_SOCIAL_URL")
+        .ok()
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .or_else(|| {
+            env::var("SUBGRAPH_BASE_URL")
+                .ok()
+                .map(|value| value.trim().trim_end_matches('/').to_string())
+                .filter(|value| !value.is_empty())
+                .map(|base| format!("{base}/subgraphs/name/dotheaven/music-social-tempo"))
+        })
+        .unwrap_or_else(|| DEFAULT_SUBGRAPH_MUSIC_SOCIAL_URL.to_string())
 }
 
 fn tempo_rpc_url() -> String {
@@ -187,14 +207,6 @@ fn max_log_scan_blocks() -> u64 {
         .unwrap_or(DEFAULT_MAX_LOG_SCAN_BLOCKS)
 }
 
-fn max_empty_chunks_after_hit() -> u32 {
-    env::var("HEAVEN_SCROBBLE_LOG_EMPTY_CHUNKS_AFTER_HIT")
-        .ok()
-        .and_then(|v| v.trim().parse::<u32>().ok())
-        .filter(|v| *v > 0)
-        .unwrap_or(DEFAULT_EMPTY_CHUNKS_AFTER_HIT)
-}
-
 fn strip_0x(value: &str) -> &str {
     value
         .strip_prefix("0x")
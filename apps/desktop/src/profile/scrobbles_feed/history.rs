@@ -0,0 +1,617 @@
+//! Incremental sync of `Scrobbled` events into the local scrobble store.
+//!
+//! Each sync reads events after the scope's cursor from the music-social subgraph and falls
+//! back to `eth_getLogs` when the subgraph is unreachable or behind. The last
+//! `REORG_WINDOW_BLOCKS` before the cursor are always fetched again, and a cursor whose block
+//! hash no longer matches the chain rewinds `DEEP_REORG_BLOCKS`, so reorged-out events leave
+//! the store when their range is replaced.
+
+use super::*;
+use crate::music_db::{ScrobbleSyncBatch, ScrobbleSyncCursor};
+use crate::scrobble::now_epoch_sec;
+use crate::shared::rpc::http_post_json;
+
+const REORG_WINDOW_BLOCKS: u64 = 64;
+const DEEP_REORG_BLOCKS: u64 = 1_024;
+const SUBGRAPH_PAGE_SIZE: usize = 1_000;
+const SUBGRAPH_MAX_PAGES: usize = 20;
+const METADATA_BACKFILL_LIMIT: usize = 100;
+
+const SOURCE_SUBGRAPH: &str = "subgraph";
+const SOURCE_RPC: &str = "rpc";
+
+/// Bring `scope` up to the chain head. Skipped when the last sync is newer than
+/// `max_age_secs`; pass 0 to always sync.
+pub(crate) fn sync_scrobble_history(
+    db: &Mutex<MusicDb>,
+    scope: &ScrobbleScope,
+    max_age_secs: u64,
+) -> Result<(), String> {
+    let now = now_epoch_sec() as i64;
+    let cursor = lock(db)?.scrobble_sync_cursor(scope)?;
+    if let Some(cursor) = &cursor {
+        if now.saturating_sub(cursor.synced_at) < max_age_secs as i64 {
+            return Ok(());
+        }
+    }
+
+    let rpc_url = tempo_rpc_url();
+    let scrobble_contract = tempo_scrobble_contract();
+    let canonical_hash = cursor
+        .as_ref()
+        .filter(|cursor| cursor.block_hash.is_some())
+        .and_then(
+            |cursor| match chain::rpc_block_hash(&rpc_url, cursor.block_number) {
+                Ok(hash) => hash,
+                Err(err) => {
+                    log::debug!("[ScrobbleHistory] cursor hash check skipped: {err}");
+                    None
+                }
+            },
+        );
+    let resume_from = rescan_from(cursor.as_ref(), canonical_hash.as_deref());
+
+    let subgraph_url = subgraph_music_social_url();
+    let fetched = fetch_subgraph_batch(resume_from, |lower, upper| {
+        query_scrobbles_page(&subgraph_url, scope, lower, upper)
+    });
+    let batch = match fetched {
+        Ok(batch) => batch,
+        Err(err) => {
+            log::warn!(
+                "[ScrobbleHistory] subgraph sync failed for {}: {err}; falling back to RPC",
+                scope.key()
+            );
+            fetch_rpc_batch(&rpc_url, &scrobble_contract, scope, resume_from)?
+        }
+    };
+    let stored = lock(db)?.apply_scrobble_sync(scope, &batch, now)?;
+    log::info!(
+        "[ScrobbleHistory] synced scope={} source={} blocks={}..{} events={}",
+        scope.key(),
+        batch.source,
+        batch.from_block,
+        batch.to_block,
+        stored
+    );
+
+    backfill_track_metadata(db, &rpc_url, &scrobble_contract, now);
+    Ok(())
+}
+
+/// First block to fetch again, or `None` when the scope has never synced.
+fn rescan_from(cursor: Option<&ScrobbleSyncCursor>, canonical_hash: Option<&str>) -> Option<u64> {
+    let cursor = cursor?;
+    let reorged = match (cursor.block_hash.as_deref(), canonical_hash) {
+        (Some(stored), Some(current)) => !stored.eq_ignore_ascii_case(current),
+        _ => false,
+    };
+    if reorged {
+        log::warn!(
+            "[ScrobbleHistory] block {} changed since last sync; rewinding {} blocks",
+            cursor.block_number,
+            DEEP_REORG_BLOCKS
+        );
+    }
+    let depth = if reorged {
+        DEEP_REORG_BLOCKS
+    } else {
+        REORG_WINDOW_BLOCKS
+    };
+    Some((cursor.block_number + 1).saturating_sub(depth))
+}
+
+/// Page through the subgraph from `resume_from` up to the head it reports on the first page.
+/// `fetch_page(lower, upper)` returns the scrobbles in `lower..=upper`, oldest first.
+fn fetch_subgraph_batch(
+    resume_from: Option<u64>,
+    mut fetch_page: impl FnMut(u64, Option<u64>) -> Result<SubgraphPage, String>,
+) -> Result<ScrobbleSyncBatch, String> {
+    let from_block = resume_from.unwrap_or(0);
+    let mut lower = from_block;
+    let mut head: Option<(u64, Option<String>)> = None;
+    let mut events = Vec::new();
+    let mut tracks = Vec::new();
+
+    for _ in 0..SUBGRAPH_MAX_PAGES {
+        let page = fetch_page(lower, head.as_ref().map(|h| h.0))?;
+        let (head_block, head_hash) = match &head {
+            Some(head) => head.clone(),
+            None => {
+                if page.head_block < from_block {
+                    return Err(format!(
+                        "subgraph head {} is behind local cursor {}",
+                        page.head_block, from_block
+                    ));
+                }
+                head = Some((page.head_block, page.head_hash.clone()));
+                (page.head_block, page.head_hash.clone())
+            }
+        };
+        let full = page.events.len() >= SUBGRAPH_PAGE_SIZE;
+        let last_block = page.events.iter().map(|e| e.block_number).max();
+        events.extend(page.events);
+        tracks.extend(page.tracks);
+
+        match last_block {
+            Some(last_block) if full && last_block > lower => {
+                // The last block may continue on the next page: drop it and ask again.
+                events.retain(|e| e.block_number < last_block);
+                lower = last_block;
+            }
+            Some(last_block) if full => {
+                log::warn!(
+                    "[ScrobbleHistory] block {last_block} has more than {SUBGRAPH_PAGE_SIZE} scrobbles; keeping the first page"
+                );
+                lower = last_block + 1;
+            }
+            _ => {
+                return Ok(ScrobbleSyncBatch {
+                    from_block,
+                    to_block: head_block,
+                    to_block_hash: head_hash,
+                    source: SOURCE_SUBGRAPH.to_string(),
+                    events,
+                    tracks,
+                });
+            }
+        }
+    }
+
+    // Page budget spent: commit what is complete and continue on the next sync.
+    Ok(ScrobbleSyncBatch {
+        from_block,
+        to_block: lower.saturating_sub(1),
+        to_block_hash: None,
+        source: SOURCE_SUBGRAPH.to_string(),
+        events,
+        tracks,
+    })
+}
+
+struct SubgraphPage {
+    head_block: u64,
+    head_hash: Option<String>,
+    events: Vec<ScrobbleEvent>,
+    tracks: Vec<ScrobbleTrackMeta>,
+}
+
+fn query_scrobbles_page(
+    subgraph_url: &str,
+    scope: &ScrobbleScope,
+    lower: u64,
+    upper: Option<u64>,
+) -> Result<SubgraphPage, String> {
+    let mut filters = vec![format!("blockNumber_gte: \"{lower}\"")];
+    if let Some(upper) = upper {
+        filters.push(format!("blockNumber_lte: \"{upper}\""));
+    }
+    if let ScrobbleScope::User(address) = scope {
+        filters.push(format!("user: \"{address}\""));
+    }
+    let query = format!(
+        "{{ _meta {{ block {{ number hash }} }} scrobbles(first: {SUBGRAPH_PAGE_SIZE}, orderBy: blockNumber, orderDirection: asc, where: {{ {} }}) {{ id user timestamp blockNumber track {{ id title artist album coverCid kind scrobbleCountVerified }} }} }}",
+        filters.join(", ")
+    );
+    let response = http_post_json(subgraph_url, json!({ "query": query }))?;
+    if let Some(errors) = response.get("errors") {
+        return Err(format!("Music-social subgraph error: {errors}"));
+    }
+    let data = response.get("data").ok_or("subgraph returned no data")?;
+    let head = data
+        .get("_meta")
+        .and_then(|meta| meta.get("block"))
+        .ok_or("subgraph returned no _meta block")?;
+    let head_block = head
+        .get("number")
+        .and_then(Value::as_u64)
+        .ok_or("subgraph _meta block has no number")?;
+    let head_hash = head
+        .get("hash")
+        .and_then(Value::as_str)
+        .map(str::to_ascii_lowercase);
+
+    let mut events = Vec::new();
+    let mut tracks = Vec::new();
+    for row in data
+        .get("scrobbles")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some((event, track)) = parse_subgraph_scrobble(row) else {
+            log::debug!("[ScrobbleHistory] skip malformed subgraph scrobble: {row}");
+            continue;
+        };
+        events.push(event);
+        tracks.extend(track);
+    }
+    Ok(SubgraphPage {
+        head_block,
+        head_hash,
+        events,
+        tracks,
+    })
+}
+
+fn parse_subgraph_scrobble(row: &Value) -> Option<(ScrobbleEvent, Option<ScrobbleTrackMeta>)> {
+    let track = row.get("track")?;
+    let track_id = track.get("id")?.as_str()?.trim().to_ascii_lowercase();
+    let event = ScrobbleEvent {
+        user_address: row.get("user")?.as_str()?.trim().to_ascii_lowercase(),
+        track_id: track_id.clone(),
+        played_at_sec: subgraph_u64(row.get("timestamp"))?,
+        block_number: subgraph_u64(row.get("blockNumber"))?,
+        // Entity ids are `<txHash>-<logIndex>`; only the order within a block matters here.
+        log_index: row
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.rsplit_once('-'))
+            .and_then(|(_, index)| index.parse().ok())
+            .unwrap_or(0),
+    };
+    let meta = track.get("title").and_then(Value::as_str).map(|title| {
+        let field = |name: &str| track.get(name).and_then(Value::as_str).unwrap_or_default();
+        ScrobbleTrackMeta {
+            track_id,
+            title: format::sanitize_string_field(title, "Unknown Track"),
+            artist: format::sanitize_string_field(field("artist"), "Unknown Artist"),
+            album: format::sanitize_string_field(field("album"), ""),
+            cover_cid: format::sanitize_cover_ref(field("coverCid")),
+            kind: subgraph_u64(track.get("kind")).and_then(|kind| u8::try_from(kind).ok()),
+            verified_plays: subgraph_u64(track.get("scrobbleCountVerified")),
+        }
+    });
+    Some((event, meta))
+}
+
+/// Subgraph BigInts arrive as strings, Ints as numbers.
+fn subgraph_u64(value: Option<&Value>) -> Option<u64> {
+    match value? {
+        Value::String(raw) => raw.trim().parse().ok(),
+        Value::Number(number) => number.as_u64(),
+        _ => None,
+    }
+}
+
+/// A first RPC sync only reaches back `max_log_scan_blocks`; the subgraph has full history.
+fn fetch_rpc_batch(
+    rpc_url: &str,
+    scrobble_contract: &str,
+    scope: &ScrobbleScope,
+    resume_from: Option<u64>,
+) -> Result<ScrobbleSyncBatch, String> {
+    let head = chain::rpc_block_number(rpc_url)?;
+    let from_block = resume_from
+        .unwrap_or_else(|| (head + 1).saturating_sub(max_log_scan_blocks()))
+        .min(head);
+    let user = match scope {
+        ScrobbleScope::User(address) => Some(address.as_str()),
+        ScrobbleScope::All => None,
+    };
+    let events =
+        chain::fetch_scrobbled_events_range(rpc_url, scrobble_contract, user, from_block, head)?;
+    let to_block_hash = chain::rpc_block_hash(rpc_url, head).unwrap_or_else(|err| {
+        log::debug!("[ScrobbleHistory] head hash unavailable: {err}");
+        None
+    });
+    Ok(ScrobbleSyncBatch {
+        from_block,
+        to_block: head,
+        to_block_hash,
+        source: SOURCE_RPC.to_string(),
+        events,
+        tracks: Vec::new(),
+    })
+}
+
+/// Resolve metadata for stored track ids the sync source did not describe.
+fn backfill_track_metadata(db: &Mutex<MusicDb>, rpc_url: &str, scrobble_contract: &str, now: i64) {
+    let missing =
+        match lock(db).and_then(|db| db.scrobble_tracks_missing_meta(METADATA_BACKFILL_LIMIT)) {
+            Ok(missing) if !missing.is_empty() => missing,
+            Ok(_) => return,
+            Err(err) => {
+                log::warn!("[ScrobbleHistory] metadata backfill query failed: {err}");
+                return;
+            }
+        };
+    let tracks = chain::fetch_track_metadata_onchain(rpc_url, scrobble_contract, &missing);
+    if let Err(err) = lock(db).and_then(|db| db.upsert_scrobble_tracks(&tracks, now)) {
+        log::warn!("[ScrobbleHistory] metadata backfill failed: {err}");
+    }
+}
+
+fn lock(db: &Mutex<MusicDb>) -> Result<std::sync::MutexGuard<'_, MusicDb>, String> {
+    db.lock().map_err(|e| format!("music db lock failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(user: &str, track: &str, played_at_sec: u64, block_number: u64) -> ScrobbleEvent {
+        ScrobbleEvent {
+            user_address: user.to_string(),
+            track_id: track.to_string(),
+            played_at_sec,
+            block_number,
+            log_index: 0,
+        }
+    }
+
+    fn batch(
+        from_block: u64,
+        to_block: u64,
+        hash: &str,
+        events: Vec<ScrobbleEvent>,
+    ) -> ScrobbleSyncBatch {
+        ScrobbleSyncBatch {
+            from_block,
+            to_block,
+            to_block_hash: Some(hash.to_string()),
+            source: SOURCE_RPC.to_string(),
+            events,
+            tracks: Vec::new(),
+        }
+    }
+
+    fn temp_db() -> (MusicDb, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-history-{}-{}",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        (MusicDb::open(&dir).expect("open music db"), dir)
+    }
+
+    fn page(head_block: u64, blocks: impl IntoIterator<Item = u64>) -> SubgraphPage {
+        SubgraphPage {
+            head_block,
+            head_hash: Some("0xhead".to_string()),
+            events: blocks
+                .into_iter()
+                .enumerate()
+                .map(|(index, block)| event("0xaaa", "0x01", index as u64, block))
+                .collect(),
+            tracks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn resync_window_drops_reorged_events() {
+        let (db, dir) = temp_db();
+        let user = ScrobbleScope::user("0xAAA");
+
+        db.apply_scrobble_sync(
+            &user,
+            &batch(
+                0,
+                200,
+                "0xhead",
+                vec![
+                    event("0xaaa", "0x01", 10, 50),
+                    event("0xaaa", "0x02", 20, 190),
+                    event("0xbbb", "0x03", 30, 195),
+                ],
+            ),
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(db.recent_scrobbles("0xaaa", 10).unwrap().len(), 2);
+
+        let cursor = db.scrobble_sync_cursor(&user).unwrap().unwrap();
+        assert_eq!(rescan_from(Some(&cursor), Some("0xHEAD")), Some(137));
+        assert_eq!(rescan_from(Some(&cursor), Some("0xother")), Some(0));
+        assert_eq!(rescan_from(None, None), None);
+
+        // Block 190 was reorged out; its play landed again in block 205.
+        let from = rescan_from(Some(&cursor), Some("0xhead")).unwrap();
+        db.apply_scrobble_sync(
+            &user,
+            &batch(from, 210, "0xnext", vec![event("0xaaa", "0x04", 40, 205)]),
+            1_100,
+        )
+        .unwrap();
+        let keys = db.stored_scrobble_keys("0xaaa").unwrap();
+        assert_eq!(
+            keys,
+            HashSet::from([("0x01".to_string(), 10), ("0x04".to_string(), 40)])
+        );
+        let cursor = db.scrobble_sync_cursor(&user).unwrap().unwrap();
+        assert_eq!(cursor.block_number, 210);
+        assert_eq!(cursor.block_hash.as_deref(), Some("0xnext"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn deep_reorg_rewinds_past_the_resync_window() {
+        let (db, dir) = temp_db();
+        let user = ScrobbleScope::user("0xaaa");
+        db.apply_scrobble_sync(
+            &user,
+            &batch(
+                0,
+                5_000,
+                "0xold",
+                vec![
+                    event("0xaaa", "0x01", 10, 3_000),
+                    event("0xaaa", "0x02", 20, 4_000),
+                    event("0xaaa", "0x03", 30, 4_990),
+                ],
+            ),
+            1_000,
+        )
+        .unwrap();
+        let cursor = db.scrobble_sync_cursor(&user).unwrap().unwrap();
+
+        // Unchanged or unknown hashes only re-read the shallow window.
+        assert_eq!(rescan_from(Some(&cursor), Some("0xOLD")), Some(4_937));
+        assert_eq!(rescan_from(Some(&cursor), None), Some(4_937));
+        let unhashed = ScrobbleSyncCursor {
+            block_hash: None,
+            ..cursor.clone()
+        };
+        assert_eq!(rescan_from(Some(&unhashed), Some("0xnew")), Some(4_937));
+
+        // The cursor block was replaced: everything from 3_977 is fetched again, so the
+        // play at 4_000 that the new chain no longer has leaves the store.
+        let from = rescan_from(Some(&cursor), Some("0xnew")).unwrap();
+        assert_eq!(from, 5_001 - DEEP_REORG_BLOCKS);
+        db.apply_scrobble_sync(
+            &user,
+            &batch(
+                from,
+                5_100,
+                "0xnew",
+                vec![event("0xaaa", "0x03", 30, 5_010)],
+            ),
+            1_100,
+        )
+        .unwrap();
+        assert_eq!(
+            db.stored_scrobble_keys("0xaaa").unwrap(),
+            HashSet::from([("0x01".to_string(), 10), ("0x03".to_string(), 30)])
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn user_sync_replaces_only_that_wallets_range() {
+        let (db, dir) = temp_db();
+        db.apply_scrobble_sync(
+            &ScrobbleScope::All,
+            &batch(
+                0,
+                400,
+                "0xall",
+                vec![
+                    event("0xaaa", "0x01", 10, 100),
+                    event("0xbbb", "0x02", 20, 110),
+                    event("0xaaa", "0x03", 30, 300),
+                ],
+            ),
+            1_000,
+        )
+        .unwrap();
+
+        let user = ScrobbleScope::user("0xaaa");
+        let stored = db
+            .apply_scrobble_sync(
+                &user,
+                &batch(
+                    0,
+                    200,
+                    "0xuser",
+                    vec![
+                        event("0xaaa", "0x04", 40, 150),
+                        event("0xbbb", "0x05", 50, 120),
+                    ],
+                ),
+                1_100,
+            )
+            .unwrap();
+        assert_eq!(stored, 1, "another wallet's event in the batch is ignored");
+        assert_eq!(
+            db.stored_scrobble_keys("0xaaa").unwrap(),
+            HashSet::from([("0x04".to_string(), 40), ("0x03".to_string(), 30)])
+        );
+        assert_eq!(
+            db.stored_scrobble_keys("0xbbb").unwrap(),
+            HashSet::from([("0x02".to_string(), 20)])
+        );
+        let all = db
+            .scrobble_sync_cursor(&ScrobbleScope::All)
+            .unwrap()
+            .unwrap();
+        assert_eq!(all.block_number, 400, "scopes keep their own cursors");
+        assert_eq!(
+            db.scrobble_sync_cursor(&user)
+                .unwrap()
+                .unwrap()
+                .block_number,
+            200
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn subgraph_pages_split_at_a_full_last_block() {
+        let mut calls = Vec::new();
+        // Page one ends with three events in block 150; block 150 holds four in total.
+        let first = page(
+            500,
+            std::iter::repeat_n(120, SUBGRAPH_PAGE_SIZE - 3).chain([150, 150, 150]),
+        );
+        let second = page(500, [150, 150, 150, 150, 160]);
+        let mut pages = vec![first, second].into_iter();
+        let batch = fetch_subgraph_batch(Some(100), |lower, upper| {
+            calls.push((lower, upper));
+            pages.next().ok_or_else(|| "no more pages".to_string())
+        })
+        .unwrap();
+
+        assert_eq!(calls, vec![(100, None), (150, Some(500))]);
+        assert_eq!(batch.events.len(), SUBGRAPH_PAGE_SIZE - 3 + 5);
+        let in_block = |block| {
+            batch
+                .events
+                .iter()
+                .filter(|e| e.block_number == block)
+                .count()
+        };
+        assert_eq!(
+            in_block(150),
+            4,
+            "the split block is taken whole from page two"
+        );
+        assert_eq!(
+            (
+                batch.from_block,
+                batch.to_block,
+                batch.to_block_hash.as_deref()
+            ),
+            (100, 500, Some("0xhead"))
+        );
+    }
+
+    #[test]
+    fn subgraph_block_larger_than_a_page_moves_on() {
+        let mut calls = Vec::new();
+        let mut pages = vec![
+            page(500, std::iter::repeat_n(100, SUBGRAPH_PAGE_SIZE)),
+            page(500, [200]),
+        ]
+        .into_iter();
+        let batch = fetch_subgraph_batch(Some(100), |lower, upper| {
+            calls.push((lower, upper));
+            pages.next().ok_or_else(|| "no more pages".to_string())
+        })
+        .unwrap();
+        assert_eq!(calls, vec![(100, None), (101, Some(500))]);
+        assert_eq!(batch.events.len(), SUBGRAPH_PAGE_SIZE + 1);
+
+        let behind = fetch_subgraph_batch(Some(600), |_, _| Ok(page(500, [])));
+        assert!(behind.unwrap_err().contains("behind local cursor"));
+    }
+
+    #[test]
+    fn subgraph_page_budget_commits_complete_blocks() {
+        let mut next_block = 0;
+        let batch = fetch_subgraph_batch(None, |lower, _| {
+            // Every page is full and ends one block past where it started.
+            next_block = lower + 1;
+            Ok(page(
+                100_000,
+                std::iter::repeat_n(lower, SUBGRAPH_PAGE_SIZE - 1).chain([next_block]),
+            ))
+        })
+        .unwrap();
+        assert_eq!(batch.to_block, next_block - 1);
+        assert_eq!(batch.to_block_hash, None, "a partial sync has no head hash");
+        assert!(batch
+            .events
+            .iter()
+            .all(|event| event.block_number <= batch.to_block));
+    }
+}
@@ -1,11 +1,16 @@
 //! Native Rust scrobble submitter for GPUI using Tempo transactions.
 
 use std::env;
+use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::auth::{AuthProviderKind, PersistedAuth};
-use crate::music_db::TrackRow;
+use crate::auth::{session, AuthProviderKind, PersistedAuth};
+use crate::tempo::{ReceiptSummary, TrackedSend, TxActivity, TxKind};
 
+pub mod eligibility;
+pub mod import;
+pub mod outbox;
+pub mod sinks;
 mod tempo;
 
 const DEFAULT_TEMPO_RPC_URL: &str = "https://rpc.moderato.tempo.xyz";
@@ -40,11 +45,51 @@ pub struct SubmitScrobbleInput {
 }
 
 #[derive(Debug, Clone)]
-pub struct SubmitScrobbleResult {
+pub struct SubmitScrobbleBatchResult {
     pub tx_hash: String,
     pub sender: String,
-    pub track_id: String,
-    pub already_registered: bool,
+    /// On-chain track id for each submitted play, in input order.
+    pub track_ids: Vec<String>,
+    /// Number of tracks registered by this transaction.
+    pub registered: usize,
+}
+
+/// Why a scrobble batch did not land.
+#[derive(Debug, Clone)]
+pub enum ScrobbleSubmitError {
+    /// Nothing was mined; the plays can be sent again.
+    Failed(String),
+    /// The tx was broadcast but its receipt never came back, so it may still land. Sending
+    /// the plays again before checking `tx_hash` could scrobble them twice.
+    Unconfirmed {
+        tx_hash: String,
+        /// On-chain track id for each submitted play, in input order.
+        track_ids: Vec<String>,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ScrobbleSubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Failed(message) => f.write_str(message),
+            Self::Unconfirmed {
+                tx_hash, reason, ..
+            } => write!(f, "Scrobble tx outcome unknown: txHash={tx_hash} {reason}"),
+        }
+    }
+}
+
+impl From<String> for ScrobbleSubmitError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
+    }
+}
+
+impl From<ScrobbleSubmitError> for String {
+    fn from(err: ScrobbleSubmitError) -> Self {
+        err.to_string()
+    }
 }
 
 impl ScrobbleService {
@@ -52,28 +97,30 @@ impl ScrobbleService {
         Ok(Self)
     }
 
-    pub fn submit_track(
+    /// Submit several plays in one `scrobbleBatch` / `registerAndScrobbleBatch` transaction.
+    pub fn submit_batch(
         &mut self,
         auth: &PersistedAuth,
-        track: &TrackRow,
-        played_at_sec: u64,
-    ) -> Result<SubmitScrobbleResult, String> {
+        plays: &[SubmitScrobbleInput],
+    ) -> Result<SubmitScrobbleBatchResult, ScrobbleSubmitError> {
+        let session = Self::signing_session(auth)?;
+        tempo::submit_scrobble_batch_tempo(&session, plays)
+    }
+
+    /// The receipt of a scrobble tx whose outcome was unknown; `None` while the node has
+    /// none. Blocking.
+    pub fn scrobble_receipt(
+        &mut self,
+        auth: &PersistedAuth,
+        tx_hash: &str,
+    ) -> Result<Option<ReceiptSummary>, String> {
         let session = Self::tempo_session_from_auth(auth)?;
-        let input = SubmitScrobbleInput {
-            artist: track.artist.clone(),
-            title: track.title.clone(),
-            album: if track.album.trim().is_empty() {
-                None
-            } else {
-                Some(track.album.clone())
-            },
-            mbid: track.mbid.clone(),
-            ip_id: track.ip_id.clone(),
-            duration_sec: parse_duration_to_sec(&track.duration).unwrap_or(0),
-            played_at_sec,
-        };
-
-        tempo::submit_scrobble_tempo(&session, &input)
+        crate::tempo::lookup_receipt(&session.rpc_url, tx_hash)
+    }
+
+    /// Check that `auth` carries a usable Tempo session key without touching the network.
+    pub fn check_session(&self, auth: &PersistedAuth) -> Result<(), String> {
+        Self::tempo_session_from_auth(auth).map(|_| ())
     }
 
     pub fn upload_track_cover_ref(
@@ -81,7 +128,7 @@ impl ScrobbleService {
         auth: &PersistedAuth,
         cover_path: &str,
     ) -> Result<String, String> {
-        let session = Self::tempo_session_from_auth(auth)?;
+        let session = Self::signing_session(auth)?;
         tempo::upload_cover_to_arweave(&session, cover_path)
     }
 
@@ -91,7 +138,7 @@ impl ScrobbleService {
         track_id: &str,
         lyrics_payload: &str,
     ) -> Result<String, String> {
-        let session = Self::tempo_session_from_auth(auth)?;
+        let session = Self::signing_session(auth)?;
         tempo::upload_lyrics_to_arweave(&session, track_id, lyrics_payload)
     }
 
@@ -101,7 +148,7 @@ impl ScrobbleService {
         track_id: &str,
         cover_ref: &str,
     ) -> Result<String, String> {
-        let session = Self::tempo_session_from_auth(auth)?;
+        let session = Self::signing_session(auth)?;
         tempo::ensure_track_cover_tempo(&session, track_id, cover_ref)
     }
 
@@ -116,7 +163,7 @@ impl ScrobbleService {
         track_id: &str,
         lyrics_ref: &str,
     ) -> Result<String, String> {
-        let session = Self::tempo_session_from_auth(auth)?;
+        let session = Self::signing_session(auth)?;
         tempo::ensure_track_lyrics_tempo(&session, track_id, lyrics_ref)
     }
 
@@ -143,6 +190,11 @@ impl ScrobbleService {
         tempo::read_track_lyrics_ref_tempo(&session, track_id)
     }
 
+    /// Like `tempo_session_from_auth`, but waits for a session key renewal instead of failing.
+    fn signing_session(auth: &PersistedAuth) -> Result<TempoScrobbleSession, String> {
+        Self::tempo_session_from_auth(&session::await_signing_auth(auth)?)
+    }
+
     fn tempo_session_from_auth(auth: &PersistedAuth) -> Result<TempoScrobbleSession, String> {
         if auth.provider_kind() != AuthProviderKind::TempoPasskey {
             return Err(
@@ -172,7 +224,7 @@ impl ScrobbleService {
             .clone()
             .ok_or("Missing Tempo scrobble key authorization. Sign in again to refresh auth.")?;
 
-        if now_epoch_sec() >= session_expires_at {
+        if now_epoch_sec().saturating_add(session::SIGNING_MARGIN_SECS) >= session_expires_at {
             return Err(
                 "Tempo scrobble session key has expired. Sign in again to refresh it.".to_string(),
             );
@@ -200,14 +252,16 @@ impl ScrobbleService {
 
 pub(crate) fn submit_tempo_contract_call(
     auth: &PersistedAuth,
+    kind: TxKind,
     contract_address: &str,
     call_data: Vec<u8>,
     gas_limit_min: u64,
     op_label: &str,
 ) -> Result<String, String> {
-    let session = ScrobbleService::tempo_session_from_auth(auth)?;
+    let session = ScrobbleService::signing_session(auth)?;
     tempo::submit_contract_call_tempo(
         &session,
+        kind,
         contract_address,
         call_data,
         gas_limit_min,
@@ -215,6 +269,47 @@ pub(crate) fn submit_tempo_contract_call(
     )
 }
 
+/// Resubmit the call a failed activity record describes with the current session. Only the
+/// wallet that sent it may resubmit it. `Err` means nothing was sent.
+pub(crate) fn retry_tempo_activity(
+    auth: &PersistedAuth,
+    activity: &TxActivity,
+) -> Result<TrackedSend, String> {
+    if !activity.can_retry() {
+        return Err(format!(
+            "{} cannot be retried from the activity log.",
+            activity.label
+        ));
+    }
+    let signed_in = auth.wallet_address().unwrap_or_default();
+    if !activity
+        .sender
+        .trim()
+        .eq_ignore_ascii_case(signed_in.trim())
+    {
+        return Err(format!(
+            "{} was sent by {}; switch to that account to retry it.",
+            activity.label, activity.sender
+        ));
+    }
+    let session = ScrobbleService::signing_session(auth)?;
+    tempo::retry_activity_tempo(&session, activity)
+}
+
+/// Derive the deterministic on-chain track id (`0x…` bytes32) for track metadata.
+pub fn derive_track_id(input: &SubmitScrobbleInput) -> Result<String, String> {
+    tempo::derive_track_id_hex(input)
+}
+
+/// Read a track's lyrics ref via `eth_call` without requiring an auth session.
+pub fn read_track_lyrics_ref_readonly(track_id: &str) -> Result<Option<String>, String> {
+    tempo::read_track_lyrics_ref_at(
+        &resolve_tempo_rpc_url(),
+        &resolve_tempo_scrobble_contract(),
+        track_id,
+    )
+}
+
 pub fn now_epoch_sec() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -0,0 +1,158 @@
+//! Last.fm-style scrobble eligibility: a play counts once the listener has actually heard
+//! half the track (or four minutes of it), measured as accumulated playback time.
+
+use std::sync::{Arc, Mutex};
+
+use crate::music_db::MusicDb;
+
+/// Settings keys; each has a `HEAVEN_SCROBBLE_*` environment override.
+pub const SCROBBLE_MIN_PERCENT_SETTING: &str = "scrobble_min_percent";
+pub const SCROBBLE_MIN_LISTEN_SECS_SETTING: &str = "scrobble_min_listen_secs";
+pub const SCROBBLE_MIN_TRACK_SECS_SETTING: &str = "scrobble_min_track_secs";
+
+/// Largest position advance between two polls that still counts as listening; anything
+/// bigger is a forward seek and is not credited.
+const MAX_CREDITED_ADVANCE_SECS: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrobbleRules {
+    /// Fraction of the track that must be heard (0.5 = 50%).
+    pub min_fraction: f64,
+    /// Alternatively, this many seconds heard is always enough.
+    pub min_listen_secs: f64,
+    /// Tracks this short or shorter never scrobble.
+    pub min_track_secs: f64,
+}
+
+impl Default for ScrobbleRules {
+    fn default() -> Self {
+        Self {
+            min_fraction: 0.5,
+            min_listen_secs: 240.0,
+            min_track_secs: 30.0,
+        }
+    }
+}
+
+impl ScrobbleRules {
+    /// Rules from `HEAVEN_SCROBBLE_MIN_PERCENT` / `_MIN_LISTEN_SECS` / `_MIN_TRACK_SECS`,
+    /// then the settings table, then Last.fm defaults.
+    pub fn configured(db: Option<&Arc<Mutex<MusicDb>>>) -> Self {
+        let defaults = Self::default();
+        let read = |env_key: &str, setting_key: &str| -> Option<f64> {
+            std::env::var(env_key)
+                .ok()
+                .or_else(|| {
+                    db.and_then(|handle| handle.lock().ok())
+                        .and_then(|db| db.get_setting(setting_key))
+                })
+                .and_then(|raw| raw.trim().parse::<f64>().ok())
+                .filter(|value| value.is_finite() && *value >= 0.0)
+        };
+
+        Self {
+            min_fraction: read("HEAVEN_SCROBBLE_MIN_PERCENT", SCROBBLE_MIN_PERCENT_SETTING)
+                .map(|percent| (percent / 100.0).min(1.0))
+                .unwrap_or(defaults.min_fraction),
+            min_listen_secs: read(
+                "HEAVEN_SCROBBLE_MIN_LISTEN_SECS",
+                SCROBBLE_MIN_LISTEN_SECS_SETTING,
+            )
+            .unwrap_or(defaults.min_listen_secs),
+            min_track_secs: read(
+                "HEAVEN_SCROBBLE_MIN_TRACK_SECS",
+                SCROBBLE_MIN_TRACK_SECS_SETTING,
+            )
+            .unwrap_or(defaults.min_track_secs),
+        }
+    }
+
+    /// `duration_secs` is `None` when the decoder has not reported a length yet; then only
+    /// the absolute listen threshold can qualify the play.
+    pub fn is_eligible(&self, listened_secs: f64, duration_secs: Option<f64>) -> bool {
+        match duration_secs.filter(|secs| *secs > 0.0) {
+            Some(duration) => {
+                duration > self.min_track_secs
+                    && (listened_secs >= duration * self.min_fraction
+                        || listened_secs >= self.min_listen_secs)
+            }
+            None => listened_secs >= self.min_listen_secs && listened_secs > self.min_track_secs,
+        }
+    }
+}
+
+/// Listening progress for one play of one track, fed from the playback poll.
+#[derive(Debug, Clone)]
+pub struct PlaySession {
+    pub track_path: String,
+    pub started_at_sec: u64,
+    pub listened_secs: f64,
+    pub submitted: bool,
+    last_position: Option<f64>,
+}
+
+impl PlaySession {
+    pub fn new(track_path: impl Into<String>, started_at_sec: u64) -> Self {
+        Self {
+            track_path: track_path.into(),
+            started_at_sec,
+            listened_secs: 0.0,
+            submitted: false,
+            last_position: None,
+        }
+    }
+
+    /// Credit forward progress made while playing; pauses, rewinds and seeks add nothing.
+    pub fn observe(&mut self, playing: bool, position: f64) {
+        if playing {
+            if let Some(last) = self.last_position {
+                let advance = position - last;
+                if advance > 0.0 && advance <= MAX_CREDITED_ADVANCE_SECS {
+                    self.listened_secs += advance;
+                }
+            }
+        }
+        self.last_position = Some(position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_for(session: &mut PlaySession, from: f64, secs: f64) {
+        let mut position = from;
+        session.observe(true, position);
+        while position < from + secs {
+            position += 0.2;
+            session.observe(true, position);
+        }
+    }
+
+    #[test]
+    fn seeking_to_the_end_does_not_count_but_listening_past_half_does() {
+        let rules = ScrobbleRules::default();
+        let mut session = PlaySession::new("/music/a.flac", 100);
+        play_for(&mut session, 0.0, 10.0);
+        session.observe(true, 195.0);
+        play_for(&mut session, 195.0, 5.0);
+        assert!(!rules.is_eligible(session.listened_secs, Some(200.0)));
+
+        let mut session = PlaySession::new("/music/a.flac", 100);
+        play_for(&mut session, 0.0, 60.0);
+        session.observe(false, 60.0);
+        session.observe(false, 60.0);
+        play_for(&mut session, 60.0, 45.0);
+        assert!(session.listened_secs > 100.0 && session.listened_secs < 106.0);
+        assert!(rules.is_eligible(session.listened_secs, Some(200.0)));
+    }
+
+    #[test]
+    fn long_tracks_qualify_after_four_minutes_and_short_tracks_never() {
+        let rules = ScrobbleRules::default();
+        assert!(rules.is_eligible(240.0, Some(1_800.0)));
+        assert!(!rules.is_eligible(239.0, Some(1_800.0)));
+        assert!(!rules.is_eligible(30.0, Some(30.0)));
+        assert!(rules.is_eligible(240.0, None));
+    }
+}
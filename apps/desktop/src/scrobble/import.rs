@@ -0,0 +1,330 @@
+//! Backfill on-chain scrobbles from exported listening history.
+//!
+//! A file is parsed, every play is resolved to its on-chain track id, and plays already on
+//! chain (local outbox, earlier imports, the synced `Scrobbled` history) are dropped. The rest are
+//! staged in `scrobble_import` so a long import survives restarts and is submitted in spaced
+//! batches instead of one burst of transactions.
+
+use std::collections::HashSet;
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::music_db::{ImportedScrobble, ImportedScrobbleRow, MusicDb, UnconfirmedScrobbles};
+use crate::profile::scrobbles_feed::fetch_scrobble_keys_for_user;
+
+use super::outbox::{QueuedScrobble, ScrobbleQueue};
+use super::{derive_track_id, now_epoch_sec, SubmitScrobbleInput};
+
+pub mod parse;
+
+use parse::{parse_history, ImportSource, ParsedHistory};
+
+/// Pause between import batches so a multi-year history does not monopolize the session key.
+const DEFAULT_IMPORT_INTERVAL_SECS: u64 = 15;
+
+#[derive(Debug, Clone)]
+pub struct ImportSummary {
+    pub source: ImportSource,
+    pub parsed: usize,
+    pub staged: usize,
+    pub duplicates: usize,
+    pub skipped: usize,
+}
+
+impl ImportSummary {
+    pub fn describe(&self) -> String {
+        let mut message = format!(
+            "{} history: {} new play{} queued",
+            self.source.label(),
+            self.staged,
+            if self.staged == 1 { "" } else { "s" }
+        );
+        if self.duplicates > 0 {
+            message.push_str(&format!(", {} already scrobbled", self.duplicates));
+        }
+        if self.skipped > 0 {
+            message.push_str(&format!(", {} skipped", self.skipped));
+        }
+        message.push('.');
+        message
+    }
+}
+
+pub fn import_interval_secs() -> u64 {
+    env::var("HEAVEN_SCROBBLE_IMPORT_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_IMPORT_INTERVAL_SECS)
+}
+
+/// Parse `path`, dedupe against local and on-chain scrobbles and stage the rest. Blocking.
+pub fn stage_history_file(
+    path: &Path,
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+) -> Result<ImportSummary, String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    let parsed = parse_history(&file_name, &contents)?;
+
+    // Local records only cover plays made in this app; without the on-chain history a
+    // re-import of plays scrobbled elsewhere would be submitted twice.
+    let chain_keys = fetch_scrobble_keys_for_user(db, user_address).map_err(|err| {
+        format!("Cannot check on-chain scrobbles for duplicates, nothing was queued: {err}")
+    })?;
+
+    let db = db
+        .lock()
+        .map_err(|e| format!("music db lock failed: {e}"))?;
+    let summary = stage_parsed_history(
+        parsed,
+        &db,
+        user_address,
+        &chain_keys,
+        now_epoch_sec() as i64,
+    )?;
+    log::info!(
+        "[ScrobbleImport] staged file={} source={} parsed={} staged={} duplicates={} skipped={}",
+        file_name,
+        summary.source.as_str(),
+        summary.parsed,
+        summary.staged,
+        summary.duplicates,
+        summary.skipped
+    );
+    Ok(summary)
+}
+
+fn stage_parsed_history(
+    parsed: ParsedHistory,
+    db: &MusicDb,
+    user_address: &str,
+    chain_keys: &HashSet<(String, u64)>,
+    now: i64,
+) -> Result<ImportSummary, String> {
+    let mut known = db.known_scrobble_keys(user_address)?;
+    known.extend(chain_keys.iter().cloned());
+
+    let parsed_count = parsed.plays.len();
+    let mut unresolved = 0;
+    let mut fresh = Vec::new();
+    for play in parsed.plays {
+        let Some(scrobble) = resolve_play(play, parsed.source) else {
+            unresolved += 1;
+            continue;
+        };
+        // `insert` also drops repeats inside the file itself.
+        if known.insert((scrobble.track_id.clone(), scrobble.played_at_sec)) {
+            fresh.push(scrobble);
+        }
+    }
+
+    let staged = db.stage_imported_scrobbles(user_address, &fresh, now)?;
+    Ok(ImportSummary {
+        source: parsed.source,
+        parsed: parsed_count,
+        staged,
+        duplicates: parsed_count - unresolved - staged,
+        skipped: parsed.skipped + unresolved,
+    })
+}
+
+/// Resolve the on-chain track id the same way a live play would. Exports sometimes carry
+/// malformed MBIDs; those fall back to the normalized metadata id instead of being dropped.
+fn resolve_play(mut play: SubmitScrobbleInput, source: ImportSource) -> Option<ImportedScrobble> {
+    let track_id = match derive_track_id(&play) {
+        Ok(track_id) => track_id,
+        Err(_) if play.mbid.is_some() => {
+            play.mbid = None;
+            derive_track_id(&play).ok()?
+        }
+        Err(_) => return None,
+    };
+    Some(ImportedScrobble {
+        source: source.as_str().to_string(),
+        track_id: track_id.to_ascii_lowercase(),
+        artist: play.artist,
+        title: play.title,
+        album: play.album,
+        mbid: play.mbid,
+        duration_sec: play.duration_sec,
+        played_at_sec: play.played_at_sec,
+    })
+}
+
+impl QueuedScrobble for ImportedScrobbleRow {
+    type Play = ImportedScrobble;
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    fn input(&self) -> SubmitScrobbleInput {
+        SubmitScrobbleInput {
+            artist: self.scrobble.artist.clone(),
+            title: self.scrobble.title.clone(),
+            album: self.scrobble.album.clone(),
+            mbid: self.scrobble.mbid.clone(),
+            ip_id: None,
+            duration_sec: self.scrobble.duration_sec,
+            played_at_sec: self.scrobble.played_at_sec,
+        }
+    }
+
+    fn into_play(self) -> ImportedScrobble {
+        self.scrobble
+    }
+}
+
+/// Staged history in `scrobble_import`, submitted in spaced batches.
+pub struct HistoryImport;
+
+impl ScrobbleQueue for HistoryImport {
+    type Row = ImportedScrobbleRow;
+    const LOG_TAG: &'static str = "[ScrobbleImport]";
+
+    fn due(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<ImportedScrobbleRow>, String> {
+        db.due_imported_scrobbles(user_address, now, limit)
+    }
+
+    /// Imported rows already carry their track id.
+    fn mark_confirmed(
+        db: &MusicDb,
+        confirmed: &[(i64, String)],
+        _user_address: &str,
+        tx_hash: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        let ids: Vec<i64> = confirmed.iter().map(|(id, _)| *id).collect();
+        db.mark_imported_scrobbles_submitted(&ids, tx_hash, now)
+    }
+
+    fn record_failure(
+        db: &MusicDb,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        db.record_imported_scrobble_failure(id, error, next_attempt_at, now)
+    }
+
+    fn mark_unconfirmed(
+        db: &MusicDb,
+        rows: &[(i64, String)],
+        tx_hash: &str,
+        error: &str,
+        check_at: i64,
+        now: i64,
+    ) -> Result<(), String> {
+        let ids: Vec<i64> = rows.iter().map(|(id, _)| *id).collect();
+        db.mark_imported_scrobbles_unconfirmed(&ids, tx_hash, error, check_at, now)
+    }
+
+    fn due_unconfirmed(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+    ) -> Result<Option<UnconfirmedScrobbles<ImportedScrobbleRow>>, String> {
+        db.due_unconfirmed_imported_scrobbles(user_address, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::outbox::select_batch;
+    use super::*;
+
+    fn history(plays: &[(&str, u64)]) -> ParsedHistory {
+        ParsedHistory {
+            source: ImportSource::LastFm,
+            plays: plays
+                .iter()
+                .map(|(title, played_at_sec)| SubmitScrobbleInput {
+                    artist: "Artist".to_string(),
+                    title: title.to_string(),
+                    album: None,
+                    mbid: None,
+                    ip_id: None,
+                    duration_sec: 200,
+                    played_at_sec: *played_at_sec,
+                })
+                .collect(),
+            skipped: 1,
+        }
+    }
+
+    #[test]
+    fn staging_dedupes_within_file_against_chain_and_on_reimport() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-import-test-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).expect("open music db");
+        let wallet = "0xAbC0000000000000000000000000000000000001";
+
+        let onchain = resolve_play(
+            history(&[("Old", 10)]).plays.remove(0),
+            ImportSource::LastFm,
+        )
+        .unwrap();
+        let chain_keys = HashSet::from([(onchain.track_id, 10)]);
+
+        let parsed = history(&[("Old", 10), ("New", 20), ("New", 20), ("Other", 30)]);
+        let summary = stage_parsed_history(parsed, &db, wallet, &chain_keys, 100).unwrap();
+        assert_eq!(summary.staged, 2);
+        assert_eq!(summary.duplicates, 2);
+        assert_eq!(summary.skipped, 1);
+
+        let again = stage_parsed_history(
+            history(&[("New", 20), ("Other", 30)]),
+            &db,
+            wallet,
+            &HashSet::new(),
+            200,
+        )
+        .unwrap();
+        assert_eq!(again.staged, 0);
+        assert_eq!(again.duplicates, 2);
+
+        let due = db.due_imported_scrobbles(wallet, 100, 50).unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].scrobble.title, "New");
+
+        db.record_imported_scrobble_failure(due[0].id, "reverted", Some(500), 100)
+            .unwrap();
+        let batch = select_batch(db.due_imported_scrobbles(wallet, 600, 50).unwrap(), 50);
+        assert_eq!(batch.len(), 1, "a failed play is retried on its own");
+        db.mark_imported_scrobbles_submitted(&[batch[0].id], "0xtx", 600)
+            .unwrap();
+
+        let counts = db.imported_scrobble_counts(wallet).unwrap();
+        assert_eq!((counts.pending, counts.confirmed), (1, 1));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalid_mbid_falls_back_to_metadata_id() {
+        let mut play = history(&[("Song", 1)]).plays.remove(0);
+        play.mbid = Some("not-a-uuid".to_string());
+        let resolved = resolve_play(play, ImportSource::ListenBrainz).unwrap();
+        assert_eq!(resolved.mbid, None);
+        assert!(resolved.track_id.starts_with("0x"));
+    }
+}
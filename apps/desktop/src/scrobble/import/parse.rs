@@ -0,0 +1,345 @@
+//! Parsers for exported listening history: Spotify (extended and account-data JSON),
+//! ListenBrainz (JSON array or JSONL) and Last.fm CSV.
+
+use chrono::{DateTime, NaiveDateTime};
+use serde_json::Value;
+
+use crate::scrobble::SubmitScrobbleInput;
+
+/// Spotify counts a stream after 30 seconds; shorter entries are skips.
+const SPOTIFY_MIN_MS_PLAYED: u64 = 30_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    Spotify,
+    ListenBrainz,
+    LastFm,
+}
+
+impl ImportSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Spotify => "spotify",
+            Self::ListenBrainz => "listenbrainz",
+            Self::LastFm => "lastfm",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Spotify => "Spotify",
+            Self::ListenBrainz => "ListenBrainz",
+            Self::LastFm => "Last.fm",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedHistory {
+    pub source: ImportSource,
+    pub plays: Vec<SubmitScrobbleInput>,
+    /// Entries that were not music plays (podcasts, skips) or lacked artist/title/time.
+    pub skipped: usize,
+}
+
+/// Detect the export format from its contents and parse every entry.
+pub fn parse_history(file_name: &str, contents: &str) -> Result<ParsedHistory, String> {
+    let trimmed = contents.trim_start_matches('\u{feff}').trim();
+    if trimmed.is_empty() {
+        return Err(format!("{file_name} is empty"));
+    }
+    if !trimmed.starts_with('[') && !trimmed.starts_with('{') {
+        return Ok(parse_lastfm_csv(trimmed));
+    }
+
+    let entries: Vec<Value> = if trimmed.starts_with('[') {
+        serde_json::from_str::<Vec<Value>>(trimmed)
+            .map_err(|e| format!("{file_name} is not a JSON array: {e}"))?
+    } else {
+        trimmed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{file_name} is not JSON lines: {e}"))?
+    };
+
+    let first = entries
+        .first()
+        .ok_or(format!("{file_name} has no entries"))?;
+    if first.get("listened_at").is_some() {
+        Ok(collect(
+            ImportSource::ListenBrainz,
+            &entries,
+            listenbrainz_entry,
+        ))
+    } else if first.get("ms_played").is_some() || first.get("msPlayed").is_some() {
+        Ok(collect(ImportSource::Spotify, &entries, spotify_entry))
+    } else {
+        Err(format!(
+            "{file_name} is not a Spotify or ListenBrainz listening history export"
+        ))
+    }
+}
+
+fn collect(
+    source: ImportSource,
+    entries: &[Value],
+    parse: fn(&Value) -> Option<SubmitScrobbleInput>,
+) -> ParsedHistory {
+    let plays: Vec<SubmitScrobbleInput> = entries.iter().filter_map(parse).collect();
+    ParsedHistory {
+        source,
+        skipped: entries.len() - plays.len(),
+        plays,
+    }
+}
+
+fn spotify_entry(entry: &Value) -> Option<SubmitScrobbleInput> {
+    let str_field = |key: &str| {
+        entry
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    // Extended streaming history: `ts` is when the stream ended.
+    if let Some(ts) = str_field("ts") {
+        let ms_played = entry.get("ms_played").and_then(Value::as_u64)?;
+        if ms_played < SPOTIFY_MIN_MS_PLAYED {
+            return None;
+        }
+        let ended_at = DateTime::parse_from_rfc3339(ts).ok()?.timestamp();
+        return Some(play(
+            str_field("master_metadata_album_artist_name")?,
+            str_field("master_metadata_track_name")?,
+            str_field("master_metadata_album_album_name"),
+            None,
+            0,
+            ended_at.saturating_sub((ms_played / 1000) as i64),
+        ));
+    }
+
+    // Account-data `StreamingHistory*.json`: `endTime` in UTC, minute precision.
+    let ms_played = entry.get("msPlayed").and_then(Value::as_u64)?;
+    if ms_played < SPOTIFY_MIN_MS_PLAYED {
+        return None;
+    }
+    let ended_at = NaiveDateTime::parse_from_str(str_field("endTime")?, "%Y-%m-%d %H:%M")
+        .ok()?
+        .and_utc()
+        .timestamp();
+    Some(play(
+        str_field("artistName")?,
+        str_field("trackName")?,
+        None,
+        None,
+        0,
+        ended_at.saturating_sub((ms_played / 1000) as i64),
+    ))
+}
+
+fn listenbrainz_entry(entry: &Value) -> Option<SubmitScrobbleInput> {
+    let listened_at = entry.get("listened_at").and_then(Value::as_i64)?;
+    let meta = entry.get("track_metadata")?;
+    let info = meta.get("additional_info");
+    let str_at = |value: Option<&Value>, key: &str| {
+        value
+            .and_then(|v| v.get(key))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    let mbid = str_at(info, "recording_mbid")
+        .or_else(|| str_at(meta.get("mbid_mapping"), "recording_mbid"));
+    let duration_sec = info
+        .and_then(|info| {
+            info.get("duration_ms")
+                .and_then(Value::as_u64)
+                .map(|ms| ms / 1000)
+                .or_else(|| info.get("duration").and_then(Value::as_u64))
+        })
+        .unwrap_or(0);
+    Some(play(
+        str_at(Some(meta), "artist_name")?,
+        str_at(Some(meta), "track_name")?,
+        str_at(Some(meta), "release_name"),
+        mbid,
+        duration_sec,
+        listened_at,
+    ))
+}
+
+/// Last.fm exports come either headerless (`artist,album,title,date` with dates like
+/// `31 Jan 2021 19:40`) or with a `uts,utc_time,artist,...,track,track_mbid` header.
+fn parse_lastfm_csv(contents: &str) -> ParsedHistory {
+    let mut rows = contents.lines().map(parse_csv_line).peekable();
+    let header: Option<Vec<String>> = rows
+        .peek()
+        .filter(|row| {
+            row.iter()
+                .any(|cell| cell.eq_ignore_ascii_case("uts") || cell.eq_ignore_ascii_case("track"))
+        })
+        .map(|row| row.iter().map(|cell| cell.to_ascii_lowercase()).collect());
+    if header.is_some() {
+        rows.next();
+    }
+    let column = |name: &str| {
+        header
+            .as_ref()
+            .and_then(|header| header.iter().position(|cell| cell == name))
+    };
+    let (artist_col, album_col, title_col) = match header {
+        Some(_) => (column("artist"), column("album"), column("track")),
+        None => (Some(0), Some(1), Some(2)),
+    };
+    let uts_col = column("uts");
+    let mbid_col = column("track_mbid");
+    let date_col = if header.is_some() {
+        column("utc_time")
+    } else {
+        Some(3)
+    };
+
+    let mut plays = Vec::new();
+    let mut skipped = 0;
+    for row in rows {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |idx: Option<usize>| {
+            idx.and_then(|idx| row.get(idx))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let played_at = cell(uts_col)
+            .and_then(|raw| raw.parse::<i64>().ok())
+            .or_else(|| {
+                cell(date_col).and_then(|raw| {
+                    NaiveDateTime::parse_from_str(raw, "%d %b %Y %H:%M")
+                        .ok()
+                        .map(|at| at.and_utc().timestamp())
+                })
+            });
+        match (cell(artist_col), cell(title_col), played_at) {
+            (Some(artist), Some(title), Some(played_at)) => plays.push(play(
+                artist,
+                title,
+                cell(album_col),
+                cell(mbid_col),
+                0,
+                played_at,
+            )),
+            _ => skipped += 1,
+        }
+    }
+    ParsedHistory {
+        source: ImportSource::LastFm,
+        plays,
+        skipped,
+    }
+}
+
+/// Split one CSV record, honouring double-quoted cells with `""` escapes.
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(ch),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+fn play(
+    artist: &str,
+    title: &str,
+    album: Option<&str>,
+    mbid: Option<&str>,
+    duration_sec: u64,
+    played_at: i64,
+) -> SubmitScrobbleInput {
+    SubmitScrobbleInput {
+        artist: artist.to_string(),
+        title: title.to_string(),
+        album: album.map(str::to_string),
+        mbid: mbid.map(str::to_string),
+        ip_id: None,
+        duration_sec: duration_sec.min(u64::from(u32::MAX)) as u32,
+        played_at_sec: played_at.max(0) as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spotify_extended_history_skips_podcasts_and_short_streams() {
+        let json = r#"[
+            {"ts":"2023-11-14T22:18:20Z","ms_played":200000,
+             "master_metadata_track_name":"Song","master_metadata_album_artist_name":"Artist",
+             "master_metadata_album_album_name":"Album"},
+            {"ts":"2023-11-14T22:20:00Z","ms_played":5000,
+             "master_metadata_track_name":"Skipped","master_metadata_album_artist_name":"Artist"},
+            {"ts":"2023-11-14T23:00:00Z","ms_played":900000,"master_metadata_track_name":null,
+             "episode_name":"Podcast"}
+        ]"#;
+        let parsed = parse_history("endsong.json", json).unwrap();
+        assert_eq!(parsed.source, ImportSource::Spotify);
+        assert_eq!(parsed.skipped, 2);
+        assert_eq!(parsed.plays.len(), 1);
+        assert_eq!(parsed.plays[0].played_at_sec, 1_700_000_300 - 200);
+        assert_eq!(parsed.plays[0].album.as_deref(), Some("Album"));
+    }
+
+    #[test]
+    fn listenbrainz_jsonl_reads_mbids_from_either_location() {
+        let jsonl = concat!(
+            r#"{"listened_at":1700000000,"track_metadata":{"artist_name":"A","track_name":"One","additional_info":{"recording_mbid":"f1d2b7b4-6a7e-4b39-9d38-8d0f5a4f0c11","duration_ms":181000}}}"#,
+            "\n",
+            r#"{"listened_at":1700000300,"track_metadata":{"artist_name":"A","track_name":"Two","release_name":"LP","mbid_mapping":{"recording_mbid":"0a1b2c3d-0000-4000-8000-000000000000"}}}"#,
+        );
+        let parsed = parse_history("listens.jsonl", jsonl).unwrap();
+        assert_eq!(parsed.source, ImportSource::ListenBrainz);
+        assert_eq!(parsed.plays.len(), 2);
+        assert_eq!(parsed.plays[0].duration_sec, 181);
+        assert_eq!(
+            parsed.plays[1].mbid.as_deref(),
+            Some("0a1b2c3d-0000-4000-8000-000000000000")
+        );
+    }
+
+    #[test]
+    fn lastfm_csv_with_and_without_header() {
+        let headerless = "Artist,\"Album, Deluxe\",\"Say \"\"Hi\"\"\",14 Nov 2023 22:13\n,,,\n";
+        let parsed = parse_history("lastfm.csv", headerless).unwrap();
+        assert_eq!(parsed.source, ImportSource::LastFm);
+        assert_eq!(parsed.plays.len(), 1);
+        assert_eq!(parsed.plays[0].title, "Say \"Hi\"");
+        assert_eq!(parsed.plays[0].album.as_deref(), Some("Album, Deluxe"));
+        assert_eq!(parsed.plays[0].played_at_sec, 1_699_999_980);
+
+        let with_header = "uts,utc_time,artist,artist_mbid,album,album_mbid,track,track_mbid\n\
+                           1700000000,\"14 Nov 2023, 22:13\",Artist,,Album,,Song,\n\
+                           bogus,,Artist,,Album,,,\n";
+        let parsed = parse_history("lastfm.csv", with_header).unwrap();
+        assert_eq!(parsed.plays.len(), 1);
+        assert_eq!(parsed.skipped, 1);
+        assert_eq!(parsed.plays[0].played_at_sec, 1_700_000_000);
+        assert_eq!(parsed.plays[0].title, "Song");
+    }
+}
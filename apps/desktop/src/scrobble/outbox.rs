@@ -0,0 +1,693 @@
+//! Durable scrobble outbox: finished plays are written to `MusicDb` first and flushed to
+//! Tempo in batches, so RPC/fee-payer outages or a signed-out session never drop a play.
+
+use std::sync::Mutex;
+
+use crate::auth::PersistedAuth;
+use crate::music_db::{MusicDb, ScrobbleOutboxRow, ScrobblePlay, TrackRow, UnconfirmedScrobbles};
+use crate::tempo::ReceiptSummary;
+
+use super::sinks::{ScrobbleSink, SinkReceipt, TempoSink};
+use super::{
+    now_epoch_sec, parse_duration_to_sec, ScrobbleService, ScrobbleSubmitError, SubmitScrobbleInput,
+};
+
+/// Failed attempts before a play is parked as `failed` until the user retries it.
+pub const MAX_SCROBBLE_ATTEMPTS: u32 = 8;
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+const RETRY_MAX_DELAY_SECS: i64 = 6 * 60 * 60;
+
+pub fn play_from_track(track: &TrackRow, played_at_sec: u64) -> ScrobblePlay {
+    ScrobblePlay {
+        track_path: track.file_path.clone(),
+        cover_path: track
+            .cover_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string),
+        artist: track.artist.clone(),
+        title: track.title.clone(),
+        album: if track.album.trim().is_empty() {
+            None
+        } else {
+            Some(track.album.clone())
+        },
+        mbid: track.mbid.clone(),
+        ip_id: track.ip_id.clone(),
+        duration_sec: parse_duration_to_sec(&track.duration).unwrap_or(0),
+        played_at_sec,
+    }
+}
+
+pub fn input_for_play(play: &ScrobblePlay) -> SubmitScrobbleInput {
+    SubmitScrobbleInput {
+        artist: play.artist.clone(),
+        title: play.title.clone(),
+        album: play.album.clone(),
+        mbid: play.mbid.clone(),
+        ip_id: play.ip_id.clone(),
+        duration_sec: play.duration_sec,
+        played_at_sec: play.played_at_sec,
+    }
+}
+
+/// Exponential backoff after the `failures`-th failed attempt; `None` once the budget is spent.
+pub fn next_attempt_after_failure(failures: u32, now: i64) -> Option<i64> {
+    if failures >= MAX_SCROBBLE_ATTEMPTS {
+        return None;
+    }
+    let exponent = failures.saturating_sub(1).min(20);
+    let delay = RETRY_BASE_DELAY_SECS
+        .saturating_mul(1_i64 << exponent)
+        .min(RETRY_MAX_DELAY_SECS);
+    Some(now.saturating_add(delay))
+}
+
+/// A play waiting in one of the durable scrobble tables.
+pub trait QueuedScrobble {
+    /// What the caller gets back for each play once its batch lands.
+    type Play;
+    fn id(&self) -> i64;
+    fn attempts(&self) -> u32;
+    fn input(&self) -> SubmitScrobbleInput;
+    fn into_play(self) -> Self::Play;
+}
+
+impl QueuedScrobble for ScrobbleOutboxRow {
+    type Play = ScrobblePlay;
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    fn input(&self) -> SubmitScrobbleInput {
+        input_for_play(&self.play)
+    }
+
+    fn into_play(self) -> ScrobblePlay {
+        self.play
+    }
+}
+
+/// A durable table of plays bound for Tempo. Live plays and staged history imports both
+/// drain through [`flush_outbox_batch`].
+pub trait ScrobbleQueue {
+    type Row: QueuedScrobble;
+    /// Log prefix for this queue's batches.
+    const LOG_TAG: &'static str;
+
+    fn due(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<Self::Row>, String>;
+
+    /// `confirmed` pairs each row id with its on-chain track id.
+    fn mark_confirmed(
+        db: &MusicDb,
+        confirmed: &[(i64, String)],
+        user_address: &str,
+        tx_hash: &str,
+        now: i64,
+    ) -> Result<(), String>;
+
+    fn record_failure(
+        db: &MusicDb,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String>;
+
+    /// Park a broadcast batch whose receipt never came back until `check_at`; `rows` pairs
+    /// each row id with its track id.
+    fn mark_unconfirmed(
+        db: &MusicDb,
+        rows: &[(i64, String)],
+        tx_hash: &str,
+        error: &str,
+        check_at: i64,
+        now: i64,
+    ) -> Result<(), String>;
+
+    fn due_unconfirmed(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+    ) -> Result<Option<UnconfirmedScrobbles<Self::Row>>, String>;
+}
+
+/// Plays finished in this app, queued in `scrobble_outbox`.
+pub struct PlayOutbox;
+
+impl ScrobbleQueue for PlayOutbox {
+    type Row = ScrobbleOutboxRow;
+    const LOG_TAG: &'static str = "[Scrobble] outbox";
+
+    fn due(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<ScrobbleOutboxRow>, String> {
+        db.due_scrobbles(user_address, now, limit)
+    }
+
+    fn mark_confirmed(
+        db: &MusicDb,
+        confirmed: &[(i64, String)],
+        user_address: &str,
+        tx_hash: &str,
+        now: i64,
+    ) -> Result<(), String> {
+        db.mark_scrobbles_confirmed(confirmed, user_address, tx_hash, now)
+    }
+
+    fn record_failure(
+        db: &MusicDb,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+        now: i64,
+    ) -> Result<(), String> {
+        db.record_scrobble_failure(id, error, next_attempt_at, now)
+    }
+
+    fn mark_unconfirmed(
+        db: &MusicDb,
+        rows: &[(i64, String)],
+        tx_hash: &str,
+        error: &str,
+        check_at: i64,
+        now: i64,
+    ) -> Result<(), String> {
+        db.mark_scrobbles_unconfirmed(rows, tx_hash, error, check_at, now)
+    }
+
+    fn due_unconfirmed(
+        db: &MusicDb,
+        user_address: &str,
+        now: i64,
+    ) -> Result<Option<UnconfirmedScrobbles<ScrobbleOutboxRow>>, String> {
+        db.due_unconfirmed_scrobbles(user_address, now)
+    }
+}
+
+/// The Tempo side of [`flush_outbox_batch`]: [`TempoSink`] in the app.
+pub trait TempoBatchSink: ScrobbleSink {
+    fn submit_batch(
+        &mut self,
+        plays: &[SubmitScrobbleInput],
+    ) -> Result<SinkReceipt, ScrobbleSubmitError>;
+
+    /// The receipt of a batch whose outcome was unknown; `None` while the node has none.
+    fn receipt(&mut self, tx_hash: &str) -> Result<Option<ReceiptSummary>, String>;
+
+    /// Seconds after a broadcast by which a tx without a receipt can no longer land.
+    fn inclusion_window_secs(&self) -> i64;
+}
+
+/// Coalesce up to `limit` fresh plays into one batch, but send a play that already failed on
+/// its own so a single bad entry (e.g. a reverting registration) cannot block the rest forever.
+pub fn select_batch<R: QueuedScrobble>(due: Vec<R>, limit: usize) -> Vec<R> {
+    let mut due = due.into_iter();
+    let Some(first) = due.next() else {
+        return Vec::new();
+    };
+    if first.attempts() > 0 {
+        return vec![first];
+    }
+    let mut batch = vec![first];
+    batch.extend(
+        due.filter(|row| row.attempts() == 0)
+            .take(limit.saturating_sub(1)),
+    );
+    batch
+}
+
+#[derive(Debug)]
+pub enum OutboxFlushStep<P = ScrobblePlay> {
+    /// Nothing is due right now.
+    Idle,
+    /// The session cannot sign yet (expired key, wrong provider); plays stay pending untouched.
+    Held(String),
+    /// One batch landed; each play is paired with its on-chain track id.
+    Confirmed {
+        tx_hash: String,
+        plays: Vec<(String, P)>,
+    },
+    /// One batch failed and was rescheduled (or parked as failed).
+    Failed { error: String, plays: usize },
+    /// One batch was broadcast but its outcome is unknown. Its plays wait for a receipt check
+    /// instead of being sent again, which could scrobble them twice.
+    Unconfirmed { tx_hash: String, plays: usize },
+}
+
+/// Submit the next due batch of `Q` for `user_address`. Blocking; run off the UI thread.
+pub fn flush_outbox_batch<Q: ScrobbleQueue>(
+    service: &Mutex<ScrobbleService>,
+    auth: &PersistedAuth,
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+) -> Result<OutboxFlushStep<<Q::Row as QueuedScrobble>::Play>, String> {
+    let mut service = service
+        .lock()
+        .map_err(|e| format!("scrobble service lock failed: {e}"))?;
+    flush_batch_to::<Q>(&mut TempoSink::new(&mut service, auth), db, user_address)
+}
+
+/// [`flush_outbox_batch`] over any Tempo sink. A parked batch whose receipt check is due is
+/// settled before anything new is sent.
+fn flush_batch_to<Q: ScrobbleQueue>(
+    sink: &mut impl TempoBatchSink,
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+) -> Result<OutboxFlushStep<<Q::Row as QueuedScrobble>::Play>, String> {
+    if let Err(err) = sink.check_ready() {
+        return Ok(OutboxFlushStep::Held(err));
+    }
+    let limit = sink.max_batch();
+
+    let now = now_epoch_sec() as i64;
+    let (parked, due) = {
+        let db = db
+            .lock()
+            .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+        match Q::due_unconfirmed(&db, user_address, now)? {
+            Some(parked) => (Some(parked), Vec::new()),
+            None => (None, Q::due(&db, user_address, now, limit)?),
+        }
+    };
+    if let Some(parked) = parked {
+        return settle_unconfirmed::<Q>(sink, db, user_address, parked);
+    }
+    let batch = select_batch(due, limit);
+    if batch.is_empty() {
+        return Ok(OutboxFlushStep::Idle);
+    }
+
+    let inputs: Vec<SubmitScrobbleInput> = batch.iter().map(QueuedScrobble::input).collect();
+    let result = sink.submit_batch(&inputs);
+
+    let now = now_epoch_sec() as i64;
+    let db = db
+        .lock()
+        .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+    match result {
+        Ok(receipt) => {
+            let tx_hash = receipt.reference.unwrap_or_default();
+            let confirmed: Vec<(i64, String)> = batch
+                .iter()
+                .zip(receipt.track_ids.iter())
+                .map(|(row, track_id)| (row.id(), track_id.clone()))
+                .collect();
+            Q::mark_confirmed(&db, &confirmed, user_address, &tx_hash, now)?;
+            log::info!(
+                "{} batch confirmed: txHash={} plays={}",
+                Q::LOG_TAG,
+                tx_hash,
+                batch.len()
+            );
+            let plays = batch
+                .into_iter()
+                .zip(receipt.track_ids)
+                .map(|(row, track_id)| (track_id, row.into_play()))
+                .collect();
+            Ok(OutboxFlushStep::Confirmed { tx_hash, plays })
+        }
+        Err(ScrobbleSubmitError::Unconfirmed {
+            tx_hash,
+            track_ids,
+            reason,
+        }) => {
+            let parked: Vec<(i64, String)> = batch
+                .iter()
+                .zip(track_ids)
+                .map(|(row, track_id)| (row.id(), track_id))
+                .collect();
+            let check_at = now.saturating_add(sink.inclusion_window_secs());
+            Q::mark_unconfirmed(&db, &parked, &tx_hash, &reason, check_at, now)?;
+            log::warn!(
+                "{} batch outcome unknown: txHash={} plays={} checkAt={} err={}",
+                Q::LOG_TAG,
+                tx_hash,
+                batch.len(),
+                check_at,
+                reason
+            );
+            Ok(OutboxFlushStep::Unconfirmed {
+                tx_hash,
+                plays: batch.len(),
+            })
+        }
+        Err(ScrobbleSubmitError::Failed(err)) => {
+            for row in &batch {
+                let next_attempt_at = next_attempt_after_failure(row.attempts() + 1, now);
+                Q::record_failure(&db, row.id(), &err, next_attempt_at, now)?;
+            }
+            log::warn!(
+                "{} batch failed: plays={} err={}",
+                Q::LOG_TAG,
+                batch.len(),
+                err
+            );
+            Ok(OutboxFlushStep::Failed {
+                error: err,
+                plays: batch.len(),
+            })
+        }
+    }
+}
+
+/// Look up the receipt of a parked batch once its inclusion window has passed: confirm it if
+/// it landed, hand the plays back for a retry if it reverted or never landed, and keep waiting
+/// while the node cannot answer.
+fn settle_unconfirmed<Q: ScrobbleQueue>(
+    sink: &mut impl TempoBatchSink,
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+    parked: UnconfirmedScrobbles<Q::Row>,
+) -> Result<OutboxFlushStep<<Q::Row as QueuedScrobble>::Play>, String> {
+    let UnconfirmedScrobbles { tx_hash, rows } = parked;
+    let receipt = sink.receipt(&tx_hash);
+
+    let now = now_epoch_sec() as i64;
+    let db = db
+        .lock()
+        .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+    let pairs: Vec<(i64, String)> = rows
+        .iter()
+        .map(|(row, track_id)| (row.id(), track_id.clone()))
+        .collect();
+    match receipt {
+        Ok(Some(summary)) if !summary.reverted() => {
+            Q::mark_confirmed(&db, &pairs, user_address, &tx_hash, now)?;
+            log::info!(
+                "{} unconfirmed batch landed: txHash={} plays={}",
+                Q::LOG_TAG,
+                tx_hash,
+                rows.len()
+            );
+            let plays = rows
+                .into_iter()
+                .map(|(row, track_id)| (track_id, row.into_play()))
+                .collect();
+            Ok(OutboxFlushStep::Confirmed { tx_hash, plays })
+        }
+        Ok(found) => {
+            let error = match found {
+                Some(summary) => {
+                    format!(
+                        "Scrobble tx reverted: txHash={tx_hash} {}",
+                        summary.describe()
+                    )
+                }
+                None => format!("Scrobble tx was not included: txHash={tx_hash}"),
+            };
+            for (row, _) in &rows {
+                let next_attempt_at = next_attempt_after_failure(row.attempts() + 1, now);
+                Q::record_failure(&db, row.id(), &error, next_attempt_at, now)?;
+            }
+            log::warn!(
+                "{} unconfirmed batch did not land: plays={} err={}",
+                Q::LOG_TAG,
+                rows.len(),
+                error
+            );
+            Ok(OutboxFlushStep::Failed {
+                error,
+                plays: rows.len(),
+            })
+        }
+        Err(err) => {
+            let check_at = now.saturating_add(RETRY_BASE_DELAY_SECS);
+            Q::mark_unconfirmed(&db, &pairs, &tx_hash, &err, check_at, now)?;
+            log::warn!(
+                "{} receipt check failed: txHash={} checkAt={} err={}",
+                Q::LOG_TAG,
+                tx_hash,
+                check_at,
+                err
+            );
+            Ok(OutboxFlushStep::Unconfirmed {
+                tx_hash,
+                plays: rows.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::sinks::ScrobbleSinkKind;
+    use super::*;
+
+    fn play(path: &str, played_at_sec: u64) -> ScrobblePlay {
+        ScrobblePlay {
+            track_path: path.to_string(),
+            cover_path: None,
+            artist: "Artist".to_string(),
+            title: format!("Title {path}"),
+            album: None,
+            mbid: None,
+            ip_id: None,
+            duration_sec: 200,
+            played_at_sec,
+        }
+    }
+
+    /// A node that takes every batch but never hands back a receipt for it.
+    struct ReceiptlessSink {
+        submitted: usize,
+        receipt: Result<Option<ReceiptSummary>, String>,
+        window_secs: i64,
+    }
+
+    impl ScrobbleSink for ReceiptlessSink {
+        fn kind(&self) -> ScrobbleSinkKind {
+            ScrobbleSinkKind::Tempo
+        }
+
+        fn max_batch(&self) -> usize {
+            TempoSink::max_batch()
+        }
+
+        fn check_ready(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+            Ok(self.submit_batch(plays)?)
+        }
+    }
+
+    impl TempoBatchSink for ReceiptlessSink {
+        fn submit_batch(
+            &mut self,
+            plays: &[SubmitScrobbleInput],
+        ) -> Result<SinkReceipt, ScrobbleSubmitError> {
+            self.submitted += 1;
+            Err(ScrobbleSubmitError::Unconfirmed {
+                tx_hash: format!("0xtx{}", self.submitted),
+                track_ids: (0..plays.len()).map(|i| format!("0xtrack{i}")).collect(),
+                reason: "receipt polling timed out".to_string(),
+            })
+        }
+
+        fn receipt(&mut self, _tx_hash: &str) -> Result<Option<ReceiptSummary>, String> {
+            self.receipt.clone()
+        }
+
+        fn inclusion_window_secs(&self) -> i64 {
+            self.window_secs
+        }
+    }
+
+    #[test]
+    fn accepted_batch_without_receipt_is_checked_not_resent() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-unconfirmed-test-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = Mutex::new(MusicDb::open(&dir).expect("open music db"));
+        let wallet = "0xabc0000000000000000000000000000000000002";
+        let mut sink = ReceiptlessSink {
+            submitted: 0,
+            receipt: Ok(None),
+            window_secs: 3_600,
+        };
+        {
+            let db = db.lock().unwrap();
+            db.enqueue_scrobble(Some(wallet), &play("a.mp3", 10), 100)
+                .unwrap();
+            db.enqueue_scrobble(Some(wallet), &play("b.mp3", 20), 100)
+                .unwrap();
+        }
+
+        let step = flush_batch_to::<PlayOutbox>(&mut sink, &db, wallet).unwrap();
+        assert!(matches!(
+            step,
+            OutboxFlushStep::Unconfirmed { ref tx_hash, plays: 2 } if tx_hash == "0xtx1"
+        ));
+        assert_eq!(
+            db.lock()
+                .unwrap()
+                .scrobble_outbox_counts(Some(wallet))
+                .unwrap()
+                .pending,
+            2,
+            "parked plays still count as pending"
+        );
+
+        let step = flush_batch_to::<PlayOutbox>(&mut sink, &db, wallet).unwrap();
+        assert!(matches!(step, OutboxFlushStep::Idle));
+        assert_eq!(sink.submitted, 1, "nothing is resent inside the window");
+
+        // The window has passed and the node still has no receipt: the tx never landed.
+        let end_window = |db: &Mutex<MusicDb>| {
+            let db = db.lock().unwrap();
+            let parked = db
+                .due_unconfirmed_scrobbles(wallet, i64::MAX)
+                .unwrap()
+                .unwrap();
+            let rows: Vec<(i64, String)> = parked
+                .rows
+                .iter()
+                .map(|(row, track_id)| (row.id, track_id.clone()))
+                .collect();
+            db.mark_scrobbles_unconfirmed(&rows, &parked.tx_hash, "timed out", 0, 0)
+                .unwrap();
+        };
+        end_window(&db);
+        let step = flush_batch_to::<PlayOutbox>(&mut sink, &db, wallet).unwrap();
+        assert!(matches!(step, OutboxFlushStep::Failed { plays: 2, .. }));
+        assert_eq!(sink.submitted, 1);
+        let retry = db
+            .lock()
+            .unwrap()
+            .due_scrobbles(wallet, i64::MAX, TempoSink::max_batch())
+            .unwrap();
+        assert_eq!(retry.len(), 2);
+        assert!(retry.iter().all(|row| row.attempts == 1));
+
+        // A fresh play is parked the same way, and this time the receipt shows it was mined.
+        db.lock()
+            .unwrap()
+            .enqueue_scrobble(Some(wallet), &play("c.mp3", 30), 100)
+            .unwrap();
+        let step = flush_batch_to::<PlayOutbox>(&mut sink, &db, wallet).unwrap();
+        assert!(matches!(step, OutboxFlushStep::Unconfirmed { .. }));
+        assert_eq!(sink.submitted, 2);
+        end_window(&db);
+        sink.receipt = Ok(Some(ReceiptSummary {
+            status: Some("0x1".to_string()),
+            block_number: Some("0x10".to_string()),
+            gas_used: None,
+        }));
+        let step = flush_batch_to::<PlayOutbox>(&mut sink, &db, wallet).unwrap();
+        match step {
+            OutboxFlushStep::Confirmed { tx_hash, plays } => {
+                assert_eq!(tx_hash, "0xtx2");
+                assert_eq!(plays.len(), 1);
+                assert_eq!(plays[0].1.track_path, "c.mp3");
+            }
+            _ => panic!("mined batch should confirm"),
+        }
+        assert_eq!(sink.submitted, 2, "a mined batch is never resent");
+        let counts = db
+            .lock()
+            .unwrap()
+            .scrobble_outbox_counts(Some(wallet))
+            .unwrap();
+        assert_eq!((counts.pending, counts.confirmed), (2, 1));
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn backoff_grows_then_gives_up() {
+        assert_eq!(next_attempt_after_failure(1, 1_000), Some(1_030));
+        assert_eq!(next_attempt_after_failure(3, 1_000), Some(1_120));
+        assert_eq!(
+            next_attempt_after_failure(MAX_SCROBBLE_ATTEMPTS - 1, 0),
+            Some(30 * 64)
+        );
+        assert_eq!(next_attempt_after_failure(MAX_SCROBBLE_ATTEMPTS, 0), None);
+    }
+
+    #[test]
+    fn outbox_persists_coalesces_and_isolates_retries() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-outbox-test-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).expect("open music db");
+        let wallet = "0xAbC0000000000000000000000000000000000001";
+
+        assert!(db.enqueue_scrobble(None, &play("a.mp3", 10), 100).unwrap());
+        assert!(!db.enqueue_scrobble(None, &play("a.mp3", 10), 100).unwrap());
+        assert!(db
+            .enqueue_scrobble(Some(wallet), &play("b.mp3", 20), 100)
+            .unwrap());
+        assert!(db
+            .enqueue_scrobble(Some("0xother"), &play("c.mp3", 30), 100)
+            .unwrap());
+
+        let limit = TempoSink::max_batch();
+        let due = db.due_scrobbles(wallet, 100, limit).unwrap();
+        assert_eq!(
+            due.len(),
+            1,
+            "signed-out and other wallets' plays are not flushed"
+        );
+        assert_eq!(db.scrobble_outbox_counts(None).unwrap().pending, 1);
+        assert_eq!(db.claim_signed_out_scrobbles(wallet, 100).unwrap(), 1);
+        assert_eq!(db.claim_signed_out_scrobbles("0xother", 100).unwrap(), 0);
+
+        let due = db.due_scrobbles(wallet, 100, limit).unwrap();
+        let batch = select_batch(due, limit);
+        assert_eq!(
+            batch.len(),
+            2,
+            "the claimed signed-out play joins the batch"
+        );
+
+        db.record_scrobble_failure(
+            batch[0].id,
+            "rpc down",
+            next_attempt_after_failure(1, 100),
+            100,
+        )
+        .unwrap();
+        let due = db.due_scrobbles(wallet, 200, limit).unwrap();
+        let retry = select_batch(due, limit);
+        assert_eq!(retry.len(), 1, "a previously failed play is sent alone");
+        assert_eq!(retry[0].play.track_path, "a.mp3");
+
+        db.mark_scrobbles_confirmed(&[(batch[1].id, "0xTRACK".to_string())], wallet, "0xtx", 200)
+            .unwrap();
+        db.record_scrobble_failure(retry[0].id, "reverted", None, 200)
+            .unwrap();
+        let counts = db.scrobble_outbox_counts(Some(wallet)).unwrap();
+        assert_eq!((counts.pending, counts.confirmed, counts.failed), (0, 1, 1));
+        let other = db.scrobble_outbox_counts(Some("0xother")).unwrap();
+        assert_eq!((other.pending, other.confirmed, other.failed), (1, 0, 0));
+
+        assert_eq!(db.retry_failed_scrobbles("0xother", 300).unwrap(), 0);
+        assert_eq!(db.retry_failed_scrobbles(wallet, 300).unwrap(), 1);
+        assert_eq!(db.next_scrobble_attempt_at(wallet).unwrap(), Some(300));
+        assert_eq!(db.next_scrobble_attempt_at("0xother").unwrap(), Some(100));
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
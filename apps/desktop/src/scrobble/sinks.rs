@@ -0,0 +1,507 @@
+//! Scrobble destinations. Every play lands in the outbox first; Tempo confirms rows in the
+//! outbox itself, while the other sinks replay the same rows with their own enable flag and
+//! per-play retry state in `scrobble_sink_deliveries`.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::auth::accounts::AccountRegistry;
+use crate::auth::PersistedAuth;
+use crate::music_db::MusicDb;
+use crate::tempo::ReceiptSummary;
+
+use super::outbox::{next_attempt_after_failure, select_batch, TempoBatchSink};
+use super::{now_epoch_sec, ScrobbleService, ScrobbleSubmitError, SubmitScrobbleInput};
+
+mod credentials;
+mod file_export;
+mod lastfm;
+mod listenbrainz;
+
+pub use credentials::{set_sink_secret, sink_secret, SinkSecret};
+use file_export::{JsonlExportSink, ScrobblerLogSink};
+use lastfm::LastFmSink;
+use listenbrainz::ListenBrainzSink;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrobbleSinkKind {
+    Tempo,
+    ListenBrainz,
+    LastFm,
+    Jsonl,
+    ScrobblerLog,
+}
+
+impl ScrobbleSinkKind {
+    pub const ALL: [Self; 5] = [
+        Self::Tempo,
+        Self::ListenBrainz,
+        Self::LastFm,
+        Self::Jsonl,
+        Self::ScrobblerLog,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tempo => "tempo",
+            Self::ListenBrainz => "listenbrainz",
+            Self::LastFm => "lastfm",
+            Self::Jsonl => "jsonl",
+            Self::ScrobblerLog => "scrobbler_log",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tempo => "Tempo",
+            Self::ListenBrainz => "ListenBrainz",
+            Self::LastFm => "Last.fm",
+            Self::Jsonl => "JSONL Export",
+            Self::ScrobblerLog => ".scrobbler.log Export",
+        }
+    }
+
+    /// Only Tempo is on until the user opts in to the others.
+    fn enabled_by_default(self) -> bool {
+        self == Self::Tempo
+    }
+
+    /// Sinks are switched on per account, like the outbox plays they replay.
+    fn enabled_setting_key(self, user_address: &str) -> String {
+        format!(
+            "scrobble_sink_{}_enabled.{}",
+            self.as_str(),
+            user_address.trim().to_ascii_lowercase()
+        )
+    }
+
+    fn enabled_at_setting_key(self, user_address: &str) -> String {
+        format!(
+            "scrobble_sink_{}_enabled_at.{}",
+            self.as_str(),
+            user_address.trim().to_ascii_lowercase()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SinkReceipt {
+    /// Transaction hash, listen id or file path, when the sink has one.
+    pub reference: Option<String>,
+    /// On-chain track id per play, in input order (Tempo only).
+    pub track_ids: Vec<String>,
+}
+
+pub trait ScrobbleSink: Send {
+    fn kind(&self) -> ScrobbleSinkKind;
+
+    /// Largest number of plays accepted by one `submit` call.
+    fn max_batch(&self) -> usize;
+
+    /// Local check (credentials present, session usable) done before any play is claimed;
+    /// an error here holds plays without spending retry attempts.
+    fn check_ready(&self) -> Result<(), String>;
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String>;
+}
+
+/// Tempo `ScrobbleV4` via the signed-in wallet's session key.
+pub struct TempoSink<'a> {
+    service: &'a mut ScrobbleService,
+    auth: &'a PersistedAuth,
+}
+
+impl<'a> TempoSink<'a> {
+    pub fn new(service: &'a mut ScrobbleService, auth: &'a PersistedAuth) -> Self {
+        Self { service, auth }
+    }
+
+    /// Plays registered per `ScrobbleV4` batch transaction.
+    pub const fn max_batch() -> usize {
+        super::tempo::MAX_BATCH_REGISTRATIONS
+    }
+}
+
+impl ScrobbleSink for TempoSink<'_> {
+    fn kind(&self) -> ScrobbleSinkKind {
+        ScrobbleSinkKind::Tempo
+    }
+
+    fn max_batch(&self) -> usize {
+        TempoSink::max_batch()
+    }
+
+    fn check_ready(&self) -> Result<(), String> {
+        self.service.check_session(self.auth)
+    }
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+        Ok(TempoBatchSink::submit_batch(self, plays)?)
+    }
+}
+
+impl TempoBatchSink for TempoSink<'_> {
+    fn submit_batch(
+        &mut self,
+        plays: &[SubmitScrobbleInput],
+    ) -> Result<SinkReceipt, ScrobbleSubmitError> {
+        let result = self.service.submit_batch(self.auth, plays)?;
+        log::info!(
+            "[Scrobble] tempo batch: sender={} plays={} registered={}",
+            result.sender,
+            plays.len(),
+            result.registered
+        );
+        Ok(SinkReceipt {
+            reference: Some(result.tx_hash),
+            track_ids: result.track_ids,
+        })
+    }
+
+    fn receipt(&mut self, tx_hash: &str) -> Result<Option<ReceiptSummary>, String> {
+        self.service.scrobble_receipt(self.auth, tx_hash)
+    }
+
+    fn inclusion_window_secs(&self) -> i64 {
+        crate::tempo::inclusion_window_secs() as i64
+    }
+}
+
+/// `Some(enabled_at)` when `kind` is switched on for `user_address`; external sinks only
+/// replay plays recorded after that moment so enabling one does not backfill the whole
+/// history.
+pub fn sink_enabled_since(db: &MusicDb, kind: ScrobbleSinkKind, user_address: &str) -> Option<i64> {
+    let enabled = db
+        .get_setting(&kind.enabled_setting_key(user_address))
+        .map(|raw| raw.trim() == "1")
+        .unwrap_or_else(|| kind.enabled_by_default());
+    if !enabled {
+        return None;
+    }
+    Some(
+        db.get_setting(&kind.enabled_at_setting_key(user_address))
+            .and_then(|raw| raw.trim().parse::<i64>().ok())
+            .unwrap_or(0),
+    )
+}
+
+pub fn set_sink_enabled(
+    db: &MusicDb,
+    kind: ScrobbleSinkKind,
+    user_address: &str,
+    enabled: bool,
+    now: i64,
+) -> Result<(), String> {
+    db.set_setting(
+        &kind.enabled_setting_key(user_address),
+        if enabled { "1" } else { "0" },
+    )?;
+    if enabled {
+        db.set_setting(&kind.enabled_at_setting_key(user_address), &now.to_string())?;
+    }
+    Ok(())
+}
+
+/// Build a non-Tempo sink for `user_address`. Tokens and keys come from the secret store,
+/// endpoints and export paths from settings; `HEAVEN_*` environment variables override both.
+pub fn build_external_sink(
+    db: &MusicDb,
+    kind: ScrobbleSinkKind,
+    user_address: &str,
+) -> Option<Box<dyn ScrobbleSink>> {
+    let read = |env_key: &str, setting_key: &str| setting_or_env(db, env_key, setting_key);
+
+    match kind {
+        ScrobbleSinkKind::Tempo => None,
+        ScrobbleSinkKind::ListenBrainz => Some(Box::new(ListenBrainzSink::new(
+            read("HEAVEN_LISTENBRAINZ_API_URL", "listenbrainz_api_url")
+                .unwrap_or_else(|| listenbrainz::DEFAULT_LISTENBRAINZ_API_URL.to_string()),
+            sink_secret(SinkSecret::ListenBrainzToken, user_address),
+        ))),
+        ScrobbleSinkKind::LastFm => Some(Box::new(LastFmSink::new(
+            lastfm_api_url(db),
+            sink_secret(SinkSecret::LastFmApiKey, user_address),
+            sink_secret(SinkSecret::LastFmApiSecret, user_address),
+            sink_secret(SinkSecret::LastFmSessionKey, user_address),
+        ))),
+        ScrobbleSinkKind::Jsonl => Some(Box::new(JsonlExportSink::new(
+            read("HEAVEN_SCROBBLE_JSONL_PATH", "scrobble_export_jsonl_path")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| default_export_path("scrobbles.jsonl")),
+        ))),
+        ScrobbleSinkKind::ScrobblerLog => Some(Box::new(ScrobblerLogSink::new(
+            read(
+                "HEAVEN_SCROBBLER_LOG_PATH",
+                "scrobble_export_scrobbler_log_path",
+            )
+            .map(PathBuf::from)
+            .unwrap_or_else(|| default_export_path(".scrobbler.log")),
+        ))),
+    }
+}
+
+/// A Last.fm desktop-auth token waiting for the user to approve it in the browser.
+#[derive(Debug, Clone)]
+pub struct LastFmConnect {
+    pub token: String,
+    pub authorize_url: String,
+}
+
+/// First half of connecting Last.fm: fetch a token with the account's stored API key and
+/// secret. The user approves it at `authorize_url`, then calls [`finish_lastfm_connect`].
+pub fn begin_lastfm_connect(db: &MusicDb, user_address: &str) -> Result<LastFmConnect, String> {
+    let (api_key, api_secret) = lastfm_app_credentials(user_address)?;
+    let token = lastfm::request_auth_token(&lastfm_api_url(db), &api_key, &api_secret)?;
+    Ok(LastFmConnect {
+        authorize_url: lastfm::authorize_url(&api_key, &token),
+        token,
+    })
+}
+
+/// Trade the approved `token` for a session key and keep it for `user_address`.
+pub fn finish_lastfm_connect(db: &MusicDb, user_address: &str, token: &str) -> Result<(), String> {
+    let (api_key, api_secret) = lastfm_app_credentials(user_address)?;
+    let session_key = lastfm::fetch_session_key(&lastfm_api_url(db), &api_key, &api_secret, token)?;
+    set_sink_secret(
+        SinkSecret::LastFmSessionKey,
+        user_address,
+        Some(&session_key),
+    )
+}
+
+fn lastfm_app_credentials(user_address: &str) -> Result<(String, String), String> {
+    match (
+        sink_secret(SinkSecret::LastFmApiKey, user_address),
+        sink_secret(SinkSecret::LastFmApiSecret, user_address),
+    ) {
+        (Some(key), Some(secret)) => Ok((key, secret)),
+        _ => Err("Enter your Last.fm API key and secret first.".to_string()),
+    }
+}
+
+fn lastfm_api_url(db: &MusicDb) -> String {
+    setting_or_env(db, "HEAVEN_LASTFM_API_URL", "lastfm_api_url")
+        .unwrap_or_else(|| lastfm::DEFAULT_LASTFM_API_URL.to_string())
+}
+
+fn setting_or_env(db: &MusicDb, env_key: &str, setting_key: &str) -> Option<String> {
+    std::env::var(env_key)
+        .ok()
+        .or_else(|| db.get_setting(setting_key))
+        .map(|raw| raw.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Exports live in the active account's data dir so accounts never append to one file.
+fn default_export_path(name: &str) -> PathBuf {
+    AccountRegistry::shared().scoped_path(name)
+}
+
+#[derive(Debug)]
+pub enum SinkFlushStep {
+    Idle,
+    /// The sink is not configured or cannot authenticate; plays wait untouched.
+    Held(String),
+    Delivered(usize),
+    Failed {
+        error: String,
+        plays: usize,
+    },
+}
+
+/// Deliver the next due batch of `user_address`'s outbox plays to an external sink. Blocking.
+pub fn flush_sink_batch(
+    sink: &mut dyn ScrobbleSink,
+    db: &Mutex<MusicDb>,
+    user_address: &str,
+    enabled_since: i64,
+) -> Result<SinkFlushStep, String> {
+    if let Err(err) = sink.check_ready() {
+        return Ok(SinkFlushStep::Held(err));
+    }
+    let kind = sink.kind().as_str();
+    let limit = sink.max_batch().max(1);
+
+    let now = now_epoch_sec() as i64;
+    let due = db
+        .lock()
+        .map_err(|e| format!("scrobble outbox lock failed: {e}"))?
+        .due_sink_scrobbles(kind, user_address, enabled_since, now, limit)?;
+    let batch = select_batch(due, limit);
+    if batch.is_empty() {
+        return Ok(SinkFlushStep::Idle);
+    }
+
+    let inputs: Vec<SubmitScrobbleInput> = batch
+        .iter()
+        .map(|row| super::outbox::input_for_play(&row.play))
+        .collect();
+    let result = sink.submit(&inputs);
+
+    let now = now_epoch_sec() as i64;
+    let db = db
+        .lock()
+        .map_err(|e| format!("scrobble outbox lock failed: {e}"))?;
+    match result {
+        Ok(receipt) => {
+            let ids: Vec<i64> = batch.iter().map(|row| row.id).collect();
+            db.mark_sink_delivered(kind, &ids, receipt.reference.as_deref(), now)?;
+            log::info!("[Scrobble] sink={} delivered plays={}", kind, ids.len());
+            Ok(SinkFlushStep::Delivered(ids.len()))
+        }
+        Err(err) => {
+            for row in &batch {
+                let next_attempt_at = next_attempt_after_failure(row.attempts + 1, now);
+                db.record_sink_failure(kind, row.id, &err, next_attempt_at, now)?;
+            }
+            log::warn!(
+                "[Scrobble] sink={} batch failed: plays={} err={}",
+                kind,
+                batch.len(),
+                err
+            );
+            Ok(SinkFlushStep::Failed {
+                error: err,
+                plays: batch.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music_db::ScrobblePlay;
+
+    pub(super) fn input(title: &str, played_at_sec: u64) -> SubmitScrobbleInput {
+        SubmitScrobbleInput {
+            artist: "Artist".to_string(),
+            title: title.to_string(),
+            album: Some("Album".to_string()),
+            mbid: None,
+            ip_id: None,
+            duration_sec: 200,
+            played_at_sec,
+        }
+    }
+
+    struct FlakySink {
+        fail: bool,
+        submitted: Vec<String>,
+    }
+
+    impl ScrobbleSink for FlakySink {
+        fn kind(&self) -> ScrobbleSinkKind {
+            ScrobbleSinkKind::Jsonl
+        }
+
+        fn max_batch(&self) -> usize {
+            10
+        }
+
+        fn check_ready(&self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+            if self.fail {
+                return Err("offline".to_string());
+            }
+            self.submitted
+                .extend(plays.iter().map(|play| play.title.clone()));
+            Ok(SinkReceipt::default())
+        }
+    }
+
+    #[test]
+    fn sink_deliveries_are_independent_of_the_tempo_outbox() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-sinks-test-{}-{}",
+            std::process::id(),
+            now_epoch_sec()
+        ));
+        let db = MusicDb::open(&dir).expect("open music db");
+        let play = |path: &str, at: u64| ScrobblePlay {
+            track_path: path.to_string(),
+            cover_path: None,
+            artist: "Artist".to_string(),
+            title: path.to_string(),
+            album: None,
+            mbid: None,
+            ip_id: None,
+            duration_sec: 200,
+            played_at_sec: at,
+        };
+        let alice = "0xa11ce";
+        db.enqueue_scrobble(Some(alice), &play("old.mp3", 1), 50)
+            .unwrap();
+        db.enqueue_scrobble(Some(alice), &play("a.mp3", 10), 100)
+            .unwrap();
+        db.enqueue_scrobble(Some(alice), &play("b.mp3", 20), 100)
+            .unwrap();
+        // Another local account's play never goes to alice's sinks.
+        db.enqueue_scrobble(Some("0xb0b"), &play("bob.mp3", 15), 100)
+            .unwrap();
+        let db = Mutex::new(db);
+
+        let mut sink = FlakySink {
+            fail: true,
+            submitted: Vec::new(),
+        };
+        let step = flush_sink_batch(&mut sink, &db, alice, 100).unwrap();
+        assert!(matches!(step, SinkFlushStep::Failed { plays: 2, .. }));
+
+        let guard = db.lock().unwrap();
+        let counts = guard.sink_delivery_counts("jsonl", alice, 100).unwrap();
+        assert_eq!(counts.pending, 2, "plays before enabling are not replayed");
+        assert_eq!(
+            guard.last_sink_error("jsonl", alice).unwrap().as_deref(),
+            Some("offline")
+        );
+        assert_eq!(guard.last_sink_error("jsonl", "0xb0b").unwrap(), None);
+        assert_eq!(
+            guard.scrobble_outbox_counts(Some(alice)).unwrap().pending,
+            3,
+            "tempo outbox rows are untouched by sink failures"
+        );
+        assert!(
+            guard
+                .next_sink_attempt_at("jsonl", alice, 100)
+                .unwrap()
+                .unwrap()
+                > 100
+        );
+        drop(guard);
+
+        sink.fail = false;
+        assert!(
+            matches!(
+                flush_sink_batch(&mut sink, &db, alice, 100).unwrap(),
+                SinkFlushStep::Idle
+            ),
+            "failed plays wait out their per-sink backoff"
+        );
+        {
+            let guard = db.lock().unwrap();
+            let now = now_epoch_sec() as i64;
+            guard
+                .record_sink_failure("jsonl", 2, "offline", Some(0), now)
+                .unwrap();
+            guard
+                .record_sink_failure("jsonl", 3, "offline", Some(0), now)
+                .unwrap();
+        }
+        let step = flush_sink_batch(&mut sink, &db, alice, 100).unwrap();
+        assert!(matches!(step, SinkFlushStep::Delivered(1)));
+        assert_eq!(sink.submitted, vec!["a.mp3".to_string()]);
+        let bob = db
+            .lock()
+            .unwrap()
+            .sink_delivery_counts("jsonl", "0xB0B", 100)
+            .unwrap();
+        assert_eq!((bob.pending, bob.confirmed), (1, 0));
+
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
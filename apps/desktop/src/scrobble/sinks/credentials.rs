@@ -0,0 +1,133 @@
+//! Per-account sink credentials. Tokens and keys live in the secret store under
+//! `scrobble.<name>.<address>`; `HEAVEN_*` variables still override them for local testing.
+
+use crate::secret_store::SecretStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkSecret {
+    ListenBrainzToken,
+    LastFmApiKey,
+    LastFmApiSecret,
+    LastFmSessionKey,
+}
+
+impl SinkSecret {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ListenBrainzToken => "scrobble.listenbrainz_token",
+            Self::LastFmApiKey => "scrobble.lastfm_api_key",
+            Self::LastFmApiSecret => "scrobble.lastfm_api_secret",
+            Self::LastFmSessionKey => "scrobble.lastfm_session_key",
+        }
+    }
+
+    fn env_key(self) -> &'static str {
+        match self {
+            Self::ListenBrainzToken => "HEAVEN_LISTENBRAINZ_TOKEN",
+            Self::LastFmApiKey => "HEAVEN_LASTFM_API_KEY",
+            Self::LastFmApiSecret => "HEAVEN_LASTFM_API_SECRET",
+            Self::LastFmSessionKey => "HEAVEN_LASTFM_SESSION_KEY",
+        }
+    }
+
+    fn store_key(self, user_address: &str) -> String {
+        format!(
+            "{}.{}",
+            self.name(),
+            user_address.trim().to_ascii_lowercase()
+        )
+    }
+}
+
+/// The environment override, else `user_address`'s stored value.
+pub fn sink_secret(secret: SinkSecret, user_address: &str) -> Option<String> {
+    let env = std::env::var(secret.env_key())
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|value| !value.is_empty());
+    env.or_else(|| stored_sink_secret(SecretStore::shared(), secret, user_address))
+}
+
+/// Store `value` for `user_address`, or forget it when `value` is `None` or blank.
+pub fn set_sink_secret(
+    secret: SinkSecret,
+    user_address: &str,
+    value: Option<&str>,
+) -> Result<(), String> {
+    put_sink_secret(SecretStore::shared(), secret, user_address, value)
+}
+
+fn stored_sink_secret(
+    store: &SecretStore,
+    secret: SinkSecret,
+    user_address: &str,
+) -> Option<String> {
+    let key = secret.store_key(user_address);
+    store
+        .get(&key)
+        .unwrap_or_else(|e| {
+            log::warn!("[Scrobble] Failed to read {key} from secret store: {e}");
+            None
+        })
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn put_sink_secret(
+    store: &SecretStore,
+    secret: SinkSecret,
+    user_address: &str,
+    value: Option<&str>,
+) -> Result<(), String> {
+    let value = value.map(str::trim).filter(|value| !value.is_empty());
+    store.put_optional(&secret.store_key(user_address), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret_store::mock::MockKeyring;
+
+    #[test]
+    fn secrets_are_kept_per_account() {
+        let keyring = MockKeyring::default();
+        let store = SecretStore::with_backend(Box::new(keyring.clone()));
+
+        put_sink_secret(
+            &store,
+            SinkSecret::ListenBrainzToken,
+            "0xA11CE",
+            Some(" alice-token "),
+        )
+        .unwrap();
+        put_sink_secret(
+            &store,
+            SinkSecret::ListenBrainzToken,
+            "0xb0b",
+            Some("bob-token"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            keyring.value("scrobble.listenbrainz_token.0xa11ce"),
+            Some("alice-token".to_string())
+        );
+        assert_eq!(
+            stored_sink_secret(&store, SinkSecret::ListenBrainzToken, "0xa11ce"),
+            Some("alice-token".to_string())
+        );
+        assert_eq!(
+            stored_sink_secret(&store, SinkSecret::LastFmSessionKey, "0xa11ce"),
+            None
+        );
+
+        put_sink_secret(&store, SinkSecret::ListenBrainzToken, "0xa11ce", Some("  ")).unwrap();
+        assert_eq!(
+            stored_sink_secret(&store, SinkSecret::ListenBrainzToken, "0xa11ce"),
+            None
+        );
+        assert_eq!(
+            stored_sink_secret(&store, SinkSecret::ListenBrainzToken, "0xb0b"),
+            Some("bob-token".to_string())
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Offline exporters: append-only JSONL and the Audioscrobbler portable `.scrobbler.log`.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use super::{ScrobbleSink, ScrobbleSinkKind, SinkReceipt};
+use crate::scrobble::SubmitScrobbleInput;
+
+const FILE_EXPORT_MAX_BATCH: usize = 200;
+
+pub struct JsonlExportSink {
+    path: PathBuf,
+}
+
+impl JsonlExportSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl ScrobbleSink for JsonlExportSink {
+    fn kind(&self) -> ScrobbleSinkKind {
+        ScrobbleSinkKind::Jsonl
+    }
+
+    fn max_batch(&self) -> usize {
+        FILE_EXPORT_MAX_BATCH
+    }
+
+    fn check_ready(&self) -> Result<(), String> {
+        ensure_parent_dir(&self.path)
+    }
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+        let mut out = String::new();
+        for play in plays {
+            let played_at = chrono::DateTime::from_timestamp(play.played_at_sec as i64, 0)
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default();
+            let line = json!({
+                "artist": play.artist,
+                "title": play.title,
+                "album": play.album,
+                "mbid": play.mbid,
+                "ip_id": play.ip_id,
+                "duration_sec": play.duration_sec,
+                "played_at_sec": play.played_at_sec,
+                "played_at": played_at,
+            });
+            out.push_str(&line.to_string());
+            out.push('\n');
+        }
+        append(&self.path, &out)?;
+        Ok(SinkReceipt {
+            reference: Some(self.path.display().to_string()),
+            track_ids: Vec::new(),
+        })
+    }
+}
+
+/// Audioscrobbler 1.1 log as written by portable players; importable by most scrobble tools.
+pub struct ScrobblerLogSink {
+    path: PathBuf,
+}
+
+impl ScrobblerLogSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl ScrobbleSink for ScrobblerLogSink {
+    fn kind(&self) -> ScrobbleSinkKind {
+        ScrobbleSinkKind::ScrobblerLog
+    }
+
+    fn max_batch(&self) -> usize {
+        FILE_EXPORT_MAX_BATCH
+    }
+
+    fn check_ready(&self) -> Result<(), String> {
+        ensure_parent_dir(&self.path)
+    }
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+        let needs_header = std::fs::metadata(&self.path)
+            .map(|meta| meta.len() == 0)
+            .unwrap_or(true);
+        let mut out = String::new();
+        if needs_header {
+            out.push_str(&format!(
+                "#AUDIOSCROBBLER/1.1\n#TZ/UTC\n#CLIENT/Heaven {}\n",
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        for play in plays {
+            // artist, album, title, track number, duration, rating (L = listened), timestamp, mbid
+            let fields = [
+                log_field(&play.artist),
+                log_field(play.album.as_deref().unwrap_or_default()),
+                log_field(&play.title),
+                String::new(),
+                play.duration_sec.to_string(),
+                "L".to_string(),
+                play.played_at_sec.to_string(),
+                log_field(play.mbid.as_deref().unwrap_or_default()),
+            ];
+            out.push_str(&fields.join("\t"));
+            out.push('\n');
+        }
+        append(&self.path, &out)?;
+        Ok(SinkReceipt {
+            reference: Some(self.path.display().to_string()),
+            track_ids: Vec::new(),
+        })
+    }
+}
+
+/// Tabs and newlines are the format's separators.
+fn log_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ").trim().to_string()
+}
+
+fn ensure_parent_dir(path: &Path) -> Result<(), String> {
+    match path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        Some(parent) => std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create export dir {}: {e}", parent.display())),
+        None => Ok(()),
+    }
+}
+
+fn append(path: &Path, contents: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Cannot open {}: {e}", path.display()))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.flush())
+        .map_err(|e| format!("Cannot write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::input;
+    use super::*;
+
+    #[test]
+    fn exporters_append_and_write_the_log_header_once() {
+        let dir = std::env::temp_dir().join(format!(
+            "heaven-scrobble-export-test-{}-{}",
+            std::process::id(),
+            crate::scrobble::now_epoch_sec()
+        ));
+        let mut log = ScrobblerLogSink::new(dir.join(".scrobbler.log"));
+        let mut jsonl = JsonlExportSink::new(dir.join("scrobbles.jsonl"));
+        log.check_ready().expect("create export dir");
+
+        let mut tabbed = input("Tab\tTitle", 1_700_000_000);
+        tabbed.mbid = Some("mbid-1".to_string());
+        log.submit(&[tabbed.clone()]).unwrap();
+        log.submit(&[input("Second", 1_700_000_300)]).unwrap();
+        jsonl
+            .submit(&[tabbed, input("Second", 1_700_000_300)])
+            .unwrap();
+
+        let written = std::fs::read_to_string(dir.join(".scrobbler.log")).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "#AUDIOSCROBBLER/1.1");
+        assert_eq!(
+            lines[3],
+            "Artist\tAlbum\tTab Title\t\t200\tL\t1700000000\tmbid-1"
+        );
+
+        let exported = std::fs::read_to_string(dir.join("scrobbles.jsonl")).unwrap();
+        let rows: Vec<serde_json::Value> = exported
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["played_at"], "2023-11-14T22:18:20+00:00");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
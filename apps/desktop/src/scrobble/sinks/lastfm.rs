@@ -0,0 +1,363 @@
+//! Last.fm `track.scrobble` with an API-signed request and a stored session key.
+
+use std::time::Duration;
+
+use md5::{Digest, Md5};
+use serde_json::Value;
+
+use super::{ScrobbleSink, ScrobbleSinkKind, SinkReceipt};
+use crate::scrobble::SubmitScrobbleInput;
+
+pub(super) const DEFAULT_LASTFM_API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+const LASTFM_AUTHORIZE_URL: &str = "https://www.last.fm/api/auth/";
+/// `auth.getSession` before the user approved the token.
+const LASTFM_TOKEN_NOT_AUTHORIZED: i64 = 14;
+/// `track.scrobble` takes at most 50 plays per call.
+const LASTFM_MAX_BATCH: usize = 50;
+const LASTFM_TIMEOUT_SECS: u64 = 20;
+
+pub struct LastFmSink {
+    api_url: String,
+    api_key: Option<String>,
+    api_secret: Option<String>,
+    session_key: Option<String>,
+}
+
+impl LastFmSink {
+    pub fn new(
+        api_url: impl Into<String>,
+        api_key: Option<String>,
+        api_secret: Option<String>,
+        session_key: Option<String>,
+    ) -> Self {
+        Self {
+            api_url: api_url.into(),
+            api_key,
+            api_secret,
+            session_key,
+        }
+    }
+
+    fn credentials(&self) -> Result<(&str, &str, &str), String> {
+        match (
+            self.api_key.as_deref(),
+            self.api_secret.as_deref(),
+            self.session_key.as_deref(),
+        ) {
+            (Some(key), Some(secret), Some(session)) => Ok((key, secret, session)),
+            (None, _, _) | (_, None, _) => Err(
+                "Last.fm API key and secret are not set; connect Last.fm in Settings.".to_string(),
+            ),
+            (_, _, None) => Err("Last.fm is not connected; connect it in Settings.".to_string()),
+        }
+    }
+}
+
+impl ScrobbleSink for LastFmSink {
+    fn kind(&self) -> ScrobbleSinkKind {
+        ScrobbleSinkKind::LastFm
+    }
+
+    fn max_batch(&self) -> usize {
+        LASTFM_MAX_BATCH
+    }
+
+    fn check_ready(&self) -> Result<(), String> {
+        self.credentials().map(|_| ())
+    }
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+        let (api_key, api_secret, session_key) = self.credentials()?;
+        let json = post_signed(
+            &self.api_url,
+            scrobble_params(plays, api_key, session_key),
+            api_secret,
+        )?;
+
+        let attr = json.get("scrobbles").and_then(|v| v.get("@attr")).cloned();
+        let count = |field: &str| {
+            attr.as_ref()
+                .and_then(|attr| attr.get(field))
+                .and_then(|value| value.as_u64().or_else(|| value.as_str()?.parse().ok()))
+                .unwrap_or(0)
+        };
+        let ignored = count("ignored");
+        if ignored > 0 {
+            // Ignored plays (too old, filtered artist) will never be accepted; retrying is pointless.
+            log::warn!(
+                "[Scrobble] Last.fm accepted={} ignored={}",
+                count("accepted"),
+                ignored
+            );
+        }
+        Ok(SinkReceipt::default())
+    }
+}
+
+/// Where the user approves a desktop-auth `token` for `api_key`.
+pub fn authorize_url(api_key: &str, token: &str) -> String {
+    format!(
+        "{LASTFM_AUTHORIZE_URL}?api_key={}&token={}",
+        urlencoding::encode(api_key),
+        urlencoding::encode(token)
+    )
+}
+
+/// `auth.getToken`: an unapproved token, valid for 60 minutes.
+pub fn request_auth_token(
+    api_url: &str,
+    api_key: &str,
+    api_secret: &str,
+) -> Result<String, String> {
+    let params = vec![
+        ("method".to_string(), "auth.getToken".to_string()),
+        ("api_key".to_string(), api_key.to_string()),
+    ];
+    let json = post_signed(api_url, params, api_secret)?;
+    json.get("token")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "Last.fm returned no auth token.".to_string())
+}
+
+/// `auth.getSession`: trade a token the user approved for a session key that does not expire.
+pub fn fetch_session_key(
+    api_url: &str,
+    api_key: &str,
+    api_secret: &str,
+    token: &str,
+) -> Result<String, String> {
+    let params = vec![
+        ("method".to_string(), "auth.getSession".to_string()),
+        ("api_key".to_string(), api_key.to_string()),
+        ("token".to_string(), token.to_string()),
+    ];
+    let json = post_signed(api_url, params, api_secret).map_err(|err| {
+        if err.starts_with(&format!("Last.fm error {LASTFM_TOKEN_NOT_AUTHORIZED}:")) {
+            "Approve Heaven on the Last.fm page first, then finish connecting.".to_string()
+        } else {
+            err
+        }
+    })?;
+    json.get("session")
+        .and_then(|session| session.get("key"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "Last.fm returned no session key.".to_string())
+}
+
+/// Sign `params`, POST them as a form and return the JSON body. Last.fm reports API errors as
+/// `{"error": code, "message": ...}`, sometimes with 200.
+fn post_signed(
+    api_url: &str,
+    mut params: Vec<(String, String)>,
+    api_secret: &str,
+) -> Result<Value, String> {
+    let signature = api_signature(&params, api_secret);
+    params.push(("api_sig".to_string(), signature));
+    params.push(("format".to_string(), "json".to_string()));
+
+    let request = ureq::post(api_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .config()
+        .timeout_global(Some(Duration::from_secs(LASTFM_TIMEOUT_SECS)))
+        .http_status_as_error(false)
+        .build();
+    let mut response = request
+        .send(form_body(&params).as_str())
+        .map_err(|e| format!("Last.fm request failed ({api_url}): {e}"))?;
+    let status = response.status().as_u16();
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .unwrap_or_else(|_| String::new());
+    let json = serde_json::from_str::<Value>(&body).ok();
+
+    if let Some(code) = json.as_ref().and_then(|v| v.get("error")?.as_i64()) {
+        let message = json
+            .as_ref()
+            .and_then(|v| v.get("message")?.as_str())
+            .unwrap_or("unknown error");
+        return Err(format!("Last.fm error {code}: {message}"));
+    }
+    if !(200..300).contains(&status) {
+        return Err(format!(
+            "Last.fm request failed ({status}): {}",
+            body.trim()
+        ));
+    }
+    json.ok_or_else(|| format!("Last.fm returned invalid JSON: {}", body.trim()))
+}
+
+fn scrobble_params(
+    plays: &[SubmitScrobbleInput],
+    api_key: &str,
+    session_key: &str,
+) -> Vec<(String, String)> {
+    let mut params = vec![
+        ("method".to_string(), "track.scrobble".to_string()),
+        ("api_key".to_string(), api_key.to_string()),
+        ("sk".to_string(), session_key.to_string()),
+    ];
+    for (idx, play) in plays.iter().enumerate() {
+        params.push((format!("artist[{idx}]"), play.artist.clone()));
+        params.push((format!("track[{idx}]"), play.title.clone()));
+        params.push((format!("timestamp[{idx}]"), play.played_at_sec.to_string()));
+        if let Some(album) = play.album.as_deref().filter(|v| !v.trim().is_empty()) {
+            params.push((format!("album[{idx}]"), album.to_string()));
+        }
+        if play.duration_sec > 0 {
+            params.push((format!("duration[{idx}]"), play.duration_sec.to_string()));
+        }
+        if let Some(mbid) = play.mbid.as_deref().filter(|v| !v.trim().is_empty()) {
+            params.push((format!("mbid[{idx}]"), mbid.to_string()));
+        }
+    }
+    params
+}
+
+/// `md5(k1 v1 k2 v2 ... secret)` over parameters sorted by name, excluding `format`/`callback`.
+fn api_signature(params: &[(String, String)], api_secret: &str) -> String {
+    let mut sorted: Vec<&(String, String)> = params
+        .iter()
+        .filter(|(key, _)| key != "format" && key != "callback")
+        .collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut hasher = Md5::new();
+    for (key, value) in sorted {
+        hasher.update(key.as_bytes());
+        hasher.update(value.as_bytes());
+    }
+    hasher.update(api_secret.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn form_body(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::input;
+    use super::*;
+    use crate::test_support::http_stub::HttpStub;
+
+    fn form_params(body: &str) -> Vec<(String, String)> {
+        body.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| {
+                (
+                    urlencoding::decode(key).unwrap().into_owned(),
+                    urlencoding::decode(value).unwrap().into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn signature_sorts_params_and_skips_format() {
+        let params = vec![
+            ("sk".to_string(), "S".to_string()),
+            ("api_key".to_string(), "K".to_string()),
+            ("format".to_string(), "json".to_string()),
+            ("method".to_string(), "track.scrobble".to_string()),
+        ];
+        let expected = hex::encode(Md5::digest(b"api_keyKmethodtrack.scrobbleskSsecret"));
+        assert_eq!(api_signature(&params, "secret"), expected);
+    }
+
+    #[test]
+    fn submits_signed_batch_to_mock_endpoint() {
+        let stub = HttpStub::fixed(200, r#"{"scrobbles":{"@attr":{"accepted":2,"ignored":0}}}"#);
+        let mut sink = LastFmSink::new(
+            format!("{}/2.0/", stub.url),
+            Some("key".to_string()),
+            Some("secret".to_string()),
+            Some("session".to_string()),
+        );
+        sink.submit(&[
+            input("One & Only", 1_700_000_000),
+            input("Two", 1_700_000_300),
+        ])
+        .expect("submit");
+
+        let request = stub.requests().remove(0);
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/2.0/")
+        );
+        let params = form_params(&request.body_text());
+        let get = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("track[0]"), Some("One & Only"));
+        assert_eq!(get("timestamp[1]"), Some("1700000300"));
+        let unsigned: Vec<(String, String)> = params
+            .iter()
+            .filter(|(k, _)| k != "api_sig")
+            .cloned()
+            .collect();
+        assert_eq!(
+            get("api_sig"),
+            Some(api_signature(&unsigned, "secret").as_str())
+        );
+    }
+
+    #[test]
+    fn api_errors_are_reported_even_with_http_200() {
+        let stub = HttpStub::fixed(200, r#"{"error":9,"message":"Invalid session key"}"#);
+        let mut sink = LastFmSink::new(
+            stub.url.clone(),
+            Some("key".to_string()),
+            Some("secret".to_string()),
+            Some("stale".to_string()),
+        );
+        let err = sink.submit(&[input("One", 1)]).unwrap_err();
+        assert_eq!(err, "Last.fm error 9: Invalid session key");
+    }
+
+    #[test]
+    fn session_key_comes_from_signed_get_session() {
+        let stub = HttpStub::fixed(
+            200,
+            r#"{"session":{"name":"alice","key":"session-key","subscriber":0}}"#,
+        );
+        let session_key = fetch_session_key(&stub.url, "key", "secret", "approved").unwrap();
+        assert_eq!(session_key, "session-key");
+
+        let params = form_params(&stub.requests().remove(0).body_text());
+        let get = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("method"), Some("auth.getSession"));
+        assert_eq!(get("token"), Some("approved"));
+        let expected = hex::encode(Md5::digest(
+            b"api_keykeymethodauth.getSessiontokenapprovedsecret",
+        ));
+        assert_eq!(get("api_sig"), Some(expected.as_str()));
+    }
+
+    #[test]
+    fn unapproved_token_asks_the_user_to_approve_it() {
+        let stub = HttpStub::fixed(200, r#"{"error":14,"message":"Unauthorized Token"}"#);
+        let err = fetch_session_key(&stub.url, "key", "secret", "pending").unwrap_err();
+        assert!(err.starts_with("Approve Heaven"), "{err}");
+    }
+}
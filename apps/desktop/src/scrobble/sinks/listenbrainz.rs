@@ -0,0 +1,164 @@
+//! ListenBrainz `submit-listens` with a user token.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use super::{ScrobbleSink, ScrobbleSinkKind, SinkReceipt};
+use crate::scrobble::SubmitScrobbleInput;
+
+pub(super) const DEFAULT_LISTENBRAINZ_API_URL: &str = "https://api.listenbrainz.org";
+/// ListenBrainz accepts up to 1000 listens per import; smaller batches keep retries cheap.
+const LISTENBRAINZ_MAX_BATCH: usize = 100;
+const LISTENBRAINZ_TIMEOUT_SECS: u64 = 20;
+
+pub struct ListenBrainzSink {
+    api_url: String,
+    token: Option<String>,
+}
+
+impl ListenBrainzSink {
+    pub fn new(api_url: impl Into<String>, token: Option<String>) -> Self {
+        Self {
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+}
+
+impl ScrobbleSink for ListenBrainzSink {
+    fn kind(&self) -> ScrobbleSinkKind {
+        ScrobbleSinkKind::ListenBrainz
+    }
+
+    fn max_batch(&self) -> usize {
+        LISTENBRAINZ_MAX_BATCH
+    }
+
+    fn check_ready(&self) -> Result<(), String> {
+        match self.token.as_deref() {
+            Some(_) => Ok(()),
+            None => Err("ListenBrainz user token is not set; add it in Settings.".to_string()),
+        }
+    }
+
+    fn submit(&mut self, plays: &[SubmitScrobbleInput]) -> Result<SinkReceipt, String> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or("ListenBrainz user token is not set.")?;
+        let endpoint = format!("{}/1/submit-listens", self.api_url);
+        let payload = listens_payload(plays);
+
+        let request = ureq::post(&endpoint)
+            .header("Authorization", format!("Token {token}").as_str())
+            .config()
+            .timeout_global(Some(Duration::from_secs(LISTENBRAINZ_TIMEOUT_SECS)))
+            .http_status_as_error(false)
+            .build();
+        let mut response = request
+            .send_json(&payload)
+            .map_err(|e| format!("ListenBrainz request failed ({endpoint}): {e}"))?;
+        let status = response.status().as_u16();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .unwrap_or_else(|_| String::new());
+
+        match status {
+            200..=299 => Ok(SinkReceipt::default()),
+            401 => Err("ListenBrainz rejected the user token (401).".to_string()),
+            429 => Err("ListenBrainz rate limit hit (429).".to_string()),
+            _ => {
+                let detail = serde_json::from_str::<Value>(&body)
+                    .ok()
+                    .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+                    .unwrap_or_else(|| body.trim().to_string());
+                Err(format!("ListenBrainz submit failed ({status}): {detail}"))
+            }
+        }
+    }
+}
+
+fn listens_payload(plays: &[SubmitScrobbleInput]) -> Value {
+    let listens: Vec<Value> = plays
+        .iter()
+        .map(|play| {
+            let mut additional_info = json!({
+                "submission_client": "Heaven",
+                "submission_client_version": env!("CARGO_PKG_VERSION"),
+            });
+            if play.duration_sec > 0 {
+                additional_info["duration_ms"] = json!(u64::from(play.duration_sec) * 1000);
+            }
+            if let Some(mbid) = play.mbid.as_deref().filter(|v| !v.trim().is_empty()) {
+                additional_info["recording_mbid"] = json!(mbid);
+            }
+
+            let mut track_metadata = json!({
+                "artist_name": play.artist,
+                "track_name": play.title,
+                "additional_info": additional_info,
+            });
+            if let Some(album) = play.album.as_deref().filter(|v| !v.trim().is_empty()) {
+                track_metadata["release_name"] = json!(album);
+            }
+            json!({
+                "listened_at": play.played_at_sec,
+                "track_metadata": track_metadata,
+            })
+        })
+        .collect();
+
+    json!({
+        "listen_type": if listens.len() == 1 { "single" } else { "import" },
+        "payload": listens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::input;
+    use super::*;
+    use crate::test_support::http_stub::HttpStub;
+
+    #[test]
+    fn submits_listens_with_token_auth() {
+        let stub = HttpStub::fixed(200, r#"{"status":"ok"}"#);
+        let mut sink =
+            ListenBrainzSink::new(format!("{}/", stub.url), Some("lb-token".to_string()));
+
+        sink.submit(&[input("One", 1_700_000_000), input("Two", 1_700_000_300)])
+            .expect("submit");
+
+        let request = stub.requests().remove(0);
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/1/submit-listens")
+        );
+        assert_eq!(request.header("authorization"), Some("Token lb-token"));
+        let body: Value = serde_json::from_slice(&request.body).expect("json body");
+        assert_eq!(body["listen_type"], "import");
+        assert_eq!(body["payload"][1]["listened_at"], 1_700_000_300);
+        assert_eq!(
+            body["payload"][0]["track_metadata"]["release_name"],
+            "Album"
+        );
+        assert_eq!(
+            body["payload"][0]["track_metadata"]["additional_info"]["duration_ms"],
+            200_000
+        );
+    }
+
+    #[test]
+    fn missing_or_rejected_token_is_an_error() {
+        assert!(ListenBrainzSink::new(DEFAULT_LISTENBRAINZ_API_URL, None)
+            .check_ready()
+            .is_err());
+
+        let stub = HttpStub::fixed(401, r#"{"code":401,"error":"bad"}"#);
+        let mut sink = ListenBrainzSink::new(stub.url.clone(), Some("stale".to_string()));
+        let err = sink.submit(&[input("One", 1)]).unwrap_err();
+        assert!(err.contains("401"), "{err}");
+    }
+}
@@ -1,34 +1,34 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::{Mutex, OnceLock};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 
-use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_primitives::{keccak256, Address, B256};
 use alloy_sol_types::{sol, SolCall, SolValue};
 use bundles_rs::ans104::{data_item::DataItem, tags::Tag};
 use bundles_rs::crypto::signer::SignatureType;
 use ethers::signers::{LocalWallet, Signer};
-use ethers::types::H256;
 use image::imageops::FilterType;
 use serde_json::json;
 
 use crate::shared::rpc::{read_json_or_text, rpc_json};
+use crate::tempo::{
+    ContractCall, TempoClient, TempoEndpoints, TempoSessionKey, TempoTxError, TrackedSend,
+    TxActivity, TxKind,
+};
 
-use super::{SubmitScrobbleInput, SubmitScrobbleResult, TempoScrobbleSession};
+use super::{
+    ScrobbleSubmitError, SubmitScrobbleBatchResult, SubmitScrobbleInput, TempoScrobbleSession,
+};
 
-const FEE_PAYER_SENDER_HINT_MARKER_HEX: &str = "feefeefeefee";
-const MIN_PRIORITY_FEE_PER_GAS: u64 = 1_000_000;
 const GAS_LIMIT_SCROBBLE_ONLY_MIN: u64 = 420_000;
 const GAS_LIMIT_REGISTER_AND_SCROBBLE_MIN: u64 = 1_500_000;
-const GAS_LIMIT_BUFFER: u64 = 250_000;
-const SCROBBLE_EXPIRY_WINDOW_SECS: u64 = 25;
-const MAX_EXPIRING_SUBMIT_ATTEMPTS: u32 = 3;
-const EXPIRING_RECEIPT_GRACE_SECS: u64 = 6;
-const FEE_BUMP_NUMERATOR: u64 = 12;
-const FEE_BUMP_DENOMINATOR: u64 = 10;
-const DEFAULT_RECEIPT_POLL_TIMEOUT_SECS: u64 = 45;
-const RECEIPT_POLL_INTERVAL_MS: u64 = 1_250;
+const GAS_LIMIT_PER_EXTRA_SCROBBLE: u64 = 30_000;
+const GAS_LIMIT_PER_EXTRA_REGISTRATION: u64 = 320_000;
+/// Mirrors `ScrobbleV4.MAX_TRACK_REG` / `MAX_SCROBBLES`.
+pub(super) const MAX_BATCH_REGISTRATIONS: usize = 50;
+const MAX_BATCH_SCROBBLES: usize = 200;
 const DEFAULT_ARWEAVE_TURBO_UPLOAD_URL: &str = "https://upload.ardrive.io";
 const DEFAULT_ARWEAVE_TURBO_TOKEN: &str = "ethereum";
 const MAX_ARWEAVE_COVER_BYTES: usize = 100 * 1024;
@@ -83,218 +83,142 @@ sol! {
     );
 }
 
-#[derive(Debug, Clone)]
-struct SuggestedFees {
-    max_priority_fee_per_gas: U256,
-    max_fee_per_gas: U256,
-}
-
-#[derive(Debug, Clone)]
-struct TempoUnsignedTx {
-    chain_id: U256,
-    max_priority_fee_per_gas: U256,
-    max_fee_per_gas: U256,
-    gas_limit: U256,
-    calls: Vec<TempoCall>,
-    nonce_key: U256,
-    nonce: U256,
-    valid_before: Option<U256>,
-    valid_after: Option<U256>,
-    key_authorization: Option<Vec<u8>>,
-}
-
-#[derive(Debug, Clone)]
-struct TempoCall {
-    to: Address,
-    value: U256,
-    input: Vec<u8>,
-}
-
-enum RlpValue {
-    Bytes(Vec<u8>),
-    Integer(U256),
-    List(Vec<RlpValue>),
-    Raw(Vec<u8>),
-}
-
-pub(super) fn submit_scrobble_tempo(
+pub(super) fn submit_scrobble_batch_tempo(
     session: &TempoScrobbleSession,
-    input: &SubmitScrobbleInput,
-) -> Result<SubmitScrobbleResult, String> {
-    let started_at = std::time::Instant::now();
-    let user_address = parse_address(&session.wallet_address, "wallet address")?;
-    let session_address = parse_address(&session.session_address, "session address")?;
+    inputs: &[SubmitScrobbleInput],
+) -> Result<SubmitScrobbleBatchResult, ScrobbleSubmitError> {
+    let started_at = Instant::now();
     let scrobble_v4 = parse_address(&session.scrobble_contract, "scrobble contract address")?;
+    if inputs.is_empty() {
+        return Err("Scrobble batch is empty.".to_string().into());
+    }
+    if inputs.len() > MAX_BATCH_SCROBBLES {
+        return Err(format!(
+            "Scrobble batch too large: {} plays (max {MAX_BATCH_SCROBBLES}).",
+            inputs.len()
+        )
+        .into());
+    }
 
     log::info!(
-        "[Scrobble] tempo submit start: user={} chainId={} rpc={} feePayer={} title='{}' artist='{}' playedAt={}",
+        "[Scrobble] tempo submit start: user={} chainId={} rpc={} feePayer={} plays={} first='{}' by '{}' playedAt={}",
         session.wallet_address,
         session.chain_id,
         session.rpc_url,
         session.fee_payer_url,
-        input.title,
-        input.artist,
-        input.played_at_sec
+        inputs.len(),
+        inputs[0].title,
+        inputs[0].artist,
+        inputs[0].played_at_sec
     );
 
-    let session_wallet = LocalWallet::from_str(&session.session_private_key)
-        .map_err(|e| format!("Invalid Tempo scrobble session private key: {e}"))?;
-    let expected_session_address = ethers::types::Address::from_slice(session_address.as_slice());
-    if session_wallet.address() != expected_session_address {
-        return Err(
-            "Tempo scrobble session private key does not match the callback session address."
-                .to_string(),
-        );
+    let client = tempo_client(session)?;
+    let user_address = client.account();
+
+    // Register each unknown track once; `registerAndScrobbleBatch` reverts on duplicates.
+    let mut track_ids = Vec::with_capacity(inputs.len());
+    let mut timestamps = Vec::with_capacity(inputs.len());
+    let mut checked: HashSet<B256> = HashSet::new();
+    let mut reg_kinds = Vec::new();
+    let mut reg_payloads = Vec::new();
+    let mut titles = Vec::new();
+    let mut artists = Vec::new();
+    let mut albums = Vec::new();
+    let mut durations = Vec::new();
+    for input in inputs {
+        let (kind, payload) = derive_track_kind_and_payload(input)?;
+        let track_id = compute_track_id(kind, payload);
+        if checked.insert(track_id) {
+            let already_registered = call_is_registered(&session.rpc_url, scrobble_v4, track_id)?;
+            log::info!(
+                "[Scrobble] track resolution: kind={} trackId={:#x} alreadyRegistered={}",
+                kind,
+                track_id,
+                already_registered
+            );
+            if !already_registered {
+                reg_kinds.push(kind);
+                reg_payloads.push(payload);
+                titles.push(input.title.clone());
+                artists.push(input.artist.clone());
+                albums.push(input.album.clone().unwrap_or_default());
+                durations.push(input.duration_sec);
+            }
+        }
+        track_ids.push(track_id);
+        timestamps.push(input.played_at_sec);
     }
+    if reg_kinds.len() > MAX_BATCH_REGISTRATIONS {
+        return Err(format!(
+            "Scrobble batch registers too many tracks: {} (max {MAX_BATCH_REGISTRATIONS}).",
+            reg_kinds.len()
+        )
+        .into());
+    }
+    let registered = reg_kinds.len();
+    let track_id_hexes: Vec<String> = track_ids.iter().map(|id| format!("{id:#x}")).collect();
+    let extra_scrobble_gas = GAS_LIMIT_PER_EXTRA_SCROBBLE * (inputs.len() as u64 - 1);
 
-    let (kind, payload) = derive_track_kind_and_payload(input)?;
-    let track_id = compute_track_id(kind, payload);
-    let track_id_hex = format!("{track_id:#x}");
-    let already_registered = call_is_registered(&session.rpc_url, scrobble_v4, track_id)?;
-    log::info!(
-        "[Scrobble] track resolution: kind={} trackId={:#x} alreadyRegistered={}",
-        kind,
-        track_id,
-        already_registered
-    );
-
-    let call_data = if already_registered {
+    let call_data = if registered == 0 {
         scrobbleBatchCall {
             user: user_address,
-            trackIds: vec![track_id],
-            timestamps: vec![input.played_at_sec],
+            trackIds: track_ids,
+            timestamps,
         }
         .abi_encode()
     } else {
         registerAndScrobbleBatchCall {
             user: user_address,
-            regKinds: vec![kind],
-            regPayloads: vec![payload],
-            titles: vec![input.title.clone()],
-            artists: vec![input.artist.clone()],
-            albums: vec![input.album.clone().unwrap_or_default()],
-            durations: vec![input.duration_sec],
-            trackIds: vec![track_id],
-            timestamps: vec![input.played_at_sec],
+            regKinds: reg_kinds,
+            regPayloads: reg_payloads,
+            titles,
+            artists,
+            albums,
+            durations,
+            trackIds: track_ids,
+            timestamps,
         }
         .abi_encode()
     };
 
-    let nonce_key = U256::MAX;
-    let nonce = U256::ZERO;
-    let mut fees = get_suggested_fees(&session.rpc_url)?;
-    let min_gas_limit = if already_registered {
-        U256::from(GAS_LIMIT_SCROBBLE_ONLY_MIN)
+    let min_gas_limit = if registered == 0 {
+        GAS_LIMIT_SCROBBLE_ONLY_MIN + extra_scrobble_gas
     } else {
-        U256::from(GAS_LIMIT_REGISTER_AND_SCROBBLE_MIN)
+        GAS_LIMIT_REGISTER_AND_SCROBBLE_MIN
+            + GAS_LIMIT_PER_EXTRA_REGISTRATION * (registered as u64 - 1)
+            + extra_scrobble_gas
     };
-    let gas_limit = estimate_gas_with_buffer(
-        &session.rpc_url,
-        user_address,
+    let sent = client.send(&ContractCall::new(
+        TxKind::Scrobble,
         scrobble_v4,
-        &call_data,
+        call_data,
         min_gas_limit,
-    );
-    for attempt in 1..=MAX_EXPIRING_SUBMIT_ATTEMPTS {
-        let valid_before_secs = current_unix_secs()?.saturating_add(SCROBBLE_EXPIRY_WINDOW_SECS);
-        let valid_before = U256::from(valid_before_secs);
-        log::info!(
-            "[Scrobble] tx params: attempt={}/{} nonceMode=expiring nonceKey={} nonce={} validBefore={} gasLimit={} maxFeePerGas={} maxPriorityFeePerGas={}",
-            attempt,
-            MAX_EXPIRING_SUBMIT_ATTEMPTS,
-            nonce_key,
-            nonce,
-            valid_before,
-            gas_limit,
-            fees.max_fee_per_gas,
-            fees.max_priority_fee_per_gas
-        );
-
-        let unsigned = TempoUnsignedTx {
-            chain_id: U256::from(session.chain_id),
-            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
-            max_fee_per_gas: fees.max_fee_per_gas,
-            gas_limit,
-            calls: vec![TempoCall {
-                to: scrobble_v4,
-                value: U256::ZERO,
-                input: call_data.clone(),
-            }],
-            nonce_key,
-            nonce,
-            valid_before: Some(valid_before),
-            valid_after: None,
-            // Session-key scrobbles should not attach key_authorization on each tx.
-            key_authorization: None,
-        };
-
-        let signed_tx = encode_signed_tx(&unsigned, &session_wallet, user_address)?;
-        let tx_with_hint = append_sender_hint(&signed_tx, user_address);
-        log::info!(
-            "[Scrobble] requesting fee payer signature: url={}",
-            session.fee_payer_url
-        );
-        let relay_signed_tx = sign_via_fee_payer(&session.fee_payer_url, &tx_with_hint)?;
-        log::info!(
-            "[Scrobble] fee payer signed tx; broadcasting: rpc={}",
-            session.rpc_url
-        );
-        let tx_hash = send_raw_transaction(&session.rpc_url, &relay_signed_tx)?;
-        log::info!(
-            "[Scrobble] broadcast accepted: txHash={} elapsedMs={}",
-            tx_hash,
-            started_at.elapsed().as_millis()
-        );
-
-        match await_expiring_tx_receipt(&session.rpc_url, &tx_hash, valid_before_secs)? {
-            ExpiringTxStatus::Confirmed(summary) => {
-                let status = summary.status.as_deref().unwrap_or("-");
-                let block_number = summary.block_number.as_deref().unwrap_or("-");
-                let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                log::info!(
-                    "[Scrobble] receipt confirmed: txHash={} status={} block={} gasUsed={} elapsedMs={}",
-                    tx_hash,
-                    status,
-                    block_number,
-                    gas_used,
-                    started_at.elapsed().as_millis()
-                );
-                return Ok(SubmitScrobbleResult {
-                    tx_hash,
-                    sender: user_address.to_string(),
-                    track_id: track_id_hex.clone(),
-                    already_registered,
-                });
-            }
-            ExpiringTxStatus::Reverted(summary) => {
-                let status = summary.status.as_deref().unwrap_or("-");
-                let block_number = summary.block_number.as_deref().unwrap_or("-");
-                let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                return Err(format!(
-                    "Scrobble tx reverted: status={status} block={block_number} gasUsed={gas_used}"
-                ));
-            }
-            ExpiringTxStatus::NotIncludedBeforeExpiry => {
-                if attempt >= MAX_EXPIRING_SUBMIT_ATTEMPTS {
-                    return Err(format!(
-                        "Scrobble tx not included before expiry after {} attempts (last txHash={tx_hash}).",
-                        MAX_EXPIRING_SUBMIT_ATTEMPTS
-                    ));
-                }
-                fees = bump_fees(&fees);
-                log::warn!(
-                    "[Scrobble] tx expired before inclusion: txHash={} attempt={}/{}; retrying with bumped fees maxFeePerGas={} maxPriorityFeePerGas={}",
-                    tx_hash,
-                    attempt,
-                    MAX_EXPIRING_SUBMIT_ATTEMPTS,
-                    fees.max_fee_per_gas,
-                    fees.max_priority_fee_per_gas
-                );
-            }
+        "Scrobble",
+    ));
+    let receipt = match sent {
+        Ok(receipt) => receipt,
+        Err(TempoTxError::ReceiptUnavailable { tx_hash, reason }) => {
+            return Err(ScrobbleSubmitError::Unconfirmed {
+                tx_hash,
+                track_ids: track_id_hexes,
+                reason,
+            });
         }
-    }
-
-    Err("Scrobble tx failed unexpectedly after retries.".to_string())
+        Err(err) => return Err(String::from(err).into()),
+    };
+    log::info!(
+        "[Scrobble] receipt confirmed: txHash={} {} attempts={} elapsedMs={}",
+        receipt.tx_hash,
+        receipt.summary.describe(),
+        receipt.attempts,
+        started_at.elapsed().as_millis()
+    );
+    Ok(SubmitScrobbleBatchResult {
+        tx_hash: receipt.tx_hash,
+        sender: user_address.to_string(),
+        track_ids: track_id_hexes,
+        registered,
+    })
 }
 
 pub(super) fn upload_cover_to_arweave(
@@ -513,104 +437,30 @@ pub(super) fn upload_lyrics_to_arweave(
 
 pub(super) fn submit_contract_call_tempo(
     session: &TempoScrobbleSession,
+    kind: TxKind,
     contract_address: &str,
     call_data: Vec<u8>,
     gas_limit_min: u64,
     op_label: &str,
 ) -> Result<String, String> {
-    let user_address = parse_address(&session.wallet_address, "wallet address")?;
-    let session_address = parse_address(&session.session_address, "session address")?;
     let contract = parse_address(contract_address, "contract address")?;
-    let session_wallet = LocalWallet::from_str(&session.session_private_key)
-        .map_err(|e| format!("Invalid Tempo session private key: {e}"))?;
-    let expected_session_address = ethers::types::Address::from_slice(session_address.as_slice());
-    if session_wallet.address() != expected_session_address {
-        return Err(
-            "Tempo session private key does not match the callback session address.".to_string(),
-        );
-    }
-
-    let nonce_key = U256::MAX;
-    let nonce = U256::ZERO;
-    let mut fees = get_suggested_fees(&session.rpc_url)?;
-    let gas_limit = estimate_gas_with_buffer(
-        &session.rpc_url,
-        user_address,
+    let client = tempo_client(session)?;
+    let receipt = client.send(&ContractCall::new(
+        kind,
         contract,
-        &call_data,
-        U256::from(gas_limit_min),
-    );
-
-    for attempt in 1..=MAX_EXPIRING_SUBMIT_ATTEMPTS {
-        let valid_before_secs = current_unix_secs()?.saturating_add(SCROBBLE_EXPIRY_WINDOW_SECS);
-        let valid_before = U256::from(valid_before_secs);
-        log::info!(
-            "[Scrobble] {} tx params: attempt={}/{} nonceMode=expiring nonceKey={} nonce={} validBefore={} gasLimit={} maxFeePerGas={} maxPriorityFeePerGas={}",
-            op_label,
-            attempt,
-            MAX_EXPIRING_SUBMIT_ATTEMPTS,
-            nonce_key,
-            nonce,
-            valid_before,
-            gas_limit,
-            fees.max_fee_per_gas,
-            fees.max_priority_fee_per_gas
-        );
-
-        let unsigned = TempoUnsignedTx {
-            chain_id: U256::from(session.chain_id),
-            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
-            max_fee_per_gas: fees.max_fee_per_gas,
-            gas_limit,
-            calls: vec![TempoCall {
-                to: contract,
-                value: U256::ZERO,
-                input: call_data.clone(),
-            }],
-            nonce_key,
-            nonce,
-            valid_before: Some(valid_before),
-            valid_after: None,
-            key_authorization: None,
-        };
-
-        let signed_tx = encode_signed_tx(&unsigned, &session_wallet, user_address)?;
-        let tx_with_hint = append_sender_hint(&signed_tx, user_address);
-        let relay_signed_tx = sign_via_fee_payer(&session.fee_payer_url, &tx_with_hint)?;
-        let tx_hash = send_raw_transaction(&session.rpc_url, &relay_signed_tx)?;
-
-        match await_expiring_tx_receipt(&session.rpc_url, &tx_hash, valid_before_secs)? {
-            ExpiringTxStatus::Confirmed(_) => return Ok(tx_hash),
-            ExpiringTxStatus::Reverted(summary) => {
-                let status = summary.status.as_deref().unwrap_or("-");
-                let block_number = summary.block_number.as_deref().unwrap_or("-");
-                let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                return Err(format!(
-                    "{op_label} tx reverted: status={status} block={block_number} gasUsed={gas_used}"
-                ));
-            }
-            ExpiringTxStatus::NotIncludedBeforeExpiry => {
-                if attempt >= MAX_EXPIRING_SUBMIT_ATTEMPTS {
-                    return Err(format!(
-                        "{op_label} tx not included before expiry after {} attempts (last txHash={tx_hash}).",
-                        MAX_EXPIRING_SUBMIT_ATTEMPTS
-                    ));
-                }
-                fees = bump_fees(&fees);
-                log::warn!(
-                    "[Scrobble] {} tx expired before inclusion: txHash={} attempt={}/{}; retrying with bumped fees maxFeePerGas={} maxPriorityFeePerGas={}",
-                    op_label,
-                    tx_hash,
-                    attempt,
-                    MAX_EXPIRING_SUBMIT_ATTEMPTS,
-                    fees.max_fee_per_gas,
-                    fees.max_priority_fee_per_gas
-                );
-            }
-        }
-    }
+        call_data,
+        gas_limit_min,
+        op_label,
+    ))?;
+    Ok(receipt.tx_hash)
+}
 
-    Err(format!("{op_label} tx failed unexpectedly after retries."))
+pub(super) fn retry_activity_tempo(
+    session: &TempoScrobbleSession,
+    activity: &TxActivity,
+) -> Result<TrackedSend, String> {
+    let call = ContractCall::from_activity(activity)?;
+    Ok(tempo_client(session)?.send_tracked(&call))
 }
 
 pub(super) fn ensure_track_cover_tempo(
@@ -619,8 +469,6 @@ pub(super) fn ensure_track_cover_tempo(
     cover_ref: &str,
 ) -> Result<String, String> {
     let started_at = Instant::now();
-    let user_address = parse_address(&session.wallet_address, "wallet address")?;
-    let session_address = parse_address(&session.session_address, "session address")?;
     let scrobble_v4 = parse_address(&session.scrobble_contract, "scrobble contract address")?;
     if !contract_supports_set_track_cover_for(&session.rpc_url, scrobble_v4)? {
         return Err(format!(
@@ -641,15 +489,8 @@ pub(super) fn ensure_track_cover_tempo(
         ));
     }
 
-    let session_wallet = LocalWallet::from_str(&session.session_private_key)
-        .map_err(|e| format!("Invalid Tempo scrobble session private key: {e}"))?;
-    let expected_session_address = ethers::types::Address::from_slice(session_address.as_slice());
-    if session_wallet.address() != expected_session_address {
-        return Err(
-            "Tempo scrobble session private key does not match the callback session address."
-                .to_string(),
-        );
-    }
+    let client = tempo_client(session)?;
+    let user_address = client.account();
 
     if let Some(existing_cover_ref) =
         call_get_track_cover_ref(&session.rpc_url, scrobble_v4, track_id)?
@@ -664,125 +505,56 @@ pub(super) fn ensure_track_cover_tempo(
     }
     .abi_encode();
 
-    let nonce_key = U256::MAX;
-    let nonce = U256::ZERO;
-    let mut fees = get_suggested_fees(&session.rpc_url)?;
-    let gas_limit = estimate_gas_with_buffer(
-        &session.rpc_url,
-        user_address,
+    let call = ContractCall::new(
+        TxKind::TrackCover,
         scrobble_v4,
-        &call_data,
-        U256::from(GAS_LIMIT_SET_TRACK_COVER_MIN),
+        call_data,
+        GAS_LIMIT_SET_TRACK_COVER_MIN,
+        "Track cover",
     );
-
-    for attempt in 1..=MAX_EXPIRING_SUBMIT_ATTEMPTS {
-        let valid_before_secs = current_unix_secs()?.saturating_add(SCROBBLE_EXPIRY_WINDOW_SECS);
-        let valid_before = U256::from(valid_before_secs);
-        log::info!(
-            "[Scrobble] cover sync tx params: attempt={}/{} trackId={:#x} nonceMode=expiring nonceKey={} nonce={} validBefore={} gasLimit={} maxFeePerGas={} maxPriorityFeePerGas={}",
-            attempt,
-            MAX_EXPIRING_SUBMIT_ATTEMPTS,
-            track_id,
-            nonce_key,
-            nonce,
-            valid_before,
-            gas_limit,
-            fees.max_fee_per_gas,
-            fees.max_priority_fee_per_gas
-        );
-
-        let unsigned = TempoUnsignedTx {
-            chain_id: U256::from(session.chain_id),
-            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
-            max_fee_per_gas: fees.max_fee_per_gas,
-            gas_limit,
-            calls: vec![TempoCall {
-                to: scrobble_v4,
-                value: U256::ZERO,
-                input: call_data.clone(),
-            }],
-            nonce_key,
-            nonce,
-            valid_before: Some(valid_before),
-            valid_after: None,
-            key_authorization: None,
-        };
-
-        let signed_tx = encode_signed_tx(&unsigned, &session_wallet, user_address)?;
-        let tx_with_hint = append_sender_hint(&signed_tx, user_address);
-        let relay_signed_tx = sign_via_fee_payer(&session.fee_payer_url, &tx_with_hint)?;
-        let tx_hash = send_raw_transaction(&session.rpc_url, &relay_signed_tx)?;
-
-        match await_expiring_tx_receipt(&session.rpc_url, &tx_hash, valid_before_secs)? {
-            ExpiringTxStatus::Confirmed(_) => {
-                match call_get_track_cover_ref(&session.rpc_url, scrobble_v4, track_id) {
-                    Ok(Some(existing_cover_ref)) => {
-                        log::info!(
-                            "[Scrobble] cover sync confirmed: txHash={} trackId={:#x} coverRef={} elapsedMs={}",
-                            tx_hash,
-                            track_id,
-                            existing_cover_ref,
-                            started_at.elapsed().as_millis()
-                        );
-                        return Ok(existing_cover_ref);
-                    }
-                    Ok(None) => {
-                        log::info!(
-                            "[Scrobble] cover sync confirmed: txHash={} trackId={:#x} coverRef={} elapsedMs={}",
-                            tx_hash,
-                            track_id,
-                            cover_ref,
-                            started_at.elapsed().as_millis()
-                        );
-                        return Ok(cover_ref.clone());
-                    }
-                    Err(err) => {
-                        log::warn!(
-                            "[Scrobble] cover sync post-confirmation getTrack failed: trackId={:#x} txHash={} err={}",
-                            track_id,
-                            tx_hash,
-                            err
-                        );
-                        return Ok(cover_ref.clone());
-                    }
-                }
+    match client.send(&call) {
+        Ok(receipt) => match call_get_track_cover_ref(&session.rpc_url, scrobble_v4, track_id) {
+            Ok(Some(existing_cover_ref)) => {
+                log::info!(
+                        "[Scrobble] cover sync confirmed: txHash={} trackId={:#x} coverRef={} elapsedMs={}",
+                        receipt.tx_hash,
+                        track_id,
+                        existing_cover_ref,
+                        started_at.elapsed().as_millis()
+                    );
+                return Ok(existing_cover_ref);
             }
-            ExpiringTxStatus::Reverted(summary) => {
-                if let Ok(Some(existing_cover_ref)) =
-                    call_get_track_cover_ref(&session.rpc_url, scrobble_v4, track_id)
-                {
-                    return Ok(existing_cover_ref);
-                }
-
-                let status = summary.status.as_deref().unwrap_or("-");
-                let block_number = summary.block_number.as_deref().unwrap_or("-");
-                let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                return Err(format!(
-                    "Track cover tx reverted: status={status} block={block_number} gasUsed={gas_used}"
-                ));
+            Ok(None) => {
+                log::info!(
+                        "[Scrobble] cover sync confirmed: txHash={} trackId={:#x} coverRef={} elapsedMs={}",
+                        receipt.tx_hash,
+                        track_id,
+                        cover_ref,
+                        started_at.elapsed().as_millis()
+                    );
+                return Ok(cover_ref.clone());
             }
-            ExpiringTxStatus::NotIncludedBeforeExpiry => {
-                if attempt >= MAX_EXPIRING_SUBMIT_ATTEMPTS {
-                    return Err(format!(
-                        "Track cover tx not included before expiry after {} attempts (last txHash={tx_hash}).",
-                        MAX_EXPIRING_SUBMIT_ATTEMPTS
-                    ));
-                }
-                fees = bump_fees(&fees);
+            Err(err) => {
                 log::warn!(
-                    "[Scrobble] cover sync tx expired before inclusion: txHash={} attempt={}/{} trackId={:#x}; retrying with bumped fees maxFeePerGas={} maxPriorityFeePerGas={}",
-                    tx_hash,
-                    attempt,
-                    MAX_EXPIRING_SUBMIT_ATTEMPTS,
-                    track_id,
-                    fees.max_fee_per_gas,
-                    fees.max_priority_fee_per_gas
-                );
+                        "[Scrobble] cover sync post-confirmation getTrack failed: trackId={:#x} txHash={} err={}",
+                        track_id,
+                        receipt.tx_hash,
+                        err
+                    );
+                return Ok(cover_ref.clone());
             }
+        },
+        Err(err @ TempoTxError::Reverted { .. }) => {
+            // A concurrent sync may have set the ref first; that revert is a success.
+            if let Ok(Some(existing_cover_ref)) =
+                call_get_track_cover_ref(&session.rpc_url, scrobble_v4, track_id)
+            {
+                return Ok(existing_cover_ref);
+            }
+            Err(err.into())
         }
+        Err(err) => Err(err.into()),
     }
-
-    Err("Track cover sync failed unexpectedly after retries.".to_string())
 }
 
 pub(super) fn read_track_cover_ref_tempo(
@@ -807,8 +579,6 @@ pub(super) fn ensure_track_lyrics_tempo(
     lyrics_ref: &str,
 ) -> Result<String, String> {
     let started_at = Instant::now();
-    let user_address = parse_address(&session.wallet_address, "wallet address")?;
-    let session_address = parse_address(&session.session_address, "session address")?;
     let scrobble_v4 = parse_address(&session.scrobble_contract, "scrobble contract address")?;
     if !contract_supports_set_track_lyrics_for(&session.rpc_url, scrobble_v4)? {
         return Err(format!(
@@ -830,15 +600,8 @@ pub(super) fn ensure_track_lyrics_tempo(
         ));
     }
 
-    let session_wallet = LocalWallet::from_str(&session.session_private_key)
-        .map_err(|e| format!("Invalid Tempo scrobble session private key: {e}"))?;
-    let expected_session_address = ethers::types::Address::from_slice(session_address.as_slice());
-    if session_wallet.address() != expected_session_address {
-        return Err(
-            "Tempo scrobble session private key does not match the callback session address."
-                .to_string(),
-        );
-    }
+    let client = tempo_client(session)?;
+    let user_address = client.account();
 
     if let Some(existing_lyrics_ref) =
         call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id)?
@@ -853,125 +616,56 @@ pub(super) fn ensure_track_lyrics_tempo(
     }
     .abi_encode();
 
-    let nonce_key = U256::MAX;
-    let nonce = U256::ZERO;
-    let mut fees = get_suggested_fees(&session.rpc_url)?;
-    let gas_limit = estimate_gas_with_buffer(
-        &session.rpc_url,
-        user_address,
+    let call = ContractCall::new(
+        TxKind::TrackLyrics,
         scrobble_v4,
-        &call_data,
-        U256::from(GAS_LIMIT_SET_TRACK_LYRICS_MIN),
+        call_data,
+        GAS_LIMIT_SET_TRACK_LYRICS_MIN,
+        "Track lyrics",
     );
-
-    for attempt in 1..=MAX_EXPIRING_SUBMIT_ATTEMPTS {
-        let valid_before_secs = current_unix_secs()?.saturating_add(SCROBBLE_EXPIRY_WINDOW_SECS);
-        let valid_before = U256::from(valid_before_secs);
-        log::info!(
-            "[Scrobble] lyrics sync tx params: attempt={}/{} trackId={:#x} nonceMode=expiring nonceKey={} nonce={} validBefore={} gasLimit={} maxFeePerGas={} maxPriorityFeePerGas={}",
-            attempt,
-            MAX_EXPIRING_SUBMIT_ATTEMPTS,
-            track_id,
-            nonce_key,
-            nonce,
-            valid_before,
-            gas_limit,
-            fees.max_fee_per_gas,
-            fees.max_priority_fee_per_gas
-        );
-
-        let unsigned = TempoUnsignedTx {
-            chain_id: U256::from(session.chain_id),
-            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
-            max_fee_per_gas: fees.max_fee_per_gas,
-            gas_limit,
-            calls: vec![TempoCall {
-                to: scrobble_v4,
-                value: U256::ZERO,
-                input: call_data.clone(),
-            }],
-            nonce_key,
-            nonce,
-            valid_before: Some(valid_before),
-            valid_after: None,
-            key_authorization: None,
-        };
-
-        let signed_tx = encode_signed_tx(&unsigned, &session_wallet, user_address)?;
-        let tx_with_hint = append_sender_hint(&signed_tx, user_address);
-        let relay_signed_tx = sign_via_fee_payer(&session.fee_payer_url, &tx_with_hint)?;
-        let tx_hash = send_raw_transaction(&session.rpc_url, &relay_signed_tx)?;
-
-        match await_expiring_tx_receipt(&session.rpc_url, &tx_hash, valid_before_secs)? {
-            ExpiringTxStatus::Confirmed(_) => {
-                match call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id) {
-                    Ok(Some(existing_lyrics_ref)) => {
-                        log::info!(
-                        "[Scrobble] lyrics sync confirmed: txHash={} trackId={:#x} lyricsRef={} elapsedMs={}",
-                        tx_hash,
-                        track_id,
-                        existing_lyrics_ref,
-                        started_at.elapsed().as_millis()
-                    );
-                        return Ok(existing_lyrics_ref);
-                    }
-                    Ok(None) => {
-                        log::info!(
-                        "[Scrobble] lyrics sync confirmed: txHash={} trackId={:#x} lyricsRef={} elapsedMs={}",
-                        tx_hash,
-                        track_id,
-                        lyrics_ref,
-                        started_at.elapsed().as_millis()
-                    );
-                        return Ok(lyrics_ref.clone());
-                    }
-                    Err(err) => {
-                        log::warn!(
-                        "[Scrobble] lyrics sync post-confirmation getTrackLyrics failed: trackId={:#x} txHash={} err={}",
-                        track_id,
-                        tx_hash,
-                        err
-                    );
-                        return Ok(lyrics_ref.clone());
-                    }
-                }
+    match client.send(&call) {
+        Ok(receipt) => match call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id) {
+            Ok(Some(existing_lyrics_ref)) => {
+                log::info!(
+                    "[Scrobble] lyrics sync confirmed: txHash={} trackId={:#x} lyricsRef={} elapsedMs={}",
+                    receipt.tx_hash,
+                    track_id,
+                    existing_lyrics_ref,
+                    started_at.elapsed().as_millis()
+                );
+                return Ok(existing_lyrics_ref);
             }
-            ExpiringTxStatus::Reverted(summary) => {
-                if let Ok(Some(existing_lyrics_ref)) =
-                    call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id)
-                {
-                    return Ok(existing_lyrics_ref);
-                }
-
-                let status = summary.status.as_deref().unwrap_or("-");
-                let block_number = summary.block_number.as_deref().unwrap_or("-");
-                let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                return Err(format!(
-                    "Track lyrics tx reverted: status={status} block={block_number} gasUsed={gas_used}"
-                ));
+            Ok(None) => {
+                log::info!(
+                    "[Scrobble] lyrics sync confirmed: txHash={} trackId={:#x} lyricsRef={} elapsedMs={}",
+                    receipt.tx_hash,
+                    track_id,
+                    lyrics_ref,
+                    started_at.elapsed().as_millis()
+                );
+                return Ok(lyrics_ref.clone());
             }
-            ExpiringTxStatus::NotIncludedBeforeExpiry => {
-                if attempt >= MAX_EXPIRING_SUBMIT_ATTEMPTS {
-                    return Err(format!(
-                        "Track lyrics tx not included before expiry after {} attempts (last txHash={tx_hash}).",
-                        MAX_EXPIRING_SUBMIT_ATTEMPTS
-                    ));
-                }
-                fees = bump_fees(&fees);
+            Err(err) => {
                 log::warn!(
-                    "[Scrobble] lyrics sync tx expired before inclusion: txHash={} attempt={}/{} trackId={:#x}; retrying with bumped fees maxFeePerGas={} maxPriorityFeePerGas={}",
-                    tx_hash,
-                    attempt,
-                    MAX_EXPIRING_SUBMIT_ATTEMPTS,
+                    "[Scrobble] lyrics sync post-confirmation getTrackLyrics failed: trackId={:#x} txHash={} err={}",
                     track_id,
-                    fees.max_fee_per_gas,
-                    fees.max_priority_fee_per_gas
+                    receipt.tx_hash,
+                    err
                 );
+                return Ok(lyrics_ref.clone());
             }
+        },
+        Err(err @ TempoTxError::Reverted { .. }) => {
+            // A concurrent sync may have set the ref first; that revert is a success.
+            if let Ok(Some(existing_lyrics_ref)) =
+                call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id)
+            {
+                return Ok(existing_lyrics_ref);
+            }
+            Err(err.into())
         }
+        Err(err) => Err(err.into()),
     }
-
-    Err("Track lyrics sync failed unexpectedly after retries.".to_string())
 }
 
 pub(super) fn read_track_lyrics_ref_tempo(
@@ -983,6 +677,21 @@ pub(super) fn read_track_lyrics_ref_tempo(
     call_get_track_lyrics_ref(&session.rpc_url, scrobble_v4, track_id)
 }
 
+pub(super) fn read_track_lyrics_ref_at(
+    rpc_url: &str,
+    scrobble_contract: &str,
+    track_id: &str,
+) -> Result<Option<String>, String> {
+    let scrobble_v4 = parse_address(scrobble_contract, "scrobble contract address")?;
+    let track_id = parse_track_id_for_lyrics(track_id)?;
+    call_get_track_lyrics_ref(rpc_url, scrobble_v4, track_id)
+}
+
+pub(super) fn derive_track_id_hex(input: &SubmitScrobbleInput) -> Result<String, String> {
+    let (kind, payload) = derive_track_kind_and_payload(input)?;
+    Ok(format!("{:#x}", compute_track_id(kind, payload)))
+}
+
 pub(super) fn supports_track_lyrics_sync_tempo(
     session: &TempoScrobbleSession,
 ) -> Result<bool, String> {
@@ -990,47 +699,21 @@ pub(super) fn supports_track_lyrics_sync_tempo(
     contract_supports_set_track_lyrics_for(&session.rpc_url, scrobble_v4)
 }
 
-fn get_suggested_fees(rpc_url: &str) -> Result<SuggestedFees, String> {
-    let gas_price_raw = rpc_string(rpc_url, "eth_gasPrice", json!([]))?;
-    let gas_price = parse_hex_u256(&gas_price_raw)?;
-    let priority_floor = U256::from(MIN_PRIORITY_FEE_PER_GAS);
-    let priority = std::cmp::max(gas_price / U256::from(5), priority_floor);
-    let buffered = gas_price * U256::from(4);
-    let min_required = gas_price + priority;
-    let max_fee = std::cmp::max(buffered, min_required);
-    Ok(SuggestedFees {
-        max_priority_fee_per_gas: priority,
-        max_fee_per_gas: max_fee,
-    })
-}
-
-fn estimate_gas_with_buffer(
-    rpc_url: &str,
-    from: Address,
-    to: Address,
-    data: &[u8],
-    minimum: U256,
-) -> U256 {
-    let params = json!([{
-        "from": from.to_string(),
-        "to": to.to_string(),
-        "data": format!("0x{}", hex::encode(data)),
-    }]);
-    let estimated = rpc_string(rpc_url, "eth_estimateGas", params)
-        .and_then(|raw| parse_hex_u256(&raw))
-        .map(|value| value + U256::from(GAS_LIMIT_BUFFER));
-
-    match estimated {
-        Ok(value) => value.max(minimum),
-        Err(err) => {
-            log::warn!(
-                "[Scrobble] eth_estimateGas failed; using minimum gas limit {}: {}",
-                minimum,
-                err
-            );
-            minimum
-        }
-    }
+fn tempo_client(session: &TempoScrobbleSession) -> Result<TempoClient, String> {
+    let key = TempoSessionKey::new(
+        &session.wallet_address,
+        &session.session_address,
+        &session.session_private_key,
+        Some(session.session_expires_at).filter(|expires_at| *expires_at > 0),
+    )?;
+    Ok(TempoClient::new(
+        TempoEndpoints {
+            rpc_url: session.rpc_url.clone(),
+            fee_payer_url: session.fee_payer_url.clone(),
+            chain_id: session.chain_id,
+        },
+        key,
+    ))
 }
 
 fn call_is_registered(rpc_url: &str, scrobble_v4: Address, track_id: B256) -> Result<bool, String> {
@@ -1337,161 +1020,6 @@ fn compute_track_id(kind: u8, payload: B256) -> B256 {
     keccak256(buf)
 }
 
-fn encode_signed_tx(
-    tx: &TempoUnsignedTx,
-    session_wallet: &LocalWallet,
-    user_address: Address,
-) -> Result<String, String> {
-    let hash = signature_hash(tx);
-    let signature = session_wallet
-        .sign_hash(H256::from_slice(hash.as_slice()))
-        .map_err(|e| format!("Failed to sign Tempo tx hash with session key: {e}"))?;
-    let inner_signature = signature.to_vec();
-    if inner_signature.len() != 65 {
-        return Err(format!(
-            "Unexpected session signature length: {}",
-            inner_signature.len()
-        ));
-    }
-
-    let mut keychain_signature = Vec::with_capacity(1 + 20 + 65);
-    keychain_signature.push(0x03);
-    keychain_signature.extend_from_slice(user_address.as_slice());
-    keychain_signature.extend_from_slice(&inner_signature);
-
-    let full_fields = build_tx_fields(tx, Some(keychain_signature));
-    let encoded = rlp_encode(&RlpValue::List(full_fields));
-    Ok(format!("0x76{}", hex::encode(encoded)))
-}
-
-fn signature_hash(tx: &TempoUnsignedTx) -> B256 {
-    let signing_fields = build_tx_fields(tx, None);
-    let mut payload = Vec::with_capacity(1 + 512);
-    payload.push(0x76);
-    payload.extend_from_slice(&rlp_encode(&RlpValue::List(signing_fields)));
-    keccak256(payload)
-}
-
-fn build_tx_fields(tx: &TempoUnsignedTx, sender_signature: Option<Vec<u8>>) -> Vec<RlpValue> {
-    let calls = tx
-        .calls
-        .iter()
-        .map(|call| {
-            RlpValue::List(vec![
-                RlpValue::Bytes(call.to.as_slice().to_vec()),
-                RlpValue::Integer(call.value),
-                RlpValue::Bytes(call.input.clone()),
-            ])
-        })
-        .collect::<Vec<_>>();
-
-    let valid_before = tx
-        .valid_before
-        .map(RlpValue::Integer)
-        .unwrap_or_else(|| RlpValue::Bytes(Vec::new()));
-    let valid_after = tx
-        .valid_after
-        .map(RlpValue::Integer)
-        .unwrap_or_else(|| RlpValue::Bytes(Vec::new()));
-
-    let mut fields = vec![
-        RlpValue::Integer(tx.chain_id),
-        RlpValue::Integer(tx.max_priority_fee_per_gas),
-        RlpValue::Integer(tx.max_fee_per_gas),
-        RlpValue::Integer(tx.gas_limit),
-        RlpValue::List(calls),
-        RlpValue::List(Vec::new()),
-        RlpValue::Integer(tx.nonce_key),
-        RlpValue::Integer(tx.nonce),
-        valid_before,
-        valid_after,
-        RlpValue::Bytes(Vec::new()),
-        RlpValue::Bytes(vec![0x00]),
-        RlpValue::List(Vec::new()),
-    ];
-
-    if let Some(key_authorization) = tx.key_authorization.as_ref() {
-        fields.push(RlpValue::Raw(key_authorization.clone()));
-    }
-
-    if let Some(sender_signature) = sender_signature {
-        fields.push(RlpValue::Bytes(sender_signature));
-    }
-
-    fields
-}
-
-fn rlp_encode(value: &RlpValue) -> Vec<u8> {
-    match value {
-        RlpValue::Raw(encoded) => encoded.clone(),
-        RlpValue::Bytes(bytes) => rlp_encode_bytes(bytes),
-        RlpValue::Integer(number) => {
-            if number.is_zero() {
-                return vec![0x80];
-            }
-            rlp_encode_bytes(&number.to_be_bytes_trimmed_vec())
-        }
-        RlpValue::List(items) => {
-            let mut payload = Vec::new();
-            for item in items {
-                payload.extend_from_slice(&rlp_encode(item));
-            }
-            rlp_with_prefix(&payload, 0xc0)
-        }
-    }
-}
-
-fn rlp_encode_bytes(bytes: &[u8]) -> Vec<u8> {
-    if bytes.len() == 1 && bytes[0] < 0x80 {
-        return vec![bytes[0]];
-    }
-    rlp_with_prefix(bytes, 0x80)
-}
-
-fn rlp_with_prefix(payload: &[u8], short_offset: u8) -> Vec<u8> {
-    if payload.len() < 56 {
-        let mut out = Vec::with_capacity(1 + payload.len());
-        out.push(short_offset + payload.len() as u8);
-        out.extend_from_slice(payload);
-        return out;
-    }
-
-    let len_bytes = encode_len(payload.len());
-    let mut out = Vec::with_capacity(1 + len_bytes.len() + payload.len());
-    out.push(short_offset + 55 + len_bytes.len() as u8);
-    out.extend_from_slice(&len_bytes);
-    out.extend_from_slice(payload);
-    out
-}
-
-fn encode_len(mut len: usize) -> Vec<u8> {
-    let mut bytes = Vec::new();
-    while len > 0 {
-        bytes.push((len & 0xff) as u8);
-        len >>= 8;
-    }
-    bytes.reverse();
-    bytes
-}
-
-fn append_sender_hint(signed_tx_hex: &str, sender: Address) -> String {
-    let tx = strip_0x(signed_tx_hex);
-    let sender_hex = hex::encode(sender.as_slice());
-    format!("0x{}{}{}", tx, sender_hex, FEE_PAYER_SENDER_HINT_MARKER_HEX)
-}
-
-fn sign_via_fee_payer(fee_payer_url: &str, signed_tx_with_hint: &str) -> Result<String, String> {
-    rpc_string(
-        fee_payer_url,
-        "eth_signRawTransaction",
-        json!([signed_tx_with_hint]),
-    )
-}
-
-fn send_raw_transaction(rpc_url: &str, raw_tx: &str) -> Result<String, String> {
-    rpc_string(rpc_url, "eth_sendRawTransaction", json!([raw_tx]))
-}
-
 fn rpc_string(rpc_url: &str, method: &str, params: serde_json::Value) -> Result<String, String> {
     let payload = json!({
         "jsonrpc": "2.0",
@@ -1532,14 +1060,6 @@ fn parse_address(value: &str, label: &str) -> Result<Address, String> {
         .map_err(|e| format!("Invalid {label}: {e}"))
 }
 
-fn parse_hex_u256(value: &str) -> Result<U256, String> {
-    let clean = strip_0x(value);
-    if clean.is_empty() {
-        return Ok(U256::ZERO);
-    }
-    U256::from_str_radix(clean, 16).map_err(|e| format!("invalid hex quantity '{value}': {e}"))
-}
-
 fn parse_hex_bytes(value: &str) -> Result<Vec<u8>, String> {
     let clean = strip_0x(value);
     if clean.is_empty() {
@@ -1554,197 +1074,3 @@ fn strip_0x(value: &str) -> &str {
         .or_else(|| value.strip_prefix("0X"))
         .unwrap_or(value)
 }
-
-fn spawn_receipt_poll(rpc_url: String, tx_hash: String) {
-    let timeout_secs = receipt_poll_timeout_secs();
-    std::thread::spawn(move || {
-        let started_at = Instant::now();
-        let mut logged_retry_error = false;
-        loop {
-            match fetch_transaction_receipt(&rpc_url, &tx_hash) {
-                Ok(Some(summary)) => {
-                    let status = summary.status.as_deref().unwrap_or("-");
-                    let block_number = summary.block_number.as_deref().unwrap_or("-");
-                    let gas_used = summary.gas_used.as_deref().unwrap_or("-");
-                    if status.eq_ignore_ascii_case("0x0") {
-                        log::warn!(
-                            "[Scrobble] receipt confirmed: txHash={} status={} block={} gasUsed={} elapsedMs={} (reverted)",
-                            tx_hash,
-                            status,
-                            block_number,
-                            gas_used,
-                            started_at.elapsed().as_millis()
-                        );
-                    } else {
-                        log::info!(
-                            "[Scrobble] receipt confirmed: txHash={} status={} block={} gasUsed={} elapsedMs={}",
-                            tx_hash,
-                            status,
-                            block_number,
-                            gas_used,
-                            started_at.elapsed().as_millis()
-                        );
-                    }
-                    break;
-                }
-                Ok(None) => {
-                    if started_at.elapsed() >= Duration::from_secs(timeout_secs) {
-                        log::warn!(
-                            "[Scrobble] receipt poll timeout: txHash={} timeout={}s rpc={}",
-                            tx_hash,
-                            timeout_secs,
-                            rpc_url
-                        );
-                        break;
-                    }
-                }
-                Err(err) => {
-                    if !logged_retry_error {
-                        log::warn!(
-                            "[Scrobble] receipt poll transient error: txHash={} err={}",
-                            tx_hash,
-                            err
-                        );
-                        logged_retry_error = true;
-                    }
-                    if started_at.elapsed() >= Duration::from_secs(timeout_secs) {
-                        log::warn!(
-                            "[Scrobble] receipt poll failed: txHash={} timeout={}s rpc={} err={}",
-                            tx_hash,
-                            timeout_secs,
-                            rpc_url,
-                            err
-                        );
-                        break;
-                    }
-                }
-            }
-            std::thread::sleep(Duration::from_millis(RECEIPT_POLL_INTERVAL_MS));
-        }
-    });
-}
-
-#[derive(Debug, Clone)]
-struct ReceiptSummary {
-    status: Option<String>,
-    block_number: Option<String>,
-    gas_used: Option<String>,
-}
-
-enum ExpiringTxStatus {
-    Confirmed(ReceiptSummary),
-    Reverted(ReceiptSummary),
-    NotIncludedBeforeExpiry,
-}
-
-fn fetch_transaction_receipt(
-    rpc_url: &str,
-    tx_hash: &str,
-) -> Result<Option<ReceiptSummary>, String> {
-    let payload = json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "eth_getTransactionReceipt",
-        "params": [tx_hash],
-    });
-    let result = rpc_json(rpc_url, payload)?;
-    if result.is_null() {
-        return Ok(None);
-    }
-    let summary = ReceiptSummary {
-        status: result
-            .get("status")
-            .and_then(|v| v.as_str())
-            .map(str::to_string),
-        block_number: result
-            .get("blockNumber")
-            .and_then(|v| v.as_str())
-            .map(str::to_string),
-        gas_used: result
-            .get("gasUsed")
-            .and_then(|v| v.as_str())
-            .map(str::to_string),
-    };
-    Ok(Some(summary))
-}
-
-fn await_expiring_tx_receipt(
-    rpc_url: &str,
-    tx_hash: &str,
-    valid_before_secs: u64,
-) -> Result<ExpiringTxStatus, String> {
-    let timeout_secs = receipt_poll_timeout_secs();
-    let expiry_deadline = valid_before_secs.saturating_add(EXPIRING_RECEIPT_GRACE_SECS);
-    let started_at = Instant::now();
-    let mut logged_retry_error = false;
-
-    loop {
-        match fetch_transaction_receipt(rpc_url, tx_hash) {
-            Ok(Some(summary)) => {
-                if summary
-                    .status
-                    .as_deref()
-                    .is_some_and(|status| status.eq_ignore_ascii_case("0x0"))
-                {
-                    return Ok(ExpiringTxStatus::Reverted(summary));
-                }
-                return Ok(ExpiringTxStatus::Confirmed(summary));
-            }
-            Ok(None) => {
-                let now_secs = current_unix_secs()?;
-                if now_secs > expiry_deadline {
-                    return Ok(ExpiringTxStatus::NotIncludedBeforeExpiry);
-                }
-                if started_at.elapsed() >= Duration::from_secs(timeout_secs) {
-                    return Ok(ExpiringTxStatus::NotIncludedBeforeExpiry);
-                }
-            }
-            Err(err) => {
-                if !logged_retry_error {
-                    log::warn!(
-                        "[Scrobble] receipt poll transient error: txHash={} err={}",
-                        tx_hash,
-                        err
-                    );
-                    logged_retry_error = true;
-                }
-                if started_at.elapsed() >= Duration::from_secs(timeout_secs) {
-                    return Err(format!(
-                        "receipt poll failed: txHash={tx_hash} timeout={}s rpc={} err={}",
-                        timeout_secs, rpc_url, err
-                    ));
-                }
-            }
-        }
-        std::thread::sleep(Duration::from_millis(RECEIPT_POLL_INTERVAL_MS));
-    }
-}
-
-fn receipt_poll_timeout_secs() -> u64 {
-    std::env::var("HEAVEN_SCROBBLE_RECEIPT_TIMEOUT_SECS")
-        .ok()
-        .and_then(|raw| raw.trim().parse::<u64>().ok())
-        .filter(|secs| *secs > 0)
-        .unwrap_or(DEFAULT_RECEIPT_POLL_TIMEOUT_SECS)
-}
-
-fn bump_fees(current: &SuggestedFees) -> SuggestedFees {
-    let numerator = U256::from(FEE_BUMP_NUMERATOR);
-    let denominator = U256::from(FEE_BUMP_DENOMINATOR);
-    let bumped_priority = ((current.max_priority_fee_per_gas * numerator) / denominator)
-        .max(current.max_priority_fee_per_gas + U256::from(1_u8));
-    let bumped_max_fee = ((current.max_fee_per_gas * numerator) / denominator)
-        .max(current.max_fee_per_gas + U256::from(1_u8));
-    let max_fee = bumped_max_fee.max(bumped_priority + U256::from(1_u8));
-    SuggestedFees {
-        max_priority_fee_per_gas: bumped_priority,
-        max_fee_per_gas: max_fee,
-    }
-}
-
-fn current_unix_secs() -> Result<u64, String> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| format!("system clock before unix epoch: {e}"))?;
-    Ok(now.as_secs())
-}
@@ -0,0 +1,430 @@
+//! Unified storage for key material and tokens.
+//!
+//! Secrets live in the OS keyring (Secret Service, Keychain, Credential Manager). When no
+//! keyring is reachable on first use they fall back to a passphrase-encrypted file in the app
+//! data dir. The choice is recorded, so a keyring that is briefly down on a later start
+//! surfaces errors instead of sending new secrets to the file.
+//! Callers keep non-secret metadata in their own files and only put the secret values here.
+
+mod file;
+mod os_keyring;
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub use file::EncryptedFileBackend;
+pub use os_keyring::KeyringBackend;
+
+const SECRETS_FILE: &str = "secrets.enc.json";
+const PROBE_KEY: &str = "heaven.probe";
+
+/// The backend an install settled on, kept next to the secrets file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BackendChoice {
+    Keyring,
+    File,
+}
+
+/// Where secret values are kept. Keys are short dotted names such as `auth.access_token`.
+pub trait SecretBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, key: &str, value: &str) -> Result<(), String>;
+    /// Deleting a missing key is not an error.
+    fn delete(&self, key: &str) -> Result<(), String>;
+    /// Whether the secrets are only as safe as this machine's identity, which any local
+    /// process can read.
+    fn machine_bound(&self) -> bool {
+        false
+    }
+}
+
+pub struct SecretStore {
+    backend: Box<dyn SecretBackend>,
+    /// The other backend, only read from: secrets written while it was the one in use
+    /// stay readable after the switch.
+    fallback: Option<Box<dyn SecretBackend>>,
+}
+
+impl SecretStore {
+    pub fn with_backend(backend: Box<dyn SecretBackend>) -> Self {
+        Self {
+            backend,
+            fallback: None,
+        }
+    }
+
+    /// Use the backend recorded for this install. On first use that is `keyring` if it
+    /// answers a lookup, otherwise the encrypted file at `fallback_path`. `passphrase` is
+    /// only asked for when the file is opened.
+    pub fn open_with(
+        keyring: Box<dyn SecretBackend>,
+        fallback_path: PathBuf,
+        passphrase: &dyn Fn() -> String,
+    ) -> Self {
+        let choice_path = fallback_path.with_extension("backend");
+        let open_file = || -> Box<dyn SecretBackend> {
+            Box::new(EncryptedFileBackend::new(
+                fallback_path.clone(),
+                &passphrase(),
+            ))
+        };
+        let choice = read_backend_choice(&choice_path).unwrap_or_else(|| {
+            let choice = match keyring.get(PROBE_KEY) {
+                Ok(_) => BackendChoice::Keyring,
+                Err(err) => {
+                    log::warn!(
+                        "[Secrets] {} unavailable ({err}); using encrypted file {}",
+                        keyring.name(),
+                        fallback_path.display()
+                    );
+                    BackendChoice::File
+                }
+            };
+            write_backend_choice(&choice_path, choice);
+            choice
+        });
+        match choice {
+            BackendChoice::Keyring => Self {
+                backend: keyring,
+                fallback: fallback_path.exists().then(open_file),
+            },
+            BackendChoice::File => Self {
+                backend: open_file(),
+                fallback: Some(keyring),
+            },
+        }
+    }
+
+    /// Process-wide store, opened on first use.
+    pub fn shared() -> &'static SecretStore {
+        static SHARED: OnceLock<SecretStore> = OnceLock::new();
+        SHARED.get_or_init(|| {
+            let fallback_path = app_data_dir().join(SECRETS_FILE);
+            if std::env::var("HEAVEN_SECRET_BACKEND").is_ok_and(|v| v.trim() == "file") {
+                return Self::with_backend(Box::new(EncryptedFileBackend::new(
+                    fallback_path,
+                    &fallback_passphrase(),
+                )));
+            }
+            Self::open_with(
+                Box::new(KeyringBackend::default()),
+                fallback_path,
+                &fallback_passphrase,
+            )
+        })
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// True when secrets fell back to a file keyed to the machine identity because no
+    /// keyring was reachable and no `HEAVEN_SECRET_PASSPHRASE` was set. Settings warns.
+    pub fn machine_bound(&self) -> bool {
+        self.backend.machine_bound()
+    }
+
+    /// Reads the fallback when the backend fails or has no value; the backend's own answer
+    /// is returned when the fallback has nothing either.
+    pub fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let found = self.backend.get(key);
+        let Some(fallback) = self.fallback.as_ref() else {
+            return found;
+        };
+        if matches!(found, Ok(Some(_))) {
+            return found;
+        }
+        match fallback.get(key) {
+            Ok(Some(value)) => Ok(Some(value)),
+            _ => found,
+        }
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.backend.set(key, value)
+    }
+
+    /// Also clears the key from the fallback, so an older copy does not come back.
+    pub fn delete(&self, key: &str) -> Result<(), String> {
+        if let Some(fallback) = self.fallback.as_ref() {
+            fallback.delete(key).unwrap_or_else(|err| {
+                log::warn!(
+                    "[Secrets] deleting {key} from {} failed: {err}",
+                    fallback.name()
+                )
+            });
+        }
+        self.backend.delete(key)
+    }
+
+    /// Store `value` under `key`, or delete the key when `value` is `None`.
+    pub fn put_optional(&self, key: &str, value: Option<&str>) -> Result<(), String> {
+        match value {
+            Some(value) => self.set(key, value),
+            None => self.delete(key),
+        }
+    }
+}
+
+fn read_backend_choice(path: &Path) -> Option<BackendChoice> {
+    match std::fs::read_to_string(path).ok()?.trim() {
+        "keyring" => Some(BackendChoice::Keyring),
+        "file" => Some(BackendChoice::File),
+        _ => None,
+    }
+}
+
+fn write_backend_choice(path: &Path, choice: BackendChoice) {
+    let label = match choice {
+        BackendChoice::Keyring => "keyring",
+        BackendChoice::File => "file",
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(path, label));
+    if let Err(err) = written {
+        log::warn!(
+            "[Secrets] recording secret backend in {} failed: {err}",
+            path.display()
+        );
+    }
+}
+
+fn app_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("heaven-gpui")
+}
+
+/// `HEAVEN_SECRET_PASSPHRASE` when set; otherwise the file is only bound to this machine.
+fn fallback_passphrase() -> String {
+    match std::env::var("HEAVEN_SECRET_PASSPHRASE") {
+        Ok(value) if !value.is_empty() => value,
+        _ => {
+            log::warn!(
+                "[Secrets] HEAVEN_SECRET_PASSPHRASE not set; file fallback is keyed to machine identity"
+            );
+            machine_secret_material()
+        }
+    }
+}
+
+/// Stable per-machine string. Only as secret as the machine id; used to open files written
+/// before the keyring existed and as the last-resort fallback passphrase.
+pub fn machine_secret_material() -> String {
+    if let Ok(raw) = std::fs::read_to_string("/etc/machine-id") {
+        let trimmed = raw.trim();
+        if !trimmed.is_empty() {
+            return trimmed.to_string();
+        }
+    }
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "unknown-host".to_string());
+    let user = std::env::var("USER")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "unknown-user".to_string());
+    let home = dirs::home_dir()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string());
+    format!("{host}:{user}:{home}")
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::SecretBackend;
+
+    /// In-memory keyring; clones share entries so tests can inspect what was stored.
+    #[derive(Clone, Default)]
+    pub(crate) struct MockKeyring {
+        pub(crate) entries: Arc<Mutex<HashMap<String, String>>>,
+        pub(crate) unavailable: bool,
+    }
+
+    impl MockKeyring {
+        pub(crate) fn unavailable() -> Self {
+            Self {
+                unavailable: true,
+                ..Self::default()
+            }
+        }
+
+        pub(crate) fn value(&self, key: &str) -> Option<String> {
+            self.entries.lock().unwrap().get(key).cloned()
+        }
+    }
+
+    impl SecretBackend for MockKeyring {
+        fn name(&self) -> &'static str {
+            "mock keyring"
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>, String> {
+            if self.unavailable {
+                return Err("no secret service on the session bus".to_string());
+            }
+            Ok(self.value(key))
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), String> {
+            if self.unavailable {
+                return Err("no secret service on the session bus".to_string());
+            }
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<(), String> {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockKeyring;
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "heaven-secrets-{}-{}-{name}",
+            std::process::id(),
+            rand::random::<u32>()
+        ))
+    }
+
+    fn pass() -> String {
+        "pass".to_string()
+    }
+
+    #[test]
+    fn prefers_keyring_and_falls_back_to_encrypted_file() {
+        let keyring = MockKeyring::default();
+        let unused = temp_path("unused.json");
+        let asked = std::cell::Cell::new(0);
+        let counted_pass = || {
+            asked.set(asked.get() + 1);
+            pass()
+        };
+        let store =
+            SecretStore::open_with(Box::new(keyring.clone()), unused.clone(), &counted_pass);
+        assert_eq!(store.backend_name(), "mock keyring");
+        assert_eq!(asked.get(), 0, "no passphrase needed without the file");
+        store.set("auth.access_token", "tok").unwrap();
+        assert_eq!(keyring.value("auth.access_token").as_deref(), Some("tok"));
+        let _ = std::fs::remove_file(unused.with_extension("backend"));
+
+        let path = temp_path("fallback.json");
+        let store =
+            SecretStore::open_with(Box::new(MockKeyring::unavailable()), path.clone(), &pass);
+        assert_eq!(store.backend_name(), "encrypted file");
+        store
+            .put_optional("auth.access_token", Some("tok-secret"))
+            .unwrap();
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("tok-secret"));
+
+        let reopened = EncryptedFileBackend::new(path.clone(), "pass");
+        assert_eq!(
+            reopened.get("auth.access_token").unwrap().as_deref(),
+            Some("tok-secret")
+        );
+        assert!(EncryptedFileBackend::new(path.clone(), "wrong")
+            .get("auth.access_token")
+            .is_err());
+        store.put_optional("auth.access_token", None).unwrap();
+        assert_eq!(reopened.get("auth.access_token").unwrap(), None);
+        let _ = std::fs::remove_file(path.with_extension("backend"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn recorded_keyring_is_kept_while_it_is_briefly_unavailable() {
+        let path = temp_path("recorded.json");
+        let keyring = MockKeyring::default();
+        SecretStore::open_with(Box::new(keyring), path.clone(), &pass);
+
+        let store =
+            SecretStore::open_with(Box::new(MockKeyring::unavailable()), path.clone(), &pass);
+        assert_eq!(store.backend_name(), "mock keyring");
+        assert!(store.set("auth.access_token", "tok").is_err());
+        assert!(
+            !path.exists(),
+            "nothing is written to the file behind the keyring's back"
+        );
+        let _ = std::fs::remove_file(path.with_extension("backend"));
+    }
+
+    #[test]
+    fn secrets_left_in_the_file_stay_readable_from_the_keyring_store() {
+        let path = temp_path("legacy.json");
+        EncryptedFileBackend::new(path.clone(), "pass")
+            .set("auth.access_token", "old-tok")
+            .unwrap();
+        let keyring = MockKeyring::default();
+        let store = SecretStore::open_with(Box::new(keyring.clone()), path.clone(), &pass);
+        assert_eq!(store.backend_name(), "mock keyring");
+        assert_eq!(
+            store.get("auth.access_token").unwrap().as_deref(),
+            Some("old-tok")
+        );
+
+        store.set("auth.access_token", "new-tok").unwrap();
+        assert_eq!(
+            store.get("auth.access_token").unwrap().as_deref(),
+            Some("new-tok")
+        );
+        store.delete("auth.access_token").unwrap();
+        assert_eq!(store.get("auth.access_token").unwrap(), None);
+        let _ = std::fs::remove_file(path.with_extension("backend"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn concurrent_writes_to_the_file_keep_every_secret() {
+        let path = temp_path("concurrent.json");
+        let backend = std::sync::Arc::new(EncryptedFileBackend::new(path.clone(), "pass"));
+        let writers = (0..8)
+            .map(|i| {
+                let backend = backend.clone();
+                std::thread::spawn(move || backend.set(&format!("key.{i}"), "value").unwrap())
+            })
+            .collect::<Vec<_>>();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        for i in 0..8 {
+            assert_eq!(
+                backend.get(&format!("key.{i}")).unwrap().as_deref(),
+                Some("value")
+            );
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn a_file_keyed_to_the_machine_identity_is_labelled() {
+        let path = temp_path("machine.json");
+        let machine = EncryptedFileBackend::new(path.clone(), &machine_secret_material());
+        assert!(machine.machine_bound());
+        assert_eq!(machine.name(), "machine-keyed file");
+        let store = SecretStore::with_backend(Box::new(machine));
+        assert!(store.machine_bound());
+        assert!(
+            !SecretStore::with_backend(Box::new(EncryptedFileBackend::new(path, "pass")))
+                .machine_bound()
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{machine_secret_material, SecretBackend};
+
+const FILE_VERSION: u32 = 1;
+const KDF_NAME: &str = "argon2id";
+/// Encrypted under the derived key so a wrong passphrase fails before touching entries.
+const VERIFIER_PLAINTEXT: &[u8] = b"heaven-secrets-v1";
+const VERIFIER_AAD: &[u8] = b"heaven-secrets-verifier";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SecretsFile {
+    version: u32,
+    kdf: String,
+    salt: String,
+    verifier: String,
+    #[serde(default)]
+    entries: BTreeMap<String, String>,
+}
+
+/// Secrets sealed with AES-256-GCM under an Argon2id key derived from a passphrase.
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    passphrase: String,
+    /// Derived key for the salt it was derived with; Argon2 is deliberately slow.
+    derived: Mutex<Option<(String, [u8; 32])>>,
+    /// Held across each read-modify-write of the file.
+    write_lock: Mutex<()>,
+}
+
+impl EncryptedFileBackend {
+    pub fn new(path: PathBuf, passphrase: &str) -> Self {
+        Self {
+            path,
+            passphrase: passphrase.to_string(),
+            derived: Mutex::new(None),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn read_file(&self) -> Result<Option<SecretsFile>, String> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("read {}: {e}", self.path.display())),
+        };
+        let file: SecretsFile = serde_json::from_str(&text)
+            .map_err(|e| format!("parse {}: {e}", self.path.display()))?;
+        if file.version != FILE_VERSION || file.kdf != KDF_NAME {
+            return Err(format!(
+                "Unsupported secrets file format (version {}, kdf {}).",
+                file.version, file.kdf
+            ));
+        }
+        Ok(Some(file))
+    }
+
+    /// Open the file with this passphrase, or start a new one.
+    fn unlock(&self) -> Result<(SecretsFile, [u8; 32]), String> {
+        if let Some(file) = self.read_file()? {
+            let key = self.derive_key(&file.salt)?;
+            open_value(&key, VERIFIER_AAD, &file.verifier)
+                .map_err(|_| "Secrets file passphrase is incorrect.".to_string())?;
+            return Ok((file, key));
+        }
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let salt = hex::encode(salt);
+        let key = self.derive_key(&salt)?;
+        let verifier = seal_value(&key, VERIFIER_AAD, VERIFIER_PLAINTEXT)?;
+        Ok((
+            SecretsFile {
+                version: FILE_VERSION,
+                kdf: KDF_NAME.to_string(),
+                salt,
+                verifier,
+                entries: BTreeMap::new(),
+            },
+            key,
+        ))
+    }
+
+    fn derive_key(&self, salt_hex: &str) -> Result<[u8; 32], String> {
+        let mut cached = self.derived.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((salt, key)) = cached.as_ref() {
+            if salt == salt_hex {
+                return Ok(*key);
+            }
+        }
+        let salt = hex::decode(salt_hex).map_err(|e| format!("Invalid secrets salt: {e}"))?;
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("Failed deriving secrets key: {e}"))?;
+        *cached = Some((salt_hex.to_string(), key));
+        Ok(key)
+    }
+
+    fn write_file(&self, file: &SecretsFile) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("mkdir {}: {e}", parent.display()))?;
+        }
+        let encoded = serde_json::to_string_pretty(file)
+            .map_err(|e| format!("Failed encoding secrets file: {e}"))?;
+        let tmp = self.path.with_extension("json.tmp");
+        write_private(&tmp, encoded.as_bytes())?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("replace {}: {e}", self.path.display()))
+    }
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn name(&self) -> &'static str {
+        if self.machine_bound() {
+            "machine-keyed file"
+        } else {
+            "encrypted file"
+        }
+    }
+
+    fn machine_bound(&self) -> bool {
+        self.passphrase == machine_secret_material()
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let (file, derived) = self.unlock()?;
+        let Some(sealed) = file.entries.get(key) else {
+            return Ok(None);
+        };
+        let plaintext = open_value(&derived, key.as_bytes(), sealed)?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|e| format!("Invalid UTF-8 in secret {key}: {e}"))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut file, derived) = self.unlock()?;
+        let sealed = seal_value(&derived, key.as_bytes(), value.as_bytes())?;
+        file.entries.insert(key.to_string(), sealed);
+        self.write_file(&file)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(mut file) = self.read_file()? else {
+            return Ok(());
+        };
+        if file.entries.remove(key).is_some() {
+            self.write_file(&file)?;
+        }
+        Ok(())
+    }
+}
+
+/// `iv_hex:ciphertext_hex`, with the secret's key name bound as associated data.
+fn seal_value(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> Result<String, String> {
+    let mut iv = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut iv);
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| format!("Failed creating secrets cipher: {e}"))?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|e| format!("Failed encrypting secret: {e}"))?;
+    Ok(format!("{}:{}", hex::encode(iv), hex::encode(ciphertext)))
+}
+
+fn open_value(key: &[u8; 32], aad: &[u8], sealed: &str) -> Result<Vec<u8>, String> {
+    let (iv_hex, ciphertext_hex) = sealed
+        .split_once(':')
+        .ok_or("Invalid sealed secret format.")?;
+    let iv = hex::decode(iv_hex).map_err(|e| format!("Invalid secret IV: {e}"))?;
+    if iv.len() != 12 {
+        return Err(format!("Invalid secret IV length: {}", iv.len()));
+    }
+    let ciphertext =
+        hex::decode(ciphertext_hex).map_err(|e| format!("Invalid secret ciphertext: {e}"))?;
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| format!("Failed creating secrets cipher: {e}"))?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: &ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| "Failed decrypting secret.".to_string())
+}
+
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| format!("open {}: {e}", path.display()))?;
+        file.write_all(bytes)
+            .map_err(|e| format!("write {}: {e}", path.display()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(path, bytes).map_err(|e| format!("write {}: {e}", path.display()))
+    }
+}
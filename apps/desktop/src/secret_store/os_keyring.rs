@@ -0,0 +1,49 @@
+use super::SecretBackend;
+
+const KEYRING_SERVICE: &str = "heaven-desktop";
+
+/// OS keyring via the `keyring` crate; one credential per secret key.
+pub struct KeyringBackend {
+    service: String,
+}
+
+impl Default for KeyringBackend {
+    fn default() -> Self {
+        Self {
+            service: KEYRING_SERVICE.to_string(),
+        }
+    }
+}
+
+impl KeyringBackend {
+    fn entry(&self, key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(&self.service, key).map_err(|e| format!("keyring entry {key}: {e}"))
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn name(&self) -> &'static str {
+        "system keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("keyring read {key}: {e}")),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.entry(key)?
+            .set_password(value)
+            .map_err(|e| format!("keyring write {key}: {e}"))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("keyring delete {key}: {e}")),
+        }
+    }
+}
@@ -1,15 +1,20 @@
 //! Settings page for account/session actions and developer tooling.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use gpui::prelude::FluentBuilder;
 use gpui::*;
+use gpui_component::input::{Input, InputState};
 use gpui_component::select::{SelectEvent, SelectState};
 use gpui_component::StyledExt;
 
 use crate::auth;
-use crate::load_storage::{LoadStorageService, TrackMetaInput};
+use crate::load_storage::{
+    ContentCacheUsage, ContentKeyStatus, LoadStorageResult, LoadStorageService, TrackMetaInput,
+    UploadControl,
+};
+use crate::shared::address::abbreviate_address;
 use crate::voice::jacktrip::JackTripController;
 
 mod helpers;
@@ -38,6 +43,28 @@ define_color_fns! {
     ACCENT_RED => accent_red,
 }
 
+/// Where a content key restore reads the key from.
+#[derive(Clone)]
+enum KeyRestoreSource {
+    Backup { path: PathBuf, passphrase: String },
+    Phrase(String),
+}
+
+/// A content key restore held back until the user confirms it replaces the published key.
+struct PendingKeyRestore {
+    source: KeyRestoreSource,
+    published_fingerprint: String,
+}
+
+/// Which scrobble services the signed-in account has credentials for.
+#[derive(Clone, Copy)]
+struct ScrobbleAccountStatus {
+    listenbrainz: bool,
+    /// Last.fm API key and secret are saved.
+    lastfm_app: bool,
+    lastfm_session: bool,
+}
+
 pub struct SettingsView {
     storage: Arc<Mutex<LoadStorageService>>,
     jacktrip_test: Arc<Mutex<JackTripController>>,
@@ -49,6 +76,24 @@ pub struct SettingsView {
     show_dev_tools: bool,
     pub imported_theme_name: Option<String>,
     pub theme_select: Entity<SelectState<Vec<String>>>,
+    /// Signed-in wallets on this device, most recently used first.
+    saved_accounts: Vec<auth::accounts::AccountEntry>,
+    content_key_status: Option<ContentKeyStatus>,
+    /// Recovery phrase while the user has it revealed.
+    content_key_phrase: Option<String>,
+    content_key_passphrase_input: Entity<InputState>,
+    content_key_phrase_input: Entity<InputState>,
+    content_key_device_input: Entity<InputState>,
+    content_key_pending_restore: Option<PendingKeyRestore>,
+    content_cache_usage: Option<ContentCacheUsage>,
+    /// Remote lyrics rows and their text size.
+    lyrics_cache_usage: Option<(usize, u64)>,
+    scrobble_accounts: Option<ScrobbleAccountStatus>,
+    listenbrainz_token_input: Entity<InputState>,
+    lastfm_api_key_input: Entity<InputState>,
+    lastfm_api_secret_input: Entity<InputState>,
+    /// Last.fm token opened in the browser, waiting for the user to approve it.
+    lastfm_pending_token: Option<String>,
 }
 
 impl SettingsView {
@@ -75,6 +75,24 @@ pub(super) fn result_box(title: &str, body: &str) -> impl IntoElement {
         )
 }
 
+pub(super) fn text_field(state: &Entity<InputState>) -> impl IntoElement {
+    div()
+        .flex_1()
+        .px_3()
+        .rounded(px(8.))
+        .bg(BG_HOVER())
+        .border_1()
+        .border_color(BORDER_SUBTLE())
+        .child(Input::new(state).appearance(false).cleanable(false))
+}
+
+/// Read an input's value and clear it, so secrets do not linger on screen.
+pub(super) fn take_input(input: &Entity<InputState>, window: &mut Window, cx: &mut App) -> String {
+    let value = input.read(cx).value().trim().to_string();
+    input.update(cx, |state, cx| state.set_value("", window, cx));
+    value
+}
+
 pub(super) fn action_button(
     label: &'static str,
     enabled: bool,
@@ -113,6 +131,16 @@ pub(super) fn action_button(
         .child(label)
 }
 
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= 1024.0 * MB {
+        format!("{:.1} GB", bytes / (1024.0 * MB))
+    } else {
+        format!("{:.1} MB", bytes / MB)
+    }
+}
+
 pub(super) fn local_jacktrip_port() -> u16 {
     std::env::var("HEAVEN_JACKTRIP_PORT")
         .ok()
@@ -1,4 +1,7 @@
 mod auth_lit;
+mod cache;
+mod content_key;
 mod jacktrip;
+mod scrobble_accounts;
 mod storage;
 mod theme;
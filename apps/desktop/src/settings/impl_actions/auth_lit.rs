@@ -35,6 +35,39 @@ impl SettingsView {
         )
         .detach();
 
+        let content_key_passphrase_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("Backup passphrase")
+        });
+        let content_key_phrase_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("24-word recovery phrase"));
+        let content_key_device_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("0x04... other device's content public key")
+        });
+
+        let listenbrainz_token_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("ListenBrainz user token")
+        });
+        let lastfm_api_key_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Last.fm API key"));
+        let lastfm_api_secret_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("Last.fm shared secret")
+        });
+
+        cx.observe_global::<auth::AuthState>(|this, cx| {
+            this.saved_accounts = auth::list_accounts();
+            // Scrobble credentials are per account; load them again for whoever is signed in.
+            this.scrobble_accounts = None;
+            this.lastfm_pending_token = None;
+            cx.notify();
+        })
+        .detach();
+
         Self {
             storage: Arc::new(Mutex::new(LoadStorageService::new())),
             jacktrip_test: Arc::new(Mutex::new(JackTripController::new())),
@@ -46,6 +79,20 @@ impl SettingsView {
             show_dev_tools: false,
             imported_theme_name: None,
             theme_select,
+            saved_accounts: auth::list_accounts(),
+            content_key_status: None,
+            content_key_phrase: None,
+            content_key_passphrase_input,
+            content_key_phrase_input,
+            content_key_device_input,
+            content_key_pending_restore: None,
+            content_cache_usage: None,
+            lyrics_cache_usage: None,
+            scrobble_accounts: None,
+            listenbrainz_token_input,
+            lastfm_api_key_input,
+            lastfm_api_secret_input,
+            lastfm_pending_token: None,
         }
     }
 
@@ -137,6 +184,47 @@ impl SettingsView {
         .detach();
     }
 
+    /// Make a saved account active without re-running the passkey flow.
+    pub(crate) fn switch_account(&mut self, address: String, cx: &mut Context<Self>) {
+        if self.busy || cx.global::<auth::AuthState>().authing {
+            return;
+        }
+        match auth::switch_account(&address) {
+            Ok(persisted) => {
+                self.error = None;
+                self.status = format!("Switched to {}", abbreviate_address(&address));
+                self.publish_status_success("settings.auth", self.status.clone(), cx);
+                log::info!("[Settings] Switched active account to {address}");
+                cx.update_global::<auth::AuthState, _>(|state, _| {
+                    state.persisted = Some(persisted);
+                });
+            }
+            Err(e) => {
+                self.status = "Account switch failed".into();
+                self.error = Some(e.clone());
+                self.publish_status_error("settings.auth", format!("{}: {}", self.status, e), cx);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Forget a saved account; signing out too if it is the active one.
+    pub(crate) fn remove_saved_account(&mut self, address: String, cx: &mut Context<Self>) {
+        let is_active = cx
+            .global::<auth::AuthState>()
+            .display_address()
+            .is_some_and(|active| active.eq_ignore_ascii_case(&address));
+        if is_active {
+            self.logout(cx);
+            return;
+        }
+        auth::remove_account(&address);
+        self.saved_accounts = auth::list_accounts();
+        self.status = format!("Removed {}", abbreviate_address(&address));
+        self.publish_status_info("settings.auth", self.status.clone(), cx);
+        cx.notify();
+    }
+
     pub(crate) fn logout(&mut self, cx: &mut Context<Self>) {
         auth::delete_from_disk();
         self.last_storage_response = None;
@@ -0,0 +1,130 @@
+use super::super::helpers::format_bytes;
+use super::super::*;
+use crate::auth::accounts::AccountRegistry;
+use crate::load_storage::{ContentCache, ContentCacheSettings};
+use crate::music_db::MusicDb;
+
+const STATUS_KEY: &str = "settings.cache";
+
+impl SettingsView {
+    pub(crate) fn refresh_cache_usage(&mut self, cx: &mut Context<Self>) {
+        self.run_cache_task(
+            "Measuring cache...",
+            |cache| Ok(cache.usage()),
+            false,
+            |usage| format!("Cache uses {}", format_bytes(usage.total_bytes())),
+            cx,
+        );
+    }
+
+    pub(crate) fn set_cache_max_bytes(&mut self, max_bytes: u64, cx: &mut Context<Self>) {
+        self.run_cache_task(
+            "Applying cache size...",
+            move |cache| {
+                cache.configure(ContentCacheSettings {
+                    max_bytes,
+                    ..cache.settings()
+                })
+            },
+            false,
+            |usage| {
+                format!(
+                    "Cache limited to {}",
+                    format_bytes(usage.settings.max_bytes)
+                )
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn toggle_cache_encryption(&mut self, cx: &mut Context<Self>) {
+        self.run_cache_task(
+            "Re-encoding cached audio...",
+            |cache| {
+                let settings = cache.settings();
+                cache.configure(ContentCacheSettings {
+                    encrypt_at_rest: !settings.encrypt_at_rest,
+                    ..settings
+                })
+            },
+            false,
+            |usage| {
+                if usage.settings.encrypt_at_rest {
+                    "Cached shared audio is now encrypted at rest".to_string()
+                } else {
+                    "Cached shared audio is now stored decrypted".to_string()
+                }
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn clear_content_cache(&mut self, cx: &mut Context<Self>) {
+        self.run_cache_task(
+            "Clearing cache...",
+            |cache| cache.clear(),
+            true,
+            |_| "Cache cleared".to_string(),
+            cx,
+        );
+    }
+
+    fn run_cache_task(
+        &mut self,
+        progress: &str,
+        task: impl FnOnce(&ContentCache) -> Result<ContentCacheUsage, String> + Send + 'static,
+        clear_lyrics: bool,
+        summary: fn(&ContentCacheUsage) -> String,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+        self.status = progress.to_string();
+        self.error = None;
+        self.publish_status_progress(STATUS_KEY, self.status.clone(), cx);
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                let usage = task(&ContentCache::active())?;
+                let lyrics = lyrics_cache_usage(clear_lyrics)?;
+                Ok::<_, String>((usage, lyrics))
+            })
+            .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.busy = false;
+                match result {
+                    Ok((usage, lyrics)) => {
+                        this.status = summary(&usage);
+                        this.content_cache_usage = Some(usage);
+                        this.lyrics_cache_usage = Some(lyrics);
+                        this.publish_status_success(STATUS_KEY, this.status.clone(), cx);
+                    }
+                    Err(err) => {
+                        this.status = "Cache action failed".into();
+                        this.error = Some(err.clone());
+                        this.publish_status_error(
+                            STATUS_KEY,
+                            format!("{}: {}", this.status, err),
+                            cx,
+                        );
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
+
+/// Lyrics are cached as rows in the shared `music.db`, not as files.
+fn lyrics_cache_usage(clear: bool) -> Result<(usize, u64), String> {
+    let db = MusicDb::open(AccountRegistry::shared().base_dir())?;
+    if clear {
+        db.clear_lyrics_cache()?;
+    }
+    db.lyrics_cache_usage()
+}
@@ -0,0 +1,350 @@
+use super::super::helpers::take_input;
+use super::super::*;
+use crate::load_storage::{ContentKeyRestore, ContentKeyRestoreOutcome, DeviceKeySync};
+
+const STATUS_KEY: &str = "settings.content_key";
+
+impl SettingsView {
+    pub(crate) fn refresh_content_key_status(&mut self, cx: &mut Context<Self>) {
+        self.run_content_key_task(
+            "Loading content key...",
+            |svc| svc.content_key_status(),
+            |this, status, _| {
+                this.content_key_status = Some(status);
+                "Content key loaded".to_string()
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn export_content_key_backup(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        let passphrase = take_input(&self.content_key_passphrase_input, window, cx);
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Save Content Key Backup")
+            .set_file_name("heaven-content-key-backup.json")
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        self.run_content_key_task(
+            "Exporting content key backup...",
+            move |svc| svc.content_key_export_backup(&path, &passphrase),
+            |_, backup, _| {
+                format!(
+                    "Content key {} backed up to {}",
+                    backup.fingerprint,
+                    backup.path.display()
+                )
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn import_content_key_backup(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        let passphrase = take_input(&self.content_key_passphrase_input, window, cx);
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Select Content Key Backup")
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        self.restore_content_key(KeyRestoreSource::Backup { path, passphrase }, false, cx);
+    }
+
+    pub(crate) fn toggle_content_key_phrase(&mut self, cx: &mut Context<Self>) {
+        if self.content_key_phrase.take().is_some() {
+            cx.notify();
+            return;
+        }
+        self.run_content_key_task(
+            "Loading recovery phrase...",
+            |svc| svc.content_key_recovery_phrase(),
+            |this, phrase, _| {
+                this.content_key_phrase = Some(phrase);
+                "Recovery phrase shown. Write it down and keep it offline.".to_string()
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn restore_content_key_from_phrase(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        let phrase = take_input(&self.content_key_phrase_input, window, cx);
+        self.restore_content_key(KeyRestoreSource::Phrase(phrase), false, cx);
+    }
+
+    /// Go ahead with the restore that was held back because it replaces the published key.
+    pub(crate) fn confirm_content_key_restore(&mut self, cx: &mut Context<Self>) {
+        if self.busy {
+            return;
+        }
+        if let Some(pending) = self.content_key_pending_restore.take() {
+            self.restore_content_key(pending.source, true, cx);
+        }
+    }
+
+    pub(crate) fn cancel_content_key_restore(&mut self, cx: &mut Context<Self>) {
+        if self.content_key_pending_restore.take().is_some() {
+            self.status = "Content key restore cancelled".to_string();
+            cx.notify();
+        }
+    }
+
+    fn restore_content_key(
+        &mut self,
+        source: KeyRestoreSource,
+        replace_published: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(auth) = auth::load_from_disk() else {
+            self.error = Some("No persisted auth. Click Sign In first.".into());
+            cx.notify();
+            return;
+        };
+        let progress = match &source {
+            KeyRestoreSource::Backup { .. } => "Restoring content key from backup...",
+            KeyRestoreSource::Phrase(_) => "Restoring content key from recovery phrase...",
+        };
+        let task_source = source.clone();
+        self.run_content_key_task(
+            progress,
+            move |svc| match &task_source {
+                KeyRestoreSource::Backup { path, passphrase } => {
+                    svc.content_key_import_backup(&auth, path, passphrase, replace_published)
+                }
+                KeyRestoreSource::Phrase(phrase) => {
+                    svc.content_key_restore_from_phrase(&auth, phrase, replace_published)
+                }
+            },
+            move |this, outcome, cx| match outcome {
+                ContentKeyRestoreOutcome::Restored(restore) => {
+                    this.content_key_phrase = None;
+                    this.load_content_key_status(cx);
+                    restore_summary(&restore)
+                }
+                ContentKeyRestoreOutcome::NeedsConfirmation {
+                    published_fingerprint,
+                } => {
+                    let message = format!(
+                        "This key differs from the published content key {published_fingerprint}. Confirm to replace it."
+                    );
+                    this.content_key_pending_restore = Some(PendingKeyRestore {
+                        source,
+                        published_fingerprint,
+                    });
+                    message
+                }
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn set_content_key_secondary(&mut self, secondary: bool, cx: &mut Context<Self>) {
+        self.run_content_key_task(
+            if secondary {
+                "Marking this device as linked..."
+            } else {
+                "Marking this device as primary..."
+            },
+            move |svc| svc.content_key_set_secondary(secondary),
+            move |this, status, _| {
+                this.content_key_status = Some(status);
+                if secondary {
+                    "This device now receives keys from your primary device. Link its public key there."
+                        .to_string()
+                } else {
+                    "This device is primary again.".to_string()
+                }
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn copy_content_public_key(&mut self, cx: &mut Context<Self>) {
+        let Some(status) = self.content_key_status.as_ref() else {
+            return;
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(status.public_key.clone()));
+        self.status = "Content public key copied".to_string();
+        self.publish_status_info(STATUS_KEY, self.status.clone(), cx);
+        cx.notify();
+    }
+
+    pub(crate) fn link_content_key_device(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.busy {
+            return;
+        }
+        let Some(auth) = auth::load_from_disk() else {
+            self.error = Some("No persisted auth. Click Sign In first.".into());
+            cx.notify();
+            return;
+        };
+        let public_key = take_input(&self.content_key_device_input, window, cx);
+        self.run_content_key_task(
+            "Linking device and re-wrapping content keys...",
+            move |svc| svc.content_key_link_device(&auth, &public_key),
+            |this, sync, cx| {
+                this.load_content_key_status(cx);
+                sync_summary("Device linked", &sync)
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn sync_content_key_devices(&mut self, cx: &mut Context<Self>) {
+        let Some(auth) = auth::load_from_disk() else {
+            self.error = Some("No persisted auth. Click Sign In first.".into());
+            cx.notify();
+            return;
+        };
+        self.run_content_key_task(
+            "Re-wrapping content keys for linked devices...",
+            move |svc| svc.content_key_sync_devices(&auth),
+            |this, sync, cx| {
+                this.load_content_key_status(cx);
+                sync_summary("Linked devices synced", &sync)
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn unlink_content_key_device(
+        &mut self,
+        fingerprint: String,
+        cx: &mut Context<Self>,
+    ) {
+        self.run_content_key_task(
+            "Unlinking device...",
+            move |svc| {
+                svc.content_key_unlink_device(&fingerprint)?;
+                svc.content_key_status()
+            },
+            |this, status, _| {
+                this.content_key_status = Some(status);
+                "Device unlinked. Keys it already received stay readable to it.".to_string()
+            },
+            cx,
+        );
+    }
+
+    /// Refresh the key summary in the background without touching the status line.
+    fn load_content_key_status(&mut self, cx: &mut Context<Self>) {
+        let storage = self.storage.clone();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
+                svc.content_key_status()
+            })
+            .await;
+            let _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok(status) => this.content_key_status = Some(status),
+                    Err(err) => log::warn!("[Settings] content key status failed: {}", err),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn run_content_key_task<T: Send + 'static>(
+        &mut self,
+        progress: &str,
+        task: impl FnOnce(&mut LoadStorageService) -> LoadStorageResult<T> + Send + 'static,
+        on_success: impl FnOnce(&mut Self, T, &mut Context<Self>) -> String + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+        self.status = progress.to_string();
+        self.error = None;
+        self.publish_status_progress(STATUS_KEY, self.status.clone(), cx);
+        cx.notify();
+
+        let storage = self.storage.clone();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
+                task(&mut svc)
+            })
+            .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.busy = false;
+                match result {
+                    Ok(value) => {
+                        this.status = on_success(this, value, cx);
+                        this.publish_status_success(STATUS_KEY, this.status.clone(), cx);
+                    }
+                    Err(err) => {
+                        this.status = "Content key action failed".into();
+                        this.error = Some(err.to_string());
+                        this.publish_status_error(
+                            STATUS_KEY,
+                            format!("{}: {}", this.status, err.user_message()),
+                            cx,
+                        );
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
+
+fn restore_summary(restore: &ContentKeyRestore) -> String {
+    let mut summary = if restore.replaced_previous {
+        format!(
+            "Content key {} restored; {} cached keys moved over from the previous key",
+            restore.fingerprint, restore.rewrapped_keys
+        )
+    } else {
+        format!("Content key {} restored", restore.fingerprint)
+    };
+    if !restore.published {
+        summary.push_str(" (publishing its public key failed; it is retried on your next upload)");
+    }
+    summary
+}
+
+fn sync_summary(prefix: &str, sync: &DeviceKeySync) -> String {
+    let mut summary = format!(
+        "{prefix}: {} envelopes published to {} devices",
+        sync.envelope_ids.len(),
+        sync.devices
+    );
+    if !sync.failed_content_ids.is_empty() {
+        summary.push_str(&format!(
+            " ({} keys failed; retry Sync Devices)",
+            sync.failed_content_ids.len()
+        ));
+    }
+    summary
+}
@@ -0,0 +1,182 @@
+use super::super::helpers::take_input;
+use super::super::*;
+use crate::auth::accounts::AccountRegistry;
+use crate::music_db::MusicDb;
+use crate::scrobble::sinks::{
+    begin_lastfm_connect, finish_lastfm_connect, set_sink_secret, sink_secret, SinkSecret,
+};
+
+const STATUS_KEY: &str = "settings.scrobble_accounts";
+
+impl SettingsView {
+    pub(crate) fn refresh_scrobble_accounts(&mut self, cx: &mut Context<Self>) {
+        self.run_scrobble_account_task(
+            "Loading scrobble accounts...",
+            |_| Ok(()),
+            |_, _| "Scrobble accounts loaded".to_string(),
+            cx,
+        );
+    }
+
+    pub(crate) fn save_listenbrainz_token(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.busy {
+            return;
+        }
+        let token = take_input(&self.listenbrainz_token_input, window, cx);
+        if token.is_empty() {
+            self.error = Some("Paste your ListenBrainz user token first.".to_string());
+            cx.notify();
+            return;
+        }
+        self.run_scrobble_account_task(
+            "Saving ListenBrainz token...",
+            move |user| set_sink_secret(SinkSecret::ListenBrainzToken, user, Some(&token)),
+            |_, _| "ListenBrainz connected".to_string(),
+            cx,
+        );
+    }
+
+    pub(crate) fn disconnect_listenbrainz(&mut self, cx: &mut Context<Self>) {
+        self.run_scrobble_account_task(
+            "Disconnecting ListenBrainz...",
+            |user| set_sink_secret(SinkSecret::ListenBrainzToken, user, None),
+            |_, _| "ListenBrainz disconnected".to_string(),
+            cx,
+        );
+    }
+
+    /// Save any API key and secret typed in, then open Last.fm's approval page for a new token.
+    pub(crate) fn connect_lastfm(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.busy {
+            return;
+        }
+        let api_key = take_input(&self.lastfm_api_key_input, window, cx);
+        let api_secret = take_input(&self.lastfm_api_secret_input, window, cx);
+        self.run_scrobble_account_task(
+            "Requesting Last.fm authorization...",
+            move |user| {
+                if !api_key.is_empty() {
+                    set_sink_secret(SinkSecret::LastFmApiKey, user, Some(&api_key))?;
+                }
+                if !api_secret.is_empty() {
+                    set_sink_secret(SinkSecret::LastFmApiSecret, user, Some(&api_secret))?;
+                }
+                let connect = begin_lastfm_connect(&open_music_db()?, user)?;
+                open::that(&connect.authorize_url)
+                    .map_err(|e| format!("Failed to open browser: {e}"))?;
+                Ok(connect.token)
+            },
+            |this, token| {
+                this.lastfm_pending_token = Some(token);
+                "Approve Heaven on the Last.fm page, then press Finish Connecting".to_string()
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn complete_lastfm_connect(&mut self, cx: &mut Context<Self>) {
+        let Some(token) = self.lastfm_pending_token.clone() else {
+            return;
+        };
+        self.run_scrobble_account_task(
+            "Finishing Last.fm connection...",
+            move |user| finish_lastfm_connect(&open_music_db()?, user, &token),
+            |this, _| {
+                this.lastfm_pending_token = None;
+                "Last.fm connected".to_string()
+            },
+            cx,
+        );
+    }
+
+    pub(crate) fn disconnect_lastfm(&mut self, cx: &mut Context<Self>) {
+        self.lastfm_pending_token = None;
+        self.run_scrobble_account_task(
+            "Disconnecting Last.fm...",
+            |user| {
+                for secret in [
+                    SinkSecret::LastFmSessionKey,
+                    SinkSecret::LastFmApiSecret,
+                    SinkSecret::LastFmApiKey,
+                ] {
+                    set_sink_secret(secret, user, None)?;
+                }
+                Ok(())
+            },
+            |_, _| "Last.fm disconnected".to_string(),
+            cx,
+        );
+    }
+
+    /// Run `task` for the signed-in wallet off the UI thread, then reload what is connected.
+    fn run_scrobble_account_task<T: Send + 'static>(
+        &mut self,
+        progress: &str,
+        task: impl FnOnce(&str) -> Result<T, String> + Send + 'static,
+        on_success: impl FnOnce(&mut Self, T) -> String + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        if self.busy {
+            return;
+        }
+        let Some(user_address) = cx
+            .global::<auth::AuthState>()
+            .persisted
+            .as_ref()
+            .and_then(|auth| auth.wallet_address().map(str::to_string))
+        else {
+            self.error = Some("Sign in to connect scrobble accounts.".to_string());
+            cx.notify();
+            return;
+        };
+        self.busy = true;
+        self.status = progress.to_string();
+        self.error = None;
+        self.publish_status_progress(STATUS_KEY, self.status.clone(), cx);
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let result = smol::unblock(move || {
+                let value = task(&user_address)?;
+                Ok::<_, String>((value, scrobble_account_status(&user_address)))
+            })
+            .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.busy = false;
+                match result {
+                    Ok((value, status)) => {
+                        this.scrobble_accounts = Some(status);
+                        this.status = on_success(this, value);
+                        this.publish_status_success(STATUS_KEY, this.status.clone(), cx);
+                    }
+                    Err(err) => {
+                        this.status = "Scrobble account action failed".into();
+                        this.error = Some(err.clone());
+                        this.publish_status_error(
+                            STATUS_KEY,
+                            format!("{}: {}", this.status, err),
+                            cx,
+                        );
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
+
+fn scrobble_account_status(user_address: &str) -> ScrobbleAccountStatus {
+    let has = |secret| sink_secret(secret, user_address).is_some();
+    ScrobbleAccountStatus {
+        listenbrainz: has(SinkSecret::ListenBrainzToken),
+        lastfm_app: has(SinkSecret::LastFmApiKey) && has(SinkSecret::LastFmApiSecret),
+        lastfm_session: has(SinkSecret::LastFmSessionKey),
+    }
+}
+
+/// Sink endpoint overrides are rows in the shared `music.db`.
+fn open_music_db() -> Result<MusicDb, String> {
+    MusicDb::open(AccountRegistry::shared().base_dir())
+}
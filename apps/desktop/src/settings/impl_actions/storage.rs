@@ -35,7 +35,7 @@ impl SettingsView {
                     }
                     Err(e) => {
                         this.status = "Storage health check failed".into();
-                        this.error = Some(e.clone());
+                        this.error = Some(e.to_string());
                         this.publish_status_error(
                             "settings.storage",
                             format!("{}: {}", this.status, e),
@@ -91,7 +91,7 @@ impl SettingsView {
                     }
                     Err(e) => {
                         this.status = "Load storage status failed".into();
-                        this.error = Some(e.clone());
+                        this.error = Some(e.to_string());
                         this.publish_status_error(
                             "settings.storage",
                             format!("{}: {}", this.status, e),
@@ -147,7 +147,7 @@ impl SettingsView {
                     }
                     Err(e) => {
                         this.status = "Load preflight check failed".into();
-                        this.error = Some(e.clone());
+                        this.error = Some(e.to_string());
                         this.publish_status_error(
                             "settings.storage",
                             format!("{}: {}", this.status, e),
@@ -185,7 +185,7 @@ impl SettingsView {
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
-                svc.storage_deposit_and_approve(&auth, "0.0001")
+                svc.storage_deposit_and_approve(&auth)
             })
             .await;
 
@@ -203,7 +203,7 @@ impl SettingsView {
                     }
                     Err(e) => {
                         this.status = "Load funding flow failed".into();
-                        this.error = Some(e.clone());
+                        this.error = Some(e.to_string());
                         this.publish_status_error(
                             "settings.storage",
                             format!("{}: {}", this.status, e),
@@ -257,7 +257,9 @@ impl SettingsView {
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let result = smol::unblock(move || {
                 let mut svc = storage.lock().map_err(|e| format!("storage lock: {e}"))?;
-                svc.content_encrypt_upload_register(&auth, &path_str, true, track_meta)
+                svc.content_encrypt_upload_register(&auth, &path_str, true, track_meta, &|_| {
+                    UploadControl::Continue
+                })
             })
             .await;
 
@@ -275,7 +277,7 @@ impl SettingsView {
                     }
                     Err(e) => {
                         this.status = "Encrypted upload failed".into();
-                        this.error = Some(e.clone());
+                        this.error = Some(e.to_string());
                         this.publish_status_error(
                             "settings.storage",
                             format!("{}: {}", this.status, e),
@@ -32,6 +32,12 @@ impl Render for SettingsView {
             .child(self.render_account_section(is_authed, addr.as_deref(), auth_provider, cx))
             // ── Appearance ──
             .child(self.render_appearance_section(cx))
+            // ── Content Key ──
+            .child(self.render_content_key_section(cx))
+            // ── Scrobble Accounts ──
+            .child(self.render_scrobble_accounts_section(is_authed, cx))
+            // ── Cache ──
+            .child(self.render_cache_section(cx))
             // ── Developer Tools (collapsible) ──
             .child(self.render_dev_tools_section(
                 addr.as_deref().unwrap_or("N/A"),
@@ -112,7 +118,21 @@ impl SettingsView {
                                         ))
                                     })
                                     .when(is_authed, |el| {
-                                        el.child(
+                                        el.child(action_button(
+                                            "Add Account",
+                                            !self.busy,
+                                            false,
+                                            cx.listener(|this, _, _, cx| this.sign_in(cx)),
+                                        ))
+                                        .child(action_button(
+                                            "Renew Session Key",
+                                            !cx.global::<auth::AuthState>().authing,
+                                            false,
+                                            cx.listener(|_, _, _, cx| {
+                                                auth::session::SessionManager::renew(cx)
+                                            }),
+                                        ))
+                                        .child(
                                             div()
                                                 .id("settings-logout")
                                                 .h_flex()
@@ -137,8 +157,91 @@ impl SettingsView {
                                         )
                                     }),
                             ),
-                    ),
+                    )
+                    .when(self.saved_accounts.len() > 1, |el| {
+                        el.child(self.render_saved_accounts(addr, cx))
+                    })
+                    .child(secret_storage_row()),
+            )
+    }
+
+    /// Other signed-in wallets, switchable without another passkey prompt.
+    fn render_saved_accounts(&self, active: Option<&str>, cx: &mut Context<Self>) -> AnyElement {
+        let rows: Vec<AnyElement> = self
+            .saved_accounts
+            .iter()
+            .map(|account| {
+                let is_active =
+                    active.is_some_and(|active| active.eq_ignore_ascii_case(&account.address));
+                let switch_address = account.address.clone();
+                let remove_address = account.address.clone();
+                div()
+                    .id(ElementId::Name(
+                        format!("settings-account-{}", account.address).into(),
+                    ))
+                    .h_flex()
+                    .items_center()
+                    .gap_3()
+                    .py(px(6.))
+                    .border_t_1()
+                    .border_color(BORDER_SUBTLE())
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_base()
+                            .text_color(if is_active {
+                                TEXT_PRIMARY()
+                            } else {
+                                TEXT_MUTED()
+                            })
+                            .child(abbreviate_address(&account.address)),
+                    )
+                    .when(is_active, |el| {
+                        el.child(div().text_sm().text_color(ACCENT_BLUE()).child("Active"))
+                    })
+                    .when(!is_active, |el| {
+                        el.child(
+                            div()
+                                .id(ElementId::Name(
+                                    format!("settings-account-switch-{}", account.address).into(),
+                                ))
+                                .text_sm()
+                                .text_color(ACCENT_BLUE())
+                                .cursor_pointer()
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.switch_account(switch_address.clone(), cx)
+                                }))
+                                .child("Switch"),
+                        )
+                    })
+                    .child(
+                        div()
+                            .id(ElementId::Name(
+                                format!("settings-account-remove-{}", account.address).into(),
+                            ))
+                            .text_sm()
+                            .text_color(TEXT_MUTED())
+                            .cursor_pointer()
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.remove_saved_account(remove_address.clone(), cx)
+                            }))
+                            .child("Remove"),
+                    )
+                    .into_any_element()
+            })
+            .collect();
+
+        div()
+            .v_flex()
+            .child(
+                div()
+                    .pb(px(6.))
+                    .text_base()
+                    .text_color(TEXT_MUTED())
+                    .child("Accounts"),
             )
+            .children(rows)
+            .into_any_element()
     }
 
     fn render_appearance_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -191,6 +294,485 @@ impl SettingsView {
             )
     }
 
+    /// Backup, recovery and linked devices for the key that opens tracks shared with us.
+    fn render_content_key_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = self.content_key_status.as_ref();
+        let secondary = status.is_some_and(|status| status.secondary);
+        let device_rows: Vec<AnyElement> = status
+            .map(|status| status.linked_devices.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|device| {
+                let fingerprint = device.fingerprint.clone();
+                div()
+                    .id(ElementId::Name(
+                        format!("settings-linked-device-{}", device.fingerprint).into(),
+                    ))
+                    .h_flex()
+                    .items_center()
+                    .gap_3()
+                    .py(px(6.))
+                    .border_t_1()
+                    .border_color(BORDER_SUBTLE())
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_base()
+                            .text_color(TEXT_PRIMARY())
+                            .child(device.fingerprint.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(TEXT_MUTED())
+                            .child(format!("{} keys synced", device.synced_keys)),
+                    )
+                    .child(
+                        div()
+                            .id(ElementId::Name(
+                                format!("settings-linked-device-unlink-{}", device.fingerprint)
+                                    .into(),
+                            ))
+                            .text_sm()
+                            .text_color(TEXT_MUTED())
+                            .cursor_pointer()
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.unlink_content_key_device(fingerprint.clone(), cx)
+                            }))
+                            .child("Unlink"),
+                    )
+                    .into_any_element()
+            })
+            .collect();
+
+        div()
+            .v_flex()
+            .gap_3()
+            .child(section_heading("Content Key"))
+            .child(
+                div()
+                    .v_flex()
+                    .gap_3()
+                    .p_4()
+                    .rounded(px(6.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .child(div().text_sm().text_color(TEXT_DIM()).child(
+                        "Opens tracks shared with you. Back it up so they stay readable if this device is lost.",
+                    ))
+                    .child(match status {
+                        Some(status) => div()
+                            .v_flex()
+                            .gap_1()
+                            .child(info_line("Fingerprint", &status.fingerprint))
+                            .child(info_line(
+                                "Role",
+                                if status.secondary {
+                                    "Linked (receives keys from another device)"
+                                } else {
+                                    "Primary"
+                                },
+                            ))
+                            .child(info_line("Cached keys", &status.cached_keys.to_string()))
+                            .into_any_element(),
+                        None => action_button(
+                            "Load Content Key",
+                            !self.busy,
+                            false,
+                            cx.listener(|this, _, _, cx| this.refresh_content_key_status(cx)),
+                        )
+                        .into_any_element(),
+                    })
+                    // Passphrase backup
+                    .child(
+                        div()
+                            .h_flex()
+                            .items_center()
+                            .gap_2()
+                            .child(text_field(&self.content_key_passphrase_input))
+                            .child(action_button(
+                                "Export Backup",
+                                !self.busy,
+                                true,
+                                cx.listener(|this, _, window, cx| {
+                                    this.export_content_key_backup(window, cx)
+                                }),
+                            ))
+                            .child(action_button(
+                                "Import Backup",
+                                !self.busy,
+                                false,
+                                cx.listener(|this, _, window, cx| {
+                                    this.import_content_key_backup(window, cx)
+                                }),
+                            )),
+                    )
+                    // Recovery phrase
+                    .child(
+                        div()
+                            .h_flex()
+                            .items_center()
+                            .gap_2()
+                            .child(text_field(&self.content_key_phrase_input))
+                            .child(action_button(
+                                "Restore From Phrase",
+                                !self.busy,
+                                false,
+                                cx.listener(|this, _, window, cx| {
+                                    this.restore_content_key_from_phrase(window, cx)
+                                }),
+                            ))
+                            .child(action_button(
+                                if self.content_key_phrase.is_some() {
+                                    "Hide Recovery Phrase"
+                                } else {
+                                    "Show Recovery Phrase"
+                                },
+                                !self.busy,
+                                false,
+                                cx.listener(|this, _, _, cx| this.toggle_content_key_phrase(cx)),
+                            )),
+                    )
+                    .when_some(self.content_key_phrase.clone(), |el, phrase| {
+                        el.child(result_box("Recovery phrase (keep offline)", &phrase))
+                    })
+                    .when_some(
+                        self.content_key_pending_restore
+                            .as_ref()
+                            .map(|pending| pending.published_fingerprint.clone()),
+                        |el, published| {
+                            el.child(
+                                div()
+                                    .v_flex()
+                                    .gap_2()
+                                    .child(div().text_sm().text_color(ACCENT_RED()).child(format!(
+                                        "Your account publishes content key {published}. Replacing it points new shares at the restored key; tracks already shared to {published} stay readable only if this device holds their keys."
+                                    )))
+                                    .child(
+                                        div()
+                                            .h_flex()
+                                            .gap_2()
+                                            .child(action_button(
+                                                "Replace Published Key",
+                                                !self.busy,
+                                                true,
+                                                cx.listener(|this, _, _, cx| {
+                                                    this.confirm_content_key_restore(cx)
+                                                }),
+                                            ))
+                                            .child(action_button(
+                                                "Cancel Restore",
+                                                !self.busy,
+                                                false,
+                                                cx.listener(|this, _, _, cx| {
+                                                    this.cancel_content_key_restore(cx)
+                                                }),
+                                            )),
+                                    ),
+                            )
+                        },
+                    )
+                    // Linked devices
+                    .child(
+                        div()
+                            .h_flex()
+                            .items_center()
+                            .gap_2()
+                            .child(text_field(&self.content_key_device_input))
+                            .child(action_button(
+                                "Link Device",
+                                !self.busy && !secondary,
+                                true,
+                                cx.listener(|this, _, window, cx| {
+                                    this.link_content_key_device(window, cx)
+                                }),
+                            ))
+                            .child(action_button(
+                                "Sync Devices",
+                                !self.busy && !device_rows.is_empty(),
+                                false,
+                                cx.listener(|this, _, _, cx| this.sync_content_key_devices(cx)),
+                            )),
+                    )
+                    .when(status.is_some(), |el| {
+                        el.child(
+                            div()
+                                .h_flex()
+                                .gap_2()
+                                .child(action_button(
+                                    "Copy Public Key",
+                                    true,
+                                    false,
+                                    cx.listener(|this, _, _, cx| this.copy_content_public_key(cx)),
+                                ))
+                                .child(action_button(
+                                    if secondary {
+                                        "Use as Primary"
+                                    } else {
+                                        "Use as Linked Device"
+                                    },
+                                    !self.busy,
+                                    false,
+                                    cx.listener(move |this, _, _, cx| {
+                                        this.set_content_key_secondary(!secondary, cx)
+                                    }),
+                                )),
+                        )
+                    })
+                    .when(!device_rows.is_empty(), |el| {
+                        el.child(
+                            div()
+                                .v_flex()
+                                .child(
+                                    div()
+                                        .pb(px(6.))
+                                        .text_base()
+                                        .text_color(TEXT_MUTED())
+                                        .child("Linked devices"),
+                                )
+                                .children(device_rows),
+                        )
+                    }),
+            )
+    }
+
+    fn render_scrobble_accounts_section(
+        &self,
+        is_authed: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let status = self.scrobble_accounts;
+        let connected = |yes: bool| if yes { "Connected" } else { "Not connected" };
+
+        div()
+            .v_flex()
+            .gap_3()
+            .child(section_heading("Scrobble Accounts"))
+            .child(
+                div()
+                    .v_flex()
+                    .gap_3()
+                    .p_4()
+                    .rounded(px(6.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .child(div().text_sm().text_color(TEXT_DIM()).child(
+                        "Tokens for ListenBrainz and Last.fm, kept in secret storage for the signed-in account. Turn each destination on from the library menu.",
+                    ))
+                    .child(match status {
+                        Some(status) => div()
+                            .v_flex()
+                            .gap_1()
+                            .child(info_line("ListenBrainz", connected(status.listenbrainz)))
+                            .child(info_line(
+                                "Last.fm",
+                                if status.lastfm_session {
+                                    "Connected"
+                                } else if status.lastfm_app {
+                                    "API key saved, not authorized"
+                                } else {
+                                    "Not connected"
+                                },
+                            ))
+                            .into_any_element(),
+                        None => action_button(
+                            "Load Scrobble Accounts",
+                            !self.busy && is_authed,
+                            false,
+                            cx.listener(|this, _, _, cx| this.refresh_scrobble_accounts(cx)),
+                        )
+                        .into_any_element(),
+                    })
+                    // ListenBrainz user token
+                    .child(
+                        div()
+                            .h_flex()
+                            .items_center()
+                            .gap_2()
+                            .child(text_field(&self.listenbrainz_token_input))
+                            .child(action_button(
+                                "Save Token",
+                                !self.busy && is_authed,
+                                true,
+                                cx.listener(|this, _, window, cx| {
+                                    this.save_listenbrainz_token(window, cx)
+                                }),
+                            ))
+                            .when(status.is_some_and(|status| status.listenbrainz), |el| {
+                                el.child(action_button(
+                                    "Disconnect ListenBrainz",
+                                    !self.busy,
+                                    false,
+                                    cx.listener(|this, _, _, cx| this.disconnect_listenbrainz(cx)),
+                                ))
+                            }),
+                    )
+                    // Last.fm desktop auth
+                    .child(
+                        div()
+                            .h_flex()
+                            .items_center()
+                            .gap_2()
+                            .child(text_field(&self.lastfm_api_key_input))
+                            .child(text_field(&self.lastfm_api_secret_input))
+                            .child(action_button(
+                                "Connect Last.fm",
+                                !self.busy && is_authed,
+                                self.lastfm_pending_token.is_none(),
+                                cx.listener(|this, _, window, cx| this.connect_lastfm(window, cx)),
+                            ))
+                            .when(self.lastfm_pending_token.is_some(), |el| {
+                                el.child(action_button(
+                                    "Finish Connecting",
+                                    !self.busy,
+                                    true,
+                                    cx.listener(|this, _, _, cx| this.complete_lastfm_connect(cx)),
+                                ))
+                            })
+                            .when(
+                                status.is_some_and(|status| status.lastfm_app || status.lastfm_session),
+                                |el| {
+                                    el.child(action_button(
+                                        "Disconnect Last.fm",
+                                        !self.busy,
+                                        false,
+                                        cx.listener(|this, _, _, cx| this.disconnect_lastfm(cx)),
+                                    ))
+                                },
+                            ),
+                    )
+                    .when(self.lastfm_pending_token.is_some(), |el| {
+                        el.child(div().text_sm().text_color(TEXT_MUTED()).child(
+                            "Approve Heaven on the Last.fm page that opened, then press Finish Connecting. Leave the key fields empty to reuse the saved ones.",
+                        ))
+                    }),
+            )
+    }
+
+    fn render_cache_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        const GB: u64 = 1024 * 1024 * 1024;
+        const CAP_PRESETS: [(&str, u64); 5] = [
+            ("512 MB", GB / 2),
+            ("1 GB", GB),
+            ("2 GB", 2 * GB),
+            ("5 GB", 5 * GB),
+            ("10 GB", 10 * GB),
+        ];
+        let usage = self.content_cache_usage.as_ref();
+        let encrypted = usage.is_some_and(|usage| usage.settings.encrypt_at_rest);
+
+        div()
+            .v_flex()
+            .gap_3()
+            .child(section_heading("Cache"))
+            .child(
+                div()
+                    .v_flex()
+                    .gap_3()
+                    .p_4()
+                    .rounded(px(6.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .child(div().text_sm().text_color(TEXT_DIM()).child(
+                        "Decrypted shared tracks and downloaded artwork. The least recently played files are removed past the limit.",
+                    ))
+                    .child(match usage {
+                        Some(usage) => {
+                            let (lyrics_rows, lyrics_bytes) =
+                                self.lyrics_cache_usage.unwrap_or_default();
+                            div()
+                                .v_flex()
+                                .gap_1()
+                                .child(info_line(
+                                    "Used",
+                                    &format!(
+                                        "{} of {}",
+                                        format_bytes(usage.total_bytes()),
+                                        format_bytes(usage.settings.max_bytes)
+                                    ),
+                                ))
+                                .child(info_line(
+                                    "Shared tracks",
+                                    &format!(
+                                        "{} ({} files)",
+                                        format_bytes(usage.audio_bytes),
+                                        usage.audio_files
+                                    ),
+                                ))
+                                .child(info_line(
+                                    "Artwork",
+                                    &format!(
+                                        "{} ({} files)",
+                                        format_bytes(usage.cover_bytes),
+                                        usage.cover_files
+                                    ),
+                                ))
+                                .child(info_line(
+                                    "Lyrics",
+                                    &format!("{} ({lyrics_rows} entries)", format_bytes(lyrics_bytes)),
+                                ))
+                                .into_any_element()
+                        }
+                        None => action_button(
+                            "Load Cache Usage",
+                            !self.busy,
+                            false,
+                            cx.listener(|this, _, _, cx| this.refresh_cache_usage(cx)),
+                        )
+                        .into_any_element(),
+                    })
+                    .when_some(usage, |el, usage| {
+                        el.child(
+                            div()
+                                .h_flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(TEXT_DIM())
+                                        .min_w(px(120.))
+                                        .child("Limit:"),
+                                )
+                                .children(CAP_PRESETS.map(|(label, max_bytes)| {
+                                    action_button(
+                                        label,
+                                        !self.busy,
+                                        usage.settings.max_bytes == max_bytes,
+                                        cx.listener(move |this, _, _, cx| {
+                                            this.set_cache_max_bytes(max_bytes, cx)
+                                        }),
+                                    )
+                                })),
+                        )
+                        .child(
+                            div()
+                                .h_flex()
+                                .gap_2()
+                                .child(action_button(
+                                    if encrypted {
+                                        "Store Decrypted"
+                                    } else {
+                                        "Encrypt at Rest"
+                                    },
+                                    !self.busy,
+                                    false,
+                                    cx.listener(|this, _, _, cx| this.toggle_cache_encryption(cx)),
+                                ))
+                                .child(action_button(
+                                    "Clear Cache",
+                                    !self.busy,
+                                    false,
+                                    cx.listener(|this, _, _, cx| this.clear_content_cache(cx)),
+                                )),
+                        )
+                    }),
+            )
+    }
+
     fn render_dev_tools_section(
         &self,
         addr: &str,
@@ -348,6 +930,36 @@ impl SettingsView {
     }
 }
 
+/// Where keys and tokens are kept, with a warning when only the machine identity guards them.
+fn secret_storage_row() -> impl IntoElement {
+    let store = crate::secret_store::SecretStore::shared();
+    div()
+        .v_flex()
+        .gap_2()
+        .pt_3()
+        .border_t_1()
+        .border_color(BORDER_SUBTLE())
+        .child(
+            div()
+                .text_base()
+                .text_color(TEXT_MUTED())
+                .child("Secret storage"),
+        )
+        .child(
+            div()
+                .text_base()
+                .text_color(TEXT_PRIMARY())
+                .child(store.backend_name()),
+        )
+        .when(store.machine_bound(), |el| {
+            el.child(div().text_sm().text_color(ACCENT_RED()).child(
+                "No keyring was reachable, so keys and tokens are in a file encrypted only with \
+                 this machine's identity; anything that can read your files can open it. Set \
+                 HEAVEN_SECRET_PASSPHRASE and restart to protect it with a passphrase.",
+            ))
+        })
+}
+
 fn section_heading(label: &str) -> impl IntoElement {
     div()
         .text_base()
@@ -0,0 +1,733 @@
+//! Gateway pool for content fetched from Load, Arweave and IPFS.
+//!
+//! Each gateway keeps a running time-to-first-byte estimate and a decaying error rate. A
+//! fetch asks the best ranked gateway first and, when it is slow to start answering, hedges
+//! with the next one; the first body that passes its integrity check wins and the other
+//! requests are cancelled. Gateways configured in the environment rank ahead of the
+//! built-in defaults, and replace them entirely when `HEAVEN_GATEWAYS_STRICT` is set.
+
+mod cid;
+mod health;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+
+use super::config::{bool_env, non_empty_env};
+use super::rpc::{http_get_bytes, http_timeout, HttpError};
+use health::{FailureKind, GatewayHealth};
+
+const DEFAULT_LOAD_GATEWAYS: &[&str] = &["https://gateway.s3-node-1.load.network"];
+const DEFAULT_ARWEAVE_GATEWAYS: &[&str] = &["https://arweave.net"];
+const DEFAULT_IPFS_GATEWAYS: &[&str] = &["https://ipfs.io", "https://dweb.link"];
+/// Requests one fetch may have in flight at once.
+const MAX_PARALLEL: usize = 3;
+const MIN_HEDGE_DELAY: Duration = Duration::from_millis(250);
+const MAX_HEDGE_DELAY: Duration = Duration::from_secs(3);
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Checks a body beyond its digest, e.g. that it is an encrypted payload and not an
+/// error page served with a 200.
+pub type ContentCheck = dyn Fn(&[u8]) -> Result<(), String> + Sync;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GatewayKind {
+    Load,
+    Arweave,
+    Ipfs,
+}
+
+impl GatewayKind {
+    const ALL: [GatewayKind; 3] = [Self::Load, Self::Arweave, Self::Ipfs];
+
+    /// The single-URL variable the app has always read, and the comma-separated list.
+    fn env_keys(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Load => ("HEAVEN_LOAD_GATEWAY_URL", "HEAVEN_LOAD_GATEWAYS"),
+            Self::Arweave => ("HEAVEN_ARWEAVE_GATEWAY_URL", "HEAVEN_ARWEAVE_GATEWAYS"),
+            Self::Ipfs => ("HEAVEN_IPFS_GATEWAY_URL", "HEAVEN_IPFS_GATEWAYS"),
+        }
+    }
+
+    fn defaults(self) -> &'static [&'static str] {
+        match self {
+            Self::Load => DEFAULT_LOAD_GATEWAYS,
+            Self::Arweave => DEFAULT_ARWEAVE_GATEWAYS,
+            Self::Ipfs => DEFAULT_IPFS_GATEWAYS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Gateway {
+    kind: GatewayKind,
+    /// Without a trailing slash, and for IPFS without the `/ipfs` path.
+    base: String,
+    preferred: bool,
+}
+
+impl Gateway {
+    fn new(kind: GatewayKind, url: &str, preferred: bool) -> Option<Self> {
+        let mut base = url.trim().trim_end_matches('/');
+        if kind == GatewayKind::Ipfs {
+            base = base.trim_end_matches("/ipfs");
+        }
+        if !(base.starts_with("https://") || base.starts_with("http://")) {
+            return None;
+        }
+        Some(Self {
+            kind,
+            base: base.to_string(),
+            preferred,
+        })
+    }
+}
+
+/// Something a gateway can serve: a Load data item, an Arweave transaction or an IPFS CID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayResource {
+    kind: GatewayKind,
+    id: String,
+    /// Path or query after the id (e.g. image transforms), kept when failing over.
+    suffix: String,
+}
+
+impl GatewayResource {
+    pub fn load(id: &str) -> Self {
+        Self::new(GatewayKind::Load, id, "")
+    }
+
+    pub fn arweave(id: &str) -> Self {
+        Self::new(GatewayKind::Arweave, id, "")
+    }
+
+    fn new(kind: GatewayKind, id: &str, suffix: &str) -> Self {
+        Self {
+            kind,
+            id: id.trim().to_string(),
+            suffix: suffix.to_string(),
+        }
+    }
+
+    /// An `ar://`, `ls3://`, `load-s3://` or `ipfs://` storage ref.
+    pub fn from_storage_ref(raw_ref: &str) -> Option<Self> {
+        let raw = raw_ref.trim();
+        let (kind, id) = if let Some(id) = raw.strip_prefix("ar://") {
+            (GatewayKind::Arweave, id)
+        } else if let Some(id) = raw
+            .strip_prefix("ls3://")
+            .or_else(|| raw.strip_prefix("load-s3://"))
+        {
+            (GatewayKind::Load, id)
+        } else {
+            (GatewayKind::Ipfs, raw.strip_prefix("ipfs://")?)
+        };
+        let (id, suffix) = split_id(id);
+        (!id.is_empty()).then(|| Self::new(kind, id, suffix))
+    }
+
+    fn url_on(&self, gateway: &Gateway) -> String {
+        match gateway.kind {
+            GatewayKind::Load => format!("{}/resolve/{}{}", gateway.base, self.id, self.suffix),
+            GatewayKind::Arweave => format!("{}/{}{}", gateway.base, self.id, self.suffix),
+            GatewayKind::Ipfs => format!("{}/ipfs/{}{}", gateway.base, self.id, self.suffix),
+        }
+    }
+
+    /// Gateway kinds that serve this resource, in order. Load items are bundled onto
+    /// Arweave, so Arweave gateways back up the Load ones.
+    fn served_by(&self) -> &'static [GatewayKind] {
+        match self.kind {
+            GatewayKind::Load => &[GatewayKind::Load, GatewayKind::Arweave],
+            GatewayKind::Arweave => &[GatewayKind::Arweave],
+            GatewayKind::Ipfs => &[GatewayKind::Ipfs],
+        }
+    }
+
+    /// The digest the id itself commits to. Transformed bodies (a suffix) differ from it.
+    fn intrinsic_sha256(&self) -> Option<String> {
+        match self.kind {
+            GatewayKind::Ipfs if self.suffix.is_empty() => cid::raw_cid_sha256(&self.id),
+            _ => None,
+        }
+    }
+}
+
+/// What a fetched body must satisfy besides what its id commits to.
+#[derive(Default, Clone, Copy)]
+pub struct Integrity<'a> {
+    /// Expected sha256 of the body, hex.
+    pub sha256: Option<&'a str>,
+    pub check: Option<&'a ContentCheck>,
+}
+
+impl Integrity<'_> {
+    fn verify(&self, resource: &GatewayResource, bytes: &[u8]) -> Result<(), String> {
+        let intrinsic = resource.intrinsic_sha256();
+        let expected = self.sha256.or(intrinsic.as_deref());
+        if let Some(expected) = expected {
+            let actual = hex::encode(Sha256::digest(bytes));
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                return Err(format!("sha256 mismatch (got {actual}, want {expected})"));
+            }
+        }
+        match self.check {
+            Some(check) => check(bytes),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GatewayFetch {
+    pub bytes: Vec<u8>,
+    pub url: String,
+}
+
+/// A body being read as it arrives, for payloads too large to buffer.
+pub struct GatewayStream {
+    pub reader: Box<dyn Read>,
+    pub url: String,
+}
+
+enum AttemptEvent {
+    /// The gateway answered with a success status; its body is on the way.
+    Started(usize),
+    Finished(usize, Result<Vec<u8>, HttpError>, Duration),
+}
+
+pub struct GatewayPool {
+    gateways: Vec<Gateway>,
+    health: Mutex<HashMap<String, GatewayHealth>>,
+}
+
+impl GatewayPool {
+    /// Process-wide pool, configured from the environment on first use.
+    pub fn shared() -> &'static GatewayPool {
+        static SHARED: OnceLock<GatewayPool> = OnceLock::new();
+        SHARED.get_or_init(Self::from_env)
+    }
+
+    fn from_env() -> Self {
+        let strict = bool_env("HEAVEN_GATEWAYS_STRICT");
+        let mut gateways = Vec::new();
+        for kind in GatewayKind::ALL {
+            let (single, list) = kind.env_keys();
+            let configured = non_empty_env(single)
+                .into_iter()
+                .chain(non_empty_env(list))
+                .flat_map(|value| {
+                    value
+                        .split(',')
+                        .filter_map(|url| Gateway::new(kind, url, true))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let use_defaults = !strict || configured.is_empty();
+            gateways.extend(configured);
+            if use_defaults {
+                gateways.extend(
+                    kind.defaults()
+                        .iter()
+                        .filter_map(|url| Gateway::new(kind, url, false)),
+                );
+            }
+        }
+        Self::new(gateways)
+    }
+
+    fn new(gateways: Vec<Gateway>) -> Self {
+        let mut unique = Vec::<Gateway>::new();
+        for gateway in gateways {
+            if !unique
+                .iter()
+                .any(|seen| seen.kind == gateway.kind && seen.base == gateway.base)
+            {
+                unique.push(gateway);
+            }
+        }
+        Self {
+            gateways: unique,
+            health: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Base URL of the best ranked gateway of `kind`, for building URLs handed to other
+    /// clients (the GPUI image loader comes back through [`Self::resource_for_url`]).
+    pub fn preferred_base(&self, kind: GatewayKind) -> Option<String> {
+        let resource = GatewayResource::new(kind, "", "");
+        self.ranked(&resource, None)
+            .into_iter()
+            .find(|gateway| gateway.kind == kind)
+            .map(|gateway| gateway.base)
+    }
+
+    /// The resource behind `url` when it points at a pooled gateway.
+    pub fn resource_for_url(&self, url: &str) -> Option<GatewayResource> {
+        self.gateways.iter().find_map(|gateway| {
+            let path = url.strip_prefix(&gateway.base)?;
+            let rest = match gateway.kind {
+                GatewayKind::Load => path.strip_prefix("/resolve/")?,
+                GatewayKind::Ipfs => path.strip_prefix("/ipfs/")?,
+                GatewayKind::Arweave => path.strip_prefix('/')?,
+            };
+            let (id, suffix) = split_id(rest);
+            // Arweave gateways also serve APIs (`/graphql`, `/info`); ids are 43 base64url chars.
+            let plausible = match gateway.kind {
+                GatewayKind::Arweave => is_arweave_id(id),
+                _ => !id.is_empty(),
+            };
+            plausible.then(|| GatewayResource::new(gateway.kind, id, suffix))
+        })
+    }
+
+    /// GET `url`, through the pool when it points at a pooled gateway.
+    pub fn get_bytes(&self, url: &str) -> Result<Vec<u8>, HttpError> {
+        match self.resource_for_url(url) {
+            Some(resource) => self
+                .fetch(&resource, Integrity::default(), None)
+                .map(|fetched| fetched.bytes),
+            None => http_get_bytes(url),
+        }
+    }
+
+    /// Fetch `resource`, best gateways first, hedging when the current ones are slow to
+    /// answer. `hint` is a gateway (or a URL on one) to rank first, such as the one an
+    /// upload went through.
+    pub fn fetch(
+        &self,
+        resource: &GatewayResource,
+        integrity: Integrity<'_>,
+        hint: Option<&str>,
+    ) -> Result<GatewayFetch, HttpError> {
+        let candidates = self.ranked(resource, hint);
+        if candidates.is_empty() {
+            return Err(HttpError::Response(format!(
+                "No gateway configured for {:?} content.",
+                resource.kind
+            )));
+        }
+        let urls = candidates
+            .iter()
+            .map(|gateway| resource.url_on(gateway))
+            .collect::<Vec<_>>();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel::<AttemptEvent>();
+        let launch = |index: usize| {
+            let url = urls[index].clone();
+            let tx = tx.clone();
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                let started = Instant::now();
+                let result = fetch_cancellable(&url, &cancel, || {
+                    let _ = tx.send(AttemptEvent::Started(index));
+                    started.elapsed()
+                });
+                if !cancel.load(Ordering::Relaxed) {
+                    let (result, first_byte) = match result {
+                        Ok((bytes, first_byte)) => (Ok(bytes), first_byte),
+                        Err(err) => (Err(err), started.elapsed()),
+                    };
+                    let _ = tx.send(AttemptEvent::Finished(index, result, first_byte));
+                }
+            });
+        };
+
+        let mut next = 0;
+        let mut in_flight = 0;
+        let mut streaming = 0;
+        let mut started = vec![false; candidates.len()];
+        let mut errors = Vec::<HttpError>::new();
+        let outcome = loop {
+            if in_flight == 0 {
+                if next == candidates.len() {
+                    break Err(all_gateways_failed(&resource.id, errors));
+                }
+                launch(next);
+                next += 1;
+                in_flight += 1;
+            }
+            // Hedge only while nothing has started streaming a body.
+            let can_hedge = streaming == 0 && next < candidates.len() && in_flight < MAX_PARALLEL;
+            let event = if can_hedge {
+                rx.recv_timeout(self.hedge_delay(&candidates[next - 1]))
+            } else {
+                rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+            match event {
+                Err(RecvTimeoutError::Timeout) => {
+                    log::debug!("[Gateways] hedging {} with {}", resource.id, urls[next]);
+                    launch(next);
+                    next += 1;
+                    in_flight += 1;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Err(HttpError::Transport(
+                        "Gateway workers disconnected".to_string(),
+                    ));
+                }
+                Ok(AttemptEvent::Started(index)) => {
+                    started[index] = true;
+                    streaming += 1;
+                }
+                Ok(AttemptEvent::Finished(index, result, first_byte)) => {
+                    in_flight -= 1;
+                    if started[index] {
+                        streaming -= 1;
+                    }
+                    let gateway = &candidates[index];
+                    let failure = match result {
+                        Ok(bytes) => match integrity.verify(resource, &bytes) {
+                            Ok(()) => {
+                                self.record_success(gateway, first_byte);
+                                break Ok(GatewayFetch {
+                                    bytes,
+                                    url: urls[index].clone(),
+                                });
+                            }
+                            Err(err) => (
+                                FailureKind::Corrupt,
+                                HttpError::Response(format!("{}: {err}", urls[index])),
+                            ),
+                        },
+                        Err(err) => (FailureKind::Unreachable, err),
+                    };
+                    log::warn!(
+                        "[Gateways] {} failed ({:?}): {}",
+                        urls[index],
+                        failure.0,
+                        failure.1
+                    );
+                    self.record_failure(gateway, failure.0);
+                    errors.push(failure.1);
+                    // Replace the failed attempt now rather than waiting out a hedge delay.
+                    if in_flight > 0 && streaming == 0 && next < candidates.len() {
+                        launch(next);
+                        next += 1;
+                        in_flight += 1;
+                    }
+                }
+            }
+        };
+        cancel.store(true, Ordering::Relaxed);
+        outcome
+    }
+
+    /// Open `resource` on the best ranked gateway that answers, without reading the body.
+    /// Nothing is hedged or verified here; the caller checks the bytes as it reads them.
+    pub fn open(
+        &self,
+        resource: &GatewayResource,
+        hint: Option<&str>,
+    ) -> Result<GatewayStream, HttpError> {
+        let candidates = self.ranked(resource, hint);
+        if candidates.is_empty() {
+            return Err(HttpError::Response(format!(
+                "No gateway configured for {:?} content.",
+                resource.kind
+            )));
+        }
+        let mut errors = Vec::<HttpError>::new();
+        for gateway in &candidates {
+            let url = resource.url_on(gateway);
+            let started = Instant::now();
+            let request = ureq::get(&url)
+                .config()
+                .timeout_recv_response(Some(http_timeout()))
+                .http_status_as_error(false)
+                .build();
+            let failure = match request.call() {
+                Ok(resp) if resp.status().as_u16() < 400 => {
+                    self.record_success(gateway, started.elapsed());
+                    return Ok(GatewayStream {
+                        reader: Box::new(resp.into_body().into_reader()),
+                        url,
+                    });
+                }
+                Ok(resp) => {
+                    let status = resp.status().as_u16();
+                    HttpError::Status(status, format!("HTTP GET {url} failed ({status})"))
+                }
+                Err(e) => HttpError::Transport(format!("HTTP GET failed ({url}): {e}")),
+            };
+            log::warn!("[Gateways] {failure}");
+            self.record_failure(gateway, FailureKind::Unreachable);
+            errors.push(failure);
+        }
+        Err(all_gateways_failed(&resource.id, errors))
+    }
+
+    fn ranked(&self, resource: &GatewayResource, hint: Option<&str>) -> Vec<Gateway> {
+        let mut candidates = hint
+            .and_then(|hint| Gateway::new(resource.kind, hint_base(hint), true))
+            .filter(|_| resource.kind != GatewayKind::Ipfs)
+            .into_iter()
+            .collect::<Vec<_>>();
+        for kind in resource.served_by() {
+            for gateway in self.gateways.iter().filter(|g| g.kind == *kind) {
+                if !candidates.iter().any(|seen| seen.base == gateway.base) {
+                    candidates.push(gateway.clone());
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let rank = |gateway: &Gateway| {
+            let stats = health.get(&gateway.base).cloned().unwrap_or_default();
+            let kind_order = resource
+                .served_by()
+                .iter()
+                .position(|kind| *kind == gateway.kind)
+                .unwrap_or(usize::MAX);
+            (
+                stats.cooling_down(now),
+                kind_order,
+                !gateway.preferred,
+                stats.score(),
+            )
+        };
+        candidates.sort_by(|a, b| {
+            rank(a)
+                .partial_cmp(&rank(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        candidates
+    }
+
+    /// Wait for about twice the gateway's usual time to first byte before hedging.
+    fn hedge_delay(&self, gateway: &Gateway) -> Duration {
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let expected = health
+            .get(&gateway.base)
+            .map(GatewayHealth::expected_latency)
+            .unwrap_or_else(|| GatewayHealth::default().expected_latency());
+        (expected * 2).clamp(MIN_HEDGE_DELAY, MAX_HEDGE_DELAY)
+    }
+
+    fn record_success(&self, gateway: &Gateway, first_byte: Duration) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        health
+            .entry(gateway.base.clone())
+            .or_default()
+            .record_success(first_byte);
+    }
+
+    fn record_failure(&self, gateway: &Gateway, kind: FailureKind) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        health
+            .entry(gateway.base.clone())
+            .or_default()
+            .record_failure(kind, Instant::now());
+    }
+}
+
+/// GET `url`, giving up between body chunks once `cancel` is set. `on_start` runs when a
+/// success status arrives and returns the time to first byte.
+fn fetch_cancellable(
+    url: &str,
+    cancel: &AtomicBool,
+    on_start: impl FnOnce() -> Duration,
+) -> Result<(Vec<u8>, Duration), HttpError> {
+    let request = ureq::get(url)
+        .config()
+        .timeout_global(Some(http_timeout()))
+        .http_status_as_error(false)
+        .build();
+    let mut resp = request
+        .call()
+        .map_err(|e| HttpError::Transport(format!("HTTP GET failed ({url}): {e}")))?;
+    let status = resp.status().as_u16();
+    if status >= 400 {
+        return Err(HttpError::Status(
+            status,
+            format!("HTTP GET {url} failed ({status})"),
+        ));
+    }
+    let first_byte = on_start();
+
+    let mut reader = resp.body_mut().as_reader();
+    let mut bytes = Vec::new();
+    let mut chunk = vec![0u8; READ_CHUNK];
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(HttpError::Transport("cancelled".to_string()));
+        }
+        let n = reader
+            .read(&mut chunk)
+            .map_err(|e| HttpError::Transport(format!("Failed reading HTTP body ({url}): {e}")))?;
+        if n == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..n]);
+    }
+    Ok((bytes, first_byte))
+}
+
+/// One error for a resource no gateway served. It keeps the status when every gateway
+/// answered with the same one (say, all 404), is a `Response` error when every gateway
+/// answered with a bad body, and a `Transport` error otherwise.
+fn all_gateways_failed(id: &str, errors: Vec<HttpError>) -> HttpError {
+    let message = format!(
+        "All gateways failed for {id}: {}",
+        errors
+            .iter()
+            .map(HttpError::message)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    let shared_status = errors
+        .first()
+        .and_then(HttpError::status)
+        .filter(|status| errors.iter().all(|err| err.status() == Some(*status)));
+    if let Some(status) = shared_status {
+        return HttpError::Status(status, message);
+    }
+    if errors
+        .iter()
+        .all(|err| matches!(err, HttpError::Response(_)))
+    {
+        return HttpError::Response(message);
+    }
+    HttpError::Transport(message)
+}
+
+/// Split `<id>[/path][?query]` into the id and what follows it.
+fn split_id(rest: &str) -> (&str, &str) {
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    (rest[..end].trim(), &rest[end..])
+}
+
+/// The gateway part of a hint that may be a full `/resolve/<id>` URL.
+fn hint_base(hint: &str) -> &str {
+    let hint = hint.trim();
+    hint.split_once("/resolve/").map_or(hint, |(base, _)| base)
+}
+
+fn is_arweave_id(id: &str) -> bool {
+    id.len() == 43
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_stub::{HttpStub, StubResponse};
+
+    /// Serves `body` for every request after `delay`.
+    fn spawn_gateway(body: &'static [u8], delay: Duration) -> String {
+        HttpStub::spawn(move |_| StubResponse::new(200, body).delayed(delay)).url
+    }
+
+    fn pool(bases: &[&str]) -> GatewayPool {
+        GatewayPool::new(
+            bases
+                .iter()
+                .filter_map(|base| Gateway::new(GatewayKind::Ipfs, base, false))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn skips_gateways_serving_bytes_that_miss_the_cid_digest() {
+        let corrupt = spawn_gateway(b"hellp", Duration::ZERO);
+        let honest = spawn_gateway(b"hello", Duration::ZERO);
+        let pool = pool(&[&corrupt, &honest]);
+        let resource = GatewayResource::new(
+            GatewayKind::Ipfs,
+            "bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq",
+            "",
+        );
+
+        let fetched = pool
+            .fetch(&resource, Integrity::default(), None)
+            .expect("honest gateway serves the block");
+        assert_eq!(fetched.bytes, b"hello");
+        assert!(fetched.url.starts_with(&honest));
+        // The corrupt gateway now ranks last.
+        assert_eq!(pool.ranked(&resource, None)[0].base, honest);
+    }
+
+    #[test]
+    fn hedges_a_slow_gateway_and_honors_custom_checks() {
+        let slow = spawn_gateway(b"slow body", Duration::from_secs(5));
+        let fast = spawn_gateway(b"fast body", Duration::ZERO);
+        let pool = pool(&[&slow, &fast]);
+        let resource = GatewayResource::new(
+            GatewayKind::Ipfs,
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+            "",
+        );
+
+        let started = Instant::now();
+        let fetched = pool
+            .fetch(&resource, Integrity::default(), None)
+            .expect("hedged request wins");
+        assert_eq!(fetched.bytes, b"fast body");
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let reject_all: &ContentCheck = &|_| Err("not a payload".to_string());
+        let integrity = Integrity {
+            sha256: None,
+            check: Some(reject_all),
+        };
+        assert!(matches!(
+            pool.fetch(&resource, integrity, None),
+            Err(HttpError::Response(_))
+        ));
+    }
+
+    #[test]
+    fn opens_the_first_gateway_that_answers() {
+        let down = "http://127.0.0.1:1".to_string();
+        let up = spawn_gateway(b"streamed body", Duration::ZERO);
+        let pool = pool(&[&down, &up]);
+        let resource = GatewayResource::new(
+            GatewayKind::Ipfs,
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+            "",
+        );
+
+        let mut opened = pool.open(&resource, None).expect("second gateway answers");
+        assert!(opened.url.starts_with(&up));
+        let mut body = Vec::new();
+        opened.reader.read_to_end(&mut body).unwrap();
+        assert_eq!(body, b"streamed body");
+        assert_eq!(pool.ranked(&resource, None)[0].base, up);
+    }
+
+    #[test]
+    fn maps_gateway_urls_back_to_resources() {
+        let pool = GatewayPool::new(vec![
+            Gateway::new(GatewayKind::Ipfs, "https://ipfs.example/ipfs/", false).unwrap(),
+            Gateway::new(GatewayKind::Arweave, "https://ar.example", false).unwrap(),
+        ]);
+        assert_eq!(
+            pool.resource_for_url("https://ipfs.example/ipfs/bafyabc?img-width=96"),
+            Some(GatewayResource::new(
+                GatewayKind::Ipfs,
+                "bafyabc",
+                "?img-width=96"
+            ))
+        );
+        let ar_id = "a".repeat(43);
+        assert_eq!(
+            pool.resource_for_url(&format!("https://ar.example/{ar_id}")),
+            Some(GatewayResource::new(GatewayKind::Arweave, &ar_id, ""))
+        );
+        assert!(pool
+            .resource_for_url("https://ar.example/graphql")
+            .is_none());
+        assert!(pool
+            .resource_for_url("https://other.example/ipfs/bafyabc")
+            .is_none());
+    }
+}
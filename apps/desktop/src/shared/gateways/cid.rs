@@ -0,0 +1,59 @@
+//! Just enough CID parsing to check a body against the digest its CID commits to.
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+const CID_V1: u8 = 0x01;
+const CODEC_RAW: u8 = 0x55;
+const MULTIHASH_SHA2_256: u8 = 0x12;
+
+/// The sha256 (hex) a CIDv1 with the raw codec commits to. Other CIDs (v0, dag-pb) name
+/// the root of a DAG rather than the bytes a gateway serves, so they cannot be checked here.
+pub(super) fn raw_cid_sha256(cid: &str) -> Option<String> {
+    let encoded = cid.trim().strip_prefix('b')?;
+    let bytes = decode_base32(encoded)?;
+    match bytes.as_slice() {
+        [CID_V1, CODEC_RAW, MULTIHASH_SHA2_256, 32, digest @ ..] if digest.len() == 32 => {
+            Some(hex::encode(digest))
+        }
+        _ => None,
+    }
+}
+
+/// Unpadded, lowercase RFC 4648 base32 (multibase `b`).
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+    for ch in input.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&c| c == ch.to_ascii_lowercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_digest_of_raw_cids_only() {
+        assert_eq!(
+            raw_cid_sha256("bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku")
+                .as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        // dag-pb (v1) and v0 CIDs address a DAG root.
+        assert!(
+            raw_cid_sha256("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").is_none()
+        );
+        assert!(raw_cid_sha256("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG").is_none());
+        assert!(raw_cid_sha256("b!!").is_none());
+    }
+}
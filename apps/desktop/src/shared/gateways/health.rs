@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+/// Time to first byte assumed for a gateway that has not answered yet.
+const LATENCY_PRIOR: Duration = Duration::from_millis(800);
+/// Weight of the newest sample in the latency average.
+const LATENCY_WEIGHT: f64 = 0.3;
+/// Share of the past outcome counts kept on each new outcome, so old errors fade.
+const OUTCOME_DECAY: f64 = 0.9;
+/// A corrupt body counts as this many failed requests.
+const CORRUPT_PENALTY: f64 = 3.0;
+const COOLDOWN_AFTER_FAILURES: u32 = 3;
+const BASE_COOLDOWN: Duration = Duration::from_secs(15);
+const MAX_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FailureKind {
+    /// Transport error or an error status.
+    Unreachable,
+    /// A body that failed its integrity check.
+    Corrupt,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct GatewayHealth {
+    latency_ms: Option<f64>,
+    successes: f64,
+    failures: f64,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+}
+
+impl GatewayHealth {
+    pub(super) fn record_success(&mut self, first_byte: Duration) {
+        let sample = first_byte.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(avg) => avg + LATENCY_WEIGHT * (sample - avg),
+            None => sample,
+        });
+        self.successes = self.successes * OUTCOME_DECAY + 1.0;
+        self.failures *= OUTCOME_DECAY;
+        self.consecutive_failures = 0;
+        self.cooldown_until = None;
+    }
+
+    pub(super) fn record_failure(&mut self, kind: FailureKind, now: Instant) {
+        let weight = match kind {
+            FailureKind::Unreachable => 1.0,
+            FailureKind::Corrupt => CORRUPT_PENALTY,
+        };
+        self.successes *= OUTCOME_DECAY;
+        self.failures = self.failures * OUTCOME_DECAY + weight;
+        self.consecutive_failures += 1;
+        // A gateway serving bad bytes sits out at once; an unreachable one after a streak.
+        let streak = match kind {
+            FailureKind::Corrupt => self.consecutive_failures.max(COOLDOWN_AFTER_FAILURES),
+            FailureKind::Unreachable => self.consecutive_failures,
+        };
+        if streak >= COOLDOWN_AFTER_FAILURES {
+            let doublings = (streak - COOLDOWN_AFTER_FAILURES).min(6);
+            let cooldown = (BASE_COOLDOWN * 2u32.pow(doublings)).min(MAX_COOLDOWN);
+            self.cooldown_until = Some(now + cooldown);
+        }
+    }
+
+    pub(super) fn cooling_down(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+
+    pub(super) fn expected_latency(&self) -> Duration {
+        self.latency_ms
+            .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+            .unwrap_or(LATENCY_PRIOR)
+    }
+
+    pub(super) fn error_rate(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total <= f64::EPSILON {
+            0.0
+        } else {
+            self.failures / total
+        }
+    }
+
+    /// Lower is better: expected latency, inflated by the recent error rate.
+    pub(super) fn score(&self) -> f64 {
+        self.expected_latency().as_secs_f64() * 1000.0 * (1.0 + 4.0 * self.error_rate())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_outweigh_speed_and_corrupt_bodies_cool_down_at_once() {
+        let now = Instant::now();
+        let mut fast_flaky = GatewayHealth::default();
+        let mut slow_steady = GatewayHealth::default();
+        for _ in 0..4 {
+            fast_flaky.record_success(Duration::from_millis(100));
+            fast_flaky.record_failure(FailureKind::Unreachable, now);
+            slow_steady.record_success(Duration::from_millis(300));
+        }
+        assert!(slow_steady.score() < fast_flaky.score());
+        assert!(!fast_flaky.cooling_down(now));
+
+        let mut corrupt = GatewayHealth::default();
+        corrupt.record_failure(FailureKind::Corrupt, now);
+        assert!(corrupt.cooling_down(now));
+        assert!(!corrupt.cooling_down(now + MAX_COOLDOWN));
+        corrupt.record_success(Duration::from_millis(50));
+        assert!(!corrupt.cooling_down(now));
+    }
+}
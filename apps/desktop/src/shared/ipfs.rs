@@ -1,33 +1,29 @@
-use std::env;
+use super::gateways::{GatewayKind, GatewayPool};
 
 const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
 const DEFAULT_LS3_GATEWAY: &str = "https://gateway.s3-node-1.load.network";
 const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net";
 
-fn normalized_env_url(key: &str, fallback: &str) -> String {
-    let value = env::var(key).ok().filter(|v| !v.trim().is_empty());
-    let mut url = value.unwrap_or_else(|| fallback.to_string());
-    while url.ends_with('/') {
-        url.pop();
-    }
-    url
+/// Best ranked gateway of `kind` right now; configured gateways rank first.
+fn pooled_gateway(kind: GatewayKind, fallback: &str) -> String {
+    GatewayPool::shared()
+        .preferred_base(kind)
+        .unwrap_or_else(|| fallback.to_string())
 }
 
 fn ipfs_gateway() -> String {
-    let base = normalized_env_url("HEAVEN_IPFS_GATEWAY_URL", DEFAULT_IPFS_GATEWAY);
-    if base.ends_with("/ipfs") {
-        format!("{base}/")
-    } else {
-        format!("{base}/ipfs/")
-    }
+    format!(
+        "{}/ipfs/",
+        pooled_gateway(GatewayKind::Ipfs, DEFAULT_IPFS_GATEWAY)
+    )
 }
 
 fn ls3_gateway() -> String {
-    normalized_env_url("HEAVEN_LOAD_GATEWAY_URL", DEFAULT_LS3_GATEWAY)
+    pooled_gateway(GatewayKind::Load, DEFAULT_LS3_GATEWAY)
 }
 
 fn arweave_gateway() -> String {
-    normalized_env_url("HEAVEN_ARWEAVE_GATEWAY_URL", DEFAULT_ARWEAVE_GATEWAY)
+    pooled_gateway(GatewayKind::Arweave, DEFAULT_ARWEAVE_GATEWAY)
 }
 
 pub fn resolve_ipfs_url(url: &str) -> String {
@@ -74,3 +70,25 @@ pub fn heaven_cover_image_url(ref_or_cid: &str, width: u32, height: u32, quality
     }
     heaven_ipfs_image_url(raw, width, height, quality)
 }
+
+/// Resolve a storage ref (`ar://`, `ls3://`, `load-s3://`, `ipfs://` or a plain URL)
+/// into a fetchable gateway URL without image transforms.
+pub fn resolve_storage_ref_url(raw_ref: &str) -> Option<String> {
+    let raw = raw_ref.trim();
+    if let Some(id) = raw.strip_prefix("ar://") {
+        return Some(format!("{}/{}", arweave_gateway(), id.trim()));
+    }
+    if let Some(id) = raw
+        .strip_prefix("ls3://")
+        .or_else(|| raw.strip_prefix("load-s3://"))
+    {
+        return Some(format!("{}/resolve/{}", ls3_gateway(), id.trim()));
+    }
+    if raw.starts_with("ipfs://") {
+        return Some(resolve_ipfs_url(raw));
+    }
+    if raw.starts_with("http://") || raw.starts_with("https://") {
+        return Some(raw.to_string());
+    }
+    None
+}
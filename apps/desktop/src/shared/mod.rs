@@ -1,4 +1,5 @@
 pub mod address;
 pub mod config;
+pub mod gateways;
 pub mod ipfs;
 pub mod rpc;
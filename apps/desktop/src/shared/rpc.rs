@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io::Read;
 use std::time::Duration;
 
@@ -13,10 +14,56 @@ fn http_timeout_secs() -> u64 {
         .unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS)
 }
 
-fn http_timeout() -> Duration {
+pub fn http_timeout() -> Duration {
     Duration::from_secs(http_timeout_secs())
 }
 
+/// Why an HTTP helper failed, keeping the response status for callers that act on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    /// No usable response: connect failure, timeout or a body cut off mid-read.
+    Transport(String),
+    /// The server answered with this error status.
+    Status(u16, String),
+    /// A response arrived but was not what was asked for, e.g. a JSON-RPC error.
+    Response(String),
+}
+
+impl HttpError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Transport(message) | Self::Status(_, message) | Self::Response(message) => {
+                message
+            }
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Status(status, _) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+impl From<HttpError> for String {
+    fn from(err: HttpError) -> Self {
+        match err {
+            HttpError::Transport(message)
+            | HttpError::Status(_, message)
+            | HttpError::Response(message) => message,
+        }
+    }
+}
+
 pub fn read_json_or_text(resp: &mut ureq::http::Response<ureq::Body>) -> Value {
     let text = resp
         .body_mut()
@@ -25,25 +72,30 @@ pub fn read_json_or_text(resp: &mut ureq::http::Response<ureq::Body>) -> Value {
     serde_json::from_str::<Value>(&text).unwrap_or_else(|_| json!({ "raw": text }))
 }
 
-pub fn http_get_json(url: &str) -> Result<Value, String> {
+pub fn http_get_json(url: &str) -> Result<Value, HttpError> {
     let timeout_secs = http_timeout_secs();
     let request = ureq::get(url)
         .config()
         .timeout_global(Some(http_timeout()))
         .http_status_as_error(false)
         .build();
-    let mut resp = request
-        .call()
-        .map_err(|e| format!("HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"))?;
+    let mut resp = request.call().map_err(|e| {
+        HttpError::Transport(format!(
+            "HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"
+        ))
+    })?;
     let status = resp.status().as_u16();
     let body = read_json_or_text(&mut resp);
     if status >= 400 {
-        return Err(format!("HTTP GET {url} failed ({status}): {body}"));
+        return Err(HttpError::Status(
+            status,
+            format!("HTTP GET {url} failed ({status}): {body}"),
+        ));
     }
     Ok(body)
 }
 
-pub fn http_post_json(url: &str, payload: Value) -> Result<Value, String> {
+pub fn http_post_json(url: &str, payload: Value) -> Result<Value, HttpError> {
     let timeout_secs = http_timeout_secs();
     let request = ureq::post(url)
         .header("Content-Type", "application/json")
@@ -51,44 +103,60 @@ pub fn http_post_json(url: &str, payload: Value) -> Result<Value, String> {
         .timeout_global(Some(http_timeout()))
         .http_status_as_error(false)
         .build();
-    let mut resp = request
-        .send_json(payload)
-        .map_err(|e| format!("HTTP POST failed ({url}, timeout={timeout_secs}s): {e}"))?;
+    let mut resp = request.send_json(payload).map_err(|e| {
+        HttpError::Transport(format!(
+            "HTTP POST failed ({url}, timeout={timeout_secs}s): {e}"
+        ))
+    })?;
     let status = resp.status().as_u16();
     let body = read_json_or_text(&mut resp);
     if status >= 400 {
-        return Err(format!("HTTP POST {url} failed ({status}): {body}"));
+        return Err(HttpError::Status(
+            status,
+            format!("HTTP POST {url} failed ({status}): {body}"),
+        ));
     }
     Ok(body)
 }
 
-pub fn http_get_bytes(url: &str) -> Result<Vec<u8>, String> {
+pub fn http_get_bytes(url: &str) -> Result<Vec<u8>, HttpError> {
     let timeout_secs = http_timeout_secs();
     let request = ureq::get(url)
         .config()
         .timeout_global(Some(http_timeout()))
         .http_status_as_error(false)
         .build();
-    let mut resp = request
-        .call()
-        .map_err(|e| format!("HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"))?;
+    let mut resp = request.call().map_err(|e| {
+        HttpError::Transport(format!(
+            "HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"
+        ))
+    })?;
     let status = resp.status().as_u16();
     if status >= 400 {
         let body = read_json_or_text(&mut resp);
-        return Err(format!("HTTP GET {url} failed ({status}): {body}"));
+        return Err(HttpError::Status(
+            status,
+            format!("HTTP GET {url} failed ({status}): {body}"),
+        ));
     }
 
     let mut bytes = Vec::new();
     resp.body_mut()
         .as_reader()
         .read_to_end(&mut bytes)
-        .map_err(|e| format!("Failed reading HTTP body ({url}): {e}"))?;
+        .map_err(|e| HttpError::Transport(format!("Failed reading HTTP body ({url}): {e}")))?;
     Ok(bytes)
 }
 
-pub fn http_get_bytes_range(url: &str, start: u64, end_inclusive: u64) -> Result<Vec<u8>, String> {
+pub fn http_get_bytes_range(
+    url: &str,
+    start: u64,
+    end_inclusive: u64,
+) -> Result<Vec<u8>, HttpError> {
     if start > end_inclusive {
-        return Err(format!("Invalid byte range: {start}..={end_inclusive}"));
+        return Err(HttpError::Response(format!(
+            "Invalid byte range: {start}..={end_inclusive}"
+        )));
     }
     let range = format!("bytes={start}-{end_inclusive}");
     let timeout_secs = http_timeout_secs();
@@ -99,29 +167,34 @@ pub fn http_get_bytes_range(url: &str, start: u64, end_inclusive: u64) -> Result
         .http_status_as_error(false)
         .build();
 
-    let mut resp = request
-        .call()
-        .map_err(|e| format!("HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"))?;
+    let mut resp = request.call().map_err(|e| {
+        HttpError::Transport(format!(
+            "HTTP GET failed ({url}, timeout={timeout_secs}s): {e}"
+        ))
+    })?;
     let status = resp.status().as_u16();
     if status >= 400 {
         let body = read_json_or_text(&mut resp);
-        return Err(format!("HTTP GET {url} failed ({status}): {body}"));
+        return Err(HttpError::Status(
+            status,
+            format!("HTTP GET {url} failed ({status}): {body}"),
+        ));
     }
     if status != 200 && status != 206 {
-        return Err(format!(
+        return Err(HttpError::Response(format!(
             "Unexpected HTTP status for range GET {url}: {status}"
-        ));
+        )));
     }
 
     let mut bytes = Vec::new();
     resp.body_mut()
         .as_reader()
         .read_to_end(&mut bytes)
-        .map_err(|e| format!("Failed reading HTTP body ({url}): {e}"))?;
+        .map_err(|e| HttpError::Transport(format!("Failed reading HTTP body ({url}): {e}")))?;
     Ok(bytes)
 }
 
-pub fn rpc_json(rpc_url: &str, payload: Value) -> Result<Value, String> {
+pub fn rpc_json(rpc_url: &str, payload: Value) -> Result<Value, HttpError> {
     let timeout_secs = http_timeout_secs();
     let method = payload
         .get("method")
@@ -135,21 +208,26 @@ pub fn rpc_json(rpc_url: &str, payload: Value) -> Result<Value, String> {
         .http_status_as_error(false)
         .build();
     let mut resp = request.send_json(payload).map_err(|e| {
-        format!("RPC request failed ({method} {rpc_url}, timeout={timeout_secs}s): {e}")
+        HttpError::Transport(format!(
+            "RPC request failed ({method} {rpc_url}, timeout={timeout_secs}s): {e}"
+        ))
     })?;
     let status = resp.status().as_u16();
     let body = read_json_or_text(&mut resp);
     if status >= 400 {
-        return Err(format!(
-            "RPC HTTP failure ({method} {rpc_url}, status={status}): {body}"
+        return Err(HttpError::Status(
+            status,
+            format!("RPC HTTP failure ({method} {rpc_url}, status={status}): {body}"),
         ));
     }
     if let Some(err) = body.get("error") {
-        return Err(format!("RPC error ({method} {rpc_url}): {err}"));
+        return Err(HttpError::Response(format!(
+            "RPC error ({method} {rpc_url}): {err}"
+        )));
     }
-    body.get("result")
-        .cloned()
-        .ok_or_else(|| format!("RPC response missing result ({method} {rpc_url})"))
+    body.get("result").cloned().ok_or_else(|| {
+        HttpError::Response(format!("RPC response missing result ({method} {rpc_url})"))
+    })
 }
 
 pub fn eth_call_address(rpc_url: &str, to: &str, data: &str) -> Result<String, String> {
@@ -5,9 +5,13 @@ use gpui_component::StyledExt;
 
 use crate::audio::AudioHandle;
 use crate::library;
-use crate::lyrics::{resolve_lyrics_for_track, LyricsTrackSignature, ResolvedLyrics};
+use crate::lyrics::editor::LyricsEditorState;
+use crate::lyrics::{
+    resolve_lyrics_for_track, LyricsLayerKind, LyricsTrackSignature, ResolvedLyrics,
+};
 use crate::shell::app_sidebar::NavChannel;
 
+mod lyrics_editor;
 mod render;
 
 #[derive(Debug, Clone)]
@@ -33,11 +37,17 @@ pub(crate) struct SidePlayerView {
     cached_track_artist: Option<String>,
     cached_track_album: Option<String>,
     lyrics_track_path: Option<String>,
+    lyrics_signature: Option<LyricsTrackSignature>,
     lyrics_fetch_seq: u64,
     lyrics_state: LyricsFetchState,
     lyrics_scroll_handle: ScrollHandle,
     lyrics_initial_scroll_retries: u8,
     last_active_lyric_idx: Option<usize>,
+    /// Translation/romanization layer shown under each lyric line; kept across tracks.
+    lyrics_layer_kind: Option<LyricsLayerKind>,
+    lyrics_editor: Option<LyricsEditorState>,
+    lyrics_editor_status: Option<String>,
+    lyrics_editor_busy: bool,
     _seek_slider_subscription: Subscription,
 }
 
@@ -70,7 +80,7 @@ impl SidePlayerView {
         let lib = library_view.clone();
         cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
             let mut prev_playing = false;
-            let mut prev_position_bucket: i64 = -1; // playback position in 0.5s buckets
+            let mut prev_position_bucket: i64 = -1; // playback position in 0.5s/0.1s buckets
             let mut prev_track: Option<String> = None;
             loop {
                 // Poll faster during playback (200ms), much slower when idle (1s).
@@ -101,15 +111,7 @@ impl SidePlayerView {
                             }
 
                             let signature = playback.track_path.as_deref().and_then(|path| {
-                                track_metadata.clone().map(
-                                    |(track_name, artist_name, album_name)| LyricsTrackSignature {
-                                        track_path: path.to_string(),
-                                        track_name,
-                                        artist_name,
-                                        album_name,
-                                        duration_sec: duration_hint,
-                                    },
-                                )
+                                lib.read(cx).lyrics_signature_for_path(path, duration_hint)
                             });
                             this.ensure_lyrics_for_playback(
                                 playback.track_path.as_deref(),
@@ -118,8 +120,14 @@ impl SidePlayerView {
                             );
                         }
 
-                        // Trigger redraws only for meaningful playback updates.
-                        let cur_position_bucket = (playback.position * 2.0) as i64;
+                        // Trigger redraws only for meaningful playback updates; word-timed
+                        // lyrics need finer steps for karaoke highlighting.
+                        let buckets_per_sec = if this.lyrics_have_word_timing() {
+                            10.0
+                        } else {
+                            2.0
+                        };
+                        let cur_position_bucket = (playback.position * buckets_per_sec) as i64;
                         let changed = playback.playing != prev_playing
                             || cur_position_bucket != prev_position_bucket
                             || track_changed;
@@ -135,6 +143,7 @@ impl SidePlayerView {
                     break;
                 }
                 let _ = lib.update(cx, |lib, cx| {
+                    lib.track_playback_progress(cx);
                     lib.check_auto_advance(cx);
                 });
             }
@@ -156,15 +165,50 @@ impl SidePlayerView {
             cached_track_artist: None,
             cached_track_album: None,
             lyrics_track_path: None,
+            lyrics_signature: None,
             lyrics_fetch_seq: 0,
             lyrics_state: LyricsFetchState::Idle,
             lyrics_scroll_handle: ScrollHandle::new(),
             lyrics_initial_scroll_retries: 0,
             last_active_lyric_idx: None,
+            lyrics_layer_kind: None,
+            lyrics_editor: None,
+            lyrics_editor_status: None,
+            lyrics_editor_busy: false,
             _seek_slider_subscription,
         }
     }
 
+    fn lyrics_have_word_timing(&self) -> bool {
+        matches!(
+            &self.lyrics_state,
+            LyricsFetchState::Ready(lyrics)
+                if lyrics.synced_lines.iter().any(|line| !line.words.is_empty())
+        )
+    }
+
+    /// Step through original → each available layer kind → original.
+    fn cycle_lyrics_layer(&mut self, cx: &mut Context<Self>) {
+        let LyricsFetchState::Ready(lyrics) = &self.lyrics_state else {
+            return;
+        };
+        let mut kinds: Vec<LyricsLayerKind> = Vec::new();
+        for layer in &lyrics.layers {
+            if !kinds.contains(&layer.kind) {
+                kinds.push(layer.kind);
+            }
+        }
+        let next = match self.lyrics_layer_kind {
+            None => kinds.first().copied(),
+            Some(current) => kinds
+                .iter()
+                .position(|kind| *kind == current)
+                .and_then(|idx| kinds.get(idx + 1).copied()),
+        };
+        self.lyrics_layer_kind = next;
+        cx.notify();
+    }
+
     fn ensure_lyrics_for_playback(
         &mut self,
         track_path: Option<&str>,
@@ -174,6 +218,8 @@ impl SidePlayerView {
         let Some(track_path) = track_path.map(str::trim).filter(|path| !path.is_empty()) else {
             self.lyrics_fetch_seq = self.lyrics_fetch_seq.wrapping_add(1);
             self.lyrics_track_path = None;
+            self.lyrics_signature = None;
+            self.lyrics_editor = None;
             self.lyrics_state = LyricsFetchState::Idle;
             self.lyrics_scroll_handle = ScrollHandle::new();
             self.lyrics_initial_scroll_retries = 0;
@@ -184,6 +230,9 @@ impl SidePlayerView {
         if self.lyrics_track_path.as_deref() == Some(track_path) {
             return;
         }
+        self.lyrics_editor = None;
+        self.lyrics_editor_status = None;
+        self.lyrics_signature = signature.clone();
 
         let Some(signature) = signature else {
             self.lyrics_fetch_seq = self.lyrics_fetch_seq.wrapping_add(1);
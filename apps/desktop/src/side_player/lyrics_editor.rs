@@ -0,0 +1,120 @@
+use super::*;
+use crate::lyrics::editor::{save_edited_lyrics, LyricsEditorState};
+
+impl SidePlayerView {
+    pub(super) fn open_lyrics_editor(&mut self, cx: &mut Context<Self>) {
+        let editor = match &self.lyrics_state {
+            LyricsFetchState::Ready(lyrics) => LyricsEditorState::from_resolved(lyrics),
+            _ => LyricsEditorState::default(),
+        };
+        self.lyrics_editor = Some(editor);
+        self.lyrics_editor_status = None;
+        cx.notify();
+    }
+
+    pub(super) fn close_lyrics_editor(&mut self, cx: &mut Context<Self>) {
+        self.lyrics_editor = None;
+        self.lyrics_editor_status = None;
+        cx.notify();
+    }
+
+    /// Replace the editor lines with whatever plain text or LRC is on the clipboard.
+    pub(super) fn paste_lyrics_into_editor(&mut self, cx: &mut Context<Self>) {
+        let text = cx
+            .read_from_clipboard()
+            .and_then(|item| item.text())
+            .filter(|text| !text.trim().is_empty());
+        let Some(text) = text else {
+            self.lyrics_editor_status = Some("Clipboard has no text to paste.".to_string());
+            cx.notify();
+            return;
+        };
+        let editor = LyricsEditorState::from_text(&text);
+        self.lyrics_editor_status = Some(format!("Pasted {} lines.", editor.lines.len()));
+        self.lyrics_editor = Some(editor);
+        cx.notify();
+    }
+
+    pub(super) fn lyrics_editor_tap(&mut self, cx: &mut Context<Self>) {
+        let position = self.audio.read_state().position;
+        if let Some(editor) = self.lyrics_editor.as_mut() {
+            editor.tap(position);
+            cx.notify();
+        }
+    }
+
+    pub(super) fn lyrics_editor_update(
+        &mut self,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&mut LyricsEditorState),
+    ) {
+        if let Some(editor) = self.lyrics_editor.as_mut() {
+            update(editor);
+            cx.notify();
+        }
+    }
+
+    /// Write the sidecar `.lrc`, refresh the displayed lyrics, and optionally publish.
+    pub(super) fn save_lyrics_editor(&mut self, publish: bool, cx: &mut Context<Self>) {
+        if self.lyrics_editor_busy {
+            return;
+        }
+        let Some(editor) = self.lyrics_editor.as_ref() else {
+            return;
+        };
+        let Some(signature) = self.lyrics_signature.clone() else {
+            self.lyrics_editor_status =
+                Some("Missing track metadata; cannot save lyrics.".to_string());
+            cx.notify();
+            return;
+        };
+        let lrc = match editor.to_lrc(&signature) {
+            Ok(lrc) => lrc,
+            Err(err) => {
+                self.lyrics_editor_status = Some(err);
+                cx.notify();
+                return;
+            }
+        };
+
+        self.lyrics_editor_busy = true;
+        self.lyrics_editor_status = Some("Saving lyrics...".to_string());
+        cx.notify();
+
+        let db_handle = self.library_view.read(cx).lyrics_db_handle();
+        let library_view = self.library_view.clone();
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let save_signature = signature.clone();
+            let result = smol::unblock(move || {
+                save_edited_lyrics(&save_signature, &lrc, db_handle.as_ref())
+            })
+            .await;
+            let _ = this.update(cx, |this, cx| {
+                this.lyrics_editor_busy = false;
+                match result {
+                    Ok(lyrics) => {
+                        if this.lyrics_track_path.as_deref() == Some(signature.track_path.as_str())
+                        {
+                            this.lyrics_fetch_seq = this.lyrics_fetch_seq.wrapping_add(1);
+                            this.lyrics_state = LyricsFetchState::Ready(lyrics.clone());
+                            this.last_active_lyric_idx = None;
+                        }
+                        this.lyrics_editor = None;
+                        this.lyrics_editor_status = None;
+                        if publish {
+                            library_view.update(cx, |lib, cx| {
+                                lib.publish_edited_lyrics(signature, lyrics, cx);
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        log::warn!("[lyrics] editor save failed: {}", err);
+                        this.lyrics_editor_status = Some(format!("Save failed: {err}"));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
@@ -6,7 +6,26 @@ pub(super) fn render_lyrics_panel(
     playback_position_sec: f64,
     lyrics_scroll_handle: &ScrollHandle,
     active_synced_idx: Option<usize>,
+    layer_kind: Option<crate::lyrics::LyricsLayerKind>,
+    can_edit: bool,
+    entity: Entity<SidePlayerView>,
 ) -> impl IntoElement {
+    let (layer, has_layers) = match lyrics_state {
+        LyricsFetchState::Ready(lyrics) => (
+            layer_kind.and_then(|kind| lyrics.layers.iter().find(|layer| layer.kind == kind)),
+            !lyrics.layers.is_empty(),
+        ),
+        _ => (None, false),
+    };
+    let layer_label = layer
+        .map(|layer| layer.label())
+        .unwrap_or_else(|| "Original".to_string());
+    let layer_entity = entity.clone();
+    let can_edit = can_edit
+        && matches!(
+            lyrics_state,
+            LyricsFetchState::Ready(_) | LyricsFetchState::Error(_)
+        );
     let mut panel = div()
         .v_flex()
         .gap_2()
@@ -15,13 +34,50 @@ pub(super) fn render_lyrics_panel(
         .border_t_1()
         .border_color(hsla(0., 0., 0.21, 1.))
         .child(
-            div().h_flex().items_center().justify_between().child(
-                div()
-                    .text_sm()
-                    .font_weight(FontWeight::SEMIBOLD)
-                    .text_color(hsla(0., 0., 0.90, 1.))
-                    .child("Lyrics"),
-            ),
+            div()
+                .h_flex()
+                .items_center()
+                .justify_between()
+                .child(
+                    div()
+                        .text_sm()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .text_color(hsla(0., 0., 0.90, 1.))
+                        .child("Lyrics"),
+                )
+                .child(
+                    div()
+                        .h_flex()
+                        .items_center()
+                        .gap_3()
+                        .when(has_layers, |el| {
+                            el.child(
+                                div()
+                                    .id("side-player-lyrics-layer")
+                                    .cursor_pointer()
+                                    .text_xs()
+                                    .text_color(hsla(0., 0., 0.64, 1.))
+                                    .on_click(move |_, _, cx| {
+                                        layer_entity
+                                            .update(cx, |this, cx| this.cycle_lyrics_layer(cx));
+                                    })
+                                    .child(layer_label),
+                            )
+                        })
+                        .when(can_edit, |el| {
+                            el.child(
+                                div()
+                                    .id("side-player-lyrics-edit")
+                                    .cursor_pointer()
+                                    .text_xs()
+                                    .text_color(hsla(0., 0., 0.64, 1.))
+                                    .on_click(move |_, _, cx| {
+                                        entity.update(cx, |this, cx| this.open_lyrics_editor(cx));
+                                    })
+                                    .child("Edit / sync"),
+                            )
+                        }),
+                ),
         );
 
     match lyrics_state {
@@ -53,15 +109,23 @@ pub(super) fn render_lyrics_panel(
                         .child("Instrumental"),
                 );
             } else if !lyrics.synced_lines.is_empty() {
+                if let Some(label) = lyrics.provenance.label() {
+                    panel = panel.child(render_source_badge(&label));
+                }
                 let active_idx = active_synced_idx
                     .or_else(|| active_synced_index(&lyrics.synced_lines, playback_position_sec));
                 panel = panel.child(render_synced_lyrics(
                     &lyrics.synced_lines,
                     active_idx,
+                    playback_position_sec,
+                    layer,
                     lyrics_scroll_handle,
                 ));
             } else if let Some(plain) = lyrics.plain_lyrics.as_deref() {
-                panel = panel.child(render_plain_lyrics(plain, lyrics_scroll_handle));
+                if let Some(label) = lyrics.provenance.label() {
+                    panel = panel.child(render_source_badge(&label));
+                }
+                panel = panel.child(render_plain_lyrics(plain, layer, lyrics_scroll_handle));
             }
         }
     }
@@ -85,6 +149,8 @@ fn render_source_badge(label: &str) -> impl IntoElement {
 fn render_synced_lyrics(
     lines: &[crate::lyrics::LyricsLine],
     active_idx: Option<usize>,
+    playback_position_sec: f64,
+    layer: Option<&crate::lyrics::LyricsLayer>,
     scroll_handle: &ScrollHandle,
 ) -> impl IntoElement {
     let mut list = div()
@@ -109,6 +175,20 @@ fn render_synced_lyrics(
         } else {
             hsla(0., 0., 0., 0.)
         };
+        let content = if is_active && !line.words.is_empty() {
+            render_karaoke_words(line, playback_position_sec).into_any_element()
+        } else {
+            div()
+                .min_w_0()
+                .text_sm()
+                .text_color(text_color)
+                .child(if line.text.trim().is_empty() {
+                    " ".to_string()
+                } else {
+                    line.text.clone()
+                })
+                .into_any_element()
+        };
         list = list.child(
             div()
                 .w_full()
@@ -117,20 +197,73 @@ fn render_synced_lyrics(
                 .py(px(3.))
                 .rounded(px(6.))
                 .bg(bg)
-                .child(div().min_w_0().text_sm().text_color(text_color).child(
-                    if line.text.trim().is_empty() {
-                        " ".to_string()
-                    } else {
-                        line.text.clone()
-                    },
-                )),
+                .when_some(line.voice, |el, voice| {
+                    el.border_l_2().border_color(voice_color(voice))
+                })
+                .child(content)
+                .when_some(layer.and_then(|layer| layer.line(idx)), |el, text| {
+                    el.child(render_layer_line(text, is_active))
+                }),
         );
     }
 
     list.vertical_scrollbar(scroll_handle)
 }
 
-fn render_plain_lyrics(text: &str, scroll_handle: &ScrollHandle) -> impl IntoElement {
+/// Active-line word highlighting for enhanced LRC: sung words are bright,
+/// the current word fades in with its progress, upcoming words stay dim.
+fn render_karaoke_words(
+    line: &crate::lyrics::LyricsLine,
+    playback_position_sec: f64,
+) -> impl IntoElement {
+    let position = playback_position_sec + 0.02;
+    let mut row = div().h_flex().flex_wrap().min_w_0().gap(px(4.)).text_sm();
+    for word in &line.words {
+        let progress = match word.end_sec {
+            _ if position < word.start_sec => 0.0,
+            Some(end) if end > word.start_sec => {
+                ((position - word.start_sec) / (end - word.start_sec)).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        };
+        let lightness = 0.64 + (0.96 - 0.64) * progress as f32;
+        row = row.child(
+            div()
+                .text_color(hsla(0., 0., lightness, 1.))
+                .when(progress > 0.0 && progress < 1.0, |el| {
+                    el.font_weight(FontWeight::SEMIBOLD)
+                })
+                .child(word.text.clone()),
+        );
+    }
+    row
+}
+
+fn voice_color(voice: crate::lyrics::LyricsVoice) -> Hsla {
+    match voice {
+        crate::lyrics::LyricsVoice::Male => hsla(0.58, 0.65, 0.60, 1.),
+        crate::lyrics::LyricsVoice::Female => hsla(0.92, 0.65, 0.65, 1.),
+        crate::lyrics::LyricsVoice::Duet => hsla(0.13, 0.75, 0.60, 1.),
+    }
+}
+
+fn render_layer_line(text: &str, is_active: bool) -> impl IntoElement {
+    div()
+        .min_w_0()
+        .text_xs()
+        .text_color(if is_active {
+            hsla(0., 0., 0.82, 1.)
+        } else {
+            hsla(0., 0., 0.50, 1.)
+        })
+        .child(text.to_string())
+}
+
+fn render_plain_lyrics(
+    text: &str,
+    layer: Option<&crate::lyrics::LyricsLayer>,
+    scroll_handle: &ScrollHandle,
+) -> impl IntoElement {
     let mut body = div()
         .id("side-player-plain-lyrics-list")
         .v_flex()
@@ -138,7 +271,15 @@ fn render_plain_lyrics(text: &str, scroll_handle: &ScrollHandle) -> impl IntoEle
         .max_h(px(280.))
         .overflow_y_scroll()
         .track_scroll(scroll_handle);
+    // Layers index only the non-empty plain lines.
+    let mut layer_idx = 0usize;
     for line in text.lines() {
+        let layer_text = if line.trim().is_empty() {
+            None
+        } else {
+            layer_idx += 1;
+            layer.and_then(|layer| layer.line(layer_idx - 1))
+        };
         body = body.child(
             div()
                 .w_full()
@@ -151,6 +292,9 @@ fn render_plain_lyrics(text: &str, scroll_handle: &ScrollHandle) -> impl IntoEle
                     line.to_string()
                 }),
         );
+        if let Some(layer_text) = layer_text {
+            body = body.child(render_layer_line(layer_text, false));
+        }
     }
     body.vertical_scrollbar(scroll_handle)
 }
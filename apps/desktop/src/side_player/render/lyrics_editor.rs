@@ -0,0 +1,199 @@
+use super::*;
+use crate::lyrics::editor::OFFSET_STEP_MS;
+use gpui_component::scroll::ScrollableElement;
+
+pub(super) fn render_lyrics_editor(
+    editor: &LyricsEditorState,
+    status: Option<&str>,
+    busy: bool,
+    scroll_handle: &ScrollHandle,
+    entity: Entity<SidePlayerView>,
+) -> impl IntoElement {
+    let header = div()
+        .h_flex()
+        .items_center()
+        .justify_between()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(hsla(0., 0., 0.90, 1.))
+                .child("Sync lyrics"),
+        )
+        .child(
+            div()
+                .text_xs()
+                .text_color(hsla(0., 0., 0.58, 1.))
+                .child(format!(
+                    "{}/{} timed · offset {:+}ms",
+                    editor.timed_count(),
+                    editor.lines.len(),
+                    editor.offset_ms
+                )),
+        );
+
+    let tap_entity = entity.clone();
+    let undo_entity = entity.clone();
+    let earlier_entity = entity.clone();
+    let later_entity = entity.clone();
+    let paste_entity = entity.clone();
+    let cancel_entity = entity.clone();
+    let save_entity = entity.clone();
+    let publish_entity = entity.clone();
+
+    let timing_row = div()
+        .h_flex()
+        .flex_wrap()
+        .gap_1()
+        .child(
+            editor_button("lyrics-editor-tap", "Tap line", true).on_click(move |_, _, cx| {
+                tap_entity.update(cx, |this, cx| this.lyrics_editor_tap(cx));
+            }),
+        )
+        .child(
+            editor_button("lyrics-editor-undo", "Undo", false).on_click(move |_, _, cx| {
+                undo_entity.update(cx, |this, cx| {
+                    this.lyrics_editor_update(cx, |editor| editor.undo_tap());
+                });
+            }),
+        )
+        .child(
+            editor_button("lyrics-editor-earlier", "Earlier", false).on_click(move |_, _, cx| {
+                earlier_entity.update(cx, |this, cx| {
+                    this.lyrics_editor_update(cx, |editor| editor.shift_offset(OFFSET_STEP_MS));
+                });
+            }),
+        )
+        .child(
+            editor_button("lyrics-editor-later", "Later", false).on_click(move |_, _, cx| {
+                later_entity.update(cx, |this, cx| {
+                    this.lyrics_editor_update(cx, |editor| editor.shift_offset(-OFFSET_STEP_MS));
+                });
+            }),
+        );
+
+    let actions_row = div()
+        .h_flex()
+        .flex_wrap()
+        .gap_1()
+        .child(
+            editor_button("lyrics-editor-paste", "Paste", false).on_click(move |_, _, cx| {
+                paste_entity.update(cx, |this, cx| this.paste_lyrics_into_editor(cx));
+            }),
+        )
+        .child(
+            editor_button("lyrics-editor-cancel", "Cancel", false).on_click(move |_, _, cx| {
+                cancel_entity.update(cx, |this, cx| this.close_lyrics_editor(cx));
+            }),
+        )
+        .when(!busy && editor.is_complete(), |row| {
+            row.child(editor_button("lyrics-editor-save", "Save", true).on_click(
+                move |_, _, cx| {
+                    save_entity.update(cx, |this, cx| this.save_lyrics_editor(false, cx));
+                },
+            ))
+            .child(
+                editor_button("lyrics-editor-publish", "Save & publish", false).on_click(
+                    move |_, _, cx| {
+                        publish_entity.update(cx, |this, cx| this.save_lyrics_editor(true, cx));
+                    },
+                ),
+            )
+        });
+
+    let mut list = div()
+        .id("side-player-lyrics-editor-list")
+        .v_flex()
+        .gap_1()
+        .max_h(px(240.))
+        .w_full()
+        .min_w_0()
+        .overflow_y_scroll()
+        .track_scroll(scroll_handle);
+    if editor.is_empty() {
+        list = list.child(div().text_sm().text_color(hsla(0., 0., 0.58, 1.)).child(
+            "Copy lyrics to the clipboard and press Paste, then tap each line as it starts.",
+        ));
+    }
+    for (idx, line) in editor.lines.iter().enumerate() {
+        let is_cursor = idx == editor.cursor;
+        let restart_entity = entity.clone();
+        let stamp = line
+            .start_sec
+            .map(crate::lyrics::format_lrc_timestamp)
+            .unwrap_or_else(|| "--:--.--".to_string());
+        list = list.child(
+            div()
+                .id(("lyrics-editor-line", idx))
+                .h_flex()
+                .gap_2()
+                .w_full()
+                .min_w_0()
+                .px_2()
+                .py(px(3.))
+                .rounded(px(6.))
+                .cursor_pointer()
+                .when(is_cursor, |el| el.bg(hsla(0.62, 0.60, 0.44, 0.50)))
+                .on_click(move |_, _, cx| {
+                    restart_entity.update(cx, |this, cx| {
+                        this.lyrics_editor_update(cx, |editor| editor.restart_from(idx));
+                    });
+                })
+                .child(
+                    div()
+                        .flex_none()
+                        .text_xs()
+                        .text_color(hsla(0., 0., 0.52, 1.))
+                        .child(stamp),
+                )
+                .child(
+                    div()
+                        .min_w_0()
+                        .text_sm()
+                        .text_color(if line.start_sec.is_some() {
+                            hsla(0., 0., 0.90, 1.)
+                        } else {
+                            hsla(0., 0., 0.64, 1.)
+                        })
+                        .child(line.text.clone()),
+                ),
+        );
+    }
+
+    div()
+        .v_flex()
+        .gap_2()
+        .pt_3()
+        .mt_1()
+        .border_t_1()
+        .border_color(hsla(0., 0., 0.21, 1.))
+        .child(header)
+        .child(timing_row)
+        .child(list.vertical_scrollbar(scroll_handle))
+        .when_some(status, |el, status| {
+            el.child(
+                div()
+                    .text_xs()
+                    .text_color(hsla(0., 0., 0.68, 1.))
+                    .child(status.to_string()),
+            )
+        })
+        .child(actions_row)
+}
+
+fn editor_button(id: &'static str, label: &'static str, primary: bool) -> Stateful<Div> {
+    div()
+        .id(id)
+        .px_2()
+        .py(px(3.))
+        .rounded(px(6.))
+        .cursor_pointer()
+        .text_xs()
+        .bg(if primary {
+            hsla(0.62, 0.60, 0.44, 0.80)
+        } else {
+            hsla(0., 0., 0.22, 1.)
+        })
+        .text_color(hsla(0., 0., 0.92, 1.))
+        .child(label)
+}
@@ -3,6 +3,7 @@ use super::*;
 mod art;
 mod controls;
 mod lyrics;
+mod lyrics_editor;
 mod metadata;
 mod timeline;
 
@@ -139,11 +140,23 @@ impl Render for SidePlayerView {
                 self.audio.clone(),
                 self.library_view.clone(),
             ))
-            .child(lyrics::render_lyrics_panel(
-                &self.lyrics_state,
-                position,
-                &self.lyrics_scroll_handle,
-                active_lyric_idx,
-            ))
+            .map(|el| match self.lyrics_editor.as_ref() {
+                Some(editor) => el.child(lyrics_editor::render_lyrics_editor(
+                    editor,
+                    self.lyrics_editor_status.as_deref(),
+                    self.lyrics_editor_busy,
+                    &self.lyrics_scroll_handle,
+                    cx.entity(),
+                )),
+                None => el.child(lyrics::render_lyrics_panel(
+                    &self.lyrics_state,
+                    position,
+                    &self.lyrics_scroll_handle,
+                    active_lyric_idx,
+                    self.lyrics_layer_kind,
+                    self.lyrics_signature.is_some(),
+                    cx.entity(),
+                )),
+            })
     }
 }
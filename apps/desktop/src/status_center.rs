@@ -12,6 +12,7 @@ const SUCCESS_TTL: Duration = Duration::from_secs(3);
 pub enum StatusKind {
     Progress,
     Info,
+    Warning,
     Success,
     Error,
 }
@@ -25,6 +26,8 @@ pub struct StatusEntry {
     pub progress: Option<f32>,
     pub sticky: bool,
     pub expires_at: Option<Instant>,
+    /// Activity log record this message reports on, if it is about an on-chain tx.
+    pub activity_id: Option<String>,
 }
 
 #[derive(Clone, Default)]
@@ -92,7 +95,29 @@ impl StatusCenter {
         );
     }
 
+    /// Sticky like an error, for conditions the user should act on before they become one.
+    pub fn publish_warning(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.upsert(
+            key.into(),
+            StatusKind::Warning,
+            message.into(),
+            None,
+            true,
+            None,
+        );
+    }
+
     pub fn publish_success(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.publish_success_for_activity(key, message, None);
+    }
+
+    /// A success about the tx tracked by `activity_id`, which the entry links to.
+    pub fn publish_success_for_activity(
+        &mut self,
+        key: impl Into<String>,
+        message: impl Into<String>,
+        activity_id: Option<String>,
+    ) {
         self.upsert(
             key.into(),
             StatusKind::Success,
@@ -101,9 +126,20 @@ impl StatusCenter {
             false,
             Some(SUCCESS_TTL),
         );
+        self.link_latest(activity_id);
     }
 
     pub fn publish_error(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.publish_error_for_activity(key, message, None);
+    }
+
+    /// An error about the tx tracked by `activity_id`, which the entry links to.
+    pub fn publish_error_for_activity(
+        &mut self,
+        key: impl Into<String>,
+        message: impl Into<String>,
+        activity_id: Option<String>,
+    ) {
         self.upsert(
             key.into(),
             StatusKind::Error,
@@ -112,6 +148,7 @@ impl StatusCenter {
             true,
             None,
         );
+        self.link_latest(activity_id);
     }
 
     pub fn dismiss(&mut self, id: u64) -> bool {
@@ -140,6 +177,13 @@ impl StatusCenter {
         &self.entries
     }
 
+    /// `upsert` leaves the entry it wrote last.
+    fn link_latest(&mut self, activity_id: Option<String>) {
+        if let Some(entry) = self.entries.last_mut() {
+            entry.activity_id = activity_id;
+        }
+    }
+
     fn upsert(
         &mut self,
         key: String,
@@ -163,9 +207,11 @@ impl StatusCenter {
                 progress: None,
                 sticky: false,
                 expires_at: None,
+                activity_id: None,
             }
         };
 
+        entry.activity_id = None;
         entry.kind = kind;
         entry.message = message;
         entry.progress = progress.map(|value| value.clamp(0.0, 1.0));
@@ -180,7 +226,11 @@ impl StatusCenter {
     }
 }
 
-pub fn render_status_overlay(cx: &App) -> Option<AnyElement> {
+/// `on_open_activity` receives the activity id when the user follows an entry's tx link.
+pub fn render_status_overlay(
+    cx: &App,
+    on_open_activity: impl Fn(String, &mut Window, &mut App) + 'static,
+) -> Option<AnyElement> {
     let status = cx.try_global::<StatusCenter>()?;
     let latest = status.entries().last()?.clone();
     let queued = status.entries().len().saturating_sub(1);
@@ -195,6 +245,7 @@ pub fn render_status_overlay(cx: &App) -> Option<AnyElement> {
     let dot_color = match latest.kind {
         StatusKind::Progress => theme.primary,
         StatusKind::Info => theme.blue,
+        StatusKind::Warning => theme.warning,
         StatusKind::Success => theme.success,
         StatusKind::Error => theme.danger,
     };
@@ -203,6 +254,7 @@ pub fn render_status_overlay(cx: &App) -> Option<AnyElement> {
         .progress
         .map(|value| format!("{:.0}%", (value * 100.0).clamp(0.0, 100.0)));
     let dismiss_id = latest.id;
+    let activity_id = latest.activity_id.clone();
 
     Some(
         div()
@@ -235,6 +287,22 @@ pub fn render_status_overlay(cx: &App) -> Option<AnyElement> {
                     .when_some(progress_text, |el, progress| {
                         el.child(div().text_xs().text_color(muted).child(progress))
                     })
+                    .when_some(activity_id, |el, activity_id| {
+                        el.child(
+                            div()
+                                .id(ElementId::Name(
+                                    format!("global-status-activity-{}", dismiss_id).into(),
+                                ))
+                                .text_xs()
+                                .text_color(theme.primary)
+                                .cursor_pointer()
+                                .hover(|s| s.underline())
+                                .on_click(move |_, window, cx| {
+                                    on_open_activity(activity_id.clone(), window, cx);
+                                })
+                                .child("View transaction"),
+                        )
+                    })
                     .when(queued > 0, |el| {
                         el.child(
                             div()
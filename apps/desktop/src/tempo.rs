@@ -0,0 +1,1117 @@
+//! Tempo transaction client shared by scrobbling, playlists and content registration.
+//!
+//! `TempoClient` signs with the user's session (access) key, has the fee payer co-sign, and
+//! owns the whole submission loop: `eth_call` simulation, fee estimation, nonce lane leasing,
+//! receipt awaiting with fee-bumped retries when a tx expires unmined, the tx journal and the
+//! user-facing activity log.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{sol, SolCall};
+use ethers::signers::{LocalWallet, Signer};
+use serde_json::{json, Value};
+
+mod activity;
+mod fees;
+mod journal;
+mod nonce;
+mod receipt;
+mod rpc;
+mod tx;
+
+pub use activity::{TxActivity, TxActivityFilter, TxActivityLog, TxActivityStatus, TxKind};
+use fees::SuggestedFees;
+use journal::{TxJournal, TxJournalEntry, TxJournalStatus};
+use nonce::{LaneOutcome, NonceLanes};
+pub use receipt::ReceiptSummary;
+use receipt::{await_receipt, ReceiptPoll, ReceiptWait};
+use rpc::{HttpTempoRpc, TempoRpc};
+use tx::{append_sender_hint, encode_signed_tx, TempoCall, TempoUnsignedTx};
+
+const GAS_LIMIT_BUFFER: u64 = 250_000;
+const DEFAULT_EXPIRY_WINDOW_SECS: u64 = 25;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_RECEIPT_GRACE_SECS: u64 = 6;
+const DEFAULT_RECEIPT_TIMEOUT_SECS: u64 = 45;
+const DEFAULT_RECEIPT_POLL_INTERVAL_MS: u64 = 1_250;
+const DEFAULT_LANE_WAIT_SECS: u64 = 60;
+/// Tempo's nonce manager precompile, which tracks sequences for non-zero nonce keys.
+const DEFAULT_NONCE_MANAGER: &str = "0x4E4F4E4345000000000000000000000000000000";
+
+sol! {
+    function getNonce(address account, uint256 nonceKey) view returns (uint64);
+}
+
+#[derive(Debug, Clone)]
+pub struct TempoEndpoints {
+    pub rpc_url: String,
+    pub fee_payer_url: String,
+    pub chain_id: u64,
+}
+
+/// A session key authorized to sign for `account` via the account keychain.
+#[derive(Debug, Clone)]
+pub struct TempoSessionKey {
+    account: Address,
+    wallet: LocalWallet,
+    expires_at: Option<u64>,
+}
+
+impl TempoSessionKey {
+    pub fn new(
+        account: &str,
+        session_address: &str,
+        private_key: &str,
+        expires_at: Option<u64>,
+    ) -> Result<Self, TempoTxError> {
+        let account = parse_address(account, "wallet address").map_err(TempoTxError::Session)?;
+        let session_address =
+            parse_address(session_address, "session address").map_err(TempoTxError::Session)?;
+        let wallet = LocalWallet::from_str(private_key).map_err(|e| {
+            TempoTxError::Session(format!("Invalid Tempo session private key: {e}"))
+        })?;
+        if wallet.address() != ethers::types::Address::from_slice(session_address.as_slice()) {
+            return Err(TempoTxError::Session(
+                "Tempo session private key does not match the callback session address."
+                    .to_string(),
+            ));
+        }
+        Ok(Self {
+            account,
+            wallet,
+            expires_at,
+        })
+    }
+
+    fn ensure_fresh(&self) -> Result<(), TempoTxError> {
+        match self.expires_at {
+            Some(expires_at) if unix_now() >= expires_at => Err(TempoTxError::Session(
+                "Tempo session key has expired. Sign in again to refresh it.".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// One contract call to submit. `label` names the operation in logs, errors and the journal.
+#[derive(Debug, Clone)]
+pub struct ContractCall {
+    pub kind: TxKind,
+    pub to: Address,
+    pub data: Vec<u8>,
+    pub gas_limit_min: u64,
+    pub label: String,
+    /// Activity record this call resubmits.
+    pub retry_of: Option<String>,
+}
+
+impl ContractCall {
+    pub fn new(
+        kind: TxKind,
+        to: Address,
+        data: Vec<u8>,
+        gas_limit_min: u64,
+        label: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            to,
+            data,
+            gas_limit_min,
+            label: label.into(),
+            retry_of: None,
+        }
+    }
+
+    /// Rebuild the call a failed activity record describes.
+    pub fn from_activity(activity: &TxActivity) -> Result<Self, String> {
+        let to = parse_address(&activity.target, "activity target")?;
+        let data = hex::decode(strip_0x(&activity.call_data))
+            .map_err(|e| format!("Invalid activity call data: {e}"))?;
+        Ok(Self {
+            retry_of: Some(activity.id.clone()),
+            ..Self::new(
+                activity.kind,
+                to,
+                data,
+                activity.gas_limit_min,
+                activity.label.clone(),
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TempoReceipt {
+    pub tx_hash: String,
+    pub summary: ReceiptSummary,
+    /// Broadcasts it took, counting expired attempts that were re-sent with bumped fees.
+    pub attempts: u32,
+}
+
+/// A send's outcome with the id of the activity record that tracks it, so callers can link
+/// their status messages to that record.
+#[derive(Debug, Clone)]
+pub struct TrackedSend {
+    pub activity_id: String,
+    pub result: Result<TempoReceipt, TempoTxError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TempoTxError {
+    /// Session key missing, malformed, expired or not matching the callback address.
+    Session(String),
+    /// Transport or JSON-RPC failure before anything was broadcast.
+    Rpc(String),
+    /// The `eth_call` dry run reverted; nothing was broadcast.
+    Simulation { label: String, reason: String },
+    /// The fee payer refused to co-sign.
+    FeePayer(String),
+    /// The node rejected `eth_sendRawTransaction`.
+    Broadcast(String),
+    /// Mined with status 0.
+    Reverted {
+        label: String,
+        tx_hash: String,
+        summary: ReceiptSummary,
+    },
+    /// Every attempt expired before inclusion.
+    Expired {
+        label: String,
+        attempts: u32,
+        last_tx_hash: String,
+    },
+    /// Receipt polling kept failing; the tx may or may not have landed.
+    ReceiptUnavailable { tx_hash: String, reason: String },
+    /// No nonce lane became free in time.
+    NonceLanes(String),
+}
+
+impl fmt::Display for TempoTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Session(message)
+            | Self::Rpc(message)
+            | Self::Broadcast(message)
+            | Self::NonceLanes(message) => f.write_str(message),
+            Self::FeePayer(message) => write!(f, "Fee payer refused to sign: {message}"),
+            Self::Simulation { label, reason } => {
+                write!(f, "{label} tx would revert: {reason}")
+            }
+            Self::Reverted {
+                label,
+                tx_hash,
+                summary,
+            } => write!(
+                f,
+                "{label} tx reverted: txHash={tx_hash} {}",
+                summary.describe()
+            ),
+            Self::Expired {
+                label,
+                attempts,
+                last_tx_hash,
+            } => write!(
+                f,
+                "{label} tx not included before expiry after {attempts} attempts (last txHash={last_tx_hash})."
+            ),
+            Self::ReceiptUnavailable { tx_hash, reason } => {
+                write!(f, "Receipt poll failed: txHash={tx_hash} {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TempoTxError {}
+
+impl From<TempoTxError> for String {
+    fn from(err: TempoTxError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Submission timing. Defaults match the network; tests shrink them.
+#[derive(Debug, Clone)]
+pub struct TxPolicy {
+    pub expiry_window_secs: u64,
+    pub max_attempts: u32,
+    pub receipt_grace_secs: u64,
+    pub receipt_timeout: Duration,
+    pub poll_interval: Duration,
+    pub lane_wait: Duration,
+}
+
+impl Default for TxPolicy {
+    fn default() -> Self {
+        let receipt_timeout_secs = std::env::var("HEAVEN_SCROBBLE_RECEIPT_TIMEOUT_SECS")
+            .ok()
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_RECEIPT_TIMEOUT_SECS);
+        Self {
+            expiry_window_secs: DEFAULT_EXPIRY_WINDOW_SECS,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            receipt_grace_secs: DEFAULT_RECEIPT_GRACE_SECS,
+            receipt_timeout: Duration::from_secs(receipt_timeout_secs),
+            poll_interval: Duration::from_millis(DEFAULT_RECEIPT_POLL_INTERVAL_MS),
+            lane_wait: Duration::from_secs(DEFAULT_LANE_WAIT_SECS),
+        }
+    }
+}
+
+pub struct TempoClient {
+    endpoints: TempoEndpoints,
+    key: TempoSessionKey,
+    rpc: Arc<dyn TempoRpc>,
+    lanes: Arc<NonceLanes>,
+    journal: Option<Arc<TxJournal>>,
+    activity: Option<Arc<TxActivityLog>>,
+    policy: TxPolicy,
+}
+
+impl TempoClient {
+    /// Client over HTTP with the process-wide nonce lanes, tx journal and activity log.
+    pub fn new(endpoints: TempoEndpoints, key: TempoSessionKey) -> Self {
+        Self::with_parts(
+            endpoints,
+            key,
+            Arc::new(HttpTempoRpc),
+            NonceLanes::shared(),
+            Some(TxJournal::shared()),
+            Some(TxActivityLog::shared()),
+            TxPolicy::default(),
+        )
+    }
+
+    fn with_parts(
+        endpoints: TempoEndpoints,
+        key: TempoSessionKey,
+        rpc: Arc<dyn TempoRpc>,
+        lanes: Arc<NonceLanes>,
+        journal: Option<Arc<TxJournal>>,
+        activity: Option<Arc<TxActivityLog>>,
+        policy: TxPolicy,
+    ) -> Self {
+        Self {
+            endpoints,
+            key,
+            rpc,
+            lanes,
+            journal,
+            activity,
+            policy,
+        }
+    }
+
+    pub fn account(&self) -> Address {
+        self.key.account
+    }
+
+    /// Simulate, sign, co-sign, broadcast and await `call`, tracking it in the activity log.
+    /// Blocking.
+    pub fn send(&self, call: &ContractCall) -> Result<TempoReceipt, TempoTxError> {
+        self.send_tracked(call).result
+    }
+
+    /// [`Self::send`], also returning the id of the activity record written for `call`.
+    pub fn send_tracked(&self, call: &ContractCall) -> TrackedSend {
+        let now = unix_now();
+        let mut activity = TxActivity {
+            id: TxActivity::new_id(now),
+            kind: call.kind,
+            label: call.label.clone(),
+            target: format!("{:#x}", call.to),
+            sender: format!("{:#x}", self.key.account),
+            tx_hash: None,
+            status: TxActivityStatus::Pending,
+            gas_used: None,
+            error: None,
+            attempts: 0,
+            started_at: now,
+            updated_at: now,
+            call_data: format!("0x{}", hex::encode(&call.data)),
+            gas_limit_min: call.gas_limit_min,
+            retry_of: call.retry_of.clone(),
+        };
+        self.track(&mut activity);
+
+        let result = self.submit(call, &mut activity);
+        match &result {
+            Ok(receipt) => {
+                activity.status = TxActivityStatus::Confirmed;
+                activity.tx_hash = Some(receipt.tx_hash.clone());
+                activity.gas_used = receipt.summary.gas_used_value();
+                activity.attempts = receipt.attempts;
+            }
+            Err(err) => {
+                activity.status = match err {
+                    TempoTxError::Reverted { .. } => TxActivityStatus::Reverted,
+                    _ => TxActivityStatus::Failed,
+                };
+                if let TempoTxError::Reverted { summary, .. } = err {
+                    activity.gas_used = summary.gas_used_value();
+                }
+                activity.error = Some(err.to_string());
+            }
+        }
+        self.track(&mut activity);
+        TrackedSend {
+            activity_id: activity.id,
+            result,
+        }
+    }
+
+    fn submit(
+        &self,
+        call: &ContractCall,
+        activity: &mut TxActivity,
+    ) -> Result<TempoReceipt, TempoTxError> {
+        self.key.ensure_fresh()?;
+        let started_at = Instant::now();
+        let account = self.key.account;
+        self.simulate(call)?;
+        let mut fees = self.suggested_fees()?;
+        let gas_limit = self.estimate_gas(call);
+        let lease = self
+            .lanes
+            .acquire(account, self.policy.lane_wait, |key| self.lane_nonce(key))
+            .map_err(TempoTxError::NonceLanes)?;
+
+        let mut last_tx_hash = String::new();
+        for attempt in 1..=self.policy.max_attempts {
+            let valid_before = unix_now().saturating_add(self.policy.expiry_window_secs);
+            log::info!(
+                "[Tempo] {} tx params: attempt={}/{} nonceMode={} nonceKey={} nonce={} validBefore={} gasLimit={} maxFeePerGas={} maxPriorityFeePerGas={}",
+                call.label,
+                attempt,
+                self.policy.max_attempts,
+                if lease.is_expiring() { "expiring" } else { "lane" },
+                lease.nonce_key(),
+                lease.nonce(),
+                valid_before,
+                gas_limit,
+                fees.max_fee_per_gas,
+                fees.max_priority_fee_per_gas
+            );
+            let unsigned = TempoUnsignedTx {
+                chain_id: U256::from(self.endpoints.chain_id),
+                max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+                max_fee_per_gas: fees.max_fee_per_gas,
+                gas_limit,
+                calls: vec![TempoCall {
+                    to: call.to,
+                    value: U256::ZERO,
+                    input: call.data.clone(),
+                }],
+                nonce_key: lease.nonce_key(),
+                nonce: lease.nonce(),
+                valid_before: Some(U256::from(valid_before)),
+                valid_after: None,
+                // Session-key txs must not re-attach the key authorization.
+                key_authorization: None,
+            };
+            let mut entry = TxJournalEntry {
+                at: unix_now(),
+                label: call.label.clone(),
+                sender: format!("{account:#x}"),
+                to: format!("{:#x}", call.to),
+                tx_hash: None,
+                attempt,
+                nonce_key: lease.nonce_key().to_string(),
+                nonce: lease.nonce().to_string(),
+                valid_before: Some(valid_before),
+                max_fee_per_gas: fees.max_fee_per_gas.to_string(),
+                status: TxJournalStatus::Failed,
+                detail: None,
+            };
+
+            let signed_tx = encode_signed_tx(&unsigned, &self.key.wallet, account)
+                .map_err(TempoTxError::Session)?;
+            let tx_with_hint = append_sender_hint(&signed_tx, account);
+            let relay_signed_tx = match self.rpc_string(
+                &self.endpoints.fee_payer_url,
+                "eth_signRawTransaction",
+                json!([tx_with_hint]),
+            ) {
+                Ok(signed) => signed,
+                Err(err) => {
+                    self.record(&entry, TxJournalStatus::Failed, Some(&err));
+                    lease.finish(LaneOutcome::NotIncluded);
+                    return Err(TempoTxError::FeePayer(err));
+                }
+            };
+            let tx_hash = match self.rpc_string(
+                &self.endpoints.rpc_url,
+                "eth_sendRawTransaction",
+                json!([relay_signed_tx]),
+            ) {
+                Ok(tx_hash) => tx_hash,
+                Err(err) => {
+                    // A rejected broadcast can mean a stale lane nonce; the lease refetches.
+                    self.record(&entry, TxJournalStatus::Failed, Some(&err));
+                    return Err(TempoTxError::Broadcast(err));
+                }
+            };
+            entry.tx_hash = Some(tx_hash.clone());
+            self.record(&entry, TxJournalStatus::Sent, None);
+            activity.tx_hash = Some(tx_hash.clone());
+            activity.attempts = attempt;
+            self.track(activity);
+            log::info!(
+                "[Tempo] {} broadcast accepted: txHash={} elapsedMs={}",
+                call.label,
+                tx_hash,
+                started_at.elapsed().as_millis()
+            );
+
+            let poll = ReceiptPoll {
+                expiry_deadline_secs: valid_before.saturating_add(self.policy.receipt_grace_secs),
+                timeout: self.policy.receipt_timeout,
+                interval: self.policy.poll_interval,
+            };
+            match await_receipt(self.rpc.as_ref(), &self.endpoints.rpc_url, &tx_hash, &poll) {
+                Ok(ReceiptWait::Mined(summary)) if summary.reverted() => {
+                    self.record(&entry, TxJournalStatus::Reverted, Some(&summary.describe()));
+                    lease.finish(LaneOutcome::Included);
+                    return Err(TempoTxError::Reverted {
+                        label: call.label.clone(),
+                        tx_hash,
+                        summary,
+                    });
+                }
+                Ok(ReceiptWait::Mined(summary)) => {
+                    self.record(
+                        &entry,
+                        TxJournalStatus::Confirmed,
+                        Some(&summary.describe()),
+                    );
+                    lease.finish(LaneOutcome::Included);
+                    log::info!(
+                        "[Tempo] {} receipt confirmed: txHash={} {} elapsedMs={}",
+                        call.label,
+                        tx_hash,
+                        summary.describe(),
+                        started_at.elapsed().as_millis()
+                    );
+                    return Ok(TempoReceipt {
+                        tx_hash,
+                        summary,
+                        attempts: attempt,
+                    });
+                }
+                Ok(ReceiptWait::NotIncluded) => {
+                    self.record(&entry, TxJournalStatus::Expired, None);
+                    last_tx_hash = tx_hash;
+                    fees = fees.bumped();
+                    log::warn!(
+                        "[Tempo] {} tx expired before inclusion: txHash={} attempt={}/{}; bumping fees maxFeePerGas={} maxPriorityFeePerGas={}",
+                        call.label,
+                        last_tx_hash,
+                        attempt,
+                        self.policy.max_attempts,
+                        fees.max_fee_per_gas,
+                        fees.max_priority_fee_per_gas
+                    );
+                }
+                Err(reason) => {
+                    self.record(&entry, TxJournalStatus::Failed, Some(&reason));
+                    return Err(TempoTxError::ReceiptUnavailable { tx_hash, reason });
+                }
+            }
+        }
+
+        lease.finish(LaneOutcome::NotIncluded);
+        Err(TempoTxError::Expired {
+            label: call.label.clone(),
+            attempts: self.policy.max_attempts,
+            last_tx_hash,
+        })
+    }
+
+    /// Dry-run `call` as the account. Only an actual revert blocks the send; an RPC that
+    /// cannot simulate is logged and skipped.
+    fn simulate(&self, call: &ContractCall) -> Result<(), TempoTxError> {
+        let params = json!([
+            {
+                "from": format!("{:#x}", self.key.account),
+                "to": format!("{:#x}", call.to),
+                "data": format!("0x{}", hex::encode(&call.data)),
+            },
+            "latest",
+        ]);
+        match self
+            .rpc
+            .request(&self.endpoints.rpc_url, "eth_call", params)
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.to_ascii_lowercase().contains("revert") => {
+                Err(TempoTxError::Simulation {
+                    label: call.label.clone(),
+                    reason: err,
+                })
+            }
+            Err(err) => {
+                log::warn!("[Tempo] {} simulation unavailable: {}", call.label, err);
+                Ok(())
+            }
+        }
+    }
+
+    fn suggested_fees(&self) -> Result<SuggestedFees, TempoTxError> {
+        let gas_price = self
+            .rpc_string(&self.endpoints.rpc_url, "eth_gasPrice", json!([]))
+            .and_then(|raw| parse_hex_u256(&raw))
+            .map_err(TempoTxError::Rpc)?;
+        Ok(SuggestedFees::from_gas_price(gas_price))
+    }
+
+    /// `eth_estimateGas` plus a buffer, never below the call's floor; the floor alone if the
+    /// estimate fails.
+    fn estimate_gas(&self, call: &ContractCall) -> U256 {
+        let minimum = U256::from(call.gas_limit_min);
+        let params = json!([{
+            "from": format!("{:#x}", self.key.account),
+            "to": format!("{:#x}", call.to),
+            "data": format!("0x{}", hex::encode(&call.data)),
+        }]);
+        let estimated = self
+            .rpc_string(&self.endpoints.rpc_url, "eth_estimateGas", params)
+            .and_then(|raw| parse_hex_u256(&raw))
+            .map(|value| value + U256::from(GAS_LIMIT_BUFFER));
+        match estimated {
+            Ok(value) => value.max(minimum),
+            Err(err) => {
+                log::warn!(
+                    "[Tempo] eth_estimateGas failed; using minimum gas limit {}: {}",
+                    minimum,
+                    err
+                );
+                minimum
+            }
+        }
+    }
+
+    fn lane_nonce(&self, nonce_key: u64) -> Result<u64, String> {
+        let nonce_manager = std::env::var("HEAVEN_TEMPO_NONCE_MANAGER")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_NONCE_MANAGER.to_string());
+        let nonce_manager = parse_address(&nonce_manager, "nonce manager address")?;
+        let data = getNonceCall {
+            account: self.key.account,
+            nonceKey: U256::from(nonce_key),
+        }
+        .abi_encode();
+        let params = json!([
+            {
+                "to": format!("{nonce_manager:#x}"),
+                "data": format!("0x{}", hex::encode(data)),
+            },
+            "latest",
+        ]);
+        let raw = self
+            .rpc
+            .request(&self.endpoints.rpc_url, "eth_call", params)?;
+        let word = parse_hex_u256(raw.as_str().ok_or("getNonce returned non-string result")?)?;
+        u64::try_from(word).map_err(|_| format!("getNonce returned out-of-range nonce {word}"))
+    }
+
+    fn rpc_string(&self, url: &str, method: &str, params: Value) -> Result<String, String> {
+        let result = self.rpc.request(url, method, params)?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{method} returned non-string result: {result}"))
+    }
+
+    fn track(&self, activity: &mut TxActivity) {
+        let Some(log) = self.activity.as_ref() else {
+            return;
+        };
+        activity.updated_at = unix_now();
+        if let Err(err) = log.record(activity) {
+            log::warn!("[Tempo] tx activity write failed: {}", err);
+        }
+    }
+
+    fn record(&self, entry: &TxJournalEntry, status: TxJournalStatus, detail: Option<&str>) {
+        let Some(journal) = self.journal.as_ref() else {
+            return;
+        };
+        let entry = TxJournalEntry {
+            at: unix_now(),
+            status,
+            detail: detail.map(str::to_string),
+            ..entry.clone()
+        };
+        if let Err(err) = journal.append(&entry) {
+            log::warn!("[Tempo] tx journal write failed: {}", err);
+        }
+    }
+}
+
+/// One receipt lookup, for settling a send that ended in
+/// [`TempoTxError::ReceiptUnavailable`]; `None` while the node has no receipt. Blocking.
+pub fn lookup_receipt(rpc_url: &str, tx_hash: &str) -> Result<Option<ReceiptSummary>, String> {
+    match HttpTempoRpc.request(rpc_url, "eth_getTransactionReceipt", json!([tx_hash]))? {
+        Value::Null => Ok(None),
+        receipt => Ok(Some(ReceiptSummary::from_json(&receipt))),
+    }
+}
+
+/// Seconds after a broadcast by which its `validBefore` and the receipt grace have passed;
+/// a tx with no receipt by then was never included.
+pub fn inclusion_window_secs() -> u64 {
+    let policy = TxPolicy::default();
+    policy
+        .expiry_window_secs
+        .saturating_add(policy.receipt_grace_secs)
+}
+
+fn parse_address(value: &str, label: &str) -> Result<Address, String> {
+    value
+        .trim()
+        .parse::<Address>()
+        .map_err(|e| format!("Invalid {label}: {e}"))
+}
+
+fn parse_hex_u256(value: &str) -> Result<U256, String> {
+    let clean = strip_0x(value);
+    if clean.is_empty() {
+        return Ok(U256::ZERO);
+    }
+    U256::from_str_radix(clean, 16).map_err(|e| format!("invalid hex quantity '{value}': {e}"))
+}
+
+fn strip_0x(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::http_stub::{HttpStub, StubResponse};
+    use std::collections::{HashMap, VecDeque};
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    const SESSION_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const SESSION_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const ACCOUNT: &str = "0x70997970C51812dc3A010C7d01b50e79d17dc79C";
+    const CONTRACT: &str = "0xe00f7a3be3b0a5cdb9c6c4e1d2ec27a8b3bb81e1";
+
+    /// Replays recorded responses per method; the last response of each method repeats.
+    /// Receipts are keyed by tx hash and default to `null` (pending).
+    struct FixtureRpc {
+        responses: Mutex<HashMap<String, VecDeque<Value>>>,
+        receipts: HashMap<String, Value>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl FixtureRpc {
+        fn from_json(fixture: Value) -> Self {
+            let responses = fixture["responses"]
+                .as_object()
+                .map(|methods| {
+                    methods
+                        .iter()
+                        .map(|(method, values)| {
+                            let queue = values.as_array().cloned().unwrap_or_default();
+                            (method.clone(), queue.into())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let receipts = fixture["receipts"]
+                .as_object()
+                .map(|receipts| receipts.clone().into_iter().collect())
+                .unwrap_or_default();
+            Self {
+                responses: Mutex::new(responses),
+                receipts,
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn count(&self, method: &str) -> usize {
+            self.calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|m| *m == method)
+                .count()
+        }
+    }
+
+    impl TempoRpc for FixtureRpc {
+        fn request(&self, _url: &str, method: &str, params: Value) -> Result<Value, String> {
+            self.calls.lock().unwrap().push(method.to_string());
+            if method == "eth_getTransactionReceipt" {
+                let tx_hash = params[0].as_str().unwrap_or_default();
+                return Ok(self.receipts.get(tx_hash).cloned().unwrap_or(Value::Null));
+            }
+            let mut responses = self.responses.lock().unwrap();
+            let queue = responses
+                .get_mut(method)
+                .ok_or_else(|| format!("no fixture for {method}"))?;
+            let value = if queue.len() > 1 {
+                queue.pop_front().unwrap()
+            } else {
+                queue.front().cloned().unwrap_or(Value::Null)
+            };
+            match value.get("error").and_then(Value::as_str) {
+                Some(err) => Err(err.to_string()),
+                None => Ok(value),
+            }
+        }
+    }
+
+    fn temp_journal(name: &str) -> (Arc<TxJournal>, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "heaven-tempo-journal-{name}-{}-{}.jsonl",
+            std::process::id(),
+            unix_now()
+        ));
+        let _ = std::fs::remove_file(&path);
+        (Arc::new(TxJournal::new(path.clone())), path)
+    }
+
+    fn journal_entries(path: &PathBuf) -> Vec<TxJournalEntry> {
+        std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("journal line"))
+            .collect()
+    }
+
+    fn fast_policy() -> TxPolicy {
+        TxPolicy {
+            receipt_timeout: Duration::from_millis(50),
+            poll_interval: Duration::from_millis(1),
+            lane_wait: Duration::from_millis(200),
+            ..TxPolicy::default()
+        }
+    }
+
+    fn client(
+        rpc_url: &str,
+        rpc: Arc<dyn TempoRpc>,
+        lanes: Arc<NonceLanes>,
+        journal: Arc<TxJournal>,
+        activity: Option<Arc<TxActivityLog>>,
+    ) -> TempoClient {
+        TempoClient::with_parts(
+            TempoEndpoints {
+                rpc_url: rpc_url.to_string(),
+                fee_payer_url: rpc_url.to_string(),
+                chain_id: 42_431,
+            },
+            TempoSessionKey::new(ACCOUNT, SESSION_ADDRESS, SESSION_KEY, None).unwrap(),
+            rpc,
+            lanes,
+            Some(journal),
+            activity,
+            fast_policy(),
+        )
+    }
+
+    fn call(data: Vec<u8>) -> ContractCall {
+        ContractCall::new(
+            TxKind::Playlist,
+            CONTRACT.parse().unwrap(),
+            data,
+            100_000,
+            "Test",
+        )
+    }
+
+    #[test]
+    fn session_key_must_match_callback_address() {
+        let err = TempoSessionKey::new(ACCOUNT, ACCOUNT, SESSION_KEY, None).unwrap_err();
+        assert!(matches!(err, TempoTxError::Session(_)));
+        let expired = TempoSessionKey::new(ACCOUNT, SESSION_ADDRESS, SESSION_KEY, Some(1)).unwrap();
+        assert!(expired.ensure_fresh().is_err());
+    }
+
+    #[test]
+    fn expired_attempt_is_rebroadcast_with_bumped_fees() {
+        let rpc = Arc::new(FixtureRpc::from_json(
+            serde_json::from_str(include_str!("tempo/fixtures/expired_then_confirmed.json"))
+                .unwrap(),
+        ));
+        let (journal, path) = temp_journal("expired");
+        let activity_path = path.with_extension("activity.jsonl");
+        let activity = Arc::new(TxActivityLog::new(activity_path.clone()));
+        let client = client(
+            "http://fixture",
+            rpc.clone(),
+            NonceLanes::keyed(0),
+            journal,
+            Some(activity.clone()),
+        );
+
+        let sent = client.send_tracked(&call(vec![0x01, 0x02]));
+        let receipt = sent.result.unwrap();
+        assert_eq!(receipt.attempts, 2);
+        assert_eq!(receipt.summary.block_number.as_deref(), Some("0x1b2e40"));
+        assert_eq!(rpc.count("eth_sendRawTransaction"), 2);
+
+        let entries = journal_entries(&path);
+        let statuses: Vec<_> = entries.iter().map(|entry| entry.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                TxJournalStatus::Sent,
+                TxJournalStatus::Expired,
+                TxJournalStatus::Sent,
+                TxJournalStatus::Confirmed
+            ]
+        );
+        let first_fee = U256::from_str(&entries[0].max_fee_per_gas).unwrap();
+        let retry_fee = U256::from_str(&entries[2].max_fee_per_gas).unwrap();
+        assert!(retry_fee > first_fee);
+        assert_eq!(entries[0].nonce_key, U256::MAX.to_string());
+
+        let records = activity.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, sent.activity_id);
+        assert_eq!(records[0].status, TxActivityStatus::Confirmed);
+        assert_eq!(
+            records[0].tx_hash.as_deref(),
+            Some(receipt.tx_hash.as_str())
+        );
+        assert_eq!(
+            (records[0].attempts, records[0].gas_used),
+            (2, Some(0x2f4a1))
+        );
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&activity_path);
+    }
+
+    #[test]
+    fn simulation_revert_sends_nothing() {
+        let rpc = Arc::new(FixtureRpc::from_json(json!({
+            "responses": {
+                "eth_call": [{ "error": "execution reverted: cover already set" }],
+                "eth_gasPrice": ["0x1"],
+            }
+        })));
+        let (journal, path) = temp_journal("simulation");
+        let client = client(
+            "http://fixture",
+            rpc.clone(),
+            NonceLanes::keyed(0),
+            journal,
+            None,
+        );
+
+        let err = client.send(&call(vec![0x01])).unwrap_err();
+        assert!(matches!(err, TempoTxError::Simulation { .. }), "{err}");
+        assert_eq!(rpc.count("eth_gasPrice"), 0);
+        assert_eq!(rpc.count("eth_sendRawTransaction"), 0);
+        assert!(journal_entries(&path).is_empty());
+    }
+
+    #[test]
+    fn failed_send_is_logged_and_its_retry_links_back() {
+        let rpc = Arc::new(FixtureRpc::from_json(json!({
+            "responses": {
+                "eth_call": [{ "error": "execution reverted: not owner" }],
+            }
+        })));
+        let (journal, path) = temp_journal("activity-retry");
+        let activity_path = path.with_extension("activity.jsonl");
+        let activity = Arc::new(TxActivityLog::new(activity_path.clone()));
+        let client = client(
+            "http://fixture",
+            rpc,
+            NonceLanes::keyed(0),
+            journal,
+            Some(activity.clone()),
+        );
+
+        let sent = client.send_tracked(&call(vec![0xab, 0xcd]));
+        assert!(sent.result.is_err());
+        let failed = activity.get(&sent.activity_id).unwrap();
+        assert_eq!(failed.status, TxActivityStatus::Failed);
+        assert!(failed.error.as_deref().unwrap().contains("not owner"));
+        assert_eq!(failed.tx_hash, None);
+        assert_eq!(failed.call_data, "0xabcd");
+        assert!(failed.can_retry());
+
+        let retry = ContractCall::from_activity(&failed).unwrap();
+        assert_eq!(retry.data, vec![0xab, 0xcd]);
+        assert_eq!(retry.to, CONTRACT.parse::<Address>().unwrap());
+        assert_eq!(retry.retry_of.as_deref(), Some(sent.activity_id.as_str()));
+        let resent = client.send_tracked(&retry);
+        let records = activity.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, resent.activity_id);
+        assert_eq!(
+            records[0].retry_of.as_deref(),
+            Some(sent.activity_id.as_str())
+        );
+
+        let mut corrupt = failed;
+        corrupt.call_data = "0xzz".to_string();
+        assert!(ContractCall::from_activity(&corrupt).is_err());
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&activity_path);
+    }
+
+    #[derive(Default)]
+    struct StandInState {
+        nonce_fetches: usize,
+        sent: Vec<bool>,
+        /// Fail every receipt lookup, as a node that accepted a tx and then went away.
+        withhold_receipts: bool,
+    }
+
+    /// A minimal anvil-style node over HTTP: it acts as RPC and fee payer, mines every tx
+    /// immediately and reverts calls whose data contains `deadbeefdeadbeef`.
+    fn spawn_stand_in_node() -> (String, Arc<Mutex<StandInState>>) {
+        let state = Arc::new(Mutex::new(StandInState::default()));
+        let server_state = state.clone();
+        let nonce_manager = DEFAULT_NONCE_MANAGER.to_ascii_lowercase();
+        let node = HttpStub::spawn(move |request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+            let params = &request["params"];
+
+            let mut state = server_state.lock().unwrap();
+            let result = match request["method"].as_str().unwrap_or_default() {
+                "eth_call" => {
+                    let to = params[0]["to"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_ascii_lowercase();
+                    if to == nonce_manager {
+                        state.nonce_fetches += 1;
+                        json!(format!("0x{:064x}", 7))
+                    } else {
+                        json!("0x")
+                    }
+                }
+                "eth_gasPrice" => json!("0x3b9aca00"),
+                "eth_estimateGas" => json!("0x5208"),
+                "eth_signRawTransaction" => {
+                    // Drop the sender hint: 20-byte address plus the 6-byte marker.
+                    let hinted = params[0].as_str().unwrap_or_default();
+                    json!(hinted[..hinted.len() - 52].to_string())
+                }
+                "eth_sendRawTransaction" => {
+                    let raw = params[0].as_str().unwrap_or_default();
+                    state.sent.push(raw.contains("deadbeefdeadbeef"));
+                    json!(format!("0x{:064x}", state.sent.len()))
+                }
+                "eth_getTransactionReceipt" if state.withhold_receipts => {
+                    return StubResponse::new(503, r#"{"error":"upstream unavailable"}"#);
+                }
+                "eth_getTransactionReceipt" => {
+                    let index = params[0]
+                        .as_str()
+                        .and_then(|hash| u64::from_str_radix(strip_0x(hash), 16).ok())
+                        .unwrap_or(0) as usize;
+                    let reverted = state
+                        .sent
+                        .get(index.wrapping_sub(1))
+                        .copied()
+                        .unwrap_or(false);
+                    json!({
+                        "status": if reverted { "0x0" } else { "0x1" },
+                        "blockNumber": format!("0x{index:x}"),
+                        "gasUsed": "0x5208",
+                    })
+                }
+                _ => Value::Null,
+            };
+            StubResponse::new(
+                200,
+                json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string(),
+            )
+        });
+        (node.url, state)
+    }
+
+    #[test]
+    fn keyed_lane_advances_nonce_locally_across_sends_and_reverts() {
+        let (url, state) = spawn_stand_in_node();
+        let (journal, path) = temp_journal("stand-in");
+        let client = client(
+            &url,
+            Arc::new(HttpTempoRpc),
+            NonceLanes::keyed(1),
+            journal,
+            None,
+        );
+
+        client.send(&call(vec![0x01])).unwrap();
+        let err = client
+            .send(&call(hex::decode("deadbeefdeadbeef").unwrap()))
+            .unwrap_err();
+        assert!(matches!(err, TempoTxError::Reverted { .. }), "{err}");
+        let receipt = client.send(&call(vec![0x02])).unwrap();
+        assert_eq!(receipt.attempts, 1);
+
+        assert_eq!(state.lock().unwrap().nonce_fetches, 1);
+        let entries = journal_entries(&path);
+        let settled: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.status != TxJournalStatus::Sent)
+            .map(|entry| (entry.nonce_key.as_str(), entry.nonce.as_str(), entry.status))
+            .collect();
+        assert_eq!(
+            settled,
+            vec![
+                ("1", "7", TxJournalStatus::Confirmed),
+                ("1", "8", TxJournalStatus::Reverted),
+                ("1", "9", TxJournalStatus::Confirmed),
+            ]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn accepted_tx_without_receipt_is_reported_unconfirmed_not_resent() {
+        let (url, state) = spawn_stand_in_node();
+        state.lock().unwrap().withhold_receipts = true;
+        let (journal, path) = temp_journal("no-receipt");
+        let client = client(
+            &url,
+            Arc::new(HttpTempoRpc),
+            NonceLanes::keyed(1),
+            journal,
+            None,
+        );
+
+        let err = client.send(&call(vec![0x01])).unwrap_err();
+        let tx_hash = match err {
+            TempoTxError::ReceiptUnavailable { tx_hash, .. } => tx_hash,
+            other => panic!("expected ReceiptUnavailable, got {other}"),
+        };
+        assert_eq!(
+            state.lock().unwrap().sent.len(),
+            1,
+            "the tx is not re-broadcast"
+        );
+        assert!(lookup_receipt(&url, &tx_hash).is_err());
+
+        state.lock().unwrap().withhold_receipts = false;
+        let receipt = lookup_receipt(&url, &tx_hash).unwrap().expect("mined");
+        assert!(!receipt.reverted());
+        let _ = std::fs::remove_file(&path);
+    }
+}
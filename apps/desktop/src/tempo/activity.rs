@@ -0,0 +1,424 @@
+//! User-facing log of on-chain actions: one record per `TempoClient::send`, updated as the
+//! tx moves from pending to its outcome. Records are JSONL snapshots (the last line for an
+//! id wins) so a crash never loses more than the latest transition.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+const ACTIVITY_FILE: &str = "tempo-activity.jsonl";
+const DEFAULT_EXPLORER_URL: &str = "https://explore.moderato.tempo.xyz";
+/// Records kept in memory and after compaction.
+const MAX_RECORDS: usize = 500;
+
+/// One log per account dir, so switching accounts never shows or retries another's txs.
+static SHARED_LOGS: OnceLock<Mutex<HashMap<PathBuf, Arc<TxActivityLog>>>> = OnceLock::new();
+static NEXT_ACTIVITY_SEQ: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxKind {
+    Scrobble,
+    TrackCover,
+    TrackLyrics,
+    Playlist,
+    PlaylistShare,
+    ContentKey,
+}
+
+impl TxKind {
+    pub const ALL: [TxKind; 6] = [
+        TxKind::Scrobble,
+        TxKind::TrackCover,
+        TxKind::TrackLyrics,
+        TxKind::Playlist,
+        TxKind::PlaylistShare,
+        TxKind::ContentKey,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TxKind::Scrobble => "Scrobbles",
+            TxKind::TrackCover => "Cover art",
+            TxKind::TrackLyrics => "Lyrics",
+            TxKind::Playlist => "Playlists",
+            TxKind::PlaylistShare => "Access grants",
+            TxKind::ContentKey => "Content registration",
+        }
+    }
+
+    /// Scrobbles are retried by the outbox and history import; resubmitting them from the
+    /// log would double-count plays.
+    fn retryable(self) -> bool {
+        self != TxKind::Scrobble
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxActivityStatus {
+    Pending,
+    Confirmed,
+    Reverted,
+    Failed,
+}
+
+impl TxActivityStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            TxActivityStatus::Pending => "Pending",
+            TxActivityStatus::Confirmed => "Confirmed",
+            TxActivityStatus::Reverted => "Reverted",
+            TxActivityStatus::Failed => "Failed",
+        }
+    }
+
+    pub fn is_failure(self) -> bool {
+        matches!(self, TxActivityStatus::Reverted | TxActivityStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxActivity {
+    pub id: String,
+    pub kind: TxKind,
+    pub label: String,
+    /// Contract the call targets.
+    pub target: String,
+    pub sender: String,
+    pub tx_hash: Option<String>,
+    pub status: TxActivityStatus,
+    pub gas_used: Option<u64>,
+    pub error: Option<String>,
+    pub attempts: u32,
+    pub started_at: u64,
+    pub updated_at: u64,
+    /// `0x` calldata and gas floor, kept so a failed call can be resubmitted as-is.
+    pub call_data: String,
+    pub gas_limit_min: u64,
+    pub retry_of: Option<String>,
+}
+
+impl TxActivity {
+    pub fn new_id(now: u64) -> String {
+        let seq = NEXT_ACTIVITY_SEQ.fetch_add(1, Ordering::Relaxed);
+        format!("{now}-{}-{seq}", std::process::id())
+    }
+
+    pub fn can_retry(&self) -> bool {
+        self.status.is_failure() && self.kind.retryable()
+    }
+
+    pub fn explorer_url(&self) -> Option<String> {
+        let tx_hash = self.tx_hash.as_deref()?;
+        let base = std::env::var("HEAVEN_TEMPO_EXPLORER_URL")
+            .ok()
+            .map(|value| value.trim().trim_end_matches('/').to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| DEFAULT_EXPLORER_URL.to_string());
+        Some(format!("{base}/tx/{tx_hash}"))
+    }
+}
+
+/// Which records the activity list shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxActivityFilter {
+    pub kind: Option<TxKind>,
+    pub failed_only: bool,
+}
+
+impl TxActivityFilter {
+    pub fn matches(&self, activity: &TxActivity) -> bool {
+        self.kind.is_none_or(|kind| activity.kind == kind)
+            && (!self.failed_only || activity.status.is_failure())
+    }
+}
+
+#[derive(Default)]
+struct LogState {
+    loaded: bool,
+    /// Oldest first.
+    records: Vec<TxActivity>,
+    lines_on_disk: usize,
+}
+
+pub struct TxActivityLog {
+    path: PathBuf,
+    state: Mutex<LogState>,
+    revision: AtomicU64,
+}
+
+impl TxActivityLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            state: Mutex::new(LogState::default()),
+            revision: AtomicU64::new(0),
+        }
+    }
+
+    /// The active account's log, which its clients write to.
+    pub fn shared() -> Arc<Self> {
+        let path = crate::auth::accounts::AccountRegistry::shared().scoped_path(ACTIVITY_FILE);
+        SHARED_LOGS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(path.clone())
+            .or_insert_with(|| Arc::new(Self::new(path)))
+            .clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Bumped on every write so views can skip re-reading an unchanged log.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
+    }
+
+    /// Insert or replace `activity` and persist the snapshot.
+    pub fn record(&self, activity: &TxActivity) -> Result<(), String> {
+        let line = serde_json::to_string(activity)
+            .map_err(|e| format!("Failed encoding tx activity: {e}"))?;
+        let mut state = self.lock_loaded()?;
+        match state
+            .records
+            .iter()
+            .position(|known| known.id == activity.id)
+        {
+            Some(index) => state.records[index] = activity.clone(),
+            None => state.records.push(activity.clone()),
+        }
+        trim_records(&mut state.records);
+        self.revision.fetch_add(1, Ordering::Relaxed);
+
+        if state.lines_on_disk >= MAX_RECORDS * 2 {
+            self.rewrite(&state.records)?;
+            state.lines_on_disk = state.records.len();
+            return Ok(());
+        }
+        self.append_line(&line)?;
+        state.lines_on_disk += 1;
+        Ok(())
+    }
+
+    /// Newest first.
+    pub fn records(&self) -> Vec<TxActivity> {
+        match self.lock_loaded() {
+            Ok(state) => state.records.iter().rev().cloned().collect(),
+            Err(err) => {
+                log::warn!("[Tempo] activity log unavailable: {}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<TxActivity> {
+        let state = self.lock_loaded().ok()?;
+        state.records.iter().find(|record| record.id == id).cloned()
+    }
+
+    fn lock_loaded(&self) -> Result<std::sync::MutexGuard<'_, LogState>, String> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| format!("tx activity lock failed: {e}"))?;
+        if !state.loaded {
+            let (records, lines) = self.read_disk();
+            state.records = records;
+            state.lines_on_disk = lines;
+            state.loaded = true;
+        }
+        Ok(state)
+    }
+
+    /// Fold snapshots by id. A line torn by a crash is skipped.
+    fn read_disk(&self) -> (Vec<TxActivity>, usize) {
+        let Ok(contents) = std::fs::read_to_string(&self.path) else {
+            return (Vec::new(), 0);
+        };
+        let mut records: Vec<TxActivity> = Vec::new();
+        let mut lines = 0;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            lines += 1;
+            let Ok(activity) = serde_json::from_str::<TxActivity>(line) else {
+                continue;
+            };
+            match records.iter().position(|known| known.id == activity.id) {
+                Some(index) => records[index] = activity,
+                None => records.push(activity),
+            }
+        }
+        trim_records(&mut records);
+        (records, lines)
+    }
+
+    fn append_line(&self, line: &str) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create {}: {e}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Cannot open {}: {e}", self.path.display()))?;
+        file.write_all(format!("{line}\n").as_bytes())
+            .map_err(|e| format!("Cannot write {}: {e}", self.path.display()))
+    }
+
+    fn rewrite(&self, records: &[TxActivity]) -> Result<(), String> {
+        let mut contents = String::new();
+        for record in records {
+            let line = serde_json::to_string(record)
+                .map_err(|e| format!("Failed encoding tx activity: {e}"))?;
+            contents.push_str(&line);
+            contents.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, contents)
+            .map_err(|e| format!("Cannot write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .map_err(|e| format!("Cannot replace {}: {e}", self.path.display()))
+    }
+}
+
+fn trim_records(records: &mut Vec<TxActivity>) {
+    if records.len() > MAX_RECORDS {
+        let overflow = records.len() - MAX_RECORDS;
+        records.drain(0..overflow);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(id: &str, kind: TxKind, status: TxActivityStatus) -> TxActivity {
+        TxActivity {
+            id: id.to_string(),
+            kind,
+            label: "playlist setTracks".to_string(),
+            target: "0x22".to_string(),
+            sender: "0x11".to_string(),
+            tx_hash: None,
+            status,
+            gas_used: None,
+            error: None,
+            attempts: 0,
+            started_at: 100,
+            updated_at: 100,
+            call_data: "0x01".to_string(),
+            gas_limit_min: 1,
+            retry_of: None,
+        }
+    }
+
+    #[test]
+    fn snapshots_fold_by_id_and_survive_torn_lines() {
+        let path = std::env::temp_dir().join(format!(
+            "heaven-tempo-activity-test-{}-{}.jsonl",
+            std::process::id(),
+            crate::scrobble::now_epoch_sec()
+        ));
+        let log = TxActivityLog::new(path.clone());
+        let mut pending = activity("a", TxKind::Playlist, TxActivityStatus::Pending);
+        log.record(&pending).unwrap();
+        log.record(&activity(
+            "b",
+            TxKind::Scrobble,
+            TxActivityStatus::Confirmed,
+        ))
+        .unwrap();
+        pending.status = TxActivityStatus::Reverted;
+        pending.tx_hash = Some("0xABCD".to_string());
+        pending.error = Some("Playlist tx reverted: txHash=0xABCD".to_string());
+        log.record(&pending).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"id\":\"c\",\"kind\"")
+            .unwrap();
+
+        let reopened = TxActivityLog::new(path.clone());
+        let records = reopened.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "b", "newest first by first appearance");
+        let a = reopened.get("a").unwrap();
+        assert_eq!(a.status, TxActivityStatus::Reverted);
+        assert!(a.can_retry());
+        assert!(
+            !records[0].can_retry(),
+            "scrobbles retry through the outbox"
+        );
+
+        let failed = TxActivityFilter {
+            kind: None,
+            failed_only: true,
+        };
+        let scrobbles = TxActivityFilter {
+            kind: Some(TxKind::Scrobble),
+            failed_only: false,
+        };
+        assert!(failed.matches(&a) && !failed.matches(&records[0]));
+        assert!(scrobbles.matches(&records[0]) && !scrobbles.matches(&a));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn compaction_keeps_the_newest_records() {
+        let path = std::env::temp_dir().join(format!(
+            "heaven-tempo-activity-compact-{}-{}.jsonl",
+            std::process::id(),
+            crate::scrobble::now_epoch_sec()
+        ));
+        let log = TxActivityLog::new(path.clone());
+        let writes = MAX_RECORDS * 2 + 1;
+        for index in 0..writes {
+            let revision = log.revision();
+            log.record(&activity(
+                &format!("r{index}"),
+                TxKind::Playlist,
+                TxActivityStatus::Confirmed,
+            ))
+            .unwrap();
+            assert_eq!(log.revision(), revision + 1);
+        }
+
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(
+            lines, MAX_RECORDS,
+            "the log was rewritten to its kept records"
+        );
+        let reopened = TxActivityLog::new(path.clone()).records();
+        assert_eq!(reopened.len(), MAX_RECORDS);
+        assert_eq!(reopened[0].id, format!("r{}", writes - 1));
+        assert_eq!(
+            reopened[MAX_RECORDS - 1].id,
+            format!("r{}", writes - MAX_RECORDS)
+        );
+        assert!(reopened.iter().all(|record| record.id != "r0"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn explorer_link_needs_a_tx_hash() {
+        let mut record = activity("a", TxKind::ContentKey, TxActivityStatus::Failed);
+        assert_eq!(record.explorer_url(), None);
+        assert!(record.can_retry());
+
+        record.tx_hash = Some("0xabcd".to_string());
+        let url = record.explorer_url().unwrap();
+        assert!(url.ends_with("/tx/0xabcd"), "{url}");
+    }
+}
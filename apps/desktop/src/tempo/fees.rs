@@ -0,0 +1,65 @@
+//! EIP-1559 style fee suggestion from `eth_gasPrice` and replacement bumping.
+
+use alloy_primitives::U256;
+
+const MIN_PRIORITY_FEE_PER_GAS: u64 = 1_000_000;
+const FEE_BUMP_NUMERATOR: u64 = 12;
+const FEE_BUMP_DENOMINATOR: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedFees {
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+}
+
+impl SuggestedFees {
+    /// Priority fee is a fifth of the gas price (with a floor); the cap leaves 4x headroom.
+    pub fn from_gas_price(gas_price: U256) -> Self {
+        let priority_floor = U256::from(MIN_PRIORITY_FEE_PER_GAS);
+        let priority = std::cmp::max(gas_price / U256::from(5), priority_floor);
+        let buffered = gas_price * U256::from(4);
+        let min_required = gas_price + priority;
+        Self {
+            max_priority_fee_per_gas: priority,
+            max_fee_per_gas: std::cmp::max(buffered, min_required),
+        }
+    }
+
+    /// +20% on both fees (at least +1 wei) so a retry outbids the expired attempt.
+    pub fn bumped(&self) -> Self {
+        let numerator = U256::from(FEE_BUMP_NUMERATOR);
+        let denominator = U256::from(FEE_BUMP_DENOMINATOR);
+        let bumped_priority = ((self.max_priority_fee_per_gas * numerator) / denominator)
+            .max(self.max_priority_fee_per_gas + U256::from(1_u8));
+        let bumped_max_fee = ((self.max_fee_per_gas * numerator) / denominator)
+            .max(self.max_fee_per_gas + U256::from(1_u8));
+        Self {
+            max_priority_fee_per_gas: bumped_priority,
+            max_fee_per_gas: bumped_max_fee.max(bumped_priority + U256::from(1_u8)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestion_floors_priority_and_bump_always_increases() {
+        let fees = SuggestedFees::from_gas_price(U256::from(2_000_000));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(1_000_000));
+        assert_eq!(fees.max_fee_per_gas, U256::from(8_000_000));
+
+        let bumped = fees.bumped();
+        assert_eq!(bumped.max_priority_fee_per_gas, U256::from(1_200_000));
+        assert_eq!(bumped.max_fee_per_gas, U256::from(9_600_000));
+
+        let tiny = SuggestedFees {
+            max_priority_fee_per_gas: U256::from(1),
+            max_fee_per_gas: U256::from(1),
+        }
+        .bumped();
+        assert_eq!(tiny.max_priority_fee_per_gas, U256::from(2));
+        assert_eq!(tiny.max_fee_per_gas, U256::from(3));
+    }
+}
@@ -0,0 +1,136 @@
+//! Append-only JSONL journal of every Tempo transaction attempt, kept in the account's data
+//! dir so a crash mid-submission still leaves a record of what was broadcast.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+const JOURNAL_FILE: &str = "tempo-tx-journal.jsonl";
+
+static SHARED_JOURNALS: OnceLock<Mutex<HashMap<PathBuf, Arc<TxJournal>>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxJournalStatus {
+    /// Accepted by the RPC; awaiting a receipt.
+    Sent,
+    Confirmed,
+    Reverted,
+    /// Not mined before `validBefore`; a bumped retry may follow.
+    Expired,
+    /// Failed before or during broadcast, or the receipt never resolved.
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxJournalEntry {
+    pub at: u64,
+    pub label: String,
+    pub sender: String,
+    pub to: String,
+    pub tx_hash: Option<String>,
+    pub attempt: u32,
+    pub nonce_key: String,
+    pub nonce: String,
+    pub valid_before: Option<u64>,
+    pub max_fee_per_gas: String,
+    pub status: TxJournalStatus,
+    pub detail: Option<String>,
+}
+
+pub struct TxJournal {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl TxJournal {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// The active account's journal, which its clients write to.
+    pub fn shared() -> Arc<Self> {
+        let path = crate::auth::accounts::AccountRegistry::shared().scoped_path(JOURNAL_FILE);
+        SHARED_JOURNALS
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(path.clone())
+            .or_insert_with(|| Arc::new(Self::new(path)))
+            .clone()
+    }
+
+    pub fn append(&self, entry: &TxJournalEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed encoding tx journal entry: {e}"))?;
+        let _guard = self
+            .write_lock
+            .lock()
+            .map_err(|e| format!("tx journal lock failed: {e}"))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create {}: {e}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Cannot open {}: {e}", self.path.display()))?;
+        file.write_all(format!("{line}\n").as_bytes())
+            .map_err(|e| format!("Cannot write {}: {e}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_appends_one_json_line_per_entry() {
+        let path = std::env::temp_dir().join(format!(
+            "heaven-tempo-journal-test-{}-{}.jsonl",
+            std::process::id(),
+            crate::scrobble::now_epoch_sec()
+        ));
+        let journal = TxJournal::new(path.clone());
+        let entry = TxJournalEntry {
+            at: 1,
+            label: "Scrobble".to_string(),
+            sender: "0x11".to_string(),
+            to: "0x22".to_string(),
+            tx_hash: Some("0xabc".to_string()),
+            attempt: 1,
+            nonce_key: "max".to_string(),
+            nonce: "0".to_string(),
+            valid_before: Some(26),
+            max_fee_per_gas: "4000000".to_string(),
+            status: TxJournalStatus::Sent,
+            detail: None,
+        };
+        journal.append(&entry).unwrap();
+        journal
+            .append(&TxJournalEntry {
+                status: TxJournalStatus::Confirmed,
+                ..entry.clone()
+            })
+            .unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<TxJournalEntry> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], entry);
+        assert!(written.contains("\"status\":\"confirmed\""));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,239 @@
+//! Nonce lane allocation. Tempo accounts have independent nonce sequences per `nonce_key`;
+//! leasing one key per in-flight transaction lets several submissions run concurrently
+//! without racing on a single sequence. Expiring mode (`nonce_key = U256::MAX`, nonce 0)
+//! skips sequencing entirely and relies on `validBefore` for replay protection.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, U256};
+
+pub const EXPIRING_NONCE_KEY: U256 = U256::MAX;
+
+static SHARED_LANES: OnceLock<Arc<NonceLanes>> = OnceLock::new();
+
+/// What became of a leased nonce; decides the lane's next nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaneOutcome {
+    /// Mined (successfully or reverted): the nonce is spent.
+    Included,
+    /// Never reached a block: the same nonce is reused.
+    NotIncluded,
+    /// Broadcast state unknown: refetch from chain before reusing the lane.
+    Unknown,
+}
+
+#[derive(Default)]
+struct LaneState {
+    busy: HashSet<(Address, u64)>,
+    next_nonce: HashMap<(Address, u64), u64>,
+}
+
+pub struct NonceLanes {
+    lanes: u64,
+    state: Mutex<LaneState>,
+    freed: Condvar,
+}
+
+impl NonceLanes {
+    /// `lanes` sequential nonce keys (`1..=lanes`); zero means expiring mode.
+    pub fn keyed(lanes: u64) -> Arc<Self> {
+        Arc::new(Self {
+            lanes,
+            state: Mutex::new(LaneState::default()),
+            freed: Condvar::new(),
+        })
+    }
+
+    /// Process-wide lanes shared by every client, sized by `HEAVEN_TEMPO_NONCE_LANES`.
+    pub fn shared() -> Arc<Self> {
+        SHARED_LANES
+            .get_or_init(|| {
+                let lanes = std::env::var("HEAVEN_TEMPO_NONCE_LANES")
+                    .ok()
+                    .and_then(|raw| raw.trim().parse::<u64>().ok())
+                    .unwrap_or(0);
+                Self::keyed(lanes)
+            })
+            .clone()
+    }
+
+    pub fn is_expiring(&self) -> bool {
+        self.lanes == 0
+    }
+
+    /// Lease a free lane for `account`, waiting up to `wait` for one to be released.
+    /// `fetch_nonce(key)` reads the on-chain nonce when the lane has no cached value.
+    pub fn acquire(
+        self: &Arc<Self>,
+        account: Address,
+        wait: Duration,
+        mut fetch_nonce: impl FnMut(u64) -> Result<u64, String>,
+    ) -> Result<NonceLease, String> {
+        if self.is_expiring() {
+            return Ok(NonceLease {
+                lanes: self.clone(),
+                account,
+                key: None,
+                nonce: 0,
+                outcome: LaneOutcome::Unknown,
+            });
+        }
+
+        let deadline = Instant::now() + wait;
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|e| format!("nonce lane lock failed: {e}"))?;
+        let key = loop {
+            if let Some(key) = (1..=self.lanes).find(|key| !state.busy.contains(&(account, *key))) {
+                break key;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(format!(
+                    "All {} Tempo nonce lanes are busy for {account}.",
+                    self.lanes
+                ));
+            }
+            state = self
+                .freed
+                .wait_timeout(state, deadline - now)
+                .map_err(|e| format!("nonce lane lock failed: {e}"))?
+                .0;
+        };
+        state.busy.insert((account, key));
+        let cached = state.next_nonce.get(&(account, key)).copied();
+        drop(state);
+
+        // The lease owns the lane from here on; dropping it on error frees the lane again.
+        let mut lease = NonceLease {
+            lanes: self.clone(),
+            account,
+            key: Some(key),
+            nonce: 0,
+            outcome: LaneOutcome::Unknown,
+        };
+        lease.nonce = match cached {
+            Some(nonce) => nonce,
+            None => fetch_nonce(key)?,
+        };
+        Ok(lease)
+    }
+
+    fn release(&self, account: Address, key: u64, nonce: u64, outcome: LaneOutcome) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        match outcome {
+            LaneOutcome::Included => {
+                state
+                    .next_nonce
+                    .insert((account, key), nonce.saturating_add(1));
+            }
+            LaneOutcome::NotIncluded => {
+                state.next_nonce.insert((account, key), nonce);
+            }
+            LaneOutcome::Unknown => {
+                state.next_nonce.remove(&(account, key));
+            }
+        }
+        state.busy.remove(&(account, key));
+        drop(state);
+        self.freed.notify_one();
+    }
+}
+
+/// A leased `(nonce_key, nonce)`; the lane is released on drop. Unless `finish` says
+/// otherwise the outcome is treated as unknown and the nonce is refetched next time.
+pub struct NonceLease {
+    lanes: Arc<NonceLanes>,
+    account: Address,
+    key: Option<u64>,
+    nonce: u64,
+    outcome: LaneOutcome,
+}
+
+impl NonceLease {
+    pub fn nonce_key(&self) -> U256 {
+        self.key.map(U256::from).unwrap_or(EXPIRING_NONCE_KEY)
+    }
+
+    pub fn nonce(&self) -> U256 {
+        U256::from(self.nonce)
+    }
+
+    pub fn is_expiring(&self) -> bool {
+        self.key.is_none()
+    }
+
+    pub fn finish(mut self, outcome: LaneOutcome) {
+        self.outcome = outcome;
+    }
+}
+
+impl Drop for NonceLease {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.lanes
+                .release(self.account, key, self.nonce, self.outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn expiring_mode_never_blocks() {
+        let lanes = NonceLanes::keyed(0);
+        let account = Address::repeat_byte(1);
+        let a = lanes
+            .acquire(account, SHORT, |_| panic!("no fetch"))
+            .unwrap();
+        let b = lanes
+            .acquire(account, SHORT, |_| panic!("no fetch"))
+            .unwrap();
+        assert!(a.is_expiring() && b.is_expiring());
+        assert_eq!(a.nonce_key(), EXPIRING_NONCE_KEY);
+        assert_eq!(b.nonce(), U256::ZERO);
+    }
+
+    #[test]
+    fn keyed_lanes_are_exclusive_and_track_outcomes() {
+        let lanes = NonceLanes::keyed(2);
+        let account = Address::repeat_byte(1);
+
+        let first = lanes.acquire(account, SHORT, |_| Ok(5)).unwrap();
+        let second = lanes.acquire(account, SHORT, |_| Ok(9)).unwrap();
+        assert_eq!(
+            (first.nonce_key(), first.nonce()),
+            (U256::from(1), U256::from(5))
+        );
+        assert_eq!(
+            (second.nonce_key(), second.nonce()),
+            (U256::from(2), U256::from(9))
+        );
+        assert!(lanes.acquire(account, SHORT, |_| Ok(0)).is_err());
+        // Another account has its own lanes.
+        assert!(lanes
+            .acquire(Address::repeat_byte(2), SHORT, |_| Ok(0))
+            .is_ok());
+
+        first.finish(LaneOutcome::Included);
+        let reused = lanes.acquire(account, SHORT, |_| panic!("cached")).unwrap();
+        assert_eq!(reused.nonce(), U256::from(6));
+        reused.finish(LaneOutcome::NotIncluded);
+        let retried = lanes.acquire(account, SHORT, |_| panic!("cached")).unwrap();
+        assert_eq!(retried.nonce(), U256::from(6));
+        drop(retried);
+
+        let refetched = lanes.acquire(account, SHORT, |_| Ok(42)).unwrap();
+        assert_eq!(refetched.nonce(), U256::from(42));
+        drop(second);
+    }
+}
@@ -0,0 +1,98 @@
+//! Receipt polling for transactions bounded by `validBefore`.
+
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::rpc::TempoRpc;
+use super::unix_now;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReceiptSummary {
+    pub status: Option<String>,
+    pub block_number: Option<String>,
+    pub gas_used: Option<String>,
+}
+
+impl ReceiptSummary {
+    pub(super) fn from_json(value: &Value) -> Self {
+        let field = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+        Self {
+            status: field("status"),
+            block_number: field("blockNumber"),
+            gas_used: field("gasUsed"),
+        }
+    }
+
+    pub fn reverted(&self) -> bool {
+        self.status
+            .as_deref()
+            .is_some_and(|status| status.eq_ignore_ascii_case("0x0"))
+    }
+
+    pub fn gas_used_value(&self) -> Option<u64> {
+        let gas_used = self.gas_used.as_deref()?;
+        u64::from_str_radix(super::strip_0x(gas_used), 16).ok()
+    }
+
+    /// `status=… block=… gasUsed=…` with `-` for missing fields, as logged and reported.
+    pub fn describe(&self) -> String {
+        format!(
+            "status={} block={} gasUsed={}",
+            self.status.as_deref().unwrap_or("-"),
+            self.block_number.as_deref().unwrap_or("-"),
+            self.gas_used.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+pub(super) enum ReceiptWait {
+    Mined(ReceiptSummary),
+    /// Past `validBefore` (plus grace) or the poll budget without a receipt.
+    NotIncluded,
+}
+
+pub(super) struct ReceiptPoll {
+    pub(super) expiry_deadline_secs: u64,
+    pub(super) timeout: Duration,
+    pub(super) interval: Duration,
+}
+
+/// Poll until the tx is mined or can no longer be. Transient RPC errors are retried until the
+/// poll budget runs out, then returned: at that point the tx state is unknown.
+pub(super) fn await_receipt(
+    rpc: &dyn TempoRpc,
+    rpc_url: &str,
+    tx_hash: &str,
+    poll: &ReceiptPoll,
+) -> Result<ReceiptWait, String> {
+    let started_at = Instant::now();
+    let mut logged_retry_error = false;
+    loop {
+        match rpc.request(rpc_url, "eth_getTransactionReceipt", json!([tx_hash])) {
+            Ok(Value::Null) => {
+                if unix_now() > poll.expiry_deadline_secs || started_at.elapsed() >= poll.timeout {
+                    return Ok(ReceiptWait::NotIncluded);
+                }
+            }
+            Ok(receipt) => return Ok(ReceiptWait::Mined(ReceiptSummary::from_json(&receipt))),
+            Err(err) => {
+                if !logged_retry_error {
+                    log::warn!(
+                        "[Tempo] receipt poll transient error: txHash={} err={}",
+                        tx_hash,
+                        err
+                    );
+                    logged_retry_error = true;
+                }
+                if started_at.elapsed() >= poll.timeout {
+                    return Err(format!(
+                        "timeout={}s rpc={rpc_url} err={err}",
+                        poll.timeout.as_secs()
+                    ));
+                }
+            }
+        }
+        std::thread::sleep(poll.interval);
+    }
+}
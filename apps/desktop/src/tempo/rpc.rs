@@ -0,0 +1,28 @@
+//! JSON-RPC transport for the client. Production goes over HTTP; tests substitute recorded
+//! fixtures.
+
+use serde_json::{json, Value};
+
+use crate::shared::rpc::rpc_json;
+
+pub trait TempoRpc: Send + Sync {
+    /// Send `method(params)` to `url` and return the `result` member.
+    fn request(&self, url: &str, method: &str, params: Value) -> Result<Value, String>;
+}
+
+pub struct HttpTempoRpc;
+
+impl TempoRpc for HttpTempoRpc {
+    fn request(&self, url: &str, method: &str, params: Value) -> Result<Value, String> {
+        rpc_json(
+            url,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }),
+        )
+        .map_err(String::from)
+    }
+}
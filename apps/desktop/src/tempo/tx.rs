@@ -0,0 +1,242 @@
+//! Tempo (type `0x76`) transaction encoding and keychain signing with a session key.
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::H256;
+
+use super::strip_0x;
+
+const FEE_PAYER_SENDER_HINT_MARKER_HEX: &str = "feefeefeefee";
+
+#[derive(Debug, Clone)]
+pub(super) struct TempoUnsignedTx {
+    pub(super) chain_id: U256,
+    pub(super) max_priority_fee_per_gas: U256,
+    pub(super) max_fee_per_gas: U256,
+    pub(super) gas_limit: U256,
+    pub(super) calls: Vec<TempoCall>,
+    pub(super) nonce_key: U256,
+    pub(super) nonce: U256,
+    pub(super) valid_before: Option<U256>,
+    pub(super) valid_after: Option<U256>,
+    pub(super) key_authorization: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TempoCall {
+    pub(super) to: Address,
+    pub(super) value: U256,
+    pub(super) input: Vec<u8>,
+}
+
+enum RlpValue {
+    Bytes(Vec<u8>),
+    Integer(U256),
+    List(Vec<RlpValue>),
+    Raw(Vec<u8>),
+}
+
+pub(super) fn encode_signed_tx(
+    tx: &TempoUnsignedTx,
+    session_wallet: &LocalWallet,
+    user_address: Address,
+) -> Result<String, String> {
+    let hash = signature_hash(tx);
+    let signature = session_wallet
+        .sign_hash(H256::from_slice(hash.as_slice()))
+        .map_err(|e| format!("Failed to sign Tempo tx hash with session key: {e}"))?;
+    let inner_signature = signature.to_vec();
+    if inner_signature.len() != 65 {
+        return Err(format!(
+            "Unexpected session signature length: {}",
+            inner_signature.len()
+        ));
+    }
+
+    let mut keychain_signature = Vec::with_capacity(1 + 20 + 65);
+    keychain_signature.push(0x03);
+    keychain_signature.extend_from_slice(user_address.as_slice());
+    keychain_signature.extend_from_slice(&inner_signature);
+
+    let full_fields = build_tx_fields(tx, Some(keychain_signature));
+    let encoded = rlp_encode(&RlpValue::List(full_fields));
+    Ok(format!("0x76{}", hex::encode(encoded)))
+}
+
+pub(super) fn signature_hash(tx: &TempoUnsignedTx) -> B256 {
+    let signing_fields = build_tx_fields(tx, None);
+    let mut payload = Vec::with_capacity(1 + 512);
+    payload.push(0x76);
+    payload.extend_from_slice(&rlp_encode(&RlpValue::List(signing_fields)));
+    keccak256(payload)
+}
+
+fn build_tx_fields(tx: &TempoUnsignedTx, sender_signature: Option<Vec<u8>>) -> Vec<RlpValue> {
+    let calls = tx
+        .calls
+        .iter()
+        .map(|call| {
+            RlpValue::List(vec![
+                RlpValue::Bytes(call.to.as_slice().to_vec()),
+                RlpValue::Integer(call.value),
+                RlpValue::Bytes(call.input.clone()),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let valid_before = tx
+        .valid_before
+        .map(RlpValue::Integer)
+        .unwrap_or_else(|| RlpValue::Bytes(Vec::new()));
+    let valid_after = tx
+        .valid_after
+        .map(RlpValue::Integer)
+        .unwrap_or_else(|| RlpValue::Bytes(Vec::new()));
+
+    let mut fields = vec![
+        RlpValue::Integer(tx.chain_id),
+        RlpValue::Integer(tx.max_priority_fee_per_gas),
+        RlpValue::Integer(tx.max_fee_per_gas),
+        RlpValue::Integer(tx.gas_limit),
+        RlpValue::List(calls),
+        RlpValue::List(Vec::new()),
+        RlpValue::Integer(tx.nonce_key),
+        RlpValue::Integer(tx.nonce),
+        valid_before,
+        valid_after,
+        RlpValue::Bytes(Vec::new()),
+        RlpValue::Bytes(vec![0x00]),
+        RlpValue::List(Vec::new()),
+    ];
+
+    if let Some(key_authorization) = tx.key_authorization.as_ref() {
+        fields.push(RlpValue::Raw(key_authorization.clone()));
+    }
+
+    if let Some(sender_signature) = sender_signature {
+        fields.push(RlpValue::Bytes(sender_signature));
+    }
+
+    fields
+}
+
+fn rlp_encode(value: &RlpValue) -> Vec<u8> {
+    match value {
+        RlpValue::Raw(encoded) => encoded.clone(),
+        RlpValue::Bytes(bytes) => rlp_encode_bytes(bytes),
+        RlpValue::Integer(number) => {
+            if number.is_zero() {
+                return vec![0x80];
+            }
+            rlp_encode_bytes(&number.to_be_bytes_trimmed_vec())
+        }
+        RlpValue::List(items) => {
+            let mut payload = Vec::new();
+            for item in items {
+                payload.extend_from_slice(&rlp_encode(item));
+            }
+            rlp_with_prefix(&payload, 0xc0)
+        }
+    }
+}
+
+fn rlp_encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return vec![bytes[0]];
+    }
+    rlp_with_prefix(bytes, 0x80)
+}
+
+fn rlp_with_prefix(payload: &[u8], short_offset: u8) -> Vec<u8> {
+    if payload.len() < 56 {
+        let mut out = Vec::with_capacity(1 + payload.len());
+        out.push(short_offset + payload.len() as u8);
+        out.extend_from_slice(payload);
+        return out;
+    }
+
+    let len_bytes = encode_len(payload.len());
+    let mut out = Vec::with_capacity(1 + len_bytes.len() + payload.len());
+    out.push(short_offset + 55 + len_bytes.len() as u8);
+    out.extend_from_slice(&len_bytes);
+    out.extend_from_slice(payload);
+    out
+}
+
+fn encode_len(mut len: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while len > 0 {
+        bytes.push((len & 0xff) as u8);
+        len >>= 8;
+    }
+    bytes.reverse();
+    bytes
+}
+
+pub(super) fn append_sender_hint(signed_tx_hex: &str, sender: Address) -> String {
+    let tx = strip_0x(signed_tx_hex);
+    let sender_hex = hex::encode(sender.as_slice());
+    format!("0x{}{}{}", tx, sender_hex, FEE_PAYER_SENDER_HINT_MARKER_HEX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn rlp_matches_reference_vectors() {
+        let encode = |value: RlpValue| hex::encode(rlp_encode(&value));
+        assert_eq!(encode(RlpValue::Bytes(b"dog".to_vec())), "83646f67");
+        assert_eq!(
+            encode(RlpValue::List(vec![
+                RlpValue::Bytes(b"cat".to_vec()),
+                RlpValue::Bytes(b"dog".to_vec()),
+            ])),
+            "c88363617483646f67"
+        );
+        assert_eq!(encode(RlpValue::Integer(U256::ZERO)), "80");
+        assert_eq!(encode(RlpValue::Integer(U256::from(15))), "0f");
+        assert_eq!(encode(RlpValue::Integer(U256::from(1024))), "820400");
+        let long = vec![b'a'; 56];
+        assert!(encode(RlpValue::Bytes(long)).starts_with("b838"));
+    }
+
+    #[test]
+    fn signed_tx_carries_keychain_signature_and_sender_hint() {
+        let wallet = LocalWallet::from_str(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        )
+        .unwrap();
+        let user = Address::repeat_byte(0x11);
+        let tx = TempoUnsignedTx {
+            chain_id: U256::from(42431),
+            max_priority_fee_per_gas: U256::from(1_000_000),
+            max_fee_per_gas: U256::from(4_000_000),
+            gas_limit: U256::from(420_000),
+            calls: vec![TempoCall {
+                to: Address::repeat_byte(0x22),
+                value: U256::ZERO,
+                input: vec![0xde, 0xad],
+            }],
+            nonce_key: U256::MAX,
+            nonce: U256::ZERO,
+            valid_before: Some(U256::from(1_700_000_025)),
+            valid_after: None,
+            key_authorization: None,
+        };
+
+        let signed = encode_signed_tx(&tx, &wallet, user).unwrap();
+        assert!(signed.starts_with("0x76"));
+        // 0x03 keychain marker, then the user address ahead of the 65-byte session signature.
+        let marker = format!("b85603{}", hex::encode(user.as_slice()));
+        assert!(signed.contains(&marker), "{signed}");
+        assert_eq!(signature_hash(&tx), signature_hash(&tx.clone()));
+
+        let hinted = append_sender_hint(&signed, user);
+        assert!(hinted.ends_with(&format!(
+            "{}{FEE_PAYER_SENDER_HINT_MARKER_HEX}",
+            hex::encode(user.as_slice())
+        )));
+    }
+}
@@ -0,0 +1,3 @@
+//! Shared fixtures for unit tests.
+
+pub(crate) mod http_stub;
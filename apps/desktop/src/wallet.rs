@@ -7,6 +7,7 @@ use gpui_component::{clipboard::Clipboard, StyledExt};
 use crate::auth;
 use crate::shared::address::abbreviate_address;
 
+mod activity;
 mod auth_helpers;
 mod fetch;
 mod models;
@@ -14,6 +15,7 @@ mod render;
 mod status_events;
 mod ui;
 
+use activity::ActivityPanel;
 use auth_helpers::*;
 use fetch::fetch_wallet_assets;
 use models::*;
@@ -38,6 +40,7 @@ define_color_fns! {
     TEXT_MUTED => text_muted,
     TEXT_DIM => text_dim,
     ACCENT_BLUE => accent_blue,
+    ACCENT_RED => accent_red,
 }
 
 pub struct WalletView {
@@ -46,16 +49,19 @@ pub struct WalletView {
     balances_loading: bool,
     balances_error: Option<String>,
     balances_for_address: Option<String>,
+    activity: ActivityPanel,
 }
 
 impl WalletView {
-    pub fn new(_cx: &mut Context<Self>) -> Self {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self::watch_activity_log(cx);
         Self {
             status: "Idle".to_string(),
             assets: zero_wallet_assets(),
             balances_loading: false,
             balances_error: None,
             balances_for_address: None,
+            activity: ActivityPanel::default(),
         }
     }
 
@@ -0,0 +1,396 @@
+use std::collections::HashSet;
+
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::{ActiveTheme, StyledExt};
+
+use crate::status_center::StatusCenter;
+use crate::tempo::{TxActivity, TxActivityFilter, TxActivityLog, TxActivityStatus, TxKind};
+
+use super::*;
+
+const ACTIVITY_POLL_INTERVAL_MS: u64 = 1_500;
+const ACTIVITY_STATUS_KEY: &str = "wallet.activity";
+
+/// Activity log as last read, plus list UI state.
+#[derive(Default)]
+pub(super) struct ActivityPanel {
+    records: Vec<TxActivity>,
+    /// The log last read, by path since each account has its own, and its revision.
+    revision: Option<(std::path::PathBuf, u64)>,
+    filter: TxActivityFilter,
+    focused: Option<String>,
+    retrying: HashSet<String>,
+}
+
+impl WalletView {
+    /// Re-read the activity log whenever a client writes to it.
+    pub(super) fn watch_activity_log(cx: &mut Context<Self>) {
+        cx.spawn(
+            async move |this: WeakEntity<Self>, cx: &mut AsyncApp| loop {
+                let still_open = this
+                    .update(cx, |this, cx| {
+                        if this.refresh_activity() {
+                            cx.notify();
+                        }
+                    })
+                    .is_ok();
+                if !still_open {
+                    break;
+                }
+                smol::Timer::after(std::time::Duration::from_millis(ACTIVITY_POLL_INTERVAL_MS))
+                    .await;
+            },
+        )
+        .detach();
+    }
+
+    fn refresh_activity(&mut self) -> bool {
+        let log = TxActivityLog::shared();
+        let revision = (log.path().to_path_buf(), log.revision());
+        if self.activity.revision.as_ref() == Some(&revision) {
+            return false;
+        }
+        self.activity.records = log.records();
+        self.activity.revision = Some(revision);
+        true
+    }
+
+    /// Highlight `activity_id`, clearing any filter that would hide it.
+    pub fn focus_activity(&mut self, activity_id: String, cx: &mut Context<Self>) {
+        self.refresh_activity();
+        self.activity.filter = TxActivityFilter::default();
+        self.activity.focused = Some(activity_id);
+        cx.notify();
+    }
+
+    fn set_activity_filter(&mut self, filter: TxActivityFilter, cx: &mut Context<Self>) {
+        self.activity.filter = filter;
+        self.activity.focused = None;
+        cx.notify();
+    }
+
+    fn retry_activity(&mut self, activity_id: String, cx: &mut Context<Self>) {
+        let Some(record) = TxActivityLog::shared().get(&activity_id) else {
+            return;
+        };
+        let Some(auth) = auth::load_from_disk() else {
+            self.publish_status_error(
+                ACTIVITY_STATUS_KEY,
+                "Sign in again to retry this transaction.",
+                cx,
+            );
+            return;
+        };
+        if !self.activity.retrying.insert(activity_id.clone()) {
+            return;
+        }
+        self.publish_status_progress(
+            ACTIVITY_STATUS_KEY,
+            format!("Retrying {}...", record.label),
+            cx,
+        );
+        cx.notify();
+
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            let label = record.label.clone();
+            let result =
+                smol::unblock(move || crate::scrobble::retry_tempo_activity(&auth, &record)).await;
+            let _ = this.update(cx, |this, cx| {
+                this.activity.retrying.remove(&activity_id);
+                match result {
+                    Ok(sent) => {
+                        cx.update_global::<StatusCenter, _>(|status, _| match sent.result {
+                            Ok(receipt) => status.publish_success_for_activity(
+                                ACTIVITY_STATUS_KEY,
+                                format!("{label} submitted: {}", receipt.tx_hash),
+                                Some(sent.activity_id),
+                            ),
+                            Err(err) => status.publish_error_for_activity(
+                                ACTIVITY_STATUS_KEY,
+                                format!("Retry of {label} failed: {err}"),
+                                Some(sent.activity_id),
+                            ),
+                        })
+                    }
+                    Err(err) => this.publish_status_error(
+                        ACTIVITY_STATUS_KEY,
+                        format!("Retry of {label} failed: {err}"),
+                        cx,
+                    ),
+                }
+                this.refresh_activity();
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn open_activity_in_explorer(&mut self, url: String, cx: &mut Context<Self>) {
+        cx.spawn(async move |this: WeakEntity<Self>, cx: &mut AsyncApp| {
+            if let Err(err) = smol::unblock(move || open::that(&url)).await {
+                let _ = this.update(cx, |this, cx| {
+                    this.publish_status_error(
+                        ACTIVITY_STATUS_KEY,
+                        format!("Failed to open explorer: {err}"),
+                        cx,
+                    );
+                });
+            }
+        })
+        .detach();
+    }
+
+    pub(super) fn render_activity_section(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
+        self.refresh_activity();
+        let filter = self.activity.filter;
+        // A failure whose retry confirmed is resolved; it no longer offers Retry.
+        let resolved: HashSet<&str> = self
+            .activity
+            .records
+            .iter()
+            .filter(|record| record.status == TxActivityStatus::Confirmed)
+            .filter_map(|record| record.retry_of.as_deref())
+            .collect();
+        let rows: Vec<AnyElement> = self
+            .activity
+            .records
+            .iter()
+            .filter(|record| filter.matches(record))
+            .enumerate()
+            .map(|(index, record)| {
+                let retrying = self.activity.retrying.contains(&record.id);
+                let focused = self.activity.focused.as_deref() == Some(record.id.as_str());
+                let resolved = resolved.contains(record.id.as_str());
+                render_activity_row(record, index > 0, focused, retrying, resolved, cx)
+            })
+            .collect();
+
+        let mut chips = vec![
+            activity_filter_chip(
+                "all",
+                "All",
+                filter == TxActivityFilter::default(),
+                TxActivityFilter::default(),
+                cx,
+            ),
+            activity_filter_chip(
+                "failed",
+                "Failed",
+                filter.failed_only,
+                TxActivityFilter {
+                    kind: None,
+                    failed_only: true,
+                },
+                cx,
+            ),
+        ];
+        chips.extend(TxKind::ALL.into_iter().map(|kind| {
+            activity_filter_chip(
+                kind.label(),
+                kind.label(),
+                filter.kind == Some(kind),
+                TxActivityFilter {
+                    kind: Some(kind),
+                    failed_only: false,
+                },
+                cx,
+            )
+        }));
+
+        div()
+            .v_flex()
+            .gap_2()
+            .child(
+                div()
+                    .text_xl()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .text_color(TEXT_PRIMARY())
+                    .child("Activity"),
+            )
+            .child(div().h_flex().flex_wrap().gap_2().children(chips))
+            .child(
+                div()
+                    .v_flex()
+                    .w_full()
+                    .rounded(px(10.))
+                    .bg(BG_ELEVATED())
+                    .border_1()
+                    .border_color(BORDER_SUBTLE())
+                    .overflow_hidden()
+                    .when(rows.is_empty(), |el| {
+                        el.child(
+                            div()
+                                .px_4()
+                                .py(px(14.))
+                                .text_sm()
+                                .text_color(TEXT_MUTED())
+                                .child(if filter == TxActivityFilter::default() {
+                                    "No on-chain activity yet."
+                                } else {
+                                    "No transactions match this filter."
+                                }),
+                        )
+                    })
+                    .children(rows),
+            )
+    }
+}
+
+fn activity_filter_chip(
+    id: &str,
+    label: &'static str,
+    active: bool,
+    filter: TxActivityFilter,
+    cx: &mut Context<WalletView>,
+) -> AnyElement {
+    div()
+        .id(ElementId::Name(
+            format!("wallet-activity-filter-{id}").into(),
+        ))
+        .px_3()
+        .py_1()
+        .rounded_full()
+        .border_1()
+        .border_color(if active {
+            ACCENT_BLUE()
+        } else {
+            BORDER_SUBTLE()
+        })
+        .bg(if active { BG_HOVER() } else { BG_ELEVATED() })
+        .text_sm()
+        .text_color(if active { TEXT_PRIMARY() } else { TEXT_MUTED() })
+        .cursor_pointer()
+        .on_click(cx.listener(move |this, _, _, cx| {
+            this.set_activity_filter(filter, cx);
+        }))
+        .child(label)
+        .into_any_element()
+}
+
+fn render_activity_row(
+    record: &TxActivity,
+    with_top_border: bool,
+    focused: bool,
+    retrying: bool,
+    resolved: bool,
+    cx: &mut Context<WalletView>,
+) -> AnyElement {
+    let theme = cx.theme();
+    let status_color = match record.status {
+        TxActivityStatus::Pending => ACCENT_BLUE(),
+        TxActivityStatus::Confirmed => theme.success,
+        TxActivityStatus::Reverted | TxActivityStatus::Failed => ACCENT_RED(),
+    };
+    let mut details = vec![
+        record.kind.label().to_string(),
+        format_activity_time(record.updated_at),
+    ];
+    if let Some(tx_hash) = record.tx_hash.as_deref() {
+        details.push(abbreviate_address(tx_hash));
+    }
+    if let Some(gas_used) = record.gas_used {
+        details.push(format!("{gas_used} gas"));
+    }
+    if record.attempts > 1 {
+        details.push(format!("{} attempts", record.attempts));
+    }
+    if record.retry_of.is_some() {
+        details.push("retry".to_string());
+    }
+    let explorer_url = record.explorer_url();
+    let can_retry = record.can_retry() && !resolved;
+    let row_id = record.id.clone();
+    let retry_id = record.id.clone();
+
+    div()
+        .id(ElementId::Name(format!("wallet-activity-{row_id}").into()))
+        .v_flex()
+        .gap_1()
+        .w_full()
+        .px_4()
+        .py(px(10.))
+        .when(with_top_border, |el| {
+            el.border_t_1().border_color(BORDER_SUBTLE())
+        })
+        .when(focused, |el| el.bg(BG_HOVER()))
+        .child(
+            div()
+                .h_flex()
+                .items_center()
+                .gap_3()
+                .child(div().size(px(8.)).rounded_full().bg(status_color))
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .truncate()
+                        .text_base()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .text_color(TEXT_PRIMARY())
+                        .child(record.label.clone()),
+                )
+                .child(div().text_sm().text_color(status_color).child(if resolved {
+                    "Resolved by retry"
+                } else {
+                    record.status.label()
+                }))
+                .when_some(explorer_url, |el, url| {
+                    el.child(
+                        div()
+                            .id(ElementId::Name(
+                                format!("wallet-activity-open-{row_id}").into(),
+                            ))
+                            .text_sm()
+                            .text_color(ACCENT_BLUE())
+                            .cursor_pointer()
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.open_activity_in_explorer(url.clone(), cx);
+                            }))
+                            .child("Explorer"),
+                    )
+                })
+                .when(can_retry, |el| {
+                    el.child(
+                        div()
+                            .id(ElementId::Name(
+                                format!("wallet-activity-retry-{row_id}").into(),
+                            ))
+                            .px_3()
+                            .py_1()
+                            .rounded_full()
+                            .border_1()
+                            .border_color(BORDER_SUBTLE())
+                            .text_sm()
+                            .text_color(if retrying { TEXT_DIM() } else { TEXT_PRIMARY() })
+                            .when(!retrying, |el| {
+                                el.cursor_pointer()
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.retry_activity(retry_id.clone(), cx);
+                                    }))
+                            })
+                            .child(if retrying { "Retrying..." } else { "Retry" }),
+                    )
+                }),
+        )
+        .child(
+            div()
+                .text_sm()
+                .text_color(TEXT_MUTED())
+                .child(details.join(" · ")),
+        )
+        .when_some(record.error.clone(), |el, error| {
+            el.child(div().text_sm().text_color(ACCENT_RED()).child(error))
+        })
+        .into_any_element()
+}
+
+fn format_activity_time(epoch_secs: u64) -> String {
+    chrono::DateTime::from_timestamp(epoch_secs as i64, 0)
+        .map(|utc| {
+            utc.with_timezone(&chrono::Local)
+                .format("%b %-d, %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "Unknown time".to_string())
+}
@@ -190,6 +190,7 @@ impl Render for WalletView {
                                     ),
                             ),
                     )
+                    .child(self.render_activity_section(cx))
                     .child(div().text_xs().text_color(TEXT_DIM()).child(footer_text)),
             )
     }
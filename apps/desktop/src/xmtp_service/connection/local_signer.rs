@@ -1,10 +1,11 @@
 use super::super::*;
+use crate::secret_store::SecretStore;
 use ethers::signers::{LocalWallet, Signer};
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::fs;
+use std::path::PathBuf;
 
-const LOCAL_SIGNER_DIR: &str = "xmtp_identity_keys";
+/// Where identity keys were written before they moved into the secret store.
+const LEGACY_SIGNER_DIR: &str = "xmtp_identity_keys";
 
 pub(super) struct LocalXmtpSigner {
     user_wallet_address: String,
@@ -70,25 +71,43 @@ fn normalize_wallet_address(value: &str) -> Result<String, String> {
 }
 
 fn load_or_create_private_key_hex(user_wallet: &str) -> Result<String, String> {
-    let key_path = key_path_for_user_wallet(user_wallet)?;
-    if key_path.exists() {
-        let stored = fs::read_to_string(&key_path)
-            .map_err(|e| format!("read {}: {e}", key_path.display()))?;
+    let store = SecretStore::shared();
+    let secret_key = secret_key_for_user_wallet(user_wallet);
+    if let Some(stored) = store.get(&secret_key)? {
         return normalize_private_key_hex(&stored);
     }
 
+    // Keys created before the secret store were plain files; move them over.
+    let legacy_path = legacy_key_path_for_user_wallet(user_wallet);
+    if legacy_path.exists() {
+        let stored = fs::read_to_string(&legacy_path)
+            .map_err(|e| format!("read {}: {e}", legacy_path.display()))?;
+        let key_hex = normalize_private_key_hex(&stored)?;
+        store.set(&secret_key, &key_hex)?;
+        if let Err(e) = fs::remove_file(&legacy_path) {
+            log::warn!(
+                "[XMTP] migrated identity key but could not remove {}: {e}",
+                legacy_path.display()
+            );
+        }
+        return Ok(key_hex);
+    }
+
     let wallet = LocalWallet::new(&mut rand::thread_rng());
     let key_hex = hex::encode(wallet.signer().to_bytes());
-    write_private_key_hex(&key_path, &key_hex)?;
+    store.set(&secret_key, &key_hex)?;
     Ok(key_hex)
 }
 
-fn key_path_for_user_wallet(user_wallet: &str) -> Result<PathBuf, String> {
-    let normalized = normalize_wallet_address(user_wallet)?;
-    let keys_dir = app_data_dir().join(LOCAL_SIGNER_DIR);
-    fs::create_dir_all(&keys_dir).map_err(|e| format!("mkdir {}: {e}", keys_dir.display()))?;
-    let suffix = normalized.trim_start_matches("0x");
-    Ok(keys_dir.join(format!("{suffix}.key")))
+fn secret_key_for_user_wallet(user_wallet: &str) -> String {
+    format!("xmtp.identity.{}", user_wallet.trim_start_matches("0x"))
+}
+
+fn legacy_key_path_for_user_wallet(user_wallet: &str) -> PathBuf {
+    let suffix = user_wallet.trim_start_matches("0x");
+    app_data_dir()
+        .join(LEGACY_SIGNER_DIR)
+        .join(format!("{suffix}.key"))
 }
 
 fn normalize_private_key_hex(raw: &str) -> Result<String, String> {
@@ -98,26 +117,3 @@ fn normalize_private_key_hex(raw: &str) -> Result<String, String> {
     }
     Ok(clean.to_ascii_lowercase())
 }
-
-fn write_private_key_hex(path: &Path, private_key_hex: &str) -> Result<(), String> {
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::OpenOptionsExt;
-
-        let mut file = OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .mode(0o600)
-            .open(path)
-            .map_err(|e| format!("open {}: {e}", path.display()))?;
-        file.write_all(private_key_hex.as_bytes())
-            .map_err(|e| format!("write {}: {e}", path.display()))?;
-        return Ok(());
-    }
-
-    #[cfg(not(unix))]
-    {
-        fs::write(path, private_key_hex).map_err(|e| format!("write {}: {e}", path.display()))
-    }
-}
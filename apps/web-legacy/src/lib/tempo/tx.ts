@@ -6,6 +6,19 @@ export interface TempoSignerContext {
   rpId?: string
 }
 
+/** Tempo's account keychain precompile, which tracks the access keys each account authorized. */
+const ACCOUNT_KEYCHAIN_ADDRESS = '0xAAAAAAAA00000000000000000000000000000000'
+
+const REVOKE_KEY_ABI = [
+  {
+    type: 'function',
+    name: 'revokeKey',
+    stateMutability: 'nonpayable',
+    inputs: [{ name: 'keyId', type: 'address' }],
+    outputs: [],
+  },
+] as const
+
 export interface TempoSponsoredTokenTransferParams extends TempoSignerContext {
   token: `0x${string}`
   to: `0x${string}`
@@ -85,3 +98,33 @@ export async function sendSponsoredTokenTransfer(
 
   return { receipt: result?.receipt }
 }
+
+/**
+ * Revoke access keys on the account keychain in one sponsored tx signed by the root passkey.
+ * Returns the key ids that are now revoked.
+ */
+export async function revokeTempoAccessKeys(
+  context: TempoSignerContext,
+  keyIds: `0x${string}`[]
+): Promise<`0x${string}`[]> {
+  if (keyIds.length === 0) return []
+  const { viem } = await getTempoRuntime()
+  const { client } = await createTempoSponsoredClient(context)
+  const { sendTransactionSync } = await import('viem/actions')
+
+  const receipt = await sendTransactionSync(client, {
+    calls: keyIds.map((keyId) => ({
+      to: ACCOUNT_KEYCHAIN_ADDRESS,
+      data: viem.encodeFunctionData({
+        abi: REVOKE_KEY_ABI,
+        functionName: 'revokeKey',
+        args: [keyId],
+      }),
+    })),
+    feePayer: true,
+  } as any)
+  if (receipt?.status !== 'success') {
+    throw new Error(`Access key revocation reverted (tx ${receipt?.transactionHash ?? 'unknown'})`)
+  }
+  return keyIds
+}
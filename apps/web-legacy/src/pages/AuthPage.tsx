@@ -59,6 +59,11 @@ export const AuthPage: Component = () => {
     import.meta.env.VITE_TEMPO_RP_ID ||
     window.location.hostname
   const initialMode = authParams.get('mode')
+  // Session keys the desktop app replaced; revoked on-chain once the new one is authorized.
+  const revokeSessionKeys = (authParams.get('revokeSessionKeys') || '')
+    .split(',')
+    .map((key) => key.trim())
+    .filter((key): key is `0x${string}` => /^0x[0-9a-fA-F]{40}$/.test(key))
 
   const buildTempoScrobbleSession = async (
     result: TempoAuthResult
@@ -99,6 +104,29 @@ export const AuthPage: Component = () => {
     }
   }
 
+  // A failed revocation doesn't fail the sign-in: the app keeps the keys and asks again on
+  // the next renewal.
+  const revokeReplacedSessionKeys = async (result: TempoAuthResult): Promise<string[]> => {
+    if (revokeSessionKeys.length === 0) return []
+    if (!result.tempoCredentialId || !result.tempoPublicKey) return []
+    try {
+      const { revokeTempoAccessKeys } = await import('../lib/tempo/tx')
+      return await revokeTempoAccessKeys(
+        {
+          chainId: result.tempoChainId,
+          credentialId: result.tempoCredentialId,
+          publicKey: result.tempoPublicKey,
+          feePayerUrl: result.tempoFeePayerUrl,
+          rpId: result.tempoRpId,
+        },
+        revokeSessionKeys
+      )
+    } catch (e) {
+      console.error('[AuthPage] Session key revocation failed:', e)
+      return []
+    }
+  }
+
   console.log('[AuthPage] callback flow:', isCallbackFlow, 'transport:', callbackTransport)
 
   // Send auth result to callback transport:
@@ -142,6 +170,7 @@ export const AuthPage: Component = () => {
 
   const handleTempoAuthSuccess = async (result: TempoAuthResult, isNewUser: boolean) => {
     const scrobbleSession = await buildTempoScrobbleSession(result)
+    const revokedSessionKeys = await revokeReplacedSessionKeys(result)
 
     const callbackPayload: Record<string, unknown> = {
       version: 2,
@@ -158,6 +187,7 @@ export const AuthPage: Component = () => {
       tempoSessionAddress: scrobbleSession.tempoSessionAddress,
       tempoSessionExpiresAt: scrobbleSession.tempoSessionExpiresAt,
       tempoSessionKeyAuthorization: scrobbleSession.tempoSessionKeyAuthorization,
+      revokedSessionKeys,
       isNewUser,
     }
 